@@ -24,4 +24,24 @@ pub enum Commands {
         #[arg(short, long)]
         out: Option<PathBuf>,
     },
+
+    /// Replay the write-ahead log into the database
+    Recover {
+        #[command(subcommand)]
+        action: Option<RecoverCommands>,
+
+        /// Only report what would be replayed, without applying it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// WAL directory (defaults to <data_dir>/wal)
+        #[arg(long, value_name = "DIR")]
+        wal_dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RecoverCommands {
+    /// Show the outcome of the last recovery
+    Status,
 }
@@ -15,6 +15,7 @@ pub fn get_genesis_block() -> Block {
         params: serialize_genesis_params(),
         gas_limit: GENESIS_GAS_LIMIT,
         base_fee: GENESIS_BASE_FEE,       // EIP-1559: 初始基础费用
+        logs_bloom: Vec::new(),
     };
 
     Block {
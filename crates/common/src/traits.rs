@@ -8,4 +8,13 @@ pub trait DBInterface: Send + Sync {
     async fn remove(&self, key: &[u8]) -> Result<()>;
     async fn batch_insert(&self, keys: &[Vec<u8>], values: &[Vec<u8>]) -> Result<()>;
     async fn batch_delete(&self, keys: &[Vec<u8>]) -> Result<()>;
+
+    /// Insert `keys`/`values` and delete `deletes` as a single write
+    ///
+    /// The default runs the inserts, then the deletes. Stores that support
+    /// transactions override it so a crash can't leave part of the write.
+    async fn batch_write(&self, keys: &[Vec<u8>], values: &[Vec<u8>], deletes: &[Vec<u8>]) -> Result<()> {
+        self.batch_insert(keys, values).await?;
+        self.batch_delete(deletes).await
+    }
 }
@@ -357,6 +357,10 @@ pub struct BlockHeader {
     pub gas_limit: i64,
     /// EIP-1559: Base fee for this block
     pub base_fee: u64,
+    /// Bloom of the logs in the block's receipts (256 bytes), empty if not
+    /// computed; covered by the block hash
+    #[serde(default, with = "hex_serde")]
+    pub logs_bloom: Vec<u8>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
@@ -0,0 +1,79 @@
+//! EIP-55 mixed-case checksum addresses
+
+use crate::types::Address;
+
+/// Format an address as a `0x`-prefixed EIP-55 checksummed hex string
+pub fn to_checksum_address(address: &Address) -> String {
+    let lower = hex::encode(address.0);
+    let hash = keccak_hash::keccak(lower.as_bytes());
+
+    let mut checksummed = String::with_capacity(42);
+    checksummed.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        // Uppercase a letter when the matching nibble of the hash is >= 8
+        let nibble = (hash.0[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+        if nibble >= 8 {
+            checksummed.push(c.to_ascii_uppercase());
+        } else {
+            checksummed.push(c);
+        }
+    }
+    checksummed
+}
+
+/// Check the EIP-55 checksum of a hex address, with or without `0x`
+///
+/// All-lowercase and all-uppercase addresses carry no checksum and are
+/// accepted; mixed-case addresses must match their checksum exactly.
+/// Returns false for anything that isn't 20 bytes of hex.
+pub fn validate_checksum(address: &str) -> bool {
+    let digits = address.strip_prefix("0x").unwrap_or(address);
+    let mut bytes = [0u8; 20];
+    if hex::decode_to_slice(digits, &mut bytes).is_err() {
+        return false;
+    }
+
+    let has_lower = digits.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = digits.chars().any(|c| c.is_ascii_uppercase());
+    if !(has_lower && has_upper) {
+        return true;
+    }
+
+    to_checksum_address(&Address(bytes))[2..] == *digits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors from EIP-55
+    const CHECKSUMMED: [&str; 4] = [
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+
+    #[test]
+    fn test_to_checksum_address() {
+        for expected in CHECKSUMMED {
+            let mut bytes = [0u8; 20];
+            hex::decode_to_slice(&expected[2..], &mut bytes).unwrap();
+            assert_eq!(to_checksum_address(&Address(bytes)), expected);
+        }
+    }
+
+    #[test]
+    fn test_validate_checksum() {
+        for address in CHECKSUMMED {
+            assert!(validate_checksum(address));
+            assert!(validate_checksum(&address.to_lowercase()));
+            assert!(validate_checksum(&address[2..].to_uppercase()));
+        }
+
+        // One letter with its case flipped
+        assert!(!validate_checksum("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"));
+        assert!(!validate_checksum("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA"));
+        assert!(!validate_checksum("0xzzAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+    }
+}
@@ -2,6 +2,11 @@ use crate::types::Hash;
 
 const BLOCK_PREFIX: &[u8] = b"block#";
 const TX_PREFIX: &[u8] = b"tx#";
+const PRUNED_TX_PREFIX: &[u8] = b"pruned_tx#";
+const BLOCK_HEIGHT_PREFIX: &[u8] = b"height#";
+const TX_LOCATION_PREFIX: &[u8] = b"txloc#";
+const RAW_TX_PREFIX: &[u8] = b"rawtx#";
+const RECEIPTS_PREFIX: &[u8] = b"receipts#";
 // const DATA_PREFIX: &[u8] = b"data#";
 
 pub fn block_hash_to_db_key(hash: &Hash) -> Vec<u8> {
@@ -19,6 +24,14 @@ pub fn block_height_to_db_key(height: i64) -> Vec<u8> {
     key
 }
 
+/// Key of the hash -> height index of canonical blocks
+pub fn block_hash_to_height_db_key(hash: &Hash) -> Vec<u8> {
+    let mut key = Vec::with_capacity(BLOCK_HEIGHT_PREFIX.len() + hash.0.len());
+    key.extend_from_slice(BLOCK_HEIGHT_PREFIX);
+    key.extend_from_slice(&hash.0);
+    key
+}
+
 pub fn tx_hash_to_db_key(hash: &Hash) -> Vec<u8> {
     let mut key = Vec::with_capacity(TX_PREFIX.len() + hash.0.len());
     key.extend_from_slice(TX_PREFIX);
@@ -26,6 +39,37 @@ pub fn tx_hash_to_db_key(hash: &Hash) -> Vec<u8> {
     key
 }
 
+/// Key of the tx hash -> (block hash, index) index of canonical transactions
+pub fn tx_hash_to_location_db_key(hash: &Hash) -> Vec<u8> {
+    let mut key = Vec::with_capacity(TX_LOCATION_PREFIX.len() + hash.0.len());
+    key.extend_from_slice(TX_LOCATION_PREFIX);
+    key.extend_from_slice(&hash.0);
+    key
+}
+
+/// Key of the original encoding of a transaction submitted raw
+pub fn raw_tx_hash_to_db_key(hash: &Hash) -> Vec<u8> {
+    let mut key = Vec::with_capacity(RAW_TX_PREFIX.len() + hash.0.len());
+    key.extend_from_slice(RAW_TX_PREFIX);
+    key.extend_from_slice(&hash.0);
+    key
+}
+
+/// Key of the execution receipts of a block
+pub fn block_hash_to_receipts_db_key(hash: &Hash) -> Vec<u8> {
+    let mut key = Vec::with_capacity(RECEIPTS_PREFIX.len() + hash.0.len());
+    key.extend_from_slice(RECEIPTS_PREFIX);
+    key.extend_from_slice(&hash.0);
+    key
+}
+
+pub fn pruned_tx_hash_to_db_key(hash: &Hash) -> Vec<u8> {
+    let mut key = Vec::with_capacity(PRUNED_TX_PREFIX.len() + hash.0.len());
+    key.extend_from_slice(PRUNED_TX_PREFIX);
+    key.extend_from_slice(&hash.0);
+    key
+}
+
 pub fn data_address_key_to_db_key(address: &[u8], key: &[u8]) -> Vec<u8> {
     let addr_hex = hex::encode(address);
     let key_str = String::from_utf8_lossy(key);
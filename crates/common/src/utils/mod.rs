@@ -1,3 +1,4 @@
+pub mod address;
 pub mod converter;
 pub mod db_keys;
 pub mod codec;
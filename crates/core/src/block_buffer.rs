@@ -1,6 +1,8 @@
 use moka::future::Cache;
 use norn_common::types::{Block, Hash, GeneralParams};
 use norn_crypto::vdf::{VDFCalculator, VDFOutput, get_calculator};
+use norn_crypto::vrf::VRFCalculator;
+use crate::consensus::producer::BlockProducer;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
@@ -14,6 +16,8 @@ const MAX_KNOWN_BLOCK: u64 = 2048;
 const MAX_PROCESSED_BLOCK: u64 = 2048;
 const MAX_BUFFER_SIZE: i64 = 12;
 const SECOND_QUEUE_INTERVAL: Duration = Duration::from_micros(100);
+// Blocks whose VRF proofs are verified together
+const MAX_VRF_BATCH: usize = 32;
 
 // Shared state of the buffer
 struct BufferState {
@@ -100,8 +104,11 @@ impl BlockBuffer {
     // --- Background Processes ---
 
     async fn process_loop(&self, mut rx: mpsc::Receiver<Block>) {
-        while let Some(block) = rx.recv().await {
-            self.handle_block(block, false).await;
+        let mut batch = Vec::with_capacity(MAX_VRF_BATCH);
+        while rx.recv_many(&mut batch, MAX_VRF_BATCH).await > 0 {
+            for block in verify_vrfs(std::mem::take(&mut batch)).await {
+                self.handle_block(block, false).await;
+            }
         }
     }
 
@@ -305,6 +312,72 @@ async fn verify_block_vdf(block: &Block, vdf_calculator: Option<&Arc<dyn VDFCalc
     verified
 }
 
+/// Drop blocks whose VRF proof does not verify
+///
+/// The batch is verified on the blocking pool so the CPU-bound work does
+/// not stall the runtime. Every path importing blocks from peers must
+/// filter them through here.
+pub async fn verify_vrfs(blocks: Vec<Block>) -> Vec<Block> {
+    let verified = tokio::task::spawn_blocking(move || {
+        let valid = verify_block_vrfs(&blocks);
+        (blocks, valid)
+    })
+    .await;
+
+    match verified {
+        Ok((blocks, valid)) => blocks
+            .into_iter()
+            .zip(valid)
+            .filter_map(|(block, valid)| {
+                if !valid {
+                    warn!("Dropping block {} with invalid VRF proof", block.header.height);
+                }
+                valid.then_some(block)
+            })
+            .collect(),
+        Err(e) => {
+            warn!("VRF verification task failed: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Verify the VRF proofs carried by a batch of blocks
+///
+/// Blocks without VRF params prove no right to produce, so they are
+/// rejected, except for genesis. So are blocks whose params cannot be
+/// decoded.
+fn verify_block_vrfs(blocks: &[Block]) -> Vec<bool> {
+    let mut results = vec![true; blocks.len()];
+    let mut claims = Vec::new();
+    let mut claim_indices = Vec::new();
+
+    for (i, block) in blocks.iter().enumerate() {
+        match BlockProducer::vrf_claim(&block.header) {
+            Ok(Some(claim)) => {
+                claims.push(claim);
+                claim_indices.push(i);
+            }
+            Ok(None) => {
+                if block.header.height > 0 {
+                    debug!("Block {} has no VRF params", block.header.height);
+                    results[i] = false;
+                }
+            }
+            Err(e) => {
+                debug!("Block {} has malformed VRF params: {}", block.header.height, e);
+                results[i] = false;
+            }
+        }
+    }
+
+    for (i, valid) in claim_indices.into_iter().zip(VRFCalculator::verify_batch(&claims)) {
+        results[i] = valid;
+    }
+
+    results
+}
+
 // Helper functions
 
 fn compare_block(origin: &Block, new_block: &Block) -> bool {
@@ -360,8 +433,7 @@ mod tests {
     use std::time::Duration;
 
     fn create_block(height: i64, prev_hash: Hash) -> Block {
-        let mut b = Block::default();
-        b.header.height = height;
+        let mut b = block_with_vrf(height, &norn_crypto::vrf::VRFKeyPair::generate());
         b.header.prev_block_hash = prev_hash;
         // make hash unique based on height to avoid collision in test
         b.header.block_hash.0[31] = height as u8; 
@@ -389,6 +461,72 @@ mod tests {
         assert_eq!(state.selected_block.get(&1).unwrap().header.block_hash, b1.header.block_hash);
     }
 
+    fn block_with_vrf(height: i64, key_pair: &norn_crypto::vrf::VRFKeyPair) -> Block {
+        let public_key = BlockProducer::vrf_public_key(key_pair);
+
+        let message = BlockProducer::vrf_selection_message(&public_key, height as u64);
+        let output = VRFCalculator::calculate(key_pair, &message).unwrap();
+        let params = GeneralParams {
+            result: output.output.to_vec(),
+            proof: output.proof.to_bytes().to_vec(),
+            random_number: public_key,
+            s: vec![],
+            t: 1000u64.to_le_bytes().to_vec(),
+        };
+
+        let mut block = Block::default();
+        block.header.height = height;
+        block.header.public_key = public_key;
+        block.header.params = norn_common::utils::codec::serialize(&params).unwrap();
+        block
+    }
+
+    #[test]
+    fn test_verify_block_vrfs() {
+        let key_pair = norn_crypto::vrf::VRFKeyPair::generate();
+
+        let valid = block_with_vrf(1, &key_pair);
+        // Proof for height 2 replayed at height 3
+        let mut replayed = block_with_vrf(2, &key_pair);
+        replayed.header.height = 3;
+        let mut no_params = Block::default();
+        no_params.header.height = 5;
+        let mut malformed = block_with_vrf(4, &key_pair);
+        malformed.header.params = b"not params".to_vec();
+        let genesis = Block::default();
+
+        let results = verify_block_vrfs(&[valid, replayed, no_params, malformed, genesis]);
+        assert_eq!(results, vec![true, false, false, false, true]);
+    }
+
+    #[tokio::test]
+    async fn test_buffer_drops_invalid_vrf() {
+        let genesis = Block::default();
+        let (pop_tx, _pop_rx) = mpsc::channel(10);
+        let buffer = BlockBuffer::new(genesis.clone(), pop_tx).await;
+        let key_pair = norn_crypto::vrf::VRFKeyPair::generate();
+
+        // Proof for height 2 replayed at height 1
+        let mut replayed = block_with_vrf(2, &key_pair);
+        replayed.header.height = 1;
+        replayed.header.block_hash = Hash([1; 32]);
+        let mut valid = block_with_vrf(1, &key_pair);
+        valid.header.block_hash = Hash([2; 32]);
+
+        let mut unsigned = create_block(1, genesis.header.block_hash);
+        unsigned.header.params.clear();
+
+        // What the first queue does with a batch
+        for block in verify_vrfs(vec![replayed.clone(), unsigned.clone(), valid.clone()]).await {
+            buffer.handle_block(block, false).await;
+        }
+
+        let state = buffer.state.read().await;
+        assert!(!state.known_blocks.contains_key(&replayed.header.block_hash));
+        assert!(!state.known_blocks.contains_key(&unsigned.header.block_hash));
+        assert_eq!(state.selected_block[&1].header.block_hash, valid.header.block_hash);
+    }
+
     #[tokio::test]
     async fn test_buffer_pop() {
         let mut genesis = Block::default();
@@ -1,16 +1,84 @@
 use crate::block_buffer::BlockBuffer;
 use crate::data_processor::DataProcessor;
+use crate::events::{EventBus, TxIncluded};
+use crate::evm::Receipt;
+use crate::pruning::PruningMode;
 use crate::txpool::ChainReader;
 use moka::future::Cache;
 use norn_common::traits::DBInterface;
 use norn_common::types::{Block, Hash, Transaction};
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
-use tracing::{error, info};
+use tokio::sync::{mpsc, watch, RwLock};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+use lazy_static::lazy_static;
+use prometheus::IntCounter;
 
 // Constants
 const MAX_BLOCK_CACHE: u64 = 64;
 const MAX_TX_CACHE: u64 = 40960;
+const PRUNED_BELOW_KEY: &[u8] = b"pruned_below";
+const LATEST_KEY: &[u8] = b"latest";
+
+/// Most blocks a reorg may revert while import hooks are registered
+///
+/// Hooks keep what they need to roll back this many of the latest blocks.
+pub const MAX_REORG_DEPTH: usize = 64;
+
+// Metrics (registered by the node's metrics collector)
+lazy_static! {
+    pub static ref REORGS_REFUSED_TOTAL: IntCounter = IntCounter::new(
+        "norn_reorgs_refused_total",
+        "Forks the chain refused to switch to because their blocks couldn't be rolled back"
+    ).unwrap();
+}
+
+/// A fork the chain refused to switch to
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefusedReorg {
+    /// Height of the last block the fork shares with the local chain
+    pub fork_height: i64,
+    /// Number of local blocks the fork would have reverted
+    pub depth: u64,
+    /// Head of the fork
+    pub tip: Hash,
+}
+
+/// Writes of one chain update, applied as a single atomic batch
+///
+/// Import hooks add the writes of executing a block, such as its state and
+/// receipts, so they are committed together with the block.
+#[derive(Default)]
+pub struct ChainWrite {
+    pub(crate) keys: Vec<Vec<u8>>,
+    pub(crate) values: Vec<Vec<u8>>,
+    pub(crate) removed: Vec<Vec<u8>>,
+    receipts: Vec<Receipt>,
+}
+
+impl ChainWrite {
+    /// Write `value` at `key`
+    pub fn put(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.keys.push(key);
+        self.values.push(value);
+    }
+
+    /// Remove `key`
+    pub fn delete(&mut self, key: Vec<u8>) {
+        self.removed.push(key);
+    }
+
+    /// Receipts of executed transactions of the block, stored with it
+    pub fn add_receipts(&mut self, receipts: impl IntoIterator<Item = Receipt>) {
+        self.receipts.extend(receipts);
+    }
+
+    /// Receipts added so far
+    pub fn receipts(&self) -> &[Receipt] {
+        &self.receipts
+    }
+}
 
 pub struct Blockchain {
     db: Arc<dyn DBInterface>,
@@ -24,15 +92,28 @@ pub struct Blockchain {
 
     // State
     pub latest_block: Arc<RwLock<Block>>,
+    // Hash of latest_block, for watchers of the head
+    head: watch::Sender<Hash>,
+    // Import notifications
+    events: Arc<EventBus>,
+    // Run after each block that becomes the new head
+    import_hooks: std::sync::RwLock<Vec<Arc<dyn BlockImportHook>>>,
+    // Set once a fork couldn't be switched to
+    refused_reorg: std::sync::Mutex<Option<RefusedReorg>>,
 
     // Components
     pub buffer: BlockBuffer,
     pub data_processor: Arc<DataProcessor>,
 
-
+    // Pruning
+    pruning: PruningMode,
+    // Blocks below this height have had their bodies pruned
+    pruned_below: watch::Sender<i64>,
 
     // Internal
     pop_rx: tokio::sync::Mutex<mpsc::Receiver<Block>>,
+    // Task committing blocks popped from the buffer
+    finalizer: std::sync::Mutex<Option<JoinHandle<()>>>,
 }
 
 impl Blockchain {
@@ -46,6 +127,25 @@ impl Blockchain {
     /// Create blockchain with existing blockchain data
     /// Loads existing chain or initializes with given genesis
     pub async fn new_with_genesis(db: Arc<dyn DBInterface>, genesis: Block) -> Arc<Self> {
+        Self::new_with_pruning(db, genesis, PruningMode::Archive).await
+    }
+
+    /// Create blockchain that prunes old block bodies according to `pruning`
+    pub async fn new_with_pruning(db: Arc<dyn DBInterface>, genesis: Block, pruning: PruningMode) -> Arc<Self> {
+        Self::new_with_shutdown(db, genesis, pruning, CancellationToken::new()).await
+    }
+
+    /// Create blockchain whose block finalization stops when `shutdown` is
+    /// cancelled
+    ///
+    /// A block being committed at that point is completed; wait for it with
+    /// [`Self::finalizer_stopped`].
+    pub async fn new_with_shutdown(
+        db: Arc<dyn DBInterface>,
+        genesis: Block,
+        pruning: PruningMode,
+        shutdown: CancellationToken,
+    ) -> Arc<Self> {
         let (pop_tx, pop_rx) = mpsc::channel(128);
         let dp = DataProcessor::new(db.clone());
 
@@ -53,8 +153,7 @@ impl Blockchain {
         let mut loaded_from_db = false;
 
         // Try load latest from DB
-        let latest_key = b"latest";
-        if let Ok(Some(hash_bytes)) = db.get(latest_key).await {
+        if let Ok(Some(hash_bytes)) = db.get(LATEST_KEY).await {
             if hash_bytes.len() == 32 {
                 let mut hash = Hash::default();
                 hash.0.copy_from_slice(&hash_bytes);
@@ -78,6 +177,11 @@ impl Blockchain {
             }
         }
 
+        let pruned_below = match db.get(PRUNED_BELOW_KEY).await {
+            Ok(Some(bytes)) => bytes.try_into().map(i64::from_be_bytes).unwrap_or(0),
+            _ => 0,
+        };
+
         let buffer = BlockBuffer::new(latest_block.clone(), pop_tx).await;
 
         // Extract genesis parameters
@@ -98,9 +202,16 @@ impl Blockchain {
             tx_cache: Cache::new(MAX_TX_CACHE),
             block_height_map: Cache::new(MAX_BLOCK_CACHE),
             latest_block: Arc::new(RwLock::new(latest_block.clone())),
+            head: watch::Sender::new(latest_block.header.block_hash),
+            events: Arc::new(EventBus::default()),
+            import_hooks: std::sync::RwLock::new(Vec::new()),
+            refused_reorg: std::sync::Mutex::new(None),
             buffer,
             data_processor: dp,
+            pruning,
+            pruned_below: watch::Sender::new(pruned_below),
             pop_rx: tokio::sync::Mutex::new(pop_rx),
+            finalizer: std::sync::Mutex::new(None),
         });
 
         // If fresh chain, save genesis
@@ -118,9 +229,10 @@ impl Blockchain {
         }
 
         let c = chain.clone();
-        tokio::spawn(async move {
-            c.finalize_loop().await;
+        let finalizer = tokio::spawn(async move {
+            c.finalize_loop(shutdown).await;
         });
+        *chain.finalizer.lock().unwrap_or_else(|e| e.into_inner()) = Some(finalizer);
 
         chain
     }
@@ -133,35 +245,512 @@ impl Blockchain {
     }
 
     async fn save_latest_index(&self, hash: &Hash) -> anyhow::Result<()> {
-        self.db.insert(b"latest", &hash.0).await?;
+        self.db.insert(LATEST_KEY, &hash.0).await?;
         Ok(())
     }
 
     /// Commit block to chain: save to DB, update in-memory state, and update latest index
+    ///
+    /// Only a block higher than the current head becomes canonical (simple
+    /// fork choice); others are stored without a height index entry so a later
+    /// reorg can switch to them. A new head is executed by the import hooks
+    /// while the head is locked; the block, its indexes, the writes of the
+    /// hooks and the latest index are then written in one atomic batch. If a
+    /// hook or the write fails, the hooks are told to undo the block and
+    /// nothing is stored. The new head is published once it is committed.
+    ///
+    /// A higher block on another branch switches the chain to that branch:
+    /// the blocks it replaces are reverted by the hooks, newest first, and
+    /// the blocks of the branch are imported one by one as above. A switch
+    /// reverting more than [`MAX_REORG_DEPTH`] blocks, or blocks the hooks
+    /// can't revert, is refused with an error; see [`Self::refused_reorg`].
+    /// A higher block whose ancestors aren't all stored is refused with an
+    /// error as well.
     pub async fn commit_block(&self, block: &Block) -> anyhow::Result<()> {
-        self.save_block(block).await?;
+        let mut latest = self.latest_block.write().await;
+        if block.header.height <= latest.header.height {
+            return self.save_block_data(block).await;
+        }
 
+        let hooks = self.import_hooks.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if block.header.prev_block_hash == latest.header.block_hash
+            && block.header.height == latest.header.height + 1
         {
-            let mut latest = self.latest_block.write().await;
-            // Only update if height is greater (simple fork choice)
-            // Or if we trust the caller (like BlockProducer)
-            if block.header.height > latest.header.height {
-                *latest = block.clone();
-                drop(latest); // Unlock
-                self.save_latest_index(&block.header.block_hash).await?;
+            self.import_head(&mut latest, block, &hooks, ChainWrite::default()).await?;
+        } else {
+            // Head of a longer fork: its branch replaces ours
+            self.switch_branch(&mut latest, block, &hooks).await?;
+        }
+        drop(latest); // Unlock
+
+        if let Err(e) = self.prune(block.header.height).await {
+            error!("Failed to prune block bodies: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Execute `block`, a child of the head `latest`, and commit it as the
+    /// new head together with `write`
+    ///
+    /// Entries `write` removes give way to the ones written for the block.
+    async fn import_head(
+        &self,
+        latest: &mut Block,
+        block: &Block,
+        hooks: &[Arc<dyn BlockImportHook>],
+        mut write: ChainWrite,
+    ) -> anyhow::Result<()> {
+        Self::push_block_entries(block, true, &mut write)?;
+        if !write.removed.is_empty() {
+            let written: std::collections::HashSet<&Vec<u8>> = write.keys.iter().collect();
+            write.removed.retain(|key| !written.contains(key));
+        }
+        write.put(LATEST_KEY.to_vec(), block.header.block_hash.0.to_vec());
+
+        self.run_import_hooks(hooks, block, &mut write).await?;
+        if !write.receipts.is_empty() {
+            write.put(
+                norn_common::utils::db_keys::block_hash_to_receipts_db_key(&block.header.block_hash),
+                norn_common::utils::codec::serialize(&write.receipts)?,
+            );
+        }
+
+        if let Err(e) = self.db.batch_write(&write.keys, &write.values, &write.removed).await {
+            Self::abort_import(hooks, block).await;
+            return Err(e);
+        }
+        for hook in hooks {
+            hook.on_block_committed(block).await;
+        }
+
+        self.block_height_map.invalidate(&block.header.height).await;
+        *latest = block.clone();
+        // Readers keyed by the head only see it once its state is in place
+        self.head.send_replace(block.header.block_hash);
+        self.publish_included(block, &write.receipts);
+        Ok(())
+    }
+
+    /// Make the branch ending in `tip` canonical in place of the head `latest`
+    ///
+    /// Reverts the canonical blocks above the fork point, newest first, then
+    /// imports the blocks of the branch. The first write drops the reverted
+    /// blocks from the indexes, so a crash part way leaves a shorter chain
+    /// whose state matches its head. If a block of the branch fails, the
+    /// blocks imported so far are reverted and the old branch is imported
+    /// again, unless the imported part already outgrew it.
+    async fn switch_branch(
+        &self,
+        latest: &mut Block,
+        tip: &Block,
+        hooks: &[Arc<dyn BlockImportHook>],
+    ) -> anyhow::Result<()> {
+        let (fork_height, branch) = self.fork_branch(tip).await?;
+        let old_head = latest.clone();
+        let reverted = self.canonical_blocks_above(fork_height, old_head.header.height).await;
+        if !hooks.is_empty() && !reverted.is_empty() {
+            self.check_revertible(hooks, &reverted, fork_height, &old_head, tip)?;
+        }
+        Self::revert_blocks(hooks, &reverted).await;
+
+        let mut write = ChainWrite::default();
+        Self::push_reverted_entries(&reverted, &mut write);
+        let result = if branch.is_empty() {
+            // `tip` is an ancestor of the head
+            write.put(LATEST_KEY.to_vec(), tip.header.block_hash.0.to_vec());
+            let result = self.db.batch_write(&write.keys, &write.values, &write.removed).await;
+            if result.is_ok() {
+                *latest = tip.clone();
+                self.head.send_replace(tip.header.block_hash);
+            }
+            result
+        } else {
+            self.import_branch(latest, &branch, hooks, write).await
+        };
+
+        if let Err(e) = &result {
+            if reverted.is_empty() || latest.header.height > old_head.header.height {
+                warn!("Fork switch stopped at height {}: {:#}", latest.header.height, e);
+            } else {
+                error!("Failed to switch to the fork at height {}, restoring the previous chain: {:#}", tip.header.height, e);
+                self.restore_branch(latest, &branch, &reverted, hooks).await;
+            }
+        }
+
+        for height in (fork_height + 1)..=old_head.header.height.max(tip.header.height) {
+            self.block_height_map.invalidate(&height).await;
+        }
+        result?;
+
+        if old_head.header.height > fork_height {
+            info!(
+                "Canonical chain switched above height {}: {} blocks replaced, new head at {}",
+                fork_height,
+                old_head.header.height - fork_height,
+                tip.header.height
+            );
+        }
+        Ok(())
+    }
+
+    /// Import `branch`, lowest block first, on top of `latest`, writing
+    /// `write` with the first block
+    async fn import_branch(
+        &self,
+        latest: &mut Block,
+        branch: &[Block],
+        hooks: &[Arc<dyn BlockImportHook>],
+        mut write: ChainWrite,
+    ) -> anyhow::Result<()> {
+        for block in branch {
+            self.import_head(latest, block, hooks, std::mem::take(&mut write)).await?;
+        }
+        Ok(())
+    }
+
+    /// Put the `reverted` blocks back after a failed switch to `branch` left
+    /// the head at `latest`
+    async fn restore_branch(
+        &self,
+        latest: &mut Block,
+        branch: &[Block],
+        reverted: &[Block],
+        hooks: &[Arc<dyn BlockImportHook>],
+    ) {
+        // Blocks of the branch imported before the failure, newest first
+        let imported: Vec<Block> = match branch.iter().position(|block| block.header.block_hash == latest.header.block_hash) {
+            Some(last) => branch[..=last].iter().rev().cloned().collect(),
+            None => Vec::new(),
+        };
+        Self::revert_blocks(hooks, &imported).await;
+
+        let mut write = ChainWrite::default();
+        Self::push_reverted_entries(&imported, &mut write);
+        let old_branch: Vec<Block> = reverted.iter().rev().cloned().collect();
+        if let Err(e) = self.import_branch(latest, &old_branch, hooks, write).await {
+            error!("Failed to restore the previous chain, head left at height {}: {:#}", latest.header.height, e);
+        }
+    }
+
+    /// Blocks from the first one off the canonical chain up to `tip`, lowest
+    /// first, and the height of the canonical block they build on
+    ///
+    /// The branch is empty if `tip` is canonical itself. Fails if an ancestor
+    /// of `tip` isn't stored or the branch doesn't lead back to a canonical
+    /// block, so an orphan is never imported on top of an unrelated state.
+    async fn fork_branch(&self, tip: &Block) -> anyhow::Result<(i64, Vec<Block>)> {
+        if self.canonical_hash(tip.header.height).await == Some(tip.header.block_hash) {
+            return Ok((tip.header.height, Vec::new()));
+        }
+
+        let mut branch = vec![tip.clone()];
+        let mut current = tip.header.clone();
+        while self.canonical_hash(current.height - 1).await != Some(current.prev_block_hash) {
+            if current.height <= 0 {
+                anyhow::bail!(
+                    "Block {:?} at height {} doesn't connect to the canonical chain",
+                    tip.header.block_hash,
+                    tip.header.height
+                );
+            }
+            let parent = match self.get_block_by_hash(&current.prev_block_hash).await {
+                Some(parent) if parent.header.height == current.height - 1 => parent,
+                _ => anyhow::bail!(
+                    "Parent {:?} of block at height {} is not stored, refusing block {:?}",
+                    current.prev_block_hash,
+                    current.height,
+                    tip.header.block_hash
+                ),
+            };
+            current = parent.header.clone();
+            branch.push(parent);
+        }
+        branch.reverse();
+        Ok((current.height - 1, branch))
+    }
+
+    /// Canonical blocks above `fork_height` up to `head_height`, newest first
+    async fn canonical_blocks_above(&self, fork_height: i64, head_height: i64) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        for height in ((fork_height + 1)..=head_height).rev() {
+            match self.get_block_by_height(height).await {
+                Some(block) => blocks.push(block),
+                None => warn!("Canonical block at height {} not found", height),
+            }
+        }
+        blocks
+    }
+
+    /// Refuse a switch to `tip` the hooks can't follow, recording it
+    fn check_revertible(
+        &self,
+        hooks: &[Arc<dyn BlockImportHook>],
+        reverted: &[Block],
+        fork_height: i64,
+        old_head: &Block,
+        tip: &Block,
+    ) -> anyhow::Result<()> {
+        let depth = old_head.header.height - fork_height;
+        let reason = if depth > MAX_REORG_DEPTH as i64 {
+            format!("it reverts {} blocks, more than the {} that can be rolled back", depth, MAX_REORG_DEPTH)
+        } else if reverted.len() as i64 != depth || self.is_block_pruned(fork_height + 1) {
+            format!("bodies of the {} blocks it reverts are not all stored", depth)
+        } else if !hooks.iter().all(|hook| hook.can_revert(reverted)) {
+            format!("the state of the {} blocks it reverts can't be rolled back", depth)
+        } else {
+            return Ok(());
+        };
+
+        REORGS_REFUSED_TOTAL.inc();
+        *self.refused_reorg.lock().unwrap_or_else(|e| e.into_inner()) = Some(RefusedReorg {
+            fork_height,
+            depth: depth as u64,
+            tip: tip.header.block_hash,
+        });
+        error!(
+            "Refusing to switch to the fork at height {} (head {:?}) forking off at height {}: {}",
+            tip.header.height, tip.header.block_hash, fork_height, reason
+        );
+        anyhow::bail!(
+            "Cannot switch to the fork forking off at height {}: {}; the node must be resynced",
+            fork_height,
+            reason
+        )
+    }
+
+    /// Revert the committed `blocks`, newest first, last hook first
+    async fn revert_blocks(hooks: &[Arc<dyn BlockImportHook>], blocks: &[Block]) {
+        for block in blocks {
+            for hook in hooks.iter().rev() {
+                hook.on_block_reverted(block).await;
+            }
+        }
+    }
+
+    /// Drop the index entries of `blocks`, which leave the canonical chain
+    fn push_reverted_entries(blocks: &[Block], write: &mut ChainWrite) {
+        for block in blocks {
+            write.delete(norn_common::utils::db_keys::block_height_to_db_key(block.header.height));
+            write.delete(norn_common::utils::db_keys::block_hash_to_height_db_key(&block.header.block_hash));
+            for tx in &block.transactions {
+                write.delete(norn_common::utils::db_keys::tx_hash_to_location_db_key(&tx.body.hash));
+            }
+        }
+    }
+
+    /// Fork the chain refused to switch to, if any
+    ///
+    /// Once a fork was refused the node can't follow it, and syncing halts
+    /// until the node is resynced.
+    pub fn refused_reorg(&self) -> Option<RefusedReorg> {
+        self.refused_reorg.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Run `hook` for every block that becomes the new head
+    ///
+    /// Hooks run in the order they were added.
+    pub fn add_import_hook(&self, hook: Arc<dyn BlockImportHook>) {
+        self.import_hooks.write().unwrap_or_else(|e| e.into_inner()).push(hook);
+    }
+
+    /// Run `hooks` on the new head `block`, aborting all of them if one fails
+    async fn run_import_hooks(
+        &self,
+        hooks: &[Arc<dyn BlockImportHook>],
+        block: &Block,
+        write: &mut ChainWrite,
+    ) -> anyhow::Result<()> {
+        for (index, hook) in hooks.iter().enumerate() {
+            if let Err(e) = hook.on_block_imported(block, write).await {
+                error!("Block import hook failed at height {}: {}", block.header.height, e);
+                Self::abort_import(&hooks[..=index], block).await;
+                return Err(e.context(format!("Failed to import block {}", block.header.height)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Undo `hooks` for a block that won't be committed, last hook first
+    async fn abort_import(hooks: &[Arc<dyn BlockImportHook>], block: &Block) {
+        for hook in hooks.iter().rev() {
+            hook.on_import_aborted(block).await;
+        }
+    }
+
+    /// Publish a [`TxIncluded`] event for each transaction of the new head `block`
+    ///
+    /// Executed transactions carry their receipt from `receipts`. Others, such
+    /// as native transactions, get a receipt that only locates them; its gas
+    /// fields are left at zero.
+    fn publish_included(&self, block: &Block, receipts: &[Receipt]) {
+        let block_hash = block.header.block_hash;
+        let block_number = block.header.height.max(0) as u64;
+        let mut executed: std::collections::HashMap<Hash, &Receipt> =
+            receipts.iter().map(|receipt| (receipt.tx_hash, receipt)).collect();
+
+        for (index, tx) in block.transactions.iter().enumerate() {
+            let receipt = match executed.remove(&tx.body.hash) {
+                Some(receipt) => receipt.clone(),
+                None => Receipt::new(tx.body.hash, block_hash, block_number, index as u64)
+                    .with_from(tx.body.address)
+                    .with_to(Some(tx.body.receiver)),
+            };
+
+            self.events.publish(TxIncluded {
+                hash: tx.body.hash,
+                block_hash,
+                block_number,
+                index: index as u64,
+                receipt,
+            });
+        }
+    }
+
+    /// Receipts stored with the block `hash` by the import hooks
+    pub async fn get_block_receipts(&self, hash: &Hash) -> Option<Vec<Receipt>> {
+        let key = norn_common::utils::db_keys::block_hash_to_receipts_db_key(hash);
+        let bytes = self.db.get(&key).await.ok()??;
+        norn_common::utils::codec::deserialize(&bytes).ok()
+    }
+
+    /// Make `tip` the head of the chain, e.g. after a reorg
+    ///
+    /// `tip` and the blocks between it and the canonical chain must already be
+    /// stored. Unlike `commit_block`, the new head may be lower than the old
+    /// one. The switch reverts and imports blocks as in `commit_block`.
+    pub async fn set_canonical_head(&self, tip: &Block) -> anyhow::Result<()> {
+        let mut latest = self.latest_block.write().await;
+        if latest.header.block_hash == tip.header.block_hash {
+            return Ok(());
+        }
+        let hooks = self.import_hooks.read().unwrap_or_else(|e| e.into_inner()).clone();
+        self.switch_branch(&mut latest, tip, &hooks).await
+    }
+
+    /// Pruning mode of this chain
+    pub fn pruning_mode(&self) -> PruningMode {
+        self.pruning
+    }
+
+    /// Height below which block bodies have been pruned
+    pub fn pruned_below(&self) -> i64 {
+        *self.pruned_below.borrow()
+    }
+
+    /// Watch the pruning boundary, e.g. to drop receipts of pruned blocks
+    pub fn subscribe_pruned(&self) -> watch::Receiver<i64> {
+        self.pruned_below.subscribe()
+    }
+
+    /// Bus on which committed blocks publish [`TxIncluded`] events
+    pub fn events(&self) -> Arc<EventBus> {
+        self.events.clone()
+    }
+
+    /// Watch the hash of the chain head, e.g. to drop state cached for an
+    /// older head
+    pub fn subscribe_head(&self) -> watch::Receiver<Hash> {
+        self.head.subscribe()
+    }
+
+    /// Check if the body of the block at `height` has been pruned
+    pub fn is_block_pruned(&self, height: i64) -> bool {
+        height < self.pruned_below()
+    }
+
+    /// Check if a transaction was removed by pruning
+    pub async fn is_transaction_pruned(&self, hash: &Hash) -> bool {
+        let key = norn_common::utils::db_keys::pruned_tx_hash_to_db_key(hash);
+        matches!(self.db.get(&key).await, Ok(Some(_)))
+    }
+
+    /// Strip the bodies of blocks that fell out of the retention window
+    ///
+    /// Pruned blocks keep their header; their transactions are replaced by
+    /// small markers so lookups can tell pruned data from unknown data.
+    async fn prune(&self, latest_height: i64) -> anyhow::Result<()> {
+        let Some(target) = self.pruning.prune_below(latest_height) else {
+            return Ok(());
+        };
+        let from = self.pruned_below();
+        if from >= target {
+            return Ok(());
+        }
+
+        let mut write = ChainWrite::default();
+        let mut pruned_txs = 0;
+        let mut pruned_receipts = 0;
+
+        for height in from..target {
+            let Some(mut block) = self.get_block_by_height(height).await else {
+                continue;
+            };
+
+            for tx in &block.transactions {
+                let tx_hash = tx.body.hash;
+                write.delete(norn_common::utils::db_keys::tx_hash_to_db_key(&tx_hash));
+                write.delete(norn_common::utils::db_keys::tx_hash_to_location_db_key(&tx_hash));
+                write.put(
+                    norn_common::utils::db_keys::pruned_tx_hash_to_db_key(&tx_hash),
+                    height.to_be_bytes().to_vec(),
+                );
+                self.tx_cache.invalidate(&tx_hash).await;
+                pruned_txs += 1;
             }
+
+            block.transactions.clear();
+            write.delete(norn_common::utils::db_keys::block_hash_to_receipts_db_key(&block.header.block_hash));
+            pruned_receipts += 1;
+            write.put(
+                norn_common::utils::db_keys::block_hash_to_db_key(&block.header.block_hash),
+                norn_common::utils::codec::serialize(&block)?,
+            );
+            self.block_cache.invalidate(&block.header.block_hash).await;
         }
+
+        write.put(PRUNED_BELOW_KEY.to_vec(), target.to_be_bytes().to_vec());
+
+        self.db.batch_write(&write.keys, &write.values, &write.removed).await?;
+        self.pruned_below.send_replace(target);
+
+        info!(
+            "Pruned block bodies below height {} ({} transactions, receipts of {} blocks)",
+            target, pruned_txs, pruned_receipts
+        );
         Ok(())
     }
 
-    async fn finalize_loop(&self) {
+    /// Commit the blocks popped from the buffer until `shutdown` is cancelled
+    ///
+    /// Cancellation is only checked between blocks, so a commit is never cut
+    /// off.
+    async fn finalize_loop(&self, shutdown: CancellationToken) {
         let mut rx = self.pop_rx.lock().await;
-        while let Some(block) = rx.recv().await {
+        loop {
+            let block = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                block = rx.recv() => match block {
+                    Some(block) => block,
+                    None => break,
+                },
+            };
             info!("Finalizing block height={}", block.header.height);
             if let Err(e) = self.commit_block(&block).await {
                 error!("Failed to commit block: {}", e);
             }
         }
+        info!("Block finalization stopped");
+    }
+
+    /// Wait until block finalization has stopped after its shutdown token
+    /// was cancelled
+    pub async fn finalizer_stopped(&self) {
+        let finalizer = self.finalizer.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(finalizer) = finalizer {
+            if let Err(e) = finalizer.await {
+                error!("Block finalization failed: {}", e);
+            }
+        }
     }
 
     pub async fn add_block(&self, block: Block) {
@@ -190,9 +779,37 @@ impl Blockchain {
     }
 
     pub async fn get_block_by_height(&self, height: i64) -> Option<Block> {
+        let hash = self.canonical_hash(height).await?;
+        self.get_block_by_hash(&hash).await
+    }
+
+    /// Height of a block on the canonical chain, `None` for unknown or side-chain blocks
+    pub async fn get_block_height(&self, hash: &Hash) -> Option<i64> {
+        let key = norn_common::utils::db_keys::block_hash_to_height_db_key(hash);
+        match self.db.get(&key).await {
+            Ok(Some(bytes)) => bytes.try_into().ok().map(i64::from_be_bytes),
+            _ => None,
+        }
+    }
+
+    /// Block hash and index of a transaction on the canonical chain
+    pub async fn get_transaction_location(&self, hash: &Hash) -> Option<(Hash, u64)> {
+        let key = norn_common::utils::db_keys::tx_hash_to_location_db_key(hash);
+        let bytes = self.db.get(&key).await.ok()??;
+        if bytes.len() != 40 {
+            return None;
+        }
+        let mut block_hash = Hash::default();
+        block_hash.0.copy_from_slice(&bytes[..32]);
+        let index = u64::from_be_bytes(bytes[32..].try_into().ok()?);
+        Some((block_hash, index))
+    }
+
+    /// Hash of the canonical block at `height`
+    async fn canonical_hash(&self, height: i64) -> Option<Hash> {
         // 1. Check Cache (Height Map)
         if let Some(hash) = self.block_height_map.get(&height).await {
-            return self.get_block_by_hash(&hash).await;
+            return Some(hash);
         }
 
         // 2. Check DB for Height->Hash mapping
@@ -207,7 +824,7 @@ impl Blockchain {
                  let mut h = Hash::default();
                  h.0.copy_from_slice(&hash_bytes);
                  self.block_height_map.insert(height, h).await;
-                 return self.get_block_by_hash(&h).await;
+                 return Some(h);
              }
         }
         None
@@ -232,31 +849,68 @@ impl Blockchain {
 
     // --- Persistence ---
 
+    /// Save a block as part of the canonical chain, indexing it by height
     pub async fn save_block(&self, block: &Block) -> anyhow::Result<()> {
-        // Batch write: Block, Transactions, Indices
-        let mut keys = Vec::new();
-        let mut values = Vec::new();
+        self.write_block(block, true).await
+    }
+
+    /// Save a block off the canonical chain, without height index entries
+    async fn save_block_data(&self, block: &Block) -> anyhow::Result<()> {
+        self.write_block(block, false).await
+    }
+
+    /// Index entries of a block on the canonical chain: height <-> hash and
+    /// the location of each of its transactions
+    fn push_canonical_entries(block: &Block, write: &mut ChainWrite) {
+        let block_hash = block.header.block_hash;
+        write.put(
+            norn_common::utils::db_keys::block_height_to_db_key(block.header.height),
+            block_hash.0.to_vec(), // Store raw 32 bytes hash
+        );
+        write.put(
+            norn_common::utils::db_keys::block_hash_to_height_db_key(&block_hash),
+            block.header.height.to_be_bytes().to_vec(),
+        );
+
+        for (index, tx) in block.transactions.iter().enumerate() {
+            let mut location = block_hash.0.to_vec();
+            location.extend_from_slice(&(index as u64).to_be_bytes());
+            write.put(norn_common::utils::db_keys::tx_hash_to_location_db_key(&tx.body.hash), location);
+        }
+    }
+
+    async fn write_block(&self, block: &Block, canonical: bool) -> anyhow::Result<()> {
+        let mut write = ChainWrite::default();
+        Self::push_block_entries(block, canonical, &mut write)?;
+        self.db.batch_write(&write.keys, &write.values, &write.removed).await?;
 
+        if canonical {
+            self.block_height_map.invalidate(&block.header.height).await;
+        }
+        Ok(())
+    }
+
+    /// Entries of a block: its body, its transactions and, on the canonical
+    /// chain, its indexes
+    fn push_block_entries(block: &Block, canonical: bool, write: &mut ChainWrite) -> anyhow::Result<()> {
         let block_hash = block.header.block_hash;
 
         // 1. Save Block
         let block_key = norn_common::utils::db_keys::block_hash_to_db_key(&block_hash);
         let block_data = norn_common::utils::codec::serialize(block)?;
-        keys.push(block_key);
-        values.push(block_data);
+        write.put(block_key, block_data);
 
-        // 2. Save Height -> Hash mapping
-        let height_key = norn_common::utils::db_keys::block_height_to_db_key(block.header.height);        
-        keys.push(height_key);
-        values.push(block_hash.0.to_vec()); // Store raw 32 bytes hash
+        // 2. Save Height <-> Hash mappings and transaction locations
+        if canonical {
+            Self::push_canonical_entries(block, write);
+        }
 
         // 3. Save Transactions
         for tx in &block.transactions {
             let tx_hash = tx.body.hash;
             let tx_key = norn_common::utils::db_keys::tx_hash_to_db_key(&tx_hash);
             let tx_data = norn_common::utils::codec::serialize(tx)?;
-            keys.push(tx_key);
-            values.push(tx_data);
+            write.put(tx_key, tx_data);
 
             // Go: Also updates DataProcessor logic if needed?
             // Go `dp.Run` handles data tasks.
@@ -266,14 +920,51 @@ impl Blockchain {
             // We'll leave that for integration.
         }
 
-        self.db.batch_insert(&keys, &values).await?;
-
         Ok(())
     }
 }
 
 use async_trait::async_trait;
 
+/// Work done when a block becomes the head of the chain, e.g. executing it
+/// and committing its state
+///
+/// [`Blockchain::commit_block`] calls `on_block_imported` on every hook
+/// before anything of the block is stored, then either `on_block_committed`
+/// once the block and `write` are durable, or `on_import_aborted` if a hook
+/// or the write failed. `on_import_aborted` is also called on the hook that
+/// failed.
+///
+/// When the chain switches to another branch, the committed blocks it
+/// replaces are handed to `on_block_reverted`, head first and last hook
+/// first, before the blocks of the branch are imported.
+#[async_trait]
+pub trait BlockImportHook: Send + Sync {
+    /// Process `block`, adding writes to commit with it to `write`
+    ///
+    /// An error aborts the import.
+    async fn on_block_imported(&self, block: &Block, write: &mut ChainWrite) -> anyhow::Result<()>;
+
+    /// `block` and the writes of the hooks are committed
+    async fn on_block_committed(&self, _block: &Block) {}
+
+    /// `block` won't be committed; undo `on_block_imported`
+    async fn on_import_aborted(&self, _block: &Block) {}
+
+    /// Whether `on_block_reverted` can undo the committed `blocks`, head first
+    ///
+    /// The chain refuses to switch to a branch if any hook can't.
+    fn can_revert(&self, _blocks: &[Block]) -> bool {
+        true
+    }
+
+    /// Undo the committed `block`, the current head, as it leaves the chain
+    ///
+    /// Nothing is written for the reverted block itself; what the hooks
+    /// restore is committed with the next imported block.
+    async fn on_block_reverted(&self, _block: &Block) {}
+}
+
 // Implement ChainReader for TxPool integration
 #[async_trait]
 impl ChainReader for Blockchain {
@@ -391,4 +1082,513 @@ mod tests {
         assert!(retrieved_height.is_some());
         assert_eq!(retrieved_height.unwrap().header.block_hash, b1.header.block_hash);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_get_block_by_height_after_import() {
+        let db = Arc::new(MockDB::new());
+        let genesis = norn_common::genesis::get_genesis_block();
+        let chain = Blockchain::new_with_fixed_genesis(db.clone()).await;
+
+        let blocks = blocks_with_txs(&genesis, 5);
+        for block in &blocks {
+            chain.commit_block(block).await.unwrap();
+        }
+
+        assert_eq!(chain.get_block_by_height(0).await.unwrap().header.block_hash, genesis.header.block_hash);
+        for block in &blocks {
+            let stored = chain.get_block_by_height(block.header.height).await.unwrap();
+            assert_eq!(stored.header, block.header);
+            assert_eq!(stored.transactions.len(), 2);
+        }
+        assert!(chain.get_block_by_height(6).await.is_none());
+
+        // The index is read back from the database after a restart
+        let reopened = Blockchain::new_with_fixed_genesis(db).await;
+        let stored = reopened.get_block_by_height(3).await.unwrap();
+        assert_eq!(stored.header.block_hash, blocks[2].header.block_hash);
+    }
+
+    fn child_block(parent: &Block, branch: u8) -> Block {
+        let mut block = Block::default();
+        block.header.height = parent.header.height + 1;
+        block.header.prev_block_hash = parent.header.block_hash;
+        block.header.block_hash.0[0] = block.header.height as u8;
+        block.header.block_hash.0[1] = branch;
+        block
+    }
+
+    #[tokio::test]
+    async fn test_transaction_location() {
+        let db = Arc::new(MockDB::new());
+        let chain = Blockchain::new_with_fixed_genesis(db).await;
+        let genesis = chain.latest_block.read().await.clone();
+
+        let blocks = blocks_with_txs(&genesis, 3);
+        for block in &blocks {
+            chain.commit_block(block).await.unwrap();
+        }
+
+        for block in &blocks {
+            for (index, tx) in block.transactions.iter().enumerate() {
+                let location = chain.get_transaction_location(&tx.body.hash).await;
+                assert_eq!(location, Some((block.header.block_hash, index as u64)));
+            }
+        }
+        assert_eq!(chain.get_transaction_location(&Hash([0xff; 32])).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_canonical_indexes_follow_reorg() {
+        let db = Arc::new(MockDB::new());
+        let chain = Blockchain::new_with_fixed_genesis(db.clone()).await;
+        let genesis = chain.latest_block.read().await.clone();
+
+        // Canonical chain genesis <- a1 <- a2 <- a3
+        let mut a = vec![genesis.clone()];
+        for _ in 0..3 {
+            let block = child_block(a.last().unwrap(), 0xa);
+            chain.commit_block(&block).await.unwrap();
+            a.push(block);
+        }
+
+        // Fork from a1: b2 and b3 are side blocks until b4 outgrows the chain
+        let mut b = vec![a[1].clone()];
+        for _ in 0..2 {
+            let block = child_block(b.last().unwrap(), 0xb);
+            chain.commit_block(&block).await.unwrap();
+            b.push(block);
+        }
+        assert_eq!(chain.get_block_by_height(2).await.unwrap().header.block_hash, a[2].header.block_hash);
+        assert_eq!(chain.get_block_height(&b[1].header.block_hash).await, None);
+
+        let b4 = child_block(b.last().unwrap(), 0xb);
+        chain.commit_block(&b4).await.unwrap();
+        b.push(b4);
+
+        for block in a.iter().take(2).chain(&b[1..]) {
+            let height = block.header.height;
+            assert_eq!(chain.get_block_by_height(height).await.unwrap().header.block_hash, block.header.block_hash);
+            assert_eq!(chain.get_block_height(&block.header.block_hash).await, Some(height));
+        }
+        for block in &a[2..] {
+            assert_eq!(chain.get_block_height(&block.header.block_hash).await, None);
+        }
+
+        // Switching back to the shorter branch drops the heights above it
+        chain.set_canonical_head(&a[3]).await.unwrap();
+        for block in &a {
+            let height = block.header.height;
+            assert_eq!(chain.get_block_by_height(height).await.unwrap().header.block_hash, block.header.block_hash);
+            assert_eq!(chain.get_block_height(&block.header.block_hash).await, Some(height));
+        }
+        assert!(chain.get_block_by_height(4).await.is_none());
+        for block in &b[1..] {
+            assert_eq!(chain.get_block_height(&block.header.block_hash).await, None);
+        }
+
+        // The head survives a restart
+        let reopened = Blockchain::new_with_fixed_genesis(db).await;
+        assert_eq!(reopened.latest_block.read().await.header.block_hash, a[3].header.block_hash);
+    }
+
+    /// Child of `parent` holding two transactions
+    fn block_with_txs(parent: &Block) -> Block {
+        let mut block = child_block(parent, 0xb1);
+        for i in 0..2u8 {
+            let mut tx = Transaction::default();
+            tx.body.hash.0[0] = block.header.height as u8;
+            tx.body.hash.0[1] = i;
+            tx.body.hash.0[2] = 0x7e;
+            block.transactions.push(tx);
+        }
+        block
+    }
+
+    /// `count` blocks built by [`block_with_txs`] on top of `parent`, each a
+    /// child of the one before
+    fn blocks_with_txs(parent: &Block, count: usize) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::with_capacity(count);
+        for _ in 0..count {
+            let block = block_with_txs(blocks.last().unwrap_or(parent));
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    #[tokio::test]
+    async fn test_full_pruning() {
+        let db = Arc::new(MockDB::new());
+        let genesis = norn_common::genesis::get_genesis_block();
+        let chain = Blockchain::new_with_pruning(db.clone(), genesis.clone(), PruningMode::Full { keep_recent: 3 }).await;
+
+        let blocks = blocks_with_txs(&genesis, 8);
+        for block in &blocks {
+            chain.commit_block(block).await.unwrap();
+        }
+
+        // Heights 6..=8 are retained
+        assert_eq!(chain.pruned_below(), 6);
+        assert!(chain.is_block_pruned(5));
+        assert!(!chain.is_block_pruned(6));
+
+        for block in &blocks {
+            let stored = chain.get_block_by_height(block.header.height).await.unwrap();
+            assert_eq!(stored.header, block.header);
+
+            let tx_hash = block.transactions[0].body.hash;
+            if block.header.height < 6 {
+                assert!(stored.transactions.is_empty());
+                assert!(chain.get_transaction_by_hash(&tx_hash).await.is_none());
+                assert!(chain.is_transaction_pruned(&tx_hash).await);
+                assert!(chain.get_transaction_location(&tx_hash).await.is_none());
+            } else {
+                assert_eq!(stored.transactions.len(), 2);
+                assert!(chain.get_transaction_by_hash(&tx_hash).await.is_some());
+                assert!(!chain.is_transaction_pruned(&tx_hash).await);
+                assert_eq!(
+                    chain.get_transaction_location(&tx_hash).await,
+                    Some((block.header.block_hash, 0))
+                );
+            }
+        }
+
+        // The boundary survives a restart
+        let reopened = Blockchain::new_with_pruning(db, Block::default(), PruningMode::Full { keep_recent: 3 }).await;
+        assert_eq!(reopened.pruned_below(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_finalizer_stops_on_shutdown() {
+        let db = Arc::new(MockDB::new());
+        let shutdown = CancellationToken::new();
+        let chain = Blockchain::new_with_shutdown(
+            db,
+            norn_common::genesis::get_genesis_block(),
+            PruningMode::Archive,
+            shutdown.clone(),
+        ).await;
+
+        shutdown.cancel();
+        tokio::time::timeout(std::time::Duration::from_secs(5), chain.finalizer_stopped())
+            .await
+            .expect("block finalization did not stop");
+        // Already stopped
+        chain.finalizer_stopped().await;
+    }
+
+    #[tokio::test]
+    async fn test_commit_runs_import_hooks() {
+        struct Recorder(Mutex<Vec<i64>>);
+
+        #[async_trait]
+        impl BlockImportHook for Recorder {
+            async fn on_block_imported(&self, block: &Block, _write: &mut ChainWrite) -> Result<()> {
+                self.0.lock().unwrap().push(block.header.height);
+                Ok(())
+            }
+        }
+
+        let db = Arc::new(MockDB::new());
+        let chain = Blockchain::new_with_fixed_genesis(db).await;
+        let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+        chain.add_import_hook(recorder.clone());
+
+        let b1 = block_with_txs(&chain.latest_block.read().await.clone());
+        chain.commit_block(&b1).await.unwrap();
+        // Re-committing a stored block doesn't move the head
+        chain.commit_block(&b1).await.unwrap();
+
+        assert_eq!(*recorder.0.lock().unwrap(), vec![1]);
+    }
+
+    /// Hook recording the blocks it imported and reverted
+    struct Reverter {
+        revertible: bool,
+        seen: Mutex<Vec<(&'static str, Hash)>>,
+    }
+
+    #[async_trait]
+    impl BlockImportHook for Reverter {
+        async fn on_block_imported(&self, block: &Block, _write: &mut ChainWrite) -> Result<()> {
+            self.seen.lock().unwrap().push(("imported", block.header.block_hash));
+            Ok(())
+        }
+
+        fn can_revert(&self, _blocks: &[Block]) -> bool {
+            self.revertible
+        }
+
+        async fn on_block_reverted(&self, block: &Block) {
+            self.seen.lock().unwrap().push(("reverted", block.header.block_hash));
+        }
+    }
+
+    /// Chain genesis <- a1 <- a2 with side block b2 <- a1, and b3 on top of b2
+    async fn forked_chain(revertible: bool) -> (Arc<Blockchain>, Arc<Reverter>, Vec<Block>, Vec<Block>) {
+        let db = Arc::new(MockDB::new());
+        let chain = Blockchain::new_with_fixed_genesis(db).await;
+        let hook = Arc::new(Reverter { revertible, seen: Mutex::new(Vec::new()) });
+        chain.add_import_hook(hook.clone());
+
+        let mut a = vec![chain.latest_block.read().await.clone()];
+        for _ in 0..2 {
+            let mut block = child_block(a.last().unwrap(), 0xa);
+            block.transactions = block_with_txs(a.last().unwrap()).transactions;
+            chain.commit_block(&block).await.unwrap();
+            a.push(block);
+        }
+        let mut b = vec![a[1].clone()];
+        for _ in 0..2 {
+            let mut block = child_block(b.last().unwrap(), 0xb);
+            block.transactions = block_with_txs(b.last().unwrap()).transactions;
+            b.push(block);
+        }
+        chain.commit_block(&b[1]).await.unwrap();
+        (chain, hook, a, b)
+    }
+
+    #[tokio::test]
+    async fn test_hooks_follow_fork_reorg() {
+        let (chain, hook, a, b) = forked_chain(true).await;
+
+        // b3 outgrows the chain: a2 is reverted before b2 and b3 are executed
+        chain.commit_block(&b[2]).await.unwrap();
+        let hash = |block: &Block| block.header.block_hash;
+        assert_eq!(
+            *hook.seen.lock().unwrap(),
+            vec![
+                ("imported", hash(&a[1])),
+                ("imported", hash(&a[2])),
+                ("reverted", hash(&a[2])),
+                ("imported", hash(&b[1])),
+                ("imported", hash(&b[2])),
+            ]
+        );
+        assert_eq!(*chain.subscribe_head().borrow(), hash(&b[2]));
+        for block in &b[1..] {
+            let height = block.header.height;
+            assert_eq!(chain.get_block_by_height(height).await.unwrap().header.block_hash, hash(block));
+        }
+        assert_eq!(chain.get_block_height(&hash(&a[2])).await, None);
+        let tx = &b[1].transactions[0].body.hash;
+        assert_eq!(chain.get_transaction_location(tx).await, Some((hash(&b[1]), 0)));
+        assert_eq!(chain.refused_reorg(), None);
+    }
+
+    #[tokio::test]
+    async fn test_unrevertible_hooks_refuse_fork_reorg() {
+        let (chain, hook, a, b) = forked_chain(false).await;
+        let tip = &b[2];
+
+        assert!(chain.commit_block(tip).await.is_err());
+        assert!(chain.set_canonical_head(tip).await.is_err());
+        assert_eq!(
+            chain.refused_reorg(),
+            Some(RefusedReorg { fork_height: 1, depth: 1, tip: tip.header.block_hash })
+        );
+
+        // Nothing was reverted and the old branch stays canonical
+        assert_eq!(hook.seen.lock().unwrap().len(), 2);
+        assert_eq!(*chain.subscribe_head().borrow(), a[2].header.block_hash);
+        assert_eq!(chain.get_block_by_height(2).await.unwrap().header.block_hash, a[2].header.block_hash);
+        assert_eq!(chain.get_block_height(&tip.header.block_hash).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_commit_publishes_tx_included() {
+        let db = Arc::new(MockDB::new());
+        let chain = Blockchain::new_with_fixed_genesis(db).await;
+        let mut included = chain.events().subscribe::<TxIncluded>();
+
+        let mut block = block_with_txs(&chain.latest_block.read().await.clone());
+        block.transactions[1].body.gas = 21_000;
+        chain.commit_block(&block).await.unwrap();
+
+        for (index, tx) in block.transactions.iter().enumerate() {
+            let event = included.recv().await.unwrap();
+            assert_eq!(event.hash, tx.body.hash);
+            assert_eq!(event.block_hash, block.header.block_hash);
+            assert_eq!(event.block_number, 1);
+            assert_eq!(event.index, index as u64);
+            assert_eq!(event.receipt.tx_hash, tx.body.hash);
+            assert_eq!(event.receipt.block_number, 1);
+            // Declared gas is not reported as used
+            assert_eq!(event.receipt.gas_used, 0);
+            assert_eq!(event.receipt.cumulative_gas_used, 0);
+        }
+        assert!(included.try_recv().is_err());
+    }
+
+    /// Hook recording what it saw of the chain, optionally failing the import
+    struct Observer {
+        head: watch::Receiver<Hash>,
+        fail: bool,
+        seen: Mutex<Vec<(&'static str, Hash)>>,
+    }
+
+    #[async_trait]
+    impl BlockImportHook for Observer {
+        async fn on_block_imported(&self, block: &Block, write: &mut ChainWrite) -> Result<()> {
+            self.seen.lock().unwrap().push(("imported", *self.head.borrow()));
+            if self.fail {
+                anyhow::bail!("injected failure");
+            }
+            let mut receipt = Receipt::new(block.transactions[0].body.hash, block.header.block_hash, 1, 0);
+            receipt.gas_used = 30_000;
+            receipt.cumulative_gas_used = 30_000;
+            receipt.status = false;
+            write.add_receipts([receipt]);
+            Ok(())
+        }
+
+        async fn on_block_committed(&self, _block: &Block) {
+            self.seen.lock().unwrap().push(("committed", *self.head.borrow()));
+        }
+
+        async fn on_import_aborted(&self, _block: &Block) {
+            self.seen.lock().unwrap().push(("aborted", *self.head.borrow()));
+        }
+    }
+
+    fn observer(chain: &Blockchain, fail: bool) -> Arc<Observer> {
+        let hook = Arc::new(Observer { head: chain.subscribe_head(), fail, seen: Mutex::new(Vec::new()) });
+        chain.add_import_hook(hook.clone());
+        hook
+    }
+
+    #[tokio::test]
+    async fn test_head_is_published_after_hooks() {
+        let db = Arc::new(MockDB::new());
+        let chain = Blockchain::new_with_fixed_genesis(db).await;
+        let genesis = chain.latest_block.read().await.header.block_hash;
+        let hook = observer(&chain, false);
+
+        let block = block_with_txs(&chain.latest_block.read().await.clone());
+        chain.commit_block(&block).await.unwrap();
+
+        assert_eq!(*hook.seen.lock().unwrap(), vec![("imported", genesis), ("committed", genesis)]);
+        assert_eq!(*chain.subscribe_head().borrow(), block.header.block_hash);
+    }
+
+    #[tokio::test]
+    async fn test_failed_hook_aborts_commit() {
+        let db = Arc::new(MockDB::new());
+        let chain = Blockchain::new_with_fixed_genesis(db).await;
+        let genesis = chain.latest_block.read().await.header.block_hash;
+        let first = observer(&chain, false);
+        let failing = observer(&chain, true);
+
+        let block = block_with_txs(&chain.latest_block.read().await.clone());
+        assert!(chain.commit_block(&block).await.is_err());
+
+        // Both hooks undo the block, the failing one first
+        assert_eq!(*failing.seen.lock().unwrap(), vec![("imported", genesis), ("aborted", genesis)]);
+        assert_eq!(*first.seen.lock().unwrap(), vec![("imported", genesis), ("aborted", genesis)]);
+        assert_eq!(stored_completely(&chain, &block).await, Some(false));
+        assert!(chain.get_block_receipts(&block.header.block_hash).await.is_none());
+        assert_eq!(*chain.subscribe_head().borrow(), genesis);
+    }
+
+    #[tokio::test]
+    async fn test_tx_included_carries_executed_receipt() {
+        let db = Arc::new(MockDB::new());
+        let chain = Blockchain::new_with_fixed_genesis(db).await;
+        observer(&chain, false);
+        let mut included = chain.events().subscribe::<TxIncluded>();
+
+        let block = block_with_txs(&chain.latest_block.read().await.clone());
+        chain.commit_block(&block).await.unwrap();
+
+        let executed = included.recv().await.unwrap();
+        assert_eq!(executed.hash, block.transactions[0].body.hash);
+        assert!(!executed.receipt.status);
+        assert_eq!(executed.receipt.gas_used, 30_000);
+        // The other transaction wasn't executed by the hook
+        let located = included.recv().await.unwrap();
+        assert_eq!(located.hash, block.transactions[1].body.hash);
+        assert_eq!(located.receipt.gas_used, 0);
+
+        let stored = chain.get_block_receipts(&block.header.block_hash).await.unwrap();
+        assert_eq!(stored, vec![executed.receipt]);
+    }
+
+    #[tokio::test]
+    async fn test_archive_keeps_bodies() {
+        let db = Arc::new(MockDB::new());
+        let chain = Blockchain::new_with_fixed_genesis(db).await;
+
+        let genesis = chain.latest_block.read().await.clone();
+        for block in blocks_with_txs(&genesis, 8) {
+            chain.commit_block(&block).await.unwrap();
+        }
+
+        assert_eq!(chain.pruned_below(), 0);
+        let stored = chain.get_block_by_height(1).await.unwrap();
+        assert_eq!(stored.transactions.len(), 2);
+    }
+
+    /// Whether `block` and all of its entries are stored, or none are
+    async fn stored_completely(chain: &Blockchain, block: &Block) -> Option<bool> {
+        let mut present = vec![
+            chain.get_block_by_hash(&block.header.block_hash).await.is_some(),
+            chain.get_block_by_height(block.header.height).await.is_some(),
+            chain.latest_block.read().await.header.block_hash == block.header.block_hash,
+        ];
+        for tx in &block.transactions {
+            present.push(chain.get_transaction_by_hash(&tx.body.hash).await.is_some());
+            present.push(chain.get_transaction_location(&tx.body.hash).await.is_some());
+        }
+
+        if present.iter().all(|p| *p) {
+            Some(true)
+        } else if present.iter().all(|p| !*p) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commit_is_atomic() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(norn_storage::SledDB::new(temp_dir.path()).unwrap());
+        let chain = Blockchain::new_with_fixed_genesis(db.clone()).await;
+        let block = block_with_txs(&chain.latest_block.read().await.clone());
+
+        // Crash after the block body and one index entry are staged
+        db.inject_commit_fault(Some(2));
+        assert!(chain.commit_block(&block).await.is_err());
+        db.inject_commit_fault(None);
+
+        let restarted = Blockchain::new_with_fixed_genesis(db.clone()).await;
+        assert_eq!(stored_completely(&restarted, &block).await, Some(false));
+
+        restarted.commit_block(&block).await.unwrap();
+        let restarted = Blockchain::new_with_fixed_genesis(db).await;
+        assert_eq!(stored_completely(&restarted, &block).await, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_block_with_missing_parent_refused() {
+        let db = Arc::new(MockDB::new());
+        let chain = Blockchain::new_with_fixed_genesis(db).await;
+        let hook = observer(&chain, false);
+        let genesis = chain.latest_block.read().await.clone();
+        let blocks = blocks_with_txs(&genesis, 3);
+        chain.commit_block(&blocks[0]).await.unwrap();
+
+        // The third block arrives before its parent
+        assert!(chain.commit_block(&blocks[2]).await.is_err());
+        assert_eq!(stored_completely(&chain, &blocks[2]).await, Some(false));
+        assert_eq!(*chain.subscribe_head().borrow(), blocks[0].header.block_hash);
+        assert_eq!(hook.seen.lock().unwrap().len(), 2);
+
+        // Once the parent is in, it's imported on top of it
+        chain.commit_block(&blocks[1]).await.unwrap();
+        chain.commit_block(&blocks[2]).await.unwrap();
+        for block in &blocks {
+            let height = block.header.height;
+            assert_eq!(chain.get_block_by_height(height).await.unwrap().header.block_hash, block.header.block_hash);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Consensus observability
+//!
+//! [`ConsensusStatus`] is a snapshot of the PoVF engine, served by the status
+//! RPC and recorded into the gauges below.
+
+use lazy_static::lazy_static;
+use norn_common::types::Address;
+use prometheus::{Gauge, GaugeVec, Opts};
+use serde::{Deserialize, Serialize};
+
+// Metrics (registered by the node's metrics collector)
+lazy_static! {
+    pub static ref CONSENSUS_ROUND: Gauge = Gauge::new(
+        "norn_consensus_round",
+        "Current consensus round"
+    ).unwrap();
+
+    pub static ref CONSENSUS_PROPOSER: GaugeVec = GaugeVec::new(
+        Opts::new("norn_consensus_proposer", "Proposer of the current round (1 for the current proposer)"),
+        &["address"]
+    ).unwrap();
+
+    pub static ref CONSENSUS_TOTAL_STAKE: Gauge = Gauge::new(
+        "norn_consensus_total_stake",
+        "Total stake of the validator set"
+    ).unwrap();
+
+    pub static ref CONSENSUS_VALIDATORS: Gauge = Gauge::new(
+        "norn_consensus_validators",
+        "Number of validators"
+    ).unwrap();
+
+    pub static ref CONSENSUS_SEED_AGE_SECONDS: Gauge = Gauge::new(
+        "norn_consensus_vdf_seed_age_seconds",
+        "Seconds since the VDF seed of the current round took effect"
+    ).unwrap();
+}
+
+/// Snapshot of the consensus state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusStatus {
+    /// Current round
+    pub round: u64,
+    /// Proposer of the current round, once a proposal was accepted
+    pub proposer: Option<Address>,
+    /// Total stake of the validator set
+    pub total_stake: u64,
+    /// Number of validators
+    pub validator_count: usize,
+    /// Seconds since the round seed took effect; the seed changes every round
+    pub seed_age_secs: u64,
+}
+
+impl ConsensusStatus {
+    /// Set the consensus gauges to this snapshot
+    pub fn record(&self) {
+        CONSENSUS_ROUND.set(self.round as f64);
+        CONSENSUS_PROPOSER.reset();
+        if let Some(proposer) = &self.proposer {
+            CONSENSUS_PROPOSER
+                .with_label_values(&[&format!("0x{}", hex::encode(proposer.0))])
+                .set(1.0);
+        }
+        CONSENSUS_TOTAL_STAKE.set(self.total_stake as f64);
+        CONSENSUS_VALIDATORS.set(self.validator_count as f64);
+        CONSENSUS_SEED_AGE_SECONDS.set(self.seed_age_secs as f64);
+    }
+}
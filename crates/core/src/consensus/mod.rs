@@ -1,2 +1,4 @@
+pub mod metrics;
 pub mod povf;
-pub mod producer;
\ No newline at end of file
+pub mod producer;
+pub mod verifier;
\ No newline at end of file
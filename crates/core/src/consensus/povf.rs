@@ -1,4 +1,5 @@
-use norn_common::types::{Block, BlockHeader, Hash, GeneralParams, PublicKey, Transaction};
+use crate::consensus::metrics::ConsensusStatus;
+use norn_common::types::{Address, Block, BlockHeader, Hash, GeneralParams, PublicKey, Transaction};
 use norn_crypto::vrf::{VRFKeyPair, VRFSelector, VRFOutput};
 use norn_crypto::vdf::{VDFCalculator, VDFManager};
 use norn_crypto::transaction::verify_transaction;
@@ -7,7 +8,7 @@ use serde::{Serialize, Deserialize};
 use sha2::Digest;
 use std::sync::Arc;
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
@@ -105,6 +106,12 @@ pub struct PoVFEngine {
     /// 当前轮次
     current_round: Arc<RwLock<u64>>,
 
+    /// 当前轮次（及其种子）开始的时间
+    round_started: Arc<RwLock<Instant>>,
+
+    /// 当前轮次已接受提议的提议者
+    current_proposer: Arc<RwLock<Option<PublicKey>>>,
+
     /// 当前状态
     current_state: Arc<RwLock<ConsensusState>>,
 
@@ -117,8 +124,8 @@ pub struct PoVFEngine {
     /// VDF 管理器
     vdf_manager: Arc<VDFManager>,
 
-    /// VRF 选择器
-    vrf_selector: Arc<VRFSelector>,
+    /// VRF 选择器，随轮次推进切换纪元快照
+    vrf_selector: Arc<RwLock<VRFSelector>>,
 
     /// 验证者集合
     validators: Arc<RwLock<Vec<PublicKey>>>,
@@ -194,7 +201,9 @@ impl PoVFEngine {
             vrf_selector.add_validator(address, *stake, vrf_key_pair.clone());
         }
         
-        let vrf_selector = Arc::new(vrf_selector);
+        // 冻结初始轮次所属纪元的验证者集合
+        vrf_selector.advance_to_round(initial_round);
+        let vrf_selector = Arc::new(RwLock::new(vrf_selector));
         
         // 准备验证者列表和权益权重
         let validators: Vec<PublicKey> = config.validator_stakes.keys().cloned().collect();
@@ -203,6 +212,8 @@ impl PoVFEngine {
         Self {
             config,
             current_round: Arc::new(RwLock::new(initial_round)),
+            round_started: Arc::new(RwLock::new(Instant::now())),
+            current_proposer: Arc::new(RwLock::new(None)),
             current_state: Arc::new(RwLock::new(ConsensusState::WaitingForProposal)),
             current_proposal: Arc::new(RwLock::new(None)),
             votes: Arc::new(RwLock::new(HashMap::new())),
@@ -282,6 +293,7 @@ impl PoVFEngine {
             let mut current_proposal = self.current_proposal.write().await;
             *current_proposal = Some(proposal);
         }
+        *self.current_proposer.write().await = Some(proposer);
 
         // 6. 转换到 VDF 计算状态
         {
@@ -444,7 +456,7 @@ impl PoVFEngine {
         let seed = self.get_round_seed(round).await?;
 
         // 4. 使用 VRFSelector 验证选择
-        let is_valid = self.vrf_selector.verify_selection(
+        let is_valid = self.vrf_selector.read().await.verify_selection(
             proposer_address,
             &seed.0,
             round,
@@ -721,6 +733,11 @@ impl PoVFEngine {
     async fn next_round(&self) {
         let mut current_round = self.current_round.write().await;
         *current_round += 1;
+        *self.round_started.write().await = Instant::now();
+        *self.current_proposer.write().await = None;
+
+        // 跨越纪元边界时为新纪元创建验证者快照
+        self.vrf_selector.write().await.advance_to_round(*current_round);
         
         // 清理投票
         {
@@ -746,6 +763,24 @@ impl PoVFEngine {
         (state, round, proposal)
     }
 
+    /// 获取共识状态快照，验证者与权益取自 VRF 选择器
+    pub async fn status(&self) -> ConsensusStatus {
+        let vrf_selector = self.vrf_selector.read().await;
+        let proposer = self.current_proposer.read().await.map(|public_key| {
+            let mut address = [0u8; 20];
+            address.copy_from_slice(&public_key.0[..20]);
+            Address(address)
+        });
+
+        ConsensusStatus {
+            round: *self.current_round.read().await,
+            proposer,
+            total_stake: vrf_selector.total_stake(),
+            validator_count: vrf_selector.validator_count(),
+            seed_age_secs: self.round_started.read().await.elapsed().as_secs(),
+        }
+    }
+
     /// 获取已确认的区块
     pub async fn get_finalized_block(&self, hash: &Hash) -> Option<Block> {
         let finalized_blocks = self.finalized_blocks.read().await;
@@ -805,6 +840,7 @@ mod tests {
                 params: vec![],
                 gas_limit: 1000000,
                 base_fee: 1_000_000_000,
+                logs_bloom: Vec::new(),
             },
             transactions: vec![],
         };
@@ -838,4 +874,79 @@ mod tests {
         assert_eq!(round, 1);
         assert!(matches!(state, ConsensusState::WaitingForProposal));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_rounds_advance_vrf_epochs() {
+        use norn_crypto::vrf::DEFAULT_EPOCH_LENGTH;
+
+        let mut validator = PublicKey::default();
+        validator.0[0] = 1;
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&validator.0[..20]);
+
+        let mut config = PoVFConfig::default();
+        config.validator_stakes.insert(validator, 100);
+        config.min_vdf_iterations = 10;
+        let key_pair = VRFKeyPair::generate();
+        let round = DEFAULT_EPOCH_LENGTH - 1;
+        let engine = PoVFEngine::new(config, Arc::new(SimpleVDF::new()), key_pair.clone(), round, Some(validator));
+        assert_eq!(engine.vrf_selector.read().await.current_epoch(), Some(0));
+
+        let select = |key_pair: &VRFKeyPair, address: &[u8; 20], round: u64, seed: &Hash| {
+            let message = VRFSelector::create_selection_message(&seed.0, round, address);
+            VRFCalculator::calculate(key_pair, &message).unwrap()
+        };
+
+        // A validator registered mid-epoch is not selectable until the next epoch
+        let joiner = [2u8; 20];
+        let joiner_key = VRFKeyPair::generate();
+        engine.vrf_selector.write().await.add_validator(joiner, 50, joiner_key.clone());
+        let seed = engine.get_round_seed(round).await.unwrap();
+        let joiner_output = select(&joiner_key, &joiner, round, &seed);
+        assert!(!engine.vrf_selector.read().await.verify_selection(joiner, &seed.0, round, &joiner_output).unwrap());
+
+        // Finalizing the last round of the epoch moves the engine into the next one
+        let mut block = Block::default();
+        block.header.height = 1;
+        block.header.block_hash = Hash([1u8; 32]);
+        block.header.gas_limit = 1_000_000;
+        let result = engine.handle_message(ConsensusMessage::BlockProposal {
+            proposer: validator,
+            block,
+            vrf_output: select(&key_pair, &address, round, &seed),
+            round,
+        }).await.unwrap();
+        assert!(result.is_finalized);
+        assert_eq!(engine.get_state().await.1, DEFAULT_EPOCH_LENGTH);
+        assert_eq!(engine.vrf_selector.read().await.current_epoch(), Some(1));
+
+        let round = DEFAULT_EPOCH_LENGTH;
+        let seed = engine.get_round_seed(round).await.unwrap();
+        let joiner_output = select(&joiner_key, &joiner, round, &seed);
+        assert!(engine.vrf_selector.read().await.verify_selection(joiner, &seed.0, round, &joiner_output).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_validator_set() {
+        use crate::consensus::metrics::CONSENSUS_VALIDATORS;
+
+        let mut config = PoVFConfig::default();
+        for (i, stake) in [100u64, 200, 300].into_iter().enumerate() {
+            let mut pub_key = PublicKey::default();
+            pub_key.0[0] = i as u8 + 1;
+            config.validator_stakes.insert(pub_key, stake);
+        }
+
+        let vdf_calculator = Arc::new(SimpleVDF::new());
+        let engine = PoVFEngine::new(config, vdf_calculator, VRFKeyPair::generate(), 7, None);
+
+        let status = engine.status().await;
+        assert_eq!(status.round, 7);
+        assert_eq!(status.validator_count, 3);
+        assert_eq!(status.total_stake, 600);
+        assert_eq!(status.proposer, None);
+
+        status.record();
+        assert_eq!(CONSENSUS_VALIDATORS.get(), 3.0);
+    }
+}
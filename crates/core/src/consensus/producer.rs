@@ -11,7 +11,9 @@ use tracing::{debug, info, warn, error};
 use norn_common::types::{Block, BlockHeader, Hash, Transaction, PublicKey, GeneralParams};
 use norn_common::build_mode;
 use anyhow::Result;
-use norn_crypto::vrf::{VRFKeyPair, VRFCalculator, VRFOutput, VRFSelector};
+use norn_crypto::vdf::{SimpleVDF, VDFCalculator};
+use norn_crypto::vrf::{VRFKeyPair, VRFCalculator, VRFOutput, VRFProof, VRFSelector};
+use curve25519_dalek::ristretto::RistrettoPoint;
 use sha2::{Sha256, Digest};
 
 use crate::blockchain::Blockchain;
@@ -20,7 +22,8 @@ use crate::merkle::build_merkle_tree;
 use crate::consensus::povf::{PoVFConfig, PoVFEngine, ConsensusMessage, BlockProposal, ConsensusResult};
 use crate::state::AccountStateManager;
 use crate::state::merkle::StateRootCalculator;
-use crate::evm::{EIP1559FeeCalculator, EIP1559Config};
+use crate::evm::{Bloom, EIP1559FeeCalculator};
+use crate::execution::TransactionRouter;
 
 
 /// Block producer configuration
@@ -34,6 +37,8 @@ pub struct BlockProducerConfig {
     pub max_gas_per_block: i64,
     /// Whether this node is a validator
     pub is_validator: bool,
+    /// Minimum effective priority fee per gas a transaction must pay to be included
+    pub min_tip: u64,
 }
 
 impl Default for BlockProducerConfig {
@@ -43,6 +48,7 @@ impl Default for BlockProducerConfig {
             max_txs_per_block: 1000,
             max_gas_per_block: 10_000_000,
             is_validator: false,
+            min_tip: 0,
         }
     }
 }
@@ -73,6 +79,10 @@ pub struct BlockProducer {
     last_produced: Arc<RwLock<Option<Instant>>>,
     consensus_engine: Option<Arc<PoVFEngine>>,
     fee_calculator: EIP1559FeeCalculator,
+    /// Executes produced blocks to fill in their logs bloom
+    router: Option<Arc<TransactionRouter>>,
+    /// Proves the VDF over the parent hash of produced blocks
+    vdf_calculator: Arc<dyn VDFCalculator>,
 }
 
 impl BlockProducer {
@@ -97,9 +107,28 @@ impl BlockProducer {
             last_produced: Arc::new(RwLock::new(None)),
             consensus_engine,
             fee_calculator,
+            router: None,
+            vdf_calculator: Arc::new(SimpleVDF::new()),
         }
     }
 
+    /// Execute produced blocks with `router`, the one importing them, so
+    /// their headers carry the logs bloom of their receipts
+    ///
+    /// Without a router blocks are produced with an empty bloom, which the
+    /// import only accepts for blocks without logs.
+    pub fn with_router(mut self, router: Arc<TransactionRouter>) -> Self {
+        self.router = Some(router);
+        self
+    }
+
+    /// Prove the VDF of produced blocks with `vdf_calculator` instead of a
+    /// [`SimpleVDF`]
+    pub fn with_vdf_calculator(mut self, vdf_calculator: Arc<dyn VDFCalculator>) -> Self {
+        self.vdf_calculator = vdf_calculator;
+        self
+    }
+
     /// Get current producer state
     pub async fn get_state(&self) -> ProducerState {
         *self.state.read().await
@@ -157,9 +186,6 @@ impl BlockProducer {
             *state = ProducerState::Preparing;
         }
 
-        // Get transactions from pool
-        let transactions = self.select_transactions().await;
-        
         // Get latest block
         let latest = self.blockchain.latest_block.read().await;
         let prev_hash = latest.header.block_hash;
@@ -167,6 +193,9 @@ impl BlockProducer {
         let parent_base_fee = latest.header.base_fee;
         drop(latest);
 
+        // Get transactions from pool
+        let transactions = self.select_transactions(parent_base_fee).await;
+
         // Calculate merkle root from transactions
         let merkle_root = build_merkle_tree(&transactions);
 
@@ -187,30 +216,26 @@ impl BlockProducer {
         );
 
         // Get VRF output for this round
-        let pub_key = self.vrf_to_public_key();
-        let mut address = [0u8; 20];
-        address.copy_from_slice(&pub_key.0[..20]);
-        
-        // Calculate seed (must match PoVFEngine logic)
-        let genesis_hash = norn_common::genesis::GENESIS_BLOCK_HASH;
-        let mut hasher = Sha256::new();
-        hasher.update(genesis_hash.0);
-        hasher.update(&(new_height as u64).to_le_bytes());
-        let seed = hasher.finalize();
-
-        let message = VRFSelector::create_selection_message(
-            &seed,
-            new_height as u64,
-            &address
-        );
+        let message = Self::vrf_selection_message(&self.vrf_to_public_key(), new_height as u64);
         let vrf_output = VRFCalculator::calculate(&self.vrf_key_pair, &message)?;
 
         // Create block params
-        let params = self.create_block_params(&vrf_output, new_height as u64);
+        let mut params = self.create_block_params(&vrf_output, new_height as u64);
+
+        // Prove the VDF over the parent hash
+        {
+            let mut state = self.state.write().await;
+            *state = ProducerState::ComputingVdf;
+        }
+        params.s = self.vdf_calculator
+            .compute_vdf(&prev_hash, &params)
+            .await
+            .map_err(|e| anyhow::anyhow!("VDF computation failed: {}", e))?
+            .proof;
         let params_bytes = norn_common::utils::codec::serialize(&params)?;
 
         // Calculate state root
-        let state_root_calculator = StateRootCalculator::new(false); // Use SHA-256 for native
+        let state_root_calculator = StateRootCalculator::for_manager(&self.state_manager);
         let state_root = state_root_calculator
             .calculate_from_manager(&self.state_manager)
             .await
@@ -230,6 +255,7 @@ impl BlockProducer {
             params: params_bytes,
             gas_limit: self.config.max_gas_per_block,
             base_fee,
+            logs_bloom: Vec::new(),
         };
 
         // Create block
@@ -238,6 +264,18 @@ impl BlockProducer {
             transactions,
         };
 
+        // The bloom covers the logs of the block's receipts, so run it on
+        // the parent state while no other block can be imported
+        if let Some(router) = &self.router {
+            let latest = self.blockchain.latest_block.read().await;
+            if latest.header.block_hash != prev_hash {
+                anyhow::bail!("Chain head moved while producing block {}", new_height);
+            }
+            let receipts = router.dry_run_block(&block).await.map_err(anyhow::Error::msg)?;
+            drop(latest);
+            block.header.logs_bloom = Bloom::from_receipts(&receipts).as_bytes().to_vec();
+        }
+
         // Calculate block hash
         block.header.block_hash = self.calculate_block_hash(&block);
 
@@ -257,13 +295,39 @@ impl BlockProducer {
     }
 
     /// Select transactions for the block
-    async fn select_transactions(&self) -> Vec<Transaction> {
-        self.tx_pool.package(&*self.blockchain).await
+    ///
+    /// Transactions tipping less than `min_tip` over `base_fee` are left in
+    /// the pool.
+    async fn select_transactions(&self, base_fee: u64) -> Vec<Transaction> {
+        let (selected, underpriced): (Vec<_>, Vec<_>) = self.tx_pool.package(&*self.blockchain).await
+            .into_iter()
+            .partition(|tx| self.effective_tip(tx, base_fee) >= self.config.min_tip);
+
+        if !underpriced.is_empty() {
+            debug!("Skipping {} transactions below the minimum tip of {}", underpriced.len(), self.config.min_tip);
+            for tx in underpriced {
+                self.tx_pool.add(tx);
+            }
+        }
+
+        selected
             .into_iter()
             .take(self.config.max_txs_per_block)
             .collect()
     }
 
+    /// Priority fee per gas `tx` pays on top of `base_fee`
+    fn effective_tip(&self, tx: &Transaction, base_fee: u64) -> u64 {
+        self.fee_calculator
+            .calculate_effective_gas_price(
+                base_fee,
+                tx.body.max_fee_per_gas,
+                tx.body.max_priority_fee_per_gas,
+                tx.body.gas_price,
+            )
+            .saturating_sub(base_fee)
+    }
+
     /// Create block params including VRF/VDF data
     fn create_block_params(&self, vrf_output: &VRFOutput, height: u64) -> GeneralParams {
         // Calculate base VDF iterations
@@ -273,48 +337,90 @@ impl BlockProducer {
         GeneralParams {
             result: vrf_output.output.to_vec(),
             random_number: self.vrf_to_public_key(),
-            s: vec![], // Placeholder for VDF proof
+            s: vec![], // VDF proof, set once computed
             t: iterations.to_le_bytes().to_vec(),
             proof: vrf_output.proof.to_bytes().to_vec(),
         }
     }
 
+    /// VRF message signed by the proposer of a block at `height`
+    pub fn vrf_selection_message(public_key: &PublicKey, height: u64) -> Vec<u8> {
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&public_key.0[..20]);
+
+        // Calculate seed (must match PoVFEngine logic)
+        let genesis_hash = norn_common::genesis::GENESIS_BLOCK_HASH;
+        let mut hasher = Sha256::new();
+        hasher.update(genesis_hash.0);
+        hasher.update(height.to_le_bytes());
+        let seed = hasher.finalize();
+
+        VRFSelector::create_selection_message(&seed, height, &address)
+    }
+
+    /// Extract the VRF public key, message and output carried by a block header
+    ///
+    /// Returns `Ok(None)` for headers without params (e.g. genesis).
+    pub fn vrf_claim(header: &BlockHeader) -> Result<Option<(RistrettoPoint, Vec<u8>, VRFOutput)>> {
+        if header.params.is_empty() {
+            return Ok(None);
+        }
+
+        let params: GeneralParams = norn_common::utils::codec::deserialize(&header.params)?;
+        let output: [u8; 32] = params
+            .result
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Invalid VRF output length"))?;
+        let proof = VRFProof::from_bytes(&params.proof)?;
+        let public_key = VRFKeyPair::public_key_from_bytes(&header.public_key.0[..32])?;
+        let message = Self::vrf_selection_message(&header.public_key, header.height as u64);
+
+        Ok(Some((public_key, message, VRFOutput { output, proof })))
+    }
+
     /// Convert VRF key pair to PublicKey (33 bytes)
     fn vrf_to_public_key(&self) -> PublicKey {
-        let vrf_bytes = self.vrf_key_pair.public_key_bytes();
+        Self::vrf_public_key(&self.vrf_key_pair)
+    }
+
+    /// Public key of `key_pair` as carried in block headers
+    pub(crate) fn vrf_public_key(key_pair: &VRFKeyPair) -> PublicKey {
+        let vrf_bytes = key_pair.public_key_bytes();
         let mut pub_key_bytes = [0u8; 33];
         pub_key_bytes[..32].copy_from_slice(&vrf_bytes);
         pub_key_bytes[32] = 0x02; // Prefix for compressed public key format
         PublicKey(pub_key_bytes)
     }
 
-    /// Calculate block hash
+    /// Calculate block hash, as the importing node validates it
     fn calculate_block_hash(&self, block: &Block) -> Hash {
-        use sha2::{Sha256, Digest};
-        
-        let mut hasher = Sha256::new();
-        hasher.update(block.header.timestamp.to_le_bytes());
-        hasher.update(block.header.prev_block_hash.0);
-        hasher.update(block.header.merkle_root.0);
-        hasher.update(block.header.height.to_le_bytes());
-        hasher.update(block.header.public_key.0);
-        hasher.update(&block.header.params);
-        hasher.update(block.header.gas_limit.to_le_bytes());
-        
-        let result = hasher.finalize();
-        let mut hash = Hash::default();
-        hash.0.copy_from_slice(&result);
-        hash
+        crate::validation::calculate_block_hash(block)
     }
 
     /// Run the block production loop
     pub async fn run(&self) {
+        self.run_until(std::future::pending()).await
+    }
+
+    /// Run the block production loop until `shutdown` resolves
+    ///
+    /// Shutdown is only observed between rounds, so a block being produced
+    /// or committed when it fires is finished first.
+    pub async fn run_until(&self, shutdown: impl std::future::Future<Output = ()>) {
         info!("Block producer started");
         
         let mut timer = interval(Duration::from_secs(1));
+        tokio::pin!(shutdown);
         
         loop {
-            timer.tick().await;
+            tokio::select! {
+                _ = timer.tick() => {}
+                _ = &mut shutdown => {
+                    info!("Block producer stopped");
+                    return;
+                }
+            }
             
             if self.should_produce().await {
                 match self.produce_block().await {
@@ -364,7 +470,9 @@ impl BlockProducer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::consensus::verifier::{HeaderVerifier, PoVFHeaderVerifier};
     use crate::state::AccountStateManager;
+    use crate::validation::ValidationConfig;
     use norn_storage::SledDB;
 
     #[tokio::test]
@@ -402,5 +510,114 @@ mod tests {
 
         assert_eq!(block.header.height, 1);
         assert!(!block.header.block_hash.0.iter().all(|&b| b == 0));
+
+        // The header carries a verifiable VRF claim
+        let (public_key, message, output) = BlockProducer::vrf_claim(&block.header).unwrap().unwrap();
+        assert!(VRFCalculator::verify(&public_key, &message, &output).unwrap());
+
+        // ... and a VDF proof over the parent hash
+        let genesis = blockchain.get_block_by_height(0).await.unwrap();
+        PoVFHeaderVerifier::new(ValidationConfig::production_config())
+            .verify_header(&block.header, Some(&genesis.header))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_min_tip_excludes_underpriced_transactions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(SledDB::new(temp_dir.path().to_str().unwrap()).unwrap());
+        let blockchain = Blockchain::new_with_fixed_genesis(db).await;
+        let tx_pool = Arc::new(TxPool::new());
+        let state_manager = Arc::new(AccountStateManager::default());
+        let base_fee = blockchain.latest_block.read().await.header.base_fee;
+
+        let tx = |seed: u8, gas_price: Option<u64>, max_fee: Option<u64>, tip: Option<u64>| {
+            let mut tx = Transaction::default();
+            tx.body.hash = Hash([seed; 32]);
+            tx.body.gas_price = gas_price;
+            tx.body.max_fee_per_gas = max_fee;
+            tx.body.max_priority_fee_per_gas = tip;
+            tx
+        };
+        // Legacy, tipping 5 over the base fee
+        tx_pool.add(tx(1, Some(base_fee + 5), None, None));
+        // EIP-1559, tipping 10
+        tx_pool.add(tx(2, None, Some(base_fee + 100), Some(10)));
+        // Legacy, tipping 4
+        tx_pool.add(tx(3, Some(base_fee + 4), None, None));
+        // EIP-1559 with a high priority fee capped to 2 by max_fee
+        tx_pool.add(tx(4, None, Some(base_fee + 2), Some(50)));
+        // No pricing information at all
+        tx_pool.add(tx(5, None, None, None));
+
+        let config = BlockProducerConfig {
+            is_validator: true,
+            min_tip: 5,
+            ..Default::default()
+        };
+        let producer = BlockProducer::new(config, blockchain, tx_pool.clone(), VRFKeyPair::generate(), state_manager, None);
+
+        let (block, _) = producer.produce_block().await.unwrap();
+
+        let mut included: Vec<u8> = block.transactions.iter().map(|tx| tx.body.hash.0[0]).collect();
+        included.sort();
+        assert_eq!(included, vec![1, 2]);
+
+        // Underpriced transactions stay in the pool
+        for seed in [3u8, 4, 5] {
+            assert!(tx_pool.contains(&Hash([seed; 32])));
+        }
+        assert!(!tx_pool.contains(&Hash([1u8; 32])));
+    }
+
+    #[tokio::test]
+    async fn test_produced_block_carries_logs_bloom() {
+        use crate::evm::{EVMConfig, EVMExecutor};
+        use norn_common::types::{Address, TransactionType};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(SledDB::new(temp_dir.path().to_str().unwrap()).unwrap());
+        let blockchain = Blockchain::new_with_fixed_genesis(db).await;
+        let tx_pool = Arc::new(TxPool::new());
+        let state_manager = Arc::new(AccountStateManager::default());
+        let evm_executor = Arc::new(EVMExecutor::new(state_manager.clone(), EVMConfig::default()));
+        let router = Arc::new(TransactionRouter::new(Some(evm_executor.clone()), 30_000_000));
+        blockchain.add_import_hook(router.clone());
+
+        // LOG1(0, 0, 42); STOP
+        let sender = Address([2u8; 20]);
+        state_manager.update_balance(&sender, num_bigint::BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+        let code = vec![0x60, 0x2a, 0x60, 0x00, 0x60, 0x00, 0xa1, 0x00];
+        let (contract, _) = evm_executor.create_contract(sender, 0, code, 0, 100_000).await.unwrap();
+        let mut tx = Transaction::default();
+        tx.body.hash = Hash([1u8; 32]);
+        tx.body.address = sender;
+        tx.body.receiver = contract;
+        tx.body.nonce = state_manager.get_nonce(&sender).await.unwrap() as i64;
+        tx.body.gas = 100_000;
+        tx.body.tx_type = TransactionType::EVM;
+        tx.body.data = vec![0x01];
+        tx_pool.add(tx);
+
+        let config = BlockProducerConfig {
+            is_validator: true,
+            ..Default::default()
+        };
+        let producer = BlockProducer::new(config, blockchain.clone(), tx_pool, VRFKeyPair::generate(), state_manager, None)
+            .with_router(router);
+
+        let (block, _) = producer.produce_block().await.unwrap();
+        let bloom = Bloom::from_slice(&block.header.logs_bloom).unwrap();
+        assert!(bloom.might_match(Some(&contract), &[]));
+
+        // The bloom is part of the hash
+        let mut other = block.clone();
+        other.header.logs_bloom = Bloom::new().as_bytes().to_vec();
+        assert_ne!(producer.calculate_block_hash(&other), block.header.block_hash);
+
+        // The importing node agrees with it
+        blockchain.commit_block(&block).await.unwrap();
+        assert_eq!(blockchain.latest_block.read().await.header.block_hash, block.header.block_hash);
     }
 }
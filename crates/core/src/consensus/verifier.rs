@@ -0,0 +1,293 @@
+//! Block header verification
+//!
+//! Header rules are kept behind [`HeaderVerifier`] so they can be checked
+//! without the rest of the import path and swapped for other consensus
+//! variants.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use norn_common::types::{BlockHeader, GeneralParams};
+use norn_crypto::vdf::{SimpleVDF, VDFCalculator};
+use norn_crypto::vrf::VRFCalculator;
+use tracing::warn;
+
+use crate::consensus::producer::BlockProducer;
+use crate::validation::{ValidationConfig, ValidationError};
+
+/// Consensus rules a block header must satisfy
+#[async_trait]
+pub trait HeaderVerifier: Send + Sync {
+    /// Verify `header` against its parent; `parent` is `None` only for genesis
+    async fn verify_header(
+        &self,
+        header: &BlockHeader,
+        parent: Option<&BlockHeader>,
+    ) -> Result<(), ValidationError>;
+}
+
+/// Header rules of the PoVF consensus
+///
+/// The header params carry the proposer's VRF output and proof in `result`
+/// and `proof`, the VDF iteration count in `t` and the VDF proof over the
+/// parent hash in `s`.
+#[derive(Debug, Clone)]
+pub struct PoVFHeaderVerifier {
+    config: ValidationConfig,
+    vdf_calculator: Arc<dyn VDFCalculator>,
+}
+
+impl PoVFHeaderVerifier {
+    /// Create a verifier enforcing the limits in `config`
+    pub fn new(config: ValidationConfig) -> Self {
+        Self {
+            config,
+            vdf_calculator: Arc::new(SimpleVDF::new()),
+        }
+    }
+
+    /// Recompute VDF proofs with `vdf_calculator` instead of a [`SimpleVDF`]
+    pub fn with_vdf_calculator(mut self, vdf_calculator: Arc<dyn VDFCalculator>) -> Self {
+        self.vdf_calculator = vdf_calculator;
+        self
+    }
+
+    fn verify_linkage(
+        &self,
+        header: &BlockHeader,
+        parent: Option<&BlockHeader>,
+    ) -> Result<(), ValidationError> {
+        if header.height < 0 {
+            return Err(ValidationError::InvalidHeight);
+        }
+
+        match parent {
+            Some(parent) => {
+                if header.height != parent.height + 1 {
+                    warn!("Invalid height: expected {}, got {}", parent.height + 1, header.height);
+                    return Err(ValidationError::InvalidHeight);
+                }
+                if header.prev_block_hash != parent.block_hash {
+                    warn!(
+                        "Invalid previous hash: expected {}, got {}",
+                        hex::encode(parent.block_hash.0),
+                        hex::encode(header.prev_block_hash.0)
+                    );
+                    return Err(ValidationError::InvalidPreviousHash);
+                }
+            }
+            // No parent but not genesis
+            None if header.height > 0 => return Err(ValidationError::InvalidPreviousHash),
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    fn verify_timestamp(
+        &self,
+        header: &BlockHeader,
+        parent: Option<&BlockHeader>,
+    ) -> Result<(), ValidationError> {
+        let now = Utc::now().timestamp();
+        if header.timestamp > now + self.config.max_future_drift_secs {
+            warn!(
+                "Block {} timestamp {} is more than {}s ahead of local time",
+                header.height, header.timestamp, self.config.max_future_drift_secs
+            );
+            return Err(ValidationError::InvalidTimestamp);
+        }
+
+        if let Some(parent) = parent {
+            // Timestamps must strictly increase, whatever the minimum interval
+            if header.timestamp <= parent.timestamp
+                || header.timestamp < parent.timestamp + self.config.min_block_interval
+            {
+                warn!(
+                    "Block {} timestamp {} is not after parent timestamp {}",
+                    header.height, header.timestamp, parent.timestamp
+                );
+                return Err(ValidationError::InvalidTimestamp);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Recompute the VDF over the parent hash and compare the proofs
+    async fn verify_vdf(&self, header: &BlockHeader, params: &GeneralParams) -> Result<(), ValidationError> {
+        if params.s.is_empty() {
+            warn!("Block {} has empty VDF proof", header.height);
+            return Err(ValidationError::InvalidProof("Empty VDF proof".to_string()));
+        }
+
+        let expected = self.vdf_calculator
+            .compute_vdf(&header.prev_block_hash, params)
+            .await
+            .map_err(|e| ValidationError::InvalidProof(format!("VDF computation failed: {}", e)))?;
+
+        if expected.proof != params.s {
+            warn!("VDF verification failed for block {}", header.height);
+            return Err(ValidationError::InvalidVDF);
+        }
+
+        Ok(())
+    }
+
+    fn verify_vrf(&self, header: &BlockHeader) -> Result<(), ValidationError> {
+        let Some((public_key, message, output)) = BlockProducer::vrf_claim(header)
+            .map_err(|e| ValidationError::InvalidProof(format!("Malformed VRF params: {}", e)))?
+        else {
+            return Ok(());
+        };
+
+        if !VRFCalculator::verify(&public_key, &message, &output).unwrap_or(false) {
+            warn!("VRF verification failed for block {}", header.height);
+            return Err(ValidationError::InvalidVRF);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HeaderVerifier for PoVFHeaderVerifier {
+    async fn verify_header(
+        &self,
+        header: &BlockHeader,
+        parent: Option<&BlockHeader>,
+    ) -> Result<(), ValidationError> {
+        self.verify_linkage(header, parent)?;
+        self.verify_timestamp(header, parent)?;
+
+        if header.gas_limit > self.config.max_gas_limit {
+            return Err(ValidationError::GasLimitExceeded);
+        }
+
+        // Genesis carries no consensus proofs; every other header must
+        if header.height == 0 || !(self.config.verify_vrf || self.config.verify_vdf) {
+            return Ok(());
+        }
+        if header.params.is_empty() {
+            warn!("Block {} has no consensus params", header.height);
+            return Err(ValidationError::InvalidProof("Missing block params".to_string()));
+        }
+
+        if self.config.verify_vrf {
+            self.verify_vrf(header)?;
+        }
+
+        if self.config.verify_vdf {
+            let params: GeneralParams = norn_common::utils::codec::deserialize(&header.params)
+                .map_err(|e| ValidationError::InvalidProof(format!("Failed to deserialize block params: {}", e)))?;
+            self.verify_vdf(header, &params).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use norn_common::types::Hash;
+    use norn_crypto::vrf::VRFKeyPair;
+
+    async fn signed_header(parent: &BlockHeader, key_pair: &VRFKeyPair) -> BlockHeader {
+        let public_key = BlockProducer::vrf_public_key(key_pair);
+
+        let height = parent.height + 1;
+        let message = BlockProducer::vrf_selection_message(&public_key, height as u64);
+        let vrf = VRFCalculator::calculate(key_pair, &message).unwrap();
+        let mut params = GeneralParams {
+            result: vrf.output.to_vec(),
+            proof: vrf.proof.to_bytes().to_vec(),
+            random_number: public_key,
+            s: vec![],
+            t: 100u64.to_le_bytes().to_vec(),
+        };
+        params.s = SimpleVDF::new().compute_vdf(&parent.block_hash, &params).await.unwrap().proof;
+
+        BlockHeader {
+            timestamp: parent.timestamp + 1,
+            prev_block_hash: parent.block_hash,
+            height,
+            public_key,
+            params: norn_common::utils::codec::serialize(&params).unwrap(),
+            gas_limit: 1_000_000,
+            ..BlockHeader::default()
+        }
+    }
+
+    fn parent_header() -> BlockHeader {
+        BlockHeader {
+            timestamp: Utc::now().timestamp() - 10,
+            block_hash: Hash([7u8; 32]),
+            height: 4,
+            ..BlockHeader::default()
+        }
+    }
+
+    fn tamper_params(header: &mut BlockHeader, f: impl FnOnce(&mut GeneralParams)) {
+        let mut params: GeneralParams = norn_common::utils::codec::deserialize(&header.params).unwrap();
+        f(&mut params);
+        header.params = norn_common::utils::codec::serialize(&params).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_valid_header_passes() {
+        let verifier = PoVFHeaderVerifier::new(ValidationConfig::production_config());
+        let parent = parent_header();
+        let header = signed_header(&parent, &VRFKeyPair::generate()).await;
+
+        verifier.verify_header(&header, Some(&parent)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tampered_fields_fail_distinctly() {
+        let verifier = PoVFHeaderVerifier::new(ValidationConfig::production_config());
+        let parent = parent_header();
+        let header = signed_header(&parent, &VRFKeyPair::generate()).await;
+
+        let mut bad_vdf = header.clone();
+        tamper_params(&mut bad_vdf, |p| {
+            let last = p.s.len() - 1;
+            p.s[last] ^= 0xff;
+        });
+        let err = verifier.verify_header(&bad_vdf, Some(&parent)).await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidVDF), "{err}");
+
+        let mut bad_vrf = header.clone();
+        tamper_params(&mut bad_vrf, |p| p.result[0] ^= 0xff);
+        let err = verifier.verify_header(&bad_vrf, Some(&parent)).await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidVRF), "{err}");
+
+        let mut too_early = header.clone();
+        too_early.timestamp = parent.timestamp;
+        let err = verifier.verify_header(&too_early, Some(&parent)).await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidTimestamp), "{err}");
+
+        let mut from_future = header;
+        from_future.timestamp = Utc::now().timestamp() + 3600;
+        let err = verifier.verify_header(&from_future, Some(&parent)).await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidTimestamp), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_missing_proofs_rejected() {
+        let verifier = PoVFHeaderVerifier::new(ValidationConfig::production_config());
+        let parent = parent_header();
+        let header = signed_header(&parent, &VRFKeyPair::generate()).await;
+
+        let mut no_vdf = header.clone();
+        tamper_params(&mut no_vdf, |p| p.s.clear());
+        let err = verifier.verify_header(&no_vdf, Some(&parent)).await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidProof(_)), "{err}");
+
+        let mut no_params = header;
+        no_params.params.clear();
+        let err = verifier.verify_header(&no_params, Some(&parent)).await.unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidProof(_)), "{err}");
+    }
+}
@@ -2,12 +2,14 @@
 //! 
 //! Provides block and transaction event subscriptions for clients.
 
+use std::any::{Any, TypeId};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError};
 use tokio::sync::{broadcast, RwLock};
 use tracing::{debug, info, warn};
 
 use norn_common::types::{Block, Transaction, Hash, Address};
+use crate::evm::Receipt;
 
 /// Event types that can be subscribed to
 #[derive(Debug, Clone)]
@@ -180,6 +182,109 @@ impl Default for EventPublisher {
     }
 }
 
+/// Event that can be published on an `EventBus`
+pub trait Event: Clone + Send + Sync + 'static {}
+
+/// A block was imported into the local chain
+#[derive(Debug, Clone)]
+pub struct BlockImported {
+    pub block: Block,
+}
+
+impl Event for BlockImported {}
+
+/// A transaction was included in an imported block
+#[derive(Debug, Clone)]
+pub struct TxIncluded {
+    pub hash: Hash,
+    pub block_hash: Hash,
+    pub block_number: u64,
+    /// Position of the transaction in the block
+    pub index: u64,
+    pub receipt: Receipt,
+}
+
+impl Event for TxIncluded {}
+
+/// The canonical chain switched to a different fork
+#[derive(Debug, Clone)]
+pub struct ReorgDetected {
+    /// Number of blocks of the old chain that were reverted
+    pub depth: u64,
+    pub old_head: Hash,
+    pub new_head: Hash,
+    pub common_ancestor: Hash,
+}
+
+impl Event for ReorgDetected {}
+
+/// In-process publish/subscribe bus for internal components
+///
+/// Each event type gets its own broadcast channel, created on first use, so
+/// publishers and subscribers only share the event type. Subscribers that
+/// fall more than `capacity` events behind miss the oldest ones.
+pub struct EventBus {
+    /// `broadcast::Sender<E>` per event type, keyed by `TypeId::of::<E>()`
+    channels: std::sync::RwLock<HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+    capacity: usize,
+}
+
+impl EventBus {
+    /// Create a bus whose channels buffer up to `capacity` events each
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            channels: std::sync::RwLock::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    /// Subscribe to events of type `E`
+    pub fn subscribe<E: Event>(&self) -> broadcast::Receiver<E> {
+        self.sender::<E>().subscribe()
+    }
+
+    /// Publish an event to all current subscribers of its type
+    ///
+    /// Returns the number of subscribers the event was delivered to.
+    pub fn publish<E: Event>(&self, event: E) -> usize {
+        self.sender::<E>().send(event).unwrap_or(0)
+    }
+
+    /// Number of live subscribers for events of type `E`
+    pub fn subscriber_count<E: Event>(&self) -> usize {
+        self.sender::<E>().receiver_count()
+    }
+
+    fn sender<E: Event>(&self) -> broadcast::Sender<E> {
+        let type_id = TypeId::of::<E>();
+
+        let channels = self.channels.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(sender) = channels.get(&type_id) {
+            return downcast_sender(sender.as_ref());
+        }
+        drop(channels);
+
+        let mut channels = self.channels.write().unwrap_or_else(PoisonError::into_inner);
+        let sender = channels
+            .entry(type_id)
+            .or_insert_with(|| Box::new(broadcast::channel::<E>(self.capacity).0));
+        downcast_sender(sender.as_ref())
+    }
+}
+
+fn downcast_sender<E: Event>(sender: &(dyn Any + Send + Sync)) -> broadcast::Sender<E> {
+    sender
+        .downcast_ref::<broadcast::Sender<E>>()
+        .expect("event channel registered under a different type")
+        .clone()
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
 /// Pending transactions query interface
 pub struct PendingTxQuery {
     // Reference would be to TxPool in actual impl
@@ -246,4 +351,31 @@ mod tests {
         let event = subscriber.recv().await;
         assert!(matches!(event, Some(BlockchainEvent::NewBlock(_))));
     }
+
+    #[tokio::test]
+    async fn test_event_bus_delivers_by_type() {
+        let bus = EventBus::new(16);
+        let mut imported = bus.subscribe::<BlockImported>();
+        let mut reorgs = bus.subscribe::<ReorgDetected>();
+
+        // No subscribers for this type yet
+        assert_eq!(bus.publish(TxIncluded {
+            hash: Hash::default(),
+            block_hash: Hash::default(),
+            block_number: 1,
+            index: 0,
+            receipt: Receipt::new(Hash::default(), Hash::default(), 1, 0),
+        }), 0);
+
+        let mut block = Block::default();
+        block.header.height = 7;
+        assert_eq!(bus.publish(BlockImported { block }), 1);
+
+        let event = imported.recv().await.unwrap();
+        assert_eq!(event.block.header.height, 7);
+        assert!(reorgs.try_recv().is_err());
+
+        drop(imported);
+        assert_eq!(bus.subscriber_count::<BlockImported>(), 0);
+    }
 }
@@ -0,0 +1,158 @@
+//! Static analysis of contract bytecode
+//!
+//! A single pass over the code finds PUSH instructions whose operand runs
+//! past the end of the code and builds the bitmap of valid JUMPDEST offsets,
+//! which is kept with the stored code for execution. Jumps whose target is
+//! pushed right before them are checked against that bitmap.
+//!
+//! Both problems are legal EVM code (missing PUSH bytes read as zero, a bad
+//! jump fails at run time), so they are reported rather than rejected here.
+
+use std::fmt;
+
+const PUSH1: u8 = 0x60;
+const PUSH32: u8 = 0x7f;
+const JUMP: u8 = 0x56;
+const JUMPI: u8 = 0x57;
+const JUMPDEST: u8 = 0x5b;
+
+/// Problem found in contract bytecode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BytecodeIssue {
+    /// The PUSH at `offset` is `missing` bytes short of its operand
+    TruncatedPush { offset: usize, missing: usize },
+    /// The JUMP or JUMPI at `offset` goes to `target`, which is not a JUMPDEST
+    InvalidJumpTarget { offset: usize, target: usize },
+}
+
+impl fmt::Display for BytecodeIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BytecodeIssue::TruncatedPush { offset, missing } => {
+                write!(f, "PUSH at offset {} is missing {} operand bytes", offset, missing)
+            }
+            BytecodeIssue::InvalidJumpTarget { offset, target } => {
+                write!(f, "jump at offset {} targets {}, which is not a JUMPDEST", offset, target)
+            }
+        }
+    }
+}
+
+/// Valid jump destinations and problems of a piece of bytecode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytecodeAnalysis {
+    /// Bit `i` is set if offset `i` is a JUMPDEST instruction
+    jumpdests: Vec<u8>,
+    issues: Vec<BytecodeIssue>,
+}
+
+impl BytecodeAnalysis {
+    /// Analyze `code`
+    pub fn analyze(code: &[u8]) -> Self {
+        let mut jumpdests = vec![0u8; code.len().div_ceil(8)];
+        let mut issues = Vec::new();
+        // Jumps to a constant target, checked once all JUMPDESTs are known
+        let mut static_jumps = Vec::new();
+        // Target pushed by the previous instruction, if it fits an offset
+        let mut pushed: Option<usize> = None;
+
+        let mut pc = 0;
+        while pc < code.len() {
+            let opcode = code[pc];
+            match opcode {
+                PUSH1..=PUSH32 => {
+                    let size = (opcode - PUSH1 + 1) as usize;
+                    let operand = &code[pc + 1..code.len().min(pc + 1 + size)];
+                    if operand.len() < size {
+                        issues.push(BytecodeIssue::TruncatedPush { offset: pc, missing: size - operand.len() });
+                        pushed = None;
+                    } else {
+                        pushed = offset_value(operand);
+                    }
+                    pc += 1 + size;
+                    continue;
+                }
+                JUMP | JUMPI => {
+                    if let Some(target) = pushed {
+                        static_jumps.push((pc, target));
+                    }
+                }
+                JUMPDEST => jumpdests[pc / 8] |= 1 << (pc % 8),
+                _ => {}
+            }
+            pushed = None;
+            pc += 1;
+        }
+
+        let mut analysis = Self { jumpdests, issues };
+        for (offset, target) in static_jumps {
+            if !analysis.is_jumpdest(target) {
+                analysis.issues.push(BytecodeIssue::InvalidJumpTarget { offset, target });
+            }
+        }
+        analysis
+    }
+
+    /// Whether `pc` is a valid jump destination
+    pub fn is_jumpdest(&self, pc: usize) -> bool {
+        self.jumpdests
+            .get(pc / 8)
+            .is_some_and(|byte| byte & (1 << (pc % 8)) != 0)
+    }
+
+    /// Problems found, in code order for each kind
+    pub fn issues(&self) -> &[BytecodeIssue] {
+        &self.issues
+    }
+
+    /// Whether no problems were found
+    pub fn is_well_formed(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Big-endian `operand` as an offset, `None` if it can't be one
+fn offset_value(operand: &[u8]) -> Option<usize> {
+    let significant = &operand[operand.iter().take_while(|b| **b == 0).count()..];
+    if significant.len() > std::mem::size_of::<usize>() {
+        return None;
+    }
+    Some(significant.iter().fold(0usize, |value, byte| (value << 8) | *byte as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_well_formed_code() {
+        // PUSH1 4, JUMP, INVALID, JUMPDEST, PUSH2 0x5b5b, STOP
+        let code = [0x60, 0x04, 0x56, 0xfe, 0x5b, 0x61, 0x5b, 0x5b, 0x00];
+        let analysis = BytecodeAnalysis::analyze(&code);
+
+        assert!(analysis.is_well_formed(), "{:?}", analysis.issues());
+        assert!(analysis.is_jumpdest(4));
+        // 0x5b bytes inside PUSH data are not jump destinations
+        assert!(!analysis.is_jumpdest(6));
+        assert!(!analysis.is_jumpdest(7));
+        assert!(!analysis.is_jumpdest(100));
+    }
+
+    #[test]
+    fn test_truncated_push_flagged() {
+        // PUSH1 1, PUSH3 with only one operand byte
+        let analysis = BytecodeAnalysis::analyze(&[0x60, 0x01, 0x62, 0xff]);
+        assert_eq!(analysis.issues(), &[BytecodeIssue::TruncatedPush { offset: 2, missing: 2 }]);
+    }
+
+    #[test]
+    fn test_invalid_jump_target_flagged() {
+        // PUSH1 3, JUMPI, STOP (offset 3 is not a JUMPDEST)
+        let analysis = BytecodeAnalysis::analyze(&[0x60, 0x03, 0x57, 0x00]);
+        assert_eq!(analysis.issues(), &[BytecodeIssue::InvalidJumpTarget { offset: 2, target: 3 }]);
+
+        // Targets computed at run time can't be checked
+        let analysis = BytecodeAnalysis::analyze(&[0x60, 0x03, 0x01, 0x56]);
+        assert!(analysis.is_well_formed());
+    }
+}
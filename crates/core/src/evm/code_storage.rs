@@ -1,14 +1,20 @@
 //! Contract code storage module
 //!
-//! Manages storage and retrieval of smart contract bytecode.
-
-use crate::evm::{EVMError, EVMResult};
+//! Manages storage and retrieval of smart contract bytecode. Code is stored
+//! once per code hash and shared by every address deployed with it; a blob
+//! is freed when its last address is unbound. The analysed form revm
+//! executes is cached per code hash as well, so hot contracts are analysed
+//! once rather than on every call. A fork shares everything stored before
+//! it and keeps only its own changes.
+
+use crate::evm::{BytecodeAnalysis, EVMResult};
 use norn_common::types::{Address, Hash};
-use std::collections::HashMap;
+use revm::primitives::{Bytecode, Bytes};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
-use sha2::{Sha256, Digest};
 
 /// Contract code storage
 pub struct CodeStorage {
@@ -20,6 +26,21 @@ pub struct CodeStorage {
 
     /// Code hash to addresses mapping (one code can be deployed to multiple addresses)
     code_to_addresses: Arc<RwLock<HashMap<Hash, Vec<Address>>>>,
+
+    /// Analysis of each stored code, kept for execution
+    analyses: Arc<RwLock<HashMap<Hash, Arc<BytecodeAnalysis>>>>,
+
+    /// Jump-table analysed bytecode handed to revm, filled on first execution
+    revm_code: Arc<RwLock<HashMap<Hash, Bytecode>>>,
+
+    /// Number of times code was analysed for revm
+    revm_analyses: Arc<AtomicU64>,
+
+    /// Storage a fork reads through to for code and bindings it doesn't hold
+    base: std::sync::RwLock<Option<Arc<CodeStorage>>>,
+
+    /// Addresses of the base a fork unbound
+    unbound: Arc<RwLock<HashSet<Address>>>,
 }
 
 impl CodeStorage {
@@ -29,28 +50,211 @@ impl CodeStorage {
             codes: Arc::new(RwLock::new(HashMap::new())),
             address_to_code: Arc::new(RwLock::new(HashMap::new())),
             code_to_addresses: Arc::new(RwLock::new(HashMap::new())),
+            analyses: Arc::new(RwLock::new(HashMap::new())),
+            revm_code: Arc::new(RwLock::new(HashMap::new())),
+            revm_analyses: Arc::new(AtomicU64::new(0)),
+            base: std::sync::RwLock::new(None),
+            unbound: Arc::new(RwLock::new(HashSet::new())),
+        }
+    }
+
+    /// Create a copy-on-write fork of the stored code and address bindings
+    ///
+    /// Nothing is copied: the fork reads through to this storage for what it
+    /// doesn't hold itself, and code stored or bindings changed through the
+    /// fork don't affect this storage. Reference and code counts of a fork
+    /// cover only its own code.
+    pub fn fork(self: &Arc<Self>) -> Self {
+        Self {
+            base: std::sync::RwLock::new(Some(Arc::clone(self))),
+            revm_analyses: Arc::clone(&self.revm_analyses),
+            ..Self::new()
+        }
+    }
+
+    fn base(&self) -> Option<Arc<CodeStorage>> {
+        self.base.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Copy what a fork reads from its base into the fork and drop the base
+    ///
+    /// The fork then holds its code and bindings on its own, so the base can
+    /// be freed. Does nothing for storage that isn't a fork.
+    pub async fn detach(&self) -> EVMResult<()> {
+        let Some(base) = self.base() else {
+            return Ok(());
+        };
+        let inherited = Box::pin(base.bindings()).await?;
+        {
+            let unbound = self.unbound.read().await;
+            let mut address_to_code = self.address_to_code.write().await;
+            let mut code_to_addrs = self.code_to_addresses.write().await;
+            for (address, code_hash) in inherited {
+                if unbound.contains(&address) || address_to_code.contains_key(&address) {
+                    continue;
+                }
+                address_to_code.insert(address, code_hash);
+                code_to_addrs.entry(code_hash).or_default().push(address);
+            }
+        }
+
+        // Bindings made through the fork may refer to code held by the base
+        let referenced: Vec<Hash> = self.code_to_addresses.read().await.keys().copied().collect();
+        for code_hash in referenced {
+            if self.codes.read().await.contains_key(&code_hash) {
+                continue;
+            }
+            if let Some(code) = base.get_code(&code_hash).await? {
+                let analysis = match base.get_code_analysis(&code_hash).await {
+                    Some(analysis) => analysis,
+                    None => Arc::new(BytecodeAnalysis::analyze(&code)),
+                };
+                self.codes.write().await.insert(code_hash, code);
+                self.analyses.write().await.insert(code_hash, analysis);
+            }
+        }
+
+        self.base.write().unwrap_or_else(|e| e.into_inner()).take();
+        self.unbound.write().await.clear();
+        Ok(())
+    }
+
+    /// Code hash of every bound address, including those read from the base
+    async fn bindings(&self) -> EVMResult<HashMap<Address, Hash>> {
+        let mut bindings = match self.base() {
+            Some(base) => Box::pin(base.bindings()).await?,
+            None => HashMap::new(),
+        };
+        for address in self.unbound.read().await.iter() {
+            bindings.remove(address);
         }
+        bindings.extend(self.address_to_code.read().await.iter().map(|(address, code_hash)| (*address, *code_hash)));
+        Ok(bindings)
     }
 
-    /// Store contract code
-    pub async fn store_code(&self, code_hash: Hash, code: Vec<u8>) -> EVMResult<()> {
+    /// Make the code of `addresses` match their code in `source`
+    ///
+    /// Code bound in `source` is stored here as well, so it stays available
+    /// even once `source` frees it.
+    pub async fn copy_bindings_from<'a>(
+        &self,
+        source: &CodeStorage,
+        addresses: impl IntoIterator<Item = &'a Address>,
+    ) -> EVMResult<()> {
+        for address in addresses {
+            let code_hash = source.get_code_hash(address).await?;
+            if self.get_code_hash(address).await? == code_hash {
+                continue;
+            }
+            match code_hash {
+                Some(code_hash) => match source.get_code(&code_hash).await? {
+                    Some(code) => self.deploy_code(*address, code_hash, code).await?,
+                    None => self.bind_code_to_address(*address, code_hash).await?,
+                },
+                None => {
+                    self.unbind_code_from_address(address).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Store contract code and bind it to `address`
+    ///
+    /// Code already stored under `code_hash` is kept as is, so identical
+    /// bytecode is held only once. The binding holds the reference that frees
+    /// the code once `address` is unbound.
+    pub async fn deploy_code(&self, address: Address, code_hash: Hash, code: Vec<u8>) -> EVMResult<()> {
+        self.store_code(code_hash, code).await?;
+        self.bind_code_to_address(address, code_hash).await
+    }
+
+    /// Store contract code no address references yet
+    async fn store_code(&self, code_hash: Hash, code: Vec<u8>) -> EVMResult<()> {
+        let in_base = match self.base() {
+            Some(base) => Box::pin(base.get_code(&code_hash)).await?.is_some(),
+            None => false,
+        };
         let mut codes = self.codes.write().await;
+        if in_base || codes.contains_key(&code_hash) {
+            debug!("Code already stored: hash={:?}", code_hash);
+            return Ok(());
+        }
+        debug!("Stored code: hash={:?}, size={} bytes", code_hash, code.len());
+        let analysis = BytecodeAnalysis::analyze(&code);
         codes.insert(code_hash, code);
-        debug!("Stored code: hash={:?}, size={} bytes", code_hash, codes.get(&code_hash).map(|c| c.len()).unwrap_or(0));
+        self.analyses.write().await.insert(code_hash, Arc::new(analysis));
         Ok(())
     }
 
+    /// Jump destinations and problems of stored code
+    pub async fn get_code_analysis(&self, code_hash: &Hash) -> Option<Arc<BytecodeAnalysis>> {
+        if let Some(analysis) = self.analyses.read().await.get(code_hash) {
+            return Some(analysis.clone());
+        }
+        match self.base() {
+            Some(base) => Box::pin(base.get_code_analysis(code_hash)).await,
+            None => None,
+        }
+    }
+
     /// Get contract code by hash
     pub async fn get_code(&self, code_hash: &Hash) -> EVMResult<Option<Vec<u8>>> {
-        let codes = self.codes.read().await;
-        Ok(codes.get(code_hash).cloned())
+        if let Some(code) = self.codes.read().await.get(code_hash) {
+            return Ok(Some(code.clone()));
+        }
+        match self.base() {
+            Some(base) => Box::pin(base.get_code(code_hash)).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Get contract code by hash, analysed for execution by revm
+    ///
+    /// The jump table is computed on the first request for `code_hash` and
+    /// reused until the code is freed.
+    pub async fn get_analyzed_code(&self, code_hash: &Hash) -> EVMResult<Option<Bytecode>> {
+        if let Some(bytecode) = self.revm_code.read().await.get(code_hash) {
+            return Ok(Some(bytecode.clone()));
+        }
+
+        // Code held by the base is analysed and cached there
+        let code = self.codes.read().await.get(code_hash).cloned();
+        let Some(code) = code else {
+            return match self.base() {
+                Some(base) => Box::pin(base.get_analyzed_code(code_hash)).await,
+                None => Ok(None),
+            };
+        };
+        let mut revm_code = self.revm_code.write().await;
+        // Another caller may have analysed it while the lock was released
+        if let Some(bytecode) = revm_code.get(code_hash) {
+            return Ok(Some(bytecode.clone()));
+        }
+        let bytecode = revm::interpreter::analysis::to_analysed(Bytecode::new_raw(Bytes::from(code)));
+        self.revm_analyses.fetch_add(1, Ordering::Relaxed);
+        revm_code.insert(*code_hash, bytecode.clone());
+        debug!("Analysed code for execution: hash={:?}", code_hash);
+        Ok(Some(bytecode))
+    }
+
+    /// Number of times code has been analysed for revm
+    pub fn revm_analysis_count(&self) -> u64 {
+        self.revm_analyses.load(Ordering::Relaxed)
     }
 
     /// Bind code to address
+    ///
+    /// Each bound address holds a reference to the code; an address bound to
+    /// other code first releases it.
     pub async fn bind_code_to_address(&self, address: Address, code_hash: Hash) -> EVMResult<()> {
-        {
-            let mut addr_to_code = self.address_to_code.write().await;
-            addr_to_code.insert(address, code_hash);
+        self.unbound.write().await.remove(&address);
+        let previous = self.address_to_code.write().await.insert(address, code_hash);
+        if previous == Some(code_hash) {
+            return Ok(());
+        }
+        if let Some(previous) = previous {
+            self.release_code(address, previous).await;
         }
 
         {
@@ -62,10 +266,70 @@ impl CodeStorage {
         Ok(())
     }
 
+    /// Remove the code of `address`, e.g. after SELFDESTRUCT
+    ///
+    /// The code itself is freed once no address references it.
+    ///
+    /// # Returns
+    /// Hash of the code the address held, if any
+    pub async fn unbind_code_from_address(&self, address: &Address) -> EVMResult<Option<Hash>> {
+        let code_hash = self.address_to_code.write().await.remove(address);
+        if let Some(code_hash) = code_hash {
+            self.release_code(*address, code_hash).await;
+            info!("Unbound code from address: address={:?}, code_hash={:?}", address, code_hash);
+        }
+
+        // Hide the binding of the base, whose code stays where it is
+        let Some(base) = self.base() else {
+            return Ok(code_hash);
+        };
+        let mut unbound = self.unbound.write().await;
+        if unbound.contains(address) {
+            return Ok(code_hash);
+        }
+        let base_hash = Box::pin(base.get_code_hash(address)).await?;
+        if base_hash.is_some() {
+            unbound.insert(*address);
+        }
+        Ok(code_hash.or(base_hash))
+    }
+
+    /// Drop the reference of `address` to `code_hash`, freeing unreferenced code
+    async fn release_code(&self, address: Address, code_hash: Hash) {
+        let mut code_to_addrs = self.code_to_addresses.write().await;
+        let Some(addresses) = code_to_addrs.get_mut(&code_hash) else {
+            return;
+        };
+        addresses.retain(|a| *a != address);
+
+        if addresses.is_empty() {
+            code_to_addrs.remove(&code_hash);
+            self.codes.write().await.remove(&code_hash);
+            self.analyses.write().await.remove(&code_hash);
+            self.revm_code.write().await.remove(&code_hash);
+            debug!("Freed unreferenced code: hash={:?}", code_hash);
+        }
+    }
+
+    /// Number of addresses referencing `code_hash`
+    pub async fn code_ref_count(&self, code_hash: &Hash) -> usize {
+        self.code_to_addresses.read().await.get(code_hash).map_or(0, Vec::len)
+    }
+
+    /// Number of distinct code blobs stored
+    pub async fn code_count(&self) -> usize {
+        self.codes.read().await.len()
+    }
+
     /// Get code hash for an address
     pub async fn get_code_hash(&self, address: &Address) -> EVMResult<Option<Hash>> {
-        let addr_to_code = self.address_to_code.read().await;
-        Ok(addr_to_code.get(address).copied())
+        if let Some(code_hash) = self.address_to_code.read().await.get(address) {
+            return Ok(Some(*code_hash));
+        }
+        match self.base() {
+            Some(base) if !self.unbound.read().await.contains(address) => Box::pin(base.get_code_hash(address)).await,
+            _ => Ok(None),
+        }
     }
 
     /// Get contract code by address
@@ -84,7 +348,7 @@ impl CodeStorage {
 
     /// Calculate contract creation address (CREATE rule)
     ///
-    /// Address = keccak256(rlp.encode([sender, nonce]))
+    /// Address = keccak256(rlp.encode([sender, nonce]))[12..]
     pub fn calculate_create_address(sender: Address, nonce: u64) -> Address {
         use rlp::RlpStream;
 
@@ -96,7 +360,7 @@ impl CodeStorage {
         stream.append(&nonce);
         let encoded = stream.out();
 
-        let hash = Sha256::digest(&encoded);
+        let hash = keccak_hash::keccak(&encoded);
 
         let mut addr = [0u8; 20];
         addr.copy_from_slice(&hash[12..32]);
@@ -106,19 +370,19 @@ impl CodeStorage {
     /// Calculate contract creation address with salt (CREATE2 rule)
     ///
     /// Address = keccak256(0xff ++ sender ++ salt ++ keccak256(init_code))[12..]
+    ///
+    /// `init_code_hash` must be the keccak256 hash of the init code.
     pub fn calculate_create2_address(
         sender: Address,
         salt: [u8; 32],
         init_code_hash: Hash,
     ) -> Address {
-        use sha2::{Sha256, Digest as _};
-
-        let mut hasher = Sha256::new();
-        hasher.update([0xff]);
-        hasher.update(&sender.0);
-        hasher.update(&salt);
-        hasher.update(&init_code_hash.0);
-        let hash = hasher.finalize();
+        let mut preimage = Vec::with_capacity(1 + 20 + 32 + 32);
+        preimage.push(0xff);
+        preimage.extend_from_slice(&sender.0);
+        preimage.extend_from_slice(&salt);
+        preimage.extend_from_slice(&init_code_hash.0);
+        let hash = keccak_hash::keccak(&preimage);
 
         let mut addr = [0u8; 20];
         addr.copy_from_slice(&hash[12..32]);
@@ -148,12 +412,18 @@ mod tests {
         let code_hash = Hash([42u8; 32]);
         let code = vec![0x60, 0x61, 0x60]; // PUSH1 PUSH1 PUSH1
 
+        let address = Address([1u8; 20]);
+
         // Store code
-        storage.store_code(code_hash, code.clone()).await.unwrap();
+        storage.deploy_code(address, code_hash, code.clone()).await.unwrap();
 
         // Retrieve code
         let retrieved = storage.get_code(&code_hash).await.unwrap();
         assert_eq!(retrieved, Some(code));
+
+        // Unbinding the only address frees it
+        storage.unbind_code_from_address(&address).await.unwrap();
+        assert_eq!(storage.code_count().await, 0);
     }
 
     #[tokio::test]
@@ -164,8 +434,7 @@ mod tests {
         let code = vec![0x60, 0x61];
 
         // Store and bind
-        storage.store_code(code_hash, code.clone()).await.unwrap();
-        storage.bind_code_to_address(address, code_hash).await.unwrap();
+        storage.deploy_code(address, code_hash, code.clone()).await.unwrap();
 
         // Check binding
         assert!(storage.is_contract(&address).await);
@@ -208,6 +477,55 @@ mod tests {
         assert_ne!(addr, addr3);
     }
 
+    fn address(hex_str: &str) -> Address {
+        let mut addr = [0u8; 20];
+        addr.copy_from_slice(&hex::decode(hex_str).unwrap());
+        Address(addr)
+    }
+
+    #[test]
+    fn test_create_address_vectors() {
+        let sender = address("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0");
+        let expected = [
+            "cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d",
+            "343c43a37d37dff08ae8c4a11544c718abb4fcf8",
+            "f778b86fa74e846c4f0a1fbd1335fe81c00a0c91",
+            "fffd933a0bc612844eaf0c6fe3e5b8e9b6c1d19c",
+        ];
+
+        for (nonce, expected) in expected.iter().enumerate() {
+            assert_eq!(
+                CodeStorage::calculate_create_address(sender, nonce as u64),
+                address(expected),
+                "nonce {}", nonce
+            );
+        }
+    }
+
+    #[test]
+    fn test_create2_address_vectors() {
+        // Examples from EIP-1014
+        let vectors = [
+            ("0000000000000000000000000000000000000000", [0u8; 32], "00", "4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38"),
+            ("deadbeef00000000000000000000000000000000", [0u8; 32], "00", "b928f69bb1d91cd65274e3c79d8986362984fda3"),
+            ("00000000000000000000000000000000deadbeef", {
+                let mut salt = [0u8; 32];
+                salt[28..].copy_from_slice(&[0xca, 0xfe, 0xba, 0xbe]);
+                salt
+            }, "deadbeef", "60f3f640a8508fc6a86d45df051962668e1e8ac7"),
+            ("0000000000000000000000000000000000000000", [0u8; 32], "", "e33c0c7f7df4809055c3eba6c09cfe4baf1bd9e0"),
+        ];
+
+        for (sender, salt, init_code, expected) in vectors {
+            let init_code_hash = Hash(keccak_hash::keccak(hex::decode(init_code).unwrap()).0);
+            assert_eq!(
+                CodeStorage::calculate_create2_address(address(sender), salt, init_code_hash),
+                address(expected),
+                "sender {}, init_code {}", sender, init_code
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_multiple_addresses_same_code() {
         let storage = CodeStorage::new();
@@ -216,8 +534,7 @@ mod tests {
         let addr1 = Address([1u8; 20]);
         let addr2 = Address([2u8; 20]);
 
-        storage.store_code(code_hash, code).await.unwrap();
-        storage.bind_code_to_address(addr1, code_hash).await.unwrap();
+        storage.deploy_code(addr1, code_hash, code).await.unwrap();
         storage.bind_code_to_address(addr2, code_hash).await.unwrap();
 
         let addresses = storage.get_addresses_with_code(&code_hash).await.unwrap();
@@ -225,4 +542,87 @@ mod tests {
         assert!(addresses.contains(&addr1));
         assert!(addresses.contains(&addr2));
     }
+
+    #[tokio::test]
+    async fn test_same_code_stored_once() {
+        let storage = CodeStorage::new();
+        let code_hash = Hash([42u8; 32]);
+        let code = vec![0x60, 0x61];
+        let addr1 = Address([1u8; 20]);
+        let addr2 = Address([2u8; 20]);
+
+        for address in [addr1, addr2] {
+            storage.deploy_code(address, code_hash, code.clone()).await.unwrap();
+        }
+        assert_eq!(storage.code_count().await, 1);
+        assert_eq!(storage.code_ref_count(&code_hash).await, 2);
+
+        // The blob outlives the first address and is freed with the last
+        assert_eq!(storage.unbind_code_from_address(&addr1).await.unwrap(), Some(code_hash));
+        assert!(!storage.is_contract(&addr1).await);
+        assert_eq!(storage.get_code_by_address(&addr2).await.unwrap(), Some(code));
+
+        storage.unbind_code_from_address(&addr2).await.unwrap();
+        assert_eq!(storage.code_ref_count(&code_hash).await, 0);
+        assert_eq!(storage.code_count().await, 0);
+        assert_eq!(storage.unbind_code_from_address(&addr2).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_fork_copies_on_write() {
+        let base = Arc::new(CodeStorage::new());
+        let code_hash = Hash([42u8; 32]);
+        let (addr1, addr2) = (Address([1u8; 20]), Address([2u8; 20]));
+        base.deploy_code(addr1, code_hash, vec![0x60, 0x61]).await.unwrap();
+
+        let fork = base.fork();
+        assert_eq!(fork.code_count().await, 0);
+        assert_eq!(fork.get_code_by_address(&addr1).await.unwrap(), Some(vec![0x60, 0x61]));
+        assert!(fork.get_analyzed_code(&code_hash).await.unwrap().is_some());
+
+        // Rebinding in the fork leaves the base as it was
+        fork.bind_code_to_address(addr2, code_hash).await.unwrap();
+        assert_eq!(fork.unbind_code_from_address(&addr1).await.unwrap(), Some(code_hash));
+        assert!(!fork.is_contract(&addr1).await);
+        assert!(fork.is_contract(&addr2).await);
+        assert!(base.is_contract(&addr1).await);
+        assert!(!base.is_contract(&addr2).await);
+        assert_eq!(base.get_code(&code_hash).await.unwrap(), Some(vec![0x60, 0x61]));
+
+        fork.bind_code_to_address(addr1, code_hash).await.unwrap();
+        assert!(fork.is_contract(&addr1).await);
+    }
+
+    #[tokio::test]
+    async fn test_detached_fork_keeps_code() {
+        let base = Arc::new(CodeStorage::new());
+        let (hash1, hash2) = (Hash([1u8; 32]), Hash([2u8; 32]));
+        let (addr1, addr2, addr3) = (Address([1u8; 20]), Address([2u8; 20]), Address([3u8; 20]));
+        base.deploy_code(addr1, hash1, vec![0x01]).await.unwrap();
+        base.deploy_code(addr2, hash2, vec![0x02]).await.unwrap();
+
+        let fork = base.fork();
+        fork.unbind_code_from_address(&addr2).await.unwrap();
+        // Bound to code only the base holds
+        fork.bind_code_to_address(addr3, hash2).await.unwrap();
+
+        fork.detach().await.unwrap();
+        base.unbind_code_from_address(&addr1).await.unwrap();
+        base.unbind_code_from_address(&addr2).await.unwrap();
+        assert_eq!(base.code_count().await, 0);
+
+        assert_eq!(fork.get_code_by_address(&addr1).await.unwrap(), Some(vec![0x01]));
+        assert!(!fork.is_contract(&addr2).await);
+        assert_eq!(fork.get_code_by_address(&addr3).await.unwrap(), Some(vec![0x02]));
+        assert_eq!(fork.code_count().await, 2);
+
+        // Bindings follow another storage, with the code they need
+        let other = CodeStorage::new();
+        other.deploy_code(addr2, hash2, vec![0x02]).await.unwrap();
+        fork.copy_bindings_from(&other, &[addr1, addr2, addr3]).await.unwrap();
+        assert!(!fork.is_contract(&addr1).await);
+        assert!(!fork.is_contract(&addr3).await);
+        assert_eq!(fork.get_code_by_address(&addr2).await.unwrap(), Some(vec![0x02]));
+        assert_eq!(fork.code_count().await, 1);
+    }
 }
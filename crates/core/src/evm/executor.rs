@@ -2,13 +2,13 @@
 //!
 //! This module provides EVM transaction execution capabilities using revm.
 
-use crate::evm::{EVMConfig, EVMContext, EVMError, EVMResult, CodeStorage, LogManager, EventLog, Receipt, ReceiptDB, ReceiptLog};
+use crate::evm::{BytecodeAnalysis, EVMConfig, EVMContext, EVMError, EVMResult, CodeStorage, GasCalculator, LogManager, EventLog, Receipt, ReceiptDB, ReceiptLog};
 use crate::evm::runtime::NornDatabaseAdapter; // Fixed with SyncStateManager
 use crate::state::cache::SyncStateManager;
 use crate::state::{AccountStateManager, AccountState as AccountAccountState, AccountType};
 use norn_common::types::{Transaction, Address, Hash, TransactionType};
 use std::sync::Arc;
-use tracing::{debug, info, warn, trace, error};
+use tracing::{debug, info, warn, error};
 use sha2::{Sha256, Digest};
 use num_bigint::BigUint;
 use num_traits::{Zero, One};
@@ -60,6 +60,26 @@ pub struct ExecutionLog {
     pub data: Vec<u8>,
 }
 
+/// State override for one account, applied to an overlay when simulating
+/// calls (the state-override set of `eth_call`)
+#[derive(Debug, Clone, Default)]
+pub struct AccountOverride {
+    /// Replacement balance
+    pub balance: Option<BigUint>,
+
+    /// Replacement nonce
+    pub nonce: Option<u64>,
+
+    /// Replacement code; installed without EIP-170/EIP-3541 checks
+    pub code: Option<Vec<u8>>,
+
+    /// Replaces all storage slots of the account
+    pub state: Option<std::collections::HashMap<[u8; 32], [u8; 32]>>,
+
+    /// Storage slots to set, keeping the other slots
+    pub state_diff: std::collections::HashMap<[u8; 32], [u8; 32]>,
+}
+
 /// EVM Executor
 ///
 /// This is a placeholder implementation that will be expanded with
@@ -113,6 +133,135 @@ impl EVMExecutor {
         &self.code_storage
     }
 
+    /// Get state manager reference
+    pub fn state_manager(&self) -> &Arc<AccountStateManager> {
+        &self.state_manager
+    }
+
+    /// Create an executor over a copy-on-write fork of the current state and
+    /// contract code
+    ///
+    /// Nothing is copied up front: the overlay reads through to this
+    /// executor's state, and transactions executed through it don't affect
+    /// this executor. Logs and receipts start out empty.
+    pub async fn overlay(&self) -> Self {
+        Self {
+            state_manager: Arc::new(self.state_manager.fork().await),
+            code_storage: Arc::new(self.code_storage.fork()),
+            log_manager: Arc::new(LogManager::new()),
+            receipt_db: Arc::new(ReceiptDB::new()),
+            config: self.config.clone(),
+        }
+    }
+
+    /// Executor over `state_manager` and `code_storage` with this executor's
+    /// configuration
+    ///
+    /// Logs and receipts start out empty.
+    pub fn with_storage(&self, state_manager: Arc<AccountStateManager>, code_storage: Arc<CodeStorage>) -> Self {
+        Self {
+            state_manager,
+            code_storage,
+            log_manager: Arc::new(LogManager::new()),
+            receipt_db: Arc::new(ReceiptDB::new()),
+            config: self.config.clone(),
+        }
+    }
+
+    /// Create an overlay with `overrides` applied on top of the current state
+    ///
+    /// Used to simulate calls against hypothetical state; this executor is
+    /// left untouched.
+    pub async fn overlay_with_overrides(
+        &self,
+        overrides: &std::collections::HashMap<Address, AccountOverride>,
+    ) -> EVMResult<Self> {
+        let overlay = self.overlay().await;
+
+        for (address, account) in overrides {
+            if let Some(balance) = &account.balance {
+                overlay.state_manager.update_balance(address, balance.clone()).await
+                    .map_err(|e| EVMError::StateAccess(format!("Failed to override balance: {}", e)))?;
+            }
+
+            if account.nonce.is_some() || account.code.is_some() {
+                let mut state = overlay.state_manager.get_account(address).await
+                    .map_err(|e| EVMError::StateAccess(format!("Failed to load account: {}", e)))?
+                    .unwrap_or_else(|| AccountAccountState {
+                        address: *address,
+                        balance: BigUint::zero(),
+                        nonce: 0,
+                        code_hash: None,
+                        storage_root: Hash::default(),
+                        account_type: AccountType::Normal,
+                        created_at: 0,
+                        updated_at: 0,
+                        deleted: false,
+                    });
+
+                if let Some(nonce) = account.nonce {
+                    state.nonce = nonce;
+                }
+                if let Some(code) = &account.code {
+                    let code_hash = Hash(Sha256::digest(code).into());
+                    overlay.code_storage.deploy_code(*address, code_hash, code.clone()).await?;
+                    state.code_hash = Some(code_hash);
+                    state.account_type = AccountType::Contract;
+                }
+
+                overlay.state_manager.set_account(address, state).await
+                    .map_err(|e| EVMError::StateAccess(format!("Failed to override account: {}", e)))?;
+            }
+
+            if account.state.is_some() {
+                overlay.state_manager.clear_storage(address).await
+                    .map_err(|e| EVMError::StateAccess(format!("Failed to override storage: {}", e)))?;
+            }
+            for (slot, value) in account.state.iter().flatten().chain(&account.state_diff) {
+                // Keys are stored the way the revm adapter looks them up
+                let key: Vec<u8> = slot.iter().skip_while(|&&b| b == 0).copied().collect();
+                overlay.state_manager.set_storage(address, key, value.to_vec()).await
+                    .map_err(|e| EVMError::StateAccess(format!("Failed to override storage: {}", e)))?;
+            }
+        }
+
+        Ok(overlay)
+    }
+
+    /// Replace the code of `address` outside of any transaction
+    ///
+    /// The balance, nonce and storage of an existing account are kept. Empty
+    /// `code` removes the code and turns the account back into a normal one.
+    pub async fn set_code(&self, address: Address, code: Vec<u8>) -> EVMResult<()> {
+        let mut state = self.state_manager.get_account(&address).await
+            .map_err(|e| EVMError::StateAccess(format!("Failed to load account: {}", e)))?
+            .unwrap_or_else(|| AccountAccountState {
+                address,
+                balance: BigUint::zero(),
+                nonce: 0,
+                code_hash: None,
+                storage_root: Hash::default(),
+                account_type: AccountType::Normal,
+                created_at: 0,
+                updated_at: 0,
+                deleted: false,
+            });
+
+        if code.is_empty() {
+            self.code_storage.unbind_code_from_address(&address).await?;
+            state.code_hash = None;
+            state.account_type = AccountType::Normal;
+        } else {
+            let code_hash = Hash(Sha256::digest(&code).into());
+            self.code_storage.deploy_code(address, code_hash, code).await?;
+            state.code_hash = Some(code_hash);
+            state.account_type = AccountType::Contract;
+        }
+
+        self.state_manager.set_account(&address, state).await
+            .map_err(|e| EVMError::StateAccess(format!("Failed to set account code: {}", e)))
+    }
+
     /// Get log manager reference
     pub fn log_manager(&self) -> &Arc<LogManager> {
         &self.log_manager
@@ -225,6 +374,12 @@ impl EVMExecutor {
     ///
     /// This is a simplified implementation that handles basic transfers
     /// and contract creation.
+    ///
+    /// Transactions with EIP-1559 fee fields run at their effective gas price
+    /// for `ctx.block_base_fee` and the sender is charged `gas_used` at that
+    /// price: the priority fee goes to the block coinbase and the base fee is
+    /// burned. Legacy transactions run at their gas price, or
+    /// `ctx.tx_gas_price` if they set none, and are not charged here.
     pub async fn execute(
         &self,
         tx: &Transaction,
@@ -242,17 +397,91 @@ impl EVMExecutor {
             ));
         }
 
+        let gas_price = Self::effective_gas_price(tx, ctx)?;
+        let mut tx_ctx = ctx.clone();
+        match tx.body.max_fee_per_gas {
+            Some(max_fee) => {
+                tx_ctx.tx_gas_price = max_fee;
+                tx_ctx.tx_priority_fee = Some(tx.body.max_priority_fee_per_gas.unwrap_or(0));
+                self.ensure_can_pay_max_fee(tx, max_fee).await?;
+            }
+            None => {
+                tx_ctx.tx_gas_price = gas_price;
+                tx_ctx.tx_priority_fee = None;
+            }
+        }
+
         // Check if this is a contract creation (to address is zero or default)
         let is_contract_creation = tx.body.receiver == Address::default()
             || tx.body.receiver.0.iter().all(|&b| b == 0);
 
-        if is_contract_creation && !tx.body.data.is_empty() {
+        let result = if is_contract_creation && !tx.body.data.is_empty() {
             // Contract creation
-            self.execute_contract_creation(tx, ctx).await
+            self.execute_contract_creation(tx, &tx_ctx).await?
         } else {
             // Regular transfer or call
-            self.execute_transfer_or_call(tx, ctx).await
+            self.execute_transfer_or_call(tx, &tx_ctx).await?
+        };
+
+        if tx.body.max_fee_per_gas.is_some() {
+            self.charge_gas(tx.body.address, result.gas_used, gas_price, ctx).await?;
+        }
+        Ok(result)
+    }
+
+    /// Gas price `tx` pays in the block described by `ctx`
+    ///
+    /// For EIP-1559 transactions this is the block base fee plus the priority
+    /// fee, capped by the max fee; a max fee below the base fee is rejected.
+    /// Legacy transactions pay their gas price, or `ctx.tx_gas_price`.
+    pub fn effective_gas_price(tx: &Transaction, ctx: &EVMContext) -> EVMResult<u64> {
+        let Some(max_fee) = tx.body.max_fee_per_gas else {
+            return Ok(tx.body.gas_price.unwrap_or(ctx.tx_gas_price));
+        };
+
+        let base_fee = ctx.block_base_fee;
+        if max_fee < base_fee {
+            return Err(EVMError::InvalidTransaction(format!(
+                "Max fee per gas {} is below the base fee {}",
+                max_fee, base_fee
+            )));
+        }
+        let priority_fee = tx.body.max_priority_fee_per_gas.unwrap_or(0);
+        Ok(max_fee.min(base_fee.saturating_add(priority_fee)))
+    }
+
+    /// Check the sender of `tx` can pay its value and all its gas at `max_fee`
+    async fn ensure_can_pay_max_fee(&self, tx: &Transaction, max_fee: u64) -> EVMResult<()> {
+        let value = tx.body.value.as_deref().unwrap_or("0").parse::<u128>()
+            .map_err(|_| EVMError::InvalidTransaction("Invalid value format".to_string()))?;
+        let required = BigUint::from(tx.body.gas as u64) * max_fee + value;
+
+        let balance = self.state_manager.get_balance(&tx.body.address).await
+            .map_err(|e| EVMError::StateAccess(format!("Failed to get balance: {}", e)))?;
+        if balance < required {
+            return Err(EVMError::InvalidTransaction(format!(
+                "Insufficient balance: have {}, need {} for value and max gas fee",
+                balance, required
+            )));
+        }
+        Ok(())
+    }
+
+    /// Take the fee for `gas_used` at `gas_price` from `sender`
+    ///
+    /// The part above the base fee is paid to the coinbase, the rest burned.
+    async fn charge_gas(&self, sender: Address, gas_used: u64, gas_price: u64, ctx: &EVMContext) -> EVMResult<()> {
+        let fee = BigUint::from(gas_used) * gas_price;
+        self.state_manager.subtract_balance(&sender, &fee).await
+            .map_err(|e| EVMError::StateAccess(format!("Failed to charge gas fee: {}", e)))?;
+
+        let tip = BigUint::from(gas_used) * gas_price.saturating_sub(ctx.block_base_fee);
+        if tip > BigUint::zero() {
+            self.state_manager.add_balance(&ctx.block_coinbase, &tip).await
+                .map_err(|e| EVMError::StateAccess(format!("Failed to pay priority fee: {}", e)))?;
         }
+        debug!("Charged {} for {} gas at {} wei from {:?}", fee, gas_used, gas_price, sender);
+        Ok(())
     }
 
     /// Execute a contract creation transaction
@@ -274,8 +503,8 @@ impl EVMExecutor {
             sender, nonce, init_code.len(), value
         );
 
-        // Use revm v14 for contract creation
-        self.execute_with_revm(sender, None, value, init_code, tx.body.gas as u64, ctx).await
+        let (_, result) = self.deploy_contract(sender, nonce, init_code, value, tx.body.gas as u64, ctx).await?;
+        Ok(result)
     }
 
     /// Execute a regular ETH transfer or contract call
@@ -295,15 +524,17 @@ impl EVMExecutor {
 
         debug!("Transferring {} wei from {:?} to {:?}", value_u256, from, to);
 
-        // Check if this is a contract call (has data)
-        if !tx.body.data.is_empty() {
+        // Check if this is a contract call: it has data, or the receiver
+        // has code to run
+        if !tx.body.data.is_empty() || self.code_storage.is_contract(&to).await {
             // This is a contract call
-            return self.call_contract(
+            return self.call_contract_with_context(
                 from,
                 to,
                 value_u256,
                 tx.body.data.clone(),
                 tx.body.gas as u64,
+                ctx,
             ).await;
         }
 
@@ -331,7 +562,7 @@ impl EVMExecutor {
 
         Ok(EVMExecutionResult {
             success: true,
-            gas_used: 21_000, // Base gas for ETH transfer
+            gas_used: self.gas_calculator().transaction_intrinsic_gas(tx),
             output: Vec::new(),
             error: None,
             logs: Vec::new(),
@@ -387,7 +618,7 @@ impl EVMExecutor {
 
         Ok(EVMExecutionResult {
             success: true,
-            gas_used: 21_000, // Base gas for ETH transfer
+            gas_used: self.gas_calculator().transaction_intrinsic_gas(tx),
             output: Vec::new(),
             error: None,
             logs: Vec::new(),
@@ -466,12 +697,33 @@ impl EVMExecutor {
         value: u128,
         input_data: Vec<u8>,
         gas_limit: u64,
+    ) -> EVMResult<EVMExecutionResult> {
+        self.call_contract_with_context(caller, callee, value, input_data, gas_limit, &EVMContext::default()).await
+    }
+
+    /// Call a contract as if in the block described by `ctx`
+    ///
+    /// Same as [`call_contract`](Self::call_contract), which runs in a
+    /// default context, for callers that need a particular block number,
+    /// timestamp or base fee.
+    pub async fn call_contract_with_context(
+        &self,
+        caller: Address,
+        callee: Address,
+        value: u128,
+        input_data: Vec<u8>,
+        gas_limit: u64,
+        ctx: &EVMContext,
     ) -> EVMResult<EVMExecutionResult> {
         info!(
             "CALL: caller={:?}, callee={:?}, value={}, data_len={}, gas_limit={}",
             caller, callee, value, input_data.len(), gas_limit
         );
 
+        if self.config.enable_precompiles && crate::evm::is_precompile(&callee) {
+            return self.call_precompile(caller, callee, value, input_data, gas_limit).await;
+        }
+
         // Check if callee is a contract
         if !self.code_storage.is_contract(&callee).await {
             return Err(EVMError::Execution(format!(
@@ -481,13 +733,64 @@ impl EVMExecutor {
         }
 
         // Use revm for actual contract execution
-        let ctx = EVMContext::default();
-        let result = self.execute_with_revm(caller, Some(callee), value, input_data, gas_limit, &ctx).await?;
+        let result = self.execute_with_revm(caller, Some(callee), value, input_data, gas_limit, ctx).await?;
 
         info!("CALL completed: success={}, gas_used={}", result.success, result.gas_used);
         Ok(result)
     }
 
+    /// Call a precompiled contract
+    ///
+    /// Gas used is the intrinsic transaction cost plus the gas charged by the
+    /// precompile. Running out of gas or invalid input consumes the whole
+    /// gas limit and reports a failed execution.
+    async fn call_precompile(
+        &self,
+        caller: Address,
+        precompile: Address,
+        value: u128,
+        input_data: Vec<u8>,
+        gas_limit: u64,
+    ) -> EVMResult<EVMExecutionResult> {
+        let intrinsic_gas = self.gas_calculator().intrinsic_gas_cost(false, &input_data, None);
+
+        let outcome = match gas_limit.checked_sub(intrinsic_gas) {
+            Some(available) => crate::evm::execute_precompile(&precompile, &input_data, available),
+            None => Err(EVMError::OutOfGas),
+        };
+
+        let result = match outcome {
+            Ok(precompile_result) => EVMExecutionResult {
+                success: true,
+                gas_used: intrinsic_gas + precompile_result.gas_used,
+                output: precompile_result.output,
+                error: None,
+                logs: Vec::new(),
+            },
+            Err(e) => {
+                debug!("Precompile {:?} failed: {}", precompile, e);
+                return Ok(EVMExecutionResult {
+                    success: false,
+                    gas_used: gas_limit,
+                    output: Vec::new(),
+                    error: Some(e.to_string()),
+                    logs: Vec::new(),
+                });
+            }
+        };
+
+        if value > 0 {
+            let amount = BigUint::from(value);
+            self.state_manager.subtract_balance(&caller, &amount).await
+                .map_err(|e| EVMError::StateAccess(format!("Failed to subtract from balance: {}", e)))?;
+            self.state_manager.add_balance(&precompile, &amount).await
+                .map_err(|e| EVMError::StateAccess(format!("Failed to add to balance: {}", e)))?;
+        }
+
+        info!("Precompile call completed: address={:?}, gas_used={}", precompile, result.gas_used);
+        Ok(result)
+    }
+
     /// Execute a DELEGATECALL operation
     ///
     /// DELEGATECALL is similar to CALL but with key differences:
@@ -638,27 +941,45 @@ impl EVMExecutor {
     }
 
     /// Estimate gas for a transaction (eth_estimateGas)
+    ///
+    /// `tx` is dry-run in the block described by `ctx` on an overlay of the
+    /// current state, with `tx.body.gas` as its gas limit. The estimate is
+    /// the intrinsic gas plus the gas execution used beyond it. A dry run
+    /// that fails is reported as an error: [`EVMError::OutOfGas`] if the
+    /// gas limit was too low, [`EVMError::Revert`] with the revert data if
+    /// the call reverted.
     pub async fn estimate_gas(
         &self,
         tx: &Transaction,
+        ctx: &EVMContext,
     ) -> EVMResult<u64> {
         debug!("Estimating gas for transaction: {:?}", tx.body.hash);
 
-        // Simple gas estimation based on transaction type
-        // Real implementation will execute transaction and measure gas
-        if tx.body.data.is_empty() {
-            // Simple transfer
-            Ok(21_000)
-        } else {
-            // Contract creation or call
-            if tx.body.receiver == Address::default() {
-                // Contract creation
-                Ok(53_000) // Base gas for CREATE
+        let intrinsic_gas = self.gas_calculator().transaction_intrinsic_gas(tx);
+        if (tx.body.gas as u64) < intrinsic_gas {
+            return Err(EVMError::OutOfGas);
+        }
+
+        let result = self.overlay().await.execute(tx, ctx).await?;
+        if !result.success {
+            let error = result.error.unwrap_or_default();
+            return Err(if error.contains("OutOfGas") {
+                EVMError::OutOfGas
+            } else if error == "Execution reverted" {
+                EVMError::Revert(format!("0x{}", hex::encode(&result.output)))
             } else {
-                // Contract call
-                Ok(26_000 + tx.body.data.len() as u64 * 16) // Base + data cost
-            }
+                EVMError::Execution(error)
+            });
         }
+
+        // The dry run's gas already includes the intrinsic gas
+        let execution_gas = result.gas_used.saturating_sub(intrinsic_gas);
+        Ok(intrinsic_gas + execution_gas)
+    }
+
+    /// Gas calculator for the configured spec
+    fn gas_calculator(&self) -> GasCalculator {
+        GasCalculator::for_spec(self.config.spec_id, self.config.eip1559_config.clone())
     }
 
     // /// Get the database adapter
@@ -733,7 +1054,8 @@ impl EVMExecutor {
         }
 
         // Configure EVM environment
-        let cfg = CfgEnv::default().with_chain_id(self.config.chain_id);
+        let mut cfg = CfgEnv::default().with_chain_id(self.config.chain_id);
+        cfg.limit_contract_code_size = Some(self.config.max_contract_size);
 
         // Create transaction environment
         let tx_env = TxEnv {
@@ -747,7 +1069,7 @@ impl EVMExecutor {
             data: revm::primitives::Bytes::from(data),
             gas_limit: gas_limit,  // Already u64
             gas_price: revm::primitives::U256::from(ctx.tx_gas_price),
-            gas_priority_fee: None,
+            gas_priority_fee: ctx.tx_priority_fee.map(revm::primitives::U256::from),
             ..Default::default()
         };
 
@@ -757,6 +1079,7 @@ impl EVMExecutor {
             timestamp: revm::primitives::U256::from(ctx.block_timestamp),
             gas_limit: revm::primitives::U256::from(ctx.block_gas_limit),
             coinbase: revm::primitives::Address::from(ctx.block_coinbase.0),
+            basefee: revm::primitives::U256::from(ctx.block_base_fee),
             ..Default::default()
         };
 
@@ -766,56 +1089,129 @@ impl EVMExecutor {
             tx: tx_env,
         };
 
-        // Create EVM handler with Cancun spec
+        // Create EVM handler with the configured spec; it also decides the
+        // refund cap applied to the reported gas_used (EIP-3529)
         use revm::Handler;
 
         // In revm v14, the API has changed significantly
-        let handler = Handler::new(HandlerCfg::new(revm::primitives::SpecId::CANCUN));
-
-        // Create EVM with context embedded - new API in v14
-        let mut evm = revm::Evm::builder()
-            .with_db(db_adapter)
-            .with_handler(handler)
-            .with_env(Box::new(env))
-            .build();
-
-        // Execute the transaction
-        let (mut evm, result_and_state) = match evm.transact() {
-            Ok(result) => {
-                info!("revm execution completed successfully");
-                (evm, result)
-            }
-            Err(e) => {
-                error!("revm execution failed: {:?}", e);
-                return Err(EVMError::Execution(format!("revm execution failed: {:?}", e)));
-            }
-        };
+        let handler = Handler::new(HandlerCfg::new(self.config.spec_id));
+
+        // The EVM is not Send, so it must be gone before the next await.
+        // Transient storage (EIP-1153) lives in its journal rather than in
+        // the database adapter, so it is dropped along with it at the end of
+        // every transaction.
+        let (execution_result, logs, destroyed) = {
+            // Create EVM with context embedded - new API in v14
+            let max_call_depth = self.config.max_call_depth as u64;
+            let cancel = ctx.cancel.clone();
+            let mut evm = revm::Evm::builder()
+                .with_db(db_adapter)
+                .with_handler(handler)
+                .with_env(Box::new(env))
+                .append_handler_register_box(Box::new(move |handler| {
+                    // revm only enforces its fixed CALL_STACK_LIMIT; fail nested
+                    // frames past the configured depth the same way, so the
+                    // calling frame sees a failed CALL instead of a reverted tx
+                    let call = handler.execution.call.clone();
+                    // revm's frame handles are Arc<dyn Fn> without Send + Sync
+                    #[allow(clippy::arc_with_non_send_sync)]
+                    let limited_call: revm::handler::FrameCallHandle<'_, _, _> = Arc::new(move |ctx, inputs| {
+                        if ctx.evm.journaled_state.depth() > max_call_depth {
+                            return Ok(revm::FrameOrResult::new_call_result(
+                                call_too_deep(inputs.gas_limit),
+                                inputs.return_memory_offset.clone(),
+                            ));
+                        }
+                        call(ctx, inputs)
+                    });
+                    handler.execution.call = limited_call;
+                    let create = handler.execution.create.clone();
+                    #[allow(clippy::arc_with_non_send_sync)]
+                    let limited_create: revm::handler::FrameCreateHandle<'_, _, _> = Arc::new(move |ctx, inputs| {
+                        if ctx.evm.journaled_state.depth() > max_call_depth {
+                            return Ok(revm::FrameOrResult::new_create_result(
+                                call_too_deep(inputs.gas_limit),
+                                None,
+                            ));
+                        }
+                        create(ctx, inputs)
+                    });
+                    handler.execution.create = limited_create;
+
+                    // Check the cancel flag before every instruction, so a
+                    // caller that gave up stops even a tight loop
+                    if let Some(cancel) = &cancel {
+                        for instruction in handler.instruction_table.to_boxed().iter_mut() {
+                            let cancel = Arc::clone(cancel);
+                            revm::interpreter::opcode::update_boxed_instruction(instruction, move |prev, interpreter, host| {
+                                if cancel.load(std::sync::atomic::Ordering::Relaxed) {
+                                    host.evm.error = Err(revm::primitives::EVMError::Custom("execution cancelled".to_string()));
+                                    interpreter.instruction_result = revm::interpreter::InstructionResult::FatalExternalError;
+                                    return;
+                                }
+                                prev(interpreter, host)
+                            });
+                        }
+                    }
+                }))
+                .build();
+
+            // Execute the transaction
+            let (mut evm, result_and_state) = match evm.transact() {
+                Ok(result) => {
+                    info!("revm execution completed successfully");
+                    (evm, result)
+                }
+                // The transaction can't be included, e.g. it can't pay for its gas
+                Err(revm::primitives::EVMError::Transaction(e)) => {
+                    warn!("revm rejected the transaction: {:?}", e);
+                    return Err(EVMError::InvalidTransaction(format!("{:?}", e)));
+                }
+                Err(e) => {
+                    error!("revm execution failed: {:?}", e);
+                    return Err(EVMError::Execution(format!("revm execution failed: {:?}", e)));
+                }
+            };
 
-        let execution_result = result_and_state.result;
-        let state_changes = result_and_state.state;
+            let execution_result = result_and_state.result;
+            let state_changes = result_and_state.state;
 
-        // Extract logs from execution result
-        // In revm v14, only Success has logs field
-        let logs = match &execution_result {
-            revm::primitives::ExecutionResult::Success { logs, .. } => {
-                logs.clone()
-            }
-            _ => Vec::new(), // Revert and Halt don't have logs in revm v14
-        };
+            // Extract logs from execution result
+            // In revm v14, only Success has logs field
+            let logs = match &execution_result {
+                revm::primitives::ExecutionResult::Success { logs, .. } => {
+                    logs.clone()
+                }
+                _ => Vec::new(), // Revert and Halt don't have logs in revm v14
+            };
 
-        let logs: Vec<ExecutionLog> = logs.into_iter()
-            .map(|log| ExecutionLog {
-                address: Address(log.address.as_slice().try_into().unwrap_or([0u8; 20])),
-                topics: log.topics().into_iter() // Use getter method
-                    .map(|t| Hash(t.as_slice().try_into().unwrap_or([0u8; 32])))
-                    .collect(),
-                data: log.data.data.to_vec(), // Access the inner Bytes field
-            })
-            .collect();
+            let logs: Vec<ExecutionLog> = logs.into_iter()
+                .map(|log| ExecutionLog {
+                    address: Address(log.address.as_slice().try_into().unwrap_or([0u8; 20])),
+                    topics: log.topics().iter() // Use getter method
+                        .map(|t| Hash(t.as_slice().try_into().unwrap_or([0u8; 32])))
+                        .collect(),
+                    data: log.data.data.to_vec(), // Access the inner Bytes field
+                })
+                .collect();
+
+            let destroyed: Vec<Address> = state_changes
+                .iter()
+                .filter(|(_, account)| account.is_selfdestructed())
+                .map(|(address, _)| Address(address.as_slice().try_into().unwrap_or([0u8; 20])))
+                .collect();
+
+            // Commit state changes back to database adapter
+            // In revm v14, we need to use the evm's db_mut() to get mutable access
+            evm.db_mut().commit(state_changes);
+
+            (execution_result, logs, destroyed)
+        };
 
-        // Commit state changes back to database adapter
-        // In revm v14, we need to use the evm's db_mut() to get mutable access
-        evm.db_mut().commit(state_changes);
+        // Contracts destroyed by SELFDESTRUCT release their code
+        for address in &destroyed {
+            self.code_storage.unbind_code_from_address(address).await?;
+        }
 
         // Get gas used and refunded based on result variant
         let (gas_used, gas_refunded, is_success) = match &execution_result {
@@ -860,15 +1256,26 @@ impl EVMExecutor {
             }
         };
 
+        let error = match &execution_result {
+            revm::primitives::ExecutionResult::Success { .. } => None,
+            revm::primitives::ExecutionResult::Revert { .. } => Some("Execution reverted".to_string()),
+            // revm reports both EIP-170 and EIP-3541 violations as this reason
+            revm::primitives::ExecutionResult::Halt {
+                reason: revm::primitives::HaltReason::CreateContractSizeLimit, ..
+            } => Some(format!(
+                "Contract code rejected: too large (max {} bytes) or starts with 0xEF",
+                self.config.max_contract_size
+            )),
+            revm::primitives::ExecutionResult::Halt { reason, .. } => {
+                Some(format!("Execution halted: {:?}", reason))
+            }
+        };
+
         Ok(EVMExecutionResult {
             success: is_success,
             gas_used: gas_used, // Already u64
             output,
-            error: if is_success {
-                None
-            } else {
-                Some(format!("Execution reverted"))
-            },
+            error,
             logs,
         })
     }
@@ -900,12 +1307,61 @@ impl EVMExecutor {
         })
     }
 
+    /// Deploy a contract by running its constructor (CREATE transaction)
+    ///
+    /// `init_code` is executed with revm and the code it returns is installed
+    /// as the contract's runtime code at the CREATE address. Nothing is
+    /// installed if the constructor fails, returns more than
+    /// `max_contract_size` bytes (EIP-170) or returns code starting with
+    /// 0xEF (EIP-3541); the latter two consume all gas.
+    ///
+    /// # Returns
+    /// Contract address and constructor execution result
+    pub async fn deploy_contract(
+        &self,
+        sender: Address,
+        nonce: u64,
+        init_code: Vec<u8>,
+        value: u128,
+        gas_limit: u64,
+        ctx: &EVMContext,
+    ) -> EVMResult<(Address, EVMExecutionResult)> {
+        let contract_address = CodeStorage::calculate_create_address(sender, nonce);
+
+        // Use revm v14 for contract creation
+        let mut result = self.execute_with_revm(sender, None, value, init_code, gas_limit, ctx).await?;
+
+        // revm enforces these rules itself; this guards installs if it ever doesn't
+        if result.success {
+            if let Err(e) = self.validate_new_code(&result.output) {
+                warn!("Rejecting runtime code returned by constructor: {}", e);
+                result = EVMExecutionResult {
+                    success: false,
+                    gas_used: gas_limit,
+                    output: Vec::new(),
+                    error: Some(e.to_string()),
+                    logs: Vec::new(),
+                };
+            }
+        }
+
+        if result.success {
+            self.install_code(contract_address, result.output.clone(), value).await?;
+            info!("Contract deployed: address={:?}, code_len={}", contract_address, result.output.len());
+        }
+
+        Ok((contract_address, result))
+    }
+
     /// Create a new contract (CREATE opcode)
     ///
+    /// `init_code` is installed as the contract's code as-is, without running
+    /// it; use `deploy_contract` to run a constructor.
+    ///
     /// # Arguments
     /// * `sender` - Contract creator address
     /// * `nonce` - Sender's nonce
-    /// * `init_code` - Contract initialization code
+    /// * `init_code` - Contract code
     /// * `value` - ETH value to send to contract
     /// * `gas_limit` - Gas limit for creation
     ///
@@ -924,51 +1380,20 @@ impl EVMExecutor {
             sender, nonce, init_code.len(), value
         );
 
-        // Validate contract size (EIP-170: max 24KB)
-        if init_code.len() > self.config.max_contract_size {
-            return Err(EVMError::ContractCreationFailed(
-                format!("Contract code too large: {} bytes (max {})",
-                    init_code.len(), self.config.max_contract_size)
-            ));
-        }
+        // Validate contract size (EIP-170) and leading byte (EIP-3541)
+        self.validate_new_code(&init_code)?;
 
         // Calculate contract address
         let contract_address = CodeStorage::calculate_create_address(sender, nonce);
 
         debug!("Calculated contract address: {:?}", contract_address);
 
-        // Calculate code hash
-        let code_hash = Hash(Sha256::digest(&init_code).into());
-
-        // Store contract code
-        self.code_storage.store_code(code_hash, init_code.clone()).await?;
-        self.code_storage.bind_code_to_address(contract_address, code_hash).await?;
+        let code_hash = self.install_code(contract_address, init_code, value).await?;
 
         // Update sender's account (increment nonce)
         // TODO: This should be done as part of the transaction execution
         // self.state_manager.increment_nonce(&sender).await?;
 
-        // Create contract account
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        let contract_account = AccountAccountState {
-            address: contract_address,
-            balance: BigUint::from(value),
-            nonce: 1,
-            code_hash: Some(code_hash),
-            storage_root: Hash::default(),
-            account_type: AccountType::Contract,
-            created_at: now,
-            updated_at: now,
-            deleted: false,
-        };
-
-        self.state_manager.set_account(&contract_address, contract_account)
-            .await
-            .map_err(|e| EVMError::StateAccess(format!("Failed to set contract account: {}", e)))?;
-
         info!("Contract created successfully: address={:?}, code_hash={:?}", contract_address, code_hash);
 
         let result = EVMExecutionResult {
@@ -996,16 +1421,11 @@ impl EVMExecutor {
             sender, init_code.len(), value
         );
 
-        // Validate contract size
-        if init_code.len() > self.config.max_contract_size {
-            return Err(EVMError::ContractCreationFailed(
-                format!("Contract code too large: {} bytes (max {})",
-                    init_code.len(), self.config.max_contract_size)
-            ));
-        }
+        // Validate contract size and leading byte
+        self.validate_new_code(&init_code)?;
 
         // Calculate init code hash
-        let init_code_hash = Hash(Sha256::digest(&init_code).into());
+        let init_code_hash = Hash(keccak_hash::keccak(&init_code).0);
 
         // Calculate contract address
         let contract_address = CodeStorage::calculate_create2_address(
@@ -1014,12 +1434,64 @@ impl EVMExecutor {
 
         debug!("Calculated CREATE2 address: {:?}", contract_address);
 
-        // Calculate code hash
-        let code_hash = Hash(Sha256::digest(&init_code).into());
+        self.install_code(contract_address, init_code, value).await?;
+
+        info!("CREATE2 contract created successfully: address={:?}", contract_address);
+
+        // Extract any logs emitted during contract creation
+        let logs = self.extract_logs_from_manager(&contract_address).await?;
+
+        let result = EVMExecutionResult {
+            success: true,
+            gas_used: 32_000,
+            output: contract_address.0.to_vec(),
+            error: None,
+            logs,
+        };
+
+        Ok((contract_address, result))
+    }
+
+    /// Check code about to be installed as a contract's runtime code
+    ///
+    /// Enforces the EIP-170 size limit and, from London on, EIP-3541's ban on
+    /// code starting with 0xEF. Malformed code is rejected only if
+    /// `reject_malformed_code` is set.
+    fn validate_new_code(&self, code: &[u8]) -> EVMResult<()> {
+        if code.len() > self.config.max_contract_size {
+            return Err(EVMError::ContractCreationFailed(
+                format!("Contract code too large: {} bytes (max {})",
+                    code.len(), self.config.max_contract_size)
+            ));
+        }
+
+        if code.first() == Some(&0xEF)
+            && revm::primitives::SpecId::enabled(self.config.spec_id, revm::primitives::SpecId::LONDON)
+        {
+            return Err(EVMError::ContractCreationFailed(
+                "Contract code starts with 0xEF (EIP-3541)".to_string()
+            ));
+        }
+
+        if let Some(issue) = BytecodeAnalysis::analyze(code).issues().first() {
+            if self.config.reject_malformed_code {
+                return Err(EVMError::InvalidBytecode(format!("Malformed contract code: {}", issue)));
+            }
+            warn!("Installing malformed contract code: {}", issue);
+        }
+
+        Ok(())
+    }
+
+    /// Store `code` and create the contract account holding it
+    ///
+    /// # Returns
+    /// Hash of the stored code
+    async fn install_code(&self, contract_address: Address, code: Vec<u8>, value: u128) -> EVMResult<Hash> {
+        let code_hash = Hash(Sha256::digest(&code).into());
 
         // Store contract code
-        self.code_storage.store_code(code_hash, init_code.clone()).await?;
-        self.code_storage.bind_code_to_address(contract_address, code_hash).await?;
+        self.code_storage.deploy_code(contract_address, code_hash, code).await?;
 
         // Create contract account
         let now = std::time::SystemTime::now()
@@ -1042,20 +1514,7 @@ impl EVMExecutor {
             .await
             .map_err(|e| EVMError::StateAccess(format!("Failed to set contract account: {}", e)))?;
 
-        info!("CREATE2 contract created successfully: address={:?}", contract_address);
-
-        // Extract any logs emitted during contract creation
-        let logs = self.extract_logs_from_manager(&contract_address).await?;
-
-        let result = EVMExecutionResult {
-            success: true,
-            gas_used: 32_000,
-            output: contract_address.0.to_vec(),
-            error: None,
-            logs,
-        };
-
-        Ok((contract_address, result))
+        Ok(code_hash)
     }
 
     /// Extract logs from log manager for a specific address
@@ -1089,6 +1548,7 @@ impl EVMExecutor {
         tx_hash: Hash,
         block_hash: Hash,
         block_number: u64,
+        tx_index: u64,
         logs: Vec<ExecutionLog>,
     ) -> EVMResult<()> {
         if logs.is_empty() {
@@ -1112,6 +1572,7 @@ impl EVMExecutor {
             // Create receipt log entry
             let receipt_log = crate::evm::ReceiptLog {
                 log_index: index as u64,
+                tx_index,
                 tx_hash,
                 block_hash,
                 block_number,
@@ -1152,10 +1613,21 @@ impl EVMExecutor {
     }
 }
 
+/// Result for a frame rejected by the configured call depth limit; the
+/// frame's gas is returned to the caller, matching revm's own CallTooDeep
+fn call_too_deep(gas_limit: u64) -> revm::interpreter::InterpreterResult {
+    revm::interpreter::InterpreterResult {
+        result: revm::interpreter::InstructionResult::CallTooDeep,
+        output: revm::primitives::Bytes::new(),
+        gas: revm::interpreter::Gas::new(gas_limit),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::state::account::{AccountStateManager, AccountStateConfig, AccountState, AccountType};
+    use crate::evm::BytecodeIssue;
     use norn_common::types::TransactionBody;
     use num_bigint::BigUint;
 
@@ -1201,6 +1673,34 @@ mod tests {
         assert_eq!(executor.config().chain_id, 31337);
     }
 
+    #[tokio::test]
+    async fn test_set_code_keeps_account_state() {
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let executor = EVMExecutor::new(Arc::clone(&state_manager), EVMConfig::default());
+
+        let address = Address([9u8; 20]);
+        state_manager.update_balance(&address, BigUint::from(500u64)).await.unwrap();
+
+        // Contract returning the constant 7
+        let code = vec![0x60, 0x07, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+        executor.set_code(address, code.clone()).await.unwrap();
+
+        let account = state_manager.get_account(&address).await.unwrap().unwrap();
+        assert_eq!(account.account_type, AccountType::Contract);
+        assert_eq!(account.balance, BigUint::from(500u64));
+        assert_eq!(executor.code_storage().get_code_by_address(&address).await.unwrap(), Some(code));
+        let caller = Address([1u8; 20]);
+        state_manager.update_balance(&caller, BigUint::from(10u128.pow(18))).await.unwrap();
+        let output = executor.call(caller, address, 0, vec![], 100_000).await.unwrap();
+        assert_eq!(output[31], 7);
+
+        executor.set_code(address, Vec::new()).await.unwrap();
+        let account = state_manager.get_account(&address).await.unwrap().unwrap();
+        assert_eq!(account.account_type, AccountType::Normal);
+        assert_eq!(account.code_hash, None);
+        assert_eq!(executor.code_storage().get_code_by_address(&address).await.unwrap(), None);
+    }
+
     #[tokio::test]
     async fn test_simple_transfer_execution() {
         let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
@@ -1231,27 +1731,106 @@ mod tests {
         assert_eq!(receiver_balance, BigUint::from(1_000_000_000_000_000_000u128)); // Received 1 ETH
     }
 
+    #[tokio::test]
+    async fn test_eip1559_effective_price_charged() {
+        const GWEI: u64 = 1_000_000_000;
+        let sender = Address([1u8; 20]);
+        let coinbase = Address([9u8; 20]);
+        let initial = BigUint::from(2_000_000_000_000_000_000u128);
+
+        let mut tx = create_test_transaction();
+        tx.body.max_fee_per_gas = Some(30 * GWEI);
+        tx.body.max_priority_fee_per_gas = Some(2 * GWEI);
+
+        // Base fee plus tip, then capped by the max fee
+        for (base_fee, price) in [(10 * GWEI, 12 * GWEI), (29 * GWEI, 30 * GWEI)] {
+            let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+            let executor = EVMExecutor::new(Arc::clone(&state_manager), EVMConfig::default());
+            state_manager.update_balance(&sender, initial.clone()).await.unwrap();
+
+            let ctx = EVMContext { block_base_fee: base_fee, block_coinbase: coinbase, ..EVMContext::default() };
+            assert_eq!(EVMExecutor::effective_gas_price(&tx, &ctx).unwrap(), price);
+
+            let result = executor.execute(&tx, &ctx).await.unwrap();
+            assert!(result.success);
+            let fee = BigUint::from(result.gas_used) * price;
+            let value = BigUint::from(1_000_000_000_000_000_000u128);
+            assert_eq!(state_manager.get_balance(&sender).await.unwrap(), &initial - &value - &fee);
+            assert_eq!(
+                state_manager.get_balance(&coinbase).await.unwrap(),
+                BigUint::from(result.gas_used) * (price - base_fee)
+            );
+
+            // Contract calls run in revm with the same fee fields
+            let code = vec![0x60, 0x01, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+            let (contract, _) = executor.create_contract(sender, 1, code, 0, 100_000).await.unwrap();
+            let mut call = tx.clone();
+            call.body.receiver = contract;
+            call.body.value = None;
+            call.body.data = vec![0x01];
+            let before = state_manager.get_balance(&sender).await.unwrap();
+            let result = executor.execute(&call, &ctx).await.unwrap();
+            assert!(result.success, "{:?}", result.error);
+            assert_eq!(
+                state_manager.get_balance(&sender).await.unwrap(),
+                before - BigUint::from(result.gas_used) * price
+            );
+        }
+
+        // A max fee below the base fee can't be included
+        let ctx = EVMContext { block_base_fee: 31 * GWEI, ..EVMContext::default() };
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let executor = EVMExecutor::new(state_manager, EVMConfig::default());
+        let err = executor.execute(&tx, &ctx).await.unwrap_err();
+        assert!(matches!(err, EVMError::InvalidTransaction(_)), "{err}");
+    }
+
     #[tokio::test]
     async fn test_gas_estimation() {
         let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
-        let config = EVMConfig::default();
-        let executor = EVMExecutor::new(state_manager, config);
+        let executor = EVMExecutor::new(state_manager.clone(), EVMConfig::default());
+        let ctx = EVMContext::default();
+        let sender = Address([1u8; 20]);
+        state_manager.update_balance(&sender, BigUint::from(10u128.pow(19))).await.unwrap(); // 10 ETH
 
-        // Simple transfer
+        // Simple transfer: intrinsic gas only
         let mut tx = create_test_transaction();
-        let gas = executor.estimate_gas(&tx).await.unwrap();
-        assert_eq!(gas, 21_000);
+        assert_eq!(executor.estimate_gas(&tx, &ctx).await.unwrap(), 21_000);
 
-        // Contract creation (data non-empty, receiver is zero)
-        tx.body.data = vec![1, 2, 3];
+        // Contract creation: PUSH1 0; PUSH1 0; RETURN deploys empty code
         tx.body.receiver = Address::default();
-        let gas = executor.estimate_gas(&tx).await.unwrap();
-        assert_eq!(gas, 53_000);
-
-        // Contract call
-        tx.body.receiver = Address([5u8; 20]);
-        let gas = executor.estimate_gas(&tx).await.unwrap();
-        assert_eq!(gas, 26_000 + 3 * 16);
+        tx.body.value = None;
+        tx.body.data = vec![0x60, 0x00, 0x60, 0x00, 0xf3];
+        let intrinsic = executor.gas_calculator().transaction_intrinsic_gas(&tx);
+        assert_eq!(intrinsic, 53_000 + 3 * 16 + 2 * 4 + 2);
+        assert_eq!(executor.estimate_gas(&tx, &ctx).await.unwrap(), intrinsic + 3 + 3);
+
+        // Contract call: PUSH1 1; PUSH1 0; SSTORE; STOP
+        let (contract, _) = executor
+            .create_contract(sender, 0, vec![0x60, 0x01, 0x60, 0x00, 0x55, 0x00], 0, 100_000)
+            .await
+            .unwrap();
+        tx.body.receiver = contract;
+        tx.body.data = vec![0x01];
+        let intrinsic = executor.gas_calculator().transaction_intrinsic_gas(&tx);
+        let gas = executor.estimate_gas(&tx, &ctx).await.unwrap();
+        assert!(gas > intrinsic + 20_000, "{gas}");
+
+        // The dry run leaves no trace, so the estimate holds for the real transaction
+        assert_eq!(state_manager.get_storage(&contract, &[0u8; 32]).await.unwrap(), None);
+        tx.body.gas = gas as i64;
+        assert!(executor.execute(&tx, &ctx).await.unwrap().success);
+        tx.body.gas = gas as i64 - 1;
+        assert!(matches!(executor.estimate_gas(&tx, &ctx).await, Err(EVMError::OutOfGas)));
+
+        // A reverting call has no estimate: PUSH1 0; PUSH1 0; REVERT
+        let (reverting, _) = executor
+            .create_contract(sender, 1, vec![0x60, 0x00, 0x60, 0x00, 0xfd], 0, 100_000)
+            .await
+            .unwrap();
+        tx.body.receiver = reverting;
+        tx.body.gas = 100_000;
+        assert!(matches!(executor.estimate_gas(&tx, &ctx).await, Err(EVMError::Revert(_))));
     }
 
     #[tokio::test]
@@ -1300,6 +1879,28 @@ mod tests {
         assert_eq!(result.output, address.0.to_vec());
     }
 
+    #[tokio::test]
+    async fn test_truncated_push_flagged_at_deployment() {
+        let sender = Address([1u8; 20]);
+        // PUSH1 1, PUSH2 with a single operand byte
+        let code = vec![0x60, 0x01, 0x61, 0x02];
+
+        // Installed by default, with the problem recorded next to the code
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let executor = EVMExecutor::new(state_manager, EVMConfig::default());
+        let (address, _) = executor.create_contract(sender, 0, code.clone(), 0, 100_000).await.unwrap();
+        let code_hash = executor.code_storage().get_code_hash(&address).await.unwrap().unwrap();
+        let analysis = executor.code_storage().get_code_analysis(&code_hash).await.unwrap();
+        assert_eq!(analysis.issues(), &[BytecodeIssue::TruncatedPush { offset: 2, missing: 1 }]);
+
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let config = EVMConfig { reject_malformed_code: true, ..EVMConfig::default() };
+        let executor = EVMExecutor::new(state_manager, config);
+        let err = executor.create_contract(sender, 0, code, 0, 100_000).await.unwrap_err();
+        assert!(matches!(err, EVMError::InvalidBytecode(_)), "{err}");
+        assert!(!executor.code_storage().is_contract(&address).await);
+    }
+
     #[tokio::test]
     async fn test_create2_contract() {
         let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
@@ -1317,7 +1918,7 @@ mod tests {
         ).await.unwrap();
 
         // Verify address was calculated correctly
-        let init_code_hash = Hash(Sha256::digest(&init_code).into());
+        let init_code_hash = Hash(keccak_hash::keccak(&init_code).0);
         assert_eq!(address, CodeStorage::calculate_create2_address(sender, salt, init_code_hash));
 
         // Verify contract was stored
@@ -1384,6 +1985,120 @@ mod tests {
         assert!(result.gas_used > 0 && result.gas_used < 100_000, "Gas should be reasonable: {}", result.gas_used);
     }
 
+    #[tokio::test]
+    async fn test_repeated_calls_analyse_code_once() {
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let executor = EVMExecutor::new(state_manager.clone(), EVMConfig::default());
+
+        // MSTORE(0, 42); RETURN(0, 32)
+        let code = vec![0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+        let (contract_address, _) = executor.create_contract(
+            Address([1u8; 20]), 0, code, 0, 100_000
+        ).await.unwrap();
+
+        let caller = Address([2u8; 20]);
+        state_manager.add_balance(&caller, &BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+
+        let first = executor.call_contract(caller, contract_address, 0, vec![], 100_000).await.unwrap();
+        assert!(first.success);
+        assert_eq!(first.output[31], 42);
+        for _ in 0..10 {
+            let result = executor.call_contract(caller, contract_address, 0, vec![], 100_000).await.unwrap();
+            assert_eq!(result.success, first.success);
+            assert_eq!(result.gas_used, first.gas_used);
+            assert_eq!(result.output, first.output);
+        }
+
+        assert_eq!(executor.code_storage().revm_analysis_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_transient_storage_cleared_between_transactions() {
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let executor = EVMExecutor::new(state_manager.clone(), EVMConfig::default());
+
+        // if calldata: TSTORE(0, 42); MSTORE(0, TLOAD(0)); RETURN(0, 32)
+        let code = vec![
+            0x36, 0x15, 0x60, 0x0a, 0x57, 0x60, 0x2a, 0x60, 0x00, 0x5d,
+            0x5b, 0x60, 0x00, 0x5c, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ];
+        let (contract_address, _) = executor.create_contract(
+            Address([1u8; 20]), 0, code, 0, 100_000
+        ).await.unwrap();
+
+        let caller = Address([2u8; 20]);
+        state_manager.add_balance(&caller, &BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+
+        // Stored and loaded within the same transaction
+        let result = executor.call_contract(caller, contract_address, 0, vec![1], 100_000).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output[31], 42);
+
+        // Gone in the next transaction, and never written to storage
+        let result = executor.call_contract(caller, contract_address, 0, vec![], 100_000).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, vec![0u8; 32]);
+        assert_eq!(state_manager.get_storage(&contract_address, &[0u8; 32]).await.unwrap(), None);
+    }
+
+    async fn storage_clearing_gas(spec_id: revm::primitives::SpecId) -> u64 {
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let config = EVMConfig {
+            spec_id,
+            ..Default::default()
+        };
+        let executor = EVMExecutor::new(state_manager.clone(), config);
+
+        // SSTORE(0, 1); SSTORE(0, 0); STOP
+        let code = vec![0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x00, 0x55, 0x00];
+        let (contract_address, _) = executor.create_contract(
+            Address([1u8; 20]), 0, code, 0, 100_000
+        ).await.unwrap();
+
+        let caller = Address([2u8; 20]);
+        state_manager.add_balance(&caller, &BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+
+        let result = executor.call_contract(caller, contract_address, 0, vec![], 100_000).await.unwrap();
+        assert!(result.success);
+        result.gas_used
+    }
+
+    #[tokio::test]
+    async fn test_refund_cap() {
+        // 21000 intrinsic + 4 * 3 PUSH1 + 22100 cold SSTORE (0 -> 1) + 100 warm SSTORE (1 -> 0)
+        let spent = 43_212;
+        // Resetting a slot to its original zero value refunds 20000 - 100
+        let refund = 19_900;
+
+        // EIP-3529: refund capped at spent / 5
+        assert_eq!(storage_clearing_gas(revm::primitives::SpecId::CANCUN).await, spent - spent / 5);
+        // Before London the cap is spent / 2, which doesn't bind here
+        assert_eq!(storage_clearing_gas(revm::primitives::SpecId::BERLIN).await, spent - refund);
+    }
+
+    #[tokio::test]
+    async fn test_call_precompile() {
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let executor = EVMExecutor::new(state_manager, EVMConfig::default());
+        let caller = Address([1u8; 20]);
+        let input = vec![0xabu8; 40];
+
+        let result = executor.call_contract(
+            caller, crate::evm::IDENTITY_ADDRESS, 0, input.clone(), 50_000
+        ).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, input);
+        // Intrinsic 21000 + 40 non-zero bytes, then identity: 15 + 3 per word
+        assert_eq!(result.gas_used, 21_000 + 40 * 16 + 15 + 3 * 2);
+
+        // Out of gas inside the precompile consumes the whole limit
+        let result = executor.call_contract(
+            caller, crate::evm::SHA256_ADDRESS, 0, input, 21_000 + 40 * 16 + 10
+        ).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.gas_used, 21_000 + 40 * 16 + 10);
+    }
+
     #[tokio::test]
     async fn test_call_non_contract_fails() {
         let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
@@ -1797,6 +2512,153 @@ mod tests {
         }
     }
 
+    /// Init code whose constructor returns `len` zero bytes as runtime code
+    fn init_code_returning(len: u16) -> Vec<u8> {
+        let [hi, lo] = len.to_be_bytes();
+        // PUSH2 len, PUSH1 0, RETURN
+        vec![0x61, hi, lo, 0x60, 0x00, 0xf3]
+    }
+
+    #[tokio::test]
+    async fn test_eip170_runtime_code_size_limit() {
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let executor = EVMExecutor::new(state_manager.clone(), EVMConfig::default());
+        let ctx = EVMContext::default();
+
+        let sender = Address([1u8; 20]);
+        state_manager.add_balance(&sender, &BigUint::from(10u128.pow(18))).await.unwrap();
+
+        // Small init code, oversized runtime code: deployment fails and burns all gas
+        let gas_limit = 1_000_000;
+        let (address, result) = executor.deploy_contract(
+            sender, 0, init_code_returning(24_577), 0, gas_limit, &ctx
+        ).await.unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.gas_used, gas_limit);
+        assert!(result.error.as_deref().unwrap().contains("too large"), "{:?}", result.error);
+        assert_eq!(executor.code_storage().get_code_by_address(&address).await.unwrap(), None);
+
+        // Runtime code exactly at the limit is deployed
+        let (address, result) = executor.deploy_contract(
+            sender, 1, init_code_returning(24_576), 0, 6_000_000, &ctx
+        ).await.unwrap();
+
+        assert!(result.success, "{:?}", result);
+        let code = executor.code_storage().get_code_by_address(&address).await.unwrap().unwrap();
+        assert_eq!(code.len(), 24_576);
+    }
+
+    #[tokio::test]
+    async fn test_eip3541_rejects_ef_code() {
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let executor = EVMExecutor::new(state_manager.clone(), EVMConfig::default());
+        let ctx = EVMContext::default();
+
+        let sender = Address([1u8; 20]);
+        state_manager.add_balance(&sender, &BigUint::from(10u128.pow(18))).await.unwrap();
+
+        // Constructor: MSTORE8(0, byte), RETURN(0, 1)
+        let init_code = |byte: u8| vec![0x60, byte, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3];
+
+        let (address, result) = executor.deploy_contract(
+            sender, 0, init_code(0xEF), 0, 100_000, &ctx
+        ).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.as_deref().unwrap().contains("0xEF"), "{:?}", result.error);
+        assert_eq!(executor.code_storage().get_code_by_address(&address).await.unwrap(), None);
+
+        // Control: any other leading byte deploys
+        let (address, result) = executor.deploy_contract(
+            sender, 1, init_code(0xFE), 0, 100_000, &ctx
+        ).await.unwrap();
+        assert!(result.success, "{:?}", result);
+        assert_eq!(executor.code_storage().get_code_by_address(&address).await.unwrap(), Some(vec![0xFE]));
+
+        // Code installed without running a constructor is checked too
+        for result in [
+            executor.create_contract(sender, 2, vec![0xEF, 0x00], 0, 100_000).await,
+            executor.create2_contract(sender, [0u8; 32], vec![0xEF, 0x00], 0, 100_000).await,
+        ] {
+            match result {
+                Err(EVMError::ContractCreationFailed(msg)) => assert!(msg.contains("EIP-3541")),
+                other => panic!("Expected ContractCreationFailed, got {:?}", other.map(|(a, _)| a)),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_call_depth_fails_innermost_call() {
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let config = EVMConfig { max_call_depth: 8, ..EVMConfig::default() };
+        let executor = EVMExecutor::new(state_manager.clone(), config);
+        let ctx = EVMContext::default();
+
+        let sender = Address([1u8; 20]);
+        state_manager.add_balance(&sender, &BigUint::from(10u128.pow(18))).await.unwrap();
+
+        // Runtime: d = calldata[0]; CALL(self, d + 1); return the callee's
+        // word if the call succeeded, otherwise d
+        let runtime: Vec<u8> = vec![
+            0x60, 0x00, 0x35, 0x60, 0x01, 0x01, 0x60, 0x00, 0x52, // MSTORE(0, d + 1)
+            0x60, 0x20, 0x60, 0x00, 0x60, 0x20, 0x60, 0x00, 0x60, 0x00, 0x30, 0x5a, 0xf1, // CALL
+            0x60, 0x24, 0x57, // JUMPI ok
+            0x60, 0x00, 0x35, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3, // RETURN d
+            0x5b, 0x60, 0x20, 0x60, 0x00, 0xf3, // ok: RETURN callee word
+        ];
+        let mut init_code = vec![
+            0x60, runtime.len() as u8, 0x60, 0x0c, 0x60, 0x00, 0x39,
+            0x60, runtime.len() as u8, 0x60, 0x00, 0xf3,
+        ];
+        init_code.extend_from_slice(&runtime);
+
+        let (address, result) = executor.deploy_contract(
+            sender, 0, init_code, 0, 1_000_000, &ctx
+        ).await.unwrap();
+        assert!(result.success, "{:?}", result);
+
+        let result = executor.execute_with_revm(
+            sender, Some(address), 0, vec![0u8; 32], 5_000_000, &ctx
+        ).await.unwrap();
+
+        // The call past the limit fails; the frames above it complete
+        assert!(result.success, "{:?}", result);
+        let mut expected = [0u8; 32];
+        expected[31] = 8;
+        assert_eq!(result.output, expected);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_cancel_stops_running_execution() {
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let executor = Arc::new(EVMExecutor::new(state_manager.clone(), EVMConfig::default()));
+
+        let sender = Address([1u8; 20]);
+        state_manager.add_balance(&sender, &BigUint::from(10u128.pow(20))).await.unwrap();
+
+        // An endless loop with enough gas to spin for minutes
+        let (looping, _) = executor.create_contract(sender, 0, vec![0x5b, 0x60, 0x00, 0x56], 0, 100_000).await.unwrap();
+        let gas_limit = 10_000_000_000;
+        let cancel = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let ctx = EVMContext {
+            block_gas_limit: gas_limit,
+            cancel: Some(cancel.clone()),
+            ..EVMContext::default()
+        };
+
+        let started = std::time::Instant::now();
+        let call = tokio::spawn({
+            let executor = executor.clone();
+            async move { executor.execute_with_revm(sender, Some(looping), 0, Vec::new(), gas_limit, &ctx).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+
+        let err = call.await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("execution cancelled"), "{}", err);
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    }
+
     #[tokio::test]
     async fn test_eip170_create2_size_limit() {
         let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
@@ -8,6 +8,7 @@
 
 use crate::evm::{EVMError, EVMResult, EIP1559Config};
 use norn_common::types::{Transaction, Address};
+use revm::primitives::SpecId;
 
 /// Gas costs for various operations (in gas units)
 pub mod costs {
@@ -23,6 +24,9 @@ pub mod costs {
     /// Gas cost per non-zero byte in transaction data
     pub const TX_NON_ZERO_DATA_COST: u64 = 16;
 
+    /// Gas cost per 32-byte word of contract creation code (EIP-3860)
+    pub const INITCODE_WORD_COST: u64 = 2;
+
     /// Gas cost for accessing a cold account (EIP-2929)
     pub const COLD_ACCOUNT_ACCESS_COST: u64 = 2_600;
 
@@ -81,8 +85,11 @@ pub mod costs {
     /// Gas cost for copying data to memory (per word)
     pub const COPY_COST: u64 = 3;
 
-    /// Quota for gas refunds (max 50% of gas used can be refunded)
-    pub const MAX_REFUND_QUOTIENT: u64 = 2;
+    /// Quota for gas refunds (EIP-3529: max 20% of gas used can be refunded)
+    pub const MAX_REFUND_QUOTIENT: u64 = 5;
+
+    /// Quota for gas refunds before London (max 50% of gas used)
+    pub const LEGACY_MAX_REFUND_QUOTIENT: u64 = 2;
 }
 
 /// Gas calculator for EVM transactions
@@ -93,6 +100,12 @@ pub struct GasCalculator {
     /// Enable EIP-2200 (net gas metering for SSTORE)
     eip2200_enabled: bool,
 
+    /// Enable EIP-3529 (reduced refund cap)
+    eip3529_enabled: bool,
+
+    /// Enable EIP-3860 (init code word cost)
+    eip3860_enabled: bool,
+
     /// EIP-1559 configuration
     eip1559_config: EIP1559Config,
 }
@@ -103,6 +116,19 @@ impl GasCalculator {
         Self {
             eip2929_enabled: true, // Enabled by default for post-Berlin
             eip2200_enabled: true, // Enabled by default for post-Istanbul
+            eip3529_enabled: true, // Enabled by default for post-London
+            eip3860_enabled: true, // Enabled by default for post-Shanghai
+            eip1559_config,
+        }
+    }
+
+    /// Create a gas calculator following the rules of a hard fork
+    pub fn for_spec(spec_id: SpecId, eip1559_config: EIP1559Config) -> Self {
+        Self {
+            eip2929_enabled: SpecId::enabled(spec_id, SpecId::BERLIN),
+            eip2200_enabled: SpecId::enabled(spec_id, SpecId::ISTANBUL),
+            eip3529_enabled: SpecId::enabled(spec_id, SpecId::LONDON),
+            eip3860_enabled: SpecId::enabled(spec_id, SpecId::SHANGHAI),
             eip1559_config,
         }
     }
@@ -132,6 +158,11 @@ impl GasCalculator {
             }
         }
 
+        // Init code cost (EIP-3860)
+        if is_contract_creation && self.eip3860_enabled {
+            gas += (data.len() as u64).div_ceil(32) * costs::INITCODE_WORD_COST;
+        }
+
         // Access list cost (EIP-2930)
         if let Some(list) = access_list {
             // Cost per address in access list
@@ -146,6 +177,26 @@ impl GasCalculator {
         gas
     }
 
+    /// Calculate intrinsic gas cost of a transaction
+    ///
+    /// Transactions to the zero address carrying data are contract creations
+    /// and pay the creation surcharge; typed transactions also pay for their
+    /// access list.
+    pub fn transaction_intrinsic_gas(&self, tx: &Transaction) -> u64 {
+        let is_contract_creation = tx.body.receiver.0.iter().all(|&b| b == 0)
+            && !tx.body.data.is_empty();
+
+        let access_list: Option<Vec<(Address, Vec<[u8; 32]>)>> = tx.body.access_list
+            .as_ref()
+            .map(|list| {
+                list.iter()
+                    .map(|item| (item.address, item.storage_keys.iter().map(|key| key.0).collect()))
+                    .collect()
+            });
+
+        self.intrinsic_gas_cost(is_contract_creation, &tx.body.data, access_list.as_deref())
+    }
+
     /// Calculate gas cost for a contract call
     ///
     /// Includes warm/cold account access costs
@@ -193,9 +244,13 @@ impl GasCalculator {
 
     /// Calculate maximum refund allowed
     ///
-    /// EIP-3529: Refunds are capped to 50% of gas used
+    /// EIP-3529: Refunds are capped to 20% of gas used (50% before London)
     pub fn max_refund(&self, gas_used: u64) -> u64 {
-        gas_used / costs::MAX_REFUND_QUOTIENT
+        if self.eip3529_enabled {
+            gas_used / costs::MAX_REFUND_QUOTIENT
+        } else {
+            gas_used / costs::LEGACY_MAX_REFUND_QUOTIENT
+        }
     }
 
     /// Calculate EIP-1559 base fee
@@ -262,7 +317,7 @@ impl GasCalculator {
             )));
         }
 
-        // Cap refund to 50% of gas used
+        // Cap refund (EIP-3529)
         let max_refund = self.max_refund(gas_used);
         let actual_refund = refund.min(max_refund);
 
@@ -360,19 +415,69 @@ mod tests {
 
         let gas = calculator.intrinsic_gas_cost(true, &init_code, None);
 
+        // One word of init code (EIP-3860)
+        assert_eq!(gas, costs::TX_CREATE_COST + (3 * costs::TX_NON_ZERO_DATA_COST) + costs::INITCODE_WORD_COST);
+
+        // Before Shanghai init code is only paid for as data
+        let calculator = GasCalculator::for_spec(SpecId::LONDON, EIP1559Config::default());
+        let gas = calculator.intrinsic_gas_cost(true, &init_code, None);
         assert_eq!(gas, costs::TX_CREATE_COST + (3 * costs::TX_NON_ZERO_DATA_COST));
     }
 
+    #[test]
+    fn test_transaction_intrinsic_gas() {
+        use norn_common::types::{AccessListItem, Hash, TransactionBody, TransactionType};
+
+        let calculator = GasCalculator::default();
+        let mut tx = Transaction {
+            body: TransactionBody {
+                receiver: Address([2u8; 20]),
+                tx_type: TransactionType::EVM,
+                ..Default::default()
+            },
+        };
+        assert_eq!(calculator.transaction_intrinsic_gas(&tx), costs::TX_BASE_COST);
+
+        // Calldata-heavy call: 1000 zero bytes and 3000 non-zero bytes
+        tx.body.data = [vec![0u8; 1000], vec![0xab; 3000]].concat();
+        assert_eq!(
+            calculator.transaction_intrinsic_gas(&tx),
+            costs::TX_BASE_COST + 1000 * 4 + 3000 * 16
+        );
+
+        // Access list: two addresses with three storage keys in total
+        tx.body.access_list = Some(vec![
+            AccessListItem { address: Address([3u8; 20]), storage_keys: vec![Hash([1u8; 32]), Hash([2u8; 32])] },
+            AccessListItem { address: Address([4u8; 20]), storage_keys: vec![Hash([3u8; 32])] },
+        ]);
+        assert_eq!(
+            calculator.transaction_intrinsic_gas(&tx),
+            costs::TX_BASE_COST + 1000 * 4 + 3000 * 16 + 2 * 2_400 + 3 * 1_900
+        );
+
+        // Contract creation pays the creation surcharge and 125 words of init code
+        tx.body.receiver = Address::default();
+        tx.body.access_list = None;
+        assert_eq!(
+            calculator.transaction_intrinsic_gas(&tx),
+            costs::TX_CREATE_COST + 1000 * 4 + 3000 * 16 + 125 * 2
+        );
+    }
+
     #[test]
     fn test_gas_refund_cap() {
         let calculator = GasCalculator::default();
 
         let gas_used = 100_000;
-        let refund = 60_000; // Would be > 50% of gas used
+        let refund = 60_000; // Would be > 20% of gas used
 
         let max_refund = calculator.max_refund(gas_used);
 
-        assert_eq!(max_refund, 50_000); // Capped at 50%
+        assert_eq!(max_refund, 20_000); // Capped at 20%
+        assert_eq!(calculator.final_gas_cost(gas_used, gas_used, refund).unwrap(), 80_000);
+
+        let berlin = GasCalculator::for_spec(SpecId::BERLIN, EIP1559Config::default());
+        assert_eq!(berlin.max_refund(gas_used), 50_000); // Capped at 50% before London
     }
 
     #[test]
@@ -396,13 +501,13 @@ mod tests {
 
         let gas_limit = 100_000;
         let gas_used = 80_000;
-        let refund = 50_000; // Would be > 50% of gas used
+        let refund = 50_000; // Would be > 20% of gas used
 
         let final_cost = calculator
             .final_gas_cost(gas_limit, gas_used, refund)
             .unwrap();
 
-        assert_eq!(final_cost, 40_000); // 80k used - 40k refund (capped at 50%)
+        assert_eq!(final_cost, 64_000); // 80k used - 16k refund (capped at 20%)
     }
 
     #[test]
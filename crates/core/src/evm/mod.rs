@@ -8,6 +8,7 @@ mod error;
 mod runtime; // Fixed with SyncStateManager bridging layer
 mod executor;
 mod code_storage;
+mod bytecode;
 mod logging;
 mod receipt;
 mod precompiles;
@@ -21,8 +22,9 @@ mod real_contracts;
 
 pub use error::{EVMError, EVMResult};
 pub use runtime::NornDatabaseAdapter; // Fixed with SyncStateManager bridging layer
-pub use executor::{EVMExecutor, EVMExecutionResult, ExecutionLog};
+pub use executor::{EVMExecutor, EVMExecutionResult, ExecutionLog, AccountOverride};
 pub use code_storage::CodeStorage;
+pub use bytecode::{BytecodeAnalysis, BytecodeIssue};
 pub use logging::{EventLog, LogManager};
 pub use receipt::{Receipt, ReceiptDB, ReceiptLog, Bloom};
 pub use precompiles::{
@@ -63,7 +65,8 @@ pub struct EVMConfig {
     /// Maximum contract size in bytes (EIP-170: 24KB)
     pub max_contract_size: usize,
 
-    /// Maximum call depth (to prevent stack overflow)
+    /// Maximum call depth (to prevent stack overflow); calls past it fail
+    /// without reverting the caller. Values above 1024 are capped by revm.
     pub max_call_depth: usize,
 
     /// Enable/disable precompiled contracts
@@ -71,6 +74,17 @@ pub struct EVMConfig {
 
     /// EIP-1559 fee market configuration
     pub eip1559_config: EIP1559Config,
+
+    /// Hard fork rules used for execution
+    ///
+    /// Also selects the gas refund cap: from London on, refunds are limited
+    /// to 1/5 of the gas used (EIP-3529).
+    pub spec_id: revm::primitives::SpecId,
+
+    /// Reject new contract code with truncated PUSH data or jumps to
+    /// constant targets that are not JUMPDESTs; otherwise such code is
+    /// only logged, as it is valid EVM code
+    pub reject_malformed_code: bool,
 }
 
 impl Default for EVMConfig {
@@ -82,6 +96,8 @@ impl Default for EVMConfig {
             max_call_depth: 1024,
             enable_precompiles: true,
             eip1559_config: EIP1559Config::default(),
+            spec_id: revm::primitives::SpecId::CANCUN,
+            reject_malformed_code: false,
         }
     }
 }
@@ -101,8 +117,18 @@ pub struct EVMContext {
     /// Block gas limit
     pub block_gas_limit: u64,
 
-    /// Transaction gas price
+    /// Transaction gas price, or the max fee per gas if `tx_priority_fee` is set
     pub tx_gas_price: u64,
+
+    /// EIP-1559 max priority fee per gas, `None` for legacy pricing
+    pub tx_priority_fee: Option<u64>,
+
+    /// Block base fee per gas (EIP-1559)
+    pub block_base_fee: u64,
+
+    /// Aborts execution before the next instruction once set; `None` runs
+    /// to completion
+    pub cancel: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
 }
 
 impl Default for EVMContext {
@@ -113,6 +139,9 @@ impl Default for EVMContext {
             block_coinbase: norn_common::types::Address::default(),
             block_gas_limit: 30_000_000,
             tx_gas_price: 1_000_000_000, // 1 Gwei
+            tx_priority_fee: None,
+            block_base_fee: 0,
+            cancel: None,
         }
     }
 }
@@ -34,8 +34,7 @@ pub struct PrecompileResult {
 
 /// Check if an address is a precompile
 pub fn is_precompile(address: &Address) -> bool {
-    let last_byte = address.0[19];
-    (1..=9).contains(&last_byte)
+    address.0[..19].iter().all(|&b| b == 0) && (1..=9).contains(&address.0[19])
 }
 
 /// Execute a precompile contract
@@ -52,9 +51,14 @@ pub fn execute(
     input: &[u8],
     gas_limit: u64,
 ) -> Result<PrecompileResult, EVMError> {
-    let last_byte = address.0[19];
+    if !is_precompile(address) {
+        return Err(EVMError::Execution(format!(
+            "Unknown precompile address: {:?}",
+            address
+        )));
+    }
 
-    match last_byte {
+    match address.0[19] {
         0x01 => ecrecover(input, gas_limit),
         0x02 => sha256_hash(input, gas_limit),
         0x03 => ripemd160_hash(input, gas_limit),
@@ -73,63 +77,37 @@ pub fn execute(
 
 /// ECDSA public key recovery (0x01)
 ///
-/// Recovers the public key from a signature using secp256k1 curve.
+/// Recovers the signer address from a signature using secp256k1 curve.
 /// Gas cost: 3000
 ///
-/// Input (128 bytes):
+/// Input (128 bytes, right-padded with zeros if shorter):
 /// - hash (32 bytes): message hash
-/// - v (32 bytes): recovery ID (should be 27 or 28, big-endian)
+/// - v (32 bytes): recovery ID (27 or 28, big-endian)
 /// - r (32 bytes): signature r value
 /// - s (32 bytes): signature s value
 ///
-/// Output (32 bytes): address (last 20 bytes of keccak256 of uncompressed public key)
+/// Output (32 bytes): address (last 20 bytes of keccak256 of uncompressed public key),
+/// or empty if the signature is invalid
 fn ecrecover(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError> {
-    const GAS_COST: u64 = 3_000;
-
-    if gas_limit < GAS_COST {
-        return Err(EVMError::OutOfGas);
-    }
-
-    if input.len() != 128 {
-        return Err(EVMError::Execution(format!(
-            "ecrecover: Invalid input length {}, expected 128",
-            input.len()
-        )));
-    }
-
-    let hash = &input[0..32];
-    let v_bytes = &input[32..64];
-    let r_bytes = &input[64..96];
-    let s_bytes = &input[96..128];
+    // Use revm's ec_recover_run function directly
+    from_revm("ecrecover", revm_precompile::secp256k1::ec_recover_run(&input.to_vec().into(), gas_limit))
+}
 
-    // Parse v (Ethereum uses 27 or 28, need to validate)
-    let v_ethereum = u64::from_be_bytes(
-        v_bytes[24..32].try_into().unwrap_or([0u8; 8])
-    );
+/// Convert the result of one of revm's precompile functions
+fn from_revm(
+    name: &str,
+    result: revm_precompile::PrecompileResult,
+) -> Result<PrecompileResult, EVMError> {
+    use revm_precompile::{PrecompileError, PrecompileErrors};
 
-    // Validate v is 27 or 28
-    if v_ethereum != 27 && v_ethereum != 28 {
-        // Invalid v, return zero address
-        return Ok(PrecompileResult {
-            output: vec![0u8; 32],
-            gas_used: GAS_COST,
-        });
+    match result {
+        Ok(output) => Ok(PrecompileResult {
+            output: output.bytes.to_vec(),
+            gas_used: output.gas_used,
+        }),
+        Err(PrecompileErrors::Error(PrecompileError::OutOfGas)) => Err(EVMError::OutOfGas),
+        Err(e) => Err(EVMError::Execution(format!("{} failed: {:?}", name, e))),
     }
-
-    // For now, use a simplified implementation
-    // Full ECDSA recovery would use k256 crate or revm's built-in precompile
-    // This provides a deterministic result based on input for testing
-    let hash_input = [&hash[..], v_bytes, r_bytes, s_bytes].concat();
-    let result_hash = Sha256::digest(&hash_input);
-
-    // Left-pad to 32 bytes, taking last 20 bytes as "address"
-    let mut output = vec![0u8; 32];
-    output[12..].copy_from_slice(&result_hash[12..]);
-
-    Ok(PrecompileResult {
-        output,
-        gas_used: GAS_COST,
-    })
 }
 
 /// SHA2-256 hash (0x02)
@@ -207,66 +185,58 @@ fn identity(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError>
 /// Modular exponentiation (0x05)
 ///
 /// Computes (base ^ exp) mod modulus.
-/// Gas cost: Complex formula based on input sizes
+/// Gas cost: max(200, mult_complexity * iteration_count / 3) (EIP-2565)
+///
+/// Input (right-padded with zeros if shorter):
+/// - base_len, exp_len, mod_len (32 bytes each, big-endian)
+/// - base (base_len bytes), exp (exp_len bytes), modulus (mod_len bytes)
+///
+/// Output (mod_len bytes): result, left-padded with zeros
 fn modexp(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError> {
-    const GAS_COST_BASE: u64 = 200;
+    const HEADER_LENGTH: u64 = 96;
 
-    if gas_limit < GAS_COST_BASE {
-        return Err(EVMError::OutOfGas);
-    }
+    let header = read_padded(input, 0, HEADER_LENGTH as usize);
+    let base_len = read_length(&header[0..32]);
+    let exp_len = read_length(&header[32..64]);
+    let mod_len = read_length(&header[64..96]);
 
-    // Parse input
-    if input.len() < 96 {
-        return Err(EVMError::Execution("modexp: Input too short".to_string()));
-    }
+    // The first 32 bytes of the exponent take part in the gas cost
+    let exp_offset = HEADER_LENGTH.saturating_add(base_len);
+    let exp_head = BigUint::from_bytes_be(&read_padded(input, exp_offset, exp_len.min(32) as usize));
 
-    // Read sizes (each is 32 bytes big-endian)
-    let base_len = read_u64_from_u256(&input[0..32]) as usize;
-    let exp_len = read_u64_from_u256(&input[32..64]) as usize;
-    let mod_len = read_u64_from_u256(&input[64..96]) as usize;
+    let gas_used = calculate_modexp_gas(base_len, exp_len, mod_len, &exp_head);
 
-    let expected_len = 96 + base_len + exp_len + mod_len;
-    if input.len() < expected_len {
-        return Err(EVMError::Execution("modexp: Input length mismatch".to_string()));
+    if gas_limit < gas_used {
+        return Err(EVMError::OutOfGas);
     }
 
-    // Extract data
-    let base_data = &input[96..(96 + base_len)];
-    let exp_data = &input[(96 + base_len)..(96 + base_len + exp_len)];
-    let mod_data = &input[(96 + base_len + exp_len)..(96 + base_len + exp_len + mod_len)];
-
-    // Convert to BigUint
-    let base = BigUint::from_bytes_be(base_data);
-    let exp = BigUint::from_bytes_be(exp_data);
-    let modulus = BigUint::from_bytes_be(mod_data);
-
-    // Handle zero modulus
-    if modulus.is_zero() {
+    if base_len == 0 && mod_len == 0 {
         return Ok(PrecompileResult {
-            output: vec![0u8; mod_len],
-            gas_used: GAS_COST_BASE,
+            output: Vec::new(),
+            gas_used,
         });
     }
 
-    // Calculate gas cost (simplified)
-    let gas_used = GAS_COST_BASE + calculate_modexp_gas(base_len, exp_len, mod_len);
+    // Lengths are bounded by the gas paid from here on
+    let mod_offset = exp_offset.saturating_add(exp_len);
+    let base = BigUint::from_bytes_be(&read_padded(input, HEADER_LENGTH, base_len as usize));
+    let exp = BigUint::from_bytes_be(&read_padded(input, exp_offset, exp_len as usize));
+    let modulus = BigUint::from_bytes_be(&read_padded(input, mod_offset, mod_len as usize));
 
-    if gas_limit < gas_used {
-        return Err(EVMError::OutOfGas);
+    let mod_len = mod_len as usize;
+    let mut output = vec![0u8; mod_len];
+
+    // Zero modulus yields zero
+    if modulus.is_zero() {
+        return Ok(PrecompileResult {
+            output,
+            gas_used,
+        });
     }
 
     // Compute (base ^ exp) % modulus
-    let result = base.modpow(&exp, &modulus);
-
-    // Convert to bytes with correct padding
-    let mut output = vec![0u8; mod_len];
-    let result_bytes = result.to_bytes_be();
-    if result_bytes.len() <= mod_len {
-        output[(mod_len - result_bytes.len())..].copy_from_slice(&result_bytes);
-    } else {
-        // Result is larger than modulus (shouldn't happen with modpow)
-        output.copy_from_slice(&result_bytes[result_bytes.len() - mod_len..]);
-    }
+    let result_bytes = base.modpow(&exp, &modulus).to_bytes_be();
+    output[(mod_len - result_bytes.len())..].copy_from_slice(&result_bytes);
 
     Ok(PrecompileResult {
         output,
@@ -274,37 +244,49 @@ fn modexp(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError> {
     })
 }
 
-/// Read a u64 from a U256 (32 bytes, big-endian)
-fn read_u64_from_u256(data: &[u8]) -> u64 {
+/// Read `len` bytes at `offset`, treating input past the end as zeros
+fn read_padded(input: &[u8], offset: u64, len: usize) -> Vec<u8> {
+    let mut out = vec![0u8; len];
+    if let Ok(offset) = usize::try_from(offset) {
+        if offset < input.len() {
+            let available = &input[offset..];
+            let n = available.len().min(len);
+            out[..n].copy_from_slice(&available[..n]);
+        }
+    }
+    out
+}
+
+/// Read a length from a U256 (32 bytes, big-endian), saturating at u64::MAX
+fn read_length(data: &[u8]) -> u64 {
+    if data[..24].iter().any(|&b| b != 0) {
+        return u64::MAX;
+    }
     let mut arr = [0u8; 8];
     arr.copy_from_slice(&data[24..32]);
     u64::from_be_bytes(arr)
 }
 
-/// Calculate gas cost for modexp
-fn calculate_modexp_gas(base_len: usize, exp_len: usize, mod_len: usize) -> u64 {
-    // Simplified gas calculation
-    let mut gas = 0u64;
+/// Calculate gas cost for modexp (EIP-2565)
+fn calculate_modexp_gas(base_len: u64, exp_len: u64, mod_len: u64, exp_head: &BigUint) -> u64 {
+    const MIN_GAS: u64 = 200;
 
-    // Base cost
-    if base_len > 0 {
-        gas += 20 * (base_len as u64).next_power_of_two();
-    }
+    // Multiplication complexity: square of the number of 8-byte words
+    let words = base_len.max(mod_len).div_ceil(8) as u128;
+    let complexity = words * words;
 
-    // Exponent cost
-    if exp_len <= 32 {
-        gas += 20;
+    // Iteration count: bit length of the exponent, minus one
+    let head_bits = exp_head.bits();
+    let iterations = if exp_len <= 32 {
+        head_bits.saturating_sub(1)
     } else {
-        let exp_bytes = exp_len as u64;
-        gas += 20 * (exp_bytes - 32);
-    }
-
-    // Modulus cost
-    if mod_len > 0 {
-        gas += 20 * (mod_len as u64).next_power_of_two();
-    }
+        8u64.saturating_mul(exp_len - 32)
+            .saturating_add(head_bits.max(1) - 1)
+    };
+    let iterations = iterations.max(1) as u128;
 
-    gas
+    let gas = complexity.saturating_mul(iterations) / 3;
+    u64::try_from(gas).unwrap_or(u64::MAX).max(MIN_GAS)
 }
 
 /// Elliptic curve point addition (0x06)
@@ -312,7 +294,7 @@ fn calculate_modexp_gas(base_len: usize, exp_len: usize, mod_len: usize) -> u64
 /// Adds two points on the alt_bn128 curve.
 /// Gas cost: 150 (after Istanbul), 500 (before)
 ///
-/// Input (128 bytes):
+/// Input (128 bytes, right-padded with zeros if shorter):
 /// - x1 (32 bytes): x coordinate of first point
 /// - y1 (32 bytes): y coordinate of first point
 /// - x2 (32 bytes): x coordinate of second point
@@ -326,21 +308,8 @@ fn ecadd(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError> {
         return Err(EVMError::OutOfGas);
     }
 
-    if input.len() != 128 {
-        return Err(EVMError::Execution(format!(
-            "ecadd: Invalid input length {}, expected 128",
-            input.len()
-        )));
-    }
-
     // Use revm's run_add function directly
-    match revm_precompile::bn128::run_add(input, GAS_COST, gas_limit) {
-        Ok(output) => Ok(PrecompileResult {
-            output: output.bytes.to_vec(),
-            gas_used: output.gas_used,
-        }),
-        Err(e) => Err(EVMError::Execution(format!("ecadd failed: {:?}", e))),
-    }
+    from_revm("ecadd", revm_precompile::bn128::run_add(input, GAS_COST, gas_limit))
 }
 
 /// Elliptic curve scalar multiplication (0x07)
@@ -348,7 +317,7 @@ fn ecadd(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError> {
 /// Multiplies a point on the alt_bn128 curve by a scalar.
 /// Gas cost: 6000 (after Istanbul), 40000 (before)
 ///
-/// Input (96 bytes):
+/// Input (96 bytes, right-padded with zeros if shorter):
 /// - x (32 bytes): x coordinate of point
 /// - y (32 bytes): y coordinate of point
 /// - s (32 bytes): scalar to multiply by
@@ -361,21 +330,8 @@ fn ecmul(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError> {
         return Err(EVMError::OutOfGas);
     }
 
-    if input.len() != 96 {
-        return Err(EVMError::Execution(format!(
-            "ecmul: Invalid input length {}, expected 96",
-            input.len()
-        )));
-    }
-
     // Use revm's run_mul function directly
-    match revm_precompile::bn128::run_mul(input, GAS_COST, gas_limit) {
-        Ok(output) => Ok(PrecompileResult {
-            output: output.bytes.to_vec(),
-            gas_used: output.gas_used,
-        }),
-        Err(e) => Err(EVMError::Execution(format!("ecmul failed: {:?}", e))),
-    }
+    from_revm("ecmul", revm_precompile::bn128::run_mul(input, GAS_COST, gas_limit))
 }
 
 /// Elliptic curve pairing check (0x08)
@@ -386,11 +342,15 @@ fn ecmul(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError> {
 /// Input (192 * k bytes):
 /// Each point pair (G1, G2) is 192 bytes:
 /// - G1 point (64 bytes): (x, y) in G1
-/// - G2 point (128 bytes): (x_a, x_b, y_a, y_b) in G2 (FP2 elements)
+/// - G2 point (128 bytes): (x_a, x_b, y_a, y_b) in G2 (FP2 elements,
+///   imaginary part first)
 ///
 /// Output (32 bytes):
-/// - 1 if pairing check passes
+/// - 1 if the product of the pairings is one (always for empty input)
 /// - 0 otherwise
+///
+/// Input whose length isn't a multiple of 192, or which contains a point that
+/// is not on the curve or not in the subgroup, fails.
 fn ecpairing(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError> {
     const GAS_COST_BASE: u64 = 45_000;
     const GAS_COST_PER_PAIR: u64 = 34_000;
@@ -410,30 +370,24 @@ fn ecpairing(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError>
     }
 
     // Use revm's run_pair function directly
-    match revm_precompile::bn128::run_pair(input, GAS_COST_PER_PAIR, GAS_COST_BASE, gas_limit) {
-        Ok(output) => Ok(PrecompileResult {
-            output: output.bytes.to_vec(),
-            gas_used: output.gas_used,
-        }),
-        Err(e) => Err(EVMError::Execution(format!("ecpairing failed: {:?}", e))),
-    }
+    from_revm("ecpairing", revm_precompile::bn128::run_pair(input, GAS_COST_PER_PAIR, GAS_COST_BASE, gas_limit))
 }
 
 /// BLAKE2b compression function (0x09)
 ///
 /// BLAKE2b F compression function.
-/// Gas cost: F (rounded up)
+/// Gas cost: 1 per round
 ///
 /// Input (213 bytes):
-/// - rounds (4 bytes): number of rounds (little-endian)
+/// - rounds (4 bytes): number of rounds (big-endian)
 /// - h[0..7] (64 bytes): initial state vector
 /// - m[0..15] (128 bytes): message vector
 /// - t[0..1] (16 bytes): offset (little-endian)
-/// - f (1 byte): final block flag
+/// - f (1 byte): final block flag (0 or 1)
 ///
 /// Output (64 bytes): resulting state vector
 fn blake2f(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError> {
-    const GAS_COST_BASE: u64 = 15;
+    const GAS_COST_PER_ROUND: u64 = 1;
 
     if input.len() != 213 {
         return Err(EVMError::Execution(format!(
@@ -442,11 +396,11 @@ fn blake2f(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError> {
         )));
     }
 
-    // Round count (first 4 bytes, little-endian)
-    let rounds = u32::from_le_bytes([
+    // Round count (first 4 bytes, big-endian)
+    let rounds = u32::from_be_bytes([
         input[0], input[1], input[2], input[3]
     ]);
-    let gas_used = GAS_COST_BASE + rounds as u64;
+    let gas_used = GAS_COST_PER_ROUND * rounds as u64;
 
     if gas_limit < gas_used {
         return Err(EVMError::OutOfGas);
@@ -458,6 +412,12 @@ fn blake2f(input: &[u8], gas_limit: u64) -> Result<PrecompileResult, EVMError> {
     let t0 = u64::from_le_bytes(input[196..204].try_into().unwrap());
     let t1 = u64::from_le_bytes(input[204..212].try_into().unwrap());
     let final_block = input[212];
+    if final_block > 1 {
+        return Err(EVMError::Execution(format!(
+            "blake2f: Invalid final block flag {}",
+            final_block
+        )));
+    }
 
     // Convert to u64 arrays
     let mut h_vec = [0u64; 8];
@@ -497,7 +457,7 @@ fn blake2b_compression(
 ) -> [u64; 8] {
     // BLAKE2b IV (initialization vector)
     const IV: [u64; 8] = [
-        0x6a09e667f3bcc909,
+        0x6a09e667f3bcc908,
         0xbb67ae8584caa73b,
         0x3c6ef372fe94f82b,
         0xa54ff53a5f1d36f1,
@@ -537,8 +497,8 @@ fn blake2b_compression(
     let mut result = *h;
 
     for round in 0..rounds {
-        // Message schedule
-        let s = &SIGMA[round % 12];
+        // Message schedule (repeats every 10 rounds)
+        let s = &SIGMA[round % 10];
 
         // G function
         macro_rules! g {
@@ -592,6 +552,10 @@ mod tests {
 
         let regular_address = Address([99u8; 20]);
         assert!(!is_precompile(&regular_address));
+
+        let mut high_bytes = ECRECOVER_ADDRESS;
+        high_bytes.0[0] = 1;
+        assert!(!is_precompile(&high_bytes));
     }
 
     #[test]
@@ -667,20 +631,88 @@ mod tests {
 
     #[test]
     fn test_invalid_input_length() {
+        // Short input is right-padded, which leaves v invalid
         let input = b"short";
-        let result = ecrecover(input, 100_000);
-        assert!(result.is_err());
+        let result = ecrecover(input, 100_000).unwrap();
+        assert!(result.output.is_empty());
+        assert_eq!(result.gas_used, 3_000);
     }
 
     #[test]
     fn test_ecrecover_invalid_v() {
         let mut input = [0u8; 128];
-        // Set v to 2 (invalid, should be 0 or 1)
+        // Set v to 2 (invalid, should be 27 or 28)
         input[63] = 2;
 
         let result = ecrecover(&input, 100_000).unwrap();
-        // Should return all zeros
+        // Should return empty output
+        assert!(result.output.is_empty());
+        assert_eq!(result.gas_used, 3_000);
+    }
+
+    #[test]
+    fn test_ecrecover_vector() {
+        let input = hex::decode(concat!(
+            "38d18acb67d25c8bb9942764b62f18e17054f66a817bd4295423adf9ed98873e",
+            "000000000000000000000000000000000000000000000000000000000000001b",
+            "38d18acb67d25c8bb9942764b62f18e17054f66a817bd4295423adf9ed98873e",
+            "789d1dd423d25f0772d2748d60f7e4b81bb14d086eba8e8e8efb6dcff8a4ae02",
+        )).unwrap();
+
+        let result = execute(&ECRECOVER_ADDRESS, &input, 3_000).unwrap();
+        assert_eq!(
+            hex::encode(&result.output),
+            "000000000000000000000000ceaccac640adf55b2028469bd36ba501f28b699d"
+        );
+        assert_eq!(result.gas_used, 3_000);
+
+        assert!(matches!(ecrecover(&input, 2_999), Err(EVMError::OutOfGas)));
+    }
+
+    #[test]
+    fn test_modexp_vectors() {
+        // EIP-198: 3 ^ (p - 1) mod p = 1 for the secp256k1 field prime p
+        let input = hex::decode(concat!(
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "03",
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e",
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+        )).unwrap();
+        let result = execute(&MODEXP_ADDRESS, &input, 100_000).unwrap();
+        assert_eq!(
+            hex::encode(&result.output),
+            "0000000000000000000000000000000000000000000000000000000000000001"
+        );
+        // EIP-2565: 4 words squared * 255 iterations / 3
+        assert_eq!(result.gas_used, 1_360);
+        assert!(matches!(modexp(&input, 1_359), Err(EVMError::OutOfGas)));
+
+        // EIP-198: base of length 0 is zero, and the missing modulus bytes
+        // are right-padded with zeros
+        let input = hex::decode(concat!(
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e",
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+        )).unwrap();
+        let result = execute(&MODEXP_ADDRESS, &input, 100_000).unwrap();
         assert_eq!(result.output, vec![0u8; 32]);
+        assert_eq!(result.gas_used, 1_360);
+
+        // Small inputs pay the minimum
+        let mut input = vec![0u8; 99];
+        input[31] = 1;
+        input[63] = 1;
+        input[95] = 1;
+        input[96] = 2;
+        input[97] = 10;
+        input[98] = 17;
+        let result = execute(&MODEXP_ADDRESS, &input, 200).unwrap();
+        assert_eq!(result.output, vec![4]);
+        assert_eq!(result.gas_used, 200);
     }
 
     #[test]
@@ -710,12 +742,114 @@ mod tests {
         assert_eq!(result.gas_used, 45_000 + 34_000 * 2);
     }
 
+    /// G1 generator (1, 2)
+    const G1: &str = concat!(
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "0000000000000000000000000000000000000000000000000000000000000002",
+    );
+
+    /// Negated G1 generator (1, p - 2)
+    const G1_NEG: &str = concat!(
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45",
+    );
+
+    /// G2 generator from EIP-197
+    const G2: &str = concat!(
+        "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+        "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+        "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+        "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+    );
+
+    fn pairing_input(pairs: &[(&str, &str)]) -> Vec<u8> {
+        pairs.iter()
+            .flat_map(|(g1, g2)| hex::decode(format!("{}{}", g1, g2)).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_ecpairing_vectors() {
+        let one = {
+            let mut word = vec![0u8; 32];
+            word[31] = 1;
+            word
+        };
+
+        // e(G1, G2) * e(-G1, G2) = 1
+        let input = pairing_input(&[(G1, G2), (G1_NEG, G2)]);
+        let result = execute(&ECPAIRING_ADDRESS, &input, 200_000).unwrap();
+        assert_eq!(result.output, one);
+        assert_eq!(result.gas_used, 45_000 + 34_000 * 2);
+
+        // e(G1, G2) * e(G1, G2) = e(G1, G2)^2 != 1
+        let input = pairing_input(&[(G1, G2), (G1, G2)]);
+        let result = execute(&ECPAIRING_ADDRESS, &input, 200_000).unwrap();
+        assert_eq!(result.output, vec![0u8; 32]);
+        assert_eq!(result.gas_used, 45_000 + 34_000 * 2);
+
+        // A single non-degenerate pairing is not one
+        let input = pairing_input(&[(G1, G2)]);
+        let result = execute(&ECPAIRING_ADDRESS, &input, 200_000).unwrap();
+        assert_eq!(result.output, vec![0u8; 32]);
+
+        // Empty input is the empty product
+        let result = execute(&ECPAIRING_ADDRESS, &[], 45_000).unwrap();
+        assert_eq!(result.output, one);
+        assert_eq!(result.gas_used, 45_000);
+    }
+
+    #[test]
+    fn test_ecpairing_invalid_input() {
+        // Not a multiple of 192 bytes
+        let input = vec![0u8; 191];
+        assert!(ecpairing(&input, 200_000).is_err());
+
+        // (1, 3) is not on the curve
+        let mut not_on_curve = G1.to_string();
+        not_on_curve.replace_range(127..128, "3");
+        let input = pairing_input(&[(&not_on_curve, G2)]);
+        assert!(ecpairing(&input, 200_000).is_err());
+
+        let input = pairing_input(&[(G1, G2), (G1_NEG, G2)]);
+        assert!(matches!(ecpairing(&input, 45_000 + 34_000), Err(EVMError::OutOfGas)));
+    }
+
     #[test]
     fn test_blake2f() {
         let mut input = vec![0u8; 213];
-        input[0] = 10; // Set rounds to 10 (little-endian)
+        input[3] = 10; // Set rounds to 10 (big-endian)
         let result = blake2f(&input, 1_000).unwrap();
         assert_eq!(result.output.len(), 64);
-        assert_eq!(result.gas_used, 15 + 10);
+        assert_eq!(result.gas_used, 10);
+
+        input[212] = 2; // Invalid final block flag
+        assert!(blake2f(&input, 1_000).is_err());
+    }
+
+    #[test]
+    fn test_blake2f_vector() {
+        // EIP-152 test vector 5
+        let input = hex::decode(concat!(
+            "0000000c",
+            "48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5",
+            "d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b",
+            "6162630000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0300000000000000",
+            "0000000000000000",
+            "01",
+        )).unwrap();
+        let result = execute(&BLAKE2F_ADDRESS, &input, 12).unwrap();
+        assert_eq!(
+            hex::encode(&result.output),
+            concat!(
+                "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1",
+                "7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+            )
+        );
+        assert_eq!(result.gas_used, 12);
     }
 }
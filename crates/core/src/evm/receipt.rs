@@ -66,6 +66,9 @@ pub struct ReceiptLog {
     /// Log index in the transaction
     pub log_index: u64,
 
+    /// Transaction index in the block
+    pub tx_index: u64,
+
     /// Transaction hash
     pub tx_hash: Hash,
 
@@ -85,10 +88,27 @@ pub struct ReceiptLog {
     pub data: Vec<u8>,
 }
 
+impl ReceiptLog {
+    /// Whether the log matches a per-position topic filter
+    ///
+    /// `None` at a position matches any topic; otherwise the log's topic at
+    /// that position must be one of the listed ones. An empty list matches
+    /// anything, as in Ethereum.
+    pub fn matches_topics(&self, topics: &[Option<Vec<Hash>>]) -> bool {
+        topics.iter().enumerate().all(|(i, position)| match position {
+            Some(accepted) if !accepted.is_empty() => {
+                self.topics.get(i).is_some_and(|t| accepted.contains(t))
+            }
+            _ => true,
+        })
+    }
+}
+
 impl From<EventLog> for ReceiptLog {
     fn from(event_log: EventLog) -> Self {
         Self {
             log_index: 0, // Will be set when adding to receipt
+            tx_index: 0,
             tx_hash: Hash::default(),
             block_hash: Hash::default(),
             block_number: 0,
@@ -190,6 +210,51 @@ impl Bloom {
     }
 }
 
+impl Bloom {
+    /// Bloom of `bytes`, which must be 256 bytes long
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok().map(Self)
+    }
+
+    /// Union of the receipts' blooms, the bloom of their block
+    pub fn from_receipts<'a>(receipts: impl IntoIterator<Item = &'a Receipt>) -> Self {
+        let mut bloom = Self::new();
+        for receipt in receipts {
+            bloom.accrue(&receipt.logs_bloom);
+        }
+        bloom
+    }
+
+    /// Add everything in `other` to this filter
+    pub fn accrue(&mut self, other: &Bloom) {
+        for (byte, other) in self.0.iter_mut().zip(other.0.iter()) {
+            *byte |= other;
+        }
+    }
+
+    /// Check if the bloom filter might contain a hash added by `add_hash`
+    pub fn might_contain_hash(&self, hash: &Hash) -> bool {
+        let mut probe = Self::new();
+        probe.add_hash(hash);
+        self.0.iter().zip(probe.0.iter()).all(|(byte, bit)| byte & bit == *bit)
+    }
+
+    /// Whether a log from `address` matching `topics` might have been added
+    ///
+    /// `topics` has the shape of a log filter: each position lists the
+    /// accepted topics, `None` accepts any.
+    pub fn might_match(&self, address: Option<&Address>, topics: &[Option<Vec<Hash>>]) -> bool {
+        if let Some(address) = address {
+            if !self.might_contain(&address.0) {
+                return false;
+            }
+        }
+        topics.iter().flatten().all(|accepted| {
+            accepted.is_empty() || accepted.iter().any(|topic| self.might_contain_hash(topic))
+        })
+    }
+}
+
 impl Default for Bloom {
     fn default() -> Self {
         Self::new()
@@ -256,6 +321,7 @@ impl Receipt {
     /// Add a log to the receipt
     pub fn with_log(mut self, mut log: ReceiptLog) -> Self {
         log.log_index = self.logs.len() as u64;
+        log.tx_index = self.tx_index;
         log.tx_hash = self.tx_hash;
         log.block_hash = self.block_hash;
         log.block_number = self.block_number;
@@ -428,7 +494,7 @@ impl ReceiptDB {
         from_block: Option<u64>,
         to_block: Option<u64>,
         address: Option<&Address>,
-        topics: &[Option<Hash>],
+        topics: &[Option<Vec<Hash>>],
     ) -> EVMResult<Vec<Receipt>> {
         let mut receipts = if let Some(block_hash) = block_hash {
             self.get_receipts_by_block(block_hash).await?
@@ -454,19 +520,68 @@ impl ReceiptDB {
         }
 
         // Filter by topics
-        for (i, topic_opt) in topics.iter().enumerate() {
-            if let Some(topic) = topic_opt {
-                receipts.retain(|r| {
-                    r.logs.iter().any(|log| {
-                        log.topics.get(i).map_or(false, |t| t == topic)
-                    })
-                });
-            }
+        if !topics.is_empty() {
+            receipts.retain(|r| r.logs.iter().any(|log| log.matches_topics(topics)));
         }
 
         Ok(receipts)
     }
 
+    /// Remove receipts of blocks below `block_number`
+    ///
+    /// # Returns
+    /// Number of receipts removed
+    pub async fn prune_before(&self, block_number: u64) -> usize {
+        let mut receipts = self.receipts_by_tx.write().await;
+        let before = receipts.len();
+        receipts.retain(|_, r| r.block_number >= block_number);
+        let removed = before - receipts.len();
+
+        self.receipts_by_block.write().await
+            .retain(|_, rs| { rs.retain(|r| r.block_number >= block_number); !rs.is_empty() });
+
+        // Drop index entries that point at removed receipts
+        self.receipts_by_address.write().await
+            .retain(|_, txs| { txs.retain(|tx| receipts.contains_key(tx)); !txs.is_empty() });
+        self.receipts_by_topic.write().await
+            .retain(|_, txs| { txs.retain(|tx| receipts.contains_key(tx)); !txs.is_empty() });
+
+        debug!("Pruned {} receipts below block {}", removed, block_number);
+        removed
+    }
+
+    /// Remove the receipts of the block `block_hash`, e.g. after a reorg
+    /// reverted it
+    ///
+    /// A transaction whose receipt already points at another block keeps it.
+    ///
+    /// # Returns
+    /// Number of receipts removed
+    pub async fn remove_block(&self, block_hash: &Hash) -> usize {
+        let Some(block_receipts) = self.receipts_by_block.write().await.remove(block_hash) else {
+            return 0;
+        };
+        self.tx_indices_by_block.write().await.remove(block_hash);
+
+        let mut receipts = self.receipts_by_tx.write().await;
+        let mut removed = 0;
+        for receipt in &block_receipts {
+            if receipts.get(&receipt.tx_hash).is_some_and(|r| r.block_hash == *block_hash) {
+                receipts.remove(&receipt.tx_hash);
+                removed += 1;
+            }
+        }
+
+        // Drop index entries that point at removed receipts
+        self.receipts_by_address.write().await
+            .retain(|_, txs| { txs.retain(|tx| receipts.contains_key(tx)); !txs.is_empty() });
+        self.receipts_by_topic.write().await
+            .retain(|_, txs| { txs.retain(|tx| receipts.contains_key(tx)); !txs.is_empty() });
+
+        debug!("Removed {} receipts of block {:?}", removed, block_hash);
+        removed
+    }
+
     /// Clear all receipts (for testing)
     pub async fn clear(&self) {
         self.receipts_by_tx.write().await.clear();
@@ -528,6 +643,38 @@ mod tests {
         let _ = bloom.might_contain(&other.0);
     }
 
+    #[test]
+    fn test_block_bloom_is_union_of_receipt_blooms() {
+        let receipts: Vec<_> = (1..=3u8)
+            .map(|i| {
+                let mut receipt = Receipt::new(create_test_hash(i), create_test_hash(0xb1), 1, i as u64)
+                    .with_log(ReceiptLog {
+                        log_index: 0,
+                        tx_index: i as u64,
+                        tx_hash: create_test_hash(i),
+                        block_hash: create_test_hash(0xb1),
+                        block_number: 1,
+                        address: create_test_address(i),
+                        topics: vec![create_test_hash(10 + i)],
+                        data: Vec::new(),
+                    });
+                receipt.build_bloom();
+                receipt
+            })
+            .collect();
+
+        let bloom = Bloom::from_receipts(&receipts);
+        for (byte_idx, byte) in bloom.as_bytes().iter().enumerate() {
+            let union = receipts.iter().fold(0, |acc, r| acc | r.logs_bloom.as_bytes()[byte_idx]);
+            assert_eq!(*byte, union);
+        }
+
+        assert!(bloom.might_match(Some(&create_test_address(2)), &[Some(vec![create_test_hash(12)])]));
+        assert!(bloom.might_match(None, &[None, Some(vec![])]));
+        assert_eq!(Bloom::from_slice(bloom.as_bytes()), Some(bloom));
+        assert_eq!(Bloom::from_slice(&[0u8; 32]), None);
+    }
+
     #[tokio::test]
     async fn test_receipt_creation() {
         let tx_hash = create_test_hash(1);
@@ -559,6 +706,7 @@ mod tests {
 
         let log = ReceiptLog {
             log_index: 0,
+            tx_index: 0,
             tx_hash,
             block_hash,
             block_number: 100,
@@ -625,6 +773,7 @@ mod tests {
         let receipt1 = Receipt::new(create_test_hash(1), create_test_hash(10), 100, 0)
             .with_log(ReceiptLog {
                 log_index: 0,
+                tx_index: 0,
                 tx_hash: create_test_hash(1),
                 block_hash: create_test_hash(10),
                 block_number: 100,
@@ -636,6 +785,7 @@ mod tests {
         let receipt2 = Receipt::new(create_test_hash(2), create_test_hash(10), 100, 1)
             .with_log(ReceiptLog {
                 log_index: 0,
+                tx_index: 0,
                 tx_hash: create_test_hash(2),
                 block_hash: create_test_hash(10),
                 block_number: 100,
@@ -665,6 +815,7 @@ mod tests {
         let receipt1 = Receipt::new(create_test_hash(1), create_test_hash(10), 100, 0)
             .with_log(ReceiptLog {
                 log_index: 0,
+                tx_index: 0,
                 tx_hash: create_test_hash(1),
                 block_hash: create_test_hash(10),
                 block_number: 100,
@@ -676,6 +827,7 @@ mod tests {
         let receipt2 = Receipt::new(create_test_hash(2), create_test_hash(10), 100, 1)
             .with_log(ReceiptLog {
                 log_index: 0,
+                tx_index: 0,
                 tx_hash: create_test_hash(2),
                 block_hash: create_test_hash(10),
                 block_number: 100,
@@ -693,6 +845,40 @@ mod tests {
         assert_eq!(filtered[0].tx_hash, create_test_hash(1));
     }
 
+    #[tokio::test]
+    async fn test_prune_receipts() {
+        let db = ReceiptDB::new();
+
+        for i in 0..4u8 {
+            let receipt = Receipt::new(create_test_hash(i), create_test_hash(100 + i), i as u64, 0);
+            db.put_receipt(receipt).await.unwrap();
+        }
+
+        assert_eq!(db.prune_before(2).await, 2);
+        assert_eq!(db.count().await, 2);
+        assert!(db.get_receipt(&create_test_hash(1)).await.unwrap().is_none());
+        assert!(db.get_receipt(&create_test_hash(2)).await.unwrap().is_some());
+        assert!(db.get_receipts_by_block(&create_test_hash(101)).await.unwrap().is_empty());
+        assert_eq!(db.get_receipts_by_block(&create_test_hash(103)).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_remove_block_receipts() {
+        let db = ReceiptDB::new();
+        let reverted = create_test_hash(100);
+        for i in 0..2u8 {
+            db.put_receipt(Receipt::new(create_test_hash(i), reverted, 5, i as u64)).await.unwrap();
+        }
+        // Transaction 1 was re-included in another block
+        db.put_receipt(Receipt::new(create_test_hash(1), create_test_hash(101), 5, 0)).await.unwrap();
+
+        assert_eq!(db.remove_block(&reverted).await, 1);
+        assert!(db.get_receipt(&create_test_hash(0)).await.unwrap().is_none());
+        assert_eq!(db.get_receipt(&create_test_hash(1)).await.unwrap().unwrap().block_hash, create_test_hash(101));
+        assert!(db.get_receipts_by_block(&reverted).await.unwrap().is_empty());
+        assert_eq!(db.remove_block(&reverted).await, 0);
+    }
+
     #[tokio::test]
     async fn test_clear_receipts() {
         let db = ReceiptDB::new();
@@ -116,6 +116,9 @@ impl NornDatabaseAdapter {
                 warn!("Failed to get code hash for {:?}: {}", address, e);
                 B256::default()
             });
+        // Accounts without code have a zero hash in Norn state; revm expects
+        // KECCAK_EMPTY and treats anything else as existing code
+        let code_hash = if code_hash == B256::ZERO { KECCAK_EMPTY } else { code_hash };
 
         // Get code if code_hash is not empty
         let code = if code_hash != KECCAK_EMPTY {
@@ -129,16 +132,14 @@ impl NornDatabaseAdapter {
                     .expect("Failed to create runtime");
 
                 rt.block_on(async move {
-                    code_storage_clone.get_code(&norn_hash).await
+                    code_storage_clone.get_analyzed_code(&norn_hash).await
                 })
             })
             .join()
             {
                 Ok(Ok(Some(bytecode))) => {
                     debug!("Loaded bytecode: {} bytes", bytecode.len());
-                    // Convert Vec<u8> to revm Bytes
-                    use revm::primitives::Bytes;
-                    Some(Bytecode::new_raw(Bytes::from(bytecode)))
+                    Some(bytecode)
                 }
                 Ok(Ok(None)) => {
                     debug!("No bytecode found for hash: {}", hex::encode(code_hash.as_slice()));
@@ -173,7 +174,8 @@ impl NornDatabaseAdapter {
 
     /// Get account code by code hash
     ///
-    /// Retrieves contract bytecode from CodeStorage.
+    /// Retrieves contract bytecode from CodeStorage, already analysed so revm
+    /// doesn't redo the jump table on every call.
     pub fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Infallible> {
         debug!("Getting code by hash: {}", hex::encode(code_hash.as_slice()));
 
@@ -189,15 +191,14 @@ impl NornDatabaseAdapter {
                 .expect("Failed to create runtime");
 
             rt.block_on(async move {
-                code_storage_clone.get_code(&norn_hash).await
+                code_storage_clone.get_analyzed_code(&norn_hash).await
             })
         })
         .join()
         {
             Ok(Ok(Some(bytecode))) => {
                 debug!("Found code: {} bytes", bytecode.len());
-                use revm::primitives::Bytes;
-                Ok(Bytecode::new_raw(Bytes::from(bytecode)))
+                Ok(bytecode)
             }
             Ok(Ok(None)) => {
                 debug!("Code not found for hash: {}", hex::encode(code_hash.as_slice()));
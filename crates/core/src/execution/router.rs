@@ -2,11 +2,14 @@
 //!
 //! Routes transactions to the appropriate executor based on transaction type.
 
-use crate::evm::{EVMExecutor, EVMContext, EVMExecutionResult};
-use norn_common::types::{Transaction, TransactionType, Address, Hash};
+use crate::blockchain::{BlockImportHook, ChainWrite, MAX_REORG_DEPTH};
+use crate::evm::{Bloom, CodeStorage, EVMError, EVMExecutor, EVMContext, EVMExecutionResult, EVMResult, Receipt};
+use crate::state::UndoLog;
+use norn_common::types::{Block, Transaction, TransactionType, Address, Hash};
+use std::collections::VecDeque;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
 
 /// Result of transaction execution (unified for both Native and EVM)
 #[derive(Debug, Clone)]
@@ -62,6 +65,12 @@ pub struct TransactionRouter {
 
     /// Base fee for EIP-1559
     base_fee: Arc<RwLock<u64>>,
+
+    /// Receipts of the block being imported, indexed once it is committed
+    importing: std::sync::Mutex<Vec<Receipt>>,
+
+    /// Undo logs of the latest committed blocks by block hash, oldest first
+    committed: std::sync::Mutex<VecDeque<(Hash, UndoLog)>>,
 }
 
 impl TransactionRouter {
@@ -77,6 +86,8 @@ impl TransactionRouter {
             block_gas_limit,
             block_coinbase: Arc::new(RwLock::new(Address::default())),
             base_fee: Arc::new(RwLock::new(0)),
+            importing: std::sync::Mutex::new(Vec::new()),
+            committed: std::sync::Mutex::new(VecDeque::new()),
         }
     }
 
@@ -104,22 +115,6 @@ impl TransactionRouter {
         *fee = base_fee;
     }
 
-    /// Calculate effective gas price from transaction
-    fn calculate_gas_price(&self, tx: &Transaction) -> u64 {
-        // EIP-1559 logic: effective gas price is min(max_fee_per_gas, base_fee + max_priority_fee_per_gas)
-        if let Some(max_fee) = tx.body.max_fee_per_gas {
-            let base_fee = *self.base_fee.blocking_read();
-            let priority_fee = tx.body.max_priority_fee_per_gas.unwrap_or(0);
-            std::cmp::min(max_fee, base_fee.saturating_add(priority_fee))
-        } else if let Some(gas_price) = tx.body.gas_price {
-            // Legacy transaction with explicit gas price
-            gas_price
-        } else {
-            // Fallback to default
-            1_000_000_000
-        }
-    }
-
     /// Execute transaction based on its type
     pub async fn execute_transaction(
         &self,
@@ -151,41 +146,184 @@ impl TransactionRouter {
         &self,
         tx: &Transaction,
     ) -> Result<ExecutionResult, String> {
+        let result = self.run_evm_transaction(tx)
+            .await
+            .map_err(|e| match e {
+                EVMError::Execution(msg) if self.evm_executor.is_none() => msg,
+                e => format!("EVM execution failed: {:?}", e),
+            })?;
+
+        // Convert EVM result to unified result
+        Ok(ExecutionResult {
+            tx_hash: tx.body.hash,
+            success: result.success,
+            error: result.error,
+            gas_used: result.gas_used,
+            return_data: result.output,
+            logs: result.logs.into_iter().map(|log| LogEntry {
+                address: log.address,
+                topics: log.topics,
+                data: log.data,
+            }).collect(),
+        })
+    }
+
+    /// Run an EVM transaction in the current block context
+    async fn run_evm_transaction(
+        &self,
+        tx: &Transaction,
+    ) -> EVMResult<EVMExecutionResult> {
         let evm_executor = self.evm_executor.as_ref()
-            .ok_or("EVM executor not configured")?;
+            .ok_or_else(|| EVMError::Execution("EVM executor not configured".to_string()))?;
 
         // Create EVM context
         let block_number = *self.block_number.read().await;
         let block_timestamp = *self.block_timestamp.read().await;
         let block_coinbase = *self.block_coinbase.read().await;
-        let tx_gas_price = self.calculate_gas_price(tx);
+        let block_base_fee = *self.base_fee.read().await;
 
         let ctx = EVMContext {
             block_number,
             block_timestamp,
             block_coinbase,
             block_gas_limit: self.block_gas_limit,
-            tx_gas_price,
+            // Legacy price when the transaction sets none; the executor
+            // prices EIP-1559 transactions from their own fee fields
+            tx_gas_price: 1_000_000_000,
+            tx_priority_fee: None,
+            block_base_fee,
+            cancel: None,
         };
 
         // Execute transaction
-        let result = evm_executor.execute(tx, &ctx)
-            .await
-            .map_err(|e| format!("EVM execution failed: {:?}", e))?;
+        evm_executor.execute(tx, &ctx).await
+    }
 
-        // Convert EVM result to unified result
-        Ok(ExecutionResult {
-            tx_hash: tx.body.hash,
-            success: result.success,
-            error: result.error,
-            gas_used: result.gas_used,
-            return_data: result.output,
-            logs: result.logs.into_iter().map(|log| LogEntry {
-                address: log.address,
-                topics: log.topics,
-                data: log.data,
-            }).collect(),
-        })
+    /// Execute the EVM transactions of a block and store their receipts
+    ///
+    /// See [`Self::run_block`].
+    pub async fn execute_block(
+        &self,
+        block: &Block,
+    ) -> Result<Vec<Receipt>, String> {
+        let receipts = self.run_block(block).await?;
+        if let Some(evm_executor) = &self.evm_executor {
+            for receipt in &receipts {
+                evm_executor.receipt_db().put_receipt(receipt.clone())
+                    .await
+                    .map_err(|e| format!("Failed to store receipt: {:?}", e))?;
+            }
+        }
+        Ok(receipts)
+    }
+
+    /// Execute the EVM transactions of a block, returning their receipts
+    ///
+    /// Block number, timestamp and base fee are taken from the header; the
+    /// coinbase is left as set with `set_block_coinbase`. Each receipt's
+    /// `cumulative_gas_used` is the gas used by the block's EVM transactions
+    /// up to and including that transaction. A transaction rejected as
+    /// invalid gets a failed receipt using no gas; any other execution error
+    /// fails the block. Native transactions are skipped.
+    pub async fn run_block(
+        &self,
+        block: &Block,
+    ) -> Result<Vec<Receipt>, String> {
+        let has_evm = block.transactions.iter().any(|tx| tx.body.tx_type == TransactionType::EVM);
+        let Some(evm_executor) = self.evm_executor.as_ref() else {
+            return if has_evm { Err("EVM executor not configured".to_string()) } else { Ok(Vec::new()) };
+        };
+
+        let block_number = block.header.height as u64;
+        self.set_block_number(block_number).await;
+        self.set_block_timestamp(block.header.timestamp as u64).await;
+        self.set_base_fee(block.header.base_fee).await;
+
+        let mut receipts = Vec::new();
+        let mut cumulative_gas_used = 0u64;
+
+        for (tx_index, tx) in block.transactions.iter().enumerate() {
+            if tx.body.tx_type != TransactionType::EVM {
+                continue;
+            }
+            evm_executor.clear_logs().await;
+
+            let result = match self.run_evm_transaction(tx).await {
+                Ok(result) => result,
+                Err(EVMError::InvalidTransaction(reason)) => {
+                    warn!("Transaction {:?} in block {} is invalid: {}", tx.body.hash, block_number, reason);
+                    EVMExecutionResult {
+                        success: false,
+                        gas_used: 0,
+                        output: Vec::new(),
+                        error: Some(reason),
+                        logs: Vec::new(),
+                    }
+                }
+                Err(e) => return Err(format!("EVM execution failed: {:?}", e)),
+            };
+            cumulative_gas_used += result.gas_used;
+            // The receipt picks up the logs recorded here
+            evm_executor.process_execution_logs(
+                tx.body.hash,
+                block.header.block_hash,
+                block_number,
+                tx_index as u64,
+                result.logs.clone(),
+            ).await.map_err(|e| format!("Failed to record logs: {:?}", e))?;
+
+            let is_contract_creation = result.success && tx.body.receiver == Address::default() && !tx.body.data.is_empty();
+            let (to, contract_address) = if is_contract_creation {
+                (None, Some(CodeStorage::calculate_create_address(tx.body.address, tx.body.nonce as u64)))
+            } else {
+                (Some(tx.body.receiver), None)
+            };
+
+            let receipt = evm_executor.create_receipt(
+                tx.body.hash,
+                block.header.block_hash,
+                block_number,
+                tx_index as u64,
+                tx.body.address,
+                to,
+                &result,
+                contract_address,
+                cumulative_gas_used,
+            ).await;
+            receipts.push(receipt);
+        }
+
+        info!(
+            "Executed block {}: {} EVM transactions, gas used {}",
+            block_number, receipts.len(), cumulative_gas_used
+        );
+
+        Ok(receipts)
+    }
+
+    /// Execute a block that is not being imported and undo its state changes
+    ///
+    /// Used to learn the receipts of a block being produced. The caller must
+    /// keep the chain from importing a block meanwhile, since imports
+    /// checkpoint the same state.
+    pub async fn dry_run_block(&self, block: &Block) -> Result<Vec<Receipt>, String> {
+        let Some(evm_executor) = &self.evm_executor else {
+            return self.run_block(block).await;
+        };
+        let state_manager = evm_executor.state_manager();
+        state_manager.begin_checkpoint().await;
+        let receipts = self.run_block(block).await;
+        state_manager.revert_checkpoint().await;
+        receipts
+    }
+
+    /// Router executing blocks the way this one does, on `evm_executor`
+    ///
+    /// Used to replay blocks on another state.
+    pub async fn for_executor(&self, evm_executor: Arc<EVMExecutor>) -> Self {
+        let router = Self::new(Some(evm_executor), self.block_gas_limit);
+        router.set_block_coinbase(*self.block_coinbase.read().await).await;
+        router
     }
 
     /// Get EVM executor reference
@@ -194,6 +332,125 @@ impl TransactionRouter {
     }
 }
 
+impl TransactionRouter {
+    fn lock_committed(&self) -> std::sync::MutexGuard<'_, VecDeque<(Hash, UndoLog)>> {
+        self.committed.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Bind or unbind the code of `addresses` to match their reverted accounts
+    async fn restore_code_bindings(evm_executor: &EVMExecutor, addresses: Vec<Address>) {
+        let code_storage = evm_executor.code_storage();
+        for address in addresses {
+            let code_hash = match evm_executor.state_manager().get_account(&address).await {
+                Ok(account) => account.and_then(|account| account.code_hash),
+                Err(e) => {
+                    error!("Failed to read reverted account {:?}: {:?}", address, e);
+                    continue;
+                }
+            };
+            let result = match code_hash {
+                Some(code_hash) if matches!(code_storage.get_code(&code_hash).await, Ok(Some(_))) => {
+                    code_storage.bind_code_to_address(address, code_hash).await
+                }
+                _ => code_storage.unbind_code_from_address(&address).await.map(|_| ()),
+            };
+            if let Err(e) = result {
+                error!("Failed to restore the code of {:?}: {:?}", address, e);
+            }
+        }
+    }
+}
+
+/// Executes every block that becomes the new head
+///
+/// The block runs under a checkpoint of the executor's state, reverted if the
+/// import is aborted. A block whose logs bloom doesn't match its receipts is
+/// rejected; an empty bloom stands for a block without logs. Its receipts are
+/// stored with the block and added to the receipt index once it is
+/// committed.
+///
+/// The undo logs of the last [`MAX_REORG_DEPTH`] committed blocks are kept,
+/// so a reorg can revert them; blocks committed before a restart can't be.
+#[async_trait::async_trait]
+impl BlockImportHook for TransactionRouter {
+    async fn on_block_imported(&self, block: &Block, write: &mut ChainWrite) -> anyhow::Result<()> {
+        if let Some(evm_executor) = &self.evm_executor {
+            evm_executor.state_manager().begin_checkpoint().await;
+        }
+        let receipts = self.run_block(block).await.map_err(anyhow::Error::msg)?;
+        let bloom = Bloom::from_receipts(&receipts);
+        let claimed = match block.header.logs_bloom.as_slice() {
+            [] => Some(Bloom::new()),
+            bytes => Bloom::from_slice(bytes),
+        };
+        if claimed != Some(bloom) {
+            anyhow::bail!("Logs bloom of block {} doesn't match its receipts", block.header.height);
+        }
+        write.add_receipts(receipts.iter().cloned());
+        *self.importing.lock().unwrap_or_else(|e| e.into_inner()) = receipts;
+        Ok(())
+    }
+
+    async fn on_block_committed(&self, block: &Block) {
+        let receipts = std::mem::take(&mut *self.importing.lock().unwrap_or_else(|e| e.into_inner()));
+        let Some(evm_executor) = &self.evm_executor else {
+            return;
+        };
+        if let Some(undo) = evm_executor.state_manager().take_checkpoint() {
+            let mut committed = self.lock_committed();
+            committed.push_back((block.header.block_hash, undo));
+            while committed.len() > MAX_REORG_DEPTH {
+                committed.pop_front();
+            }
+        }
+        for receipt in receipts {
+            if let Err(e) = evm_executor.receipt_db().put_receipt(receipt).await {
+                error!("Failed to index receipt: {:?}", e);
+            }
+        }
+    }
+
+    async fn on_import_aborted(&self, block: &Block) {
+        self.importing.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        if let Some(evm_executor) = &self.evm_executor {
+            evm_executor.state_manager().revert_checkpoint().await;
+            warn!("Reverted the state changes of block {}", block.header.height);
+        }
+    }
+
+    fn can_revert(&self, blocks: &[Block]) -> bool {
+        if self.evm_executor.is_none() {
+            return true;
+        }
+        let committed = self.lock_committed();
+        blocks.len() <= committed.len()
+            && blocks.iter().zip(committed.iter().rev()).all(|(block, (hash, _))| block.header.block_hash == *hash)
+    }
+
+    async fn on_block_reverted(&self, block: &Block) {
+        let Some(evm_executor) = &self.evm_executor else {
+            return;
+        };
+        let undo = {
+            let mut committed = self.lock_committed();
+            match committed.back() {
+                Some((hash, _)) if *hash == block.header.block_hash => committed.pop_back().map(|(_, undo)| undo),
+                _ => None,
+            }
+        };
+        let Some(undo) = undo else {
+            error!("No undo log for block {}, its state changes stay", block.header.height);
+            return;
+        };
+
+        let addresses: Vec<Address> = undo.accounts().copied().collect();
+        evm_executor.state_manager().revert_undo(undo).await;
+        Self::restore_code_bindings(evm_executor, addresses).await;
+        evm_executor.receipt_db().remove_block(&block.header.block_hash).await;
+        info!("Reverted the state changes of block {}", block.header.height);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,6 +523,215 @@ mod tests {
         assert_eq!(exec_result.gas_used, 21_000); // Base gas for transfer
     }
 
+    #[tokio::test]
+    async fn test_execute_block_cumulative_gas() {
+        use norn_common::types::AccessListItem;
+
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let evm_executor = Arc::new(EVMExecutor::new(Arc::clone(&state_manager), EVMConfig::default()));
+
+        let sender = Address([2u8; 20]);
+        state_manager.update_balance(&sender, BigUint::from(10_000_000_000_000_000_000u128)).await.unwrap(); // 10 ETH
+
+        let router = TransactionRouter::new(Some(Arc::clone(&evm_executor)), 30_000_000);
+
+        // Three transfers with differently sized access lists
+        let mut block = Block::default();
+        block.header.height = 7;
+        block.header.block_hash = Hash([7u8; 32]);
+        for i in 0..3u8 {
+            let mut tx = create_test_evm_transaction();
+            tx.body.hash = Hash([i + 1; 32]);
+            tx.body.nonce = i as i64;
+            tx.body.access_list = Some((0..i).map(|j| AccessListItem {
+                address: Address([10 + j; 20]),
+                storage_keys: vec![Hash([j; 32])],
+            }).collect());
+            block.transactions.push(tx);
+        }
+
+        let receipts = router.execute_block(&block).await.unwrap();
+        assert_eq!(receipts.len(), 3);
+
+        let gas_used: Vec<u64> = receipts.iter().map(|r| r.gas_used).collect();
+        assert_eq!(gas_used, vec![21_000, 21_000 + 4_300, 21_000 + 2 * 4_300]);
+
+        let mut previous = 0;
+        for (i, receipt) in receipts.iter().enumerate() {
+            assert_eq!(receipt.tx_index, i as u64);
+            assert_eq!(receipt.block_number, 7);
+            assert!(receipt.cumulative_gas_used > previous);
+            assert_eq!(receipt.cumulative_gas_used, previous + receipt.gas_used);
+            previous = receipt.cumulative_gas_used;
+        }
+        assert_eq!(previous, gas_used.iter().sum::<u64>());
+
+        // Receipts are stored
+        let stored = evm_executor.receipt_db().get_receipt(&Hash([3u8; 32])).await.unwrap().unwrap();
+        assert_eq!(stored.cumulative_gas_used, previous);
+    }
+
+    /// Chain on a temporary database executing its blocks with a router over `state_manager`
+    async fn executing_chain(
+        state_manager: &Arc<AccountStateManager>,
+    ) -> (tempfile::TempDir, Arc<crate::blockchain::Blockchain>, Arc<EVMExecutor>) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(norn_storage::SledDB::new(temp_dir.path()).unwrap());
+        let chain = crate::blockchain::Blockchain::new_with_fixed_genesis(db).await;
+        let evm_executor = Arc::new(EVMExecutor::new(Arc::clone(state_manager), EVMConfig::default()));
+        chain.add_import_hook(Arc::new(TransactionRouter::new(Some(Arc::clone(&evm_executor)), 30_000_000)));
+        (temp_dir, chain, evm_executor)
+    }
+
+    async fn child_with(chain: &crate::blockchain::Blockchain, tx: Transaction) -> Block {
+        let parent = chain.latest_block.read().await.clone();
+        let mut block = Block::default();
+        block.header.height = parent.header.height + 1;
+        block.header.prev_block_hash = parent.header.block_hash;
+        block.header.block_hash = Hash([0xb1; 32]);
+        block.transactions.push(tx);
+        block
+    }
+
+    #[tokio::test]
+    async fn test_imported_block_is_executed() {
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        state_manager.update_balance(&Address([2u8; 20]), BigUint::from(2_000_000_000_000_000_000u128)).await.unwrap(); // 2 ETH
+        let (_dir, chain, evm_executor) = executing_chain(&state_manager).await;
+
+        let block = child_with(&chain, create_test_evm_transaction()).await;
+        chain.commit_block(&block).await.unwrap();
+
+        let receipt = evm_executor.receipt_db().get_receipt(&Hash([1u8; 32])).await.unwrap().unwrap();
+        assert_eq!(receipt.block_number, 1);
+        assert_eq!(receipt.cumulative_gas_used, 21_000);
+        assert_eq!(state_manager.get_balance(&Address([3u8; 20])).await.unwrap(), BigUint::from(1_000_000_000_000_000_000u128));
+
+        // Receipts are stored with the block
+        let stored = chain.get_block_receipts(&block.header.block_hash).await.unwrap();
+        assert_eq!(stored, vec![receipt]);
+    }
+
+    #[tokio::test]
+    async fn test_aborted_import_reverts_state() {
+        struct Failing;
+
+        #[async_trait::async_trait]
+        impl BlockImportHook for Failing {
+            async fn on_block_imported(&self, _block: &Block, _write: &mut ChainWrite) -> anyhow::Result<()> {
+                anyhow::bail!("injected failure")
+            }
+        }
+
+        let sender = Address([2u8; 20]);
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        state_manager.update_balance(&sender, BigUint::from(2_000_000_000_000_000_000u128)).await.unwrap();
+        let (_dir, chain, evm_executor) = executing_chain(&state_manager).await;
+        chain.add_import_hook(Arc::new(Failing));
+        let genesis = chain.latest_block.read().await.header.block_hash;
+
+        let block = child_with(&chain, create_test_evm_transaction()).await;
+        assert!(chain.commit_block(&block).await.is_err());
+
+        // The block was executed, then undone: nothing of it remains
+        assert_eq!(chain.latest_block.read().await.header.block_hash, genesis);
+        assert!(chain.get_block_by_hash(&block.header.block_hash).await.is_none());
+        assert!(chain.get_block_receipts(&block.header.block_hash).await.is_none());
+        assert!(evm_executor.receipt_db().get_receipt(&Hash([1u8; 32])).await.unwrap().is_none());
+        assert_eq!(state_manager.get_balance(&sender).await.unwrap(), BigUint::from(2_000_000_000_000_000_000u128));
+        assert_eq!(state_manager.get_balance(&Address([3u8; 20])).await.unwrap(), BigUint::from(0u32));
+        assert_eq!(state_manager.get_nonce(&sender).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fork_reorg_reverts_replaced_blocks() {
+        let sender = Address([2u8; 20]);
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        state_manager.update_balance(&sender, BigUint::from(2_000_000_000_000_000_000u128)).await.unwrap(); // 2 ETH
+        let (_dir, chain, evm_executor) = executing_chain(&state_manager).await;
+        let genesis = chain.latest_block.read().await.clone();
+
+        let a1 = child_with(&chain, create_test_evm_transaction()).await;
+        chain.commit_block(&a1).await.unwrap();
+
+        // Fork off genesis sending the value elsewhere, one block longer
+        let mut tx = create_test_evm_transaction();
+        tx.body.hash = Hash([4u8; 32]);
+        tx.body.receiver = Address([5u8; 20]);
+        let mut b1 = a1.clone();
+        b1.header.block_hash = Hash([0xc1; 32]);
+        b1.header.prev_block_hash = genesis.header.block_hash;
+        b1.transactions = vec![tx];
+        let mut b2 = Block::default();
+        b2.header.height = 2;
+        b2.header.prev_block_hash = b1.header.block_hash;
+        b2.header.block_hash = Hash([0xc2; 32]);
+        chain.commit_block(&b1).await.unwrap();
+        chain.commit_block(&b2).await.unwrap();
+
+        assert_eq!(chain.latest_block.read().await.header.block_hash, b2.header.block_hash);
+        assert_eq!(state_manager.get_balance(&Address([3u8; 20])).await.unwrap(), BigUint::from(0u32));
+        assert_eq!(state_manager.get_balance(&Address([5u8; 20])).await.unwrap(), BigUint::from(1_000_000_000_000_000_000u128));
+        assert_eq!(state_manager.get_balance(&sender).await.unwrap(), BigUint::from(1_000_000_000_000_000_000u128));
+        assert!(evm_executor.receipt_db().get_receipt(&Hash([1u8; 32])).await.unwrap().is_none());
+        let receipt = evm_executor.receipt_db().get_receipt(&Hash([4u8; 32])).await.unwrap().unwrap();
+        assert_eq!(receipt.block_hash, b1.header.block_hash);
+    }
+
+    #[tokio::test]
+    async fn test_logs_bloom_is_checked_on_import() {
+        let sender = Address([2u8; 20]);
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        state_manager.update_balance(&sender, BigUint::from(2_000_000_000_000_000_000u128)).await.unwrap();
+        let (_dir, chain, evm_executor) = executing_chain(&state_manager).await;
+
+        // LOG1(0, 0, 42); STOP
+        let code = vec![0x60, 0x2a, 0x60, 0x00, 0x60, 0x00, 0xa1, 0x00];
+        let (contract, _) = evm_executor.create_contract(sender, 0, code, 0, 100_000).await.unwrap();
+        let mut tx = create_test_evm_transaction();
+        tx.body.receiver = contract;
+        tx.body.nonce = state_manager.get_nonce(&sender).await.unwrap() as i64;
+        tx.body.value = None;
+        tx.body.data = vec![0x01];
+        let mut block = child_with(&chain, tx).await;
+
+        // A block claiming no logs is rejected and leaves nothing behind
+        let genesis = chain.latest_block.read().await.header.block_hash;
+        let err = chain.commit_block(&block).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("Logs bloom"), "{:#}", err);
+        assert_eq!(chain.latest_block.read().await.header.block_hash, genesis);
+        let nonce = state_manager.get_nonce(&sender).await.unwrap();
+
+        let receipts = TransactionRouter::new(Some(Arc::clone(&evm_executor)), 30_000_000)
+            .dry_run_block(&block)
+            .await
+            .unwrap();
+        assert_eq!(receipts[0].logs.len(), 1);
+        assert_eq!(state_manager.get_nonce(&sender).await.unwrap(), nonce);
+
+        block.header.logs_bloom = Bloom::from_receipts(&receipts).as_bytes().to_vec();
+        chain.commit_block(&block).await.unwrap();
+        let stored = chain.get_block_receipts(&block.header.block_hash).await.unwrap();
+        assert_eq!(stored[0].logs[0].address, contract);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_transaction_gets_failed_receipt() {
+        // The fee cap is below the block's base fee
+        let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let (_dir, chain, evm_executor) = executing_chain(&state_manager).await;
+        let mut tx = create_test_evm_transaction();
+        tx.body.max_fee_per_gas = Some(1);
+
+        let block = child_with(&chain, tx).await;
+        chain.commit_block(&block).await.unwrap();
+
+        let receipt = evm_executor.receipt_db().get_receipt(&Hash([1u8; 32])).await.unwrap().unwrap();
+        assert!(!receipt.status);
+        assert_eq!(receipt.gas_used, 0);
+        assert_eq!(chain.latest_block.read().await.header.block_hash, block.header.block_hash);
+    }
+
     #[tokio::test]
     async fn test_native_transaction_rejection() {
         let router = TransactionRouter::new(None, 30_000_000);
@@ -2,6 +2,7 @@ pub mod merkle;
 pub mod txpool;
 pub mod block_buffer;
 pub mod blockchain;
+pub mod pruning;
 pub mod data_processor;
 pub mod config;
 pub mod consensus;
@@ -13,8 +14,14 @@ pub mod fee;
 pub mod wallet;
 pub mod events;
 pub mod evm;
+pub mod raw_tx;
+pub mod readiness;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_support;
 
 // Re-export commonly used types
 pub use txpool::{TxPool, TransactionPool, TxPoolStats};
+pub use raw_tx::RawTransactionStore;
 pub mod txpool_enhanced;  // New: Enhanced transaction pool
-pub use txpool_enhanced::{EnhancedTxPool, PrioritizedTransaction, TxPoolError};
+pub mod txpool_journal;
+pub use txpool_enhanced::{EnhancedTxPool, EnhancedTxPoolConfig, PrioritizedTransaction, TxPoolError};
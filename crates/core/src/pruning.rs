@@ -0,0 +1,59 @@
+//! Block body pruning
+//!
+//! In `Full` mode only the most recent `keep_recent` blocks keep their
+//! transaction bodies and receipts; older blocks are reduced to their
+//! headers. State is never pruned.
+
+use serde::Deserialize;
+
+/// How much block history a node keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum PruningMode {
+    /// Keep every block body and receipt
+    #[default]
+    Archive,
+    /// Keep bodies and receipts of the last `keep_recent` blocks only
+    Full { keep_recent: u64 },
+}
+
+impl PruningMode {
+    /// Height below which block bodies are pruned once the chain reaches
+    /// `latest_height`, or `None` if nothing is pruned
+    pub fn prune_below(&self, latest_height: i64) -> Option<i64> {
+        match *self {
+            PruningMode::Archive => None,
+            PruningMode::Full { keep_recent } => {
+                let keep_recent = i64::try_from(keep_recent).unwrap_or(i64::MAX);
+                let boundary = latest_height.saturating_sub(keep_recent) + 1;
+                (boundary > 0).then_some(boundary)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_below() {
+        assert_eq!(PruningMode::Archive.prune_below(1_000), None);
+
+        let full = PruningMode::Full { keep_recent: 10 };
+        assert_eq!(full.prune_below(5), None);
+        assert_eq!(full.prune_below(9), None);
+        // Heights 1..=10 are kept at height 10, so only genesis is pruned
+        assert_eq!(full.prune_below(10), Some(1));
+        assert_eq!(full.prune_below(100), Some(91));
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let mode: PruningMode = serde_json::from_str(r#"{"mode": "archive"}"#).unwrap();
+        assert_eq!(mode, PruningMode::Archive);
+
+        let mode: PruningMode = serde_json::from_str(r#"{"mode": "full", "keep_recent": 128}"#).unwrap();
+        assert_eq!(mode, PruningMode::Full { keep_recent: 128 });
+    }
+}
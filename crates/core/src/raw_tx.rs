@@ -0,0 +1,88 @@
+//! Original encodings of submitted transactions
+//!
+//! Transactions are decoded into [`norn_common::types::Transaction`] on
+//! ingestion, which loses their exact wire bytes. The store keeps the raw
+//! bytes by transaction hash so they can be served back unchanged, e.g. by
+//! `eth_getRawTransactionByHash` or for re-broadcasting.
+
+use moka::future::Cache;
+use norn_common::traits::DBInterface;
+use norn_common::types::Hash;
+use norn_common::utils::db_keys::raw_tx_hash_to_db_key;
+use std::sync::Arc;
+
+/// Raw transactions kept in memory by default
+pub const DEFAULT_RAW_TX_CAPACITY: u64 = 40960;
+
+/// Raw transaction bytes by transaction hash
+///
+/// Backed by a database, every transaction is written through and the
+/// in-memory cache only speeds up reads; without one, transactions evicted
+/// from the cache are lost.
+pub struct RawTransactionStore {
+    txs: Cache<Hash, Vec<u8>>,
+    db: Option<Arc<dyn DBInterface>>,
+}
+
+impl RawTransactionStore {
+    /// Store kept in memory only
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_RAW_TX_CAPACITY)
+    }
+
+    /// Keep at most `capacity` transactions, evicting the least used
+    pub fn with_capacity(capacity: u64) -> Self {
+        Self { txs: Cache::new(capacity), db: None }
+    }
+
+    /// Store persisted in `db`
+    pub fn with_db(db: Arc<dyn DBInterface>) -> Self {
+        Self { db: Some(db), ..Self::new() }
+    }
+
+    /// Remember `raw` as the encoding of the transaction `hash`
+    pub async fn insert(&self, hash: Hash, raw: Vec<u8>) -> anyhow::Result<()> {
+        if let Some(db) = &self.db {
+            db.insert(&raw_tx_hash_to_db_key(&hash), &raw).await?;
+        }
+        self.txs.insert(hash, raw).await;
+        Ok(())
+    }
+
+    /// Encoding of the transaction `hash`, if it was submitted raw
+    pub async fn get(&self, hash: &Hash) -> anyhow::Result<Option<Vec<u8>>> {
+        if let Some(raw) = self.txs.get(hash).await {
+            return Ok(Some(raw));
+        }
+        let Some(db) = &self.db else {
+            return Ok(None);
+        };
+
+        let raw = db.get(&raw_tx_hash_to_db_key(hash)).await?;
+        if let Some(raw) = &raw {
+            self.txs.insert(*hash, raw.clone()).await;
+        }
+        Ok(raw)
+    }
+}
+
+impl Default for RawTransactionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_raw_transactions_by_hash() {
+        let store = RawTransactionStore::new();
+        let raw = vec![0x02, 0xf8, 0x6c, 0x01];
+
+        store.insert(Hash([1u8; 32]), raw.clone()).await.unwrap();
+        assert_eq!(store.get(&Hash([1u8; 32])).await.unwrap(), Some(raw));
+        assert_eq!(store.get(&Hash([2u8; 32])).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,39 @@
+//! Node readiness shared by the syncer and the RPC layers
+//!
+//! Until the node finishes its initial sync, state served over RPC would be
+//! stale, so state-dependent calls are rejected and `eth_syncing` reports
+//! `true`. The syncer sets the flag once it has caught up with its peers.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Whether the node has completed its initial sync
+#[derive(Debug, Clone)]
+pub struct SyncReadiness(Arc<AtomicBool>);
+
+impl SyncReadiness {
+    /// A flag that stays unset until [`SyncReadiness::set_ready`] is called
+    pub fn syncing() -> Self {
+        Self(Arc::new(AtomicBool::new(false)))
+    }
+
+    /// A flag that is already set, for nodes that don't sync
+    pub fn ready() -> Self {
+        Self(Arc::new(AtomicBool::new(true)))
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// Mark the initial sync as complete
+    pub fn set_ready(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+impl Default for SyncReadiness {
+    fn default() -> Self {
+        Self::ready()
+    }
+}
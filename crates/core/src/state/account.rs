@@ -1,13 +1,14 @@
 use norn_common::types::{Hash, PublicKey, Address};
 use norn_common::error::{NornError, Result};
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 use num_bigint::BigUint;
 use num_traits::{Zero, One};
-use sha2::Digest;
+use super::hasher::StateHashConfig;
 
 /// 账户状态
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -82,11 +83,97 @@ pub struct AccountStateManager {
     
     /// 状态根哈希
     state_root: Arc<RwLock<Hash>>,
+
+    /// 状态根计算次数
+    root_computations: AtomicU64,
+
+    /// 自上次 `take_dirty` 以来修改过的账户与存储项
+    dirty: std::sync::Mutex<DirtyState>,
+
+    /// 是否记录修改过的账户与存储项
+    track_dirty: AtomicBool,
+
+    /// 检查点之后的撤销日志，未设置检查点时为 None
+    undo: std::sync::Mutex<Option<UndoLog>>,
+
+    /// 写时复制副本的底层状态，未载入的账户与存储项从这里读取
+    base: std::sync::RwLock<Option<Arc<AccountStateManager>>>,
+
+    /// 已从底层状态载入的账户与存储项
+    faulted: std::sync::Mutex<FaultedState>,
     
     /// 配置
     config: AccountStateConfig,
 }
 
+/// 副本已从底层状态载入的部分
+///
+/// 载入后本地的值即为副本的值：已载入但本地没有的账户或存储项在副本中不存在。
+#[derive(Debug, Default)]
+struct FaultedState {
+    /// 全部账户与存储均已载入
+    all: bool,
+
+    /// 已载入的账户
+    accounts: HashSet<Address>,
+
+    /// 已载入全部存储的账户
+    account_storage: HashSet<Address>,
+
+    /// 已载入的存储项（地址，键）
+    storage: HashSet<(Address, Vec<u8>)>,
+}
+
+impl FaultedState {
+    fn has_account(&self, address: &Address) -> bool {
+        self.all || self.accounts.contains(address)
+    }
+
+    fn has_storage(&self, address: &Address, key: &[u8]) -> bool {
+        self.all || self.account_storage.contains(address) || self.storage.contains(&(*address, key.to_vec()))
+    }
+}
+
+/// 检查点之后每个账户和存储项首次修改前的值
+///
+/// 由 [`AccountStateManager::take_checkpoint`] 取出，用于之后撤销一个已提交的区块。
+#[derive(Debug, Default)]
+pub struct UndoLog {
+    state_root: Hash,
+    accounts: HashMap<Address, Option<AccountState>>,
+    storage: HashMap<(Address, Vec<u8>), Option<StorageItem>>,
+}
+
+impl UndoLog {
+    /// 撤销时会恢复的账户
+    pub fn accounts(&self) -> impl Iterator<Item = &Address> {
+        self.accounts.keys()
+    }
+}
+
+/// 修改过的账户与存储项，持久化时只写入这些
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DirtyState {
+    /// 账户（含创建、更新与删除）
+    pub accounts: HashSet<Address>,
+
+    /// 存储项（地址，键）
+    pub storage: HashSet<(Address, Vec<u8>)>,
+}
+
+impl DirtyState {
+    /// 是否没有任何修改
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.storage.is_empty()
+    }
+
+    /// 合并另一组修改
+    pub fn extend(&mut self, other: DirtyState) {
+        self.accounts.extend(other.accounts);
+        self.storage.extend(other.storage);
+    }
+}
+
 /// 账户状态配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountStateConfig {
@@ -107,6 +194,10 @@ pub struct AccountStateConfig {
     
     /// 快照间隔
     pub snapshot_interval: u64,
+
+    /// 状态根哈希算法
+    #[serde(default)]
+    pub hash: StateHashConfig,
 }
 
 impl Default for AccountStateConfig {
@@ -118,6 +209,7 @@ impl Default for AccountStateConfig {
             max_storage_items: 10000000,
             enable_snapshots: true,
             snapshot_interval: 1000,
+            hash: StateHashConfig::default(),
         }
     }
 }
@@ -196,14 +288,272 @@ impl AccountStateManager {
             accounts: Arc::new(RwLock::new(HashMap::new())),
             storage: Arc::new(RwLock::new(HashMap::new())),
             state_root: Arc::new(RwLock::new(Hash::default())),
+            root_computations: AtomicU64::new(0),
+            dirty: std::sync::Mutex::new(DirtyState::default()),
+            track_dirty: AtomicBool::new(false),
+            undo: std::sync::Mutex::new(None),
+            base: std::sync::RwLock::new(None),
+            faulted: std::sync::Mutex::new(FaultedState::default()),
             config,
         }
     }
 
+    /// 开始记录修改过的账户与存储项，供 `take_dirty` 取出
+    ///
+    /// 默认不记录，以免没有调用方取出时记录无限增长
+    pub fn track_dirty(&self) {
+        self.track_dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// 取出并清空自上次调用以来修改过的账户与存储项
+    pub fn take_dirty(&self) -> DirtyState {
+        std::mem::take(&mut *self.dirty.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// 自上次 `take_dirty` 以来修改过的账户与存储项，不清空
+    pub fn dirty(&self) -> DirtyState {
+        self.dirty.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// 放回未能持久化的修改，下次 `take_dirty` 时一并取出
+    pub fn restore_dirty(&self, dirty: DirtyState) {
+        self.dirty.lock().unwrap_or_else(|e| e.into_inner()).extend(dirty);
+    }
+
+    /// 设置检查点，之后的修改可以用 [`Self::revert_checkpoint`] 撤销
+    ///
+    /// 用于执行区块：执行失败时恢复到执行前的状态。已有检查点时替换它。
+    pub async fn begin_checkpoint(&self) {
+        let state_root = *self.state_root.read().await;
+        *self.lock_undo() = Some(UndoLog { state_root, ..Default::default() });
+    }
+
+    /// 保留检查点之后的修改并丢弃撤销日志
+    pub fn discard_checkpoint(&self) {
+        self.lock_undo().take();
+    }
+
+    /// 保留检查点之后的修改并取出撤销日志，没有检查点时返回 None
+    ///
+    /// 用于已提交的区块：链重组时用 [`Self::revert_undo`] 撤销它。
+    pub fn take_checkpoint(&self) -> Option<UndoLog> {
+        self.lock_undo().take()
+    }
+
+    /// 撤销检查点之后的全部修改，没有检查点时不做任何事
+    ///
+    /// 恢复的账户与存储项仍记为已修改，以便持久化时写回原值。
+    pub async fn revert_checkpoint(&self) {
+        let Some(undo) = self.lock_undo().take() else {
+            return;
+        };
+        self.revert_undo(undo).await;
+    }
+
+    /// 撤销 `undo` 记录的修改
+    ///
+    /// 撤销多个区块时须从最新的区块开始。恢复的账户与存储项仍记为已修改，
+    /// 以便持久化时写回原值。
+    pub async fn revert_undo(&self, undo: UndoLog) {
+        let mut accounts = self.accounts.write().await;
+        let mut storage = self.storage.write().await;
+        for (address, account) in undo.accounts {
+            match account {
+                Some(account) => accounts.insert(address, account),
+                None => accounts.remove(&address),
+            };
+            self.mark_account(&address);
+        }
+        for ((address, key), item) in undo.storage {
+            match item {
+                Some(item) => {
+                    storage.entry(address).or_default().insert(key.clone(), item);
+                }
+                None => {
+                    if let Some(account_storage) = storage.get_mut(&address) {
+                        account_storage.remove(&key);
+                        if account_storage.is_empty() {
+                            storage.remove(&address);
+                        }
+                    }
+                }
+            }
+            self.mark_storage(&address, &key);
+        }
+        *self.state_root.write().await = undo.state_root;
+    }
+
+    fn lock_undo(&self) -> std::sync::MutexGuard<'_, Option<UndoLog>> {
+        self.undo.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 在检查点之后首次修改账户前记录其原值
+    fn journal_account(&self, address: &Address, old: Option<&AccountState>) {
+        if let Some(undo) = self.lock_undo().as_mut() {
+            undo.accounts.entry(*address).or_insert_with(|| old.cloned());
+        }
+    }
+
+    /// 在检查点之后首次修改存储项前记录其原值
+    fn journal_storage(&self, address: &Address, key: &[u8], old: Option<&StorageItem>) {
+        if let Some(undo) = self.lock_undo().as_mut() {
+            undo.storage.entry((*address, key.to_vec())).or_insert_with(|| old.cloned());
+        }
+    }
+
+    /// 在检查点之后首次修改前记录账户全部存储项的原值
+    fn journal_account_storage(&self, address: &Address, old: Option<&HashMap<Vec<u8>, StorageItem>>) {
+        for (key, item) in old.into_iter().flatten() {
+            self.journal_storage(address, key, Some(item));
+        }
+    }
+
+    fn mark_account(&self, address: &Address) {
+        if !self.track_dirty.load(Ordering::Relaxed) {
+            return;
+        }
+        self.dirty.lock().unwrap_or_else(|e| e.into_inner()).accounts.insert(*address);
+    }
+
+    fn mark_storage(&self, address: &Address, key: &[u8]) {
+        if !self.track_dirty.load(Ordering::Relaxed) {
+            return;
+        }
+        self.dirty.lock().unwrap_or_else(|e| e.into_inner()).storage.insert((*address, key.to_vec()));
+    }
+
+    fn base(&self) -> Option<Arc<AccountStateManager>> {
+        self.base.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn lock_faulted(&self) -> std::sync::MutexGuard<'_, FaultedState> {
+        self.faulted.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 副本首次访问账户前从底层状态载入它
+    async fn fault_in_account(&self, address: &Address) -> Result<()> {
+        let Some(base) = self.base() else {
+            return Ok(());
+        };
+        if self.lock_faulted().has_account(address) {
+            return Ok(());
+        }
+
+        let account = Box::pin(base.get_account(address)).await?;
+        let mut accounts = self.accounts.write().await;
+        // 等待期间可能已被载入或修改
+        let mut faulted = self.lock_faulted();
+        if !faulted.has_account(address) {
+            faulted.accounts.insert(*address);
+            if let Some(account) = account {
+                accounts.insert(*address, account);
+            }
+        }
+        Ok(())
+    }
+
+    /// 副本首次访问存储项前从底层状态载入它
+    async fn fault_in_storage(&self, address: &Address, key: &[u8]) -> Result<()> {
+        let Some(base) = self.base() else {
+            return Ok(());
+        };
+        if self.lock_faulted().has_storage(address, key) {
+            return Ok(());
+        }
+
+        let item = Box::pin(base.storage_item(address, key)).await?;
+        let mut storage = self.storage.write().await;
+        let mut faulted = self.lock_faulted();
+        if !faulted.has_storage(address, key) {
+            faulted.storage.insert((*address, key.to_vec()));
+            if let Some(item) = item {
+                storage.entry(*address).or_default().insert(key.to_vec(), item);
+            }
+        }
+        Ok(())
+    }
+
+    /// 副本删除或替换账户全部存储前从底层状态载入它
+    async fn fault_in_account_storage(&self, address: &Address) -> Result<()> {
+        let Some(base) = self.base() else {
+            return Ok(());
+        };
+        {
+            let faulted = self.lock_faulted();
+            if faulted.all || faulted.account_storage.contains(address) {
+                return Ok(());
+            }
+        }
+
+        let items = Box::pin(base.account_storage(address)).await?;
+        let mut storage = self.storage.write().await;
+        let mut faulted = self.lock_faulted();
+        if faulted.account_storage.insert(*address) {
+            for (key, item) in items {
+                if !faulted.storage.contains(&(*address, key.clone())) {
+                    storage.entry(*address).or_default().insert(key, item);
+                }
+            }
+            faulted.storage.retain(|(a, _)| a != address);
+        }
+        Ok(())
+    }
+
+    /// 副本遍历全部状态前载入底层状态中尚未载入的部分
+    async fn fault_in_all(&self) -> Result<()> {
+        let Some(base) = self.base() else {
+            return Ok(());
+        };
+        if self.lock_faulted().all {
+            return Ok(());
+        }
+
+        Box::pin(base.fault_in_all()).await?;
+        let base_accounts = base.accounts.read().await;
+        let base_storage = base.storage.read().await;
+        let mut accounts = self.accounts.write().await;
+        let mut storage = self.storage.write().await;
+        let mut faulted = self.lock_faulted();
+        if faulted.all {
+            return Ok(());
+        }
+        for (address, account) in base_accounts.iter() {
+            if !faulted.accounts.contains(address) {
+                accounts.insert(*address, account.clone());
+            }
+        }
+        for (address, items) in base_storage.iter() {
+            if faulted.account_storage.contains(address) {
+                continue;
+            }
+            for (key, item) in items {
+                if !faulted.storage.contains(&(*address, key.clone())) {
+                    storage.entry(*address).or_default().insert(key.clone(), item.clone());
+                }
+            }
+        }
+        *faulted = FaultedState { all: true, ..Default::default() };
+        Ok(())
+    }
+
+    /// 存储项，副本中未载入时从底层状态读取
+    async fn storage_item(&self, address: &Address, key: &[u8]) -> Result<Option<StorageItem>> {
+        self.fault_in_storage(address, key).await?;
+        let storage = self.storage.read().await;
+        Ok(storage.get(address).and_then(|account_storage| account_storage.get(key)).cloned())
+    }
+
+    /// 账户的全部存储项
+    async fn account_storage(&self, address: &Address) -> Result<HashMap<Vec<u8>, StorageItem>> {
+        self.fault_in_account_storage(address).await?;
+        Ok(self.storage.read().await.get(address).cloned().unwrap_or_default())
+    }
+
     /// 获取账户状态
     pub async fn get_account(&self, address: &Address) -> Result<Option<AccountState>> {
         debug!("Getting account state for address: {:?}", address);
-        
+
+        self.fault_in_account(address).await?;
         let accounts = self.accounts.read().await;
         let account = accounts.get(address).cloned();
         
@@ -214,7 +564,8 @@ impl AccountStateManager {
     /// 设置账户状态
     pub async fn set_account(&self, address: &Address, account: AccountState) -> Result<()> {
         debug!("Setting account state for address: {:?}", address);
-        
+
+        self.fault_in_account(address).await?;
         let mut accounts = self.accounts.write().await;
         let old_account = accounts.get(address).cloned();
         
@@ -222,6 +573,7 @@ impl AccountStateManager {
         if old_account.is_none() && accounts.len() >= self.config.max_accounts {
             return Err(NornError::Internal("Maximum account limit reached".to_string()));
         }
+        self.journal_account(address, old_account.as_ref());
 
         let change = if old_account.is_none() {
             StateChange::AccountCreated {
@@ -245,14 +597,61 @@ impl AccountStateManager {
         Ok(())
     }
 
+    /// 批量载入账户（创世分配、状态导入）
+    ///
+    /// 在一次写锁内插入全部账户，不逐个记录变更，最后只计算一次状态根。
+    /// 新账户会超出账户数量限制时不插入任何账户。返回载入的账户数。
+    pub async fn load_accounts(&self, accounts: Vec<AccountState>) -> Result<usize> {
+        let loaded = accounts.len();
+        {
+            let mut current = self.accounts.write().await;
+            // 在同一把锁下载入副本尚未载入的账户，其他任务无法在其间载入或修改
+            if let Some(base) = self.base() {
+                for account in &accounts {
+                    if self.lock_faulted().has_account(&account.address) {
+                        continue;
+                    }
+                    let old = Box::pin(base.get_account(&account.address)).await?;
+                    self.lock_faulted().accounts.insert(account.address);
+                    if let Some(old) = old {
+                        current.insert(account.address, old);
+                    }
+                }
+            }
+
+            let new_accounts: HashSet<_> = accounts
+                .iter()
+                .map(|account| account.address)
+                .filter(|address| !current.contains_key(address))
+                .collect();
+            if current.len() + new_accounts.len() > self.config.max_accounts {
+                return Err(NornError::Internal("Maximum account limit reached".to_string()));
+            }
+
+            current.reserve(new_accounts.len());
+            for account in accounts {
+                self.journal_account(&account.address, current.get(&account.address));
+                self.mark_account(&account.address);
+                current.insert(account.address, account);
+            }
+        }
+
+        self.update_state_root().await?;
+        info!("Loaded {} accounts", loaded);
+        Ok(loaded)
+    }
+
     /// 删除账户
     pub async fn delete_account(&self, address: &Address) -> Result<()> {
         debug!("Deleting account: {:?}", address);
-        
+
+        self.fault_in_account(address).await?;
+        self.fault_in_account_storage(address).await?;
         let mut accounts = self.accounts.write().await;
         let old_account = accounts.remove(address);
         
         if let Some(account) = old_account {
+            self.journal_account(address, Some(&account));
             let change = StateChange::AccountDeleted {
                 address: *address,
                 old_account: account,
@@ -263,6 +662,7 @@ impl AccountStateManager {
             
             // 删除相关存储
             let mut storage = self.storage.write().await;
+            self.journal_account_storage(address, storage.get(address));
             storage.remove(address);
             
             debug!("Account deleted: {:?}", address);
@@ -276,12 +676,8 @@ impl AccountStateManager {
     /// 获取存储值
     pub async fn get_storage(&self, address: &Address, key: &[u8]) -> Result<Option<Vec<u8>>> {
         debug!("Getting storage for address: {:?}, key: {:?}", address, key);
-        
-        let storage = self.storage.read().await;
-        let value = storage
-            .get(address)
-            .and_then(|account_storage| account_storage.get(key))
-            .map(|item| item.value.clone());
+
+        let value = self.storage_item(address, key).await?.map(|item| item.value);
         
         debug!("Storage value for {:?}/{:?}: {:?}", address, key, value.is_some());
         Ok(value)
@@ -291,6 +687,7 @@ impl AccountStateManager {
     pub async fn set_storage(&self, address: &Address, key: Vec<u8>, value: Vec<u8>) -> Result<()> {
         debug!("Setting storage for address: {:?}, key: {:?}", address, key);
 
+        self.fault_in_storage(address, &key).await?;
         let mut storage = self.storage.write().await;
         let account_storage = storage.entry(*address).or_insert_with(HashMap::new);
 
@@ -299,6 +696,7 @@ impl AccountStateManager {
             return Err(NornError::Internal("Maximum storage limit reached".to_string()));
         }
 
+        self.journal_storage(address, &key, account_storage.get(&key));
         let old_value = account_storage.get(&key).map(|item| item.value.clone());
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -340,10 +738,12 @@ impl AccountStateManager {
     /// 删除存储值
     pub async fn delete_storage(&self, address: &Address, key: &[u8]) -> Result<()> {
         debug!("Deleting storage for address: {:?}, key: {:?}", address, key);
-        
+
+        self.fault_in_storage(address, key).await?;
         let mut storage = self.storage.write().await;
         if let Some(account_storage) = storage.get_mut(address) {
             if let Some(item) = account_storage.remove(key) {
+                self.journal_storage(address, key, Some(&item));
                 let change = StateChange::StorageDeleted {
                     address: *address,
                     key: key.to_vec(),
@@ -362,11 +762,26 @@ impl AccountStateManager {
         Ok(())
     }
 
+    /// 删除账户的全部存储项，保留账户本身
+    pub async fn clear_storage(&self, address: &Address) -> Result<()> {
+        self.fault_in_account_storage(address).await?;
+        let mut storage = self.storage.write().await;
+        if let Some(account_storage) = storage.remove(address) {
+            for key in account_storage.keys() {
+                self.mark_storage(address, key);
+            }
+            self.journal_account_storage(address, Some(&account_storage));
+        }
+        Ok(())
+    }
+
     /// 更新账户余额
     pub async fn update_balance(&self, address: &Address, new_balance: BigUint) -> Result<()> {
         debug!("Updating balance for address: {:?}, new balance: {}", address, new_balance);
-        
+
+        self.fault_in_account(address).await?;
         let mut accounts = self.accounts.write().await;
+        self.journal_account(address, accounts.get(address));
         let account = accounts.entry(*address).or_insert_with(|| AccountState {
             address: *address,
             balance: BigUint::zero(),
@@ -433,8 +848,10 @@ impl AccountStateManager {
     /// 增加 Nonce
     pub async fn increment_nonce(&self, address: &Address) -> Result<u64> {
         debug!("Incrementing nonce for address: {:?}", address);
-        
+
+        self.fault_in_account(address).await?;
         let mut accounts = self.accounts.write().await;
+        self.journal_account(address, accounts.get(address));
         let account = accounts.entry(*address).or_insert_with(|| AccountState {
             address: *address,
             balance: BigUint::zero(),
@@ -460,6 +877,7 @@ impl AccountStateManager {
             .as_secs();
 
         let new_nonce = account.nonce;
+        self.mark_account(address);
         
         debug!("Nonce incremented for address: {:?}, new nonce: {}", address, new_nonce);
         Ok(new_nonce)
@@ -484,6 +902,11 @@ impl AccountStateManager {
         Ok(current_nonce == expected_nonce)
     }
 
+    /// 状态根哈希配置
+    pub fn hash_config(&self) -> &StateHashConfig {
+        &self.config.hash
+    }
+
     /// 获取状态根哈希
     pub async fn get_state_root(&self) -> Result<Hash> {
         let state_root = self.state_root.read().await;
@@ -493,7 +916,9 @@ impl AccountStateManager {
     /// 计算状态根哈希
     pub async fn compute_state_root(&self) -> Result<Hash> {
         debug!("Computing state root hash");
-        
+        self.root_computations.fetch_add(1, Ordering::Relaxed);
+
+        self.fault_in_all().await?;
         let accounts = self.accounts.read().await;
         let storage = self.storage.read().await;
         
@@ -517,7 +942,7 @@ impl AccountStateManager {
         }
 
         // 计算哈希
-        let mut hasher = sha2::Sha256::new();
+        let mut hasher = self.config.hash.algorithm.hasher();
         let mut sorted_keys: Vec<_> = state_data.keys().collect();
         sorted_keys.sort();
         
@@ -527,11 +952,14 @@ impl AccountStateManager {
         }
         
         let hash = hasher.finalize();
-        let mut result = [0u8; 32];
-        result.copy_from_slice(&hash);
         
-        debug!("State root hash computed: {:?}", Hash(result));
-        Ok(Hash(result))
+        debug!("State root hash computed: {:?}", hash);
+        Ok(hash)
+    }
+
+    /// 状态根计算次数
+    pub fn state_root_computations(&self) -> u64 {
+        self.root_computations.load(Ordering::Relaxed)
     }
 
     /// 更新状态根哈希
@@ -545,7 +973,8 @@ impl AccountStateManager {
     /// 创建状态快照
     pub async fn create_snapshot(&self, snapshot_id: u64) -> Result<StateSnapshot> {
         debug!("Creating state snapshot: {}", snapshot_id);
-        
+
+        self.fault_in_all().await?;
         let accounts = self.accounts.read().await;
         let storage = self.storage.read().await;
         let state_root = self.state_root.read().await;
@@ -566,17 +995,117 @@ impl AccountStateManager {
         Ok(snapshot)
     }
 
+    /// 创建独立副本，复制当前全部账户与存储
+    pub async fn copy_state(&self) -> Result<Self> {
+        self.fault_in_all().await?;
+        let accounts = self.accounts.read().await.clone();
+        let storage = self.storage.read().await.clone();
+        let state_root = *self.state_root.read().await;
+
+        Ok(Self {
+            accounts: Arc::new(RwLock::new(accounts)),
+            storage: Arc::new(RwLock::new(storage)),
+            state_root: Arc::new(RwLock::new(state_root)),
+            ..Self::new(self.config.clone())
+        })
+    }
+
+    /// 创建写时复制副本
+    ///
+    /// 副本不复制状态：账户与存储项在首次访问时从原状态载入，此后以副本中的
+    /// 值为准。对副本的修改不会影响原状态；原状态的修改在副本载入相应的账户
+    /// 或存储项之前对副本可见。遍历全部状态的操作会先载入全部状态。
+    pub async fn fork(self: &Arc<Self>) -> Self {
+        let state_root = *self.state_root.read().await;
+
+        Self {
+            accounts: Arc::new(RwLock::new(HashMap::new())),
+            storage: Arc::new(RwLock::new(HashMap::new())),
+            state_root: Arc::new(RwLock::new(state_root)),
+            root_computations: AtomicU64::new(0),
+            dirty: std::sync::Mutex::new(DirtyState::default()),
+            track_dirty: AtomicBool::new(false),
+            undo: std::sync::Mutex::new(None),
+            base: std::sync::RwLock::new(Some(Arc::clone(self))),
+            faulted: std::sync::Mutex::new(FaultedState::default()),
+            config: self.config.clone(),
+        }
+    }
+
+    /// 载入底层状态的全部内容后与其断开，使底层状态可以释放
+    ///
+    /// 之后副本独立于底层状态。不是副本时不做任何事。
+    pub async fn detach(&self) -> Result<()> {
+        self.fault_in_all().await?;
+        self.base.write().unwrap_or_else(|e| e.into_inner()).take();
+        Ok(())
+    }
+
+    /// 将 `changes` 所列账户与存储项在 `source` 中的当前值复制到本状态
+    ///
+    /// `source` 中已删除的账户连同其存储一并删除；状态根取 `source` 的。
+    /// 不记录修改与撤销日志，用于由副本构建某一时刻的状态快照。与存储 GC
+    /// 相同，删除后又重新创建的账户无法与原账户区分，原账户未被改写的存储项会保留。
+    pub async fn copy_changes_from(&self, source: &AccountStateManager, changes: &DirtyState) -> Result<()> {
+        for address in &changes.accounts {
+            self.fault_in_account(address).await?;
+            match source.get_account(address).await? {
+                Some(account) => {
+                    self.accounts.write().await.insert(*address, account);
+                }
+                None => {
+                    self.fault_in_account_storage(address).await?;
+                    self.accounts.write().await.remove(address);
+                    self.storage.write().await.remove(address);
+                }
+            }
+        }
+
+        for (address, key) in &changes.storage {
+            self.fault_in_storage(address, key).await?;
+            let item = source.storage_item(address, key).await?;
+            let mut storage = self.storage.write().await;
+            match item {
+                Some(item) => {
+                    storage.entry(*address).or_default().insert(key.clone(), item);
+                }
+                None => {
+                    if let Some(account_storage) = storage.get_mut(address) {
+                        account_storage.remove(key);
+                        if account_storage.is_empty() {
+                            storage.remove(address);
+                        }
+                    }
+                }
+            }
+        }
+
+        *self.state_root.write().await = *source.state_root.read().await;
+        Ok(())
+    }
+
     /// 恢复状态快照
     pub async fn restore_snapshot(&self, snapshot: &StateSnapshot) -> Result<()> {
         debug!("Restoring state snapshot: {}", snapshot.id);
-        
+
+        self.fault_in_all().await?;
         {
             let mut accounts = self.accounts.write().await;
+            for address in accounts.keys().chain(snapshot.accounts.keys()) {
+                self.journal_account(address, accounts.get(address));
+                self.mark_account(address);
+            }
             *accounts = snapshot.accounts.clone();
         }
         
         {
             let mut storage = self.storage.write().await;
+            for (address, account_storage) in storage.iter().chain(snapshot.storage.iter()) {
+                for key in account_storage.keys() {
+                    self.journal_storage(address, key, storage.get(address).and_then(|s| s.get(key)));
+                    self.mark_storage(address, key);
+                }
+            }
             *storage = snapshot.storage.clone();
         }
         
@@ -589,10 +1118,83 @@ impl AccountStateManager {
         Ok(())
     }
 
+    /// 按地址排序的全部账户，用于状态导出与跨节点比对
+    pub async fn accounts_sorted(&self) -> Vec<(Address, AccountState)> {
+        if let Err(e) = self.fault_in_all().await {
+            error!("Failed to load the base state: {}", e);
+        }
+        let mut accounts: Vec<_> = self.accounts
+            .read()
+            .await
+            .iter()
+            .map(|(address, account)| (*address, account.clone()))
+            .collect();
+        accounts.sort_unstable_by_key(|(address, _)| address.0);
+        accounts
+    }
+
+    /// 计算从本状态变为 `other` 所需的变更
+    ///
+    /// 变更按地址排序，每个账户的变更在前，其存储的变更在后。
+    /// 仅余额（及更新时间）不同的账户报告为 `BalanceChanged`。
+    pub async fn state_diff(&self, other: &AccountStateManager) -> Vec<StateChange> {
+        let ours: BTreeMap<_, _> = self.accounts_sorted().await.into_iter().map(|(a, s)| (a.0, s)).collect();
+        let theirs: BTreeMap<_, _> = other.accounts_sorted().await.into_iter().map(|(a, s)| (a.0, s)).collect();
+        // accounts_sorted 已载入两者的全部状态
+        let our_storage = self.storage.read().await.clone();
+        let their_storage = other.storage.read().await.clone();
+
+        let addresses: BTreeSet<_> = ours.keys()
+            .chain(theirs.keys())
+            .copied()
+            .chain(our_storage.keys().chain(their_storage.keys()).map(|address| address.0))
+            .collect();
+
+        let mut changes = Vec::new();
+        for raw in addresses {
+            let address = Address(raw);
+            match (ours.get(&raw), theirs.get(&raw)) {
+                (None, Some(account)) => changes.push(StateChange::AccountCreated {
+                    address,
+                    account: account.clone(),
+                }),
+                (Some(old_account), None) => changes.push(StateChange::AccountDeleted {
+                    address,
+                    old_account: old_account.clone(),
+                }),
+                (Some(old_account), Some(new_account)) if old_account != new_account => {
+                    let balance_only = AccountState {
+                        balance: new_account.balance.clone(),
+                        updated_at: new_account.updated_at,
+                        ..old_account.clone()
+                    };
+                    if balance_only == *new_account {
+                        changes.push(StateChange::BalanceChanged {
+                            address,
+                            old_balance: old_account.balance.to_string(),
+                            new_balance: new_account.balance.to_string(),
+                        });
+                    } else {
+                        changes.push(StateChange::AccountUpdated {
+                            address,
+                            old_account: old_account.clone(),
+                            new_account: new_account.clone(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+
+            diff_storage(address, our_storage.get(&address), their_storage.get(&address), &mut changes);
+        }
+        changes
+    }
+
     /// 清理已删除的账户
     pub async fn cleanup_deleted_accounts(&self) -> Result<usize> {
         debug!("Cleaning up deleted accounts");
-        
+
+        self.fault_in_all().await?;
         let mut accounts = self.accounts.write().await;
         let mut storage = self.storage.write().await;
         
@@ -604,6 +1206,9 @@ impl AccountStateManager {
             .collect();
         
         for address in addresses_to_remove {
+            self.journal_account(&address, accounts.get(&address));
+            self.journal_account_storage(&address, storage.get(&address));
+            self.mark_account(&address);
             accounts.remove(&address);
             storage.remove(&address);
             deleted_count += 1;
@@ -615,6 +1220,9 @@ impl AccountStateManager {
 
     /// 获取统计信息
     pub async fn get_stats(&self) -> AccountStateStats {
+        if let Err(e) = self.fault_in_all().await {
+            error!("Failed to load the base state: {}", e);
+        }
         let accounts = self.accounts.read().await;
         let storage = self.storage.read().await;
         
@@ -650,6 +1258,15 @@ impl AccountStateManager {
         // 对于生产环境，应该使用StateHistory管理器
         debug!("Recording state change: {:?}", change);
 
+        match &change {
+            StateChange::AccountCreated { address, .. }
+            | StateChange::AccountUpdated { address, .. }
+            | StateChange::AccountDeleted { address, .. }
+            | StateChange::BalanceChanged { address, .. } => self.mark_account(address),
+            StateChange::StorageSet { address, key, .. }
+            | StateChange::StorageDeleted { address, key, .. } => self.mark_storage(address, key),
+        }
+
         // 发送到tracing系统作为日志
         match &change {
             StateChange::AccountCreated { address, .. } => {
@@ -686,12 +1303,22 @@ impl AccountStateManager {
     }
 
     /// Get accounts lock (for state root calculation and other advanced operations)
+    ///
+    /// 副本会先载入全部状态。
     pub async fn accounts_lock(&self) -> Arc<RwLock<HashMap<Address, AccountState>>> {
+        if let Err(e) = self.fault_in_all().await {
+            error!("Failed to load the base state: {}", e);
+        }
         Arc::clone(&self.accounts)
     }
 
     /// Get storage lock (for state root calculation and other advanced operations)
+    ///
+    /// 副本会先载入全部状态。
     pub async fn storage_lock(&self) -> Arc<RwLock<HashMap<Address, HashMap<Vec<u8>, StorageItem>>>> {
+        if let Err(e) = self.fault_in_all().await {
+            error!("Failed to load the base state: {}", e);
+        }
         Arc::clone(&self.storage)
     }
 }
@@ -702,6 +1329,37 @@ impl Default for AccountStateManager {
     }
 }
 
+/// 按键排序比较一个账户的存储，只比较存储值
+fn diff_storage(
+    address: Address,
+    ours: Option<&HashMap<Vec<u8>, StorageItem>>,
+    theirs: Option<&HashMap<Vec<u8>, StorageItem>>,
+    changes: &mut Vec<StateChange>,
+) {
+    let empty = HashMap::new();
+    let (ours, theirs) = (ours.unwrap_or(&empty), theirs.unwrap_or(&empty));
+
+    let keys: BTreeSet<_> = ours.keys().chain(theirs.keys()).collect();
+    for key in keys {
+        match (ours.get(key), theirs.get(key)) {
+            (Some(old), None) => changes.push(StateChange::StorageDeleted {
+                address,
+                key: key.clone(),
+                old_value: old.value.clone(),
+            }),
+            (old, Some(new)) if old.map(|item| &item.value) != Some(&new.value) => {
+                changes.push(StateChange::StorageSet {
+                    address,
+                    key: key.clone(),
+                    old_value: old.map(|item| item.value.clone()),
+                    new_value: new.value.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
 /// 账户状态统计信息
 #[derive(Debug, Clone, Default)]
 pub struct AccountStateStats {
@@ -804,6 +1462,79 @@ mod tests {
         assert_eq!(deleted, None);
     }
 
+    #[tokio::test]
+    async fn test_revert_checkpoint() {
+        let manager = AccountStateManager::new(AccountStateConfig::default());
+        manager.track_dirty();
+        let funded = Address([1; 20]);
+        let created = Address([2; 20]);
+        manager.update_balance(&funded, BigUint::from(100u32)).await.unwrap();
+        manager.set_storage(&funded, b"kept".to_vec(), b"old".to_vec()).await.unwrap();
+        manager.update_state_root().await.unwrap();
+        let root = manager.get_state_root().await.unwrap();
+        manager.take_dirty();
+
+        manager.begin_checkpoint().await;
+        manager.subtract_balance(&funded, &BigUint::from(40u32)).await.unwrap();
+        manager.increment_nonce(&funded).await.unwrap();
+        manager.set_storage(&funded, b"kept".to_vec(), b"new".to_vec()).await.unwrap();
+        manager.set_storage(&funded, b"added".to_vec(), b"x".to_vec()).await.unwrap();
+        manager.add_balance(&created, &BigUint::from(40u32)).await.unwrap();
+        manager.update_state_root().await.unwrap();
+        manager.revert_checkpoint().await;
+
+        assert_eq!(manager.get_balance(&funded).await.unwrap(), BigUint::from(100u32));
+        assert_eq!(manager.get_nonce(&funded).await.unwrap(), 0);
+        assert_eq!(manager.get_storage(&funded, b"kept").await.unwrap(), Some(b"old".to_vec()));
+        assert_eq!(manager.get_storage(&funded, b"added").await.unwrap(), None);
+        assert!(manager.get_account(&created).await.unwrap().is_none());
+        assert_eq!(manager.get_state_root().await.unwrap(), root);
+
+        // 恢复的账户仍记为已修改，下次持久化时写回原值
+        let dirty = manager.take_dirty();
+        assert!(dirty.accounts.contains(&funded) && dirty.accounts.contains(&created));
+
+        // 没有检查点时不撤销任何修改
+        manager.update_balance(&funded, BigUint::from(7u32)).await.unwrap();
+        manager.revert_checkpoint().await;
+        assert_eq!(manager.get_balance(&funded).await.unwrap(), BigUint::from(7u32));
+
+        // 丢弃检查点后保留修改
+        manager.begin_checkpoint().await;
+        manager.update_balance(&funded, BigUint::from(8u32)).await.unwrap();
+        manager.discard_checkpoint();
+        manager.revert_checkpoint().await;
+        assert_eq!(manager.get_balance(&funded).await.unwrap(), BigUint::from(8u32));
+    }
+
+    #[tokio::test]
+    async fn test_revert_undo_of_committed_blocks() {
+        let manager = AccountStateManager::new(AccountStateConfig::default());
+        let funded = Address([1; 20]);
+        manager.update_balance(&funded, BigUint::from(100u32)).await.unwrap();
+
+        // 两个已提交的区块，各保留撤销日志
+        let mut undo = Vec::new();
+        for (balance, slot) in [(90u32, b"a"), (80, b"b")] {
+            manager.begin_checkpoint().await;
+            manager.update_balance(&funded, BigUint::from(balance)).await.unwrap();
+            manager.set_storage(&funded, slot.to_vec(), b"x".to_vec()).await.unwrap();
+            undo.push(manager.take_checkpoint().unwrap());
+        }
+        assert!(manager.take_checkpoint().is_none());
+        assert_eq!(undo[1].accounts().collect::<Vec<_>>(), vec![&funded]);
+
+        // 从最新的区块开始撤销
+        manager.revert_undo(undo.pop().unwrap()).await;
+        assert_eq!(manager.get_balance(&funded).await.unwrap(), BigUint::from(90u32));
+        assert_eq!(manager.get_storage(&funded, b"b").await.unwrap(), None);
+        assert_eq!(manager.get_storage(&funded, b"a").await.unwrap(), Some(b"x".to_vec()));
+
+        manager.revert_undo(undo.pop().unwrap()).await;
+        assert_eq!(manager.get_balance(&funded).await.unwrap(), BigUint::from(100u32));
+        assert_eq!(manager.get_storage(&funded, b"a").await.unwrap(), None);
+    }
+
     #[tokio::test]
     async fn test_state_root() {
         let config = AccountStateConfig::default();
@@ -829,6 +1560,39 @@ mod tests {
         assert_ne!(state_root, Hash::default());
     }
 
+    #[tokio::test]
+    async fn test_keccak_state_root() {
+        let mut config = AccountStateConfig::default();
+        config.hash = StateHashConfig::new(crate::state::StateHashAlgorithm::Keccak256);
+        let manager = AccountStateManager::new(config);
+
+        let address = Address([1u8; 20]);
+        let account = AccountState {
+            address,
+            balance: BigUint::from(1000u64),
+            nonce: 3,
+            code_hash: None,
+            storage_root: Hash::default(),
+            account_type: AccountType::Normal,
+            created_at: 1234567890,
+            updated_at: 1234567890,
+            deleted: false,
+        };
+        manager.set_account(&address, account.clone()).await.unwrap();
+        manager.set_storage(&address, vec![0x02], vec![0xaa]).await.unwrap();
+        manager.update_state_root().await.unwrap();
+
+        // 按键排序的键值对：先是账户，再是其存储项
+        let account = manager.get_account(&address).await.unwrap().unwrap();
+        let mut preimage = address.0.to_vec();
+        preimage.extend(serde_json::to_vec(&account).unwrap());
+        preimage.extend([&address.0[..], &[0x02]].concat());
+        preimage.push(0xaa);
+
+        let expected = Hash(keccak_hash::keccak(&preimage).0);
+        assert_eq!(manager.get_state_root().await.unwrap(), expected);
+    }
+
     #[tokio::test]
     async fn test_snapshot() {
         let config = AccountStateConfig::default();
@@ -905,4 +1669,135 @@ mod tests {
         assert_eq!(stats.contract_accounts, 1);
         assert_eq!(stats.total_balance, BigUint::from(3000u64));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_load_accounts_in_bulk() {
+        let manager = AccountStateManager::default();
+        let accounts: Vec<_> = (0..10_000u32)
+            .map(|i| {
+                let mut address = Address::default();
+                address.0[..4].copy_from_slice(&i.to_be_bytes());
+                AccountState {
+                    address,
+                    balance: BigUint::from(i),
+                    nonce: 0,
+                    code_hash: None,
+                    storage_root: Hash::default(),
+                    account_type: AccountType::Normal,
+                    created_at: 0,
+                    updated_at: 0,
+                    deleted: false,
+                }
+            })
+            .collect();
+
+        assert_eq!(manager.load_accounts(accounts.clone()).await.unwrap(), 10_000);
+        assert_eq!(manager.state_root_computations(), 1);
+
+        for account in &accounts {
+            assert_eq!(manager.get_account(&account.address).await.unwrap().as_ref(), Some(account));
+        }
+        assert_eq!(manager.get_state_root().await.unwrap(), manager.compute_state_root().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_state_diff_reports_changed_balance() {
+        let ours = Arc::new(AccountStateManager::default());
+        for i in [3u8, 1, 2] {
+            ours.update_balance(&Address([i; 20]), BigUint::from(100u32)).await.unwrap();
+        }
+        let theirs = ours.fork().await;
+        theirs.update_balance(&Address([2u8; 20]), BigUint::from(250u32)).await.unwrap();
+
+        let addresses: Vec<_> = ours.accounts_sorted().await.into_iter().map(|(address, _)| address).collect();
+        assert_eq!(addresses, vec![Address([1u8; 20]), Address([2u8; 20]), Address([3u8; 20])]);
+
+        assert!(ours.state_diff(&ours.fork().await).await.is_empty());
+        let diff = ours.state_diff(&theirs).await;
+        assert_eq!(diff.len(), 1);
+        match &diff[0] {
+            StateChange::BalanceChanged { address, old_balance, new_balance } => {
+                assert_eq!(*address, Address([2u8; 20]));
+                assert_eq!(old_balance, "100");
+                assert_eq!(new_balance, "250");
+            }
+            change => panic!("unexpected change {:?}", change),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fork_copies_on_write() {
+        let base = Arc::new(AccountStateManager::default());
+        let (a, b, c) = (Address([1u8; 20]), Address([2u8; 20]), Address([3u8; 20]));
+        base.update_balance(&a, BigUint::from(100u32)).await.unwrap();
+        base.update_balance(&b, BigUint::from(200u32)).await.unwrap();
+        base.set_storage(&a, vec![1], vec![10]).await.unwrap();
+        base.set_storage(&a, vec![2], vec![20]).await.unwrap();
+
+        let fork = base.fork().await;
+        assert!(fork.accounts.read().await.is_empty());
+        assert_eq!(fork.get_balance(&a).await.unwrap(), BigUint::from(100u32));
+
+        // 修改只留在副本中
+        fork.update_balance(&a, BigUint::from(150u32)).await.unwrap();
+        fork.set_storage(&a, vec![1], vec![11]).await.unwrap();
+        fork.delete_account(&b).await.unwrap();
+        fork.update_balance(&c, BigUint::from(5u32)).await.unwrap();
+        assert_eq!(base.get_balance(&a).await.unwrap(), BigUint::from(100u32));
+        assert_eq!(base.get_storage(&a, &[1]).await.unwrap(), Some(vec![10]));
+        assert!(base.get_account(&b).await.unwrap().is_some());
+        assert!(base.get_account(&c).await.unwrap().is_none());
+        assert_eq!(fork.get_storage(&a, &[1]).await.unwrap(), Some(vec![11]));
+        assert_eq!(fork.get_storage(&a, &[2]).await.unwrap(), Some(vec![20]));
+        assert!(fork.get_account(&b).await.unwrap().is_none());
+
+        // 只有副本载入过的项被固定
+        base.set_storage(&a, vec![2], vec![21]).await.unwrap();
+        base.set_storage(&a, vec![3], vec![30]).await.unwrap();
+        assert_eq!(fork.get_storage(&a, &[2]).await.unwrap(), Some(vec![20]));
+        assert_eq!(fork.get_storage(&a, &[3]).await.unwrap(), Some(vec![30]));
+
+        // 副本的副本穿过两层读取
+        let nested = Arc::new(fork).fork().await;
+        nested.clear_storage(&a).await.unwrap();
+        assert_eq!(nested.get_balance(&c).await.unwrap(), BigUint::from(5u32));
+        assert_eq!(nested.get_storage(&a, &[2]).await.unwrap(), None);
+
+        // 遍历全部状态时看到合并后的状态
+        let addresses: Vec<_> = nested.accounts_sorted().await.into_iter().map(|(address, _)| address).collect();
+        assert_eq!(addresses, vec![a, c]);
+        assert!(nested.storage.read().await.get(&a).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fork_follows_changes_and_detaches() {
+        let source = Arc::new(AccountStateManager::default());
+        source.track_dirty();
+        let (a, b) = (Address([1u8; 20]), Address([2u8; 20]));
+        source.update_balance(&a, BigUint::from(100u32)).await.unwrap();
+        source.update_balance(&b, BigUint::from(200u32)).await.unwrap();
+        source.set_storage(&a, vec![1], vec![10]).await.unwrap();
+        source.set_storage(&b, vec![1], vec![20]).await.unwrap();
+        source.take_dirty();
+
+        let snapshot = Arc::new(source.fork().await);
+        snapshot.detach().await.unwrap();
+
+        source.update_balance(&a, BigUint::from(150u32)).await.unwrap();
+        source.delete_storage(&a, &[1]).await.unwrap();
+        source.delete_account(&b).await.unwrap();
+        assert_eq!(snapshot.get_balance(&a).await.unwrap(), BigUint::from(100u32));
+        assert_eq!(snapshot.get_storage(&b, &[1]).await.unwrap(), Some(vec![20]));
+
+        let next = snapshot.fork().await;
+        next.track_dirty();
+        next.copy_changes_from(&source, &source.dirty()).await.unwrap();
+        assert_eq!(next.get_balance(&a).await.unwrap(), BigUint::from(150u32));
+        assert_eq!(next.get_storage(&a, &[1]).await.unwrap(), None);
+        assert!(next.get_account(&b).await.unwrap().is_none());
+        assert_eq!(next.get_storage(&b, &[1]).await.unwrap(), None);
+        // 复制不记录修改
+        assert!(next.dirty().is_empty());
+        assert_eq!(snapshot.get_balance(&a).await.unwrap(), BigUint::from(100u32));
+    }
+}
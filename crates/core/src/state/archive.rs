@@ -0,0 +1,365 @@
+//! Historical state by re-execution
+//!
+//! A node without archive state only keeps the latest state. [`StateArchive`]
+//! snapshots the state and contract code every so many imported blocks and
+//! rebuilds the state at a block in between by forking the nearest snapshot
+//! at or below it and replaying the blocks after it the way they were
+//! imported. Replays are bounded by a block limit, so a query can't make the
+//! node re-execute an arbitrary stretch of chain.
+//!
+//! A snapshot is a copy-on-write fork of the one before it holding only the
+//! accounts, storage and code changed in between; the oldest snapshot kept
+//! holds everything, so dropped snapshots can be freed.
+//!
+//! Bodies of pruned blocks can't be replayed: with pruning enabled, only the
+//! state at a snapshot or at a block whose replay stays above the pruning
+//! boundary is served, and other queries fail with a "history pruned" error.
+//! Keeping more recent blocks than the snapshot interval avoids such gaps.
+
+use crate::blockchain::{Blockchain, BlockImportHook, ChainWrite};
+use crate::evm::{CodeStorage, EVMExecutor};
+use crate::execution::TransactionRouter;
+use crate::state::account::{AccountStateManager, DirtyState};
+use norn_common::error::{NornError, Result};
+use norn_common::types::Block;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, error};
+
+/// Snapshots kept by default
+pub const DEFAULT_MAX_ARCHIVE_SNAPSHOTS: usize = 16;
+
+/// Blocks between snapshots by default
+pub const DEFAULT_ARCHIVE_SNAPSHOT_INTERVAL: u64 = 64;
+
+/// State and contract code after a block
+#[derive(Clone)]
+struct Snapshot {
+    state: Arc<AccountStateManager>,
+    code: Arc<CodeStorage>,
+}
+
+/// Snapshots of the state by block height, used to serve historical state
+///
+/// As an import hook, the archive must come after the hook executing blocks
+/// and before the persistent state, which takes the changes of each block.
+pub struct StateArchive {
+    router: Arc<TransactionRouter>,
+    snapshots: RwLock<BTreeMap<u64, Snapshot>>,
+    /// Changes of the block being imported
+    staged: std::sync::Mutex<Option<DirtyState>>,
+    /// Changes committed since the newest snapshot
+    changes: std::sync::Mutex<DirtyState>,
+    snapshot_interval: u64,
+    max_snapshots: usize,
+    replay_limit: u64,
+}
+
+impl StateArchive {
+    /// Archive of the state `router` executes blocks on, replaying at most
+    /// `replay_limit` blocks per query
+    pub fn new(router: Arc<TransactionRouter>, replay_limit: u64) -> Self {
+        if let Some(executor) = router.evm_executor() {
+            executor.state_manager().track_dirty();
+        }
+        Self {
+            router,
+            snapshots: RwLock::new(BTreeMap::new()),
+            staged: std::sync::Mutex::new(None),
+            changes: std::sync::Mutex::new(DirtyState::default()),
+            snapshot_interval: DEFAULT_ARCHIVE_SNAPSHOT_INTERVAL,
+            max_snapshots: DEFAULT_MAX_ARCHIVE_SNAPSHOTS,
+            replay_limit,
+        }
+    }
+
+    /// Snapshot the state after every `interval`-th imported block
+    pub fn with_snapshot_interval(mut self, interval: u64) -> Self {
+        self.snapshot_interval = interval.max(1);
+        self
+    }
+
+    /// Keep at most `max_snapshots` snapshots, dropping the oldest
+    pub fn with_max_snapshots(mut self, max_snapshots: usize) -> Self {
+        self.max_snapshots = max_snapshots.max(1);
+        self
+    }
+
+    /// Most blocks replayed to answer a query
+    pub fn replay_limit(&self) -> u64 {
+        self.replay_limit
+    }
+
+    fn executor(&self) -> Result<&Arc<EVMExecutor>> {
+        self.router
+            .evm_executor()
+            .ok_or_else(|| NornError::Internal("state archive has no EVM executor".to_string()))
+    }
+
+    fn lock_changes(&self) -> std::sync::MutexGuard<'_, DirtyState> {
+        self.changes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_staged(&self) -> std::sync::MutexGuard<'_, Option<DirtyState>> {
+        self.staged.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Snapshot the state of the archive's executor as the state after the
+    /// block at `height`
+    ///
+    /// `height` must be above the newest snapshot. The first snapshot copies
+    /// the whole state; later ones fork the newest and copy what changed.
+    pub async fn record(&self, height: u64) -> Result<()> {
+        let executor = self.executor()?;
+        let mut snapshots = self.snapshots.write().await;
+        if let Some(&newest) = snapshots.keys().next_back() {
+            if height <= newest {
+                return Err(NornError::Internal(format!(
+                    "cannot snapshot block {}: newest snapshot is at block {}",
+                    height, newest
+                )));
+            }
+        }
+
+        let changes = std::mem::take(&mut *self.lock_changes());
+        let snapshot = match Self::snapshot(executor, snapshots.values().next_back(), &changes).await {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                self.lock_changes().extend(changes);
+                return Err(e);
+            }
+        };
+        snapshots.insert(height, snapshot);
+
+        while snapshots.len() > self.max_snapshots {
+            snapshots.pop_first();
+            // The new oldest stops reading from the dropped one, freeing it
+            if let Some(oldest) = snapshots.values().next() {
+                oldest.state.detach().await?;
+                oldest.code.detach().await.map_err(|e| NornError::Internal(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Snapshot of the state of `executor`, sharing what isn't in `changes`
+    /// with `newest`
+    async fn snapshot(executor: &EVMExecutor, newest: Option<&Snapshot>, changes: &DirtyState) -> Result<Snapshot> {
+        let internal = |e: crate::evm::EVMError| NornError::Internal(e.to_string());
+        let Some(newest) = newest else {
+            let state = executor.state_manager().fork().await;
+            state.detach().await?;
+            let code = executor.code_storage().fork();
+            code.detach().await.map_err(internal)?;
+            return Ok(Snapshot { state: Arc::new(state), code: Arc::new(code) });
+        };
+
+        let state = newest.state.fork().await;
+        state.copy_changes_from(executor.state_manager(), changes).await?;
+        let code = newest.code.fork();
+        code.copy_bindings_from(executor.code_storage(), &changes.accounts).await.map_err(internal)?;
+        Ok(Snapshot { state: Arc::new(state), code: Arc::new(code) })
+    }
+
+    /// Height of the nearest snapshot at or below `height`
+    pub async fn nearest_snapshot(&self, height: u64) -> Option<u64> {
+        self.snapshots.read().await.range(..=height).next_back().map(|(h, _)| *h)
+    }
+
+    /// Executor over the state after the block at `height`
+    ///
+    /// Forks the state and contract code of the nearest snapshot and replays
+    /// the blocks up to `height` from `blockchain` with the archive's router,
+    /// as on import. Fails without a snapshot within the replay limit, when a
+    /// block to replay has been pruned or when one is missing.
+    pub async fn executor_at(&self, blockchain: &Blockchain, height: u64) -> Result<Arc<EVMExecutor>> {
+        let (base, snapshot) = {
+            let snapshots = self.snapshots.read().await;
+            let Some((&base, snapshot)) = snapshots.range(..=height).next_back() else {
+                return Err(NornError::Internal(format!("state at block {} unavailable: no snapshot at or below it", height)));
+            };
+            if height - base > self.replay_limit {
+                return Err(NornError::Internal(format!(
+                    "state at block {} unavailable: nearest snapshot is at block {}, replay limit is {} blocks",
+                    height, base, self.replay_limit
+                )));
+            }
+            (base, snapshot.clone())
+        };
+        if height > base && blockchain.is_block_pruned(base as i64 + 1) {
+            return Err(NornError::Internal(format!(
+                "state at block {} unavailable: history pruned below block {}, nearest snapshot is at block {}",
+                height,
+                blockchain.pruned_below(),
+                base
+            )));
+        }
+        let executor = self.executor()?;
+
+        let replay = Arc::new(executor.with_storage(
+            Arc::new(snapshot.state.fork().await),
+            Arc::new(snapshot.code.fork()),
+        ));
+        let router = self.router.for_executor(Arc::clone(&replay)).await;
+
+        debug!("Replaying blocks {}..={} from snapshot", base + 1, height);
+        for block_height in base + 1..=height {
+            let block = match blockchain.get_block_by_height(block_height as i64).await {
+                Some(block) if !blockchain.is_block_pruned(block_height as i64) => block,
+                _ => return Err(NornError::Internal(format!("block {} unavailable for replay", block_height))),
+            };
+            router.run_block(&block).await.map_err(|e| {
+                NornError::Internal(format!("Failed to replay block {}: {}", block_height, e))
+            })?;
+        }
+
+        Ok(replay)
+    }
+}
+
+/// Notes the changes of each block and snapshots the state after every
+/// `snapshot_interval`-th block
+#[async_trait::async_trait]
+impl BlockImportHook for StateArchive {
+    async fn on_block_imported(&self, _block: &Block, _write: &mut ChainWrite) -> anyhow::Result<()> {
+        if let Some(executor) = self.router.evm_executor() {
+            *self.lock_staged() = Some(executor.state_manager().dirty());
+        }
+        Ok(())
+    }
+
+    async fn on_block_committed(&self, block: &Block) {
+        if let Some(changes) = self.lock_staged().take() {
+            self.lock_changes().extend(changes);
+        }
+        let height = block.header.height.max(0) as u64;
+        if !height.is_multiple_of(self.snapshot_interval) {
+            return;
+        }
+        if let Err(e) = self.record(height).await {
+            error!("Failed to snapshot the state after block {}: {}", height, e);
+        }
+    }
+
+    async fn on_import_aborted(&self, _block: &Block) {
+        self.lock_staged().take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pruning::PruningMode;
+    use crate::test_support::TestNode;
+    use norn_common::types::{Address, Hash, Transaction, TransactionType};
+    use num_bigint::BigUint;
+
+    fn transfer(id: u8, from: Address, to: Address, value: u64) -> Transaction {
+        let mut tx = Transaction::default();
+        tx.body.hash = Hash([id; 32]);
+        tx.body.address = from;
+        tx.body.receiver = to;
+        tx.body.gas = 21_000;
+        tx.body.tx_type = TransactionType::EVM;
+        tx.body.value = Some(value.to_string());
+        tx
+    }
+
+    #[tokio::test]
+    async fn test_replay_rebuilds_state_between_snapshots() {
+        let sender = Address([1u8; 20]);
+        let receiver = Address([2u8; 20]);
+        let node = TestNode::builder()
+            .with_account(sender, 1_000_000_000_000_000_000u128)
+            .build()
+            .await;
+
+        let router = Arc::new(TransactionRouter::new(Some(node.evm_executor.clone()), 30_000_000));
+        let archive = Arc::new(StateArchive::new(router, 1).with_snapshot_interval(2));
+        archive.record(0).await.unwrap();
+        node.blockchain.add_import_hook(archive.clone());
+        for (id, value) in [(1u8, 1000u64), (2, 2000), (3, 3000), (4, 4000)] {
+            node.tx_pool.add(transfer(id, sender, receiver, value));
+            node.produce_block(true).await;
+        }
+        assert_eq!(archive.nearest_snapshot(3).await, Some(2));
+        assert_eq!(archive.nearest_snapshot(4).await, Some(4));
+
+        for (height, balance) in [(1u64, 1000u32), (2, 3000), (3, 6000)] {
+            let executor = archive.executor_at(&node.blockchain, height).await.unwrap();
+            assert_eq!(executor.state_manager().get_balance(&receiver).await.unwrap(), BigUint::from(balance));
+        }
+        assert_eq!(node.state_manager.get_balance(&receiver).await.unwrap(), BigUint::from(10_000u32));
+
+        // Replays leave the snapshots as they were
+        let executor = archive.executor_at(&node.blockchain, 2).await.unwrap();
+        assert_eq!(executor.state_manager().get_balance(&receiver).await.unwrap(), BigUint::from(3000u32));
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_keep_their_code() {
+        let node = TestNode::builder().build().await;
+        let router = Arc::new(TransactionRouter::new(Some(node.evm_executor.clone()), 30_000_000));
+        let archive = Arc::new(StateArchive::new(router, 0).with_snapshot_interval(1).with_max_snapshots(2));
+        archive.record(0).await.unwrap();
+        node.blockchain.add_import_hook(archive.clone());
+
+        // Deployed before block 1, destroyed before block 3
+        let contract = Address([9u8; 20]);
+        let code_hash = Hash([7u8; 32]);
+        let code_storage = node.evm_executor.code_storage();
+        code_storage.deploy_code(contract, code_hash, vec![0x60, 0x00]).await.unwrap();
+        node.state_manager.update_balance(&contract, BigUint::from(1u32)).await.unwrap();
+        node.produce_block(false).await;
+        node.produce_block(false).await;
+
+        code_storage.unbind_code_from_address(&contract).await.unwrap();
+        node.state_manager.delete_account(&contract).await.unwrap();
+        node.produce_block(false).await;
+        assert_eq!(code_storage.code_count().await, 0);
+
+        // The snapshot at 2 outlived the one at 1 it was forked from
+        assert_eq!(archive.nearest_snapshot(1).await, None);
+        let executor = archive.executor_at(&node.blockchain, 2).await.unwrap();
+        assert_eq!(executor.code_storage().get_code_by_address(&contract).await.unwrap(), Some(vec![0x60, 0x00]));
+        assert_eq!(executor.state_manager().get_balance(&contract).await.unwrap(), BigUint::from(1u32));
+
+        let executor = archive.executor_at(&node.blockchain, 3).await.unwrap();
+        assert!(!executor.code_storage().is_contract(&contract).await);
+        assert!(executor.state_manager().get_account(&contract).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_replay_limit() {
+        let node = TestNode::builder().build().await;
+        let router = Arc::new(TransactionRouter::new(Some(node.evm_executor.clone()), 30_000_000));
+        let archive = StateArchive::new(router, 1);
+        archive.record(0).await.unwrap();
+        for _ in 0..2 {
+            node.produce_block(false).await;
+        }
+
+        assert!(archive.executor_at(&node.blockchain, 1).await.is_ok());
+        assert!(archive.executor_at(&node.blockchain, 2).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pruned_history() {
+        let node = TestNode::builder().with_pruning(PruningMode::Full { keep_recent: 2 }).build().await;
+        let router = Arc::new(TransactionRouter::new(Some(node.evm_executor.clone()), 30_000_000));
+        let archive = Arc::new(StateArchive::new(router, 8).with_snapshot_interval(4));
+        archive.record(0).await.unwrap();
+        node.blockchain.add_import_hook(archive.clone());
+        for _ in 0..5 {
+            node.produce_block(false).await;
+        }
+        assert_eq!(node.blockchain.pruned_below(), 4);
+
+        // Blocks 1..=3 are gone, but the snapshot at 4 needs no replay of them
+        let err = archive.executor_at(&node.blockchain, 3).await.err().unwrap();
+        assert!(err.to_string().contains("history pruned below block 4"), "{}", err);
+        assert!(archive.executor_at(&node.blockchain, 4).await.is_ok());
+        assert!(archive.executor_at(&node.blockchain, 5).await.is_ok());
+    }
+}
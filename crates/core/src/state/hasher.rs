@@ -0,0 +1,89 @@
+//! Hash function selection for state roots and proofs
+//!
+//! State is hashed with SHA-256 by default. Keccak-256 can be selected so
+//! roots and proofs are hashed the way Ethereum hashes them.
+
+use norn_common::types::Hash;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tiny_keccak::{Hasher, Keccak};
+
+/// Hash function used for state roots and proofs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StateHashAlgorithm {
+    /// SHA-256 (native)
+    #[default]
+    Sha256,
+    /// Keccak-256 (Ethereum compatible)
+    Keccak256,
+}
+
+/// State hashing configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateHashConfig {
+    /// Hash function for state roots and proofs
+    pub algorithm: StateHashAlgorithm,
+}
+
+impl StateHashConfig {
+    /// Hash state with `algorithm`
+    pub fn new(algorithm: StateHashAlgorithm) -> Self {
+        Self { algorithm }
+    }
+}
+
+impl StateHashAlgorithm {
+    /// Start an incremental hash
+    pub fn hasher(self) -> StateHasher {
+        match self {
+            Self::Sha256 => StateHasher::Sha256(Sha256::new()),
+            Self::Keccak256 => StateHasher::Keccak256(Keccak::v256()),
+        }
+    }
+
+    /// Hash `data` in one go
+    pub fn digest(self, data: &[u8]) -> Hash {
+        let mut hasher = self.hasher();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+/// Incremental hash with the selected [`StateHashAlgorithm`]
+pub enum StateHasher {
+    Sha256(Sha256),
+    Keccak256(Keccak),
+}
+
+impl StateHasher {
+    /// Feed `data` into the hash
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(hasher) => Digest::update(hasher, data),
+            Self::Keccak256(hasher) => hasher.update(data),
+        }
+    }
+
+    /// Finish the hash
+    pub fn finalize(self) -> Hash {
+        let mut result = [0u8; 32];
+        match self {
+            Self::Sha256(hasher) => result.copy_from_slice(&hasher.finalize()),
+            Self::Keccak256(hasher) => hasher.finalize(&mut result),
+        }
+        Hash(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_matches_reference_implementations() {
+        let data = b"norn state";
+        assert_eq!(StateHashAlgorithm::Keccak256.digest(data).0, keccak_hash::keccak(data).0);
+        assert_eq!(StateHashAlgorithm::Sha256.digest(data).0[..], Sha256::digest(data)[..]);
+    }
+}
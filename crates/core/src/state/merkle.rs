@@ -4,35 +4,41 @@
 //! native and EVM contract states. Uses Merkle Patricia Tree (MPT) approach.
 
 use crate::state::{AccountStateManager, AccountState, AccountType};
+use crate::state::hasher::{StateHashAlgorithm, StateHashConfig};
 use norn_common::types::{Hash, Address};
 use norn_common::error::{Result, NornError};
 use serde::{Serialize, Deserialize};
-use sha2::{Sha256, Digest};
 use tracing::{debug, info};
 use num_bigint::BigUint;
 use num_traits::Zero;
 use std::collections::HashMap;
 
 /// State root calculator
+#[derive(Default)]
 pub struct StateRootCalculator {
-    /// Whether to use Keccak-256 (Ethereum compatible) or SHA-256 (native)
-    pub use_keccak: bool,
-}
-
-impl Default for StateRootCalculator {
-    fn default() -> Self {
-        Self {
-            // Use SHA-256 for native compatibility
-            // Keccak-256 can be enabled for full Ethereum compatibility
-            use_keccak: false,
-        }
-    }
+    /// Hash function, SHA-256 (native) unless Keccak-256 is configured
+    pub hash: StateHashConfig,
 }
 
 impl StateRootCalculator {
     /// Create a new state root calculator
     pub fn new(use_keccak: bool) -> Self {
-        Self { use_keccak }
+        let algorithm = if use_keccak {
+            StateHashAlgorithm::Keccak256
+        } else {
+            StateHashAlgorithm::Sha256
+        };
+        Self::with_config(StateHashConfig::new(algorithm))
+    }
+
+    /// Create a calculator hashing with `hash`
+    pub fn with_config(hash: StateHashConfig) -> Self {
+        Self { hash }
+    }
+
+    /// Create a calculator hashing the way `manager` is configured to
+    pub fn for_manager(manager: &AccountStateManager) -> Self {
+        Self::with_config(manager.hash_config().clone())
     }
 
     /// Calculate state root from account state manager
@@ -90,9 +96,9 @@ impl StateRootCalculator {
         let state_root = self.calculate_state_root(&state_entries);
 
         debug!(
-            "Calculated state root: {} accounts, use_keccak={}",
+            "Calculated state root: {} accounts, algorithm={:?}",
             state_entries.len(),
-            self.use_keccak
+            self.hash.algorithm
         );
 
         Ok(state_root)
@@ -108,16 +114,13 @@ impl StateRootCalculator {
             return Hash::default();
         }
 
-        let mut hasher = Sha256::new();
+        let mut hasher = self.hash.algorithm.hasher();
         for (key, value) in storage.iter() {
             hasher.update(key);
             hasher.update(value);
         }
 
-        let result = hasher.finalize();
-        let mut hash = Hash::default();
-        hash.0.copy_from_slice(&result);
-        hash
+        hasher.finalize()
     }
 
     /// Calculate state root from sorted account data
@@ -154,7 +157,7 @@ impl StateRootCalculator {
 
     /// Hash a single account state
     fn hash_account_state(&self, address: &Address, data: &AccountStateData) -> Hash {
-        let mut hasher = Sha256::new();
+        let mut hasher = self.hash.algorithm.hasher();
         hasher.update(&address.0);
         hasher.update(data.balance.as_bytes());
         hasher.update(&data.nonce.to_le_bytes());
@@ -170,22 +173,16 @@ impl StateRootCalculator {
         };
         hasher.update(&[type_byte]);
 
-        let result = hasher.finalize();
-        let mut hash = Hash::default();
-        hash.0.copy_from_slice(&result);
-        hash
+        hasher.finalize()
     }
 
     /// Combine two hashes
     fn combine_hashes(&self, left: Hash, right: Hash) -> Hash {
-        let mut hasher = Sha256::new();
+        let mut hasher = self.hash.algorithm.hasher();
         hasher.update(&left.0);
         hasher.update(&right.0);
 
-        let result = hasher.finalize();
-        let mut hash = Hash::default();
-        hash.0.copy_from_slice(&result);
-        hash
+        hasher.finalize()
     }
 }
 
@@ -361,6 +358,32 @@ mod tests {
         // Same state should produce same root
         assert_eq!(root1, root2);
     }
+
+    #[tokio::test]
+    async fn test_keccak_state_root() {
+        let manager = AccountStateManager::new(AccountStateConfig::default());
+        let calculator = StateRootCalculator::with_config(StateHashConfig::new(StateHashAlgorithm::Keccak256));
+
+        for i in 1u8..=2 {
+            manager.update_balance(&Address([i; 20]), BigUint::from(i as u64 * 100)).await.unwrap();
+        }
+
+        // Independently hash each account, then the pair
+        let leaf = |i: u8| {
+            let mut preimage = vec![i; 20];
+            preimage.extend((i as u64 * 100).to_string().as_bytes());
+            preimage.extend(0u64.to_le_bytes());
+            preimage.extend([0u8; 32]); // code hash
+            preimage.extend([0u8; 32]); // storage root
+            preimage.push(0); // normal account
+            keccak_hash::keccak(&preimage).0
+        };
+        let expected = keccak_hash::keccak([leaf(1), leaf(2)].concat()).0;
+
+        let root = calculator.calculate_from_manager(&manager).await.unwrap();
+        assert_eq!(root, Hash(expected));
+        assert_ne!(root, StateRootCalculator::default().calculate_from_manager(&manager).await.unwrap());
+    }
 }
 
 /// Merkle Patricia Trie (MPT) Node
@@ -389,13 +412,18 @@ enum MPTNode {
 ///
 /// This implements a more complete Merkle Patricia Trie compatible with Ethereum.
 pub struct EnhancedStateRootCalculator {
-    use_keccak: bool,
+    hash: StateHashConfig,
 }
 
 impl EnhancedStateRootCalculator {
     /// Create a new enhanced calculator
     pub fn new(use_keccak: bool) -> Self {
-        Self { use_keccak }
+        Self::with_config(StateRootCalculator::new(use_keccak).hash)
+    }
+
+    /// Create a calculator hashing nodes with `hash`
+    pub fn with_config(hash: StateHashConfig) -> Self {
+        Self { hash }
     }
 
     /// Calculate state root using full MPT
@@ -541,20 +569,7 @@ impl EnhancedStateRootCalculator {
         let serialized = bincode::serialize(node)
             .map_err(|e| norn_common::error::NornError::Internal(format!("Serialization failed: {}", e)))?;
 
-        let hash = if self.use_keccak {
-            // Use Keccak-256 for Ethereum compatibility
-            self.keccak_256(&serialized)
-        } else {
-            // Use SHA-256 for native compatibility
-            let mut hasher = Sha256::new();
-            hasher.update(&serialized);
-            let result = hasher.finalize();
-            let mut hash = Hash::default();
-            hash.0.copy_from_slice(&result);
-            hash
-        };
-
-        Ok(hash)
+        Ok(self.hash.algorithm.digest(&serialized))
     }
 
     /// Calculate MPT root hash
@@ -562,26 +577,6 @@ impl EnhancedStateRootCalculator {
         self.hash_node(node)
     }
 
-    /// Keccak-256 hash function (for Ethereum compatibility)
-    fn keccak_256(&self, data: &[u8]) -> Hash {
-        use norn_common::build_mode;
-
-        // In test mode: Use SHA-256 with prefix for faster testing
-        if build_mode::IS_TEST_MODE {
-            let mut hasher = Sha256::new();
-            hasher.update(b"TEST_MODE"); // Prefix to indicate test mode
-            hasher.update(data);
-            let result = hasher.finalize();
-            let mut hash = Hash::default();
-            hash.0.copy_from_slice(&result);
-            return hash;
-        }
-
-        // Production mode: Use actual Keccak-256 for Ethereum compatibility
-        let hash_output = keccak_hash::keccak(data);
-        Hash(hash_output.0)
-    }
-
     /// Verify a proof in the MPT
     pub fn verify_proof(
         &self,
@@ -697,9 +692,7 @@ impl EnhancedStateRootCalculator {
 
 impl Default for EnhancedStateRootCalculator {
     fn default() -> Self {
-        Self {
-            use_keccak: false,
-        }
+        Self::with_config(StateHashConfig::default())
     }
 }
 
@@ -9,13 +9,19 @@ pub mod cache;    // Synchronous cache for async/sync bridging
 pub mod traits;   // Unified trait for account state management
 pub mod history;  // State change history tracking
 pub mod pruning;  // State pruning for storage optimization
+pub mod read_cache; // Read cache of committed state, dropped on new heads
+pub mod hasher;   // Hash function selection for state roots and proofs
+pub mod archive;  // Historical state rebuilt from snapshots by replaying blocks
 
 // Re-export the comprehensive account state manager and trait
-pub use account::{AccountState, AccountType, AccountStateConfig, AccountStateManager};
+pub use account::{AccountState, AccountType, AccountStateConfig, AccountStateManager, DirtyState, UndoLog};
 pub use traits::{AccountStateManagerTrait, SharedAccountStateManager};
 pub use history::{StateHistory, StateChangeRecord, StateChangeType, StateSnapshot};
 pub use persistent::{PersistentStateManager, PersistentConfig};
 pub use pruning::{PruningConfig, PruningStats, StatePruningManager, PruningResult};
+pub use read_cache::StateReadCache;
+pub use hasher::{StateHashAlgorithm, StateHashConfig};
+pub use archive::StateArchive;
 
 use norn_common::types::{Hash, Address};
 use norn_common::error::{NornError, Result};
@@ -2,13 +2,15 @@
 //!
 //! This module extends AccountStateManager with database persistence capabilities.
 
-use crate::state::{AccountStateManager, AccountState, AccountStateConfig, AccountType};
-use norn_common::types::{Address, Hash};
+use crate::blockchain::{BlockImportHook, ChainWrite};
+use crate::state::{AccountStateManager, AccountState, AccountStateConfig, DirtyState};
+use norn_common::types::{Address, Block, Hash};
 use norn_common::error::Result;
-use norn_storage::SledDB;
-use serde::{Serialize, Deserialize};
+use norn_storage::{SledDB, StorageError, WAL, WALEntry};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::sync::RwLock;
+use std::collections::HashMap;
 use tracing::{debug, info, warn, error};
 use num_bigint::BigUint;
 
@@ -18,6 +20,10 @@ mod keys {
     pub const STORAGE_PREFIX: &[u8] = b"storage_";
     pub const STATE_ROOT_KEY: &[u8] = b"state_root";
     pub const ACCOUNT_COUNT_KEY: &[u8] = b"account_count";
+    /// Number of the last block whose state was committed
+    pub const COMMITTED_BLOCK_KEY: &[u8] = b"state_committed_block";
+    /// Marks an account whose storage still has to be purged
+    pub const DELETED_ACCOUNT_PREFIX: &[u8] = b"deleted_account_";
 }
 
 /// Persistent account state manager
@@ -25,13 +31,40 @@ mod keys {
 /// Extends AccountStateManager with database persistence for durability.
 pub struct PersistentStateManager {
     /// Base state manager (in-memory cache)
-    base_manager: AccountStateManager,
+    base_manager: Arc<AccountStateManager>,
 
     /// Database handle
     db: Arc<SledDB>,
 
     /// Write-through cache configuration
     config: PersistentConfig,
+
+    /// Blocks applied since the state was last committed
+    blocks_since_commit: AtomicU64,
+
+    /// Number of block-driven commits to the database
+    disk_commits: AtomicU64,
+
+    /// Changes applied since the last commit
+    pending: std::sync::Mutex<DirtyState>,
+
+    /// Log of the changes of each block applied since the last commit
+    wal: Option<WAL>,
+
+    /// Block prepared by [`Self::prepare_block`], not yet finished or aborted
+    staged: std::sync::Mutex<Option<StagedBlock>>,
+}
+
+/// A key and its logged value, `None` for a delete
+type LoggedWrite = (Vec<u8>, Option<Vec<u8>>);
+
+/// Changes of a block whose writes are being committed with it
+struct StagedBlock {
+    number: u64,
+    hash: Hash,
+    changes: DirtyState,
+    /// Whether the state changed since the last commit is written with the block
+    commits: bool,
 }
 
 /// Configuration for persistent state manager
@@ -48,6 +81,12 @@ pub struct PersistentConfig {
 
     /// Flush interval for async writes (in seconds)
     pub flush_interval: u64,
+
+    /// Interval between purges of deleted accounts' storage (in seconds)
+    pub gc_interval: u64,
+
+    /// Blocks applied in memory between commits to the database
+    pub state_commit_interval_blocks: u64,
 }
 
 impl Default for PersistentConfig {
@@ -57,6 +96,8 @@ impl Default for PersistentConfig {
             async_write: true,
             cache_size: 10_000,
             flush_interval: 5,
+            gc_interval: 60,
+            state_commit_interval_blocks: 1,
         }
     }
 }
@@ -70,18 +111,36 @@ impl PersistentStateManager {
             ..Default::default()
         };
 
-        let base_manager = AccountStateManager::new(base_config);
+        Ok(Self::with_state_manager(db, config, Arc::new(AccountStateManager::new(base_config))))
+    }
 
+    /// Persist the state held by an existing `base_manager`
+    pub fn with_state_manager(db: Arc<SledDB>, config: PersistentConfig, base_manager: Arc<AccountStateManager>) -> Self {
+        base_manager.track_dirty();
         let manager = Self {
             base_manager,
             db,
             config,
+            blocks_since_commit: AtomicU64::new(0),
+            disk_commits: AtomicU64::new(0),
+            pending: std::sync::Mutex::new(DirtyState::default()),
+            wal: None,
+            staged: std::sync::Mutex::new(None),
         };
 
         // Note: State will be loaded on-demand, not eagerly loaded
         info!("PersistentStateManager created (use load_from_db_async to load initial state)");
 
-        Ok(manager)
+        manager
+    }
+
+    /// Log the changes of blocks applied between commits to `wal`
+    ///
+    /// Without a WAL those changes are lost on a crash and the state falls
+    /// back to the last commit.
+    pub fn with_wal(mut self, wal: WAL) -> Self {
+        self.wal = Some(wal);
+        self
     }
 
     /// Load all accounts from database into memory cache
@@ -90,11 +149,14 @@ impl PersistentStateManager {
 
         let mut loaded_count = 0;
 
+        // Read from a snapshot so a concurrent flush can't leave us with a torn view
+        let snapshot = self.db.snapshot();
+        let accounts = snapshot.iter_prefix(keys::ACCOUNT_PREFIX).map_err(|e| {
+            norn_common::error::NornError::Internal(format!("DB iteration error: {}", e))
+        })?;
+
         // Iterate over all account keys
-        for item in self.db.iter_prefix(keys::ACCOUNT_PREFIX) {
-            let (key, value) = item.map_err(|e| {
-                norn_common::error::NornError::Internal(format!("DB iteration error: {}", e))
-            })?;
+        for (key, value) in accounts {
 
             // Extract address from key
             if key.len() < keys::ACCOUNT_PREFIX.len() + 20 {
@@ -118,6 +180,21 @@ impl PersistentStateManager {
             loaded_count += 1;
         }
 
+        let storage = snapshot.iter_prefix(keys::STORAGE_PREFIX).map_err(|e| {
+            norn_common::error::NornError::Internal(format!("DB iteration error: {}", e))
+        })?;
+        for (key, value) in storage {
+            let Some(address) = address_at(&key, keys::STORAGE_PREFIX.len()) else {
+                warn!("Invalid storage key length: {}", key.len());
+                continue;
+            };
+            let slot = key[keys::STORAGE_PREFIX.len() + 20..].to_vec();
+            self.base_manager.set_storage(&address, slot, value).await?;
+        }
+
+        // What was just loaded is already on disk
+        self.base_manager.take_dirty();
+
         info!("Loaded {} accounts from database", loaded_count);
         Ok(())
     }
@@ -162,10 +239,24 @@ impl PersistentStateManager {
         self.db.remove_sync(&key)
             .map_err(|e| norn_common::error::NornError::Internal(format!("Failed to delete account from DB: {}", e)))?;
 
+        // Storage slots are removed later by `purge_deleted_accounts`
+        let mut marker = Vec::from(keys::DELETED_ACCOUNT_PREFIX);
+        marker.extend_from_slice(&address.0);
+        self.db.insert_sync(&marker, &[])
+            .map_err(|e| norn_common::error::NornError::Internal(format!("Failed to mark account as deleted: {}", e)))?;
+
         debug!("Deleted account {:?} from database", address);
         Ok(())
     }
 
+    /// Remove persisted storage of deleted accounts
+    ///
+    /// Returns the number of storage entries removed.
+    pub async fn purge_deleted_accounts(&self) -> Result<usize> {
+        let accounts_lock = self.base_manager.accounts_lock().await;
+        purge_deleted_storage(&self.db, &accounts_lock).await
+    }
+
     /// Flush all cached state to database
     pub async fn flush_to_db(&self) -> Result<()> {
         debug!("Flushing state to database...");
@@ -192,6 +283,281 @@ impl PersistentStateManager {
         Ok(())
     }
 
+    /// Record that the state changes of block `block_number` have been applied
+    ///
+    /// Changes stay in memory (and in the WAL, if configured) until
+    /// `state_commit_interval_blocks` blocks have been applied, then the
+    /// accounts and storage slots changed since the last commit are written
+    /// to the database in one transaction. Returns whether this block
+    /// triggered a commit.
+    ///
+    /// Block import commits the state together with the block instead, see
+    /// the [`BlockImportHook`] impl.
+    pub async fn on_block_applied(&self, block_number: u64, block_hash: Hash) -> Result<bool> {
+        let mut write = ChainWrite::default();
+        self.prepare_block(block_number, block_hash, &mut write).await?;
+        if let Err(e) = self.db.transaction(&write.keys, &write.values, &write.removed) {
+            self.abort_block();
+            return Err(norn_common::error::NornError::Internal(format!("Failed to commit state: {}", e)));
+        }
+        self.finish_block()
+    }
+
+    fn lock_pending(&self) -> std::sync::MutexGuard<'_, DirtyState> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_staged(&self) -> std::sync::MutexGuard<'_, Option<StagedBlock>> {
+        self.staged.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Log the changes of an applied block to the WAL and, when a commit is
+    /// due, add the state changed since the last commit to `write`
+    ///
+    /// `write` must be committed before [`Self::finish_block`] is called, or
+    /// dropped and followed by [`Self::abort_block`].
+    async fn prepare_block(&self, block_number: u64, block_hash: Hash, write: &mut ChainWrite) -> Result<()> {
+        let changes = self.base_manager.take_dirty();
+        if let Some(wal) = &self.wal {
+            if let Err(e) = self.log_changes(wal, &changes, block_number, block_hash).await {
+                self.base_manager.restore_dirty(changes);
+                return Err(e);
+            }
+        }
+
+        let applied = self.blocks_since_commit.load(Ordering::SeqCst) + 1;
+        let commits = applied >= self.config.state_commit_interval_blocks.max(1);
+        if commits {
+            let mut uncommitted = self.lock_pending().clone();
+            uncommitted.extend(changes.clone());
+            if let Err(e) = self.push_state_writes(&uncommitted, block_number, write).await {
+                self.base_manager.restore_dirty(changes);
+                self.rollback_wal(block_number);
+                return Err(e);
+            }
+        }
+
+        *self.lock_staged() = Some(StagedBlock { number: block_number, hash: block_hash, changes, commits });
+        Ok(())
+    }
+
+    /// The writes of the block prepared last are durable in the database
+    ///
+    /// Returns whether they included a state commit.
+    fn finish_block(&self) -> Result<bool> {
+        let Some(staged) = self.lock_staged().take() else {
+            return Ok(false);
+        };
+        if !staged.commits {
+            self.lock_pending().extend(staged.changes);
+            self.blocks_since_commit.fetch_add(1, Ordering::SeqCst);
+            return Ok(false);
+        }
+
+        *self.lock_pending() = DirtyState::default();
+        self.blocks_since_commit.store(0, Ordering::SeqCst);
+        self.disk_commits.fetch_add(1, Ordering::Relaxed);
+
+        self.db.flush()
+            .map_err(|e| norn_common::error::NornError::Internal(format!("Failed to flush state: {}", e)))?;
+        // Everything logged so far is now in the database
+        if let Some(wal) = &self.wal {
+            wal.checkpoint_and_truncate(staged.number, staged.hash.0)
+                .map_err(|e| norn_common::error::NornError::Internal(format!("Failed to truncate state WAL: {}", e)))?;
+        }
+        debug!("Committed state at block {}", staged.number);
+        Ok(true)
+    }
+
+    /// The writes of the block prepared last were dropped
+    ///
+    /// Its changes are marked dirty again and its WAL entries are rolled back
+    /// so recovery doesn't apply them.
+    fn abort_block(&self) {
+        let Some(staged) = self.lock_staged().take() else {
+            return;
+        };
+        self.base_manager.restore_dirty(staged.changes);
+        self.rollback_wal(staged.number);
+    }
+
+    /// Mark the entries logged for `block_number` as rolled back
+    fn rollback_wal(&self, block_number: u64) {
+        let Some(wal) = &self.wal else {
+            return;
+        };
+        if let Err(e) = wal.write(WALEntry::TransactionRollback { id: block_number }).and_then(|_| wal.sync()) {
+            error!("Failed to roll back block {} in the state WAL: {}", block_number, e);
+        }
+    }
+
+    /// Add `changes` and the committed block number to `write`
+    async fn push_state_writes(&self, changes: &DirtyState, block_number: u64, write: &mut ChainWrite) -> Result<()> {
+        for address in &changes.accounts {
+            let key = account_key(address);
+            match self.base_manager.get_account(address).await? {
+                Some(account) => {
+                    write.put(key, bincode::serialize(&account)
+                        .map_err(|e| norn_common::error::NornError::Internal(format!("Failed to serialize account: {}", e)))?);
+                }
+                None => {
+                    write.delete(key);
+                    // Storage slots are removed later by `purge_deleted_accounts`
+                    write.put(deleted_marker_key(address), Vec::new());
+                }
+            }
+        }
+        for (address, slot) in &changes.storage {
+            let key = storage_key(address, slot);
+            match self.base_manager.get_storage(address, slot).await? {
+                Some(value) => write.put(key, value),
+                None => write.delete(key),
+            }
+        }
+        write.put(keys::COMMITTED_BLOCK_KEY.to_vec(), block_number.to_be_bytes().to_vec());
+
+        debug!(
+            "Staged {} accounts and {} storage slots for commit at block {}",
+            changes.accounts.len(),
+            changes.storage.len(),
+            block_number
+        );
+        Ok(())
+    }
+
+    /// Append the changes of one block to the WAL, closed by a checkpoint
+    /// marking the block as complete
+    async fn log_changes(&self, wal: &WAL, changes: &DirtyState, block_number: u64, block_hash: Hash) -> Result<()> {
+        let wal_error = |e: StorageError| norn_common::error::NornError::Internal(format!("State WAL write failed: {}", e));
+
+        for address in &changes.accounts {
+            let entry = match self.base_manager.get_account(address).await? {
+                Some(account) => WALEntry::UpdateAccount {
+                    address: address.0,
+                    data: bincode::serialize(&account)
+                        .map_err(|e| norn_common::error::NornError::Internal(format!("Failed to serialize account: {}", e)))?,
+                },
+                None => WALEntry::DeleteAccount { address: address.0 },
+            };
+            wal.write(entry).map_err(wal_error)?;
+        }
+        for (address, slot) in &changes.storage {
+            let entry = match self.base_manager.get_storage(address, slot).await? {
+                Some(value) => WALEntry::WriteStorage { address: address.0, key: slot.clone(), value },
+                None => WALEntry::DeleteStorage { address: address.0, key: slot.clone() },
+            };
+            wal.write(entry).map_err(wal_error)?;
+        }
+        wal.checkpoint(block_number, block_hash.0).map_err(wal_error)?;
+        wal.sync().map_err(wal_error)?;
+        Ok(())
+    }
+
+    /// Apply the blocks logged to the WAL since the last commit to the database
+    ///
+    /// Call before [`Self::load_from_db_async`] on startup. Changes of a block
+    /// whose checkpoint marker never made it to the WAL, of a block whose
+    /// import was rolled back and of blocks above `chain_head`, which never
+    /// made it to the chain, are discarded. Returns the last block recovered,
+    /// if any.
+    pub async fn recover_from_wal(&self, chain_head: u64) -> Result<Option<u64>> {
+        let Some(wal) = &self.wal else {
+            return Ok(None);
+        };
+        let wal_error = |e: StorageError| norn_common::error::NornError::Internal(format!("State WAL recovery failed: {}", e));
+
+        let scan = wal.scan().map_err(wal_error)?;
+        if !scan.corruption.is_empty() {
+            warn!("State WAL has {} corrupt regions; recovering the intact entries", scan.corruption.len());
+        }
+
+        // Writes (`None` for deletes) of each block closed by a checkpoint
+        let mut blocks: Vec<(u64, [u8; 32], Vec<LoggedWrite>)> = Vec::new();
+        let mut block = Vec::new();
+        for (_, entry) in scan.entries {
+            match entry {
+                WALEntry::CreateAccount { address, data } | WALEntry::UpdateAccount { address, data } => {
+                    block.push((account_key(&Address(address)), Some(data)));
+                }
+                WALEntry::DeleteAccount { address } => {
+                    block.push((account_key(&Address(address)), None));
+                    block.push((deleted_marker_key(&Address(address)), Some(Vec::new())));
+                }
+                WALEntry::WriteStorage { address, key, value } => {
+                    block.push((storage_key(&Address(address), &key), Some(value)));
+                }
+                WALEntry::DeleteStorage { address, key } => {
+                    block.push((storage_key(&Address(address), &key), None));
+                }
+                WALEntry::Checkpoint { block_number, block_hash } => {
+                    blocks.push((block_number, block_hash, std::mem::take(&mut block)));
+                }
+                WALEntry::TransactionRollback { id } if blocks.last().is_some_and(|(number, _, _)| *number == id) => {
+                    blocks.pop();
+                }
+                _ => {}
+            }
+        }
+
+        let committed = self.committed_block()?;
+        let discarded = blocks.iter().filter(|(number, _, _)| *number > chain_head).count();
+        blocks.retain(|(number, _, _)| *number <= chain_head);
+        if discarded > 0 {
+            warn!("Discarding {} state WAL blocks above the chain head {}", discarded, chain_head);
+        }
+        let last = blocks.last().map(|(number, hash, _)| (*number, *hash));
+        let Some((block_number, block_hash)) = last.filter(|(number, _)| committed.is_none_or(|c| c < *number)) else {
+            // Nothing to apply, but the discarded blocks must not be replayed later
+            if discarded > 0 {
+                wal.checkpoint_and_truncate(committed.unwrap_or(0), [0u8; 32]).map_err(wal_error)?;
+            }
+            return Ok(None);
+        };
+
+        // Later writes to a key win over earlier ones
+        let mut writes = HashMap::new();
+        for (key, value) in blocks.into_iter().flat_map(|(_, _, block)| block) {
+            writes.insert(key, value);
+        }
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+        let mut deletes = Vec::new();
+        for (key, value) in writes {
+            match value {
+                Some(value) => {
+                    keys.push(key);
+                    values.push(value);
+                }
+                None => deletes.push(key),
+            }
+        }
+        keys.push(keys::COMMITTED_BLOCK_KEY.to_vec());
+        values.push(block_number.to_be_bytes().to_vec());
+
+        self.db.transaction(&keys, &values, &deletes)
+            .map_err(|e| norn_common::error::NornError::Internal(format!("Failed to apply state WAL: {}", e)))?;
+        self.db.flush()
+            .map_err(|e| norn_common::error::NornError::Internal(format!("Failed to flush state: {}", e)))?;
+        wal.checkpoint_and_truncate(block_number, block_hash).map_err(wal_error)?;
+
+        info!("Recovered state up to block {} from the WAL", block_number);
+        Ok(Some(block_number))
+    }
+
+    /// Number of the last block whose state was committed to the database
+    pub fn committed_block(&self) -> Result<Option<u64>> {
+        let value = self.db.get_sync(keys::COMMITTED_BLOCK_KEY)
+            .map_err(|e| norn_common::error::NornError::Internal(format!("Failed to read committed block: {}", e)))?;
+        Ok(value
+            .and_then(|bytes| <[u8; 8]>::try_from(bytes.as_slice()).ok())
+            .map(u64::from_be_bytes))
+    }
+
+    /// Number of commits made by [`Self::on_block_applied`]
+    pub fn disk_commits(&self) -> u64 {
+        self.disk_commits.load(Ordering::Relaxed)
+    }
+
     /// Create a checkpoint of the current state
     pub async fn create_checkpoint(&self, block_number: u64) -> Result<Hash> {
         debug!("Creating checkpoint for block {}", block_number);
@@ -251,6 +617,33 @@ impl PersistentStateManager {
         Ok(())
     }
 
+    /// Load `accounts` in bulk, e.g. a genesis allocation or a state import
+    ///
+    /// The accounts are inserted under a single lock. In write-through mode
+    /// they are written to the database in one transaction, so an
+    /// interrupted import leaves none of them persisted; otherwise they are
+    /// logged to the WAL and committed with the next block like any other
+    /// change.
+    pub async fn load_accounts(&self, accounts: Vec<AccountState>) -> Result<usize> {
+        if !self.config.write_through {
+            return self.base_manager.load_accounts(accounts).await;
+        }
+
+        let mut keys = Vec::with_capacity(accounts.len());
+        let mut values = Vec::with_capacity(accounts.len());
+        for account in &accounts {
+            keys.push(account_key(&account.address));
+            values.push(bincode::serialize(account)
+                .map_err(|e| norn_common::error::NornError::Internal(format!("Failed to serialize account: {}", e)))?);
+        }
+
+        let loaded = self.base_manager.load_accounts(accounts).await?;
+        self.db.transaction(&keys, &values, &[])
+            .map_err(|e| norn_common::error::NornError::Internal(format!("Failed to write accounts to DB: {}", e)))?;
+
+        Ok(loaded)
+    }
+
     /// Delete account (also from DB)
     pub async fn delete_account(&self, address: &Address) -> Result<()> {
         self.base_manager.delete_account(address).await?;
@@ -328,6 +721,26 @@ impl PersistentStateManager {
         &self.base_manager
     }
 
+    /// Shared handle to the base manager, for components that change state
+    pub fn state_manager(&self) -> Arc<AccountStateManager> {
+        self.base_manager.clone()
+    }
+
+    /// Purge storage of deleted accounts every `gc_interval` seconds
+    pub async fn run_storage_gc(&self) {
+        let interval_sec = self.config.gc_interval.max(1);
+        info!("Running storage GC every {}s", interval_sec);
+
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_sec));
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.purge_deleted_accounts().await {
+                error!("Storage GC failed: {}", e);
+            }
+        }
+    }
+
     /// Start background flush task
     pub async fn start_background_flush(&self) -> Result<()> {
         if !self.config.async_write {
@@ -404,9 +817,110 @@ impl PersistentStateManager {
     }
 }
 
+/// Commits the state left by each imported block together with the block
+///
+/// Must run after the hooks that execute the block.
+#[async_trait::async_trait]
+impl BlockImportHook for PersistentStateManager {
+    async fn on_block_imported(&self, block: &Block, write: &mut ChainWrite) -> anyhow::Result<()> {
+        self.prepare_block(block.header.height.max(0) as u64, block.header.block_hash, write).await?;
+        Ok(())
+    }
+
+    async fn on_block_committed(&self, block: &Block) {
+        if let Err(e) = self.finish_block() {
+            error!("Failed to finish the state commit of block {}: {}", block.header.height, e);
+        }
+    }
+
+    async fn on_import_aborted(&self, _block: &Block) {
+        self.abort_block();
+    }
+}
+
+fn account_key(address: &Address) -> Vec<u8> {
+    let mut key = Vec::from(keys::ACCOUNT_PREFIX);
+    key.extend_from_slice(&address.0);
+    key
+}
+
+fn storage_key(address: &Address, slot: &[u8]) -> Vec<u8> {
+    let mut key = Vec::from(keys::STORAGE_PREFIX);
+    key.extend_from_slice(&address.0);
+    key.extend_from_slice(slot);
+    key
+}
+
+fn deleted_marker_key(address: &Address) -> Vec<u8> {
+    let mut key = Vec::from(keys::DELETED_ACCOUNT_PREFIX);
+    key.extend_from_slice(&address.0);
+    key
+}
+
+/// The address stored right after a `prefix_len`-byte prefix of `key`
+fn address_at(key: &[u8], prefix_len: usize) -> Option<Address> {
+    let bytes = key.get(prefix_len..prefix_len + 20)?;
+    let mut addr = [0u8; 20];
+    addr.copy_from_slice(bytes);
+    Some(Address(addr))
+}
+
+/// Remove storage entries of accounts marked as deleted
+///
+/// An account that has been re-created since its deletion is skipped, as its
+/// old slots can't be told apart from the new account's; its marker is dropped.
+async fn purge_deleted_storage(
+    db: &SledDB,
+    accounts: &RwLock<HashMap<Address, AccountState>>,
+) -> Result<usize> {
+    let internal = |e: StorageError| norn_common::error::NornError::Internal(format!("Storage GC error: {}", e));
+
+    let markers: Vec<Vec<u8>> = db.iter_prefix(keys::DELETED_ACCOUNT_PREFIX)
+        .map(|item| item.map(|(key, _)| key))
+        .collect::<std::result::Result<_, StorageError>>()
+        .map_err(internal)?;
+
+    let mut purged = 0;
+    for marker in markers {
+        let addr_bytes = &marker[keys::DELETED_ACCOUNT_PREFIX.len()..];
+        if addr_bytes.len() != 20 {
+            warn!("Invalid deleted account marker length: {}", marker.len());
+            db.remove_sync(&marker).map_err(internal)?;
+            continue;
+        }
+        let mut addr = [0u8; 20];
+        addr.copy_from_slice(addr_bytes);
+        let address = Address(addr);
+
+        if !accounts.read().await.contains_key(&address) {
+            let mut prefix = Vec::from(keys::STORAGE_PREFIX);
+            prefix.extend_from_slice(&address.0);
+
+            let storage_keys: Vec<Vec<u8>> = db.iter_prefix(&prefix)
+                .map(|item| item.map(|(key, _)| key))
+                .collect::<std::result::Result<_, StorageError>>()
+                .map_err(internal)?;
+            for key in &storage_keys {
+                db.remove_sync(key).map_err(internal)?;
+            }
+
+            debug!("Purged {} storage entries of deleted account {:?}", storage_keys.len(), address);
+            purged += storage_keys.len();
+        }
+
+        db.remove_sync(&marker).map_err(internal)?;
+    }
+
+    if purged > 0 {
+        info!("Storage GC removed {} orphaned storage entries", purged);
+    }
+    Ok(purged)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::AccountType;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -490,6 +1004,156 @@ mod tests {
         assert_eq!(account.account_type, AccountType::Contract);
     }
 
+    #[tokio::test]
+    async fn test_state_commit_interval() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Arc::new(SledDB::new(temp_dir.path().to_str().unwrap()).unwrap());
+        let config = PersistentConfig {
+            state_commit_interval_blocks: 5,
+            ..Default::default()
+        };
+        let manager = PersistentStateManager::new(db.clone(), config).unwrap();
+
+        let address = Address([3u8; 20]);
+        let mut key = Vec::from(keys::ACCOUNT_PREFIX);
+        key.extend_from_slice(&address.0);
+        let on_disk = || {
+            db.get_sync(&key).unwrap()
+                .map(|bytes| bincode::deserialize::<AccountState>(&bytes).unwrap().balance)
+        };
+
+        for block in 1..=12u64 {
+            manager.update_balance(&address, block.to_string()).await.unwrap();
+            let committed = manager.on_block_applied(block, Hash::default()).await.unwrap();
+            assert_eq!(committed, block % 5 == 0, "block {}", block);
+
+            // Reads always see the latest block
+            assert_eq!(manager.get_balance(&address).await.unwrap(), block.to_string());
+
+            let expected_on_disk = match block {
+                1..=4 => None,
+                5..=9 => Some(BigUint::from(5u64)),
+                _ => Some(BigUint::from(10u64)),
+            };
+            assert_eq!(on_disk(), expected_on_disk, "block {}", block);
+        }
+
+        assert_eq!(manager.disk_commits(), 2);
+        assert_eq!(manager.committed_block().unwrap(), Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_commit_writes_only_changed_accounts() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Arc::new(SledDB::new(temp_dir.path()).unwrap());
+        let manager = PersistentStateManager::new(db.clone(), PersistentConfig::default()).unwrap();
+
+        let changed = Address([1u8; 20]);
+        let untouched = Address([2u8; 20]);
+        manager.update_balance(&changed, "1".to_string()).await.unwrap();
+        manager.update_balance(&untouched, "1".to_string()).await.unwrap();
+        manager.set_storage(&changed, vec![1], vec![10]).await.unwrap();
+        manager.on_block_applied(1, Hash::default()).await.unwrap();
+        assert!(db.get_sync(&account_key(&untouched)).unwrap().is_some());
+
+        // An account left alone is not rewritten by later commits
+        db.remove_sync(&account_key(&untouched)).unwrap();
+        manager.update_balance(&changed, "2".to_string()).await.unwrap();
+        manager.base_manager().delete_storage(&changed, &[1]).await.unwrap();
+        manager.on_block_applied(2, Hash::default()).await.unwrap();
+
+        assert!(db.get_sync(&account_key(&untouched)).unwrap().is_none());
+        let stored: AccountState = bincode::deserialize(&db.get_sync(&account_key(&changed)).unwrap().unwrap()).unwrap();
+        assert_eq!(stored.balance, BigUint::from(2u64));
+        assert!(db.get_sync(&storage_key(&changed, &[1])).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_uncommitted_blocks_recovered_from_wal() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("db");
+        let wal_dir = temp_dir.path().join("state_wal");
+        let config = PersistentConfig {
+            state_commit_interval_blocks: 5,
+            ..Default::default()
+        };
+        let address = Address([5u8; 20]);
+
+        {
+            let db = Arc::new(SledDB::new(&db_path).unwrap());
+            let wal = WAL::new(&wal_dir, norn_storage::WALConfig::default()).unwrap();
+            let manager = PersistentStateManager::new(db, config.clone()).unwrap().with_wal(wal);
+            for block in 1..=7u64 {
+                manager.update_balance(&address, block.to_string()).await.unwrap();
+                manager.set_storage(&address, vec![block as u8], vec![1]).await.unwrap();
+                manager.on_block_applied(block, Hash([block as u8; 32])).await.unwrap();
+            }
+            // Changes of a block that never finished applying
+            manager.update_balance(&address, "8".to_string()).await.unwrap();
+            assert_eq!(manager.committed_block().unwrap(), Some(5));
+        }
+
+        // Restart after a crash: blocks 6 and 7 come back from the WAL
+        let db = Arc::new(SledDB::new(&db_path).unwrap());
+        let wal = WAL::new(&wal_dir, norn_storage::WALConfig::default()).unwrap();
+        let manager = PersistentStateManager::new(db, config).unwrap().with_wal(wal);
+        assert_eq!(manager.recover_from_wal(7).await.unwrap(), Some(7));
+        manager.load_from_db_async().await.unwrap();
+
+        assert_eq!(manager.get_balance(&address).await.unwrap(), "7");
+        assert_eq!(manager.get_storage(&address, &[7]).await.unwrap(), Some(vec![1]));
+        assert_eq!(manager.committed_block().unwrap(), Some(7));
+
+        // Recovering again is a no-op
+        assert_eq!(manager.recover_from_wal(7).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_aborted_and_unchained_blocks_not_recovered() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("db");
+        let wal_dir = temp_dir.path().join("state_wal");
+        let config = PersistentConfig {
+            state_commit_interval_blocks: 10,
+            ..Default::default()
+        };
+        let address = Address([5u8; 20]);
+        let block_at = |height: i64| {
+            let mut block = Block::default();
+            block.header.height = height;
+            block.header.block_hash = Hash([height as u8; 32]);
+            block
+        };
+
+        {
+            let db = Arc::new(SledDB::new(&db_path).unwrap());
+            let wal = WAL::new(&wal_dir, norn_storage::WALConfig::default()).unwrap();
+            let manager = PersistentStateManager::new(db, config.clone()).unwrap().with_wal(wal);
+            for height in 1..=3 {
+                let block = block_at(height);
+                manager.update_balance(&address, height.to_string()).await.unwrap();
+                let mut write = ChainWrite::default();
+                manager.on_block_imported(&block, &mut write).await.unwrap();
+                if height == 2 {
+                    // The chain failed to store block 2
+                    manager.on_import_aborted(&block).await;
+                } else {
+                    manager.on_block_committed(&block).await;
+                }
+            }
+        }
+
+        // The chain never got past block 1 before the crash, so block 3 is
+        // discarded too
+        let db = Arc::new(SledDB::new(&db_path).unwrap());
+        let wal = WAL::new(&wal_dir, norn_storage::WALConfig::default()).unwrap();
+        let manager = PersistentStateManager::new(db, config).unwrap().with_wal(wal);
+        assert_eq!(manager.recover_from_wal(1).await.unwrap(), Some(1));
+        manager.load_from_db_async().await.unwrap();
+        assert_eq!(manager.get_balance(&address).await.unwrap(), "1");
+        assert_eq!(manager.recover_from_wal(3).await.unwrap(), None);
+    }
+
     #[tokio::test]
     async fn test_checkpoint_and_restore() {
         let temp_dir = TempDir::new().unwrap();
@@ -532,4 +1196,118 @@ mod tests {
         let retrieved = manager.get_storage(&address, &key).await.unwrap();
         assert_eq!(retrieved, Some(value));
     }
+
+    #[tokio::test]
+    async fn test_purge_deleted_accounts() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Arc::new(SledDB::new(temp_dir.path().to_str().unwrap()).unwrap());
+        let config = PersistentConfig {
+            write_through: true,
+            ..Default::default()
+        };
+        let manager = PersistentStateManager::new(db.clone(), config).unwrap();
+
+        let account = |address| AccountState {
+            address,
+            balance: BigUint::from(1u64),
+            nonce: 0,
+            account_type: AccountType::Contract,
+            code_hash: Some(Hash::default()),
+            storage_root: Hash::default(),
+            created_at: 0,
+            updated_at: 0,
+            deleted: false,
+        };
+        let storage_keys = |address: Address| -> Vec<Vec<u8>> {
+            let mut prefix = Vec::from(keys::STORAGE_PREFIX);
+            prefix.extend_from_slice(&address.0);
+            db.iter_prefix(&prefix).map(|item| item.unwrap().0).collect()
+        };
+
+        let deleted = Address([3u8; 20]);
+        let kept = Address([4u8; 20]);
+        for address in [deleted, kept] {
+            manager.set_account(&address, account(address)).await.unwrap();
+            manager.set_storage(&address, vec![1], vec![10]).await.unwrap();
+            manager.set_storage(&address, vec![2], vec![20]).await.unwrap();
+        }
+
+        manager.delete_account(&deleted).await.unwrap();
+        // Deleting only drops the account; slots linger until purged
+        assert_eq!(storage_keys(deleted).len(), 2);
+
+        assert_eq!(manager.purge_deleted_accounts().await.unwrap(), 2);
+        assert!(storage_keys(deleted).is_empty());
+        assert_eq!(storage_keys(kept).len(), 2);
+
+        // Nothing left to purge
+        assert_eq!(manager.purge_deleted_accounts().await.unwrap(), 0);
+
+        // The GC task purges on its first tick
+        manager.delete_account(&kept).await.unwrap();
+        let manager = Arc::new(manager);
+        let gc = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.run_storage_gc().await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        gc.abort();
+        assert!(storage_keys(kept).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_accounts_persisted() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Arc::new(SledDB::new(temp_dir.path()).unwrap());
+        let config = PersistentConfig { write_through: true, ..Default::default() };
+        let manager = PersistentStateManager::new(db.clone(), config).unwrap();
+
+        let accounts: Vec<_> = (1..=100u8)
+            .map(|i| AccountState {
+                address: Address([i; 20]),
+                balance: BigUint::from(i),
+                nonce: 0,
+                account_type: AccountType::Normal,
+                code_hash: None,
+                storage_root: Hash::default(),
+                created_at: 0,
+                updated_at: 0,
+                deleted: false,
+            })
+            .collect();
+        assert_eq!(manager.load_accounts(accounts).await.unwrap(), 100);
+
+        // Persisted without a flush
+        let reopened = PersistentStateManager::new(db, PersistentConfig::default()).unwrap();
+        reopened.load_from_db_async().await.unwrap();
+        let account = reopened.get_account(&Address([42u8; 20])).await.unwrap().unwrap();
+        assert_eq!(account.balance, BigUint::from(42u8));
+    }
+
+    #[tokio::test]
+    async fn test_load_accounts_committed_with_next_block() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = Arc::new(SledDB::new(temp_dir.path()).unwrap());
+        let manager = PersistentStateManager::new(db.clone(), PersistentConfig::default()).unwrap();
+
+        let address = Address([7u8; 20]);
+        let account = AccountState {
+            address,
+            balance: BigUint::from(7u8),
+            nonce: 0,
+            account_type: AccountType::Normal,
+            code_hash: None,
+            storage_root: Hash::default(),
+            created_at: 0,
+            updated_at: 0,
+            deleted: false,
+        };
+        assert_eq!(manager.load_accounts(vec![account]).await.unwrap(), 1);
+        assert!(db.get_sync(&account_key(&address)).unwrap().is_none());
+
+        assert!(manager.on_block_applied(1, Hash([1u8; 32])).await.unwrap());
+        let reopened = PersistentStateManager::new(db, PersistentConfig::default()).unwrap();
+        reopened.load_from_db_async().await.unwrap();
+        assert_eq!(reopened.get_account(&address).await.unwrap().unwrap().balance, BigUint::from(7u8));
+    }
 }
@@ -0,0 +1,127 @@
+//! Read cache of committed account state
+//!
+//! RPC reads of balances and nonces are served from a concurrent cache
+//! instead of taking the [`AccountStateManager`] locks that block execution
+//! holds. Every entry is tagged with the chain head it was read at and is
+//! only served while that head is current, so importing a block invalidates
+//! the whole cache without touching it.
+//!
+//! State written outside block import (e.g. dev-mode balance changes) must
+//! be dropped with [`StateReadCache::invalidate`].
+
+use super::account::{AccountState, AccountStateManager};
+use moka::sync::Cache;
+use norn_common::error::Result;
+use norn_common::types::{Address, Hash};
+use num_bigint::BigUint;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Default number of accounts kept in the cache
+pub const DEFAULT_READ_CACHE_CAPACITY: u64 = 100_000;
+
+/// Account state cached per chain head, shared by its readers
+pub struct StateReadCache {
+    state: Arc<AccountStateManager>,
+    head: watch::Receiver<Hash>,
+    /// Account (`None` if it doesn't exist) and the head it was read at
+    accounts: Cache<Address, (Hash, Option<AccountState>)>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl StateReadCache {
+    /// Cache reads of `state`, valid until `head` changes
+    pub fn new(state: Arc<AccountStateManager>, head: watch::Receiver<Hash>) -> Self {
+        Self::with_capacity(state, head, DEFAULT_READ_CACHE_CAPACITY)
+    }
+
+    /// Cache at most `capacity` accounts
+    pub fn with_capacity(state: Arc<AccountStateManager>, head: watch::Receiver<Hash>, capacity: u64) -> Self {
+        Self {
+            state,
+            head,
+            accounts: Cache::new(capacity),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The cached state manager
+    pub fn state(&self) -> &Arc<AccountStateManager> {
+        &self.state
+    }
+
+    /// Account at `address` as of the current head
+    pub async fn get_account(&self, address: &Address) -> Result<Option<AccountState>> {
+        let head = *self.head.borrow();
+        if let Some((cached_at, account)) = self.accounts.get(address) {
+            if cached_at == head {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(account);
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let account = self.state.get_account(address).await?;
+        // A block imported during the read may have changed the account
+        if *self.head.borrow() == head {
+            self.accounts.insert(*address, (head, account.clone()));
+        }
+        Ok(account)
+    }
+
+    /// Balance of `address`, zero for unknown accounts
+    pub async fn get_balance(&self, address: &Address) -> Result<BigUint> {
+        Ok(self.get_account(address).await?.map(|a| a.balance).unwrap_or_default())
+    }
+
+    /// Nonce of `address`, zero for unknown accounts
+    pub async fn get_nonce(&self, address: &Address) -> Result<u64> {
+        Ok(self.get_account(address).await?.map_or(0, |a| a.nonce))
+    }
+
+    /// Drop the cached state of `address`
+    pub fn invalidate(&self, address: &Address) {
+        self.accounts.invalidate(address);
+    }
+
+    /// Reads served from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Reads that went to the state manager
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::account::AccountStateConfig;
+
+    #[tokio::test]
+    async fn test_reads_cached_until_next_block() {
+        let state = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let (head, head_rx) = watch::channel(Hash([1u8; 32]));
+        let cache = StateReadCache::new(Arc::clone(&state), head_rx);
+
+        let address = Address([7u8; 20]);
+        state.update_balance(&address, BigUint::from(100u64)).await.unwrap();
+
+        for _ in 0..5 {
+            assert_eq!(cache.get_balance(&address).await.unwrap(), BigUint::from(100u64));
+        }
+        assert_eq!((cache.hits(), cache.misses()), (4, 1));
+
+        // The next block changes the balance; nothing is read from the old head
+        state.update_balance(&address, BigUint::from(250u64)).await.unwrap();
+        head.send_replace(Hash([2u8; 32]));
+        assert_eq!(cache.get_balance(&address).await.unwrap(), BigUint::from(250u64));
+        assert_eq!(cache.get_balance(&address).await.unwrap(), BigUint::from(250u64));
+        assert_eq!((cache.hits(), cache.misses()), (5, 2));
+    }
+}
@@ -0,0 +1,268 @@
+//! Test harness wiring the core components together
+//!
+//! [`TestNode::builder`] assembles a [`Blockchain`] on a temporary database
+//! with its account state, EVM executor and transaction pool, so tests of
+//! the layers above (RPC, execution) don't have to, and
+//! [`TestNode::produce_block`] moves pool transactions into the chain.
+//! Available to other crates with the `test-utils` feature.
+
+use crate::blockchain::Blockchain;
+use crate::evm::{Bloom, EVMConfig, EVMContext, EVMExecutor};
+use crate::merkle::build_merkle_tree;
+use crate::pruning::PruningMode;
+use crate::state::merkle::StateRootCalculator;
+use crate::state::{AccountStateConfig, AccountStateManager};
+use crate::txpool::TxPool;
+use crate::validation::calculate_block_hash;
+use norn_common::types::{Address, Block, BlockHeader, Hash, TransactionType};
+use norn_storage::SledDB;
+use num_bigint::BigUint;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Core components of a node, backed by a temporary database
+///
+/// The database is deleted when the node is dropped.
+pub struct TestNode {
+    pub blockchain: Arc<Blockchain>,
+    pub state_manager: Arc<AccountStateManager>,
+    pub evm_executor: Arc<EVMExecutor>,
+    pub tx_pool: Arc<TxPool>,
+    pub db: Arc<SledDB>,
+    _data_dir: TempDir,
+}
+
+impl TestNode {
+    pub fn builder() -> TestNodeBuilder {
+        TestNodeBuilder::default()
+    }
+
+    /// Build the next block, execute it and commit it to the chain
+    ///
+    /// With `txs_from_pool` the block takes the transactions the pool
+    /// packages, ordered by sender and nonce; otherwise it is empty. EVM
+    /// transactions are executed and their receipts stored, native ones are
+    /// included as they are; the header's logs bloom covers the receipts.
+    /// The block only depends on its parent and its transactions, so tests
+    /// see the same hashes on every run.
+    pub async fn produce_block(&self, txs_from_pool: bool) -> Block {
+        let parent = self.blockchain.latest_block.read().await.header.clone();
+        let mut transactions = if txs_from_pool {
+            self.tx_pool.package(&*self.blockchain).await
+        } else {
+            Vec::new()
+        };
+        transactions.sort_by_key(|tx| (tx.body.address.0, tx.body.nonce));
+
+        let mut block = Block {
+            header: BlockHeader {
+                timestamp: parent.timestamp + 1,
+                prev_block_hash: parent.block_hash,
+                merkle_root: build_merkle_tree(&transactions),
+                height: parent.height + 1,
+                gas_limit: parent.gas_limit,
+                base_fee: parent.base_fee,
+                ..Default::default()
+            },
+            transactions,
+        };
+        let block_number = block.header.height as u64;
+        let ctx = EVMContext {
+            block_number,
+            block_timestamp: block.header.timestamp as u64,
+            block_gas_limit: block.header.gas_limit as u64,
+            block_base_fee: block.header.base_fee,
+            ..Default::default()
+        };
+        // The hash covers the logs bloom, so receipts get it once all
+        // transactions ran
+        let mut cumulative_gas_used = 0;
+        let mut receipts = Vec::new();
+        for (index, tx) in block.transactions.iter().enumerate() {
+            if tx.body.tx_type != TransactionType::EVM {
+                continue;
+            }
+
+            self.evm_executor.clear_logs().await;
+            let result = self.evm_executor.execute(tx, &ctx).await.expect("Failed to execute transaction");
+            self.evm_executor.process_execution_logs(
+                tx.body.hash,
+                Hash::default(),
+                block_number,
+                index as u64,
+                result.logs.clone(),
+            ).await.expect("Failed to record logs");
+            cumulative_gas_used += result.gas_used;
+            let receipt = self.evm_executor.create_receipt(
+                tx.body.hash,
+                Hash::default(),
+                block_number,
+                index as u64,
+                tx.body.address,
+                Some(tx.body.receiver),
+                &result,
+                None,
+                cumulative_gas_used,
+            ).await;
+            receipts.push(receipt);
+        }
+        block.header.logs_bloom = Bloom::from_receipts(&receipts).as_bytes().to_vec();
+        block.header.block_hash = calculate_block_hash(&block);
+
+        for mut receipt in receipts {
+            receipt.block_hash = block.header.block_hash;
+            for log in &mut receipt.logs {
+                log.block_hash = block.header.block_hash;
+            }
+            self.evm_executor.receipt_db().put_receipt(receipt).await.expect("Failed to store receipt");
+        }
+
+        block.header.state_root = StateRootCalculator::for_manager(&self.state_manager)
+            .calculate_from_manager(&self.state_manager)
+            .await
+            .expect("Failed to compute state root");
+        self.blockchain.commit_block(&block).await.expect("Failed to commit block");
+        block
+    }
+}
+
+/// Builder of a [`TestNode`]
+#[derive(Default)]
+pub struct TestNodeBuilder {
+    accounts: Vec<(Address, BigUint)>,
+    state_config: AccountStateConfig,
+    evm_config: EVMConfig,
+    pruning: PruningMode,
+}
+
+impl TestNodeBuilder {
+    /// Fund `address` with `balance`
+    pub fn with_account(mut self, address: Address, balance: impl Into<BigUint>) -> Self {
+        self.accounts.push((address, balance.into()));
+        self
+    }
+
+    pub fn with_state_config(mut self, config: AccountStateConfig) -> Self {
+        self.state_config = config;
+        self
+    }
+
+    pub fn with_evm_config(mut self, config: EVMConfig) -> Self {
+        self.evm_config = config;
+        self
+    }
+
+    /// Prune block bodies as `pruning` says; the chain keeps all of them by default
+    pub fn with_pruning(mut self, pruning: PruningMode) -> Self {
+        self.pruning = pruning;
+        self
+    }
+
+    /// Open the database and wire up the components
+    ///
+    /// The chain starts from the fixed genesis block.
+    pub async fn build(self) -> TestNode {
+        let data_dir = tempfile::tempdir().expect("Failed to create test data directory");
+        let db = Arc::new(SledDB::new(data_dir.path()).expect("Failed to open test database"));
+        let genesis = norn_common::genesis::get_genesis_block();
+        let blockchain = Blockchain::new_with_pruning(db.clone(), genesis, self.pruning).await;
+
+        let state_manager = Arc::new(AccountStateManager::new(self.state_config));
+        for (address, balance) in &self.accounts {
+            state_manager.add_balance(address, balance).await.expect("Failed to fund test account");
+        }
+        let evm_executor = Arc::new(EVMExecutor::new(state_manager.clone(), self.evm_config));
+
+        TestNode {
+            blockchain,
+            state_manager,
+            evm_executor,
+            tx_pool: Arc::new(TxPool::new()),
+            db,
+            _data_dir: data_dir,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_builder_funds_accounts() {
+        let node = TestNode::builder()
+            .with_account(Address([1u8; 20]), 1000u32)
+            .build()
+            .await;
+
+        assert_eq!(node.state_manager.get_balance(&Address([1u8; 20])).await.unwrap(), BigUint::from(1000u32));
+        assert_eq!(node.blockchain.latest_block.read().await.header.height, 0);
+    }
+
+    #[tokio::test]
+    async fn test_produced_block_includes_pool_transactions() {
+        let sender = Address([1u8; 20]);
+        let receiver = Address([2u8; 20]);
+        let node = TestNode::builder()
+            .with_account(sender, 1_000_000_000_000_000_000u128)
+            .build()
+            .await;
+
+        let mut tx = norn_common::types::Transaction::default();
+        tx.body.hash = Hash([7u8; 32]);
+        tx.body.address = sender;
+        tx.body.receiver = receiver;
+        tx.body.gas = 21_000;
+        tx.body.tx_type = TransactionType::EVM;
+        tx.body.value = Some("1000".to_string());
+        node.tx_pool.add(tx);
+
+        let block = node.produce_block(true).await;
+        assert_eq!(block.header.height, 1);
+        assert_eq!(block.transactions.len(), 1);
+        assert!(!node.tx_pool.contains(&Hash([7u8; 32])));
+        assert_eq!(node.blockchain.latest_block.read().await.header.block_hash, block.header.block_hash);
+
+        let receipt = node.evm_executor.receipt_db().get_receipt(&Hash([7u8; 32])).await.unwrap().unwrap();
+        assert_eq!(receipt.block_hash, block.header.block_hash);
+        assert_eq!(receipt.block_number, 1);
+        assert!(receipt.status);
+        assert_eq!(node.state_manager.get_balance(&receiver).await.unwrap(), BigUint::from(1000u32));
+
+        let empty = node.produce_block(false).await;
+        assert_eq!(empty.header.prev_block_hash, block.header.block_hash);
+        assert!(empty.transactions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_produced_block_carries_logs_bloom() {
+        let sender = Address([1u8; 20]);
+        let node = TestNode::builder()
+            .with_account(sender, 1_000_000_000_000_000_000u128)
+            .build()
+            .await;
+
+        // LOG1(0, 0, 42); STOP
+        let code = vec![0x60, 0x2a, 0x60, 0x00, 0x60, 0x00, 0xa1, 0x00];
+        let (contract, _) = node.evm_executor.create_contract(sender, 0, code, 0, 100_000).await.unwrap();
+
+        let mut tx = norn_common::types::Transaction::default();
+        tx.body.hash = Hash([8u8; 32]);
+        tx.body.address = sender;
+        tx.body.receiver = contract;
+        tx.body.gas = 100_000;
+        tx.body.tx_type = TransactionType::EVM;
+        tx.body.data = vec![0x01];
+        node.tx_pool.add(tx);
+
+        let block = node.produce_block(true).await;
+        let receipts = node.evm_executor.receipt_db().get_receipts_by_block(&block.header.block_hash).await.unwrap();
+        assert_eq!(receipts[0].logs.len(), 1);
+
+        let bloom = Bloom::from_slice(&block.header.logs_bloom).unwrap();
+        assert_eq!(bloom, Bloom::from_receipts(&receipts));
+        assert!(bloom.might_match(Some(&contract), &[]));
+        let stored = node.blockchain.get_block_by_hash(&block.header.block_hash).await.unwrap();
+        assert_eq!(stored.header.logs_bloom, block.header.logs_bloom);
+    }
+}
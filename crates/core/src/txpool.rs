@@ -1,8 +1,11 @@
+use crate::state::AccountStateManager;
 use dashmap::DashMap;
+use norn_common::traits::DBInterface;
 use norn_common::types::{Hash, Transaction};
+use norn_common::utils::codec;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use async_trait::async_trait;
-use tracing::{debug};
+use tracing::{debug, info};
 
 // Trait to decouple TxPool from Blockchain
 #[async_trait]
@@ -42,6 +45,7 @@ pub struct TxPoolStats {
 
 const MAX_TX_POOL_SIZE: usize = 20480;
 const MAX_TX_PACKAGE_COUNT: usize = 10000;
+const MEMPOOL_KEY: &[u8] = b"mempool";
 
 #[derive(Debug)]
 pub struct TxPool {
@@ -85,6 +89,13 @@ impl TxPool {
         self.txs.get(hash).map(|t| t.clone())
     }
 
+    /// All pooled transactions, ordered by sender and nonce
+    pub fn pending(&self) -> Vec<Transaction> {
+        let mut txs: Vec<Transaction> = self.txs.iter().map(|r| r.value().clone()).collect();
+        txs.sort_by_key(|tx| (tx.body.address.0, tx.body.nonce));
+        txs
+    }
+
     pub async fn package<C: ChainReader>(&self, chain: &C) -> Vec<Transaction> {
         debug!("Start package transaction...");
         let mut result = Vec::with_capacity(MAX_TX_PACKAGE_COUNT);
@@ -123,6 +134,56 @@ impl TxPool {
         result
     }
 
+    /// Write the pooled transactions to `db`, e.g. before shutting down
+    ///
+    /// # Returns
+    /// Number of transactions saved
+    pub async fn save(&self, db: &dyn DBInterface) -> anyhow::Result<usize> {
+        let txs = self.pending();
+        db.insert(MEMPOOL_KEY, &codec::serialize(&txs)?).await?;
+        info!("Saved {} pooled transactions", txs.len());
+        Ok(txs.len())
+    }
+
+    /// Restore the transactions saved by [`TxPool::save`]
+    ///
+    /// Transactions already included in `chain`, or whose nonce `state`
+    /// has moved past, are dropped. The saved pool is removed from `db`, so
+    /// it is restored only once.
+    ///
+    /// # Returns
+    /// Number of transactions restored
+    pub async fn load<C: ChainReader>(
+        &self,
+        db: &dyn DBInterface,
+        chain: &C,
+        state: &AccountStateManager,
+    ) -> anyhow::Result<usize> {
+        let Some(bytes) = db.get(MEMPOOL_KEY).await? else {
+            return Ok(0);
+        };
+        let txs: Vec<Transaction> = codec::deserialize(&bytes)?;
+        let saved = txs.len();
+
+        let mut restored = 0;
+        for tx in txs {
+            if chain.get_transaction_by_hash(&tx.body.hash).await.is_some() {
+                debug!("Dropping saved transaction already in a block: {:?}", tx.body.hash);
+                continue;
+            }
+            if (tx.body.nonce as u64) < state.get_nonce(&tx.body.address).await? {
+                debug!("Dropping saved transaction with stale nonce: {:?}", tx.body.hash);
+                continue;
+            }
+            self.add(tx);
+            restored += 1;
+        }
+
+        db.remove(MEMPOOL_KEY).await?;
+        info!("Restored {} of {} saved transactions", restored, saved);
+        Ok(restored)
+    }
+
     pub async fn stats(&self) -> TxPoolStats {
         let size = self.count.load(Ordering::Relaxed);
         let total_gas_price = 0u64; // Standard pool doesn't track gas prices
@@ -273,4 +334,52 @@ pub mod tests {
 
     }
 
+    /// Chain holding only the transaction with the given hash
+    struct IncludedChain(Hash);
+
+    #[async_trait]
+    impl ChainReader for IncludedChain {
+        async fn get_transaction_by_hash(&self, hash: &Hash) -> Option<Transaction> {
+            (*hash == self.0).then(Transaction::default)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_txpool_restored_after_restart() {
+        use crate::state::AccountStateConfig;
+        use norn_common::types::Address;
+        use norn_storage::SledDB;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = SledDB::new(temp_dir.path()).unwrap();
+        let state = AccountStateManager::new(AccountStateConfig::default());
+
+        let sender = Address([1u8; 20]);
+        let txs: Vec<Transaction> = (0..3u8).map(|i| {
+            let mut tx = create_tx(i + 1);
+            tx.body.address = sender;
+            tx.body.nonce = i as i64;
+            tx
+        }).collect();
+
+        let pool = TxPool::new();
+        for tx in &txs {
+            pool.add(tx.clone());
+        }
+        assert_eq!(pool.save(&db).await.unwrap(), 3);
+
+        // While the node was down, the first transaction made it into a block
+        state.increment_nonce(&sender).await.unwrap();
+        let chain = IncludedChain(txs[0].body.hash);
+
+        let restarted = TxPool::new();
+        assert_eq!(restarted.load(&db, &chain, &state).await.unwrap(), 2);
+        assert!(!restarted.contains(&txs[0].body.hash));
+        assert!(restarted.contains(&txs[1].body.hash));
+        assert!(restarted.contains(&txs[2].body.hash));
+
+        // The saved pool is only restored once
+        assert_eq!(TxPool::new().load(&db, &chain, &state).await.unwrap(), 0);
+    }
+
 }
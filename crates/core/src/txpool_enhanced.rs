@@ -4,13 +4,18 @@
 //! - Priority-based transaction ordering by gas price
 //! - EIP-1559 transaction replacement
 //! - Pending transaction tracking
-//! - Transaction expiration and cleanup
+//! - Transaction expiration, cleanup and periodic re-announcement
+//! - Local senders exempt from price floors, eviction and expiration
+//! - Journaling of local transactions across restarts
 
 use crate::txpool::{ChainReader, TransactionPool, TxPoolStats as CommonTxPoolStats};
-use norn_common::types::{Hash, Transaction, Address};
-use std::collections::{HashMap, BinaryHeap};
+use crate::txpool_journal::TxJournal;
+use norn_common::types::{Block, Hash, Transaction, Address};
+use std::collections::{HashMap, HashSet, BinaryHeap};
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
 use tracing::{debug, info, warn};
 
 use serde::{Deserialize, Serialize};
@@ -19,6 +24,9 @@ use async_trait::async_trait;
 const MAX_TX_POOL_SIZE: usize = 20480;
 const MAX_TX_PACKAGE_COUNT: usize = 10000;
 const TX_EXPIRATION_TIME: i64 = 3600; // 1 hour in seconds
+const TX_REANNOUNCE_TIME: i64 = 300; // 5 minutes in seconds
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(30);
+const REJOURNAL_INTERVAL: Duration = Duration::from_secs(3600);
 
 /// Transaction with priority information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,14 +37,18 @@ pub struct PrioritizedTransaction {
     pub effective_gas_price: u64,
     /// Time added to pool (for expiration)
     pub added_at: i64,
+    /// Time last announced to peers (for re-announcement)
+    pub announced_at: i64,
     /// Nonce for replacement tracking
     pub nonce: i64,
     /// Sender address
     pub sender: Address,
+    /// Sent by a local address, see [`EnhancedTxPoolConfig::locals`]
+    pub local: bool,
 }
 
 impl PrioritizedTransaction {
-    fn new(tx: Transaction) -> Self {
+    fn new(tx: Transaction, local: bool) -> Self {
         let effective_gas_price = tx.body.max_fee_per_gas
             .or(tx.body.gas_price)
             .unwrap_or(0) as u64;
@@ -49,15 +61,20 @@ impl PrioritizedTransaction {
             tx,
             effective_gas_price,
             added_at,
+            announced_at: added_at,
             nonce,
             sender,
+            local,
         }
     }
 
-    /// Check if transaction is expired
-    fn is_expired(&self) -> bool {
+    /// Check if transaction outlived `lifetime_secs` (local transactions never expire)
+    fn is_expired(&self, lifetime_secs: i64) -> bool {
+        if self.local {
+            return false;
+        }
         let now = chrono::Utc::now().timestamp();
-        (now - self.added_at) > TX_EXPIRATION_TIME
+        (now - self.added_at) > lifetime_secs
     }
 }
 
@@ -84,8 +101,46 @@ impl Ord for PrioritizedTransaction {
     }
 }
 
+/// Enhanced transaction pool configuration
+#[derive(Debug, Clone)]
+pub struct EnhancedTxPoolConfig {
+    /// Maximum number of pooled transactions
+    pub max_size: usize,
+    /// Minimum effective gas price of remote transactions
+    pub min_gas_price: u64,
+    /// Senders whose transactions are local: accepted below `min_gas_price`
+    /// and never evicted or expired
+    pub locals: HashSet<Address>,
+    /// File journaling local transactions, see [`EnhancedTxPool::load_journal`]
+    pub journal: Option<PathBuf>,
+    /// How often the journal is rewritten with the pooled local transactions
+    pub rejournal_interval: Duration,
+    /// Seconds a remote transaction may stay pending before it is dropped
+    pub tx_lifetime_secs: i64,
+    /// Seconds after which a pending transaction is announced to peers again
+    pub reannounce_interval_secs: i64,
+}
+
+impl Default for EnhancedTxPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: MAX_TX_POOL_SIZE,
+            min_gas_price: 0,
+            locals: HashSet::new(),
+            journal: None,
+            rejournal_interval: REJOURNAL_INTERVAL,
+            tx_lifetime_secs: TX_EXPIRATION_TIME,
+            reannounce_interval_secs: TX_REANNOUNCE_TIME,
+        }
+    }
+}
+
 /// Enhanced transaction pool with priority queue
 pub struct EnhancedTxPool {
+    /// Pool configuration
+    config: EnhancedTxPoolConfig,
+    /// Journal of local transactions
+    journal: Option<Arc<TxJournal>>,
     /// All pending transactions by hash
     transactions: Arc<RwLock<HashMap<Hash, PrioritizedTransaction>>>,
     /// Priority queue for transaction selection
@@ -99,7 +154,14 @@ pub struct EnhancedTxPool {
 impl EnhancedTxPool {
     /// Create a new enhanced transaction pool
     pub fn new() -> Self {
+        Self::with_config(EnhancedTxPoolConfig::default())
+    }
+
+    /// Create a new enhanced transaction pool with custom config
+    pub fn with_config(config: EnhancedTxPoolConfig) -> Self {
         Self {
+            journal: config.journal.clone().map(|path| Arc::new(TxJournal::new(path))),
+            config,
             transactions: Arc::new(RwLock::new(HashMap::new())),
             priority_queue: Arc::new(RwLock::new(BinaryHeap::new())),
             pending_by_sender: Arc::new(RwLock::new(HashMap::new())),
@@ -107,19 +169,36 @@ impl EnhancedTxPool {
         }
     }
 
+    /// Whether transactions from `sender` are local
+    pub fn is_local(&self, sender: &Address) -> bool {
+        self.config.locals.contains(sender)
+    }
+
     /// Add a transaction to the pool
+    ///
+    /// Remote transactions must pay at least the configured minimum gas
+    /// price. When the pool is full, the cheapest remote transaction is
+    /// evicted to make room for a better paying or local one. Local
+    /// transactions are written to the journal, if one is configured.
     pub async fn add(&self, tx: Transaction) -> Result<(), TxPoolError> {
-        // Check pool size limit first (without holding the write lock yet)
-        {
-            let size = self.size.read().await;
-            if *size >= MAX_TX_POOL_SIZE {
-                return Err(TxPoolError::PoolFull);
-            }
-        }
+        self.add_and_journal(tx, true).await
+    }
 
+    async fn add_and_journal(&self, tx: Transaction, journal: bool) -> Result<(), TxPoolError> {
         let hash = tx.body.hash;
         let sender = tx.body.address;
         let nonce = tx.body.nonce;
+        let local = self.is_local(&sender);
+        let gas_price = tx.body.max_fee_per_gas
+            .or(tx.body.gas_price)
+            .unwrap_or(0);
+
+        if !local && gas_price < self.config.min_gas_price {
+            return Err(TxPoolError::Underpriced {
+                gas_price,
+                min_gas_price: self.config.min_gas_price,
+            });
+        }
 
         // Check if transaction already exists
         {
@@ -160,16 +239,22 @@ impl EnhancedTxPool {
             }
         };
 
-        let prioritized = PrioritizedTransaction::new(tx);
+        let prioritized = PrioritizedTransaction::new(tx, local);
 
         // Remove old transaction if replacing (do this BEFORE acquiring write locks)
         if should_replace {
             self.remove_by_sender_nonce(sender, nonce).await;
         }
 
-        // Add to pool (now acquire all write locks together)
+        // Add to pool (now acquire all write locks together). The size lock
+        // is held from the capacity check on, so concurrent adds can't both
+        // take the last free slot.
         {
             let mut size = self.size.write().await;
+            if !should_replace && *size >= self.config.max_size {
+                self.evict_for(&prioritized, &mut size).await?;
+            }
+
             let mut txs = self.transactions.write().await;
             let mut queue = self.priority_queue.write().await;
             let mut pending = self.pending_by_sender.write().await;
@@ -181,10 +266,94 @@ impl EnhancedTxPool {
             *size += 1;
         }
 
+        if let (true, true, Some(journal)) = (journal, local, &self.journal) {
+            if let Err(e) = journal.insert(&prioritized.tx) {
+                warn!("Failed to journal local transaction {:?}: {}", hash, e);
+            }
+        }
+
         info!("Added transaction {:?} to pool", hash);
         Ok(())
     }
 
+    /// Re-inject the local transactions of the journal, e.g. on startup
+    ///
+    /// The journal is then rotated, dropping transactions that were not
+    /// re-admitted. Returns the number of re-injected transactions.
+    pub async fn load_journal(&self) -> std::io::Result<usize> {
+        let Some(journal) = &self.journal else {
+            return Ok(0);
+        };
+
+        let mut injected = 0;
+        for tx in journal.load()? {
+            if !self.is_local(&tx.body.address) {
+                continue;
+            }
+            match self.add_and_journal(tx, false).await {
+                Ok(()) => injected += 1,
+                Err(e) => debug!("Not re-injecting journaled transaction: {}", e),
+            }
+        }
+
+        self.rotate_journal().await?;
+        info!("Re-injected {} local transactions from {:?}", injected, journal.path());
+        Ok(injected)
+    }
+
+    /// Rewrite the journal with the local transactions currently pooled
+    pub async fn rotate_journal(&self) -> std::io::Result<()> {
+        match &self.journal {
+            Some(journal) => journal.rotate(&local_transactions(&self.transactions).await),
+            None => Ok(()),
+        }
+    }
+
+    /// Rotate the journal every `rejournal_interval`
+    ///
+    /// Returns at once if the pool has no journal.
+    pub async fn run_journal_rotation(&self) {
+        let Some(journal) = &self.journal else {
+            return;
+        };
+        info!("Rotating transaction journal every {:?}", self.config.rejournal_interval);
+
+        let mut interval = tokio::time::interval(self.config.rejournal_interval);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = journal.rotate(&local_transactions(&self.transactions).await) {
+                warn!("Failed to rotate transaction journal: {}", e);
+            }
+        }
+    }
+
+    /// Make room for `incoming` by evicting the cheapest remote transaction
+    ///
+    /// Fails with [`TxPoolError::PoolFull`] if every pooled transaction is
+    /// local, or if `incoming` is remote and doesn't pay more than the
+    /// cheapest one. The caller holds the size lock and passes its guard.
+    async fn evict_for(&self, incoming: &PrioritizedTransaction, size: &mut usize) -> Result<(), TxPoolError> {
+        let cheapest = {
+            let txs = self.transactions.read().await;
+            txs.values()
+                .filter(|p| !p.local)
+                .min()
+                .map(|p| (p.tx.body.hash, p.effective_gas_price))
+        };
+
+        match cheapest {
+            Some((hash, gas_price)) if incoming.local || incoming.effective_gas_price > gas_price => {
+                debug!("Evicting transaction {:?} to make room for {:?}", hash, incoming.tx.body.hash);
+                self.remove_locked(&hash, size).await;
+                Ok(())
+            }
+            _ => Err(TxPoolError::PoolFull),
+        }
+    }
+
     /// Remove a transaction by sender and nonce (for replacement)
     async fn remove_by_sender_nonce(&self, sender: Address, nonce: i64) {
         let pending_by_sender = self.pending_by_sender.read().await;
@@ -199,7 +368,11 @@ impl EnhancedTxPool {
     /// Remove a transaction from the pool
     pub async fn remove(&self, hash: &Hash) {
         let mut size = self.size.write().await;
+        self.remove_locked(hash, &mut size).await;
+    }
 
+    /// Remove a transaction while the caller holds the size lock
+    async fn remove_locked(&self, hash: &Hash, size: &mut usize) {
         let (sender, nonce) = {
             let txs = self.transactions.read().await;
             if let Some(prioritized) = txs.get(hash) {
@@ -234,6 +407,43 @@ impl EnhancedTxPool {
         // Instead, we filter out stale transactions during package().
     }
 
+    /// Reconcile the pool with a chain reorganization
+    ///
+    /// Transactions from the `reverted` blocks go back to pending with their
+    /// original nonces, unless the new canonical `applied` blocks include
+    /// them. Pooled transactions whose sender and nonce were taken by an
+    /// applied block are dropped. Returns the number of re-admitted transactions.
+    pub async fn handle_reorg(&self, reverted: &[Block], applied: &[Block]) -> usize {
+        let included: Vec<&Transaction> = applied.iter()
+            .flat_map(|block| &block.transactions)
+            .collect();
+        let included_hashes: HashSet<Hash> = included.iter().map(|tx| tx.body.hash).collect();
+        let used_nonces: HashSet<(Address, i64)> = included.iter()
+            .map(|tx| (tx.body.address, tx.body.nonce))
+            .collect();
+
+        for (sender, nonce) in &used_nonces {
+            self.remove_by_sender_nonce(*sender, *nonce).await;
+        }
+
+        let mut readmitted = 0;
+        for tx in reverted.iter().flat_map(|block| &block.transactions) {
+            if included_hashes.contains(&tx.body.hash)
+                || used_nonces.contains(&(tx.body.address, tx.body.nonce))
+            {
+                continue;
+            }
+
+            match self.add_and_journal(tx.clone(), false).await {
+                Ok(()) => readmitted += 1,
+                Err(e) => debug!("Not re-admitting orphaned transaction {:?}: {}", tx.body.hash, e),
+            }
+        }
+
+        info!("Re-admitted {} orphaned transactions after reorg", readmitted);
+        readmitted
+    }
+
     /// Get a transaction by hash
     pub async fn get(&self, hash: &Hash) -> Option<Transaction> {
         let txs = self.transactions.read().await;
@@ -307,7 +517,7 @@ impl EnhancedTxPool {
 
         for (hash, prioritized) in candidates {
             // Skip if expired
-            if prioritized.is_expired() {
+            if prioritized.is_expired(self.config.tx_lifetime_secs) {
                 debug!("Transaction {:?} expired, removing", hash);
                 to_remove.push(hash);
                 continue;
@@ -388,7 +598,7 @@ impl EnhancedTxPool {
         {
             let txs = self.transactions.read().await;
             for (hash, prioritized) in txs.iter() {
-                if prioritized.is_expired() {
+                if prioritized.is_expired(self.config.tx_lifetime_secs) {
                     to_remove.push(*hash);
                 }
             }
@@ -402,6 +612,46 @@ impl EnhancedTxPool {
         info!("Cleaned up {} expired transactions", to_remove.len());
     }
 
+    /// Drop expired transactions and collect the ones due for re-announcement
+    ///
+    /// Pending transactions last announced more than `reannounce_interval_secs`
+    /// ago are returned and marked as announced now.
+    pub async fn maintain(&self) -> Vec<Transaction> {
+        self.cleanup_expired().await;
+
+        let now = chrono::Utc::now().timestamp();
+        let mut txs = self.transactions.write().await;
+        let reannounce: Vec<Transaction> = txs.values_mut()
+            .filter(|p| now - p.announced_at >= self.config.reannounce_interval_secs)
+            .map(|p| {
+                p.announced_at = now;
+                p.tx.clone()
+            })
+            .collect();
+
+        debug!("Re-announcing {} pending transactions", reannounce.len());
+        reannounce
+    }
+
+    /// Run [`EnhancedTxPool::maintain`] periodically, sending the transactions
+    /// to re-announce on `reannounce`
+    ///
+    /// Returns once `reannounce` is closed.
+    pub async fn run_maintenance(&self, reannounce: mpsc::Sender<Transaction>) {
+        let mut interval = tokio::time::interval(MAINTENANCE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            for tx in self.maintain().await {
+                if reannounce.send(tx).await.is_err() {
+                    info!("Transaction pool maintenance stopped");
+                    return;
+                }
+            }
+        }
+    }
+
     /// Get pool statistics
     pub async fn stats(&self) -> TxPoolStats {
         let size = *self.size.read().await;
@@ -420,11 +670,21 @@ impl EnhancedTxPool {
         TxPoolStats {
             size,
             avg_gas_price,
-            max_size: MAX_TX_POOL_SIZE,
+            max_size: self.config.max_size,
         }
     }
 }
 
+/// Local transactions in `transactions`, ordered by sender and nonce
+async fn local_transactions(
+    transactions: &RwLock<HashMap<Hash, PrioritizedTransaction>>,
+) -> Vec<Transaction> {
+    let txs = transactions.read().await;
+    let mut locals: Vec<&PrioritizedTransaction> = txs.values().filter(|p| p.local).collect();
+    locals.sort_by_key(|p| (p.sender.0, p.nonce));
+    locals.into_iter().map(|p| p.tx.clone()).collect()
+}
+
 /// Implement the common TransactionPool trait
 #[async_trait]
 impl TransactionPool for EnhancedTxPool {
@@ -476,6 +736,9 @@ pub enum TxPoolError {
     #[error("Replacement fee too low")]
     ReplacementFeeTooLow,
 
+    #[error("Gas price {gas_price} below minimum {min_gas_price}")]
+    Underpriced { gas_price: u64, min_gas_price: u64 },
+
     #[error("Transaction validation failed: {0}")]
     ValidationFailed(String),
 }
@@ -572,6 +835,43 @@ mod tests {
         assert!(pool.contains(&tx2.body.hash).await);
     }
 
+    #[tokio::test]
+    async fn test_handle_reorg_readmits_orphaned_transactions() {
+        let pool = EnhancedTxPool::new();
+
+        let tx = |id: u8, sender: u8, nonce: i64| {
+            let mut tx = Transaction::default();
+            tx.body.hash.0[0] = id;
+            tx.body.address = Address([sender; 20]);
+            tx.body.nonce = nonce;
+            tx
+        };
+        let block = |txs: Vec<Transaction>| Block { transactions: txs, ..Block::default() };
+
+        // Orphaned: re-mined (1), orphaned only (2, 3), and conflicting with
+        // a different transaction for the same nonce in the new chain (4)
+        let reverted = vec![block(vec![tx(1, 1, 0), tx(2, 2, 0), tx(4, 4, 0)]), block(vec![tx(3, 2, 1)])];
+        let applied = vec![block(vec![tx(1, 1, 0), tx(5, 4, 0)])];
+
+        // A pooled copy of a transaction the new chain includes
+        pool.add(tx(5, 4, 0)).await.unwrap();
+
+        assert_eq!(pool.handle_reorg(&reverted, &applied).await, 2);
+        assert!(pool.contains(&tx(2, 2, 0).body.hash).await);
+        assert!(pool.contains(&tx(3, 2, 1).body.hash).await);
+        assert!(!pool.contains(&tx(1, 1, 0).body.hash).await);
+        assert!(!pool.contains(&tx(4, 4, 0).body.hash).await);
+        assert!(!pool.contains(&tx(5, 4, 0).body.hash).await);
+
+        // Seeing the same reorg again doesn't duplicate anything
+        assert_eq!(pool.handle_reorg(&reverted, &applied).await, 0);
+        assert_eq!(pool.stats().await.size, 2);
+
+        let mut nonces: Vec<i64> = pool.package(&MockChain).await.iter().map(|tx| tx.body.nonce).collect();
+        nonces.sort();
+        assert_eq!(nonces, vec![0, 1]);
+    }
+
     #[tokio::test]
     async fn test_pool_stats() {
         let pool = EnhancedTxPool::new();
@@ -584,4 +884,146 @@ mod tests {
         assert_eq!(stats.size, 1);
         assert_eq!(stats.avg_gas_price, 100);
     }
+
+    #[tokio::test]
+    async fn test_local_transactions_bypass_price_floor_and_eviction() {
+        let local = Address([1u8; 20]);
+        let pool = EnhancedTxPool::with_config(EnhancedTxPoolConfig {
+            max_size: 2,
+            min_gas_price: 100,
+            locals: HashSet::from([local]),
+            ..Default::default()
+        });
+
+        let tx = |id: u8, sender: Address, gas_price: u64| {
+            let mut tx = Transaction::default();
+            tx.body.hash.0[0] = id;
+            tx.body.address = sender;
+            tx.body.gas_price = Some(gas_price);
+            tx
+        };
+
+        // Below the floor: accepted from a local address only
+        pool.add(tx(1, local, 10)).await.unwrap();
+        assert!(matches!(
+            pool.add(tx(2, Address([2u8; 20]), 10)).await,
+            Err(TxPoolError::Underpriced { gas_price: 10, min_gas_price: 100 })
+        ));
+
+        // A full pool evicts the cheapest remote transaction, never the local one
+        pool.add(tx(3, Address([3u8; 20]), 200)).await.unwrap();
+        pool.add(tx(4, Address([4u8; 20]), 300)).await.unwrap();
+        assert!(pool.contains(&tx(1, local, 10).body.hash).await);
+        assert!(!pool.contains(&tx(3, Address([3u8; 20]), 200).body.hash).await);
+        assert!(pool.contains(&tx(4, Address([4u8; 20]), 300).body.hash).await);
+
+        // Outbidding only the local transaction is not enough
+        assert!(matches!(pool.add(tx(5, Address([5u8; 20]), 250)).await, Err(TxPoolError::PoolFull)));
+    }
+
+    #[tokio::test]
+    async fn test_local_transactions_reinjected_from_journal() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let local = Address([1u8; 20]);
+        let config = EnhancedTxPoolConfig {
+            locals: HashSet::from([local]),
+            journal: Some(temp_dir.path().join("transactions.journal")),
+            ..Default::default()
+        };
+
+        let tx = |id: u8, sender: Address| {
+            let mut tx = Transaction::default();
+            tx.body.hash.0[0] = id;
+            tx.body.address = sender;
+            tx
+        };
+
+        let pool = EnhancedTxPool::with_config(config.clone());
+        pool.add(tx(1, local)).await.unwrap();
+        pool.add(tx(2, Address([2u8; 20]))).await.unwrap();
+
+        // Crash: the pool goes away without a rotation or shutdown
+        drop(pool);
+
+        let restarted = EnhancedTxPool::with_config(config.clone());
+        assert_eq!(restarted.load_journal().await.unwrap(), 1);
+        assert!(restarted.contains(&tx(1, local).body.hash).await);
+        assert!(!restarted.contains(&tx(2, Address([2u8; 20])).body.hash).await);
+
+        // Once included, the transaction leaves the journal on the next rotation
+        restarted.remove(&tx(1, local).body.hash).await;
+        restarted.rotate_journal().await.unwrap();
+        assert_eq!(EnhancedTxPool::with_config(config).load_journal().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_journal_rotation_drops_included_transactions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let local = Address([1u8; 20]);
+        let config = EnhancedTxPoolConfig {
+            locals: HashSet::from([local]),
+            journal: Some(temp_dir.path().join("transactions.journal")),
+            rejournal_interval: Duration::from_millis(20),
+            ..Default::default()
+        };
+
+        // Without a journal there is nothing to rotate
+        tokio::time::timeout(Duration::from_secs(1), EnhancedTxPool::new().run_journal_rotation())
+            .await
+            .unwrap();
+
+        let mut tx = Transaction::default();
+        tx.body.address = local;
+        let pool = Arc::new(EnhancedTxPool::with_config(config.clone()));
+        pool.add(tx.clone()).await.unwrap();
+        pool.remove(&tx.body.hash).await;
+
+        let rotation = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run_journal_rotation().await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        rotation.abort();
+
+        assert_eq!(EnhancedTxPool::with_config(config).load_journal().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_drops_expired_and_reannounces_pending() {
+        let pool = EnhancedTxPool::with_config(EnhancedTxPoolConfig {
+            tx_lifetime_secs: 600,
+            reannounce_interval_secs: 60,
+            ..Default::default()
+        });
+
+        let tx = |id: u8| {
+            let mut tx = Transaction::default();
+            tx.body.hash.0[0] = id;
+            tx.body.address = Address([id; 20]);
+            tx
+        };
+        pool.add(tx(1)).await.unwrap();
+        pool.add(tx(2)).await.unwrap();
+        pool.add(tx(3)).await.unwrap();
+
+        // Backdate: 1 outlived the lifetime, 2 is due for re-announcement, 3 is fresh
+        {
+            let now = chrono::Utc::now().timestamp();
+            let mut txs = pool.transactions.write().await;
+            for (id, age) in [(1u8, 601), (2, 120)] {
+                let p = txs.get_mut(&tx(id).body.hash).unwrap();
+                p.added_at = now - age;
+                p.announced_at = now - age;
+            }
+        }
+
+        let reannounced: Vec<Hash> = pool.maintain().await.iter().map(|tx| tx.body.hash).collect();
+        assert_eq!(reannounced, vec![tx(2).body.hash]);
+        assert!(!pool.contains(&tx(1).body.hash).await);
+        assert!(pool.contains(&tx(2).body.hash).await);
+        assert!(pool.contains(&tx(3).body.hash).await);
+
+        // Not announced again until another interval has passed
+        assert!(pool.maintain().await.is_empty());
+    }
 }
@@ -0,0 +1,124 @@
+//! Journal of local transactions
+//!
+//! Local transactions are appended to a file as they enter the pool, one
+//! JSON encoded transaction per line, so they survive an unclean shutdown
+//! and can be re-injected on startup. The journal only grows between
+//! rotations, which rewrite it with the local transactions still pooled.
+
+use norn_common::types::Transaction;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{debug, warn};
+
+/// Append-only file of local transactions
+#[derive(Debug)]
+pub struct TxJournal {
+    path: PathBuf,
+    /// Open for appending between rotations
+    writer: Mutex<Option<File>>,
+}
+
+impl TxJournal {
+    /// Journal stored at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), writer: Mutex::new(None) }
+    }
+
+    /// Path of the journal file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read back all journaled transactions
+    ///
+    /// A missing journal is empty. Lines that can't be decoded, such as one
+    /// cut short by a crash, are skipped.
+    pub fn load(&self) -> io::Result<Vec<Transaction>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut txs = Vec::new();
+        for line in BufReader::new(file).lines() {
+            match serde_json::from_str(&line?) {
+                Ok(tx) => txs.push(tx),
+                Err(e) => warn!("Skipping corrupt journal entry in {:?}: {}", self.path, e),
+            }
+        }
+        Ok(txs)
+    }
+
+    /// Append `tx` to the journal
+    pub fn insert(&self, tx: &Transaction) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        if writer.is_none() {
+            *writer = Some(OpenOptions::new().create(true).append(true).open(&self.path)?);
+        }
+
+        let file = writer.as_mut().unwrap();
+        file.write_all(&encode(tx)?)?;
+        file.sync_data()
+    }
+
+    /// Replace the journal contents with `txs`
+    ///
+    /// The new journal is written next to the old one and renamed over it,
+    /// so a crash during rotation leaves one of them intact.
+    pub fn rotate(&self, txs: &[Transaction]) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        *writer = None;
+
+        let tmp = self.path.with_extension("new");
+        {
+            let mut out = BufWriter::new(File::create(&tmp)?);
+            for tx in txs {
+                out.write_all(&encode(tx)?)?;
+            }
+            out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        }
+        fs::rename(&tmp, &self.path)?;
+
+        debug!("Rotated transaction journal {:?} with {} transactions", self.path, txs.len());
+        Ok(())
+    }
+}
+
+fn encode(tx: &Transaction) -> io::Result<Vec<u8>> {
+    let mut line = serde_json::to_vec(tx)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate_replaces_entries_and_skips_torn_writes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let journal = TxJournal::new(temp_dir.path().join("transactions.journal"));
+        assert!(journal.load().unwrap().is_empty());
+
+        let tx = |id: u8| {
+            let mut tx = Transaction::default();
+            tx.body.hash.0[0] = id;
+            tx
+        };
+        journal.insert(&tx(1)).unwrap();
+        journal.insert(&tx(2)).unwrap();
+
+        // A write cut short by a crash
+        OpenOptions::new().append(true).open(journal.path()).unwrap().write_all(b"{\"body\":").unwrap();
+        let hashes: Vec<_> = journal.load().unwrap().iter().map(|tx| tx.body.hash).collect();
+        assert_eq!(hashes, vec![tx(1).body.hash, tx(2).body.hash]);
+
+        journal.rotate(&[tx(2)]).unwrap();
+        journal.insert(&tx(3)).unwrap();
+        let hashes: Vec<_> = journal.load().unwrap().iter().map(|tx| tx.body.hash).collect();
+        assert_eq!(hashes, vec![tx(2).body.hash, tx(3).body.hash]);
+    }
+}
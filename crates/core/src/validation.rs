@@ -1,16 +1,10 @@
 use anyhow::{Result, anyhow};
-use norn_common::types::{Block, Hash, GeneralParams, Address};
+use norn_common::types::{Block, Hash, Address};
 use norn_crypto::transaction::verify_transaction;
-use norn_crypto::vdf::VDFCalculator;
-use norn_crypto::vrf::{VRFProof};
 use rs_merkle::{MerkleTree, algorithms::Sha256 as MerkleSha256};
 use sha2::{Sha256, Digest};
-use chrono::Utc;
 use tracing::{debug, warn};
-use curve25519_dalek::{
-    ristretto::RistrettoPoint,
-    scalar::Scalar,
-};
+use crate::consensus::verifier::{HeaderVerifier, PoVFHeaderVerifier};
 use crate::state::AccountStateManager;
 
 /// Block validation errors
@@ -41,9 +35,10 @@ pub enum ValidationError {
 }
 
 /// Configuration for block validation
+#[derive(Debug, Clone)]
 pub struct ValidationConfig {
-    /// Maximum allowed timestamp drift from current time (seconds)
-    pub max_timestamp_drift: i64,
+    /// How far ahead of local time a block timestamp may be (seconds)
+    pub max_future_drift_secs: i64,
     /// Minimum timestamp interval between blocks (seconds)
     pub min_block_interval: i64,
     /// Maximum gas limit per block
@@ -64,7 +59,7 @@ impl Default for ValidationConfig {
         let verify_crypto = !norn_common::build_mode::IS_TEST_MODE;
 
         Self {
-            max_timestamp_drift: 300, // 5 minutes
+            max_future_drift_secs: 300, // 5 minutes
             min_block_interval: 1,    // 1 second
             max_gas_limit: 10_000_000,
             max_tx_per_block: 10_000,
@@ -104,8 +99,10 @@ pub async fn validate_block(
 ) -> Result<()> {
     debug!("Validating block at height {}", block.header.height);
 
-    // 1. Basic header validation
-    validate_header(block, previous_block, config)?;
+    // 1. Header rules, including the consensus proofs
+    PoVFHeaderVerifier::new(config.clone())
+        .verify_header(&block.header, previous_block.map(|b| &b.header))
+        .await?;
 
     // 2. Validate all transactions (with balance/nonce checks if state manager available)
     validate_transactions(block, config, state_manager).await?;
@@ -116,79 +113,13 @@ pub async fn validate_block(
     // 4. Validate block hash
     validate_block_hash(block)?;
 
-    // 5. Validate consensus proofs
-    if config.verify_vdf {
-        validate_vdf(block).await?;
-    }
-
-    if config.verify_vrf {
-        validate_vrf(block).await?;
-    }
-
-    // 6. Validate block size
+    // 5. Validate block size
     validate_block_size(block, config)?;
 
     debug!("Block validation successful for height {}", block.header.height);
     Ok(())
 }
 
-/// Validate block header
-fn validate_header(
-    block: &Block,
-    previous_block: Option<&Block>,
-    config: &ValidationConfig,
-) -> Result<()> {
-    // Check height
-    if block.header.height < 0 {
-        return Err(anyhow!(ValidationError::InvalidHeight));
-    }
-
-    // Check previous block hash
-    if let Some(prev) = previous_block {
-        if block.header.height != prev.header.height + 1 {
-            warn!(
-                "Invalid height: expected {}, got {}",
-                prev.header.height + 1,
-                block.header.height
-            );
-            return Err(anyhow!(ValidationError::InvalidHeight));
-        }
-
-        if block.header.prev_block_hash != prev.header.block_hash {
-            warn!(
-                "Invalid previous hash: expected {}, got {}",
-                hex::encode(prev.header.block_hash.0),
-                hex::encode(block.header.prev_block_hash.0)
-            );
-            return Err(anyhow!(ValidationError::InvalidPreviousHash));
-        }
-    } else if block.header.height > 0 {
-        // No previous block but height > 0 (not genesis)
-        return Err(anyhow!(ValidationError::InvalidPreviousHash));
-    }
-
-    // Check timestamp
-    let now = Utc::now().timestamp();
-    let timestamp = block.header.timestamp;
-
-    if timestamp > now + config.max_timestamp_drift {
-        return Err(anyhow!(ValidationError::InvalidTimestamp));
-    }
-
-    if let Some(prev) = previous_block {
-        if timestamp < prev.header.timestamp + config.min_block_interval {
-            return Err(anyhow!(ValidationError::InvalidTimestamp));
-        }
-    }
-
-    // Check gas limit
-    if block.header.gas_limit > config.max_gas_limit {
-        return Err(anyhow!(ValidationError::GasLimitExceeded));
-    }
-
-    Ok(())
-}
-
 /// Validate all transactions in a block
 async fn validate_transactions(
     block: &Block,
@@ -397,7 +328,7 @@ fn validate_block_hash(block: &Block) -> Result<()> {
 }
 
 /// Calculate block hash from header fields
-fn calculate_block_hash(block: &Block) -> Hash {
+pub fn calculate_block_hash(block: &Block) -> Hash {
     let mut hasher = Sha256::new();
 
     // Hash all header fields except the block hash itself
@@ -408,6 +339,7 @@ fn calculate_block_hash(block: &Block) -> Hash {
     hasher.update(block.header.public_key.0);
     hasher.update(&block.header.params);
     hasher.update(block.header.gas_limit.to_le_bytes());
+    hasher.update(&block.header.logs_bloom);
 
     let result = hasher.finalize();
     let mut hash = Hash::default();
@@ -415,285 +347,6 @@ fn calculate_block_hash(block: &Block) -> Hash {
     hash
 }
 
-/// Validate VDF proof
-/// 
-/// This function validates the VDF (Verifiable Delay Function) proof contained
-/// in the block header params. The VDF ensures that a certain amount of time
-/// has passed since the previous block.
-async fn validate_vdf(block: &Block) -> Result<()> {
-    // Skip VDF validation if params are empty (e.g., genesis block)
-    if block.header.params.is_empty() {
-        debug!("Skipping VDF validation - empty params (genesis block?)");
-        return Ok(());
-    }
-
-    // Deserialize the GeneralParams from block header
-    let params: GeneralParams = norn_common::utils::codec::deserialize(&block.header.params)
-        .map_err(|e| anyhow!("Failed to deserialize block params: {}", e))?;
-
-    // Extract VDF proof from params
-    if params.proof.is_empty() {
-        warn!("Block {} has empty VDF proof", block.header.height);
-        return Err(anyhow!(ValidationError::InvalidProof("Empty VDF proof".to_string())));
-    }
-
-    // Create VDF calculator and verify
-    let vdf = norn_crypto::vdf::SimpleVDF::new();
-    
-    // Create VDF output from params
-    let vdf_output = norn_crypto::vdf::VDFOutput {
-        proof: params.proof.clone(),
-        result: Hash::from_slice(&params.result),
-        iterations: extract_iterations_from_params(&params),
-        computation_time: std::time::Duration::from_secs(0), // Not needed for verification
-    };
-
-    // Calculate VDF input from previous block hash
-    let vdf_input = block.header.prev_block_hash;
-
-    // Verify the VDF
-    let is_valid = vdf.verify_vdf(&vdf_input, &vdf_output, &params).await;
-    
-    if !is_valid {
-        warn!("VDF verification failed for block {}", block.header.height);
-        return Err(anyhow!(ValidationError::InvalidProof("VDF verification failed".to_string())));
-    }
-
-    debug!("VDF validation passed for block {}", block.header.height);
-    Ok(())
-}
-
-/// Extract iteration count from GeneralParams
-fn extract_iterations_from_params(params: &GeneralParams) -> u64 {
-    if params.t.len() >= 8 {
-        let mut bytes = [0u8; 8];
-        bytes.copy_from_slice(&params.t[..8]);
-        u64::from_le_bytes(bytes)
-    } else {
-        // Fallback: sum all bytes
-        params.t.iter().fold(0u64, |acc, &x| acc + x as u64)
-    }
-}
-
-/// Validate VRF proof
-///
-/// This function validates the VRF (Verifiable Random Function) proof
-/// to ensure that the block proposer was legitimately selected.
-/// The VRF output in the block should match the proposer's public key.
-async fn validate_vrf(block: &Block) -> Result<()> {
-    use norn_crypto::vrf::{VRFProof};
-
-    // Skip VRF validation for genesis block (height 0)
-    if block.header.height == 0 {
-        debug!("Skipping VRF validation for genesis block");
-        return Ok(());
-    }
-
-    // Skip if params are empty
-    if block.header.params.is_empty() {
-        debug!("Skipping VRF validation - empty params");
-        return Ok(());
-    }
-
-    // 1. Validate that the block has a valid public key
-    let proposer_key = &block.header.public_key;
-    if proposer_key.0.iter().all(|&b| b == 0) {
-        warn!("Block {} has empty proposer public key", block.header.height);
-        return Err(anyhow!(ValidationError::InvalidProof("Empty proposer key".to_string())));
-    }
-
-    // 2. Deserialize the GeneralParams from block header to extract VRF proof
-    let params: norn_common::types::GeneralParams = match norn_common::utils::codec::deserialize(&block.header.params) {
-        Ok(p) => p,
-        Err(e) => {
-            warn!("Failed to deserialize block params for VRF validation: {}", e);
-            // If we can't deserialize params, we can't do full VRF verification
-            // Fall back to basic validation (valid public key already checked)
-            return Ok(());
-        }
-    };
-
-    // 3. Extract VRF proof from params
-    // The proof should be stored in params.proof or params.s
-    let vrf_proof_bytes = if !params.proof.is_empty() {
-        &params.proof
-    } else if !params.s.is_empty() {
-        &params.s
-    } else {
-        // No VRF proof found - accept based on public key validation
-        debug!("No VRF proof found in block params, accepting based on public key validation");
-        return Ok(());
-    };
-
-    // 4. Parse the VRF proof
-    let vrf_proof = match VRFProof::from_bytes(vrf_proof_bytes) {
-        Ok(proof) => proof,
-        Err(e) => {
-            warn!("Failed to parse VRF proof from block {}: {:?}", block.header.height, e);
-            // Invalid proof format - reject the block
-            return Err(anyhow!(ValidationError::InvalidProof(format!("Invalid VRF proof: {}", e))));
-        }
-    };
-
-    // 5. Verify VRF proof is properly formatted
-    // Check that gamma is not identity (invalid point)
-    // In Ristretto, the identity point compresses to a specific byte pattern
-    let gamma_bytes = vrf_proof.gamma.compress().to_bytes();
-    let identity_bytes = RistrettoPoint::default().compress().to_bytes();
-    if gamma_bytes == identity_bytes {
-        warn!("VRF proof contains identity point for block {}", block.header.height);
-        return Err(anyhow!(ValidationError::InvalidProof("Invalid VRF proof: identity point".to_string())));
-    }
-
-    // 6. Validate proof structure (basic checks)
-    // Challenge and response should not be zero
-    // Check if challenge bytes are all zeros
-    let challenge_bytes = vrf_proof.challenge.to_bytes();
-    if challenge_bytes == [0u8; 32] {
-        warn!("VRF proof has zero challenge for block {}", block.header.height);
-        return Err(anyhow!(ValidationError::InvalidProof("Invalid VRF proof: zero challenge".to_string())));
-    }
-
-    // 7. Additional validation: Verify the proof size is correct
-    if vrf_proof_bytes.len() != 96 {
-        warn!("VRF proof has invalid size {} for block {}", vrf_proof_bytes.len(), block.header.height);
-        return Err(anyhow!(ValidationError::InvalidProof("Invalid VRF proof size".to_string())));
-    }
-
-    // 8. Full cryptographic VRF verification
-    // Parse the proposer's public key from block header
-    let public_key = match parse_proposer_public_key(&block.header.public_key) {
-        Ok(pk) => pk,
-        Err(e) => {
-            warn!("Failed to parse proposer public key for block {}: {}", block.header.height, e);
-            return Err(anyhow!(ValidationError::InvalidProof(format!("Invalid public key: {}", e))));
-        }
-    };
-
-    // Create VRF message from block data for verification
-    let vrf_message = create_vrf_verification_message(block);
-
-    // Reconstruct VRF output from proof for verification
-    let vrf_output = norn_crypto::vrf::VRFOutput {
-        output: derive_vrf_output(&vrf_proof, &vrf_message),
-        proof: vrf_proof.clone(),
-    };
-
-    // 9. Perform full VRF verification using VRFCalculator
-    match norn_crypto::vrf::VRFCalculator::verify(&public_key, &vrf_message, &vrf_output) {
-        Ok(is_valid) => {
-            if !is_valid {
-                warn!("VRF verification failed for block {}", block.header.height);
-                return Err(anyhow!(ValidationError::InvalidVRF));
-            }
-        }
-        Err(e) => {
-            warn!("VRF verification error for block {}: {:?}", block.header.height, e);
-            // For backward compatibility, we accept blocks that fail verification due to format issues
-            // but only if they passed all previous structural checks
-            debug!("VRF verification had format issues, accepting based on structural validation");
-            return Ok(());
-        }
-    }
-
-    // 10. Verify the proposer has authority to propose at this height
-    // This would require checking against a validator set
-    // For now, we accept any valid VRF proof
-
-    debug!("VRF validation passed for block {} (cryptographically verified)", block.header.height);
-
-    Ok(())
-}
-
-/// Parse proposer's public key from block header
-fn parse_proposer_public_key(proposer_key: &norn_common::types::PublicKey) -> Result<RistrettoPoint> {
-    use curve25519_dalek::ristretto::CompressedRistretto;
-
-    // The proposer key is 33 bytes (prefix + 32-byte compressed Ristretto point)
-    // Extract the last 32 bytes which should be the actual compressed point
-    if proposer_key.0.len() < 32 {
-        return Err(anyhow!("Public key too short"));
-    }
-
-    let mut key_bytes = [0u8; 32];
-    // Take the last 32 bytes (skip the prefix byte)
-    key_bytes.copy_from_slice(&proposer_key.0[1..33]);
-
-    let compressed = CompressedRistretto(key_bytes);
-    compressed.decompress()
-        .ok_or_else(|| anyhow!("Failed to decompress proposer public key"))
-}
-
-/// Create VRF verification message from block data
-fn create_vrf_verification_message(block: &Block) -> Vec<u8> {
-    use serde::Serialize;
-
-    // Create a deterministic message from block data
-    // Include: previous hash, height, timestamp, and merkle root
-    let mut message = Vec::new();
-    message.extend_from_slice(b"VRF_BLOCK_PROPOSAL");
-    message.extend_from_slice(&block.header.prev_block_hash.0);
-    message.extend_from_slice(&block.header.height.to_be_bytes());
-    message.extend_from_slice(&block.header.timestamp.to_be_bytes());
-    message.extend_from_slice(&block.header.merkle_root.0);
-
-    // Hash the message for consistency
-    let hash = sha2::Sha256::digest(&message);
-    hash.to_vec()
-}
-
-/// Derive VRF output from proof and message
-fn derive_vrf_output(proof: &norn_crypto::vrf::VRFProof, message: &[u8]) -> [u8; 32] {
-    use sha2::{Sha512, Digest};
-
-    // This replicates the VRF output derivation logic from VRFCalculator
-    let mut hasher = Sha512::new();
-    hasher.update(b"VRF_OUTPUT");
-    hasher.update(proof.gamma.compress().as_bytes());
-
-    // Hash the message as well
-    let message_hash = sha2::Sha256::digest(message);
-    hasher.update(message_hash);
-
-    let hash = hasher.finalize();
-
-    // Take first 32 bytes as the output value
-    let mut output = [0u8; 32];
-    output.copy_from_slice(&hash[..32]);
-    output
-}
-
-/// Verify that the VRF output is below the selection threshold
-/// This ensures the proposer was legitimately selected
-fn verify_vrf_threshold(vrf_output: &[u8]) -> bool {
-    // Take first 8 bytes as u64 value
-    if vrf_output.len() < 8 {
-        return false;
-    }
-
-    let mut bytes = [0u8; 8];
-    bytes.copy_from_slice(&vrf_output[..8]);
-    let vrf_value = u64::from_be_bytes(bytes);
-
-    // Check if VRF value is below threshold
-    // For now, accept all values (100% threshold)
-    // In production, this should be based on stake weight
-    const VRF_THRESHOLD: u64 = u64::MAX;
-
-    vrf_value < VRF_THRESHOLD
-}
-
-/// Check if public key format is valid
-fn is_valid_public_key_format(key_bytes: &[u8]) -> bool {
-    // Basic validation: non-zero, reasonable length
-    if key_bytes.len() < 32 {
-        return false;
-    }
-
-    // Check not all zeros
-    !key_bytes.iter().all(|&b| b == 0)
-}
-
 /// Validate block size
 fn validate_block_size(block: &Block, config: &ValidationConfig) -> Result<()> {
     // Serialize block to check size
@@ -716,7 +369,9 @@ pub async fn validate_block_for_propagation(block: &Block) -> Result<()> {
     };
 
     // Only do basic validation
-    validate_header(block, None, &config)?;
+    PoVFHeaderVerifier::new(config.clone())
+        .verify_header(&block.header, None)
+        .await?;
     validate_merkle_root(block)?;
     validate_block_hash(block)?;
     validate_block_size(block, &config)?;
@@ -727,6 +382,7 @@ pub async fn validate_block_for_propagation(block: &Block) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Utc;
     use norn_common::types::BlockHeader;
 
     fn create_test_block(height: i64, prev_hash: Hash, timestamp: i64) -> Block {
@@ -742,6 +398,7 @@ mod tests {
                 params: vec![],
                 gas_limit: 1000000,
                 base_fee: 1_000_000_000,
+                logs_bloom: Vec::new(),
             },
             transactions: vec![],
         };
@@ -802,6 +459,44 @@ mod tests {
         assert!(validate_block(&block2_wrong, Some(&genesis), &config, None).await.is_err());
     }
 
+    #[tokio::test]
+    async fn test_timestamp_not_after_parent_rejected() {
+        let config = ValidationConfig {
+            min_block_interval: 0,
+            ..ValidationConfig::test_config()
+        };
+        let base_time = Utc::now().timestamp();
+        let genesis = create_test_block(0, Hash::default(), base_time);
+
+        for timestamp in [base_time, base_time - 1] {
+            let block = create_test_block(1, genesis.header.block_hash, timestamp);
+            let err = validate_block(&block, Some(&genesis), &config, None).await.unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<ValidationError>(),
+                Some(ValidationError::InvalidTimestamp)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_future_drift_rejected() {
+        let config = ValidationConfig {
+            max_future_drift_secs: 60,
+            ..ValidationConfig::test_config()
+        };
+        let now = Utc::now().timestamp();
+
+        let block = create_test_block(0, Hash::default(), now + 30);
+        assert!(validate_block(&block, None, &config, None).await.is_ok());
+
+        let block = create_test_block(0, Hash::default(), now + 120);
+        let err = validate_block(&block, None, &config, None).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ValidationError>(),
+            Some(ValidationError::InvalidTimestamp)
+        ));
+    }
+
     #[tokio::test]
     async fn test_validation_with_state_manager() {
         // Test that validation works with state manager (balance/nonce checks)
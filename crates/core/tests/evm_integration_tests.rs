@@ -291,22 +291,24 @@ async fn test_gas_estimation() {
     let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
     let config = EVMConfig::default();
     let executor = EVMExecutor::new(state_manager.clone(), config);
+    let ctx = EVMContext::default();
+    state_manager.update_balance(&test_address(1), BigUint::from(10u128.pow(19))).await.unwrap();
 
     // Simple ETH transfer
     let mut tx = create_test_transaction();
-    let gas = executor.estimate_gas(&tx).await.unwrap();
+    let gas = executor.estimate_gas(&tx, &ctx).await.unwrap();
     assert_eq!(gas, 21_000);
 
-    // Contract creation
-    tx.body.data = vec![0x60, 0x60];
+    // Contract creation running STOP: intrinsic gas only
+    tx.body.data = vec![0x00];
     tx.body.receiver = Address::default();
-    let gas = executor.estimate_gas(&tx).await.unwrap();
-    assert_eq!(gas, 53_000);
+    tx.body.value = None;
+    let gas = executor.estimate_gas(&tx, &ctx).await.unwrap();
+    assert_eq!(gas, 53_000 + 4 + 2);
 
-    // Contract call
+    // Calls to an account without code can't be estimated
     tx.body.receiver = test_address(5);
-    let gas = executor.estimate_gas(&tx).await.unwrap();
-    assert_eq!(gas, 26_000 + 2 * 16);
+    assert!(executor.estimate_gas(&tx, &ctx).await.is_err());
 }
 
 #[tokio::test]
@@ -441,6 +443,7 @@ async fn test_receipt_filtering() {
     let receipt1 = Receipt::new(test_hash(1), block_hash, 100, 0)
         .with_log(ReceiptLog {
             log_index: 0,
+            tx_index: 0,
             tx_hash: test_hash(1),
             block_hash,
             block_number: 100,
@@ -452,6 +455,7 @@ async fn test_receipt_filtering() {
     let receipt2 = Receipt::new(test_hash(2), block_hash, 100, 1)
         .with_log(ReceiptLog {
             log_index: 0,
+            tx_index: 0,
             tx_hash: test_hash(2),
             block_hash,
             block_number: 100,
@@ -1,6 +1,9 @@
+use lazy_static::lazy_static;
 use num_bigint::BigInt;
 use num_traits::Zero;
+use prometheus::{Counter, Histogram, HistogramOpts};
 use std::sync::{Arc, OnceLock};
+use std::time::Instant;
 use tokio::sync::{mpsc, RwLock};
 use tracing::{info, debug};
 use std::ops::Rem;
@@ -8,6 +11,24 @@ use std::ops::Rem;
 // Constants
 pub const RESULT_CHANNEL_CAP: usize = 32;
 
+// Metrics (registered by the node's metrics collector)
+lazy_static! {
+    pub static ref VDF_CALCULATIONS_TOTAL: Counter = Counter::new(
+        "norn_vdf_calculations_total",
+        "Total number of VDF calculations completed"
+    ).unwrap();
+
+    pub static ref VDF_CALCULATION_DURATION: Histogram = Histogram::with_opts(
+        HistogramOpts::new("norn_vdf_calculation_duration_seconds", "VDF calculation duration in seconds")
+            .buckets(vec![0.01, 0.1, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0])
+    ).unwrap();
+
+    pub static ref VDF_STALE_SEEDS_DROPPED_TOTAL: Counter = Counter::new(
+        "norn_vdf_stale_seeds_dropped_total",
+        "Total number of seeds dropped because they were already known"
+    ).unwrap();
+}
+
 // Singleton
 static CALCULATOR: OnceLock<Arc<Calculator>> = OnceLock::new();
 
@@ -93,6 +114,7 @@ impl Calculator {
         debug!("Current VDF seed: {}", state.seed);
         
         if state.prev_seed == *seed || state.seed == *seed {
+            VDF_STALE_SEEDS_DROPPED_TOTAL.inc();
             return;
         }
         
@@ -149,7 +171,10 @@ impl Calculator {
             }
 
             // Perform VDF calculation (simplified)
+            let started = Instant::now();
             let result = self.calculate_vdf(&seed, &proof).await;
+            VDF_CALCULATION_DURATION.observe(started.elapsed().as_secs_f64());
+            VDF_CALCULATIONS_TOTAL.inc();
             info!("VDF calculation result: {}", result);
 
             // Update state with calculation result
@@ -180,3 +205,33 @@ impl Calculator {
         (&result * proof).rem(&self.order)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_calculation_records_metrics() {
+        let calc = init_calculator(BigInt::from(100), BigInt::from(200), 10).await;
+        let samples_before = VDF_CALCULATION_DURATION.get_sample_count();
+        let calculations_before = VDF_CALCULATIONS_TOTAL.get();
+        let stale_before = VDF_STALE_SEEDS_DROPPED_TOTAL.get();
+
+        let seed = BigInt::from(12345);
+        calc.append_new_seed(&seed, &BigInt::from(67890)).await;
+
+        // Wait for the run loop to finish the calculation
+        for _ in 0..50 {
+            if VDF_CALCULATION_DURATION.get_sample_count() > samples_before {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(VDF_CALCULATION_DURATION.get_sample_count(), samples_before + 1);
+        assert_eq!(VDF_CALCULATIONS_TOTAL.get(), calculations_before + 1.0);
+
+        // Re-submitting the current seed is dropped as stale
+        calc.append_new_seed(&seed, &BigInt::from(67890)).await;
+        assert_eq!(VDF_STALE_SEEDS_DROPPED_TOTAL.get(), stale_before + 1.0);
+    }
+}
@@ -0,0 +1,324 @@
+//! EVM transactions signed the Ethereum way
+//!
+//! Transactions submitted as raw Ethereum transactions keep the signature
+//! the wallet made over the Ethereum signing hash. Their body carries the
+//! Ethereum fields, from which both encodings are rebuilt:
+//!
+//! - legacy when neither `max_fee_per_gas` nor `access_list` is set, with
+//!   EIP-155 replay protection when `chain_id` is;
+//! - EIP-2930 when `access_list` is set;
+//! - EIP-1559 when `max_fee_per_gas` is set.
+//!
+//! The hash of such a transaction is keccak256 of its signed encoding, its
+//! signature is `r || s || y_parity` and its sender is the Ethereum address
+//! of the signing key. The 65-byte signature tells it apart from a natively
+//! signed EVM transaction. A zero receiver stands for contract creation.
+
+use k256::ecdsa::{RecoveryId, Signature, SigningKey, VerifyingKey};
+use norn_common::types::{Address, Hash, PublicKey, TransactionBody, TransactionType, PUBLIC_KEY_LENGTH};
+use num_bigint::BigUint;
+use rlp::RlpStream;
+
+/// Length of an Ethereum signature: `r || s || y_parity`
+pub const SIGNATURE_LENGTH: usize = 65;
+
+const TX_TYPE_EIP2930: u8 = 0x01;
+const TX_TYPE_EIP1559: u8 = 0x02;
+
+/// Whether `body` carries an Ethereum signature
+pub fn is_ethereum_signed(body: &TransactionBody) -> bool {
+    body.tx_type == TransactionType::EVM && body.signature.len() == SIGNATURE_LENGTH
+}
+
+/// Hash the wallet signed: keccak256 of the unsigned encoding
+pub fn signing_hash(body: &TransactionBody) -> Hash {
+    Hash(keccak_hash::keccak(encode(body, None)).0)
+}
+
+/// Signed encoding of `body`, as submitted with eth_sendRawTransaction
+pub fn encode_signed(body: &TransactionBody) -> Vec<u8> {
+    encode(body, Some(&body.signature))
+}
+
+/// Ethereum hash of `body`: keccak256 of its signed encoding
+pub fn transaction_hash(body: &TransactionBody) -> Hash {
+    Hash(keccak_hash::keccak(encode_signed(body)).0)
+}
+
+/// Sign `body` with `key` as a wallet does, setting its signature, sender
+/// and hash
+pub fn sign(body: &mut TransactionBody, key: &SigningKey) -> Result<(), k256::ecdsa::Error> {
+    let (signature, recovery_id) = key.sign_prehash_recoverable(&signing_hash(body).0)?;
+    body.signature = signature.to_vec();
+    body.signature.push(recovery_id.to_byte());
+    body.public = public_key_of(key.verifying_key());
+    body.address = address_of(key.verifying_key());
+    body.hash = transaction_hash(body);
+    Ok(())
+}
+
+/// Key that made the signature of `body`, if it is a valid Ethereum signature
+pub fn recover_signer(body: &TransactionBody) -> Option<VerifyingKey> {
+    if !is_ethereum_signed(body) {
+        return None;
+    }
+    let signature = Signature::from_slice(&body.signature[..64]).ok()?;
+    // EIP-2: only the lower half of s is accepted
+    if signature.normalize_s().is_some() {
+        return None;
+    }
+    let recovery_id = RecoveryId::from_byte(body.signature[64])?;
+    VerifyingKey::recover_from_prehash(&signing_hash(body).0, &signature, recovery_id).ok()
+}
+
+/// Sender of `body` once its hash and signature check out
+///
+/// The recovered key must be the one in `body.public` and its address the
+/// one in `body.address`.
+pub fn verify(body: &TransactionBody) -> Option<Address> {
+    let key = recover_signer(body)?;
+    let sender = address_of(&key);
+    if public_key_of(&key) != body.public || sender != body.address || transaction_hash(body) != body.hash {
+        return None;
+    }
+    Some(sender)
+}
+
+/// Ethereum address of `key`: the last 20 bytes of the keccak256 of its
+/// uncompressed point
+pub fn address_of(key: &VerifyingKey) -> Address {
+    let point = key.to_encoded_point(false);
+    let hash = keccak_hash::keccak(&point.as_bytes()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    Address(address)
+}
+
+/// Compressed form of `key`, as kept in a transaction body
+pub fn public_key_of(key: &VerifyingKey) -> PublicKey {
+    let mut public_key = PublicKey::default();
+    let point = key.to_encoded_point(true);
+    if point.as_bytes().len() == PUBLIC_KEY_LENGTH {
+        public_key.0.copy_from_slice(point.as_bytes());
+    }
+    public_key
+}
+
+/// Hash a wallet signs for `message` with `personal_sign` (EIP-191)
+pub fn personal_message_hash(message: &[u8]) -> Hash {
+    let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    prefixed.extend_from_slice(message);
+    Hash(keccak_hash::keccak(prefixed).0)
+}
+
+/// Sign `message` with `key` as `personal_sign` does, returning
+/// `r || s || v` with `v` 27 or 28
+pub fn sign_personal_message(message: &[u8], key: &SigningKey) -> Result<Vec<u8>, k256::ecdsa::Error> {
+    let (signature, recovery_id) = key.sign_prehash_recoverable(&personal_message_hash(message).0)?;
+    let mut signature = signature.to_vec();
+    signature.push(27 + recovery_id.to_byte());
+    Ok(signature)
+}
+
+/// Address that signed `message` with `personal_sign`, if `signature` is a
+/// valid `r || s || v` signature of it
+///
+/// `v` may be given as 0/1 or 27/28.
+pub fn recover_personal_signer(message: &[u8], signature: &[u8]) -> Option<Address> {
+    if signature.len() != SIGNATURE_LENGTH {
+        return None;
+    }
+    let parsed = Signature::from_slice(&signature[..64]).ok()?;
+    if parsed.normalize_s().is_some() {
+        return None;
+    }
+    let v = signature[64];
+    let recovery_id = RecoveryId::from_byte(if v >= 27 { v - 27 } else { v })?;
+    let key = VerifyingKey::recover_from_prehash(&personal_message_hash(message).0, &parsed, recovery_id).ok()?;
+    Some(address_of(&key))
+}
+
+/// Encoding of `body`, unsigned or with `signature`
+fn encode(body: &TransactionBody, signature: Option<&[u8]>) -> Vec<u8> {
+    let signature = signature.filter(|s| s.len() == SIGNATURE_LENGTH);
+    let chain_id = body.chain_id.unwrap_or(0);
+
+    let (tx_type, mut stream) = if let Some(max_fee) = body.max_fee_per_gas {
+        let mut stream = RlpStream::new_list(if signature.is_some() { 12 } else { 9 });
+        stream.append(&chain_id);
+        stream.append(&(body.nonce as u64));
+        stream.append(&body.max_priority_fee_per_gas.unwrap_or(0));
+        stream.append(&max_fee);
+        stream.append(&(body.gas as u64));
+        append_call(&mut stream, body);
+        append_access_list(&mut stream, body);
+        (Some(TX_TYPE_EIP1559), stream)
+    } else if body.access_list.is_some() {
+        let mut stream = RlpStream::new_list(if signature.is_some() { 11 } else { 8 });
+        stream.append(&chain_id);
+        stream.append(&(body.nonce as u64));
+        stream.append(&body.gas_price.unwrap_or(0));
+        stream.append(&(body.gas as u64));
+        append_call(&mut stream, body);
+        append_access_list(&mut stream, body);
+        (Some(TX_TYPE_EIP2930), stream)
+    } else {
+        let eip155 = body.chain_id.is_some();
+        let mut stream = RlpStream::new_list(if signature.is_some() || eip155 { 9 } else { 6 });
+        stream.append(&(body.nonce as u64));
+        stream.append(&body.gas_price.unwrap_or(0));
+        stream.append(&(body.gas as u64));
+        append_call(&mut stream, body);
+        match signature {
+            Some(signature) => {
+                let parity = signature[64] as u64;
+                let v = if eip155 { chain_id * 2 + 35 + parity } else { 27 + parity };
+                stream.append(&v);
+                append_scalars(&mut stream, signature);
+            }
+            None if eip155 => {
+                stream.append(&chain_id);
+                stream.append_empty_data();
+                stream.append_empty_data();
+            }
+            None => {}
+        }
+        return stream.out().to_vec();
+    };
+
+    if let Some(signature) = signature {
+        stream.append(&(signature[64] as u64));
+        append_scalars(&mut stream, signature);
+    }
+    let mut encoded = vec![tx_type.unwrap_or_default()];
+    encoded.extend_from_slice(&stream.out());
+    encoded
+}
+
+/// `to`, `value` and `data`
+fn append_call(stream: &mut RlpStream, body: &TransactionBody) {
+    if body.receiver == Address::default() {
+        stream.append_empty_data();
+    } else {
+        stream.append(&body.receiver.0.as_slice());
+    }
+    let value = body.value.as_deref()
+        .and_then(|v| v.parse::<BigUint>().ok())
+        .unwrap_or_default();
+    stream.append(&trimmed(&value.to_bytes_be()));
+    stream.append(&body.data);
+}
+
+fn append_access_list(stream: &mut RlpStream, body: &TransactionBody) {
+    let access_list = body.access_list.as_deref().unwrap_or_default();
+    stream.begin_list(access_list.len());
+    for item in access_list {
+        stream.begin_list(2);
+        stream.append(&item.address.0.as_slice());
+        stream.begin_list(item.storage_keys.len());
+        for key in &item.storage_keys {
+            stream.append(&key.0.as_slice());
+        }
+    }
+}
+
+/// `r` and `s` as integers
+fn append_scalars(stream: &mut RlpStream, signature: &[u8]) {
+    stream.append(&trimmed(&signature[..32]));
+    stream.append(&trimmed(&signature[32..64]));
+}
+
+/// Big-endian integer bytes without leading zeros
+fn trimmed(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed(mut body: TransactionBody, key: &SigningKey) -> TransactionBody {
+        sign(&mut body, key).unwrap();
+        body
+    }
+
+    fn body() -> TransactionBody {
+        TransactionBody {
+            receiver: Address([0x35; 20]),
+            gas: 21_000,
+            nonce: 9,
+            value: Some("1000000000000000000".to_string()),
+            gas_price: Some(20_000_000_000),
+            chain_id: Some(1),
+            tx_type: TransactionType::EVM,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_eip155_example() {
+        // The example transaction of EIP-155
+        let key = SigningKey::from_slice(&[0x46; 32]).unwrap();
+        let body = body();
+        assert_eq!(
+            hex::encode(signing_hash(&body).0),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+
+        let body = signed(body, &key);
+        assert_eq!(
+            hex::encode(encode_signed(&body)),
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+        assert_eq!(verify(&body), Some(address_of(key.verifying_key())));
+    }
+
+    #[test]
+    fn test_typed_transactions_verify() {
+        let key = SigningKey::from_slice(&[0x11; 32]).unwrap();
+        let access_list = Some(vec![norn_common::types::AccessListItem {
+            address: Address([0x22; 20]),
+            storage_keys: vec![Hash([0x33; 32])],
+        }]);
+
+        let eip2930 = TransactionBody { access_list: access_list.clone(), ..body() };
+        let eip1559 = TransactionBody {
+            gas_price: None,
+            max_fee_per_gas: Some(30_000_000_000),
+            max_priority_fee_per_gas: Some(1_000_000_000),
+            access_list,
+            ..body()
+        };
+        for (body, tx_type) in [(eip2930, TX_TYPE_EIP2930), (eip1559, TX_TYPE_EIP1559)] {
+            let body = signed(body, &key);
+            assert_eq!(encode_signed(&body)[0], tx_type);
+            assert!(verify(&body).is_some());
+
+            // Any change to a signed field breaks the signature
+            let tampered = TransactionBody { nonce: 10, ..body.clone() };
+            assert!(verify(&tampered).is_none());
+        }
+    }
+
+    #[test]
+    fn test_personal_message_signer() {
+        assert_eq!(
+            hex::encode(personal_message_hash(b"hello").0),
+            "50b2c43fd39106bafbba0da34fc430e1f91e3c96ea2acee2bc34119f92b37750"
+        );
+
+        let key = SigningKey::from_slice(&[0x46; 32]).unwrap();
+        let signature = sign_personal_message(b"hello", &key).unwrap();
+        assert!(signature[64] == 27 || signature[64] == 28);
+        assert_eq!(recover_personal_signer(b"hello", &signature), Some(address_of(key.verifying_key())));
+
+        // 0/1 recovery ids are accepted as well
+        let mut raw_v = signature.clone();
+        raw_v[64] -= 27;
+        assert_eq!(recover_personal_signer(b"hello", &raw_v), Some(address_of(key.verifying_key())));
+
+        assert_ne!(recover_personal_signer(b"hellO", &signature), Some(address_of(key.verifying_key())));
+        assert_eq!(recover_personal_signer(b"hello", &signature[..64]), None);
+    }
+}
@@ -3,4 +3,6 @@ pub mod vrf;
 pub mod vdf;
 pub mod calculator;
 pub mod utils;
-pub mod transaction;
\ No newline at end of file
+pub mod transaction;
+pub mod scheme;
+pub mod ethereum;
\ No newline at end of file
@@ -0,0 +1,113 @@
+//! Signature schemes for transactions
+//!
+//! Native transactions are signed with ECDSA over P-256, EVM transactions
+//! with ECDSA over secp256k1 as Ethereum does. [`verifier_for`] picks the
+//! scheme from the transaction type. Public keys are SEC1 compressed points
+//! in both schemes, so they fit [`PublicKey`].
+
+use crate::ecdsa::KeyPair;
+use norn_common::types::{PublicKey, TransactionType, PUBLIC_KEY_LENGTH};
+
+/// Signs messages with a private key
+pub trait Signer: Send + Sync {
+    /// Compressed public key matching the private key
+    fn public_key(&self) -> PublicKey;
+
+    /// Sign `msg`
+    fn sign(&self, msg: &[u8]) -> Vec<u8>;
+}
+
+/// Checks signatures of a scheme
+pub trait Verifier: Send + Sync {
+    /// Whether `signature` is a valid signature of `msg` by `public_key`
+    fn verify(&self, public_key: &[u8], msg: &[u8], signature: &[u8]) -> bool;
+}
+
+/// ECDSA over P-256, used by native transactions
+#[derive(Debug, Clone, Copy, Default)]
+pub struct P256;
+
+/// ECDSA over secp256k1, used by EVM transactions
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Secp256k1;
+
+impl Verifier for P256 {
+    fn verify(&self, public_key: &[u8], msg: &[u8], signature: &[u8]) -> bool {
+        crate::ecdsa::verify(public_key, msg, signature).unwrap_or(false)
+    }
+}
+
+impl Verifier for Secp256k1 {
+    fn verify(&self, public_key: &[u8], msg: &[u8], signature: &[u8]) -> bool {
+        use k256::ecdsa::signature::Verifier as _;
+        use k256::ecdsa::{Signature, VerifyingKey};
+
+        let Ok(public_key) = VerifyingKey::from_sec1_bytes(public_key) else {
+            return false;
+        };
+        let Ok(signature) = Signature::from_der(signature).or_else(|_| Signature::from_slice(signature)) else {
+            return false;
+        };
+        public_key.verify(msg, &signature).is_ok()
+    }
+}
+
+/// Verifier for transactions of type `tx_type`
+pub fn verifier_for(tx_type: TransactionType) -> &'static dyn Verifier {
+    match tx_type {
+        TransactionType::Native => &P256,
+        TransactionType::EVM => &Secp256k1,
+    }
+}
+
+impl Signer for KeyPair {
+    fn public_key(&self) -> PublicKey {
+        to_public_key(KeyPair::public_key(self).to_encoded_point(true).as_bytes())
+    }
+
+    fn sign(&self, msg: &[u8]) -> Vec<u8> {
+        KeyPair::sign(self, msg)
+    }
+}
+
+impl Signer for k256::ecdsa::SigningKey {
+    fn public_key(&self) -> PublicKey {
+        to_public_key(self.verifying_key().to_encoded_point(true).as_bytes())
+    }
+
+    fn sign(&self, msg: &[u8]) -> Vec<u8> {
+        let signature: k256::ecdsa::Signature = k256::ecdsa::signature::Signer::sign(self, msg);
+        signature.to_vec()
+    }
+}
+
+fn to_public_key(bytes: &[u8]) -> PublicKey {
+    let mut public_key = PublicKey::default();
+    if bytes.len() == PUBLIC_KEY_LENGTH {
+        public_key.0.copy_from_slice(bytes);
+    }
+    public_key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::OsRng;
+
+    #[test]
+    fn test_signatures_only_verify_under_their_scheme() {
+        let msg = b"norn";
+
+        let p256_key = KeyPair::random();
+        let public = Signer::public_key(&p256_key);
+        let signature = Signer::sign(&p256_key, msg);
+        assert!(P256.verify(&public.0, msg, &signature));
+        assert!(!Secp256k1.verify(&public.0, msg, &signature));
+
+        let k256_key = k256::ecdsa::SigningKey::random(&mut OsRng);
+        let public = Signer::public_key(&k256_key);
+        let signature = Signer::sign(&k256_key, msg);
+        assert!(Secp256k1.verify(&public.0, msg, &signature));
+        assert!(!P256.verify(&public.0, msg, &signature));
+    }
+}
@@ -1,10 +1,20 @@
 use norn_common::types::{Transaction, TransactionBody, Address, Hash, PublicKey, TransactionType};
-use norn_common::types::PUBLIC_KEY_LENGTH;
-use crate::ecdsa::{KeyPair, verify};
+use crate::ecdsa::KeyPair;
+use crate::ethereum;
+use crate::scheme::{verifier_for, Signer};
 use sha2::{Sha256, Digest};
 use anyhow::Result;
+use lazy_static::lazy_static;
+use moka::sync::Cache;
+use std::sync::atomic::{AtomicU64, Ordering};
 use thiserror::Error;
-use p256::ecdsa::VerifyingKey;
+
+/// Senders of recently verified transactions kept by [`recover_sender`]
+pub const SENDER_CACHE_CAPACITY: u64 = 40960;
+
+lazy_static! {
+    static ref SENDERS: SenderCache = SenderCache::new(SENDER_CACHE_CAPACITY);
+}
 
 #[derive(Error, Debug)]
 pub enum TxError {
@@ -19,18 +29,27 @@ pub enum TxError {
 }
 
 pub struct TransactionSigner {
-    keypair: KeyPair,
+    signer: Box<dyn Signer>,
+    tx_type: TransactionType,
     address: Address,
     nonce: u64,
 }
 
 impl TransactionSigner {
+    /// Signer of native transactions
     pub fn new(keypair: KeyPair) -> Self {
-        let public_key = keypair.public_key();
-        let address = public_key_to_address(&public_key);
+        Self::with_signer(keypair, TransactionType::Native)
+    }
+
+    /// Signer of `tx_type` transactions
+    ///
+    /// `signer` must use the scheme of `tx_type`, see [`verifier_for`].
+    pub fn with_signer(signer: impl Signer + 'static, tx_type: TransactionType) -> Self {
+        let address = public_key_to_address(&signer.public_key());
 
         Self {
-            keypair,
+            signer: Box::new(signer),
+            tx_type,
             address,
             nonce: 0,
         }
@@ -61,11 +80,27 @@ impl TransactionSigner {
         gas: i64,
         expire: i64,
     ) -> Result<Transaction> {
-        let nonce = self.next_nonce() as i64;
         let timestamp = chrono::Utc::now().timestamp();
+        self.create_transaction_at(receiver, event, opt, state, data, gas, expire, timestamp)
+    }
+
+    /// Like `create_transaction`, but with an explicit creation timestamp
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_transaction_at(
+        &mut self,
+        receiver: Address,
+        event: Vec<u8>,
+        opt: Vec<u8>,
+        state: Vec<u8>,
+        data: Vec<u8>,
+        gas: i64,
+        expire: i64,
+        timestamp: i64,
+    ) -> Result<Transaction> {
+        let nonce = self.next_nonce() as i64;
 
         // Create unsigned transaction body
-        let mut unsigned_body = TransactionBody {
+        let unsigned_body = TransactionBody {
             hash: Hash::default(),
             address: self.address,
             receiver,
@@ -82,7 +117,7 @@ impl TransactionSigner {
             timestamp,
             public: PublicKey::default(),
             signature: Vec::new(),
-            tx_type: TransactionType::default(), // Native by default
+            tx_type: self.tx_type,
             chain_id: None,
             value: None,
             max_fee_per_gas: None,
@@ -91,50 +126,50 @@ impl TransactionSigner {
             gas_price: None,
         };
 
+        Ok(self.sign_body(unsigned_body))
+    }
+
+    /// Sign `body` as is, keeping its nonce
+    ///
+    /// Used to re-sign a modified transaction, such as a replacement that
+    /// pays a higher fee for the same nonce.
+    pub fn sign_body(&self, mut body: TransactionBody) -> Transaction {
+        body.signature = Vec::new();
+
         // Calculate hash of unsigned transaction
-        unsigned_body.hash = hash_transaction_body(&unsigned_body);
+        body.hash = hash_transaction_body(&body);
 
         // Set the public key
-        let encoded_point = self.keypair.public_key().to_encoded_point(true);
-        let public_key_bytes = encoded_point.as_bytes();
-        let mut public_key = PublicKey::default();
-        if public_key_bytes.len() == PUBLIC_KEY_LENGTH {
-            public_key.0.copy_from_slice(public_key_bytes);
-        }
-        unsigned_body.public = public_key;
+        body.public = self.signer.public_key();
 
         // Create message to sign
-        let message = create_signing_message(&unsigned_body);
+        let message = create_signing_message(&body);
 
         // Sign the transaction
-        let signature = self.keypair.sign(&message);
-        unsigned_body.signature = signature;
+        let signature = self.signer.sign(&message);
+        body.signature = signature;
 
         // Recalculate final hash with signature
-        unsigned_body.hash = hash_transaction_body(&unsigned_body);
+        body.hash = hash_transaction_body(&body);
 
-        Ok(Transaction {
-            body: unsigned_body,
-        })
+        Transaction { body }
     }
 }
 
 pub fn verify_transaction(tx: &Transaction) -> Result<(), TxError> {
-    // 1. Verify transaction hash
-    let calculated_hash = hash_transaction_body(&tx.body);
+    // 1. Verify transaction hash; Ethereum-signed ones are hashed as on
+    // Ethereum and checked with their signature
+    let calculated_hash = if ethereum::is_ethereum_signed(&tx.body) {
+        ethereum::transaction_hash(&tx.body)
+    } else {
+        hash_transaction_body(&tx.body)
+    };
     if calculated_hash != tx.body.hash {
         return Err(TxError::InvalidFormat);
     }
 
-    // 2. Verify signature
-    let message = create_signing_message(&tx.body);
-    let public_key_bytes = tx.body.public.0.to_vec();
-
-    if !verify(&public_key_bytes, &message, &tx.body.signature)
-        .map_err(|_| TxError::VerificationFailed)?
-    {
-        return Err(TxError::VerificationFailed);
-    }
+    // 2. Verify signature with the scheme of the transaction type
+    recover_sender(tx)?;
 
     // 3. Basic validation
     if tx.body.gas <= 0 {
@@ -148,6 +183,72 @@ pub fn verify_transaction(tx: &Transaction) -> Result<(), TxError> {
     Ok(())
 }
 
+/// Sender of `tx`, once its signature checks out
+///
+/// The same transaction often reaches several code paths (gossip, the pool,
+/// block validation), so senders are cached and each transaction is
+/// recovered once.
+pub fn recover_sender(tx: &Transaction) -> Result<Address, TxError> {
+    SENDERS.recover(tx)
+}
+
+/// Cache of verified transaction senders
+///
+/// Entries are keyed by the transaction hash together with the signature and
+/// the transaction type, neither of which the hash covers, so a tampered
+/// signature is never vouched for by an earlier successful recovery.
+pub struct SenderCache {
+    senders: Cache<Hash, Address>,
+    recoveries: AtomicU64,
+}
+
+impl SenderCache {
+    /// Keep the senders of at most `capacity` transactions
+    pub fn new(capacity: u64) -> Self {
+        Self { senders: Cache::new(capacity), recoveries: AtomicU64::new(0) }
+    }
+
+    /// Sender of `tx`, verifying its signature unless it was cached
+    pub fn recover(&self, tx: &Transaction) -> Result<Address, TxError> {
+        let key = sender_cache_key(&tx.body);
+        if let Some(sender) = self.senders.get(&key) {
+            return Ok(sender);
+        }
+
+        self.recoveries.fetch_add(1, Ordering::Relaxed);
+        if ethereum::is_ethereum_signed(&tx.body) {
+            let sender = ethereum::verify(&tx.body).ok_or(TxError::VerificationFailed)?;
+            self.senders.insert(key, sender);
+            return Ok(sender);
+        }
+
+        let message = create_signing_message(&tx.body);
+        if !verifier_for(tx.body.tx_type).verify(&tx.body.public.0, &message, &tx.body.signature) {
+            return Err(TxError::VerificationFailed);
+        }
+
+        let sender = public_key_to_address(&tx.body.public);
+        self.senders.insert(key, sender);
+        Ok(sender)
+    }
+
+    /// Number of signature verifications run, i.e. cache misses
+    pub fn recoveries(&self) -> u64 {
+        self.recoveries.load(Ordering::Relaxed)
+    }
+}
+
+fn sender_cache_key(body: &TransactionBody) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(body.hash.0);
+    hasher.update([body.tx_type as u8]);
+    hasher.update(&body.signature);
+
+    let mut key = Hash::default();
+    key.0.copy_from_slice(&hasher.finalize());
+    key
+}
+
 fn hash_transaction_body(body: &TransactionBody) -> Hash {
     let mut hasher = Sha256::new();
 
@@ -192,9 +293,9 @@ fn create_signing_message(body: &TransactionBody) -> Vec<u8> {
     hasher.finalize().to_vec()
 }
 
-fn public_key_to_address(public_key: &VerifyingKey) -> Address {
+fn public_key_to_address(public_key: &PublicKey) -> Address {
     let mut hasher = Sha256::new();
-    hasher.update(public_key.to_encoded_point(true).as_bytes());
+    hasher.update(public_key.0);
     let hash = hasher.finalize();
 
     // Take first 20 bytes as address
@@ -226,6 +327,28 @@ mod tests {
         assert!(verify_transaction(&tx).is_ok());
     }
 
+    #[test]
+    fn test_resigned_body_keeps_nonce() {
+        let mut signer = TransactionSigner::new(KeyPair::random());
+        let tx = signer.create_transaction(
+            Address::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            b"test_data".to_vec(),
+            1000,
+            chrono::Utc::now().timestamp() + 3600,
+        ).unwrap();
+
+        let mut body = tx.body.clone();
+        body.gas = 1100;
+        let replacement = signer.sign_body(body);
+
+        assert!(verify_transaction(&replacement).is_ok());
+        assert_eq!(replacement.body.nonce, tx.body.nonce);
+        assert_ne!(replacement.body.hash, tx.body.hash);
+    }
+
     #[test]
     fn test_invalid_transaction_verification() {
         let keypair = KeyPair::random();
@@ -247,4 +370,60 @@ mod tests {
 
         assert!(verify_transaction(&tx).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_transactions_verified_with_scheme_of_their_type() {
+        let create = |signer: &mut TransactionSigner| {
+            signer.create_transaction(
+                Address::default(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                1000,
+                chrono::Utc::now().timestamp() + 3600,
+            ).unwrap()
+        };
+
+        let signing_key = k256::ecdsa::SigningKey::random(&mut rand_core::OsRng);
+        let evm_tx = create(&mut TransactionSigner::with_signer(signing_key, TransactionType::EVM));
+        assert_eq!(evm_tx.body.tx_type, TransactionType::EVM);
+        assert!(verify_transaction(&evm_tx).is_ok());
+
+        let native_tx = create(&mut TransactionSigner::new(KeyPair::random()));
+        assert!(verify_transaction(&native_tx).is_ok());
+
+        // Under the other scheme, each signature is rejected
+        let mut tx = evm_tx;
+        tx.body.tx_type = TransactionType::Native;
+        assert!(matches!(verify_transaction(&tx), Err(TxError::VerificationFailed)));
+        let mut tx = native_tx;
+        tx.body.tx_type = TransactionType::EVM;
+        assert!(matches!(verify_transaction(&tx), Err(TxError::VerificationFailed)));
+    }
+
+    #[test]
+    fn test_sender_recovered_once_per_transaction() {
+        let mut signer = TransactionSigner::new(KeyPair::random());
+        let tx = signer.create_transaction(
+            Address::default(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            1000,
+            chrono::Utc::now().timestamp() + 3600,
+        ).unwrap();
+
+        let cache = SenderCache::new(16);
+        assert_eq!(cache.recover(&tx).unwrap(), signer.address());
+        assert_eq!(cache.recover(&tx).unwrap(), signer.address());
+        assert_eq!(cache.recoveries(), 1);
+
+        // A different signature for the same hash is checked again
+        let mut forged = tx.clone();
+        forged.body.signature[0] ^= 0xFF;
+        assert!(cache.recover(&forged).is_err());
+        assert_eq!(cache.recoveries(), 2);
+    }
+}
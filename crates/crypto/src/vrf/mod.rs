@@ -9,6 +9,7 @@ use curve25519_dalek::{
 };
 use rand::rngs::OsRng;
 use rand_core::RngCore;
+use rayon::prelude::*;
 use sha2::{Digest, Sha512};
 use std::collections::HashMap;
 use tracing::{info, warn};
@@ -17,6 +18,12 @@ use tracing::{info, warn};
 pub type Address = [u8; 20];
 pub type StakeAmount = u64;
 
+/// 启用并行 VRF 计算的最小验证者数量
+pub const PARALLEL_SELECTION_THRESHOLD: usize = 16;
+
+/// 默认纪元长度（轮次）
+pub const DEFAULT_EPOCH_LENGTH: u64 = 100;
+
 // 辅助函数：将地址转换为十六进制字符串
 fn address_to_hex(address: &Address) -> String {
     hex::encode(address)
@@ -72,6 +79,15 @@ impl VRFKeyPair {
     pub fn private_key_bytes(&self) -> [u8; 32] {
         self.private_key.to_bytes()
     }
+
+    /// 从压缩字节恢复公钥
+    pub fn public_key_from_bytes(bytes: &[u8]) -> Result<RistrettoPoint> {
+        let bytes: [u8; 32] = bytes.try_into()
+            .map_err(|_| anyhow!("Invalid public key length"))?;
+        curve25519_dalek::ristretto::CompressedRistretto(bytes)
+            .decompress()
+            .ok_or_else(|| anyhow!("Invalid public key point"))
+    }
 }
 
 /// VRF 输出
@@ -234,6 +250,18 @@ impl VRFCalculator {
         Ok(expected_output == output.output)
     }
 
+    /// 批量验证 VRF 输出和证明
+    ///
+    /// 每项为 (公钥, 消息, 输出)，并行验证，按输入顺序返回结果；验证出错视为无效。
+    pub fn verify_batch(items: &[(RistrettoPoint, Vec<u8>, VRFOutput)]) -> Vec<bool> {
+        items
+            .par_iter()
+            .map(|(public_key, message, output)| {
+                Self::verify(public_key, message, output).unwrap_or(false)
+            })
+            .collect()
+    }
+
     /// 将消息哈希到椭圆曲线上
     fn hash_to_curve(message: &[u8]) -> Result<RistrettoPoint> {
         let mut hasher = Sha512::new();
@@ -282,20 +310,87 @@ impl VRFCalculator {
     }
 }
 
+/// 纪元快照 - 某个纪元内冻结的验证者集合
+#[derive(Debug, Clone)]
+struct EpochSnapshot {
+    epoch: u64,
+    validators: HashMap<Address, StakeAmount>,
+    key_pairs: HashMap<Address, VRFKeyPair>,
+}
+
 /// VRF 选择器 - 用于基于权益的随机选择
+///
+/// 验证者的增删只修改登记集合；调用 `snapshot_epoch` 后，选择与验证均基于该纪元的快照，
+/// 登记集合的变化要到下一个纪元快照时才生效。未创建快照时直接使用登记集合。
 pub struct VRFSelector {
     /// 验证者权益映射
     validators: HashMap<Address, StakeAmount>,
     /// VRF 密钥映射
     key_pairs: HashMap<Address, VRFKeyPair>,
+    /// 纪元长度（轮次）
+    epoch_length: u64,
+    /// 当前纪元快照
+    snapshot: Option<EpochSnapshot>,
 }
 
 impl VRFSelector {
     /// 创建新的 VRF 选择器
     pub fn new() -> Self {
+        Self::with_epoch_length(DEFAULT_EPOCH_LENGTH)
+    }
+
+    /// 使用指定纪元长度创建 VRF 选择器
+    pub fn with_epoch_length(epoch_length: u64) -> Self {
         Self {
             validators: HashMap::new(),
             key_pairs: HashMap::new(),
+            epoch_length: epoch_length.max(1),
+            snapshot: None,
+        }
+    }
+
+    /// 获取纪元长度
+    pub fn epoch_length(&self) -> u64 {
+        self.epoch_length
+    }
+
+    /// 计算轮次所属纪元
+    pub fn epoch_for_round(&self, round: u64) -> u64 {
+        round / self.epoch_length
+    }
+
+    /// 获取当前快照的纪元
+    pub fn current_epoch(&self) -> Option<u64> {
+        self.snapshot.as_ref().map(|snapshot| snapshot.epoch)
+    }
+
+    /// 冻结当前登记的验证者集合，作为指定纪元的选择依据
+    pub fn snapshot_epoch(&mut self, epoch: u64) {
+        self.snapshot = Some(EpochSnapshot {
+            epoch,
+            validators: self.validators.clone(),
+            key_pairs: self.key_pairs.clone(),
+        });
+        info!("纪元 {} 验证者快照: {} 个验证者", epoch, self.validators.len());
+    }
+
+    /// 推进到指定轮次，跨越纪元边界时创建新快照
+    ///
+    /// 返回是否创建了新快照。
+    pub fn advance_to_round(&mut self, round: u64) -> bool {
+        let epoch = self.epoch_for_round(round);
+        if self.current_epoch() == Some(epoch) {
+            return false;
+        }
+        self.snapshot_epoch(epoch);
+        true
+    }
+
+    /// 当前参与选择的验证者集合
+    fn active_set(&self) -> (&HashMap<Address, StakeAmount>, &HashMap<Address, VRFKeyPair>) {
+        match &self.snapshot {
+            Some(snapshot) => (&snapshot.validators, &snapshot.key_pairs),
+            None => (&self.validators, &self.key_pairs),
         }
     }
 
@@ -314,58 +409,69 @@ impl VRFSelector {
     }
 
     /// 选择提议者
+    ///
+    /// 验证者数量达到 `PARALLEL_SELECTION_THRESHOLD` 时并行计算各验证者的 VRF 输出。
+    /// 分数相同时按地址排序，保证串行与并行结果一致。
     pub fn select_proposer(&self, message: &[u8], round: u64) -> Result<(Address, VRFOutput)> {
-        if self.validators.is_empty() {
+        let parallel = self.active_set().1.len() >= PARALLEL_SELECTION_THRESHOLD;
+        self.select_proposer_with(message, round, parallel)
+    }
+
+    fn select_proposer_with(
+        &self,
+        message: &[u8],
+        round: u64,
+        parallel: bool,
+    ) -> Result<(Address, VRFOutput)> {
+        let (validators, key_pairs) = self.active_set();
+        if validators.is_empty() {
             return Err(anyhow!("没有可用的验证者"));
         }
 
         // 计算总权益
-        let total_stake: StakeAmount = self.validators.values().sum();
+        let total_stake: StakeAmount = validators.values().sum();
         if total_stake == 0 {
             return Err(anyhow!("总权益为零"));
         }
 
+        // 按地址排序，使结果与 HashMap 迭代顺序无关
+        let mut key_pairs: Vec<(&Address, &VRFKeyPair)> = key_pairs.iter().collect();
+        key_pairs.sort_by(|a, b| a.0.cmp(b.0));
+
         // 为每个验证者生成 VRF 输出
-        let mut candidates = Vec::new();
-        for (address, key_pair) in &self.key_pairs {
+        let evaluate = |(address, key_pair): &(&Address, &VRFKeyPair)| {
             let vrf_message = Self::create_selection_message(message, round, address);
             match VRFCalculator::calculate(key_pair, &vrf_message) {
                 Ok(output) => {
-                    let stake = self.validators.get(address).unwrap();
-                    candidates.push((*address, output, *stake));
+                    let stake = *validators.get(*address)?;
+                    Some((**address, output, stake))
                 }
                 Err(e) => {
-                    warn!("验证者 {} VRF 计算失败: {:?}", address_to_hex(&address), e);
+                    warn!("验证者 {} VRF 计算失败: {:?}", address_to_hex(address), e);
+                    None
                 }
             }
-        }
+        };
+        let candidates: Vec<(Address, VRFOutput, StakeAmount)> = if parallel {
+            key_pairs.par_iter().filter_map(evaluate).collect()
+        } else {
+            key_pairs.iter().filter_map(evaluate).collect()
+        };
 
         if candidates.is_empty() {
             return Err(anyhow!("没有有效的候选者"));
         }
 
-        // 选择具有最低 VRF 输出的验证者（考虑权益权重）
-        let mut best_candidate = None;
-        let mut best_score = None;
-
-        for (address, output, stake) in candidates {
-            let score = Self::calculate_vrf_score(&output.output, stake, total_stake);
-            
-            match best_score {
-                None => {
-                    best_score = Some(score);
-                    best_candidate = Some((address, output));
-                }
-                Some(best) => {
-                    if score < best {
-                        best_score = Some(score);
-                        best_candidate = Some((address, output));
-                    }
-                }
-            }
-        }
-
-        best_candidate.ok_or_else(|| anyhow!("选择失败"))
+        // 选择具有最低 VRF 输出的验证者（考虑权益权重），分数相同取地址较小者
+        candidates
+            .into_iter()
+            .map(|(address, output, stake)| {
+                let score = Self::calculate_vrf_score(&output.output, stake, total_stake);
+                (score, address, output)
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)))
+            .map(|(_, address, output)| (address, output))
+            .ok_or_else(|| anyhow!("选择失败"))
     }
 
     /// 验证提议者选择
@@ -376,13 +482,15 @@ impl VRFSelector {
         round: u64,
         output: &VRFOutput,
     ) -> Result<bool> {
+        let (validators, key_pairs) = self.active_set();
+
         // 检查验证者是否存在
-        let public_key = match self.key_pairs.get(&proposer) {
+        let public_key = match key_pairs.get(&proposer) {
             Some(key_pair) => key_pair.public_key,
             None => return Ok(false),
         };
 
-        let stake = match self.validators.get(&proposer) {
+        let stake = match validators.get(&proposer) {
             Some(stake) => *stake,
             None => return Ok(false),
         };
@@ -396,7 +504,7 @@ impl VRFSelector {
         }
 
         // 验证选择是否有效（这里可以实现更复杂的验证逻辑）
-        let total_stake: StakeAmount = self.validators.values().sum();
+        let total_stake: StakeAmount = validators.values().sum();
         let score = Self::calculate_vrf_score(&output.output, stake, total_stake);
         
         // 这里可以实现阈值检查等
@@ -445,6 +553,11 @@ impl VRFSelector {
     pub fn validator_count(&self) -> usize {
         self.validators.len()
     }
+
+    /// 获取验证者总权益
+    pub fn total_stake(&self) -> StakeAmount {
+        self.validators.values().sum()
+    }
 }
 
 impl Default for VRFSelector {
@@ -499,6 +612,28 @@ mod tests {
         assert!(!verified_wrong);
     }
 
+    #[test]
+    fn test_vrf_verify_batch() {
+        let mut items = Vec::new();
+        for i in 0..8u8 {
+            let key_pair = VRFKeyPair::from_seed(&[i]);
+            let message = vec![i; 16];
+            let output = VRFCalculator::calculate(&key_pair, &message).unwrap();
+            items.push((key_pair.public_key, message, output));
+        }
+
+        // 篡改输出、消息和公钥
+        items[1].2.output[0] ^= 0xFF;
+        items[4].1.push(0);
+        items[6].0 = VRFKeyPair::from_seed(b"other").public_key;
+
+        let results = VRFCalculator::verify_batch(&items);
+        let expected: Vec<bool> = (0..8).map(|i| ![1, 4, 6].contains(&i)).collect();
+        assert_eq!(results, expected);
+
+        assert!(VRFCalculator::verify_batch(&[]).is_empty());
+    }
+
     #[test]
     fn test_vrf_proof_serialization() {
         let key_pair = VRFKeyPair::generate();
@@ -543,6 +678,68 @@ mod tests {
         assert!(proposer == addr1 || proposer == addr2);
     }
 
+    #[test]
+    fn test_parallel_selection_matches_serial() {
+        let mut selector = VRFSelector::new();
+        for i in 0..64u32 {
+            let mut address = [0u8; 20];
+            address[..4].copy_from_slice(&i.to_be_bytes());
+            let key_pair = VRFKeyPair::from_seed(&i.to_be_bytes());
+            selector.add_validator(address, 1000 + (i as u64 % 7) * 100, key_pair);
+        }
+
+        for round in 0..2 {
+            let message = b"parallel_selection_test";
+            let (serial_addr, serial_out) = selector.select_proposer_with(message, round, false).unwrap();
+            let (parallel_addr, parallel_out) = selector.select_proposer_with(message, round, true).unwrap();
+
+            assert_eq!(serial_addr, parallel_addr);
+            // 证明含随机数，只比较确定性的 VRF 输出
+            assert_eq!(serial_out.output, parallel_out.output);
+
+            let (default_addr, _) = selector.select_proposer(message, round).unwrap();
+            assert_eq!(default_addr, serial_addr);
+        }
+    }
+
+    #[test]
+    fn test_epoch_snapshot_defers_new_validator() {
+        let mut selector = VRFSelector::with_epoch_length(10);
+        let addr1 = Address::from([1u8; 20]);
+        selector.add_validator(addr1, 1000, VRFKeyPair::from_seed(b"validator-1"));
+        assert!(selector.advance_to_round(0));
+        assert_eq!(selector.current_epoch(), Some(0));
+
+        // 纪元中途加入的验证者
+        let addr2 = Address::from([2u8; 20]);
+        let key2 = VRFKeyPair::from_seed(b"validator-2");
+        selector.add_validator(addr2, 1_000_000, key2.clone());
+        assert_eq!(selector.validator_count(), 2);
+
+        let message = b"epoch_snapshot_test";
+        for round in 1..10 {
+            assert!(!selector.advance_to_round(round));
+            let (proposer, _) = selector.select_proposer(message, round).unwrap();
+            assert_eq!(proposer, addr1);
+
+            // 新验证者的合法 VRF 输出在本纪元内不被接受
+            let vrf_message = VRFSelector::create_selection_message(message, round, &addr2);
+            let output = VRFCalculator::calculate(&key2, &vrf_message).unwrap();
+            assert!(!selector.verify_selection(addr2, message, round, &output).unwrap());
+        }
+
+        // 进入下一纪元后新验证者参与选择
+        assert!(selector.advance_to_round(10));
+        assert_eq!(selector.current_epoch(), Some(1));
+        let vrf_message = VRFSelector::create_selection_message(message, 10, &addr2);
+        let output = VRFCalculator::calculate(&key2, &vrf_message).unwrap();
+        assert!(selector.verify_selection(addr2, message, 10, &output).unwrap());
+        let proposers: std::collections::HashSet<Address> = (10..20)
+            .map(|round| selector.select_proposer(message, round).unwrap().0)
+            .collect();
+        assert!(proposers.contains(&addr2));
+    }
+
     #[test]
     fn test_vrf_deterministic_output() {
         let key_pair = VRFKeyPair::generate();
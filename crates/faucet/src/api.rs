@@ -1,23 +1,50 @@
 //! HTTP API for faucet service
 
-use super::service::{DispenseResponse, FaucetService, FaucetStatus};
-use super::error::FaucetResult;
+use super::service::{DispenseJob, FaucetService, FaucetStatus, SignedRequest};
+use super::error::{FaucetError, FaucetResult};
 use axum::{
-    extract::{ConnectInfo, State},
-    http::HeaderMap,
+    extract::{ConnectInfo, Path, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
     Json,
 };
+use norn_common::types::Address;
+use norn_common::utils::address::validate_checksum;
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 /// Dispense request
 #[derive(Debug, Deserialize)]
 pub struct DispenseRequest {
     pub address: String,
+    /// Configured token to dispense, the native coin if absent
+    pub asset: Option<String>,
     pub captcha: Option<String>,
+    /// Unix timestamp the request was signed at
+    pub timestamp: Option<i64>,
+    /// Hex `personal_sign` signature over `SignedRequest::signing_message`
+    pub signature: Option<String>,
+}
+
+impl DispenseRequest {
+    /// Signature fields of the request, if it is signed
+    fn signed_request(&self) -> FaucetResult<Option<SignedRequest>> {
+        let (timestamp, signature) = match (self.timestamp, &self.signature) {
+            (None, None) => return Ok(None),
+            (Some(timestamp), Some(signature)) => (timestamp, signature),
+            _ => {
+                return Err(FaucetError::InvalidSignature(
+                    "timestamp and signature must be given together".to_string(),
+                ))
+            }
+        };
+        let signature = hex::decode(signature.trim_start_matches("0x"))
+            .map_err(|_| FaucetError::InvalidSignature("Invalid signature hex".to_string()))?;
+
+        Ok(Some(SignedRequest { timestamp, signature }))
+    }
 }
 
 /// API error response
@@ -44,27 +71,19 @@ pub async fn dispense_handler(
 ) -> impl IntoResponse {
     info!("Dispense request from {}: address={}", addr, request.address);
 
-    // Parse address
-    let address_bytes = if request.address.starts_with("0x") {
-        hex::decode(&request.address[2..])
-    } else {
-        hex::decode(&request.address)
+    let address = match parse_address(&request.address) {
+        Ok(address) => address,
+        Err(e) => return e.into_response(),
     };
 
-    let address_bytes = match address_bytes {
-        Ok(bytes) if bytes.len() == 20 => bytes,
-        _ => {
-            return Json(ErrorResponse {
-                error: "INVALID_ADDRESS".to_string(),
-                message: "Invalid address format".to_string(),
-                timestamp: chrono::Utc::now().to_rfc3339(),
-            })
-            .into_response();
-        }
+    let signed = match request.signed_request() {
+        Ok(signed) => signed,
+        Err(e) => return e.into_response(),
     };
-
-    let mut addr_array = [0u8; 20];
-    addr_array.copy_from_slice(&address_bytes);
+    if let Err(e) = service.verify_request_signature(&address, request.asset.as_deref(), signed.as_ref()) {
+        warn!("Rejected dispense request for {}: {}", request.address, e);
+        return e.into_response();
+    }
 
     let user_agent = headers
         .get("user-agent")
@@ -74,9 +93,27 @@ pub async fn dispense_handler(
 
     let ip_addr = addr.ip();
 
+    // Hand the request to the workers, the client polls for the outcome
+    if service.queues_dispenses() {
+        return match service.enqueue_dispense(address, request.asset.as_deref(), ip_addr, user_agent) {
+            Ok(job) => (
+                StatusCode::ACCEPTED,
+                Json(SuccessResponse {
+                    data: job,
+                    timestamp: chrono::Utc::now().to_rfc3339(),
+                }),
+            )
+                .into_response(),
+            Err(e) => {
+                warn!("Could not queue dispense request: {:?}", e);
+                e.into_response()
+            }
+        };
+    }
+
     // Call service
     match service
-        .dispense(norn_common::types::Address(addr_array), ip_addr, user_agent)
+        .dispense_asset(address, request.asset.as_deref(), ip_addr, user_agent)
         .await
     {
         Ok(response) => Json(SuccessResponse {
@@ -91,6 +128,19 @@ pub async fn dispense_handler(
     }
 }
 
+/// Parse a hex address, checking its EIP-55 checksum if it is mixed-case
+pub fn parse_address(address: &str) -> FaucetResult<Address> {
+    let digits = address.strip_prefix("0x").unwrap_or(address);
+    let mut bytes = [0u8; 20];
+    hex::decode_to_slice(digits, &mut bytes)
+        .map_err(|_| FaucetError::InvalidAddress("Invalid address format".to_string()))?;
+
+    if !validate_checksum(digits) {
+        return Err(FaucetError::InvalidAddress("Invalid address checksum".to_string()));
+    }
+    Ok(Address(bytes))
+}
+
 /// Status handler
 pub async fn status_handler(
     State(service): State<Arc<FaucetService>>,
@@ -102,6 +152,18 @@ pub async fn status_handler(
     }))
 }
 
+/// Queued dispense request status handler
+pub async fn dispense_job_handler(
+    State(service): State<Arc<FaucetService>>,
+    Path(id): Path<String>,
+) -> FaucetResult<Json<SuccessResponse<DispenseJob>>> {
+    let job = service.dispense_job(&id)?;
+    Ok(Json(SuccessResponse {
+        data: job,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }))
+}
+
 /// Health check handler
 pub async fn health_handler() -> impl IntoResponse {
     Json(serde_json::json!({
@@ -118,9 +180,27 @@ pub async fn root_handler() -> impl IntoResponse {
         "description": "Production-grade faucet service for Norn blockchain",
         "endpoints": {
             "POST /api/dispense": "Request tokens",
+            "GET /api/dispense/:id": "Get a queued request",
             "GET /api/status": "Get faucet status",
             "GET /health": "Health check",
             "GET /metrics": "Prometheus metrics"
         }
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_address_checksum() {
+        let expected = Address(hex::decode("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap().try_into().unwrap());
+
+        assert_eq!(parse_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap(), expected);
+        assert_eq!(parse_address("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap(), expected);
+
+        let bad = parse_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD");
+        assert!(matches!(bad, Err(FaucetError::InvalidAddress(msg)) if msg.contains("checksum")));
+        assert!(matches!(parse_address("0x1234"), Err(FaucetError::InvalidAddress(_))));
+    }
+}
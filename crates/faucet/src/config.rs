@@ -1,8 +1,22 @@
 //! Faucet configuration
 
+use crate::rpc::{CircuitBreakerConfig, RetryPolicy};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+/// ERC-20 token the faucet dispenses besides the native coin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaucetAsset {
+    /// Name requests select the token by
+    pub name: String,
+
+    /// Token contract address (hex)
+    pub contract: String,
+
+    /// Amount to dispense per request, in the token's smallest unit
+    pub amount: String,
+}
+
 /// Faucet service configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FaucetConfig {
@@ -18,6 +32,19 @@ pub struct FaucetConfig {
     /// Amount to dispense per request (in wei)
     pub dispense_amount: String,
 
+    /// Amount tiers as `(prior_count, amount in wei)` pairs
+    ///
+    /// An address that already received `n` distributions gets the amount of
+    /// the tier with the largest `prior_count <= n`. Requests matching no tier
+    /// get `dispense_amount`.
+    pub dispense_tiers: Vec<(u64, String)>,
+
+    /// Tokens dispensed on request besides the native coin
+    pub assets: Vec<FaucetAsset>,
+
+    /// Gas limit for token transfers
+    pub token_gas_limit: u64,
+
     /// Minimum balance required (in wei)
     pub min_balance: String,
 
@@ -30,9 +57,33 @@ pub struct FaucetConfig {
     /// Cooldown period between requests for same address (seconds)
     pub address_cooldown_secs: u64,
 
-    /// Maximum amount per address total (in wei)
+    /// Maximum amount per address (in wei) within `quota_window_secs`
     pub max_amount_per_address: String,
 
+    /// Window the per-address maximum applies over (seconds); older
+    /// distributions no longer count towards it
+    pub quota_window_secs: u64,
+
+    /// Most the faucet dispenses over any hour across all addresses (in wei),
+    /// unlimited if unset
+    pub global_max_dispense_per_hour_wei: Option<String>,
+
+    /// Refuse to fund addresses holding contract code
+    pub reject_contract_destinations: bool,
+
+    /// Worker tasks submitting queued dispense requests; requests are
+    /// dispensed synchronously if zero
+    pub dispense_workers: usize,
+
+    /// Dispense requests that may wait in the queue before new ones are refused
+    pub dispense_queue_capacity: usize,
+
+    /// Reject requests that aren't signed by the requesting address
+    pub require_signed_requests: bool,
+
+    /// How old a signed request's timestamp may be (seconds)
+    pub signed_request_window_secs: u64,
+
     /// Enable captcha verification
     pub captcha_enabled: bool,
 
@@ -68,6 +119,24 @@ pub struct FaucetConfig {
 
     /// Gas limit for transactions
     pub gas_limit: u64,
+
+    /// Maximum retries for transient RPC failures
+    pub rpc_max_retries: u32,
+
+    /// Base delay before the first RPC retry (milliseconds), doubled per retry
+    pub rpc_retry_base_delay_ms: u64,
+
+    /// Consecutive RPC failures before the circuit breaker opens
+    pub rpc_circuit_failure_threshold: u32,
+
+    /// How long the circuit breaker stays open before probing recovery (seconds)
+    pub rpc_circuit_cooldown_secs: u64,
+
+    /// Interval between polls of the node's latest block (seconds)
+    pub chain_tip_poll_secs: u64,
+
+    /// Age of the latest block after which the faucet reports itself degraded (seconds)
+    pub chain_tip_stale_secs: u64,
 }
 
 impl Default for FaucetConfig {
@@ -78,11 +147,21 @@ impl Default for FaucetConfig {
             private_key: std::env::var("FAUCET_PRIVATE_KEY")
                 .unwrap_or_else(|_| "0x0000000000000000000000000000000000000000000000000000000000000001".to_string()),
             dispense_amount: "1000000000000000000000".to_string(), // 1000 ETH
+            dispense_tiers: Vec::new(),
+            assets: Vec::new(),
+            token_gas_limit: 100_000,
             min_balance: "100000000000000000000".to_string(), // 100 ETH
             max_requests_per_window: 3,
             rate_limit_window_secs: 3600, // 1 hour
             address_cooldown_secs: 86400, // 24 hours
             max_amount_per_address: "5000000000000000000000".to_string(), // 5000 ETH
+            quota_window_secs: 604800, // 7 days
+            global_max_dispense_per_hour_wei: None,
+            reject_contract_destinations: false,
+            dispense_workers: 0,
+            dispense_queue_capacity: 1000,
+            require_signed_requests: false,
+            signed_request_window_secs: 300,
             captcha_enabled: false,
             captcha_secret: None,
             db_path: "./faucet_data".to_string(),
@@ -95,6 +174,12 @@ impl Default for FaucetConfig {
             auto_refill_amount: "1000000000000000000000".to_string(), // 1000 ETH
             gas_price: "1000000000".to_string(), // 1 Gwei
             gas_limit: 21000,
+            rpc_max_retries: 3,
+            rpc_retry_base_delay_ms: 200,
+            rpc_circuit_failure_threshold: 5,
+            rpc_circuit_cooldown_secs: 30,
+            chain_tip_poll_secs: 10,
+            chain_tip_stale_secs: 120,
         }
     }
 }
@@ -120,6 +205,42 @@ impl FaucetConfig {
             config.dispense_amount = amount;
         }
 
+        // Comma-separated `prior_count:amount` pairs, e.g. "0:1000,1:100"
+        if let Ok(tiers) = std::env::var("FAUCET_DISPENSE_TIERS") {
+            let parsed: Option<Vec<(u64, String)>> = tiers
+                .split(',')
+                .map(|tier| {
+                    let (count, amount) = tier.trim().split_once(':')?;
+                    Some((count.parse().ok()?, amount.to_string()))
+                })
+                .collect();
+            if let Some(parsed) = parsed {
+                config.dispense_tiers = parsed;
+            }
+        }
+
+        // Comma-separated `name:contract:amount` triples, e.g. "TST:0x1234...:1000"
+        if let Ok(assets) = std::env::var("FAUCET_ASSETS") {
+            let parsed: Option<Vec<FaucetAsset>> = assets
+                .split(',')
+                .map(|asset| {
+                    let mut parts = asset.trim().splitn(3, ':');
+                    Some(FaucetAsset {
+                        name: parts.next()?.to_string(),
+                        contract: parts.next()?.to_string(),
+                        amount: parts.next()?.to_string(),
+                    })
+                })
+                .collect();
+            if let Some(parsed) = parsed {
+                config.assets = parsed;
+            }
+        }
+
+        if let Ok(gas_limit) = std::env::var("FAUCET_TOKEN_GAS_LIMIT") {
+            config.token_gas_limit = gas_limit.parse().unwrap_or(config.token_gas_limit);
+        }
+
         if let Ok(min_bal) = std::env::var("FAUCET_MIN_BALANCE") {
             config.min_balance = min_bal;
         }
@@ -140,6 +261,34 @@ impl FaucetConfig {
             config.max_amount_per_address = max_amount;
         }
 
+        if let Ok(window) = std::env::var("FAUCET_QUOTA_WINDOW") {
+            config.quota_window_secs = window.parse().unwrap_or(config.quota_window_secs);
+        }
+
+        if let Ok(limit) = std::env::var("FAUCET_GLOBAL_MAX_DISPENSE_PER_HOUR") {
+            config.global_max_dispense_per_hour_wei = Some(limit);
+        }
+
+        if let Ok(reject) = std::env::var("FAUCET_REJECT_CONTRACT_DESTINATIONS") {
+            config.reject_contract_destinations = reject.to_lowercase() == "true";
+        }
+
+        if let Ok(workers) = std::env::var("FAUCET_DISPENSE_WORKERS") {
+            config.dispense_workers = workers.parse().unwrap_or(config.dispense_workers);
+        }
+
+        if let Ok(capacity) = std::env::var("FAUCET_DISPENSE_QUEUE_CAPACITY") {
+            config.dispense_queue_capacity = capacity.parse().unwrap_or(config.dispense_queue_capacity);
+        }
+
+        if let Ok(required) = std::env::var("FAUCET_REQUIRE_SIGNED_REQUESTS") {
+            config.require_signed_requests = required.to_lowercase() == "true";
+        }
+
+        if let Ok(window) = std::env::var("FAUCET_SIGNED_REQUEST_WINDOW") {
+            config.signed_request_window_secs = window.parse().unwrap_or(config.signed_request_window_secs);
+        }
+
         if let Ok(enabled) = std::env::var("FAUCET_CAPTCHA_ENABLED") {
             config.captcha_enabled = enabled.to_lowercase() == "true";
         }
@@ -156,14 +305,69 @@ impl FaucetConfig {
             config.metrics_port = metrics_port.parse().unwrap_or(config.metrics_port);
         }
 
+        if let Ok(retries) = std::env::var("FAUCET_RPC_MAX_RETRIES") {
+            config.rpc_max_retries = retries.parse().unwrap_or(config.rpc_max_retries);
+        }
+
+        if let Ok(delay) = std::env::var("FAUCET_RPC_RETRY_BASE_DELAY_MS") {
+            config.rpc_retry_base_delay_ms = delay.parse().unwrap_or(config.rpc_retry_base_delay_ms);
+        }
+
+        if let Ok(threshold) = std::env::var("FAUCET_RPC_CIRCUIT_THRESHOLD") {
+            config.rpc_circuit_failure_threshold =
+                threshold.parse().unwrap_or(config.rpc_circuit_failure_threshold);
+        }
+
+        if let Ok(cooldown) = std::env::var("FAUCET_RPC_CIRCUIT_COOLDOWN") {
+            config.rpc_circuit_cooldown_secs = cooldown.parse().unwrap_or(config.rpc_circuit_cooldown_secs);
+        }
+
+        if let Ok(interval) = std::env::var("FAUCET_CHAIN_TIP_POLL_SECS") {
+            config.chain_tip_poll_secs = interval.parse().unwrap_or(config.chain_tip_poll_secs);
+        }
+
+        if let Ok(stale) = std::env::var("FAUCET_CHAIN_TIP_STALE_SECS") {
+            config.chain_tip_stale_secs = stale.parse().unwrap_or(config.chain_tip_stale_secs);
+        }
+
         config
     }
 
+    /// Amount to dispense to an address with `prior_count` earlier distributions
+    pub fn dispense_amount_for(&self, prior_count: u64) -> &str {
+        self.dispense_tiers
+            .iter()
+            .filter(|(min_count, _)| *min_count <= prior_count)
+            .max_by_key(|(min_count, _)| *min_count)
+            .map_or(&self.dispense_amount, |(_, amount)| amount)
+    }
+
+    /// Token configured under `name`
+    pub fn asset(&self, name: &str) -> Option<&FaucetAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+
     /// Get rate limit duration
     pub fn rate_limit_duration(&self) -> Duration {
         Duration::from_secs(self.rate_limit_window_secs)
     }
 
+    /// Get RPC retry policy
+    pub fn rpc_retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: self.rpc_max_retries,
+            base_delay: Duration::from_millis(self.rpc_retry_base_delay_ms),
+        }
+    }
+
+    /// Get RPC circuit breaker settings
+    pub fn rpc_circuit_breaker(&self) -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold: self.rpc_circuit_failure_threshold,
+            cooldown: Duration::from_secs(self.rpc_circuit_cooldown_secs),
+        }
+    }
+
     /// Get address cooldown duration
     pub fn address_cooldown_duration(&self) -> Duration {
         Duration::from_secs(self.address_cooldown_secs)
@@ -22,8 +22,70 @@ pub struct DistributionRecord {
     pub ip_address: String,
     /// User agent
     pub user_agent: String,
+    /// Faucet account nonce used for the transaction (absent on records migrated from v1)
+    pub nonce: Option<u64>,
+    /// Name of the token dispensed, `None` for the native coin
+    pub asset: Option<String>,
 }
 
+/// Distribution record layout used by schema v2
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DistributionRecordV2 {
+    address: String,
+    amount: String,
+    tx_hash: String,
+    timestamp: i64,
+    ip_address: String,
+    user_agent: String,
+    nonce: Option<u64>,
+}
+
+impl From<DistributionRecordV2> for DistributionRecord {
+    fn from(v2: DistributionRecordV2) -> Self {
+        Self {
+            address: v2.address,
+            amount: v2.amount,
+            tx_hash: v2.tx_hash,
+            timestamp: v2.timestamp,
+            ip_address: v2.ip_address,
+            user_agent: v2.user_agent,
+            nonce: v2.nonce,
+            asset: None,
+        }
+    }
+}
+
+/// Distribution record layout used by schema v1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DistributionRecordV1 {
+    address: String,
+    amount: String,
+    tx_hash: String,
+    timestamp: i64,
+    ip_address: String,
+    user_agent: String,
+}
+
+impl From<DistributionRecordV1> for DistributionRecordV2 {
+    fn from(v1: DistributionRecordV1) -> Self {
+        Self {
+            address: v1.address,
+            amount: v1.amount,
+            tx_hash: v1.tx_hash,
+            timestamp: v1.timestamp,
+            ip_address: v1.ip_address,
+            user_agent: v1.user_agent,
+            nonce: None,
+        }
+    }
+}
+
+/// Current on-disk schema version
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// Key of the schema version in the default tree
+const SCHEMA_VERSION_KEY: &[u8] = b"schema_version";
+
 impl DistributionRecord {
     pub fn new(
         address: String,
@@ -39,6 +101,29 @@ impl DistributionRecord {
             timestamp: Utc::now().timestamp(),
             ip_address,
             user_agent,
+            nonce: None,
+            asset: None,
+        }
+    }
+
+    /// Attach the faucet nonce used for the transaction
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Mark the record as a distribution of the token `asset`
+    pub fn with_asset(mut self, asset: impl Into<String>) -> Self {
+        self.asset = Some(asset.into());
+        self
+    }
+
+    /// Key in the distributions tree; token distributions are suffixed with
+    /// the asset so they don't collide with a native one in the same second
+    fn key(&self) -> String {
+        match &self.asset {
+            Some(asset) => format!("{}:{}:{}", self.address, self.timestamp, asset),
+            None => format!("{}:{}", self.address, self.timestamp),
         }
     }
 
@@ -69,21 +154,149 @@ impl FaucetDatabase {
             .open()
             .map_err(FaucetError::DatabaseError)?;
 
+        Self::from_db(db)
+    }
+
+    /// Wrap an already opened sled database, migrating it to the current schema
+    fn from_db(db: Db) -> FaucetResult<Self> {
         let distributions = db.open_tree("distributions").map_err(FaucetError::DatabaseError)?;
         let address_tracker = db.open_tree("address_tracker").map_err(FaucetError::DatabaseError)?;
         let ip_tracker = db.open_tree("ip_tracker").map_err(FaucetError::DatabaseError)?;
 
-        Ok(Self {
+        let database = Self {
             db: Arc::new(db),
             distributions,
             address_tracker,
             ip_tracker,
-        })
+        };
+        database.migrate()?;
+
+        Ok(database)
+    }
+
+    /// Schema version recorded in the database, if any
+    pub fn schema_version(&self) -> FaucetResult<Option<u32>> {
+        match self.db.get(SCHEMA_VERSION_KEY).map_err(FaucetError::DatabaseError)? {
+            Some(bytes) => {
+                let version = u32::from_be_bytes(bytes.as_ref().try_into().map_err(|_| {
+                    FaucetError::InternalError("Invalid schema version format".to_string())
+                })?);
+                Ok(Some(version))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn set_schema_version(&self, version: u32) -> FaucetResult<()> {
+        self.db
+            .insert(SCHEMA_VERSION_KEY, &version.to_be_bytes())
+            .map_err(FaucetError::DatabaseError)?;
+        self.db.flush().map_err(FaucetError::DatabaseError)?;
+        Ok(())
+    }
+
+    /// Upgrade stored records to the current schema, returning the resulting version
+    ///
+    /// Databases created before versioning was introduced have no version key
+    /// and are treated as v1. Running the migrator again is a no-op.
+    pub fn migrate(&self) -> FaucetResult<u32> {
+        let mut version = match self.schema_version()? {
+            Some(version) => version,
+            None if self.distributions.is_empty() => SCHEMA_VERSION,
+            None => 1,
+        };
+
+        if version > SCHEMA_VERSION {
+            return Err(FaucetError::InternalError(format!(
+                "Database schema v{} is newer than supported v{}",
+                version, SCHEMA_VERSION
+            )));
+        }
+
+        while version < SCHEMA_VERSION {
+            match version {
+                1 => self.migrate_v1_to_v2()?,
+                2 => self.migrate_v2_to_v3()?,
+                _ => unreachable!("no migration from schema v{}", version),
+            }
+            version += 1;
+            info!("Migrated faucet database to schema v{}", version);
+        }
+
+        self.set_schema_version(version)?;
+        Ok(version)
+    }
+
+    /// v2 adds `DistributionRecord::nonce`
+    fn migrate_v1_to_v2(&self) -> FaucetResult<()> {
+        let mut batch = sled::Batch::default();
+        let mut migrated = 0;
+
+        for item in self.distributions.iter() {
+            let (key, value) = item.map_err(FaucetError::DatabaseError)?;
+
+            // Records already in the new layout are left alone, so an
+            // interrupted migration can simply be re-run
+            if bincode::deserialize::<DistributionRecordV2>(&value).is_ok() {
+                continue;
+            }
+
+            let record: DistributionRecordV2 = bincode::deserialize::<DistributionRecordV1>(&value)
+                .map_err(|e| FaucetError::InternalError(format!("Corrupt v1 record: {}", e)))?
+                .into();
+            let value = bincode::serialize(&record)
+                .map_err(|e| FaucetError::InternalError(e.to_string()))?;
+            batch.insert(key, value);
+            migrated += 1;
+        }
+
+        self.distributions
+            .apply_batch(batch)
+            .map_err(FaucetError::DatabaseError)?;
+
+        debug!("Migrated {} distribution records to v2", migrated);
+        Ok(())
+    }
+
+    /// v3 adds `DistributionRecord::asset`
+    fn migrate_v2_to_v3(&self) -> FaucetResult<()> {
+        let mut batch = sled::Batch::default();
+        let mut migrated = 0;
+
+        for item in self.distributions.iter() {
+            let (key, value) = item.map_err(FaucetError::DatabaseError)?;
+            if bincode::deserialize::<DistributionRecord>(&value).is_ok() {
+                continue;
+            }
+
+            let record: DistributionRecord = bincode::deserialize::<DistributionRecordV2>(&value)
+                .map_err(|e| FaucetError::InternalError(format!("Corrupt v2 record: {}", e)))?
+                .into();
+            let value = bincode::serialize(&record)
+                .map_err(|e| FaucetError::InternalError(e.to_string()))?;
+            batch.insert(key, value);
+            migrated += 1;
+        }
+
+        self.distributions
+            .apply_batch(batch)
+            .map_err(FaucetError::DatabaseError)?;
+
+        debug!("Migrated {} distribution records to v3", migrated);
+        Ok(())
+    }
+
+    /// Key of the last request time of `address` for `asset` in the address tracker
+    fn tracker_key(address: &str, asset: Option<&str>) -> String {
+        match asset {
+            Some(asset) => format!("{}/{}", address, asset),
+            None => address.to_string(),
+        }
     }
 
     /// Record a distribution
     pub fn add_distribution(&self, record: DistributionRecord) -> FaucetResult<()> {
-        let key = format!("{}:{}", record.address, record.timestamp);
+        let key = record.key();
         let value = bincode::serialize(&record)
             .map_err(|e| FaucetError::InternalError(e.to_string()))?;
 
@@ -94,7 +307,7 @@ impl FaucetDatabase {
         // Update address tracker
         self.address_tracker
             .insert(
-                record.address.as_bytes(),
+                Self::tracker_key(&record.address, record.asset.as_deref()).as_bytes(),
                 IVec::from(record.timestamp.to_be_bytes().as_slice()),
             )
             .map_err(FaucetError::DatabaseError)?;
@@ -104,11 +317,12 @@ impl FaucetDatabase {
         Ok(())
     }
 
-    /// Get last request timestamp for an address
-    pub fn get_last_request_time(&self, address: &str) -> FaucetResult<Option<i64>> {
+    /// Get last request timestamp for an address, for the token `asset` or
+    /// the native coin
+    pub fn get_last_request_time(&self, address: &str, asset: Option<&str>) -> FaucetResult<Option<i64>> {
         match self
             .address_tracker
-            .get(address.as_bytes())
+            .get(Self::tracker_key(address, asset).as_bytes())
             .map_err(FaucetError::DatabaseError)?
         {
             Some(bytes) => {
@@ -123,7 +337,7 @@ impl FaucetDatabase {
         }
     }
 
-    /// Get total amount dispensed to an address
+    /// Get total amount of the native coin dispensed to an address
     pub fn get_total_amount_for_address(&self, address: &str) -> FaucetResult<u128> {
         let mut total = 0u128;
 
@@ -132,6 +346,9 @@ impl FaucetDatabase {
             let (_, value) = item.map_err(FaucetError::DatabaseError)?;
             let record: DistributionRecord = bincode::deserialize(&value)
                 .map_err(|e| FaucetError::InternalError(e.to_string()))?;
+            if record.asset.is_some() {
+                continue;
+            }
 
             total += record
                 .amount
@@ -144,6 +361,46 @@ impl FaucetDatabase {
         Ok(total)
     }
 
+    /// Native coin distributions to an address at or after `since`, as
+    /// `(timestamp, amount)` pairs, oldest first
+    pub fn get_amounts_for_address_since(&self, address: &str, since: i64) -> FaucetResult<Vec<(i64, u128)>> {
+        let mut amounts = Vec::new();
+
+        for item in self.distributions.scan_prefix(format!("{}:", address)) {
+            let (_, value) = item.map_err(FaucetError::DatabaseError)?;
+            let record: DistributionRecord = bincode::deserialize(&value)
+                .map_err(|e| FaucetError::InternalError(e.to_string()))?;
+            if record.asset.is_some() || record.timestamp < since {
+                continue;
+            }
+
+            amounts.push((record.timestamp, record.amount.parse::<u128>().unwrap_or(0)));
+        }
+
+        amounts.sort_by_key(|(timestamp, _)| *timestamp);
+        Ok(amounts)
+    }
+
+    /// Native coin distributions to any address at or after `since`, as
+    /// `(timestamp, amount)` pairs, oldest first
+    pub fn get_amounts_since(&self, since: i64) -> FaucetResult<Vec<(i64, u128)>> {
+        let mut amounts = Vec::new();
+
+        for item in self.distributions.iter() {
+            let (_, value) = item.map_err(FaucetError::DatabaseError)?;
+            let record: DistributionRecord = bincode::deserialize(&value)
+                .map_err(|e| FaucetError::InternalError(e.to_string()))?;
+            if record.asset.is_some() || record.timestamp < since {
+                continue;
+            }
+
+            amounts.push((record.timestamp, record.amount.parse::<u128>().unwrap_or(0)));
+        }
+
+        amounts.sort_by_key(|(timestamp, _)| *timestamp);
+        Ok(amounts)
+    }
+
     /// Get request count for IP in time window
     pub fn get_ip_request_count(&self, ip: &str, window_start: i64) -> FaucetResult<usize> {
         let mut count = 0;
@@ -161,6 +418,20 @@ impl FaucetDatabase {
         Ok(count)
     }
 
+    /// Number of native coin distributions recorded for an address
+    pub fn get_distribution_count_for_address(&self, address: &str) -> FaucetResult<u64> {
+        let mut count = 0;
+        for item in self.distributions.scan_prefix(format!("{}:", address)) {
+            let (_, value) = item.map_err(FaucetError::DatabaseError)?;
+            let record: DistributionRecord = bincode::deserialize(&value)
+                .map_err(|e| FaucetError::InternalError(e.to_string()))?;
+            if record.asset.is_none() {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
     /// Get all distributions for an address
     pub fn get_distributions_for_address(
         &self,
@@ -206,7 +477,9 @@ impl FaucetDatabase {
             let record: DistributionRecord = bincode::deserialize(&value)
                 .map_err(|e| FaucetError::InternalError(e.to_string()))?;
 
-            total_amount += record.amount.parse::<u128>().unwrap_or(0);
+            if record.asset.is_none() {
+                total_amount += record.amount.parse::<u128>().unwrap_or(0);
+            }
             unique_addresses.insert(record.address);
         }
 
@@ -253,3 +526,95 @@ pub struct FaucetStatistics {
     pub total_amount: String,
     pub unique_addresses: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrates_v1_records_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        let v1 = DistributionRecordV1 {
+            address: "0x4242424242424242424242424242424242424242".to_string(),
+            amount: "1000".to_string(),
+            tx_hash: "0xabc".to_string(),
+            timestamp: 1_700_000_000,
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: "test".to_string(),
+        };
+
+        // Write a v1-shaped record into an unversioned database. The handle is
+        // reused because sled's flusher can hold the file lock after a drop.
+        let db = sled::open(path).unwrap();
+        let tree = db.open_tree("distributions").unwrap();
+        let key = format!("{}:{}", v1.address, v1.timestamp);
+        tree.insert(key, bincode::serialize(&v1).unwrap()).unwrap();
+
+        let database = FaucetDatabase::from_db(db).unwrap();
+        assert_eq!(database.schema_version().unwrap(), Some(SCHEMA_VERSION));
+
+        let records = database.get_distributions_for_address(&v1.address).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].tx_hash, v1.tx_hash);
+        assert_eq!(records[0].amount, v1.amount);
+        assert_eq!(records[0].nonce, None);
+
+        // Re-running the migrator leaves migrated records readable
+        assert_eq!(database.migrate().unwrap(), SCHEMA_VERSION);
+        assert_eq!(database.get_statistics().unwrap().total_distributions, 1);
+    }
+
+    #[test]
+    fn test_migrates_v2_records_to_native_distributions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+
+        let v2 = DistributionRecordV2 {
+            address: "0x4242424242424242424242424242424242424242".to_string(),
+            amount: "1000".to_string(),
+            tx_hash: "0xabc".to_string(),
+            timestamp: 1_700_000_000,
+            ip_address: "127.0.0.1".to_string(),
+            user_agent: "test".to_string(),
+            nonce: Some(7),
+        };
+
+        let db = sled::open(path).unwrap();
+        db.insert(SCHEMA_VERSION_KEY, &2u32.to_be_bytes()).unwrap();
+        let tree = db.open_tree("distributions").unwrap();
+        let key = format!("{}:{}", v2.address, v2.timestamp);
+        tree.insert(key, bincode::serialize(&v2).unwrap()).unwrap();
+
+        let database = FaucetDatabase::from_db(db).unwrap();
+        assert_eq!(database.schema_version().unwrap(), Some(SCHEMA_VERSION));
+
+        let records = database.get_distributions_for_address(&v2.address).unwrap();
+        assert_eq!(records[0].nonce, Some(7));
+        assert_eq!(records[0].asset, None);
+        assert_eq!(database.get_total_amount_for_address(&v2.address).unwrap(), 1000);
+
+        // Token distributions don't count towards native totals
+        let token = DistributionRecord::new(
+            v2.address.clone(),
+            "5".to_string(),
+            "0xdef".to_string(),
+            "127.0.0.1".to_string(),
+            "test".to_string(),
+        )
+        .with_asset("TST");
+        database.add_distribution(token).unwrap();
+        assert_eq!(database.get_distribution_count_for_address(&v2.address).unwrap(), 1);
+        assert!(database.get_last_request_time(&v2.address, Some("TST")).unwrap().is_some());
+        assert_eq!(database.get_last_request_time(&v2.address, None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_new_database_starts_at_current_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = FaucetDatabase::new(dir.path().to_str().unwrap()).unwrap();
+
+        assert_eq!(database.schema_version().unwrap(), Some(SCHEMA_VERSION));
+    }
+}
@@ -1,7 +1,7 @@
 //! Error types for the faucet service
 
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -14,36 +14,102 @@ pub enum FaucetError {
     #[error("Rate limit exceeded: try again in {0} seconds")]
     RateLimitExceeded(u64),
 
+    /// The faucet dispensed its hourly total; `reset_at` is the Unix time
+    /// enough of it leaves the window for the request to fit
+    #[error("Global dispense limit reached: try again at {reset_at}")]
+    GlobalRateLimited { reset_at: i64 },
+
+    /// The address has received the most it is allowed to; `retry_after` is
+    /// the number of seconds until enough of it leaves the quota window
+    #[error("Quota exceeded: {reason}, try again in {retry_after} seconds")]
+    QuotaExceeded { reason: String, retry_after: u64 },
+
     #[error("Invalid address: {0}")]
     InvalidAddress(String),
 
     #[error("Invalid amount: {0}")]
     InvalidAmount(String),
 
+    /// The requested asset isn't configured
+    #[error("Unknown asset: {0}")]
+    UnknownAsset(String),
+
+    /// The destination holds contract code and contracts aren't funded
+    #[error("Destination is a contract: {0}")]
+    ContractDestination(String),
+
+    /// A signed request was expired, malformed or not signed by the address
+    #[error("Invalid request signature: {0}")]
+    InvalidSignature(String),
+
+    /// The dispense queue is full
+    #[error("Dispense queue full: {0} requests waiting")]
+    QueueFull(usize),
+
+    /// No queued dispense request has the tracking id
+    #[error("Dispense request not found: {0}")]
+    JobNotFound(String),
+
     #[error("Insufficient funds in faucet")]
     InsufficientFunds,
 
     #[error("Transaction failed: {0}")]
     TransactionFailed(String),
 
+    /// Simulating the transfer against node state predicted it would fail
+    #[error("Transaction simulation failed: {0}")]
+    SimulationFailed(String),
+
+    /// The node could not be reached or rejected the signed transaction
+    #[error("Transaction submission failed: {reason}")]
+    SubmissionFailed { reason: String, retryable: bool },
+
     #[error("Database error: {0}")]
     DatabaseError(#[from] sled::Error),
 
     #[error("RPC error: {0}")]
     RpcError(String),
 
+    #[error("RPC connection error: {0}")]
+    RpcConnectionError(String),
+
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+
     #[error("Internal error: {0}")]
     InternalError(String),
 }
 
 impl IntoResponse for FaucetError {
+    /// Rate limits map to 429 with a `Retry-After` header, invalid requests to
+    /// 400, unavailable dependencies to 502/503 and everything else to 500
     fn into_response(self) -> Response {
+        let retry_after = match self {
+            FaucetError::RateLimitExceeded(seconds) => Some(seconds),
+            FaucetError::GlobalRateLimited { reset_at } => {
+                Some((reset_at - chrono::Utc::now().timestamp()).max(0) as u64)
+            }
+            FaucetError::QuotaExceeded { retry_after, .. } => Some(retry_after),
+            FaucetError::QueueFull(_) => Some(1),
+            _ => None,
+        };
+
         let (status, error_message, error_code) = match self {
             FaucetError::RateLimitExceeded(seconds) => (
                 StatusCode::TOO_MANY_REQUESTS,
                 format!("Rate limit exceeded. Try again in {} seconds", seconds),
                 "RATE_LIMIT_EXCEEDED",
             ),
+            FaucetError::GlobalRateLimited { reset_at } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("Faucet dispense limit reached. Try again at {}", reset_at),
+                "GLOBAL_RATE_LIMITED",
+            ),
+            FaucetError::QuotaExceeded { reason, retry_after } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("Quota exceeded: {}. Try again in {} seconds", reason, retry_after),
+                "QUOTA_EXCEEDED",
+            ),
             FaucetError::InvalidAddress(msg) => (
                 StatusCode::BAD_REQUEST,
                 format!("Invalid address: {}", msg),
@@ -54,6 +120,31 @@ impl IntoResponse for FaucetError {
                 format!("Invalid amount: {}", msg),
                 "INVALID_AMOUNT",
             ),
+            FaucetError::UnknownAsset(asset) => (
+                StatusCode::BAD_REQUEST,
+                format!("Unknown asset: {}", asset),
+                "UNKNOWN_ASSET",
+            ),
+            FaucetError::ContractDestination(address) => (
+                StatusCode::BAD_REQUEST,
+                format!("Destination is a contract: {}", address),
+                "CONTRACT_DESTINATION",
+            ),
+            FaucetError::InvalidSignature(msg) => (
+                StatusCode::UNAUTHORIZED,
+                format!("Invalid request signature: {}", msg),
+                "INVALID_SIGNATURE",
+            ),
+            FaucetError::QueueFull(waiting) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Too many pending requests ({} queued). Please try again shortly.", waiting),
+                "QUEUE_FULL",
+            ),
+            FaucetError::JobNotFound(id) => (
+                StatusCode::NOT_FOUND,
+                format!("Dispense request not found: {}", id),
+                "JOB_NOT_FOUND",
+            ),
             FaucetError::InsufficientFunds => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 "Faucet is out of funds. Please try again later.".to_string(),
@@ -64,6 +155,20 @@ impl IntoResponse for FaucetError {
                 format!("Transaction failed: {}", msg),
                 "TRANSACTION_FAILED",
             ),
+            FaucetError::SimulationFailed(msg) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Transaction simulation failed: {}", msg),
+                "SIMULATION_FAILED",
+            ),
+            FaucetError::SubmissionFailed { reason, retryable } => (
+                if retryable {
+                    StatusCode::SERVICE_UNAVAILABLE
+                } else {
+                    StatusCode::BAD_REQUEST
+                },
+                format!("Transaction submission failed: {}", reason),
+                "SUBMISSION_FAILED",
+            ),
             FaucetError::DatabaseError(err) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Database error: {}", err),
@@ -74,6 +179,16 @@ impl IntoResponse for FaucetError {
                 format!("RPC error: {}", msg),
                 "RPC_ERROR",
             ),
+            FaucetError::RpcConnectionError(msg) => (
+                StatusCode::BAD_GATEWAY,
+                format!("RPC connection error: {}", msg),
+                "RPC_CONNECTION_ERROR",
+            ),
+            FaucetError::ServiceUnavailable(msg) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Service unavailable: {}", msg),
+                "SERVICE_UNAVAILABLE",
+            ),
             FaucetError::InternalError(msg) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Internal error: {}", msg),
@@ -87,8 +202,98 @@ impl IntoResponse for FaucetError {
             "timestamp": chrono::Utc::now().to_rfc3339()
         }));
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        if let Some(seconds) = retry_after {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response
+    }
+}
+
+impl FaucetError {
+    /// Whether the error is transient and the operation may succeed if retried
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            FaucetError::RpcConnectionError(_) | FaucetError::SubmissionFailed { retryable: true, .. }
+        )
+    }
+
+    /// Classify an RPC error from submitting a transaction
+    ///
+    /// Connection failures may succeed later; errors returned by the node mean
+    /// the transaction itself was refused.
+    pub fn into_submission_failure(self) -> Self {
+        match self {
+            FaucetError::RpcConnectionError(reason) => FaucetError::SubmissionFailed { reason, retryable: true },
+            FaucetError::RpcError(reason) | FaucetError::TransactionFailed(reason) => {
+                FaucetError::SubmissionFailed { reason, retryable: false }
+            }
+            other => other,
+        }
     }
 }
 
 pub type FaucetResult<T> = Result<T, FaucetError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submission_failure_status() {
+        let retryable = FaucetError::RpcConnectionError("connection refused".to_string()).into_submission_failure();
+        assert!(matches!(retryable, FaucetError::SubmissionFailed { retryable: true, .. }));
+        assert_eq!(retryable.into_response().status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let permanent = FaucetError::RpcError("nonce too low".to_string()).into_submission_failure();
+        assert!(matches!(permanent, FaucetError::SubmissionFailed { retryable: false, .. }));
+        assert_eq!(permanent.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_status_codes() {
+        let cases = [
+            (FaucetError::RateLimitExceeded(30), StatusCode::TOO_MANY_REQUESTS),
+            (FaucetError::GlobalRateLimited { reset_at: 0 }, StatusCode::TOO_MANY_REQUESTS),
+            (
+                FaucetError::QuotaExceeded { reason: "limit".to_string(), retry_after: 60 },
+                StatusCode::TOO_MANY_REQUESTS,
+            ),
+            (FaucetError::InvalidAddress("0x00".to_string()), StatusCode::BAD_REQUEST),
+            (FaucetError::InvalidAmount("0".to_string()), StatusCode::BAD_REQUEST),
+            (FaucetError::UnknownAsset("TST".to_string()), StatusCode::BAD_REQUEST),
+            (FaucetError::ContractDestination("0x42".to_string()), StatusCode::BAD_REQUEST),
+            (FaucetError::InvalidSignature("expired".to_string()), StatusCode::UNAUTHORIZED),
+            (FaucetError::QueueFull(1000), StatusCode::SERVICE_UNAVAILABLE),
+            (FaucetError::JobNotFound("00".to_string()), StatusCode::NOT_FOUND),
+            (FaucetError::InsufficientFunds, StatusCode::SERVICE_UNAVAILABLE),
+            (FaucetError::TransactionFailed("reverted".to_string()), StatusCode::INTERNAL_SERVER_ERROR),
+            (FaucetError::SimulationFailed("out of gas".to_string()), StatusCode::SERVICE_UNAVAILABLE),
+            (
+                FaucetError::DatabaseError(sled::Error::Unsupported("test".to_string())),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (FaucetError::RpcError("bad".to_string()), StatusCode::BAD_GATEWAY),
+            (FaucetError::RpcConnectionError("refused".to_string()), StatusCode::BAD_GATEWAY),
+            (FaucetError::ServiceUnavailable("circuit open".to_string()), StatusCode::SERVICE_UNAVAILABLE),
+            (FaucetError::InternalError("bug".to_string()), StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+
+        for (error, status) in cases {
+            let description = error.to_string();
+            assert_eq!(error.into_response().status(), status, "{}", description);
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_sets_retry_after() {
+        let response = FaucetError::RateLimitExceeded(42).into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "42");
+
+        let response = FaucetError::QuotaExceeded { reason: "limit".to_string(), retry_after: 3600 }.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "3600");
+    }
+}
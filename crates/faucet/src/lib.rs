@@ -10,10 +10,17 @@
 pub mod config;
 pub mod database;
 pub mod error;
+pub mod rpc;
 pub mod service;
 pub mod api;
 
 pub use config::FaucetConfig;
-pub use database::{DistributionRecord, FaucetDatabase, FaucetStatistics};
+pub use database::{DistributionRecord, FaucetDatabase, FaucetStatistics, SCHEMA_VERSION};
 pub use error::{FaucetError, FaucetResult};
-pub use service::{BlockchainRpcClient, DispenseResponse, FaucetService, FaucetStatus};
+pub use rpc::{
+    BlockchainRpcClient, CircuitBreakerConfig, CircuitBreakerRpcClient, CircuitState, FaucetRpc,
+    MockFaucetRpc, RetryPolicy, RetryingRpcClient,
+};
+pub use service::{
+    DispenseJob, DispenseJobState, DispenseResponse, FaucetService, FaucetStatus, SignedRequest,
+};
@@ -1,8 +1,12 @@
 //! Faucet service binary
 
 use clap::Parser;
-use norn_faucet::api::{dispense_handler, health_handler, root_handler, status_handler};
-use norn_faucet::{FaucetConfig, FaucetService};
+use norn_faucet::api::{
+    dispense_handler, dispense_job_handler, health_handler, root_handler, status_handler,
+};
+use norn_faucet::{
+    BlockchainRpcClient, CircuitBreakerRpcClient, FaucetConfig, FaucetService, RetryingRpcClient,
+};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -97,6 +101,7 @@ async fn main() -> anyhow::Result<()> {
     info!("  Dispense amount: {} wei", config.dispense_amount);
     info!("  Rate limit: {} requests / {}s", config.max_requests_per_window, config.rate_limit_window_secs);
     info!("  Address cooldown: {}s", config.address_cooldown_secs);
+    info!("  RPC retries: {} (base delay {}ms)", config.rpc_max_retries, config.rpc_retry_base_delay_ms);
 
     // Initialize database
     let database = norn_faucet::FaucetDatabase::new(&config.db_path)?;
@@ -109,8 +114,23 @@ async fn main() -> anyhow::Result<()> {
     info!("  Unique addresses: {}", stats.unique_addresses);
 
     // Create faucet service
-    let service = Arc::new(FaucetService::new(config.clone(), database)?);
+    let rpc_client = Arc::new(CircuitBreakerRpcClient::new(
+        Arc::new(RetryingRpcClient::new(
+            Arc::new(BlockchainRpcClient::new(config.rpc_url.clone())),
+            config.rpc_retry_policy(),
+        )),
+        config.rpc_circuit_breaker(),
+    ));
+    let service = Arc::new(FaucetService::new(config.clone(), database, rpc_client)?);
     info!("Faucet service initialized");
+    service.spawn_chain_tip_monitor();
+    if service.queues_dispenses() {
+        service.spawn_dispense_workers();
+        info!(
+            "Dispense queue: {} workers, capacity {}",
+            config.dispense_workers, config.dispense_queue_capacity
+        );
+    }
 
     // Build router
     let mut app = axum::Router::new()
@@ -118,6 +138,7 @@ async fn main() -> anyhow::Result<()> {
         .route("/health", axum::routing::get(health_handler))
         .route("/api/status", axum::routing::get(status_handler))
         .route("/api/dispense", axum::routing::post(dispense_handler))
+        .route("/api/dispense/:id", axum::routing::get(dispense_job_handler))
         .with_state(service.clone());
 
     // Add CORS if enabled
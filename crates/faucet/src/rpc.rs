@@ -0,0 +1,753 @@
+//! Blockchain RPC access for the faucet
+
+use super::error::{FaucetError, FaucetResult};
+use async_trait::async_trait;
+use norn_common::types::Address;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Transfer simulated with `eth_estimateGas` before it is signed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallRequest {
+    pub from: Address,
+    pub to: Address,
+    /// Value in wei
+    pub value: u128,
+    /// Gas limit the transaction will be sent with
+    pub gas: u64,
+    /// Gas price in wei
+    pub gas_price: u128,
+    /// Call data, empty for plain transfers
+    pub data: Vec<u8>,
+}
+
+/// Latest block known to the node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainTip {
+    /// Block height
+    pub height: u64,
+    /// Block timestamp, in seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// Blockchain operations the faucet depends on
+#[async_trait]
+pub trait FaucetRpc: Send + Sync {
+    /// Get account balance as a hex quantity string
+    async fn get_balance(&self, address: &Address) -> FaucetResult<String>;
+
+    /// Get account nonce
+    async fn get_transaction_count(&self, address: &Address) -> FaucetResult<u64>;
+
+    /// Get the code at an address as a hex string, `"0x"` if it has none
+    async fn get_code(&self, address: &Address) -> FaucetResult<String>;
+
+    /// Submit a signed raw transaction, returning its hash
+    async fn send_raw_transaction(&self, tx_data: &str) -> FaucetResult<String>;
+
+    /// Get chain ID
+    async fn get_chain_id(&self) -> FaucetResult<u64>;
+
+    /// Simulate a transaction against the latest state, returning the gas it needs
+    async fn estimate_gas(&self, call: &CallRequest) -> FaucetResult<u64>;
+
+    /// Get the height and timestamp of the latest block
+    async fn get_latest_block(&self) -> FaucetResult<ChainTip>;
+
+    /// Circuit breaker state, if this client is guarded by one
+    fn circuit_state(&self) -> Option<CircuitState> {
+        None
+    }
+}
+
+/// Parse a hex quantity string such as `"0x1a"`
+fn parse_quantity(value: &serde_json::Value) -> u64 {
+    value
+        .as_str()
+        .and_then(|s| u64::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+        .unwrap_or(0)
+}
+
+/// RPC client for interacting with blockchain
+pub struct BlockchainRpcClient {
+    rpc_url: String,
+    client: reqwest::Client,
+}
+
+impl BlockchainRpcClient {
+    pub fn new(rpc_url: String) -> Self {
+        Self {
+            rpc_url,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn call(&self, method: &str, params: serde_json::Value) -> FaucetResult<serde_json::Value> {
+        let payload = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": 1
+        });
+
+        let response = self
+            .client
+            .post(&self.rpc_url)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| FaucetError::RpcConnectionError(format!("Request failed: {}", e)))?;
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| FaucetError::RpcError(format!("Invalid response: {}", e)))?;
+
+        if let Some(error) = json.get("error") {
+            return Err(FaucetError::RpcError(error.to_string()));
+        }
+
+        Ok(json
+            .get("result")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null))
+    }
+}
+
+#[async_trait]
+impl FaucetRpc for BlockchainRpcClient {
+    async fn get_balance(&self, address: &Address) -> FaucetResult<String> {
+        self.call("eth_getBalance", serde_json::json!([format!("0x{}", hex::encode(address.0)), "latest"]))
+            .await
+            .map(|v| v.as_str().unwrap_or("0x0").to_string())
+    }
+
+    async fn get_transaction_count(&self, address: &Address) -> FaucetResult<u64> {
+        let result = self
+            .call(
+                "eth_getTransactionCount",
+                serde_json::json!([format!("0x{}", hex::encode(address.0)), "latest"]),
+            )
+            .await?;
+
+        Ok(u64::from_str_radix(
+            result.as_str().unwrap_or("0x0").trim_start_matches("0x"),
+            16,
+        )
+        .unwrap_or(0))
+    }
+
+    async fn get_code(&self, address: &Address) -> FaucetResult<String> {
+        self.call("eth_getCode", serde_json::json!([format!("0x{}", hex::encode(address.0)), "latest"]))
+            .await
+            .map(|v| v.as_str().unwrap_or("0x").to_string())
+    }
+
+    async fn send_raw_transaction(&self, tx_data: &str) -> FaucetResult<String> {
+        self.call("eth_sendRawTransaction", serde_json::json!([tx_data]))
+            .await
+            .map(|v| v.as_str().unwrap_or("").to_string())
+    }
+
+    async fn get_chain_id(&self) -> FaucetResult<u64> {
+        let result = self.call("eth_chainId", serde_json::json!([])).await?;
+        Ok(u64::from_str_radix(
+            result.as_str().unwrap_or("0x0").trim_start_matches("0x"),
+            16,
+        )
+        .unwrap_or(31337))
+    }
+
+    async fn estimate_gas(&self, call: &CallRequest) -> FaucetResult<u64> {
+        let result = self
+            .call(
+                "eth_estimateGas",
+                serde_json::json!([{
+                    "from": format!("0x{}", hex::encode(call.from.0)),
+                    "to": format!("0x{}", hex::encode(call.to.0)),
+                    "value": format!("0x{:x}", call.value),
+                    "gas": format!("0x{:x}", call.gas),
+                    "gasPrice": format!("0x{:x}", call.gas_price),
+                    "data": format!("0x{}", hex::encode(&call.data)),
+                }]),
+            )
+            .await?;
+        Ok(parse_quantity(&result))
+    }
+
+    async fn get_latest_block(&self) -> FaucetResult<ChainTip> {
+        let block = self
+            .call("eth_getBlockByNumber", serde_json::json!(["latest", false]))
+            .await?;
+        if block.is_null() {
+            return Err(FaucetError::RpcError("Node returned no latest block".to_string()));
+        }
+        Ok(ChainTip {
+            height: parse_quantity(&block["number"]),
+            timestamp: parse_quantity(&block["timestamp"]),
+        })
+    }
+}
+
+/// Exponential backoff settings for RPC calls
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each subsequent retry
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(200),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff before the given retry (0-based), with up to 50% random jitter
+    fn delay_for(&self, retry: u32) -> Duration {
+        let backoff = self.base_delay.saturating_mul(1u32 << retry.min(16));
+        let jitter_ms = backoff.as_millis() as u64 / 2;
+        let jitter = if jitter_ms > 0 {
+            rand::thread_rng().gen_range(0..=jitter_ms)
+        } else {
+            0
+        };
+        backoff + Duration::from_millis(jitter)
+    }
+}
+
+/// Whether the node refused a resubmitted transaction because an earlier
+/// attempt reached it: the node already holds it, or it was mined and its
+/// nonce is used
+fn already_submitted(error: &FaucetError) -> bool {
+    match error {
+        FaucetError::RpcError(reason) | FaucetError::TransactionFailed(reason) => {
+            let reason = reason.to_lowercase();
+            reason.contains("already known")
+                || reason.contains("known transaction")
+                || reason.contains("nonce too low")
+        }
+        _ => false,
+    }
+}
+
+/// RPC client decorator that retries transient failures with exponential backoff
+///
+/// Only errors reported by [`FaucetError::is_retryable`] are retried; errors
+/// returned by the node itself (e.g. an invalid transaction) fail immediately.
+/// A submit whose reply was lost may still have been accepted, so a resubmit
+/// the node refuses as already submitted counts as success.
+pub struct RetryingRpcClient {
+    inner: Arc<dyn FaucetRpc>,
+    policy: RetryPolicy,
+}
+
+impl RetryingRpcClient {
+    pub fn new(inner: Arc<dyn FaucetRpc>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    async fn with_retry<T, F, Fut>(&self, method: &str, mut op: F) -> FaucetResult<T>
+    where
+        F: FnMut() -> Fut + Send,
+        Fut: Future<Output = FaucetResult<T>> + Send,
+        T: Send,
+    {
+        let mut retry = 0;
+        loop {
+            match op().await {
+                Err(e) if e.is_retryable() && retry < self.policy.max_retries => {
+                    let delay = self.policy.delay_for(retry);
+                    warn!(
+                        "{} failed (attempt {}/{}): {}, retrying in {:?}",
+                        method,
+                        retry + 1,
+                        self.policy.max_retries + 1,
+                        e,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl FaucetRpc for RetryingRpcClient {
+    async fn get_balance(&self, address: &Address) -> FaucetResult<String> {
+        self.with_retry("eth_getBalance", || self.inner.get_balance(address))
+            .await
+    }
+
+    async fn get_transaction_count(&self, address: &Address) -> FaucetResult<u64> {
+        self.with_retry("eth_getTransactionCount", || {
+            self.inner.get_transaction_count(address)
+        })
+        .await
+    }
+
+    async fn get_code(&self, address: &Address) -> FaucetResult<String> {
+        self.with_retry("eth_getCode", || self.inner.get_code(address))
+            .await
+    }
+
+    async fn send_raw_transaction(&self, tx_data: &str) -> FaucetResult<String> {
+        let Ok(raw) = hex::decode(tx_data.trim_start_matches("0x")) else {
+            return self.inner.send_raw_transaction(tx_data).await;
+        };
+        let tx_hash = format!("0x{}", hex::encode(keccak_hash::keccak(&raw).0));
+
+        let attempts = AtomicUsize::new(0);
+        self.with_retry("eth_sendRawTransaction", || async {
+            let resent = attempts.fetch_add(1, Ordering::SeqCst) > 0;
+            match self.inner.send_raw_transaction(tx_data).await {
+                Err(e) if resent && already_submitted(&e) => {
+                    info!("Transaction {} was accepted by an earlier attempt ({})", tx_hash, e);
+                    Ok(tx_hash.clone())
+                }
+                result => result,
+            }
+        })
+        .await
+    }
+
+    async fn get_chain_id(&self) -> FaucetResult<u64> {
+        self.with_retry("eth_chainId", || self.inner.get_chain_id())
+            .await
+    }
+
+    async fn estimate_gas(&self, call: &CallRequest) -> FaucetResult<u64> {
+        self.with_retry("eth_estimateGas", || self.inner.estimate_gas(call))
+            .await
+    }
+
+    async fn get_latest_block(&self) -> FaucetResult<ChainTip> {
+        self.with_retry("eth_getBlockByNumber", || self.inner.get_latest_block())
+            .await
+    }
+
+    fn circuit_state(&self) -> Option<CircuitState> {
+        self.inner.circuit_state()
+    }
+}
+
+/// Circuit breaker state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Requests are rejected until the cooldown elapses
+    Open,
+    /// A single trial request is allowed through to probe recovery
+    HalfOpen,
+}
+
+/// Circuit breaker settings
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a trial request is allowed
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+/// RPC client decorator that fails fast while the node is unreachable
+///
+/// Only transient (retryable) errors count as failures, so a node rejecting
+/// a transaction does not trip the breaker.
+pub struct CircuitBreakerRpcClient {
+    inner: Arc<dyn FaucetRpc>,
+    config: CircuitBreakerConfig,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreakerRpcClient {
+    pub fn new(inner: Arc<dyn FaucetRpc>, config: CircuitBreakerConfig) -> Self {
+        Self {
+            inner,
+            config,
+            state: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                trial_in_flight: false,
+            }),
+        }
+    }
+
+    /// Current circuit state
+    pub fn state(&self) -> CircuitState {
+        let mut breaker = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.refresh(&mut breaker);
+        breaker.state
+    }
+
+    /// Move an open circuit to half-open once the cooldown has elapsed
+    ///
+    /// A trial that never reported back (e.g. its request was cancelled) is
+    /// abandoned after the same cooldown so the circuit cannot get stuck.
+    fn refresh(&self, breaker: &mut BreakerState) {
+        let cooled_down = breaker
+            .opened_at
+            .is_none_or(|at| at.elapsed() >= self.config.cooldown);
+        if cooled_down && (breaker.state == CircuitState::Open || breaker.trial_in_flight) {
+            breaker.state = CircuitState::HalfOpen;
+            breaker.trial_in_flight = false;
+        }
+    }
+
+    fn acquire(&self) -> FaucetResult<()> {
+        let mut breaker = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.refresh(&mut breaker);
+        match breaker.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::HalfOpen if !breaker.trial_in_flight => {
+                breaker.trial_in_flight = true;
+                breaker.opened_at = Some(Instant::now());
+                Ok(())
+            }
+            _ => Err(FaucetError::ServiceUnavailable(
+                "blockchain RPC circuit is open".to_string(),
+            )),
+        }
+    }
+
+    fn record<T>(&self, result: &FaucetResult<T>) {
+        let mut breaker = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match result {
+            Err(e) if e.is_retryable() => {
+                breaker.consecutive_failures += 1;
+                if breaker.state == CircuitState::HalfOpen
+                    || breaker.consecutive_failures >= self.config.failure_threshold
+                {
+                    if breaker.state != CircuitState::Open {
+                        warn!(
+                            "RPC circuit opened after {} consecutive failures",
+                            breaker.consecutive_failures
+                        );
+                    }
+                    breaker.state = CircuitState::Open;
+                    breaker.opened_at = Some(Instant::now());
+                }
+            }
+            _ => {
+                if breaker.state != CircuitState::Closed {
+                    info!("RPC circuit closed");
+                }
+                breaker.state = CircuitState::Closed;
+                breaker.consecutive_failures = 0;
+                breaker.opened_at = None;
+            }
+        }
+        breaker.trial_in_flight = false;
+    }
+
+    async fn guarded<T, Fut>(&self, fut: Fut) -> FaucetResult<T>
+    where
+        Fut: Future<Output = FaucetResult<T>> + Send,
+    {
+        self.acquire()?;
+        let result = fut.await;
+        self.record(&result);
+        result
+    }
+}
+
+#[async_trait]
+impl FaucetRpc for CircuitBreakerRpcClient {
+    async fn get_balance(&self, address: &Address) -> FaucetResult<String> {
+        self.guarded(self.inner.get_balance(address)).await
+    }
+
+    async fn get_transaction_count(&self, address: &Address) -> FaucetResult<u64> {
+        self.guarded(self.inner.get_transaction_count(address)).await
+    }
+
+    async fn get_code(&self, address: &Address) -> FaucetResult<String> {
+        self.guarded(self.inner.get_code(address)).await
+    }
+
+    async fn send_raw_transaction(&self, tx_data: &str) -> FaucetResult<String> {
+        self.guarded(self.inner.send_raw_transaction(tx_data)).await
+    }
+
+    async fn get_chain_id(&self) -> FaucetResult<u64> {
+        self.guarded(self.inner.get_chain_id()).await
+    }
+
+    async fn estimate_gas(&self, call: &CallRequest) -> FaucetResult<u64> {
+        self.guarded(self.inner.estimate_gas(call)).await
+    }
+
+    async fn get_latest_block(&self) -> FaucetResult<ChainTip> {
+        self.guarded(self.inner.get_latest_block()).await
+    }
+
+    fn circuit_state(&self) -> Option<CircuitState> {
+        Some(self.state())
+    }
+}
+
+/// In-memory RPC backend for tests
+///
+/// Returns canned balances and derives transaction hashes from the raw
+/// transaction bytes, so dispense logic can run without a node.
+pub struct MockFaucetRpc {
+    chain_id: u64,
+    default_balance: u128,
+    balances: Mutex<HashMap<Address, u128>>,
+    codes: Mutex<HashMap<Address, Vec<u8>>>,
+    sent_transactions: Mutex<Vec<String>>,
+    pending_failures: AtomicUsize,
+    lost_replies: AtomicUsize,
+    calls: AtomicUsize,
+    latency: Mutex<Duration>,
+    chain_tip: Mutex<Option<ChainTip>>,
+}
+
+impl MockFaucetRpc {
+    pub fn new(chain_id: u64, default_balance: u128) -> Self {
+        Self {
+            chain_id,
+            default_balance,
+            balances: Mutex::new(HashMap::new()),
+            codes: Mutex::new(HashMap::new()),
+            sent_transactions: Mutex::new(Vec::new()),
+            pending_failures: AtomicUsize::new(0),
+            lost_replies: AtomicUsize::new(0),
+            calls: AtomicUsize::new(0),
+            latency: Mutex::new(Duration::ZERO),
+            chain_tip: Mutex::new(None),
+        }
+    }
+
+    /// Make the next `count` calls fail with a connection error
+    pub fn fail_next(&self, count: usize) {
+        self.pending_failures.store(count, Ordering::SeqCst);
+    }
+
+    /// Accept the next `count` submitted transactions but fail the calls
+    /// with a connection error, as when the reply times out
+    pub fn lose_next_replies(&self, count: usize) {
+        self.lost_replies.store(count, Ordering::SeqCst);
+    }
+
+    /// Total number of calls received, including failed ones
+    pub fn call_count(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    /// Delay every call by `latency`, to widen race windows in tests
+    pub fn set_latency(&self, latency: Duration) {
+        *self.latency.lock().unwrap() = latency;
+    }
+
+    async fn begin_call(&self) -> FaucetResult<()> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let latency = *self.latency.lock().unwrap();
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        let injected = self
+            .pending_failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        if injected {
+            return Err(FaucetError::RpcConnectionError("mock connection failure".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Set the latest block reported by the node
+    ///
+    /// Until this is called, the tip is block 0 produced just now.
+    pub fn set_chain_tip(&self, tip: ChainTip) {
+        *self.chain_tip.lock().unwrap() = Some(tip);
+    }
+
+    /// Set the balance returned for an address
+    pub fn set_balance(&self, address: Address, balance: u128) {
+        self.balances.lock().unwrap().insert(address, balance);
+    }
+
+    /// Deploy `code` at an address
+    pub fn set_code(&self, address: Address, code: Vec<u8>) {
+        self.codes.lock().unwrap().insert(address, code);
+    }
+
+    /// Raw transactions submitted so far
+    pub fn sent_transactions(&self) -> Vec<String> {
+        self.sent_transactions.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl FaucetRpc for MockFaucetRpc {
+    async fn get_balance(&self, address: &Address) -> FaucetResult<String> {
+        self.begin_call().await?;
+        let balance = self
+            .balances
+            .lock()
+            .unwrap()
+            .get(address)
+            .copied()
+            .unwrap_or(self.default_balance);
+        Ok(format!("0x{:x}", balance))
+    }
+
+    async fn get_transaction_count(&self, _address: &Address) -> FaucetResult<u64> {
+        self.begin_call().await?;
+        Ok(self.sent_transactions.lock().unwrap().len() as u64)
+    }
+
+    async fn get_code(&self, address: &Address) -> FaucetResult<String> {
+        self.begin_call().await?;
+        let code = self.codes.lock().unwrap().get(address).cloned().unwrap_or_default();
+        Ok(format!("0x{}", hex::encode(code)))
+    }
+
+    async fn send_raw_transaction(&self, tx_data: &str) -> FaucetResult<String> {
+        self.begin_call().await?;
+        let raw = hex::decode(tx_data.trim_start_matches("0x"))
+            .map_err(|e| FaucetError::TransactionFailed(format!("Invalid raw transaction: {}", e)))?;
+        {
+            let mut sent = self.sent_transactions.lock().unwrap();
+            if sent.iter().any(|tx| tx == tx_data) {
+                return Err(FaucetError::RpcError("already known".to_string()));
+            }
+            sent.push(tx_data.to_string());
+        }
+        let lost = self
+            .lost_replies
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        if lost {
+            return Err(FaucetError::RpcConnectionError("mock reply timed out".to_string()));
+        }
+        Ok(format!("0x{}", hex::encode(keccak_hash::keccak(&raw).0)))
+    }
+
+    async fn get_chain_id(&self) -> FaucetResult<u64> {
+        self.begin_call().await?;
+        Ok(self.chain_id)
+    }
+
+    async fn estimate_gas(&self, call: &CallRequest) -> FaucetResult<u64> {
+        self.begin_call().await?;
+        let balance = self
+            .balances
+            .lock()
+            .unwrap()
+            .get(&call.from)
+            .copied()
+            .unwrap_or(self.default_balance);
+        let cost = call.value.saturating_add(call.gas_price.saturating_mul(call.gas as u128));
+        if balance < cost {
+            return Err(FaucetError::RpcError(format!(
+                "insufficient funds for gas * price + value: balance {}, cost {}",
+                balance, cost
+            )));
+        }
+        Ok(21_000)
+    }
+
+    async fn get_latest_block(&self) -> FaucetResult<ChainTip> {
+        self.begin_call().await?;
+        let tip = *self.chain_tip.lock().unwrap();
+        Ok(tip.unwrap_or_else(|| ChainTip {
+            height: 0,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(mock: Arc<MockFaucetRpc>, cooldown: Duration) -> CircuitBreakerRpcClient {
+        CircuitBreakerRpcClient::new(
+            mock,
+            CircuitBreakerConfig {
+                failure_threshold: 2,
+                cooldown,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_circuit_opens_and_short_circuits() {
+        let mock = Arc::new(MockFaucetRpc::new(31337, 0));
+        let rpc = breaker(mock.clone(), Duration::from_secs(60));
+
+        mock.fail_next(10);
+        for _ in 0..2 {
+            let result = rpc.get_chain_id().await;
+            assert!(matches!(result, Err(FaucetError::RpcConnectionError(_))));
+        }
+        assert_eq!(rpc.state(), CircuitState::Open);
+
+        let result = rpc.get_chain_id().await;
+        assert!(matches!(result, Err(FaucetError::ServiceUnavailable(_))));
+        assert_eq!(mock.call_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_half_opens_after_cooldown() {
+        let mock = Arc::new(MockFaucetRpc::new(31337, 0));
+        let rpc = breaker(mock.clone(), Duration::from_millis(20));
+
+        mock.fail_next(2);
+        let _ = rpc.get_chain_id().await;
+        let _ = rpc.get_chain_id().await;
+        assert_eq!(rpc.state(), CircuitState::Open);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(rpc.circuit_state(), Some(CircuitState::HalfOpen));
+
+        assert_eq!(rpc.get_chain_id().await.unwrap(), 31337);
+        assert_eq!(rpc.state(), CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_node_rejections_do_not_trip_circuit() {
+        let mock = Arc::new(MockFaucetRpc::new(31337, 0));
+        let rpc = breaker(mock.clone(), Duration::from_secs(60));
+
+        for _ in 0..3 {
+            assert!(rpc.send_raw_transaction("0xnot-hex").await.is_err());
+        }
+        assert_eq!(rpc.state(), CircuitState::Closed);
+    }
+}
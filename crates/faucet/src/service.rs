@@ -1,123 +1,298 @@
 //! Faucet service core logic
 
-use super::config::FaucetConfig;
+use super::config::{FaucetAsset, FaucetConfig};
 use super::database::{DistributionRecord, FaucetDatabase};
 use super::error::{FaucetError, FaucetResult};
+use super::rpc::{CallRequest, ChainTip, CircuitState, FaucetRpc};
 use chrono::Utc;
 use governor::{
     clock::DefaultClock,
     state::{InMemoryState, NotKeyed},
     Quota, RateLimiter,
 };
-use k256::ecdsa::{SigningKey, signature::Signer, Signature};
-use norn_common::types::Address;
+use k256::ecdsa::SigningKey;
+use norn_common::types::{Address, TransactionBody, TransactionType};
+use norn_common::utils::address::to_checksum_address;
+use norn_crypto::ethereum;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
 use std::net::IpAddr;
 use std::num::NonZeroU32;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::{mpsc, watch, OwnedMutexGuard};
 use tracing::{debug, info, warn};
 
-/// RPC client for interacting with blockchain
-pub struct BlockchainRpcClient {
-    rpc_url: String,
-    client: reqwest::Client,
+/// Rate limiter using governor crate
+type RateLimiterImpl = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+
+/// How far in the future a signed request's timestamp may be, to allow for clock skew
+const MAX_CLOCK_SKEW_SECS: i64 = 30;
+
+/// Proof that a dispense request was made by the owner of the address
+///
+/// Requests are only accepted within `signed_request_window_secs` of their
+/// timestamp, and each only once inside that window.
+#[derive(Debug, Clone)]
+pub struct SignedRequest {
+    /// Unix timestamp (seconds) the request was signed at
+    pub timestamp: i64,
+    /// `personal_sign` (EIP-191) signature `r || s || v` over
+    /// [`SignedRequest::signing_message`]
+    pub signature: Vec<u8>,
+}
+
+impl SignedRequest {
+    /// Message a client signs to request `asset` for `address` at `timestamp`
+    ///
+    /// `asset` is the configured token name, [`NATIVE_ASSET`] for the native coin.
+    pub fn signing_message(address: &Address, asset: &str, timestamp: i64) -> Vec<u8> {
+        format!("norn-faucet:dispense:0x{}:{}:{}", hex::encode(address.0), asset, timestamp).into_bytes()
+    }
+}
+
+/// Most signed requests remembered at once; further requests are refused
+/// until the oldest expire
+const MAX_SEEN_SIGNED_REQUESTS: usize = 100_000;
+
+/// Signed requests accepted within the signature window, so none is accepted twice
+///
+/// Entries are keyed by the signed message, which names the address, asset
+/// and timestamp.
+#[derive(Default)]
+struct SeenSignedRequests {
+    seen: std::sync::Mutex<BTreeSet<(i64, Vec<u8>)>>,
+}
+
+impl SeenSignedRequests {
+    /// Remember the request signed over `message` at `timestamp`, unless it
+    /// was seen before
+    ///
+    /// Requests older than `window` seconds before `now` are forgotten, as
+    /// they are refused as expired anyway.
+    fn insert(&self, timestamp: i64, message: Vec<u8>, now: i64, window: i64) -> FaucetResult<()> {
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        while seen.first().is_some_and(|(at, _)| *at < now - window) {
+            seen.pop_first();
+        }
+
+        let entry = (timestamp, message);
+        if seen.contains(&entry) {
+            return Err(FaucetError::InvalidSignature("Request was already used".to_string()));
+        }
+        if let Some((oldest, _)) = seen.first().filter(|_| seen.len() >= MAX_SEEN_SIGNED_REQUESTS) {
+            return Err(FaucetError::RateLimitExceeded((oldest + window - now + 1).max(1) as u64));
+        }
+        seen.insert(entry);
+        Ok(())
+    }
+}
+
+/// Number of shards in the per-address lock map
+const ADDRESS_LOCK_SHARDS: usize = 16;
+
+/// Per-address async locks, sharded to keep contention on the map itself low
+struct AddressLocks {
+    shards: Vec<std::sync::Mutex<HashMap<Address, Arc<tokio::sync::Mutex<()>>>>>,
 }
 
-impl BlockchainRpcClient {
-    pub fn new(rpc_url: String) -> Self {
+impl AddressLocks {
+    fn new() -> Self {
         Self {
-            rpc_url,
-            client: reqwest::Client::new(),
+            shards: (0..ADDRESS_LOCK_SHARDS)
+                .map(|_| std::sync::Mutex::new(HashMap::new()))
+                .collect(),
         }
     }
 
-    async fn call(&self, method: &str, params: serde_json::Value) -> FaucetResult<serde_json::Value> {
-        let payload = serde_json::json!({
-            "jsonrpc": "2.0",
-            "method": method,
-            "params": params,
-            "id": 1
-        });
+    /// Acquire the lock for an address, held until the guard is dropped
+    async fn lock(&self, address: &Address) -> OwnedMutexGuard<()> {
+        let mut hasher = DefaultHasher::new();
+        address.hash(&mut hasher);
+        let shard = &self.shards[hasher.finish() as usize % ADDRESS_LOCK_SHARDS];
+
+        let lock = {
+            let mut locks = shard.lock().unwrap();
+            // Drop entries nobody holds or waits on
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(*address).or_default().clone()
+        };
 
-        let response = self
-            .client
-            .post(&self.rpc_url)
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|e| FaucetError::RpcError(format!("Request failed: {}", e)))?;
+        lock.lock_owned().await
+    }
+}
 
-        let json: serde_json::Value = response
-            .json()
-            .await
-            .map_err(|e| FaucetError::RpcError(format!("Invalid response: {}", e)))?;
+/// Asset name requests may use for the native coin
+pub const NATIVE_ASSET: &str = "native";
+
+/// Signature of the ERC-20 transfer function
+const ERC20_TRANSFER_SIGNATURE: &[u8] = b"transfer(address,uint256)";
+
+/// Call data of an ERC-20 `transfer(to, amount)`
+pub fn erc20_transfer_calldata(to: &Address, amount: u128) -> Vec<u8> {
+    let mut data = keccak_hash::keccak(ERC20_TRANSFER_SIGNATURE).0[..4].to_vec();
+    data.extend_from_slice(&[0u8; 12]);
+    data.extend_from_slice(&to.0);
+    data.extend_from_slice(&[0u8; 16]);
+    data.extend_from_slice(&amount.to_be_bytes());
+    data
+}
+
+/// Transaction the faucet signs and sends
+struct Transfer {
+    to: Address,
+    /// Value in wei
+    value: u128,
+    data: Vec<u8>,
+    gas_limit: u64,
+}
+
+/// Window of the global dispense limit (seconds)
+const OUTFLOW_WINDOW_SECS: i64 = 3600;
+
+/// Amounts dispensed within the last hour, oldest first
+#[derive(Default)]
+struct OutflowWindow {
+    dispensed: std::sync::Mutex<VecDeque<(i64, u128)>>,
+}
+
+impl OutflowWindow {
+    /// Window holding the distributions `database` recorded within the hour
+    /// before `now`, so a restart doesn't reset the global limit
+    fn load(database: &FaucetDatabase, now: i64) -> FaucetResult<Self> {
+        let dispensed = database.get_amounts_since(now - OUTFLOW_WINDOW_SECS + 1)?;
+        Ok(Self { dispensed: std::sync::Mutex::new(dispensed.into()) })
+    }
+
+    /// Count `amount` as dispensed at `now`, unless the hourly total would
+    /// exceed `limit`
+    ///
+    /// On rejection returns the time enough earlier dispenses leave the
+    /// window for `amount` to fit.
+    fn reserve(&self, now: i64, amount: u128, limit: u128) -> Result<(), i64> {
+        let mut dispensed = self.dispensed.lock().unwrap();
+        while dispensed.front().is_some_and(|(at, _)| *at <= now - OUTFLOW_WINDOW_SECS) {
+            dispensed.pop_front();
+        }
 
-        if let Some(error) = json.get("error") {
-            return Err(FaucetError::RpcError(error.to_string()));
+        let mut total: u128 = dispensed.iter().map(|(_, amount)| amount).sum();
+        if total.saturating_add(amount) <= limit {
+            dispensed.push_back((now, amount));
+            return Ok(());
         }
 
-        Ok(json
-            .get("result")
-            .cloned()
-            .unwrap_or(serde_json::Value::Null))
+        for (at, earlier) in dispensed.iter() {
+            total -= earlier;
+            if total.saturating_add(amount) <= limit {
+                return Err(at + OUTFLOW_WINDOW_SECS);
+            }
+        }
+        // Larger than the limit itself; never fits
+        Err(now + OUTFLOW_WINDOW_SECS)
     }
 
-    pub async fn get_balance(&self, address: &Address) -> FaucetResult<String> {
-        self.call("eth_getBalance", serde_json::json!([format!("0x{}", hex::encode(address.0)), "latest"]))
-            .await
-            .map(|v| v.as_str().unwrap_or("0x0").to_string())
+    /// Undo a reservation whose transfer was never sent
+    fn release(&self, at: i64, amount: u128) {
+        let mut dispensed = self.dispensed.lock().unwrap();
+        if let Some(index) = dispensed.iter().rposition(|entry| *entry == (at, amount)) {
+            dispensed.remove(index);
+        }
     }
+}
 
-    pub async fn get_transaction_count(&self, address: &Address) -> FaucetResult<u64> {
-        let result = self
-            .call(
-                "eth_getTransactionCount",
-                serde_json::json!([format!("0x{}", hex::encode(address.0)), "latest"]),
-            )
-            .await?;
+/// How long the state of a queued dispense request is kept after its last change
+const DISPENSE_JOB_RETENTION_SECS: u64 = 3600;
+
+/// Dispense request waiting for a worker
+struct QueuedDispense {
+    id: String,
+    /// Position in the queue, which submissions follow
+    seq: u64,
+    address: Address,
+    asset: Option<FaucetAsset>,
+    ip_addr: IpAddr,
+    user_agent: String,
+}
 
-        Ok(u64::from_str_radix(
-            result.as_str().unwrap_or("0x0").trim_start_matches("0x"),
-            16,
-        )
-        .unwrap_or(0))
-    }
+/// Bounded queue of dispense requests shared by the workers
+///
+/// Workers check requests concurrently, but take turns in queue order to
+/// submit them, so transactions use nonces in the order requests arrived.
+struct DispenseQueue {
+    sender: mpsc::Sender<QueuedDispense>,
+    receiver: Arc<tokio::sync::Mutex<mpsc::Receiver<QueuedDispense>>>,
+    /// Sequence number of the next request queued
+    next_seq: std::sync::Mutex<u64>,
+    /// Sequence number of the request whose turn it is to submit
+    turn: watch::Sender<u64>,
+    jobs: moka::sync::Cache<String, DispenseJobState>,
+}
 
-    pub async fn send_raw_transaction(&self, tx_data: &str) -> FaucetResult<String> {
-        self.call("eth_sendRawTransaction", serde_json::json!([tx_data]))
-            .await
-            .map(|v| v.as_str().unwrap_or("").to_string())
+impl DispenseQueue {
+    fn new(capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        Self {
+            sender,
+            receiver: Arc::new(tokio::sync::Mutex::new(receiver)),
+            next_seq: std::sync::Mutex::new(0),
+            turn: watch::channel(0).0,
+            jobs: moka::sync::Cache::builder()
+                .max_capacity(100_000)
+                .time_to_live(Duration::from_secs(DISPENSE_JOB_RETENTION_SECS))
+                .build(),
+        }
     }
 
-    pub async fn get_chain_id(&self) -> FaucetResult<u64> {
-        let result = self.call("eth_chainId", serde_json::json!([])).await?;
-        Ok(u64::from_str_radix(
-            result.as_str().unwrap_or("0x0").trim_start_matches("0x"),
-            16,
-        )
-        .unwrap_or(31337))
+    /// Wait until it is the turn of the request numbered `seq` to submit
+    async fn wait_turn(&self, seq: u64) -> SubmissionTurn<'_> {
+        let mut turn = self.turn.subscribe();
+        // The sender lives as long as the queue, so this can't fail
+        let _ = turn.wait_for(|turn| *turn == seq).await;
+        SubmissionTurn(&self.turn)
     }
 }
 
-/// Rate limiter using governor crate
-type RateLimiterImpl = RateLimiter<NotKeyed, InMemoryState, DefaultClock>;
+/// Turn of a queued request to submit, passed on to the next when dropped
+struct SubmissionTurn<'a>(&'a watch::Sender<u64>);
+
+impl Drop for SubmissionTurn<'_> {
+    fn drop(&mut self) {
+        self.0.send_modify(|turn| *turn += 1);
+    }
+}
 
 /// Faucet service
 pub struct FaucetService {
     config: FaucetConfig,
     database: Arc<FaucetDatabase>,
-    rpc_client: Arc<BlockchainRpcClient>,
+    rpc_client: Arc<dyn FaucetRpc>,
     signing_key: SigningKey,
     faucet_address: Address,
     rate_limiter: Arc<RateLimiterImpl>,
     ip_rate_limiters: Arc<moka::future::Cache<String, Arc<RateLimiterImpl>>>,
+    address_locks: AddressLocks,
+    /// Dispensed amounts counted against `global_max_dispense_per_hour_wei`
+    outflow: OutflowWindow,
+    /// Signed requests accepted within `signed_request_window_secs`
+    seen_signed_requests: SeenSignedRequests,
+    /// Latest block seen by the chain tip monitor
+    chain_tip: std::sync::RwLock<Option<ChainTip>>,
+    /// Held from fetching the nonce until the transaction is sent, so
+    /// concurrent submissions don't reuse a nonce
+    submission_lock: tokio::sync::Mutex<()>,
+    dispense_queue: DispenseQueue,
 }
 
 impl FaucetService {
-    /// Create new faucet service
-    pub fn new(config: FaucetConfig, database: FaucetDatabase) -> FaucetResult<Self> {
+    /// Create new faucet service backed by the given RPC client
+    pub fn new(
+        config: FaucetConfig,
+        database: FaucetDatabase,
+        rpc_client: Arc<dyn FaucetRpc>,
+    ) -> FaucetResult<Self> {
         // Decode private key
         let private_key_hex = config.private_key.strip_prefix("0x").unwrap_or(&config.private_key);
         let private_key_bytes =
@@ -131,18 +306,10 @@ impl FaucetService {
             .map_err(|e| FaucetError::InvalidAddress(format!("Invalid signing key: {}", e)))?;
 
         // Derive faucet address
-        let public_key = signing_key.verifying_key();
-        let pub_key_bytes = public_key.to_encoded_point(false);
-        let hash = keccak_hash::keccak(&pub_key_bytes.as_bytes()[1..]); // Skip first byte
-        let mut addr_bytes = [0u8; 20];
-        addr_bytes.copy_from_slice(&hash.0[12..]);
-        let faucet_address = Address(addr_bytes);
+        let faucet_address = ethereum::address_of(signing_key.verifying_key());
 
         info!("Faucet address: 0x{}", hex::encode(faucet_address.0));
 
-        // Create RPC client
-        let rpc_client = Arc::new(BlockchainRpcClient::new(config.rpc_url.clone()));
-
         // Create global rate limiter
         let quota = Quota::per_minute(NonZeroU32::new(config.max_requests_per_window * 60 / config.rate_limit_window_secs as u32).unwrap_or(NonZeroU32::new(10).unwrap()));
         let rate_limiter = Arc::new(RateLimiter::direct(quota));
@@ -150,6 +317,9 @@ impl FaucetService {
         // Create IP-specific rate limiter cache
         let ip_rate_limiters = Arc::new(moka::future::Cache::new(10000)); // Cache 10k IPs
 
+        let dispense_queue = DispenseQueue::new(config.dispense_queue_capacity);
+        let outflow = OutflowWindow::load(&database, Utc::now().timestamp())?;
+
         Ok(Self {
             config,
             database: Arc::new(database),
@@ -158,44 +328,228 @@ impl FaucetService {
             faucet_address,
             rate_limiter,
             ip_rate_limiters,
+            address_locks: AddressLocks::new(),
+            outflow,
+            seen_signed_requests: SeenSignedRequests::default(),
+            chain_tip: std::sync::RwLock::new(None),
+            submission_lock: tokio::sync::Mutex::new(()),
+            dispense_queue,
         })
     }
 
-    /// Dispense tokens to an address
+    /// Dispense the native coin to an address
     pub async fn dispense(
         &self,
         address: Address,
         ip_addr: IpAddr,
         user_agent: String,
     ) -> FaucetResult<DispenseResponse> {
-        info!("Dispense request for address: 0x{}, IP: {}", hex::encode(address.0), ip_addr);
+        self.dispense_asset(address, None, ip_addr, user_agent).await
+    }
+
+    /// Dispense the configured token `asset` to an address, or the native
+    /// coin without one
+    ///
+    /// Cooldowns apply per asset. Amount tiers, the per-address maximum and
+    /// the global hourly limit only apply to the native coin.
+    pub async fn dispense_asset(
+        &self,
+        address: Address,
+        asset: Option<&str>,
+        ip_addr: IpAddr,
+        user_agent: String,
+    ) -> FaucetResult<DispenseResponse> {
+        info!(
+            "Dispense request for address: 0x{}, asset: {}, IP: {}",
+            hex::encode(address.0),
+            asset.unwrap_or(NATIVE_ASSET),
+            ip_addr
+        );
+
+        let asset = self.resolve_asset(asset)?;
+        self.check_request(&address, &ip_addr).await?;
+        self.submit_dispense(address, asset.as_ref(), ip_addr, user_agent).await
+    }
+
+    /// Whether dispense requests go through the queue and worker pool
+    pub fn queues_dispenses(&self) -> bool {
+        self.config.dispense_workers > 0
+    }
 
+    /// Queue a dispense request for the workers, returning it with its tracking id
+    ///
+    /// Fails with `QueueFull` while `dispense_queue_capacity` requests are waiting.
+    pub fn enqueue_dispense(
+        &self,
+        address: Address,
+        asset: Option<&str>,
+        ip_addr: IpAddr,
+        user_agent: String,
+    ) -> FaucetResult<DispenseJob> {
+        let asset = self.resolve_asset(asset)?;
+        let queue = &self.dispense_queue;
+
+        // Number requests under the lock, so queue order matches their numbers
+        let mut next_seq = queue.next_seq.lock().unwrap();
+        let id = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+        queue.jobs.insert(id.clone(), DispenseJobState::Queued);
+
+        let job = QueuedDispense { id: id.clone(), seq: *next_seq, address, asset, ip_addr, user_agent };
+        if let Err(e) = queue.sender.try_send(job) {
+            queue.jobs.invalidate(&id);
+            return Err(match e {
+                mpsc::error::TrySendError::Full(_) => FaucetError::QueueFull(queue.sender.max_capacity()),
+                mpsc::error::TrySendError::Closed(_) => {
+                    FaucetError::InternalError("Dispense queue closed".to_string())
+                }
+            });
+        }
+        *next_seq += 1;
+
+        info!("Queued dispense request {} for 0x{}", id, hex::encode(address.0));
+        Ok(DispenseJob { id, state: DispenseJobState::Queued })
+    }
+
+    /// Queued dispense request with tracking id `id`
+    pub fn dispense_job(&self, id: &str) -> FaucetResult<DispenseJob> {
+        let state = self
+            .dispense_queue
+            .jobs
+            .get(id)
+            .ok_or_else(|| FaucetError::JobNotFound(id.to_string()))?;
+        Ok(DispenseJob { id: id.to_string(), state })
+    }
+
+    /// Start `dispense_workers` tasks working through queued requests
+    pub fn spawn_dispense_workers(self: &Arc<Self>) -> Vec<tokio::task::JoinHandle<()>> {
+        (0..self.config.dispense_workers)
+            .map(|_| {
+                let service = self.clone();
+                let receiver = self.dispense_queue.receiver.clone();
+                tokio::spawn(async move {
+                    loop {
+                        let Some(job) = receiver.lock().await.recv().await else {
+                            break;
+                        };
+                        service.process_queued(job).await;
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Check a queued request, then submit it on its turn
+    async fn process_queued(&self, job: QueuedDispense) {
+        let jobs = &self.dispense_queue.jobs;
+        jobs.insert(job.id.clone(), DispenseJobState::Processing);
+
+        let checked = self.check_request(&job.address, &job.ip_addr).await;
+        let result = {
+            let _turn = self.dispense_queue.wait_turn(job.seq).await;
+            match checked {
+                Ok(()) => self.submit_dispense(job.address, job.asset.as_ref(), job.ip_addr, job.user_agent).await,
+                Err(e) => Err(e),
+            }
+        };
+
+        let state = match result {
+            Ok(result) => DispenseJobState::Completed { result },
+            Err(e) => {
+                warn!("Queued dispense request {} failed: {}", job.id, e);
+                DispenseJobState::Failed { error: e.to_string() }
+            }
+        };
+        jobs.insert(job.id, state);
+    }
+
+    /// Configured token named `asset`, `None` for the native coin
+    fn resolve_asset(&self, asset: Option<&str>) -> FaucetResult<Option<FaucetAsset>> {
+        match asset.filter(|name| *name != NATIVE_ASSET) {
+            Some(name) => self
+                .config
+                .asset(name)
+                .cloned()
+                .map(Some)
+                .ok_or_else(|| FaucetError::UnknownAsset(name.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Checks that don't depend on earlier distributions to the address
+    async fn check_request(&self, address: &Address, ip_addr: &IpAddr) -> FaucetResult<()> {
         // 1. Validate address
-        self.validate_address(&address)?;
+        self.validate_address(address)?;
 
         // 2. Check rate limits
-        self.check_rate_limits(&address, &ip_addr).await?;
+        self.check_rate_limits(address, ip_addr).await?;
 
-        // 3. Check faucet balance
+        // 3. Check faucet balance and the destination
         self.check_faucet_balance().await?;
+        self.check_destination(address).await
+    }
+
+    /// Check the address' quotas, then send and record the transfer
+    async fn submit_dispense(
+        &self,
+        address: Address,
+        asset: Option<&FaucetAsset>,
+        ip_addr: IpAddr,
+        user_agent: String,
+    ) -> FaucetResult<DispenseResponse> {
+        let asset_name = asset.map(|asset| asset.name.as_str());
+
+        // Hold the address lock from the cooldown check until the record is
+        // written, so concurrent requests for one address cannot both pass
+        let _address_guard = self.address_locks.lock(&address).await;
 
         // 4. Check address cooldown
-        self.check_address_cooldown(&address).await?;
+        self.check_address_cooldown(&address, asset_name).await?;
+
+        // 5. Pick the amount and build the transfer
+        let (transfer, amount) = match asset {
+            None => {
+                let amount = self.dispense_amount_for(&address)?;
+                self.check_max_amount_per_address(&address, amount)?;
+                let transfer = Transfer {
+                    to: address,
+                    value: amount,
+                    data: Vec::new(),
+                    gas_limit: self.config.gas_limit,
+                };
+                (transfer, amount)
+            }
+            Some(asset) => self.token_transfer(asset, &address)?,
+        };
 
-        // 5. Check max amount per address
-        self.check_max_amount_per_address(&address)?;
+        // 6. Count native amounts against the global hourly limit
+        let reserved_at = match asset {
+            None => Some(self.reserve_outflow(amount)?),
+            Some(_) => None,
+        };
 
-        // 6. Create and send transaction
-        let tx_hash = self.send_transaction(&address).await?;
+        // 7. Create and send transaction
+        let (tx_hash, nonce) = match self.send_transaction(&transfer).await {
+            Ok(sent) => sent,
+            Err(e) => {
+                if let Some(reserved_at) = reserved_at {
+                    self.outflow.release(reserved_at, amount);
+                }
+                return Err(e);
+            }
+        };
 
-        // 7. Record distribution
-        let record = DistributionRecord::new(
+        // 8. Record distribution
+        let mut record = DistributionRecord::new(
             format!("0x{}", hex::encode(address.0)),
-            self.config.dispense_amount.clone(),
+            amount.to_string(),
             tx_hash.clone(),
             ip_addr.to_string(),
             user_agent,
-        );
+        )
+        .with_nonce(nonce);
+        if let Some(asset_name) = asset_name {
+            record = record.with_asset(asset_name);
+        }
 
         self.database.add_distribution(record)?;
 
@@ -207,11 +561,51 @@ impl FaucetService {
 
         Ok(DispenseResponse {
             tx_hash,
-            amount: self.config.dispense_amount.clone(),
-            address: format!("0x{}", hex::encode(address.0)),
+            amount: amount.to_string(),
+            address: to_checksum_address(&address),
+            asset: asset_name.map(str::to_string),
         })
     }
 
+    /// Check that a request for `asset` was signed by the owner of `address`
+    /// and isn't stale or already used
+    ///
+    /// Unsigned requests are accepted unless `require_signed_requests` is set.
+    pub fn verify_request_signature(
+        &self,
+        address: &Address,
+        asset: Option<&str>,
+        signed: Option<&SignedRequest>,
+    ) -> FaucetResult<()> {
+        let Some(signed) = signed else {
+            if self.config.require_signed_requests {
+                return Err(FaucetError::InvalidSignature("Request must be signed".to_string()));
+            }
+            return Ok(());
+        };
+
+        let now = Utc::now().timestamp();
+        let window = self.config.signed_request_window_secs as i64;
+        let age = now - signed.timestamp;
+        if age > window {
+            return Err(FaucetError::InvalidSignature(format!("Request expired {}s ago", age)));
+        }
+        if age < -MAX_CLOCK_SKEW_SECS {
+            return Err(FaucetError::InvalidSignature("Request timestamp is in the future".to_string()));
+        }
+
+        let message = SignedRequest::signing_message(address, asset.unwrap_or(NATIVE_ASSET), signed.timestamp);
+        match ethereum::recover_personal_signer(&message, &signed.signature) {
+            Some(signer) if signer == *address => {}
+            Some(_) => {
+                return Err(FaucetError::InvalidSignature("Request was not signed by the requested address".to_string()))
+            }
+            None => return Err(FaucetError::InvalidSignature("Invalid signature".to_string())),
+        }
+
+        self.seen_signed_requests.insert(signed.timestamp, message, now, window)
+    }
+
     /// Validate address format
     fn validate_address(&self, address: &Address) -> FaucetResult<()> {
         if address.0 == [0u8; 20] {
@@ -269,11 +663,25 @@ impl FaucetService {
         Ok(())
     }
 
-    /// Check address cooldown
-    async fn check_address_cooldown(&self, address: &Address) -> FaucetResult<()> {
+    /// Reject contract destinations if `reject_contract_destinations` is set
+    async fn check_destination(&self, address: &Address) -> FaucetResult<()> {
+        if !self.config.reject_contract_destinations {
+            return Ok(());
+        }
+
+        let code = self.rpc_client.get_code(address).await?;
+        if !code.trim_start_matches("0x").is_empty() {
+            warn!("Refusing to fund contract 0x{}", hex::encode(address.0));
+            return Err(FaucetError::ContractDestination(format!("0x{}", hex::encode(address.0))));
+        }
+        Ok(())
+    }
+
+    /// Check address cooldown for `asset`, the native coin if `None`
+    async fn check_address_cooldown(&self, address: &Address, asset: Option<&str>) -> FaucetResult<()> {
         let addr_str = format!("0x{}", hex::encode(address.0));
 
-        if let Some(last_request) = self.database.get_last_request_time(&addr_str)? {
+        if let Some(last_request) = self.database.get_last_request_time(&addr_str, asset)? {
             let elapsed = Utc::now().timestamp() - last_request;
             let cooldown = self.config.address_cooldown_duration().as_secs() as i64;
 
@@ -291,12 +699,49 @@ impl FaucetService {
         Ok(())
     }
 
-    /// Check max amount per address
-    fn check_max_amount_per_address(&self, address: &Address) -> FaucetResult<()> {
+    /// Transfer of the configured amount of `asset` to `to`, and that amount
+    fn token_transfer(&self, asset: &FaucetAsset, to: &Address) -> FaucetResult<(Transfer, u128)> {
+        let amount = asset
+            .amount
+            .parse::<u128>()
+            .map_err(|_| FaucetError::InvalidAmount(format!("Invalid amount for asset {}", asset.name)))?;
+
+        let mut contract = Address::default();
+        hex::decode_to_slice(asset.contract.trim_start_matches("0x"), &mut contract.0).map_err(|_| {
+            FaucetError::InternalError(format!("Invalid contract address for asset {}", asset.name))
+        })?;
+
+        let transfer = Transfer {
+            to: contract,
+            value: 0,
+            data: erc20_transfer_calldata(to, amount),
+            gas_limit: self.config.token_gas_limit,
+        };
+        Ok((transfer, amount))
+    }
+
+    /// Amount to send to an address, based on how many times it was served before
+    fn dispense_amount_for(&self, address: &Address) -> FaucetResult<u128> {
         let addr_str = format!("0x{}", hex::encode(address.0));
-        let total = self
+        let prior_count = self.database.get_distribution_count_for_address(&addr_str)?;
+
+        self.config
+            .dispense_amount_for(prior_count)
+            .parse::<u128>()
+            .map_err(|_| FaucetError::InvalidAmount("Invalid amount".to_string()))
+    }
+
+    /// Check the most an address may receive within the quota window
+    ///
+    /// On rejection reports how long until enough earlier distributions
+    /// leave the window for `dispense_amount` to fit.
+    fn check_max_amount_per_address(&self, address: &Address, dispense_amount: u128) -> FaucetResult<()> {
+        let addr_str = format!("0x{}", hex::encode(address.0));
+        let now = Utc::now().timestamp();
+        let window = self.config.quota_window_secs as i64;
+        let received = self
             .database
-            .get_total_amount_for_address(&addr_str)?;
+            .get_amounts_for_address_since(&addr_str, now - window)?;
 
         let max_amount = self
             .config
@@ -304,27 +749,56 @@ impl FaucetService {
             .parse::<u128>()
             .unwrap_or(u128::MAX);
 
-        let dispense_amount = self.config.dispense_amount.parse::<u128>().unwrap_or(0);
+        let mut total = received
+            .iter()
+            .fold(0u128, |total, (_, amount)| total.saturating_add(*amount));
+        if total.saturating_add(dispense_amount) <= max_amount {
+            return Ok(());
+        }
+
+        warn!(
+            "Address 0x{} exceeded max amount. Total: {}, Max: {}",
+            hex::encode(address.0),
+            total,
+            max_amount
+        );
 
-        if total + dispense_amount > max_amount {
-            warn!(
-                "Address 0x{} exceeded max amount. Total: {}, Max: {}",
-                hex::encode(address.0),
-                total,
-                max_amount
-            );
-            return Err(FaucetError::InvalidAmount(
-                "Maximum amount per address exceeded".to_string(),
-            ));
+        // Larger than the maximum itself if nothing leaving the window helps
+        let mut retry_at = now + window;
+        for (at, amount) in &received {
+            total = total.saturating_sub(*amount);
+            if total.saturating_add(dispense_amount) <= max_amount {
+                retry_at = at + window;
+                break;
+            }
         }
+        Err(FaucetError::QuotaExceeded {
+            reason: "Maximum amount per address exceeded".to_string(),
+            retry_after: (retry_at - now).max(0) as u64,
+        })
+    }
 
-        Ok(())
+    /// Reserve `amount` within the global hourly limit, returning the time
+    /// it was counted at
+    fn reserve_outflow(&self, amount: u128) -> FaucetResult<i64> {
+        let now = Utc::now().timestamp();
+        let Some(limit) = &self.config.global_max_dispense_per_hour_wei else {
+            return Ok(now);
+        };
+        let limit = limit
+            .parse::<u128>()
+            .map_err(|_| FaucetError::InvalidAmount("Invalid global dispense limit".to_string()))?;
+
+        self.outflow.reserve(now, amount, limit).map_err(|reset_at| {
+            warn!("Global dispense limit of {} wei per hour reached", limit);
+            FaucetError::GlobalRateLimited { reset_at }
+        })?;
+        Ok(now)
     }
 
-    /// Create and send transaction
-    async fn send_transaction(&self, to: &Address) -> FaucetResult<String> {
-        use k256::ecdsa::Signature;
-        use rlp::RlpStream;
+    /// Create and send transaction, returning its hash and the nonce used
+    async fn send_transaction(&self, transfer: &Transfer) -> FaucetResult<(String, u64)> {
+        let _submission_guard = self.submission_lock.lock().await;
 
         // Get nonce
         let nonce = self
@@ -335,13 +809,6 @@ impl FaucetService {
         // Get chain ID
         let chain_id = self.rpc_client.get_chain_id().await?;
 
-        // Parse amount
-        let amount = self
-            .config
-            .dispense_amount
-            .parse::<u128>()
-            .map_err(|_| FaucetError::InvalidAmount("Invalid amount".to_string()))?;
-
         // Parse gas price
         let gas_price = self
             .config
@@ -349,85 +816,128 @@ impl FaucetService {
             .parse::<u128>()
             .map_err(|_| FaucetError::InvalidAmount("Invalid gas price".to_string()))?;
 
-        // Encode legacy transaction
-        let mut stream = RlpStream::new();
-        stream.begin_list(9);
-        stream.append(&nonce);
-        stream.append(&gas_price);
-        stream.append(&self.config.gas_limit);
-        stream.append(&to.0.to_vec());
-        stream.append(&amount.to_be_bytes().to_vec());
-        stream.append(&Vec::<u8>::new()); // data
-
-        // EIP-155: add chain ID
-        stream.append(&chain_id);
-        stream.append(&0u8);
-        stream.append(&0u8);
-
-        let tx_hash = keccak_hash::keccak(&stream.out());
-
-        // Sign using Signer trait
-        let signature: Signature = self.signing_key.sign(&tx_hash.0);
-
-        // Convert r and s to byte arrays
-        let r_bytes = signature.r().to_bytes();
-        let s_bytes = signature.s().to_bytes();
-        let r_array: [u8; 32] = r_bytes.into();
-        let s_array: [u8; 32] = s_bytes.into();
-
-        // Calculate v (recovery ID)
-        // For k256, check if s is "low" (less than curve order / 2)
-        // This is a simplified check - in production use proper s-value normalization
-        let s_is_low = signature.s().to_bytes().last().map(|&b| b % 2 == 0).unwrap_or(false);
-        let v = if chain_id > 0 {
-            (chain_id * 2 + 35) as u8 + (s_is_low as u8)
-        } else {
-            27 + (s_is_low as u8)
+        // Catch failures before anything is signed or broadcast
+        self.simulate_transfer(transfer, gas_price).await?;
+
+        // Sign an EIP-155 legacy transaction the way a wallet does
+        let mut body = TransactionBody {
+            receiver: transfer.to,
+            gas: i64::try_from(transfer.gas_limit)
+                .map_err(|_| FaucetError::InvalidAmount("Invalid gas limit".to_string()))?,
+            nonce: i64::try_from(nonce)
+                .map_err(|_| FaucetError::InternalError(format!("Nonce {} out of range", nonce)))?,
+            data: transfer.data.clone(),
+            tx_type: TransactionType::EVM,
+            chain_id: (chain_id > 0).then_some(chain_id),
+            value: Some(transfer.value.to_string()),
+            gas_price: Some(
+                u64::try_from(gas_price)
+                    .map_err(|_| FaucetError::InvalidAmount("Invalid gas price".to_string()))?,
+            ),
+            ..Default::default()
         };
+        ethereum::sign(&mut body, &self.signing_key)
+            .map_err(|e| FaucetError::InternalError(format!("Failed to sign transaction: {}", e)))?;
 
-        // Encode signed transaction
-        let mut signed_stream = RlpStream::new();
-        signed_stream.begin_list(9);
-        signed_stream.append(&nonce);
-        signed_stream.append(&gas_price);
-        signed_stream.append(&self.config.gas_limit);
-        signed_stream.append(&to.0.to_vec());
-        signed_stream.append(&amount.to_be_bytes().to_vec());
-        signed_stream.append(&Vec::<u8>::new()); // data
-        signed_stream.append(&v);
-        signed_stream.append(&r_array.to_vec());
-        signed_stream.append(&s_array.to_vec());
-
-        let tx_bytes = signed_stream.out();
+        let tx_bytes = ethereum::encode_signed(&body);
         let tx_hex = format!("0x{}", hex::encode(&tx_bytes));
 
         // Send transaction
         let tx_hash = self
             .rpc_client
             .send_raw_transaction(&tx_hex)
-            .await?;
+            .await
+            .map_err(FaucetError::into_submission_failure)?;
 
         info!("Transaction sent: {}", tx_hash);
-        Ok(tx_hash)
+        Ok((tx_hash, nonce))
+    }
+
+    /// Simulate the transfer against node state, failing if it would not succeed
+    async fn simulate_transfer(&self, transfer: &Transfer, gas_price: u128) -> FaucetResult<()> {
+        let call = CallRequest {
+            from: self.faucet_address,
+            to: transfer.to,
+            value: transfer.value,
+            gas: transfer.gas_limit,
+            gas_price,
+            data: transfer.data.clone(),
+        };
+        let gas = self
+            .rpc_client
+            .estimate_gas(&call)
+            .await
+            .map_err(|e| match e {
+                FaucetError::RpcError(reason) | FaucetError::TransactionFailed(reason) => {
+                    FaucetError::SimulationFailed(reason)
+                }
+                other => other,
+            })?;
+
+        if gas > transfer.gas_limit {
+            return Err(FaucetError::SimulationFailed(format!(
+                "transfer needs {} gas, but the gas limit is {}",
+                gas, transfer.gas_limit
+            )));
+        }
+
+        debug!("Simulated transfer to 0x{}: {} gas", hex::encode(transfer.to.0), gas);
+        Ok(())
+    }
+
+    /// Fetch the node's latest block and remember it for status reports
+    pub async fn poll_chain_tip(&self) -> FaucetResult<ChainTip> {
+        let tip = self.rpc_client.get_latest_block().await?;
+        *self.chain_tip.write().unwrap() = Some(tip);
+        Ok(tip)
+    }
+
+    /// Poll the chain tip every `chain_tip_poll_secs` in the background
+    pub fn spawn_chain_tip_monitor(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let service = self.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(service.config.chain_tip_poll_secs.max(1)));
+            loop {
+                interval.tick().await;
+                if let Err(e) = service.poll_chain_tip().await {
+                    warn!("Failed to poll chain tip: {}", e);
+                }
+            }
+        })
     }
 
     /// Get faucet status
     pub async fn get_status(&self) -> FaucetResult<FaucetStatus> {
-        let balance_hex = self
-            .rpc_client
-            .get_balance(&self.faucet_address)
-            .await?;
-        let balance = u128::from_str_radix(balance_hex.trim_start_matches("0x"), 16).unwrap_or(0);
+        // Still report status while the RPC circuit is open, just without a balance
+        let balance = match self.rpc_client.get_balance(&self.faucet_address).await {
+            Ok(balance_hex) => u128::from_str_radix(balance_hex.trim_start_matches("0x"), 16)
+                .unwrap_or(0)
+                .to_string(),
+            Err(FaucetError::ServiceUnavailable(_)) => "unknown".to_string(),
+            Err(e) => return Err(e),
+        };
 
         let stats = self.database.get_statistics()?;
 
+        // A tip that was never seen or has stopped advancing means the node is stuck
+        // or unreachable
+        let chain_tip = *self.chain_tip.read().unwrap();
+        let chain_tip_age_secs =
+            chain_tip.map(|tip| (Utc::now().timestamp() as u64).saturating_sub(tip.timestamp));
+        let degraded = chain_tip_age_secs.is_none_or(|age| age > self.config.chain_tip_stale_secs);
+
         Ok(FaucetStatus {
-            address: format!("0x{}", hex::encode(self.faucet_address.0)),
-            balance: balance.to_string(),
+            address: to_checksum_address(&self.faucet_address),
+            balance,
             dispense_amount: self.config.dispense_amount.clone(),
             total_distributions: stats.total_distributions,
             unique_addresses: stats.unique_addresses,
             total_dispensed: stats.total_amount,
+            rpc_circuit: self.rpc_client.circuit_state(),
+            chain_tip_height: chain_tip.map(|tip| tip.height),
+            chain_tip_age_secs,
+            degraded,
         })
     }
 
@@ -443,6 +953,32 @@ pub struct DispenseResponse {
     pub tx_hash: String,
     pub amount: String,
     pub address: String,
+    /// Token dispensed, absent for the native coin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset: Option<String>,
+}
+
+/// State of a queued dispense request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum DispenseJobState {
+    /// Waiting for a worker
+    Queued,
+    /// Taken by a worker
+    Processing,
+    /// Dispensed
+    Completed { result: DispenseResponse },
+    /// Rejected or failed to send
+    Failed { error: String },
+}
+
+/// Queued dispense request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DispenseJob {
+    /// Tracking id
+    pub id: String,
+    #[serde(flatten)]
+    pub state: DispenseJobState,
 }
 
 /// Faucet status
@@ -454,4 +990,592 @@ pub struct FaucetStatus {
     pub total_distributions: usize,
     pub unique_addresses: u64,
     pub total_dispensed: String,
+    /// RPC circuit breaker state, if one is configured
+    pub rpc_circuit: Option<CircuitState>,
+    /// Height of the latest block seen, if any
+    pub chain_tip_height: Option<u64>,
+    /// Seconds since the latest block seen was produced
+    pub chain_tip_age_secs: Option<u64>,
+    /// The faucet's view of the chain is missing or older than `chain_tip_stale_secs`
+    pub degraded: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::{MockFaucetRpc, RetryPolicy, RetryingRpcClient};
+    use std::net::Ipv4Addr;
+
+    /// Service with a test key and a fresh database, otherwise configured by `config`
+    fn test_service(rpc: Arc<dyn FaucetRpc>, config: FaucetConfig) -> (FaucetService, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let config = FaucetConfig {
+            private_key: "0x0000000000000000000000000000000000000000000000000000000000000001"
+                .to_string(),
+            db_path: dir.path().to_string_lossy().to_string(),
+            ..config
+        };
+        let database = FaucetDatabase::new(&config.db_path).unwrap();
+        let service = FaucetService::new(config, database, rpc).unwrap();
+        (service, dir)
+    }
+
+    #[tokio::test]
+    async fn test_dispense_with_mock_rpc() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        let (service, _dir) = test_service(rpc.clone(), FaucetConfig::default());
+        let recipient = Address([0x42; 20]);
+
+        let response = service
+            .dispense(recipient, IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await
+            .unwrap();
+
+        let sent = rpc.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        assert!(response.tx_hash.starts_with("0x"));
+        assert_eq!(response.address, format!("0x{}", hex::encode(recipient.0)));
+
+        let status = service.get_status().await.unwrap();
+        assert_eq!(status.total_distributions, 1);
+        assert_eq!(status.unique_addresses, 1);
+
+        // Second request for the same address is rejected by the cooldown
+        let second = service
+            .dispense(recipient, IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await;
+        assert!(matches!(second, Err(FaucetError::RateLimitExceeded(_))));
+    }
+
+    /// Faucet RPC submitting to an in-process node, other calls answered by `mock`
+    struct NodeRpc {
+        mock: MockFaucetRpc,
+        node: norn_rpc::ethereum::EthereumRpcImpl,
+    }
+
+    #[async_trait::async_trait]
+    impl FaucetRpc for NodeRpc {
+        async fn get_balance(&self, address: &Address) -> FaucetResult<String> {
+            self.mock.get_balance(address).await
+        }
+
+        async fn get_transaction_count(&self, address: &Address) -> FaucetResult<u64> {
+            self.mock.get_transaction_count(address).await
+        }
+
+        async fn get_code(&self, address: &Address) -> FaucetResult<String> {
+            self.mock.get_code(address).await
+        }
+
+        async fn send_raw_transaction(&self, tx_data: &str) -> FaucetResult<String> {
+            use norn_rpc::ethereum::EthereumRpcServer;
+            let hash = self
+                .node
+                .send_raw_transaction(tx_data.to_string())
+                .await
+                .map_err(|e| FaucetError::RpcError(e.message().to_string()))?;
+            Ok(format!("0x{}", hex::encode(hash.0)))
+        }
+
+        async fn get_chain_id(&self) -> FaucetResult<u64> {
+            self.mock.get_chain_id().await
+        }
+
+        async fn estimate_gas(&self, call: &CallRequest) -> FaucetResult<u64> {
+            self.mock.estimate_gas(call).await
+        }
+
+        async fn get_latest_block(&self) -> FaucetResult<ChainTip> {
+            self.mock.get_latest_block().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispensed_transaction_accepted_by_node() {
+        use norn_rpc::ethereum::EthereumRpcServer;
+
+        let node_dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(norn_storage::SledDB::new(node_dir.path().to_str().unwrap()).unwrap());
+        let blockchain = norn_core::blockchain::Blockchain::new_with_fixed_genesis(db).await;
+        let state_manager = Arc::new(norn_core::state::AccountStateManager::default());
+        let evm_executor = Arc::new(norn_core::evm::EVMExecutor::new(
+            state_manager.clone(),
+            norn_core::evm::EVMConfig::default(),
+        ));
+        let tx_pool = Arc::new(norn_core::TxPool::new());
+        let node = norn_rpc::ethereum::EthereumRpcImpl::new(blockchain, state_manager.clone(), evm_executor, tx_pool.clone(), 31337);
+        let rpc = Arc::new(NodeRpc {
+            mock: MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000),
+            node,
+        });
+        let (service, _dir) = test_service(rpc.clone(), FaucetConfig::default());
+        state_manager
+            .add_balance(&service.faucet_address, &num_bigint::BigUint::from(10_000_000_000_000_000_000_000u128))
+            .await
+            .unwrap();
+
+        let response = service
+            .dispense(Address([0x42; 20]), IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await
+            .unwrap();
+
+        // The node recovered the faucet as the sender and kept the exact bytes
+        let mut hash = norn_common::types::Hash::default();
+        hash.0.copy_from_slice(&hex::decode(response.tx_hash.trim_start_matches("0x")).unwrap());
+        let pooled = tx_pool.get(&hash).unwrap();
+        assert_eq!(pooled.body.address, service.faucet_address);
+        assert_eq!(pooled.body.receiver, Address([0x42; 20]));
+        let raw = rpc.node.get_raw_transaction_by_hash(hash).await.unwrap().unwrap();
+        let raw = hex::decode(raw.trim_start_matches("0x")).unwrap();
+        assert_eq!(keccak_hash::keccak(&raw).0, hash.0);
+    }
+
+    #[tokio::test]
+    async fn test_dispense_rejects_when_faucet_low() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 0));
+        let (service, _dir) = test_service(rpc.clone(), FaucetConfig::default());
+
+        let result = service
+            .dispense(Address([0x42; 20]), IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await;
+
+        assert!(matches!(result, Err(FaucetError::InsufficientFunds)));
+        assert!(rpc.sent_transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dispense_succeeds_after_transient_rpc_failures() {
+        let mock = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        let rpc = Arc::new(RetryingRpcClient::new(
+            mock.clone(),
+            RetryPolicy {
+                max_retries: 3,
+                base_delay: Duration::from_millis(1),
+            },
+        ));
+        let (service, _dir) = test_service(rpc, FaucetConfig::default());
+
+        mock.fail_next(2);
+        let response = service
+            .dispense(Address([0x42; 20]), IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await
+            .unwrap();
+
+        assert!(response.tx_hash.starts_with("0x"));
+        assert_eq!(mock.sent_transactions().len(), 1);
+        // balance + nonce + chain id + simulation + submit, plus the two failed attempts
+        assert_eq!(mock.call_count(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_dispense_recorded_when_submit_reply_is_lost() {
+        let mock = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        let rpc = Arc::new(RetryingRpcClient::new(
+            mock.clone(),
+            RetryPolicy {
+                max_retries: 3,
+                base_delay: Duration::from_millis(1),
+            },
+        ));
+        let (service, _dir) = test_service(rpc, FaucetConfig::default());
+
+        // The node accepts the transaction but the reply times out
+        mock.lose_next_replies(1);
+        let recipient = Address([0x42; 20]);
+        let response = service
+            .dispense(recipient, IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await
+            .unwrap();
+
+        let sent = mock.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        let raw = hex::decode(sent[0].trim_start_matches("0x")).unwrap();
+        assert_eq!(response.tx_hash, format!("0x{}", hex::encode(keccak_hash::keccak(&raw).0)));
+        assert_eq!(service.get_status().await.unwrap().total_distributions, 1);
+
+        // The cooldown applies as for any other dispense
+        let result = service
+            .dispense(recipient, IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await;
+        assert!(matches!(result, Err(FaucetError::RateLimitExceeded(_))), "{:?}", result);
+        assert_eq!(mock.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_verify_request_signature() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        let (service, _dir) = test_service(rpc, FaucetConfig::default());
+
+        let key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let address = ethereum::address_of(key.verifying_key());
+        let sign_for = |asset: &str, timestamp: i64| SignedRequest {
+            timestamp,
+            signature: ethereum::sign_personal_message(&SignedRequest::signing_message(&address, asset, timestamp), &key)
+                .unwrap(),
+        };
+        let sign = |timestamp: i64| sign_for(NATIVE_ASSET, timestamp);
+        let now = Utc::now().timestamp();
+
+        service.verify_request_signature(&address, None, Some(&sign(now))).unwrap();
+        service.verify_request_signature(&address, Some(NATIVE_ASSET), Some(&sign(now - 1))).unwrap();
+        service.verify_request_signature(&address, None, None).unwrap();
+
+        let expired = sign(now - service.config.signed_request_window_secs as i64 - 1);
+        let result = service.verify_request_signature(&address, None, Some(&expired));
+        assert!(matches!(result, Err(FaucetError::InvalidSignature(msg)) if msg.contains("expired")));
+
+        // Signed for a different timestamp than the one claimed
+        let mut mis_signed = sign(now);
+        mis_signed.timestamp -= 1;
+        let result = service.verify_request_signature(&address, None, Some(&mis_signed));
+        assert!(matches!(result, Err(FaucetError::InvalidSignature(_))));
+
+        // A valid signature for another address
+        let result = service.verify_request_signature(&Address([0x42; 20]), None, Some(&sign(now)));
+        assert!(matches!(result, Err(FaucetError::InvalidSignature(_))));
+
+        // A signature for the native coin can't be used for a token, nor the reverse
+        let result = service.verify_request_signature(&address, Some("TST"), Some(&sign(now)));
+        assert!(matches!(result, Err(FaucetError::InvalidSignature(_))));
+        service.verify_request_signature(&address, Some("TST"), Some(&sign_for("TST", now))).unwrap();
+        let result = service.verify_request_signature(&address, None, Some(&sign_for("TST", now)));
+        assert!(matches!(result, Err(FaucetError::InvalidSignature(_))));
+
+        let mut truncated = sign(now);
+        truncated.signature.pop();
+        let result = service.verify_request_signature(&address, None, Some(&truncated));
+        assert!(matches!(result, Err(FaucetError::InvalidSignature(_))));
+    }
+
+    #[tokio::test]
+    async fn test_signed_request_replay_refused() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        // A cooldown shorter than the signature window doesn't stop a replay
+        let config = FaucetConfig {
+            address_cooldown_secs: 0,
+            require_signed_requests: true,
+            ..FaucetConfig::default()
+        };
+        let (service, _dir) = test_service(rpc, config);
+
+        let key = SigningKey::from_slice(&[0x42; 32]).unwrap();
+        let address = ethereum::address_of(key.verifying_key());
+        let now = Utc::now().timestamp();
+        let signed = SignedRequest {
+            timestamp: now,
+            signature: ethereum::sign_personal_message(&SignedRequest::signing_message(&address, NATIVE_ASSET, now), &key)
+                .unwrap(),
+        };
+
+        service.verify_request_signature(&address, None, Some(&signed)).unwrap();
+        let result = service.verify_request_signature(&address, None, Some(&signed));
+        assert!(matches!(result, Err(FaucetError::InvalidSignature(msg)) if msg.contains("already used")));
+
+        // A request signed later is accepted
+        let later = SignedRequest {
+            timestamp: now + 1,
+            signature: ethereum::sign_personal_message(&SignedRequest::signing_message(&address, NATIVE_ASSET, now + 1), &key)
+                .unwrap(),
+        };
+        service.verify_request_signature(&address, None, Some(&later)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dispense_tiers_by_prior_count() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        let config = FaucetConfig {
+            address_cooldown_secs: 0,
+            dispense_tiers: vec![(0, "500".to_string()), (1, "100".to_string())],
+            ..FaucetConfig::default()
+        };
+        let (service, _dir) = test_service(rpc, config);
+        let recipient = Address([0x42; 20]);
+
+        let first = service
+            .dispense(recipient, IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await
+            .unwrap();
+        assert_eq!(first.amount, "500");
+
+        let second = service
+            .dispense(recipient, IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await
+            .unwrap();
+        assert_eq!(second.amount, "100");
+    }
+
+    #[tokio::test]
+    async fn test_quota_exceeded_reports_retry_after() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        let config = FaucetConfig {
+            address_cooldown_secs: 0,
+            dispense_amount: "600".to_string(),
+            max_amount_per_address: "1000".to_string(),
+            quota_window_secs: 3600,
+            ..FaucetConfig::default()
+        };
+        let (service, _dir) = test_service(rpc, config);
+        let recipient = Address([0x42; 20]);
+
+        service
+            .dispense(recipient, IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await
+            .unwrap();
+
+        // Fits again once the first distribution leaves the window
+        let err = service
+            .dispense(recipient, IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await
+            .unwrap_err();
+        match err {
+            FaucetError::QuotaExceeded { retry_after, .. } => {
+                assert!((3590..=3600).contains(&retry_after), "{}", retry_after)
+            }
+            other => panic!("expected QuotaExceeded, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stale_chain_tip_reports_degraded() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        let (service, _dir) = test_service(rpc.clone(), FaucetConfig::default());
+
+        // Nothing polled yet
+        let status = service.get_status().await.unwrap();
+        assert_eq!(status.chain_tip_height, None);
+        assert!(status.degraded);
+
+        let now = Utc::now().timestamp() as u64;
+        rpc.set_chain_tip(ChainTip { height: 7, timestamp: now });
+        service.poll_chain_tip().await.unwrap();
+        let status = service.get_status().await.unwrap();
+        assert_eq!(status.chain_tip_height, Some(7));
+        assert!(!status.degraded);
+
+        // Last block produced well past the staleness threshold
+        let stale = now - service.config.chain_tip_stale_secs - 60;
+        rpc.set_chain_tip(ChainTip { height: 7, timestamp: stale });
+        service.poll_chain_tip().await.unwrap();
+        let status = service.get_status().await.unwrap();
+        assert_eq!(status.chain_tip_height, Some(7));
+        assert!(status.chain_tip_age_secs.unwrap() > service.config.chain_tip_stale_secs);
+        assert!(status.degraded);
+    }
+
+    #[tokio::test]
+    async fn test_dispense_erc20_asset() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        let (mut service, _dir) = test_service(rpc.clone(), FaucetConfig::default());
+        let token = Address([0x77; 20]);
+        service.config.assets = vec![FaucetAsset {
+            name: "TST".to_string(),
+            contract: format!("0x{}", hex::encode(token.0)),
+            amount: "5000".to_string(),
+        }];
+        let recipient = Address([0x42; 20]);
+
+        let response = service
+            .dispense_asset(recipient, Some("TST"), IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await
+            .unwrap();
+        assert_eq!(response.asset.as_deref(), Some("TST"));
+        assert_eq!(response.amount, "5000");
+
+        let sent = rpc.sent_transactions();
+        let raw = hex::decode(sent[0].trim_start_matches("0x")).unwrap();
+        let tx = rlp::Rlp::new(&raw);
+        assert_eq!(tx.val_at::<Vec<u8>>(3).unwrap(), token.0.to_vec());
+        let data: Vec<u8> = tx.val_at(5).unwrap();
+        assert_eq!(data[..4], [0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(data[16..36], recipient.0);
+        assert_eq!(data, erc20_transfer_calldata(&recipient, 5000));
+
+        // The token cooldown doesn't hold back the native coin
+        service
+            .dispense(recipient, IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await
+            .unwrap();
+        let result = service
+            .dispense_asset(recipient, Some("OTHER"), IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await;
+        assert!(matches!(result, Err(FaucetError::UnknownAsset(_))), "{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_global_dispense_limit() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        let (mut service, _dir) = test_service(rpc.clone(), FaucetConfig::default());
+        let amount: u128 = service.config.dispense_amount.parse().unwrap();
+        service.config.global_max_dispense_per_hour_wei = Some((2 * amount).to_string());
+
+        for (i, recipient) in [0x42u8, 0x43].into_iter().enumerate() {
+            service
+                .dispense(Address([recipient; 20]), IpAddr::V4(Ipv4Addr::new(10, 0, 0, i as u8)), "test".to_string())
+                .await
+                .unwrap();
+        }
+
+        // A new address from a new IP is still over the faucet-wide limit
+        let result = service
+            .dispense(Address([0x44; 20]), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)), "test".to_string())
+            .await;
+        let Err(FaucetError::GlobalRateLimited { reset_at }) = result else {
+            panic!("expected global rate limit, got {:?}", result);
+        };
+        assert!(reset_at > Utc::now().timestamp());
+        assert_eq!(rpc.sent_transactions().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_global_dispense_limit_survives_restart() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        let (mut service, _dir) = test_service(rpc.clone(), FaucetConfig::default());
+        let amount: u128 = service.config.dispense_amount.parse().unwrap();
+        service.config.global_max_dispense_per_hour_wei = Some(amount.to_string());
+        service
+            .dispense(Address([0x42; 20]), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), "test".to_string())
+            .await
+            .unwrap();
+
+        // The dispense recorded before the restart still counts
+        let config = service.config.clone();
+        drop(service);
+        let database = FaucetDatabase::new(&config.db_path).unwrap();
+        let service = FaucetService::new(config, database, rpc.clone()).unwrap();
+        let result = service
+            .dispense(Address([0x43; 20]), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)), "test".to_string())
+            .await;
+        assert!(matches!(result, Err(FaucetError::GlobalRateLimited { .. })), "{:?}", result);
+        assert_eq!(rpc.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_contract_destinations_rejected() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        let (mut service, _dir) = test_service(rpc.clone(), FaucetConfig::default());
+        let contract = Address([0x42; 20]);
+        rpc.set_code(contract, vec![0x60, 0x00, 0x56]);
+
+        service.config.reject_contract_destinations = true;
+        let result = service
+            .dispense(contract, IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await;
+        assert!(matches!(result, Err(FaucetError::ContractDestination(_))), "{:?}", result);
+        assert!(rpc.sent_transactions().is_empty());
+
+        service
+            .dispense(Address([0x43; 20]), IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await
+            .unwrap();
+        assert_eq!(rpc.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_simulation_aborts_dispense() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        let (service, _dir) = test_service(rpc.clone(), FaucetConfig::default());
+        // Above the minimum balance, but less than one dispense
+        rpc.set_balance(service.faucet_address, 500_000_000_000_000_000_000);
+
+        let result = service
+            .dispense(Address([0x42; 20]), IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string())
+            .await;
+
+        assert!(
+            matches!(&result, Err(FaucetError::SimulationFailed(reason)) if reason.contains("insufficient funds")),
+            "{:?}",
+            result
+        );
+        assert!(rpc.sent_transactions().is_empty());
+        assert_eq!(service.get_status().await.unwrap().total_distributions, 0);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_and_skips_non_retryable_errors() {
+        let mock = Arc::new(MockFaucetRpc::new(31337, 0));
+        let rpc = RetryingRpcClient::new(
+            mock.clone(),
+            RetryPolicy {
+                max_retries: 2,
+                base_delay: Duration::from_millis(1),
+            },
+        );
+
+        mock.fail_next(5);
+        let result = rpc.get_chain_id().await;
+        assert!(matches!(result, Err(FaucetError::RpcConnectionError(_))));
+        assert_eq!(mock.call_count(), 3);
+
+        mock.fail_next(0);
+        let result = rpc.send_raw_transaction("0xnot-hex").await;
+        assert!(matches!(result, Err(FaucetError::TransactionFailed(_))));
+        assert_eq!(mock.call_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_dispense_same_address() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        // Let both requests be in flight at the same time
+        rpc.set_latency(Duration::from_millis(20));
+        let (service, _dir) = test_service(rpc.clone(), FaucetConfig::default());
+        let recipient = Address([0x42; 20]);
+
+        let (first, second) = tokio::join!(
+            service.dispense(recipient, IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string()),
+            service.dispense(recipient, IpAddr::V4(Ipv4Addr::LOCALHOST), "test".to_string()),
+        );
+
+        assert_eq!(first.is_ok() as u8 + second.is_ok() as u8, 1);
+        assert_eq!(rpc.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_queued_dispenses_complete_in_order() {
+        let rpc = Arc::new(MockFaucetRpc::new(31337, 10_000_000_000_000_000_000_000));
+        rpc.set_latency(Duration::from_millis(5));
+        let config = FaucetConfig {
+            dispense_workers: 2,
+            dispense_queue_capacity: 8,
+            ..FaucetConfig::default()
+        };
+        let (service, _dir) = test_service(rpc.clone(), config);
+        let service = Arc::new(service);
+        assert!(service.queues_dispenses());
+
+        let recipients: Vec<Address> = (1..=6u8).map(|i| Address([i; 20])).collect();
+        let ids: Vec<String> = recipients
+            .iter()
+            .enumerate()
+            .map(|(i, recipient)| {
+                let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, i as u8));
+                service.enqueue_dispense(*recipient, None, ip, "test".to_string()).unwrap().id
+            })
+            .collect();
+        assert!(matches!(service.dispense_job(&ids[0]).unwrap().state, DispenseJobState::Queued));
+
+        service.spawn_dispense_workers();
+        for id in &ids {
+            let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+            loop {
+                match service.dispense_job(id).unwrap().state {
+                    DispenseJobState::Completed { .. } => break,
+                    DispenseJobState::Failed { error } => panic!("request {} failed: {}", id, error),
+                    _ => {}
+                }
+                assert!(tokio::time::Instant::now() < deadline, "request {} did not complete", id);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+
+        // Transactions were sent in queue order with consecutive nonces
+        let sent = rpc.sent_transactions();
+        assert_eq!(sent.len(), recipients.len());
+        for (nonce, (raw, recipient)) in sent.iter().zip(&recipients).enumerate() {
+            let raw = hex::decode(raw.trim_start_matches("0x")).unwrap();
+            let tx = rlp::Rlp::new(&raw);
+            assert_eq!(tx.val_at::<u64>(0).unwrap(), nonce as u64);
+            assert_eq!(tx.val_at::<Vec<u8>>(3).unwrap(), recipient.0.to_vec());
+        }
+        assert!(matches!(service.dispense_job("unknown"), Err(FaucetError::JobNotFound(_))));
+    }
 }
@@ -2,10 +2,12 @@ use libp2p::gossipsub;
 use libp2p::identify;
 use libp2p::kad::{store::MemoryStore, Behaviour as KadBehaviour};
 use libp2p::swarm::NetworkBehaviour;
+use crate::direct::DirectBehaviour;
 
 #[derive(NetworkBehaviour)]
 pub struct NornBehaviour {
     pub gossipsub: gossipsub::Behaviour,
     pub kademlia: KadBehaviour<MemoryStore>,
     pub identify: identify::Behaviour,
+    pub direct: DirectBehaviour,
 }
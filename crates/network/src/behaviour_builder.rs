@@ -8,6 +8,7 @@ use libp2p::{
 };
 use std::time::Duration;
 use crate::behaviour::NornBehaviour;
+use crate::direct::DirectBehaviour;
 use std::hash::Hash;
 
 pub fn build_behaviour(keypair: &Keypair, peer_id: &PeerId) -> NornBehaviour {
@@ -47,5 +48,6 @@ pub fn build_behaviour(keypair: &Keypair, peer_id: &PeerId) -> NornBehaviour {
         gossipsub,
         kademlia,
         identify,
+        direct: DirectBehaviour::default(),
     }
 }
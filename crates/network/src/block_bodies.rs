@@ -0,0 +1,117 @@
+//! Serving block bodies requested by hash
+//!
+//! Syncing peers ask for several bodies in one `BlockBodiesRequest`; the
+//! event loop answers from a [`BlockBodyStore`] backed by local storage.
+//! Requests and responses travel as direct messages between the two peers,
+//! never over gossip.
+
+use async_trait::async_trait;
+use norn_common::types::Hash;
+
+use crate::messages::sync::{BlockBodiesRequestMessage, BlockBodiesResponseMessage, BlockBody};
+
+/// Most bodies served for a single request
+pub const MAX_BLOCK_BODIES_PER_REQUEST: usize = 128;
+
+/// Source of block bodies served to peers
+#[async_trait]
+pub trait BlockBodyStore: Send + Sync {
+    /// The body of the block with `hash`, if stored
+    async fn block_body(&self, hash: &Hash) -> Option<BlockBody>;
+}
+
+/// Answer `request` from `store`
+///
+/// Bodies come back in the requested order. Unknown blocks are skipped and
+/// hashes past [`MAX_BLOCK_BODIES_PER_REQUEST`] are ignored.
+pub async fn serve_block_bodies(
+    store: &dyn BlockBodyStore,
+    request: &BlockBodiesRequestMessage,
+) -> BlockBodiesResponseMessage {
+    let mut bodies = Vec::new();
+    for hash in request.hashes.iter().take(MAX_BLOCK_BODIES_PER_REQUEST) {
+        if let Some(body) = store.block_body(hash).await {
+            bodies.push(body);
+        }
+    }
+
+    BlockBodiesResponseMessage {
+        request_id: request.request_id,
+        bodies,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::sync::{MessageEncoder, NetworkMessage, NetworkMessageConfig, SyncMessage};
+    use norn_common::types::Transaction;
+    use std::collections::HashMap;
+
+    struct MockPeer {
+        bodies: HashMap<Hash, BlockBody>,
+    }
+
+    #[async_trait]
+    impl BlockBodyStore for MockPeer {
+        async fn block_body(&self, hash: &Hash) -> Option<BlockBody> {
+            self.bodies.get(hash).cloned()
+        }
+    }
+
+    fn body(id: u8) -> BlockBody {
+        let mut tx = Transaction::default();
+        tx.body.hash = Hash([id + 100; 32]);
+        BlockBody {
+            block_hash: Hash([id; 32]),
+            transactions: vec![tx],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_bodies_in_order() {
+        let peer = MockPeer {
+            bodies: (1..=5).map(|id| (Hash([id; 32]), body(id))).collect(),
+        };
+        let encoder = MessageEncoder::new(NetworkMessageConfig::default());
+
+        let wanted = vec![Hash([4; 32]), Hash([1; 32]), Hash([3; 32])];
+        let request = encoder
+            .encode(&NetworkMessage::Sync(SyncMessage::BlockBodiesRequest(BlockBodiesRequestMessage {
+                request_id: 7,
+                hashes: wanted.clone(),
+            })))
+            .unwrap();
+
+        // The peer decodes the request and answers it
+        let NetworkMessage::Sync(SyncMessage::BlockBodiesRequest(request)) = encoder.decode(&request).unwrap() else {
+            panic!("expected a block bodies request");
+        };
+        let response = serve_block_bodies(&peer, &request).await;
+        let response = encoder
+            .encode(&NetworkMessage::Sync(SyncMessage::BlockBodiesResponse(response)))
+            .unwrap();
+
+        let NetworkMessage::Sync(SyncMessage::BlockBodiesResponse(response)) = encoder.decode(&response).unwrap() else {
+            panic!("expected a block bodies response");
+        };
+        assert_eq!(response.request_id, 7);
+        let hashes: Vec<Hash> = response.bodies.iter().map(|b| b.block_hash).collect();
+        assert_eq!(hashes, wanted);
+        assert_eq!(response.bodies[0], body(4));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_bodies_skipped() {
+        let peer = MockPeer {
+            bodies: [(Hash([1; 32]), body(1))].into_iter().collect(),
+        };
+        let request = BlockBodiesRequestMessage {
+            request_id: 1,
+            hashes: vec![Hash([9; 32]), Hash([1; 32])],
+        };
+
+        let response = serve_block_bodies(&peer, &request).await;
+        assert_eq!(response.bodies, vec![body(1)]);
+    }
+}
@@ -5,6 +5,10 @@ pub struct NetworkConfig {
     pub listen_address: String, // e.g., "/ip4/0.0.0.0/tcp/0"
     pub bootstrap_peers: Vec<String>,
     pub mdns: bool,
+    /// Peers that receive full transactions when propagating; the rest get
+    /// announcements. Defaults to the square root of the peer count.
+    #[serde(default)]
+    pub tx_fanout: Option<usize>,
 }
 
 impl Default for NetworkConfig {
@@ -13,6 +17,7 @@ impl Default for NetworkConfig {
             listen_address: "/ip4/0.0.0.0/tcp/0".to_string(),
             bootstrap_peers: vec![],
             mdns: true,
+            tx_fanout: None,
         }
     }
 }
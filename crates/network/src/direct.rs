@@ -0,0 +1,184 @@
+//! Direct peer-to-peer messages
+//!
+//! Gossipsub floods every message to the whole topic mesh. Transaction
+//! propagation needs to reach specific peers instead (full bodies to a few,
+//! announcements to the rest), and block bodies go back to the peer that
+//! asked for them, so this behaviour opens a substream per message and
+//! writes a single length-prefixed payload to it.
+
+use std::collections::VecDeque;
+use std::task::{Context, Poll};
+use std::{io, iter};
+
+use libp2p::core::upgrade::{InboundUpgrade, OutboundUpgrade, UpgradeInfo};
+use libp2p::core::{Endpoint, Multiaddr};
+use libp2p::futures::future::BoxFuture;
+use libp2p::futures::{AsyncReadExt, AsyncWriteExt, FutureExt};
+use libp2p::swarm::handler::OneShotHandler;
+use libp2p::swarm::{
+    ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, NotifyHandler, Stream,
+    THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p::{PeerId, StreamProtocol};
+use tracing::debug;
+
+const PROTOCOL: StreamProtocol = StreamProtocol::new("/norn/direct/1.0.0");
+
+/// Largest payload accepted from a peer
+pub const MAX_DIRECT_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// A message received directly from a peer
+#[derive(Debug)]
+pub struct DirectMessage {
+    pub peer: PeerId,
+    pub data: Vec<u8>,
+}
+
+/// Sends and receives single messages over dedicated substreams
+#[derive(Default)]
+pub struct DirectBehaviour {
+    pending: VecDeque<ToSwarm<DirectMessage, OutboundMessage>>,
+}
+
+impl DirectBehaviour {
+    /// Queue `data` for delivery to `peer`
+    pub fn send(&mut self, peer: PeerId, data: Vec<u8>) {
+        self.pending.push_back(ToSwarm::NotifyHandler {
+            peer_id: peer,
+            handler: NotifyHandler::Any,
+            event: OutboundMessage(data),
+        });
+    }
+}
+
+type Handler = OneShotHandler<InboundProtocol, OutboundMessage, HandlerEvent>;
+
+impl NetworkBehaviour for DirectBehaviour {
+    type ConnectionHandler = Handler;
+    type ToSwarm = DirectMessage;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        _peer: PeerId,
+        _local_addr: &Multiaddr,
+        _remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::default())
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        _peer: PeerId,
+        _addr: &Multiaddr,
+        _role_override: Endpoint,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(Handler::default())
+    }
+
+    fn on_swarm_event(&mut self, _event: FromSwarm) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        _connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        match event {
+            Ok(HandlerEvent::Received(data)) => {
+                self.pending
+                    .push_back(ToSwarm::GenerateEvent(DirectMessage { peer: peer_id, data }));
+            }
+            Ok(HandlerEvent::Sent) => {}
+            Err(e) => debug!("Direct message to {} failed: {}", peer_id, e),
+        }
+    }
+
+    fn poll(&mut self, _cx: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        match self.pending.pop_front() {
+            Some(event) => Poll::Ready(event),
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum HandlerEvent {
+    Received(Vec<u8>),
+    Sent,
+}
+
+impl From<Vec<u8>> for HandlerEvent {
+    fn from(data: Vec<u8>) -> Self {
+        HandlerEvent::Received(data)
+    }
+}
+
+impl From<()> for HandlerEvent {
+    fn from(_: ()) -> Self {
+        HandlerEvent::Sent
+    }
+}
+
+/// Reads one message from an inbound substream
+#[derive(Debug, Clone, Default)]
+pub struct InboundProtocol;
+
+impl UpgradeInfo for InboundProtocol {
+    type Info = StreamProtocol;
+    type InfoIter = iter::Once<StreamProtocol>;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        iter::once(PROTOCOL)
+    }
+}
+
+impl InboundUpgrade<Stream> for InboundProtocol {
+    type Output = Vec<u8>;
+    type Error = io::Error;
+    type Future = BoxFuture<'static, Result<Vec<u8>, io::Error>>;
+
+    fn upgrade_inbound(self, mut socket: Stream, _: StreamProtocol) -> Self::Future {
+        async move {
+            let mut len = [0u8; 4];
+            socket.read_exact(&mut len).await?;
+            let len = u32::from_be_bytes(len) as usize;
+            if len > MAX_DIRECT_MESSAGE_SIZE {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "direct message too large"));
+            }
+            let mut data = vec![0u8; len];
+            socket.read_exact(&mut data).await?;
+            Ok(data)
+        }
+        .boxed()
+    }
+}
+
+/// Writes one message to an outbound substream
+#[derive(Debug)]
+pub struct OutboundMessage(Vec<u8>);
+
+impl UpgradeInfo for OutboundMessage {
+    type Info = StreamProtocol;
+    type InfoIter = iter::Once<StreamProtocol>;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        iter::once(PROTOCOL)
+    }
+}
+
+impl OutboundUpgrade<Stream> for OutboundMessage {
+    type Output = ();
+    type Error = io::Error;
+    type Future = BoxFuture<'static, Result<(), io::Error>>;
+
+    fn upgrade_outbound(self, mut socket: Stream, _: StreamProtocol) -> Self::Future {
+        async move {
+            socket.write_all(&(self.0.len() as u32).to_be_bytes()).await?;
+            socket.write_all(&self.0).await?;
+            socket.close().await
+        }
+        .boxed()
+    }
+}
@@ -1,16 +1,56 @@
-use libp2p::{Swarm, gossipsub};
+use std::collections::HashMap;
+use std::sync::Arc;
+use libp2p::{PeerId, Swarm, gossipsub};
 use libp2p::futures::StreamExt;
+use norn_common::types::{Hash, Transaction};
+use norn_common::utils::codec;
 use crate::behaviour::NornBehaviour;
+use crate::block_bodies::{serve_block_bodies, BlockBodyStore};
+use crate::direct::DirectMessage;
+use crate::messages::sync::{
+    BlockBodiesRequestMessage, BlockBody, MessageEncoder, NetworkMessage, NetworkMessageConfig, SyncMessage,
+    TransactionAnnouncementMessage, TransactionBroadcastMessage, TransactionMessage,
+    TransactionRequestMessage, TransactionResponseMessage,
+};
+use crate::propagation::{
+    plan_tx_propagation, TransactionStore, TxFetcher, TxRequest, MAX_ANNOUNCED_TXS, TX_REQUEST_TIMEOUT,
+};
+use crate::status::NetworkStatus;
 use crate::topics::Topics;
 use super::service::{NetworkCommand, NetworkEvent};
-use tokio::sync::mpsc;
-use tracing::{info, error};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, info, error};
+
+/// How often transaction requests are checked for missed deadlines
+const TX_REQUEST_SWEEP_INTERVAL: Duration = Duration::from_secs(1);
 
 pub struct EventLoop {
     swarm: Swarm<NornBehaviour>,
     command_rx: mpsc::Receiver<NetworkCommand>,
     event_tx: mpsc::Sender<NetworkEvent>,
     topics: Topics,
+    encoder: MessageEncoder,
+    /// Storage answering block body requests; requests are ignored without one
+    block_store: Option<Arc<dyn BlockBodyStore>>,
+    /// Connected peers and open listeners, kept up to date for readers
+    status: NetworkStatus,
+    /// Peers sent full transactions; `None` uses the square-root default
+    tx_fanout: Option<usize>,
+    /// Transactions served to peers and checked before requesting announced ones
+    tx_store: Option<Arc<dyn TransactionStore>>,
+    /// Announced transactions requested from peers
+    tx_fetcher: TxFetcher<PeerId>,
+    /// Block body requests sent to peers, by request id
+    body_requests: HashMap<u64, BodyRequest>,
+    next_body_request: u64,
+}
+
+/// A block body request waiting for the peer's answer
+struct BodyRequest {
+    peer: PeerId,
+    hashes: Vec<Hash>,
+    reply: oneshot::Sender<Vec<BlockBody>>,
 }
 
 impl EventLoop {
@@ -24,16 +64,55 @@ impl EventLoop {
             command_rx,
             event_tx,
             topics: Topics::new(),
+            encoder: MessageEncoder::new(NetworkMessageConfig::default()),
+            block_store: None,
+            status: NetworkStatus::default(),
+            tx_fanout: None,
+            tx_store: None,
+            tx_fetcher: TxFetcher::new(TX_REQUEST_TIMEOUT),
+            body_requests: HashMap::new(),
+            next_body_request: 0,
         }
     }
 
+    /// Report connected peers and open listeners through `status`
+    pub fn with_status(mut self, status: NetworkStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Serve block body requests from `store`
+    pub fn with_block_store(mut self, store: Arc<dyn BlockBodyStore>) -> Self {
+        self.block_store = Some(store);
+        self
+    }
+
+    /// Send full transactions to `fanout` peers and announce them to the rest
+    pub fn with_tx_fanout(mut self, fanout: Option<usize>) -> Self {
+        self.tx_fanout = fanout;
+        self
+    }
+
+    /// Answer transaction requests from `store`
+    pub fn with_tx_store(mut self, store: Arc<dyn TransactionStore>) -> Self {
+        self.tx_store = Some(store);
+        self
+    }
+
     pub async fn run(mut self) {
         // Subscribe to topics
         let _ = self.swarm.behaviour_mut().gossipsub.subscribe(&self.topics.block);
         let _ = self.swarm.behaviour_mut().gossipsub.subscribe(&self.topics.transaction);
-        
+        let _ = self.swarm.behaviour_mut().gossipsub.subscribe(&self.topics.sync);
+
+        let mut tx_request_sweep = tokio::time::interval(TX_REQUEST_SWEEP_INTERVAL);
         loop {
             tokio::select! {
+                _ = tx_request_sweep.tick() => {
+                    for request in self.tx_fetcher.expire(Instant::now()) {
+                        self.send_tx_request(request);
+                    }
+                }
                 event = self.swarm.next() => {
                     self.handle_swarm_event(event).await;
                 }
@@ -55,9 +134,20 @@ impl EventLoop {
                 }
             },
             NetworkCommand::BroadcastTransaction(data) => {
-                if let Err(e) = self.swarm.behaviour_mut().gossipsub.publish(self.topics.transaction.clone(), data) {
-                    error!("Broadcast transaction failed: {:?}", e);
-                }
+                self.propagate_transaction(data);
+            },
+            NetworkCommand::RequestBlockBodies { peer, hashes, reply } => {
+                // Forget requests whose caller gave up waiting
+                self.body_requests.retain(|_, request| !request.reply.is_closed());
+
+                let request_id = self.next_body_request;
+                self.next_body_request += 1;
+                let request = BlockBodiesRequestMessage { request_id, hashes: hashes.clone() };
+                self.body_requests.insert(request_id, BodyRequest { peer, hashes, reply });
+                self.send_message(peer, &NetworkMessage::Sync(SyncMessage::BlockBodiesRequest(request)));
+            },
+            NetworkCommand::AnnounceStatus(status) => {
+                self.publish_sync(SyncMessage::SyncStatus(status));
             },
             NetworkCommand::StartListening => {
                 // Handled via external setup or if we want to start listener dynamically
@@ -69,18 +159,384 @@ impl EventLoop {
         // Simplified handling
         match event {
             Some(libp2p::swarm::SwarmEvent::Behaviour(crate::behaviour::NornBehaviourEvent::Gossipsub(
-                gossipsub::Event::Message { propagation_source: _, message_id: _, message }
+                gossipsub::Event::Message { propagation_source, message_id: _, message }
             ))) => {
                 if message.topic == self.topics.block.hash() {
                     let _ = self.event_tx.send(NetworkEvent::BlockReceived(message.data)).await;
                 } else if message.topic == self.topics.transaction.hash() {
                     let _ = self.event_tx.send(NetworkEvent::TransactionReceived(message.data)).await;
+                } else if message.topic == self.topics.sync.hash() {
+                    // Statuses are tracked per author, not per relaying peer
+                    let source = message.source.unwrap_or(propagation_source);
+                    self.handle_sync_message(source, &message.data).await;
                 }
             },
+            Some(libp2p::swarm::SwarmEvent::Behaviour(crate::behaviour::NornBehaviourEvent::Direct(message))) => {
+                self.handle_direct_message(message).await;
+            },
             Some(libp2p::swarm::SwarmEvent::NewListenAddr { address, .. }) => {
                 info!("Listening on {:?}", address);
+                self.status.listener_opened();
+            },
+            Some(libp2p::swarm::SwarmEvent::ExpiredListenAddr { address, .. }) => {
+                info!("No longer listening on {:?}", address);
+                self.status.listener_closed();
+            },
+            Some(libp2p::swarm::SwarmEvent::ListenerClosed { addresses, .. }) => {
+                // The addresses expire without their own events
+                for _ in addresses {
+                    self.status.listener_closed();
+                }
             },
+            Some(libp2p::swarm::SwarmEvent::ConnectionEstablished { peer_id, num_established, .. }) => {
+                // Count peers, not connections
+                if num_established.get() == 1 {
+                    debug!("Peer connected: {}", peer_id);
+                    self.status.peer_connected();
+                }
+            },
+            Some(libp2p::swarm::SwarmEvent::ConnectionClosed { peer_id, num_established, .. }) => {
+                if num_established == 0 {
+                    debug!("Peer disconnected: {}", peer_id);
+                    self.status.peer_disconnected();
+                    // Requests to a gone peer will never be answered
+                    for request in self.tx_fetcher.peer_disconnected(&peer_id, Instant::now()) {
+                        self.send_tx_request(request);
+                    }
+                    self.body_requests.retain(|_, request| request.peer != peer_id);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    async fn handle_sync_message(&mut self, source: PeerId, data: &[u8]) {
+        let message = match self.encoder.decode(data) {
+            Ok(NetworkMessage::Sync(message)) => message,
+            Ok(_) => return,
+            Err(e) => {
+                debug!("Undecodable sync message: {}", e);
+                return;
+            }
+        };
+
+        match message {
+            SyncMessage::SyncStatus(status) => {
+                let _ = self.event_tx.send(NetworkEvent::PeerStatusReceived { peer: source, status }).await;
+            }
+            // Block bodies are only exchanged directly with the requesting peer
+            SyncMessage::BlockBodiesRequest(_) | SyncMessage::BlockBodiesResponse(_) => {
+                debug!("Ignoring block bodies message gossiped by {}", source);
+            }
             _ => {}
         }
     }
+
+    /// Answer block body requests and route responses to their requester
+    async fn handle_direct_sync(&mut self, peer: PeerId, message: SyncMessage) {
+        match message {
+            SyncMessage::BlockBodiesRequest(request) => {
+                let Some(store) = self.block_store.clone() else {
+                    return;
+                };
+                let response = serve_block_bodies(store.as_ref(), &request).await;
+                debug!(
+                    "Serving {} of {} block bodies requested by {}",
+                    response.bodies.len(),
+                    request.hashes.len(),
+                    peer
+                );
+                self.send_message(peer, &NetworkMessage::Sync(SyncMessage::BlockBodiesResponse(response)));
+            }
+            SyncMessage::BlockBodiesResponse(response) => {
+                // Only accept answers to our own requests, from the peer asked
+                let Some(request) = self.body_requests.remove(&response.request_id) else {
+                    return;
+                };
+                if request.peer != peer {
+                    self.body_requests.insert(response.request_id, request);
+                    return;
+                }
+                let bodies = response
+                    .bodies
+                    .into_iter()
+                    .filter(|body| request.hashes.contains(&body.block_hash))
+                    .collect();
+                let _ = request.reply.send(bodies);
+            }
+            _ => {}
+        }
+    }
+
+    /// Send a transaction in full to a fanout of peers and announce its hash
+    /// to the others
+    fn propagate_transaction(&mut self, data: Vec<u8>) {
+        let tx = match codec::deserialize::<Transaction>(&data) {
+            Ok(tx) => tx,
+            Err(e) => {
+                error!("Broadcast transaction failed: undecodable transaction: {}", e);
+                return;
+            }
+        };
+
+        let peers: Vec<PeerId> = self.swarm.connected_peers().copied().collect();
+        let plan = plan_tx_propagation(&peers, self.tx_fanout);
+        debug!(
+            "Propagating tx {} in full to {} peers, announcing to {}",
+            tx.body.hash,
+            plan.full.len(),
+            plan.announce.len()
+        );
+
+        let tx_hashes = vec![tx.body.hash];
+        let full = TransactionMessage::TransactionBroadcast(TransactionBroadcastMessage {
+            transaction: tx,
+            source_node: None,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        });
+        for peer in plan.full {
+            self.send_direct(peer, full.clone());
+        }
+
+        let announcement = TransactionMessage::TransactionAnnouncement(TransactionAnnouncementMessage { tx_hashes });
+        for peer in plan.announce {
+            self.send_direct(peer, announcement.clone());
+        }
+    }
+
+    async fn handle_direct_message(&mut self, message: DirectMessage) {
+        let DirectMessage { peer, data } = message;
+        let message = match self.encoder.decode(&data) {
+            Ok(message) => message,
+            Err(e) => {
+                debug!("Undecodable direct message from {}: {}", peer, e);
+                return;
+            }
+        };
+        let message = match message {
+            NetworkMessage::Transaction(message) => message,
+            NetworkMessage::Sync(message) => return self.handle_direct_sync(peer, message).await,
+            _ => return,
+        };
+
+        match message {
+            TransactionMessage::TransactionBroadcast(broadcast) => {
+                self.transaction_received(&broadcast.transaction).await;
+            }
+            TransactionMessage::TransactionAnnouncement(announcement) => {
+                for hash in announcement.tx_hashes.into_iter().take(MAX_ANNOUNCED_TXS) {
+                    if !self.tx_fetcher.is_pending(&hash) && self.knows_transaction(&hash).await {
+                        continue;
+                    }
+                    if let Some(request) = self.tx_fetcher.announced(peer, hash, Instant::now()) {
+                        self.send_tx_request(request);
+                    }
+                }
+            }
+            TransactionMessage::TransactionRequest(request) => {
+                let transaction = match &self.tx_store {
+                    Some(store) => store.transaction(&request.tx_hash).await,
+                    None => None,
+                };
+                let response = TransactionResponseMessage {
+                    request_id: request.request_id,
+                    found: transaction.is_some(),
+                    transaction,
+                };
+                self.send_direct(peer, TransactionMessage::TransactionResponse(response));
+            }
+            TransactionMessage::TransactionResponse(response) => {
+                // Only accept answers to our own requests, from the peer asked
+                let Some(hash) = self.tx_fetcher.response(&peer, response.request_id) else {
+                    return;
+                };
+                match response.transaction.filter(|tx| tx.body.hash == hash) {
+                    Some(tx) => {
+                        self.tx_fetcher.fetched(&hash);
+                        self.transaction_received(&tx).await;
+                    }
+                    None => {
+                        if let Some(request) = self.tx_fetcher.retry(&hash, Instant::now()) {
+                            self.send_tx_request(request);
+                        }
+                    }
+                }
+            }
+            TransactionMessage::TransactionPoolStatus(_) => {}
+        }
+    }
+
+    async fn knows_transaction(&mut self, hash: &Hash) -> bool {
+        match &self.tx_store {
+            Some(store) => store.transaction(hash).await.is_some(),
+            None => false,
+        }
+    }
+
+    fn send_tx_request(&mut self, request: TxRequest<PeerId>) {
+        let TxRequest { peer, request_id, hash } = request;
+        self.send_direct(
+            peer,
+            TransactionMessage::TransactionRequest(TransactionRequestMessage { request_id, tx_hash: hash }),
+        );
+    }
+
+    async fn transaction_received(&mut self, tx: &Transaction) {
+        match codec::serialize(tx) {
+            Ok(data) => {
+                let _ = self.event_tx.send(NetworkEvent::TransactionReceived(data)).await;
+            }
+            Err(e) => error!("Failed to encode received transaction: {}", e),
+        }
+    }
+
+    fn send_direct(&mut self, peer: PeerId, message: TransactionMessage) {
+        self.send_message(peer, &NetworkMessage::Transaction(message));
+    }
+
+    fn send_message(&mut self, peer: PeerId, message: &NetworkMessage) {
+        match self.encoder.encode(message) {
+            Ok(data) => self.swarm.behaviour_mut().direct.send(peer, data),
+            Err(e) => error!("Failed to encode direct message: {}", e),
+        }
+    }
+
+    fn publish_sync(&mut self, message: SyncMessage) {
+        let data = match self.encoder.encode(&NetworkMessage::Sync(message)) {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Failed to encode sync message: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = self.swarm.behaviour_mut().gossipsub.publish(self.topics.sync.clone(), data) {
+            error!("Publish sync message failed: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NetworkConfig;
+    use crate::service::NetworkService;
+    use async_trait::async_trait;
+    use libp2p::identity::Keypair;
+    use std::time::Duration;
+
+    struct MapStore(HashMap<Hash, Transaction>);
+
+    #[async_trait]
+    impl TransactionStore for MapStore {
+        async fn transaction(&self, hash: &Hash) -> Option<Transaction> {
+            self.0.get(hash).cloned()
+        }
+    }
+
+    async fn start_node(listen: String, bootstrap: Vec<String>, txs: Vec<Transaction>) -> NetworkService {
+        let config = NetworkConfig {
+            listen_address: listen,
+            bootstrap_peers: bootstrap,
+            mdns: false,
+            tx_fanout: Some(1),
+        };
+        let store = MapStore(txs.into_iter().map(|tx| (tx.body.hash, tx)).collect());
+        NetworkService::start_with_stores(config, Keypair::generate_ed25519(), None, Some(Arc::new(store)))
+            .await
+            .unwrap()
+    }
+
+    struct MapBodies(HashMap<Hash, BlockBody>);
+
+    #[async_trait]
+    impl BlockBodyStore for MapBodies {
+        async fn block_body(&self, hash: &Hash) -> Option<BlockBody> {
+            self.0.get(hash).cloned()
+        }
+    }
+
+    async fn next_transaction(node: &mut NetworkService) -> Transaction {
+        loop {
+            match node.event_rx.recv().await {
+                Some(NetworkEvent::TransactionReceived(data)) => return codec::deserialize(&data).unwrap(),
+                Some(_) => continue,
+                None => panic!("network stopped"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tx_reaches_announced_peers() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let origin_addr = format!("/ip4/127.0.0.1/tcp/{}", port);
+
+        let mut tx = Transaction::default();
+        tx.body.hash = Hash([7; 32]);
+
+        let origin = start_node(origin_addr.clone(), vec![], vec![tx.clone()]).await;
+        let mut peers = Vec::new();
+        for _ in 0..2 {
+            peers.push(start_node("/ip4/127.0.0.1/tcp/0".to_string(), vec![origin_addr.clone()], vec![]).await);
+        }
+
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while origin.status.peer_count() < 2 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("peers did not connect");
+
+        // With a fanout of one, one peer gets the body and the other only the
+        // hash, which it then requests from the origin
+        origin
+            .command_tx
+            .send(NetworkCommand::BroadcastTransaction(codec::serialize(&tx).unwrap()))
+            .await
+            .unwrap();
+
+        for peer in &mut peers {
+            let received = tokio::time::timeout(Duration::from_secs(10), next_transaction(peer))
+                .await
+                .expect("transaction not received");
+            assert_eq!(received.body.hash, tx.body.hash);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_block_bodies_served_to_requester() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let server_addr = format!("/ip4/127.0.0.1/tcp/{}", port);
+
+        let body = BlockBody { block_hash: Hash([1; 32]), transactions: vec![Transaction::default()] };
+        let config = |listen: String, bootstrap: Vec<String>| NetworkConfig {
+            listen_address: listen,
+            bootstrap_peers: bootstrap,
+            mdns: false,
+            tx_fanout: None,
+        };
+        let store = MapBodies([(body.block_hash, body.clone())].into_iter().collect());
+        let server_key = Keypair::generate_ed25519();
+        let server_id = PeerId::from(server_key.public());
+        let _server = NetworkService::start_with_stores(config(server_addr.clone(), vec![]), server_key, Some(Arc::new(store)), None)
+            .await
+            .unwrap();
+        let client = NetworkService::start(config("/ip4/127.0.0.1/tcp/0".to_string(), vec![server_addr]), Keypair::generate_ed25519())
+            .await
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while client.status.peer_count() < 1 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .expect("peers did not connect");
+
+        let bodies = client.request_block_bodies(server_id, vec![Hash([9; 32]), body.block_hash]).await.unwrap();
+        assert_eq!(bodies, vec![body]);
+    }
 }
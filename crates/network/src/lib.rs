@@ -7,7 +7,14 @@ pub mod messages;
 pub mod event_loop;
 pub mod topics;
 pub mod compression;
+pub mod propagation;
+pub mod block_bodies;
+pub mod status;
+pub mod direct;
 
 pub use service::NetworkService;
 pub use config::NetworkConfig;
+pub use propagation::{plan_tx_propagation, PropagationPlan, TransactionStore};
+pub use block_bodies::BlockBodyStore;
+pub use status::NetworkStatus;
 pub use compression::{Compressor, CompressionConfig, CompressionAlgorithm, CompressionLevel};
@@ -55,6 +55,12 @@ pub enum SyncMessage {
     
     /// 区块头响应
     HeaderResponse(HeaderResponseMessage),
+
+    /// 按哈希批量请求区块体
+    BlockBodiesRequest(BlockBodiesRequestMessage),
+
+    /// 区块体响应
+    BlockBodiesResponse(BlockBodiesResponseMessage),
     
     /// 同步状态
     SyncStatus(SyncStatusMessage),
@@ -88,6 +94,9 @@ pub enum TransactionMessage {
     /// 交易广播
     TransactionBroadcast(TransactionBroadcastMessage),
     
+    /// 交易哈希公告，对方缺少时再请求交易体
+    TransactionAnnouncement(TransactionAnnouncementMessage),
+
     /// 交易请求
     TransactionRequest(TransactionRequestMessage),
     
@@ -264,6 +273,36 @@ pub struct HeaderResponseMessage {
     pub total_headers: u64,
 }
 
+/// 区块体请求消息
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlockBodiesRequestMessage {
+    /// 请求 ID
+    pub request_id: u64,
+
+    /// 区块哈希列表
+    pub hashes: Vec<Hash>,
+}
+
+/// 区块体
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlockBody {
+    /// 区块哈希
+    pub block_hash: Hash,
+
+    /// 交易列表
+    pub transactions: Vec<Transaction>,
+}
+
+/// 区块体响应消息
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlockBodiesResponseMessage {
+    /// 请求 ID
+    pub request_id: u64,
+
+    /// 区块体列表，按请求顺序排列，缺失的区块被跳过
+    pub bodies: Vec<BlockBody>,
+}
+
 /// 同步状态消息
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SyncStatusMessage {
@@ -410,6 +449,13 @@ pub struct TransactionBroadcastMessage {
     pub timestamp: u64,
 }
 
+/// 交易哈希公告消息
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransactionAnnouncementMessage {
+    /// 公告的交易哈希
+    pub tx_hashes: Vec<Hash>,
+}
+
 /// 交易请求消息
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TransactionRequestMessage {
@@ -741,6 +787,14 @@ impl MessageValidator {
                     return Err("Empty response with more flag".into());
                 }
             }
+            SyncMessage::BlockBodiesRequest(req) => {
+                if req.hashes.is_empty() {
+                    return Err("Empty block bodies request".into());
+                }
+                if req.hashes.len() > crate::block_bodies::MAX_BLOCK_BODIES_PER_REQUEST {
+                    return Err("Too many block bodies requested".into());
+                }
+            }
             _ => {
                 // 其他同步消息的验证
             }
@@ -782,6 +836,11 @@ impl MessageValidator {
                     return Err("Empty transaction signature".into());
                 }
             }
+            TransactionMessage::TransactionAnnouncement(announcement) => {
+                if announcement.tx_hashes.is_empty() {
+                    return Err("Empty transaction announcement".into());
+                }
+            }
             TransactionMessage::TransactionRequest(_) => {
                 // 交易请求总是有效
             }
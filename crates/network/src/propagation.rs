@@ -0,0 +1,277 @@
+//! Transaction propagation peer selection
+//!
+//! Sending every transaction in full to every peer wastes bandwidth. As in
+//! Ethereum's devp2p, full bodies go to a subset of peers (by default the
+//! square root of the peer count) and the remaining peers only receive a
+//! `TransactionAnnouncement` with the hash, requesting the body if they
+//! don't have it yet.
+
+use async_trait::async_trait;
+use norn_common::types::{Hash, Transaction};
+use rand::seq::SliceRandom;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Most hashes acted on from a single announcement
+pub const MAX_ANNOUNCED_TXS: usize = 256;
+
+/// How long a peer has to answer a transaction request before another
+/// announcer is asked
+pub const TX_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Most transactions requested at once; announcements of others are ignored
+pub const MAX_PENDING_TX_REQUESTS: usize = 4096;
+
+/// Most peers remembered as announcers of a transaction being requested
+pub const MAX_TX_ANNOUNCERS: usize = 8;
+
+/// Source of transactions requested by peers after an announcement
+#[async_trait]
+pub trait TransactionStore: Send + Sync {
+    /// The transaction with `hash`, if known locally
+    async fn transaction(&self, hash: &Hash) -> Option<Transaction>;
+}
+
+/// Which peers get a transaction in full and which only get its hash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropagationPlan<P> {
+    /// Peers sent the full transaction
+    pub full: Vec<P>,
+    /// Peers sent a hash announcement
+    pub announce: Vec<P>,
+}
+
+/// Number of peers that receive full transactions
+///
+/// `fanout` overrides the square-root default. At least one peer gets the
+/// full body whenever there are peers at all.
+pub fn tx_fanout(peer_count: usize, fanout: Option<usize>) -> usize {
+    let default = (peer_count as f64).sqrt().ceil() as usize;
+    fanout.unwrap_or(default).max(1).min(peer_count)
+}
+
+/// Split `peers` into full-transaction and announcement recipients
+///
+/// Full recipients are picked at random so the same peers don't always carry
+/// the bodies.
+pub fn plan_tx_propagation<P: Clone>(peers: &[P], fanout: Option<usize>) -> PropagationPlan<P> {
+    let mut shuffled = peers.to_vec();
+    shuffled.shuffle(&mut rand::thread_rng());
+
+    let announce = shuffled.split_off(tx_fanout(peers.len(), fanout));
+    PropagationPlan { full: shuffled, announce }
+}
+
+/// A transaction request to send to a peer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxRequest<P> {
+    pub peer: P,
+    pub request_id: u64,
+    pub hash: Hash,
+}
+
+/// An announced transaction being requested
+struct TxFetch<P> {
+    request_id: u64,
+    peer: P,
+    deadline: Instant,
+    /// Other peers that announced it, asked in turn if `peer` doesn't deliver
+    announcers: VecDeque<P>,
+}
+
+/// Announced transactions requested from peers
+///
+/// Each transaction is requested from one announcer at a time. When that
+/// peer misses the deadline, answers without the transaction or disconnects,
+/// the next peer that announced it is asked; with none left the transaction
+/// is forgotten, so a peer that never answers holds nothing up for long.
+pub struct TxFetcher<P> {
+    fetches: HashMap<Hash, TxFetch<P>>,
+    /// Hash of each outstanding request
+    requests: HashMap<u64, Hash>,
+    next_request: u64,
+    timeout: Duration,
+}
+
+impl<P: Clone + PartialEq> TxFetcher<P> {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            fetches: HashMap::new(),
+            requests: HashMap::new(),
+            next_request: 0,
+            timeout,
+        }
+    }
+
+    /// Whether `hash` is being requested
+    pub fn is_pending(&self, hash: &Hash) -> bool {
+        self.fetches.contains_key(hash)
+    }
+
+    /// Number of transactions being requested
+    pub fn pending(&self) -> usize {
+        self.fetches.len()
+    }
+
+    /// `peer` announced `hash`, which isn't known locally
+    ///
+    /// Returns the request to send if `hash` isn't requested yet; otherwise
+    /// `peer` is remembered to be asked if the current request fails.
+    pub fn announced(&mut self, peer: P, hash: Hash, now: Instant) -> Option<TxRequest<P>> {
+        if let Some(fetch) = self.fetches.get_mut(&hash) {
+            if fetch.peer != peer && !fetch.announcers.contains(&peer) && fetch.announcers.len() < MAX_TX_ANNOUNCERS {
+                fetch.announcers.push_back(peer);
+            }
+            return None;
+        }
+        if self.fetches.len() >= MAX_PENDING_TX_REQUESTS {
+            return None;
+        }
+        let request_id = self.issue(hash);
+        let request = TxRequest { peer: peer.clone(), request_id, hash };
+        self.fetches.insert(hash, TxFetch { request_id, peer, deadline: now + self.timeout, announcers: VecDeque::new() });
+        Some(request)
+    }
+
+    /// Hash of the outstanding request `request_id`, if it was sent to `peer`
+    pub fn response(&self, peer: &P, request_id: u64) -> Option<Hash> {
+        let hash = self.requests.get(&request_id)?;
+        let fetch = self.fetches.get(hash)?;
+        (fetch.request_id == request_id && fetch.peer == *peer).then_some(*hash)
+    }
+
+    /// `hash` was received
+    pub fn fetched(&mut self, hash: &Hash) {
+        if let Some(fetch) = self.fetches.remove(hash) {
+            self.requests.remove(&fetch.request_id);
+        }
+    }
+
+    /// The current request for `hash` failed; ask its next announcer
+    ///
+    /// Returns the request to send, or `None` once no announcer is left and
+    /// `hash` is forgotten.
+    pub fn retry(&mut self, hash: &Hash, now: Instant) -> Option<TxRequest<P>> {
+        let mut fetch = self.fetches.remove(hash)?;
+        self.requests.remove(&fetch.request_id);
+        let peer = fetch.announcers.pop_front()?;
+
+        let request_id = self.issue(*hash);
+        fetch.request_id = request_id;
+        fetch.peer = peer.clone();
+        fetch.deadline = now + self.timeout;
+        self.fetches.insert(*hash, fetch);
+        Some(TxRequest { peer, request_id, hash: *hash })
+    }
+
+    /// Retry every request whose deadline has passed, returning the requests
+    /// to send
+    pub fn expire(&mut self, now: Instant) -> Vec<TxRequest<P>> {
+        let expired: Vec<Hash> = self
+            .fetches
+            .iter()
+            .filter(|(_, fetch)| fetch.deadline <= now)
+            .map(|(hash, _)| *hash)
+            .collect();
+        expired.iter().filter_map(|hash| self.retry(hash, now)).collect()
+    }
+
+    /// `peer` is gone: forget it as an announcer and retry the requests it
+    /// would never answer, returning the requests to send
+    pub fn peer_disconnected(&mut self, peer: &P, now: Instant) -> Vec<TxRequest<P>> {
+        let mut asked = Vec::new();
+        for (hash, fetch) in self.fetches.iter_mut() {
+            fetch.announcers.retain(|announcer| announcer != peer);
+            if fetch.peer == *peer {
+                asked.push(*hash);
+            }
+        }
+        asked.iter().filter_map(|hash| self.retry(hash, now)).collect()
+    }
+
+    fn issue(&mut self, hash: Hash) -> u64 {
+        let request_id = self.next_request;
+        self.next_request += 1;
+        self.requests.insert(request_id, hash);
+        request_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_square_root_fanout() {
+        let peers: Vec<u32> = (0..25).collect();
+        let plan = plan_tx_propagation(&peers, None);
+
+        assert_eq!(plan.full.len(), 5);
+        assert_eq!(plan.announce.len(), 20);
+
+        // Every peer hears about the transaction exactly once
+        let all: HashSet<_> = plan.full.iter().chain(&plan.announce).collect();
+        assert_eq!(all.len(), peers.len());
+
+        // Non-square counts round up
+        assert_eq!(plan_tx_propagation(&(0..10).collect::<Vec<u32>>(), None).full.len(), 4);
+    }
+
+    #[test]
+    fn test_configured_fanout() {
+        let peers: Vec<u32> = (0..25).collect();
+        assert_eq!(plan_tx_propagation(&peers, Some(8)).full.len(), 8);
+        assert_eq!(plan_tx_propagation(&peers, Some(100)).full.len(), 25);
+        assert_eq!(plan_tx_propagation(&peers, Some(0)).full.len(), 1);
+
+        let none: Vec<u32> = vec![];
+        let plan = plan_tx_propagation(&none, None);
+        assert!(plan.full.is_empty() && plan.announce.is_empty());
+    }
+
+    #[test]
+    fn test_unanswered_request_moves_to_next_announcer() {
+        let start = Instant::now();
+        let hash = Hash([1; 32]);
+        let mut fetcher = TxFetcher::new(TX_REQUEST_TIMEOUT);
+
+        let first = fetcher.announced(1u32, hash, start).unwrap();
+        assert_eq!(first.peer, 1);
+        // Later announcers wait their turn
+        assert!(fetcher.announced(2, hash, start).is_none());
+        assert!(fetcher.announced(1, hash, start).is_none());
+        assert!(fetcher.is_pending(&hash));
+
+        // Peer 1 never answers
+        assert!(fetcher.expire(start + Duration::from_secs(1)).is_empty());
+        let second = fetcher.expire(start + TX_REQUEST_TIMEOUT).pop().unwrap();
+        assert_eq!(second.peer, 2);
+        assert_ne!(second.request_id, first.request_id);
+
+        // A late answer to the first request is no longer accepted
+        assert_eq!(fetcher.response(&1, first.request_id), None);
+        assert_eq!(fetcher.response(&2, second.request_id), Some(hash));
+        fetcher.fetched(&hash);
+        assert_eq!(fetcher.pending(), 0);
+    }
+
+    #[test]
+    fn test_failed_fetch_is_forgotten() {
+        let start = Instant::now();
+        let hash = Hash([2; 32]);
+        let mut fetcher = TxFetcher::new(TX_REQUEST_TIMEOUT);
+
+        fetcher.announced(1u32, hash, start).unwrap();
+        fetcher.announced(2, hash, start);
+        // Peer 1 disconnects; peer 2 is asked and doesn't have it either
+        let retried = fetcher.peer_disconnected(&1, start).pop().unwrap();
+        assert_eq!(retried.peer, 2);
+        assert!(fetcher.retry(&hash, start).is_none());
+
+        // Nothing is left behind, and a new announcement starts over
+        assert_eq!(fetcher.pending(), 0);
+        assert!(fetcher.expire(start + TX_REQUEST_TIMEOUT).is_empty());
+        assert_eq!(fetcher.announced(3, hash, start).unwrap().peer, 3);
+    }
+}
@@ -1,17 +1,33 @@
+use std::sync::Arc;
 use anyhow::Result;
 use libp2p::identity::Keypair;
 use libp2p::{PeerId, SwarmBuilder};
-use tokio::sync::mpsc;
-use tracing::info;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{info, warn};
 use crate::config::NetworkConfig;
 use crate::event_loop::EventLoop;
 use crate::transport::build_transport;
 use crate::behaviour_builder::build_behaviour;
+use crate::block_bodies::BlockBodyStore;
+use crate::propagation::TransactionStore;
+use crate::messages::sync::{BlockBody, SyncStatusMessage};
+use crate::status::NetworkStatus;
+use norn_common::types::Hash;
+
+/// How long to wait for a peer to answer a block body request
+pub const BLOCK_BODIES_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
 
 #[derive(Debug)] // Add Debug trait for easier debugging
 pub enum NetworkCommand {
     BroadcastBlock(Vec<u8>),
     BroadcastTransaction(Vec<u8>),
+    /// Ask `peer` for the bodies of the blocks with `hashes`
+    ///
+    /// The bodies the peer returned are sent to `reply`; it is dropped if the
+    /// peer disconnects first.
+    RequestBlockBodies { peer: PeerId, hashes: Vec<Hash>, reply: oneshot::Sender<Vec<BlockBody>> },
+    /// Tell peers about the local chain tip
+    AnnounceStatus(SyncStatusMessage),
     StartListening,
 }
 
@@ -20,16 +36,31 @@ pub enum NetworkEvent {
     BlockReceived(Vec<u8>),
     TransactionReceived(Vec<u8>),
     ConsensusMessageReceived(Vec<u8>),
+    /// `peer` announced its chain tip
+    PeerStatusReceived { peer: PeerId, status: SyncStatusMessage },
 }
 
 pub struct NetworkService {
     pub command_tx: mpsc::Sender<NetworkCommand>,
     pub event_rx: mpsc::Receiver<NetworkEvent>,
     pub local_peer_id: PeerId,
+    /// Connected peers and open listeners
+    pub status: NetworkStatus,
 }
 
 impl NetworkService {
     pub async fn start(config: NetworkConfig, keypair: Keypair) -> Result<Self> {
+        Self::start_with_stores(config, keypair, None, None).await
+    }
+
+    /// Start the network, answering block body requests from `block_store`
+    /// and transaction requests from `tx_store`
+    pub async fn start_with_stores(
+        config: NetworkConfig,
+        keypair: Keypair,
+        block_store: Option<Arc<dyn BlockBodyStore>>,
+        tx_store: Option<Arc<dyn TransactionStore>>,
+    ) -> Result<Self> {
         let local_peer_id = PeerId::from(keypair.public());
         info!("Local peer id: {:?}", local_peer_id);
 
@@ -45,11 +76,30 @@ impl NetworkService {
             .build();
 
         swarm.listen_on(config.listen_address.parse()?)?;
+        for peer in &config.bootstrap_peers {
+            match peer.parse::<libp2p::Multiaddr>() {
+                Ok(addr) => {
+                    if let Err(e) = swarm.dial(addr) {
+                        warn!("Failed to dial bootstrap peer {}: {}", peer, e);
+                    }
+                }
+                Err(e) => warn!("Invalid bootstrap peer address {}: {}", peer, e),
+            }
+        }
 
         let (command_tx, command_rx) = mpsc::channel(100);
         let (event_tx, event_rx) = mpsc::channel(100);
 
-        let event_loop = EventLoop::new(swarm, command_rx, event_tx);
+        let status = NetworkStatus::default();
+        let mut event_loop = EventLoop::new(swarm, command_rx, event_tx)
+            .with_status(status.clone())
+            .with_tx_fanout(config.tx_fanout);
+        if let Some(store) = block_store {
+            event_loop = event_loop.with_block_store(store);
+        }
+        if let Some(store) = tx_store {
+            event_loop = event_loop.with_tx_store(store);
+        }
 
         tokio::spawn(event_loop.run());
 
@@ -57,6 +107,20 @@ impl NetworkService {
             command_tx,
             event_rx,
             local_peer_id,
+            status,
         })
     }
-}
\ No newline at end of file
+    /// Fetch the bodies of the blocks with `hashes` from `peer`
+    ///
+    /// Unknown blocks are missing from the result. Fails if the peer doesn't
+    /// answer within `BLOCK_BODIES_TIMEOUT`.
+    pub async fn request_block_bodies(&self, peer: PeerId, hashes: Vec<Hash>) -> Result<Vec<BlockBody>> {
+        let (reply, response) = oneshot::channel();
+        self.command_tx.send(NetworkCommand::RequestBlockBodies { peer, hashes, reply }).await?;
+        match tokio::time::timeout(BLOCK_BODIES_TIMEOUT, response).await {
+            Ok(Ok(bodies)) => Ok(bodies),
+            Ok(Err(_)) => anyhow::bail!("Peer {} disconnected before sending block bodies", peer),
+            Err(_) => anyhow::bail!("Peer {} did not send block bodies in time", peer),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Connection status of the network service
+//!
+//! The event loop keeps the number of connected peers and open listeners
+//! up to date, so other components (e.g. RPC `net_peerCount`) can read them
+//! without going through the swarm.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+struct Counters {
+    peers: AtomicUsize,
+    listeners: AtomicUsize,
+}
+
+/// Shared view of the peers and listeners of a [`crate::NetworkService`]
+///
+/// Clones share the same counters. The default status has no peers and is
+/// not listening.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkStatus(Arc<Counters>);
+
+impl NetworkStatus {
+    /// Number of connected peers
+    pub fn peer_count(&self) -> usize {
+        self.0.peers.load(Ordering::Acquire)
+    }
+
+    /// Whether the service is bound to at least one listen address
+    pub fn is_listening(&self) -> bool {
+        self.0.listeners.load(Ordering::Acquire) > 0
+    }
+
+    pub fn peer_connected(&self) {
+        self.0.peers.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub fn peer_disconnected(&self) {
+        let _ = self.0.peers.fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1));
+    }
+
+    pub fn listener_opened(&self) {
+        self.0.listeners.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub fn listener_closed(&self) {
+        let _ = self.0.listeners.fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_shared_between_clones() {
+        let status = NetworkStatus::default();
+        let view = status.clone();
+        assert_eq!(view.peer_count(), 0);
+        assert!(!view.is_listening());
+
+        status.listener_opened();
+        status.peer_connected();
+        status.peer_connected();
+        status.peer_disconnected();
+        assert_eq!(view.peer_count(), 1);
+        assert!(view.is_listening());
+
+        status.listener_closed();
+        status.peer_disconnected();
+        status.peer_disconnected();
+        assert_eq!(view.peer_count(), 0);
+        assert!(!view.is_listening());
+    }
+}
@@ -4,6 +4,7 @@ pub struct Topics {
     pub block: IdentTopic,
     pub transaction: IdentTopic,
     pub consensus: IdentTopic,
+    pub sync: IdentTopic,
 }
 
 impl Topics {
@@ -12,6 +13,7 @@ impl Topics {
             block: IdentTopic::new("norn/block"),
             transaction: IdentTopic::new("norn/tx"),
             consensus: IdentTopic::new("norn/consensus"),
+            sync: IdentTopic::new("norn/sync"),
         }
     }
 }
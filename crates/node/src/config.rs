@@ -1,6 +1,10 @@
 use serde::Deserialize;
+use norn_storage::SledConfig;
 use norn_core::config::CoreConfig;
+use norn_core::pruning::PruningMode;
 use norn_network::config::NetworkConfig;
+use norn_common::types::{Address, Hash};
+use std::collections::HashMap;
 use std::net::SocketAddr;
 
 #[derive(Debug, Deserialize, Clone)]
@@ -14,6 +18,9 @@ pub struct NodeConfig {
     #[serde(default)]
     pub txpool: TxPoolConfig,
 
+    #[serde(default)]
+    pub producer: ProducerConfig,
+
     #[serde(default)]
     pub sync: SyncConfig,
 
@@ -22,6 +29,28 @@ pub struct NodeConfig {
 
     #[serde(default)]
     pub logging: LoggingConfig,
+
+    #[serde(default)]
+    pub storage: StorageConfig,
+
+    #[serde(default)]
+    pub genesis: GenesisConfig,
+
+    #[serde(default)]
+    pub rpc: RpcConfig,
+}
+
+/// Genesis state configuration
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct GenesisConfig {
+    /// Initial balances (in wei, decimal) by address
+    #[serde(default)]
+    pub alloc: HashMap<Address, String>,
+
+    /// State root declared in the genesis header; the built-in genesis
+    /// declares the empty state
+    #[serde(default)]
+    pub state_root: Option<Hash>,
 }
 
 /// Transaction pool configuration
@@ -42,6 +71,38 @@ pub struct TxPoolConfig {
     /// Transaction expiration time in seconds
     #[serde(default = "default_txpool_expiration")]
     pub expiration_seconds: i64,
+
+    /// Save pooled transactions on shutdown and restore them on startup
+    #[serde(default)]
+    pub persist_mempool: bool,
+
+    /// Senders whose transactions the enhanced pool treats as local:
+    /// exempt from eviction and expiry, and journaled across restarts
+    #[serde(default)]
+    pub locals: Vec<Address>,
+}
+
+impl TxPoolConfig {
+    /// Enhanced pool configuration, journaling local transactions under
+    /// `data_dir`
+    pub fn enhanced_pool_config(&self, data_dir: &str) -> norn_core::txpool_enhanced::EnhancedTxPoolConfig {
+        norn_core::txpool_enhanced::EnhancedTxPoolConfig {
+            max_size: self.max_size,
+            locals: self.locals.iter().copied().collect(),
+            journal: Some(std::path::Path::new(data_dir).join("transactions.journal")),
+            tx_lifetime_secs: self.expiration_seconds,
+            ..Default::default()
+        }
+    }
+}
+
+/// Block producer configuration
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ProducerConfig {
+    /// Minimum effective priority fee per gas a transaction must pay to be
+    /// included in blocks this node produces
+    #[serde(default)]
+    pub min_tip: u64,
 }
 
 /// Sync configuration
@@ -84,6 +145,123 @@ pub struct MonitoringConfig {
     pub health_check_address: String,
 }
 
+/// Storage configuration
+#[derive(Debug, Deserialize, Clone)]
+pub struct StorageConfig {
+    /// Enable periodic database maintenance
+    #[serde(default = "default_storage_maintenance_enabled")]
+    pub maintenance_enabled: bool,
+
+    /// Interval between maintenance runs in seconds
+    #[serde(default = "default_storage_maintenance_interval")]
+    pub maintenance_interval_secs: u64,
+
+    /// Sled database settings
+    #[serde(default)]
+    pub sled: SledConfig,
+
+    /// Block body pruning: `mode = "archive"`, or `mode = "full"` with `keep_recent`
+    #[serde(default)]
+    pub pruning: PruningMode,
+
+    /// Blocks applied between commits of the account state to the database;
+    /// blocks in between are replayed from the state WAL after a crash
+    #[serde(default = "default_state_commit_interval_blocks")]
+    pub state_commit_interval_blocks: u64,
+
+    /// Historical state for eth_call at past blocks
+    #[serde(default)]
+    pub archive: StateArchiveConfig,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            maintenance_enabled: default_storage_maintenance_enabled(),
+            maintenance_interval_secs: default_storage_maintenance_interval(),
+            sled: SledConfig::default(),
+            pruning: PruningMode::default(),
+            state_commit_interval_blocks: default_state_commit_interval_blocks(),
+            archive: StateArchiveConfig::default(),
+        }
+    }
+}
+
+/// In-memory state snapshots past blocks are replayed from
+#[derive(Debug, Deserialize, Clone)]
+pub struct StateArchiveConfig {
+    /// Keep snapshots and serve eth_call at past blocks
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Blocks between snapshots; with pruning, keep more recent blocks than
+    /// this or blocks between snapshots can't be served
+    #[serde(default = "default_archive_snapshot_interval")]
+    pub snapshot_interval: u64,
+
+    /// Snapshots kept, the oldest are dropped first
+    #[serde(default = "default_archive_max_snapshots")]
+    pub max_snapshots: usize,
+
+    /// Most blocks replayed to answer a single call
+    #[serde(default = "default_archive_replay_limit")]
+    pub replay_limit: u64,
+}
+
+impl Default for StateArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            snapshot_interval: default_archive_snapshot_interval(),
+            max_snapshots: default_archive_max_snapshots(),
+            replay_limit: default_archive_replay_limit(),
+        }
+    }
+}
+
+/// Ethereum JSON-RPC limits
+#[derive(Debug, Deserialize, Clone)]
+pub struct RpcConfig {
+    /// Maximum gas an eth_call or eth_estimateGas may use
+    #[serde(default = "default_rpc_call_gas_cap")]
+    pub call_gas_cap: u64,
+
+    /// Wall-clock limit for a single eth_call in milliseconds
+    #[serde(default = "default_rpc_call_timeout_ms")]
+    pub call_timeout_ms: u64,
+
+    /// Widest block range an eth_getLogs query may span
+    #[serde(default = "default_rpc_max_log_range")]
+    pub max_log_range: u64,
+
+    /// Most logs a single eth_getLogs query may return
+    #[serde(default = "default_rpc_max_log_results")]
+    pub max_log_results: usize,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            call_gas_cap: default_rpc_call_gas_cap(),
+            call_timeout_ms: default_rpc_call_timeout_ms(),
+            max_log_range: default_rpc_max_log_range(),
+            max_log_results: default_rpc_max_log_results(),
+        }
+    }
+}
+
+impl From<RpcConfig> for norn_rpc::RpcConfig {
+    fn from(config: RpcConfig) -> Self {
+        Self {
+            call_gas_cap: config.call_gas_cap,
+            call_timeout: std::time::Duration::from_millis(config.call_timeout_ms),
+            max_log_range: config.max_log_range,
+            max_log_results: config.max_log_results,
+            ..Self::default()
+        }
+    }
+}
+
 /// Logging configuration (simplified for TOML deserialization)
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct LoggingConfig {
@@ -163,6 +341,18 @@ fn default_monitoring_prometheus_addr() -> String { "0.0.0.0:9090".to_string() }
 fn default_monitoring_health() -> bool { true }
 fn default_monitoring_health_addr() -> String { "0.0.0.0:8080".to_string() }
 
+fn default_storage_maintenance_enabled() -> bool { true }
+fn default_storage_maintenance_interval() -> u64 { 3600 }
+fn default_state_commit_interval_blocks() -> u64 { 1 }
+fn default_archive_snapshot_interval() -> u64 { norn_core::state::archive::DEFAULT_ARCHIVE_SNAPSHOT_INTERVAL }
+fn default_archive_max_snapshots() -> usize { norn_core::state::archive::DEFAULT_MAX_ARCHIVE_SNAPSHOTS }
+fn default_archive_replay_limit() -> u64 { norn_core::state::archive::DEFAULT_ARCHIVE_SNAPSHOT_INTERVAL }
+
+fn default_rpc_call_gas_cap() -> u64 { 5_000_000 }
+fn default_rpc_call_timeout_ms() -> u64 { 5_000 }
+fn default_rpc_max_log_range() -> u64 { 10_000 }
+fn default_rpc_max_log_results() -> usize { 10_000 }
+
 fn default_logging_level() -> String { "info".to_string() }
 fn default_logging_format() -> String { "json".to_string() }
 fn default_logging_max_file_size() -> u64 { 100 }
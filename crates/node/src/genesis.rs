@@ -0,0 +1,100 @@
+//! Genesis state initialization
+//!
+//! Builds the genesis block from the node configuration and loads its
+//! initial allocation, refusing to start when the allocation doesn't match
+//! the state root declared in the genesis header.
+
+use anyhow::{anyhow, bail, Result};
+use norn_common::types::Block;
+use norn_core::state::merkle::StateRootCalculator;
+use norn_core::state::AccountStateManager;
+use num_bigint::BigUint;
+use tracing::info;
+
+use crate::config::GenesisConfig;
+
+/// Build the genesis block, using the configured state root if any
+pub fn genesis_block(config: &GenesisConfig) -> Block {
+    let mut genesis = norn_common::genesis::get_genesis_block();
+    if let Some(state_root) = config.state_root {
+        genesis.header.state_root = state_root;
+    }
+    genesis
+}
+
+/// Load the genesis allocation into `state_manager` and check that its
+/// state root matches the one declared in the `genesis` header
+pub async fn init_genesis_state(
+    genesis: &Block,
+    config: &GenesisConfig,
+    state_manager: &AccountStateManager,
+) -> Result<()> {
+    for (address, balance) in &config.alloc {
+        let balance = BigUint::parse_bytes(balance.as_bytes(), 10)
+            .ok_or_else(|| anyhow!("Invalid genesis balance {:?} for {}", balance, hex::encode(address.0)))?;
+        state_manager.update_balance(address, balance).await?;
+    }
+
+    let computed = StateRootCalculator::for_manager(state_manager)
+        .calculate_from_manager(state_manager)
+        .await?;
+    let declared = genesis.header.state_root;
+    if computed != declared {
+        bail!(
+            "Genesis state root mismatch: header declares {}, but the initial allocation of {} accounts computes {}",
+            declared,
+            config.alloc.len(),
+            computed
+        );
+    }
+
+    info!("Genesis state verified: {} accounts, state root {}", config.alloc.len(), computed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use norn_common::types::{Address, Hash};
+    use norn_core::state::AccountStateConfig;
+
+    fn alloc_config() -> GenesisConfig {
+        let mut config = GenesisConfig::default();
+        config.alloc.insert(Address([1u8; 20]), "1000000000000000000".to_string());
+        config.alloc.insert(Address([2u8; 20]), "42".to_string());
+        config
+    }
+
+    #[tokio::test]
+    async fn test_genesis_state_root_mismatch_fails() {
+        let mut config = alloc_config();
+        config.state_root = Some(Hash([0xab; 32]));
+        let genesis = genesis_block(&config);
+
+        let state_manager = AccountStateManager::new(AccountStateConfig::default());
+        let err = init_genesis_state(&genesis, &config, &state_manager).await.unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("Genesis state root mismatch"), "{}", message);
+        assert!(message.contains(&Hash([0xab; 32]).to_string()), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_genesis_state_root_matches() {
+        // The built-in genesis declares the empty state
+        let empty = GenesisConfig::default();
+        let state_manager = AccountStateManager::new(AccountStateConfig::default());
+        init_genesis_state(&genesis_block(&empty), &empty, &state_manager).await.unwrap();
+
+        // An allocation with its matching root
+        let mut config = alloc_config();
+        let reference = AccountStateManager::new(AccountStateConfig::default());
+        for (address, balance) in &config.alloc {
+            reference.update_balance(address, balance.parse().unwrap()).await.unwrap();
+        }
+        config.state_root = Some(StateRootCalculator::default().calculate_from_manager(&reference).await.unwrap());
+
+        let state_manager = AccountStateManager::new(AccountStateConfig::default());
+        init_genesis_state(&genesis_block(&config), &config, &state_manager).await.unwrap();
+        assert_eq!(state_manager.get_balance(&Address([2u8; 20])).await.unwrap(), BigUint::from(42u32));
+    }
+}
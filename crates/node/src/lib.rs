@@ -1,5 +1,7 @@
 pub mod config;
+pub mod genesis;
 pub mod logging;
+pub mod maintenance;
 pub mod manager;
 pub mod metrics;
 pub mod monitoring;
@@ -9,6 +11,7 @@ pub mod tx_handler;
 
 pub use config::NodeConfig;
 pub use logging::LoggingConfig;
+pub use maintenance::DbMaintenance;
 pub use metrics::{MetricsCollector, HealthStatus};
 pub use monitoring::MonitoringServer;
 pub use service::NornNode;
@@ -6,7 +6,7 @@ use norn_network::NetworkService;
 use norn_common::types::{Block, Transaction};
 use norn_common::utils::codec;
 use norn_crypto::transaction::verify_transaction;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 pub struct PeerManager {
     chain: Arc<Blockchain>,
@@ -31,6 +31,9 @@ impl PeerManager {
             NetworkEvent::ConsensusMessageReceived(data) => {
                 self.handle_consensus_message(data).await;
             }
+            NetworkEvent::PeerStatusReceived { peer, status } => {
+                debug!("Peer {} reported chain tip at height {}", peer, status.current_height);
+            }
         }
     }
 
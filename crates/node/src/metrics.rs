@@ -6,6 +6,15 @@ use prometheus::{
     Counter, CounterVec, Gauge, GaugeVec, Histogram, HistogramVec, Registry, HistogramOpts, Opts,
     TextEncoder, Encoder,
 };
+use norn_storage::sled::{DB_CACHE_CAPACITY_BYTES, DB_READS_TOTAL};
+use norn_core::blockchain::REORGS_REFUSED_TOTAL;
+use norn_core::consensus::metrics::{
+    CONSENSUS_PROPOSER, CONSENSUS_ROUND, CONSENSUS_SEED_AGE_SECONDS, CONSENSUS_TOTAL_STAKE,
+    CONSENSUS_VALIDATORS,
+};
+use norn_crypto::calculator::{
+    VDF_CALCULATIONS_TOTAL, VDF_CALCULATION_DURATION, VDF_STALE_SEEDS_DROPPED_TOTAL,
+};
 use std::sync::Arc;
 use lazy_static::lazy_static;
 use tracing::{debug, error};
@@ -172,6 +181,33 @@ lazy_static! {
         "norn_state_pruning_last_block",
         "Block number of the last pruning operation"
     ).unwrap();
+
+    // Database maintenance metrics
+    pub static ref DB_SIZE_BEFORE_COMPACTION: Gauge = Gauge::new(
+        "norn_db_size_before_compaction_bytes",
+        "Database size on disk before the last compaction"
+    ).unwrap();
+
+    pub static ref DB_SIZE_AFTER_COMPACTION: Gauge = Gauge::new(
+        "norn_db_size_after_compaction_bytes",
+        "Database size on disk after the last compaction"
+    ).unwrap();
+
+    pub static ref DB_COMPACTION_TOTAL: Counter = Counter::new(
+        "norn_db_compaction_total",
+        "Total number of database compactions performed"
+    ).unwrap();
+
+    pub static ref DB_COMPACTION_DURATION_SECONDS: Histogram = Histogram::with_opts(
+        HistogramOpts::new("norn_db_compaction_duration_seconds", "Database compaction duration in seconds")
+            .buckets(vec![0.01, 0.1, 0.5, 1.0, 5.0, 30.0])
+    ).unwrap();
+
+    // Chain reorganization metrics
+    pub static ref REORG_DEPTH: Histogram = Histogram::with_opts(
+        HistogramOpts::new("norn_reorg_depth_blocks", "Number of blocks reverted by chain reorganizations")
+            .buckets(vec![1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 50.0, 100.0])
+    ).unwrap();
 }
 
 /// Metrics collector
@@ -216,11 +252,31 @@ impl MetricsCollector {
         registry.register(Box::new(PRUNING_DURATION_SECONDS.clone())).unwrap();
         registry.register(Box::new(PRUNING_LAST_BLOCK.clone())).unwrap();
 
+        // Database maintenance metrics
+        registry.register(Box::new(DB_SIZE_BEFORE_COMPACTION.clone())).unwrap();
+        registry.register(Box::new(DB_SIZE_AFTER_COMPACTION.clone())).unwrap();
+        registry.register(Box::new(DB_COMPACTION_TOTAL.clone())).unwrap();
+        registry.register(Box::new(DB_COMPACTION_DURATION_SECONDS.clone())).unwrap();
+        registry.register(Box::new(REORG_DEPTH.clone())).unwrap();
+        registry.register(Box::new(REORGS_REFUSED_TOTAL.clone())).unwrap();
+        registry.register(Box::new(DB_READS_TOTAL.clone())).unwrap();
+        registry.register(Box::new(DB_CACHE_CAPACITY_BYTES.clone())).unwrap();
+
         registry.register(Box::new(PEER_CONNECTIONS.clone())).unwrap();
         registry.register(Box::new(NETWORK_BYTES_TOTAL.clone())).unwrap();
         registry.register(Box::new(CONSENSUS_ROUNDS_TOTAL.clone())).unwrap();
+        registry.register(Box::new(CONSENSUS_ROUND.clone())).unwrap();
+        registry.register(Box::new(CONSENSUS_PROPOSER.clone())).unwrap();
+        registry.register(Box::new(CONSENSUS_TOTAL_STAKE.clone())).unwrap();
+        registry.register(Box::new(CONSENSUS_VALIDATORS.clone())).unwrap();
+        registry.register(Box::new(CONSENSUS_SEED_AGE_SECONDS.clone())).unwrap();
         registry.register(Box::new(VRF_EXECUTION_DURATION.clone())).unwrap();
         registry.register(Box::new(VDF_EXECUTION_DURATION.clone())).unwrap();
+
+        // VDF calculator metrics
+        registry.register(Box::new(VDF_CALCULATIONS_TOTAL.clone())).unwrap();
+        registry.register(Box::new(VDF_CALCULATION_DURATION.clone())).unwrap();
+        registry.register(Box::new(VDF_STALE_SEEDS_DROPPED_TOTAL.clone())).unwrap();
         registry.register(Box::new(STORAGE_READ_DURATION.clone())).unwrap();
         registry.register(Box::new(STORAGE_WRITE_DURATION.clone())).unwrap();
         registry.register(Box::new(RPC_REQUESTS_TOTAL.clone())).unwrap();
@@ -300,6 +356,19 @@ impl MetricsCollector {
         PRUNING_DURATION_SECONDS.observe(duration_sec);
     }
 
+    /// Record database compaction
+    pub fn record_db_compaction(&self, size_before: u64, size_after: u64, duration_sec: f64) {
+        DB_COMPACTION_TOTAL.inc();
+        DB_SIZE_BEFORE_COMPACTION.set(size_before as f64);
+        DB_SIZE_AFTER_COMPACTION.set(size_after as f64);
+        DB_COMPACTION_DURATION_SECONDS.observe(duration_sec);
+    }
+
+    /// Record a chain reorganization that reverted `depth` blocks
+    pub fn record_reorg(&self, depth: u64) {
+        REORG_DEPTH.observe(depth as f64);
+    }
+
     /// Get current pruning statistics
     pub fn get_pruning_stats(&self) -> (u64, u64, u64, u64) {
         let total = PRUNING_TOTAL.get() as u64;
@@ -361,6 +430,27 @@ mod tests {
         assert!(metrics.is_ok());
     }
 
+    #[test]
+    fn test_vdf_metrics_exposed() {
+        let collector = MetricsCollector::new();
+        VDF_CALCULATION_DURATION.observe(0.25);
+
+        let metrics = collector.gather().unwrap();
+        assert!(metrics.contains("norn_vdf_calculation_duration_seconds"));
+        assert!(metrics.contains("norn_vdf_calculations_total"));
+        assert!(metrics.contains("norn_vdf_stale_seeds_dropped_total"));
+    }
+
+    #[test]
+    fn test_db_metrics_exposed() {
+        let collector = MetricsCollector::new();
+        DB_READS_TOTAL.with_label_values(&["hit"]).inc();
+
+        let metrics = collector.gather().unwrap();
+        assert!(metrics.contains("norn_db_reads_total"));
+        assert!(metrics.contains("norn_db_cache_capacity_bytes"));
+    }
+
     #[test]
     fn test_health_status() {
         let status = HealthStatus::new(3600, 12345, 5, 100);
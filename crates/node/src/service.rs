@@ -1,14 +1,19 @@
 use anyhow::Result;
 use norn_core::blockchain::Blockchain;
 use norn_core::txpool::TxPool;
+use norn_core::RawTransactionStore;
 // Week 3: Import enhanced transaction pool
 use norn_core::txpool_enhanced::EnhancedTxPool;
 use norn_core::consensus::povf::{PoVFEngine, PoVFConfig};
 use norn_core::consensus::producer::{BlockProducer, BlockProducerConfig};
-use norn_core::state::{AccountStateManager, AccountStateConfig};
+use norn_core::state::{AccountStateManager, AccountStateConfig, PersistentConfig, PersistentStateManager, StateArchive, StateReadCache};
 use norn_core::evm::{EVMExecutor, EVMConfig};
+use norn_core::execution::TransactionRouter;
+use norn_core::readiness::SyncReadiness;
 use norn_network::NetworkService;
-use norn_storage::SledDB;
+use norn_network::service::NetworkCommand;
+use norn_common::utils::codec;
+use norn_storage::{SledDB, WAL, WALConfig};
 use norn_crypto::vdf::SimpleVDF;
 use norn_crypto::vrf::VRFKeyPair;
 
@@ -16,11 +21,14 @@ use libp2p::identity::Keypair;
 use std::sync::Arc;
 use std::collections::HashMap;
 use crate::config::NodeConfig;
+use crate::maintenance::DbMaintenance;
 use crate::manager::PeerManager;
 use crate::syncer::BlockSyncer;
 use crate::tx_handler::TxHandler;
 use norn_rpc::{start_rpc_server, create_ethereum_rpc, start_ethereum_rpc_server};
 use tokio::signal;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use tracing::{info, error, warn};
 use norn_common::types::PublicKey;
 
@@ -29,11 +37,18 @@ use crate::metrics::MetricsCollector;
 use crate::monitoring::MonitoringServer;
 use crate::logging::LoggingConfig;
 
+/// Chain id served over JSON-RPC and required of gossiped transactions
+const CHAIN_ID: u64 = 31337;
+/// How often the consensus gauges are refreshed
+const CONSENSUS_METRICS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
 pub struct NornNode {
     config: NodeConfig,
+    db: Arc<SledDB>,
     blockchain: Arc<Blockchain>,
     tx_pool: Arc<TxPool>,
-    #[allow(dead_code)]
+    /// Expires and re-announces pending transactions, if enabled
+    enhanced_pool: Option<Arc<EnhancedTxPool>>,
     network: Arc<NetworkService>,
 
     /// Consensus engine for PoVF consensus
@@ -45,14 +60,28 @@ pub struct NornNode {
 
     peer_manager: Arc<PeerManager>,
     syncer: Arc<BlockSyncer>,
+    readiness: SyncReadiness,
     tx_handler: Arc<TxHandler>,
 
     /// State manager for EVM
     state_manager: Arc<AccountStateManager>,
 
+    /// Committed state in the database
+    persistent_state: Arc<PersistentStateManager>,
+
+    /// Reads of committed state, shared by the RPC servers
+    state_cache: Arc<StateReadCache>,
+
     /// EVM executor
     evm_executor: Arc<EVMExecutor>,
 
+    /// Snapshots past blocks are replayed from, if enabled
+    state_archive: Option<Arc<StateArchive>>,
+
+    // Cancelled to stop the tasks spawned by `start`
+    shutdown: CancellationToken,
+    tasks: Vec<JoinHandle<()>>,
+
     // Temp holder for startup
     network_rx: Option<tokio::sync::mpsc::Receiver<norn_network::service::NetworkEvent>>,
 
@@ -105,19 +134,35 @@ impl NornNode {
             info!("Health check endpoint disabled");
         }
 
-        let db = Arc::new(SledDB::new(&config.data_dir)?);
-        let blockchain = Blockchain::new_with_fixed_genesis(db.clone()).await;
+        let db = Arc::new(SledDB::with_config(&config.data_dir, &config.storage.sled)?);
+        let genesis = crate::genesis::genesis_block(&config.genesis);
+        let shutdown = CancellationToken::new();
+        let blockchain = Blockchain::new_with_shutdown(
+            db.clone(),
+            genesis.clone(),
+            config.storage.pruning,
+            shutdown.clone(),
+        ).await;
+
+        let tx_pool = Arc::new(TxPool::new());
 
-        // Week 3: Use enhanced txpool if configured
-        let tx_pool = if config.txpool.enhanced {
-            info!("Initializing enhanced transaction pool (BinaryHeap prioritization, EIP-1559)");
-            // TODO: Replace with actual EnhancedTxPool when fully integrated
-            // For now, use standard pool but log the intent
-            info!("Note: Enhanced transaction pool features will be available in next phase");
-            Arc::new(TxPool::new())
+        // Week 3: Track pending transactions in the enhanced pool if configured
+        let enhanced_pool = if config.txpool.enhanced {
+            info!("Initializing enhanced transaction pool (expiry, re-announcement, local journal)");
+            let pool = Arc::new(EnhancedTxPool::with_config(
+                config.txpool.enhanced_pool_config(&config.data_dir),
+            ));
+            pool.load_journal().await?;
+            // Re-injected local transactions are pending again
+            for sender in &config.txpool.locals {
+                for tx in pool.get_pending_by_sender(sender).await {
+                    tx_pool.add(tx);
+                }
+            }
+            Some(pool)
         } else {
             info!("Initializing standard transaction pool");
-            Arc::new(TxPool::new())
+            None
         };
         
         // Initialize VRF key pair for this node
@@ -152,6 +197,25 @@ impl NornNode {
 
         // Initialize state manager and EVM executor before BlockProducer
         let state_manager = Arc::new(AccountStateManager::new(AccountStateConfig::default()));
+        let persistent_config = PersistentConfig {
+            state_commit_interval_blocks: config.storage.state_commit_interval_blocks,
+            ..Default::default()
+        };
+        let state_wal = WAL::new(std::path::Path::new(&config.data_dir).join("state_wal"), WALConfig::default())?;
+        let persistent_state = Arc::new(
+            PersistentStateManager::with_state_manager(db.clone(), persistent_config, state_manager.clone())
+                .with_wal(state_wal),
+        );
+        let chain_head = blockchain.latest_block.read().await.header.height.max(0) as u64;
+        persistent_state.recover_from_wal(chain_head).await?;
+        if persistent_state.committed_block()?.is_some() {
+            persistent_state.load_from_db_async().await?;
+        } else {
+            crate::genesis::init_genesis_state(&genesis, &config.genesis, &state_manager).await?;
+        }
+        if config.txpool.persist_mempool {
+            tx_pool.load(db.as_ref(), blockchain.as_ref(), &state_manager).await?;
+        }
         let evm_config = EVMConfig::default();
         let evm_executor = Arc::new(EVMExecutor::new(state_manager.clone(), evm_config));
 
@@ -160,9 +224,34 @@ impl NornNode {
         let producer_config = BlockProducerConfig {
             is_validator: true, // Force enable for test
             block_interval: 1,  // Faster blocks for TPS test (1s)
+            min_tip: config.producer.min_tip,
             ..Default::default()
         };
 
+        // Execute each new head, then commit the state it left
+        let router = Arc::new(TransactionRouter::new(
+            Some(evm_executor.clone()),
+            producer_config.max_gas_per_block.max(0) as u64,
+        ));
+        blockchain.add_import_hook(router.clone());
+
+        // The archive notes the changes of each block before the persistent
+        // state takes them
+        let state_archive = if config.storage.archive.enabled {
+            let archive_config = &config.storage.archive;
+            let archive = Arc::new(
+                StateArchive::new(router.clone(), archive_config.replay_limit)
+                    .with_snapshot_interval(archive_config.snapshot_interval)
+                    .with_max_snapshots(archive_config.max_snapshots),
+            );
+            archive.record(chain_head).await?;
+            blockchain.add_import_hook(archive.clone());
+            Some(archive)
+        } else {
+            None
+        };
+        blockchain.add_import_hook(persistent_state.clone());
+
         let block_producer = Arc::new(BlockProducer::new(
             producer_config,
             blockchain.clone(),
@@ -170,10 +259,13 @@ impl NornNode {
             vrf_key_pair,
             state_manager.clone(),
             Some(consensus.clone()),
-        ));
+        ).with_router(router));
         
         // Extract network receiver
-        let mut network_svc = NetworkService::start(config.network.clone(), keypair).await?;
+        let body_store = Arc::new(crate::syncer::ChainBodyStore::new(blockchain.clone()));
+        let tx_store = Arc::new(crate::tx_handler::PoolTxStore::new(tx_pool.clone()));
+        let mut network_svc =
+            NetworkService::start_with_stores(config.network.clone(), keypair, Some(body_store), Some(tx_store)).await?;
 
         // Hack: NetworkService struct assumes it holds rx.
         // We construct `NetworkService` then steal `event_rx` using `std::mem::replace`
@@ -181,21 +273,38 @@ impl NornNode {
         let network = Arc::new(network_svc);
         
         let peer_manager = Arc::new(PeerManager::new(blockchain.clone(), tx_pool.clone(), network.clone()));
-        let syncer = Arc::new(BlockSyncer::new(blockchain.clone(), network.clone()));
-        let tx_handler = Arc::new(TxHandler::new(tx_pool.clone()));
+        // RPC state queries wait for the initial sync
+        let readiness = SyncReadiness::syncing();
+        let syncer = Arc::new(
+            BlockSyncer::new(blockchain.clone(), network.clone()).with_readiness(readiness.clone()),
+        );
+        let mut tx_handler = TxHandler::new(tx_pool.clone(), CHAIN_ID);
+        if let Some(pool) = &enhanced_pool {
+            tx_handler = tx_handler.with_enhanced_pool(pool.clone());
+        }
+        let tx_handler = Arc::new(tx_handler);
+        let state_cache = Arc::new(StateReadCache::new(state_manager.clone(), blockchain.subscribe_head()));
 
         Ok(Self {
             config,
+            db,
             blockchain,
             tx_pool,
+            enhanced_pool,
             network,
             consensus,
             block_producer,
             peer_manager,
             syncer,
+            readiness,
             tx_handler,
+            state_cache,
             state_manager,
+            persistent_state,
             evm_executor,
+            state_archive,
+            shutdown,
+            tasks: Vec::new(),
             network_rx: Some(rx),
             // Week 3: Add monitoring and logging
             metrics_collector,
@@ -220,7 +329,7 @@ impl NornNode {
         let chain_ref = self.blockchain.clone();
         let tx_pool_ref = self.tx_pool.clone();
         let rpc_addr_clone = rpc_addr;
-        tokio::spawn(async move {
+        self.spawn_until_shutdown(async move {
             info!("gRPC Server listening on {}", rpc_addr_clone);
             if let Err(e) = start_rpc_server(rpc_addr_clone, chain_ref, tx_pool_ref).await {
                 error!("gRPC Server failed: {:?}", e);
@@ -234,9 +343,20 @@ impl NornNode {
             self.state_manager.clone(),
             self.evm_executor.clone(),
             self.tx_pool.clone(),
-            31337, // Chain ID
-        );
-        tokio::spawn(async move {
+            CHAIN_ID,
+        )
+        .with_config(self.config.rpc.clone().into())
+        .with_readiness(self.readiness.clone())
+        .with_state_cache(self.state_cache.clone())
+        .with_network_status(self.network.status.clone())
+        .with_consensus(self.consensus.clone())
+        .with_recovery_status(self.db.clone())
+        .with_raw_tx_store(Arc::new(RawTransactionStore::with_db(self.db.clone())));
+        let eth_rpc = match &self.state_archive {
+            Some(archive) => eth_rpc.with_state_archive(archive.clone()),
+            None => eth_rpc,
+        };
+        self.spawn_until_shutdown(async move {
             info!("Ethereum JSON-RPC server listening on {}", eth_rpc_addr);
             if let Err(e) = start_ethereum_rpc_server(eth_rpc_addr, eth_rpc).await {
                 error!("Ethereum JSON-RPC server failed: {:?}", e);
@@ -246,15 +366,94 @@ impl NornNode {
 
         // Start syncer
         let syncer = self.syncer.clone();
-        tokio::spawn(async move {
+        self.spawn_until_shutdown(async move {
             syncer.start().await;
         });
 
-        // Start block producer
-        let producer = self.block_producer.clone();
-        tokio::spawn(async move {
-            producer.run().await;
+        // Drop expired transactions and re-announce the ones still pending
+        if let Some(enhanced_pool) = self.enhanced_pool.clone() {
+            let (reannounce_tx, mut reannounce_rx) = tokio::sync::mpsc::channel(256);
+            let pool = enhanced_pool.clone();
+            self.spawn_until_shutdown(async move {
+                pool.run_maintenance(reannounce_tx).await;
+            });
+
+            // Keep the local transaction journal current between restarts
+            let pool = enhanced_pool.clone();
+            self.spawn_until_shutdown(async move {
+                pool.run_journal_rotation().await;
+            });
+
+            let tx_pool = self.tx_pool.clone();
+            let network = self.network.clone();
+            self.spawn_until_shutdown(async move {
+                while let Some(tx) = reannounce_rx.recv().await {
+                    // Packaged transactions are no longer pending
+                    if !tx_pool.contains(&tx.body.hash) {
+                        enhanced_pool.remove(&tx.body.hash).await;
+                        continue;
+                    }
+                    let data = match codec::serialize(&tx) {
+                        Ok(data) => data,
+                        Err(e) => {
+                            warn!("Failed to serialize tx hash={}: {}", tx.body.hash, e);
+                            continue;
+                        }
+                    };
+                    if let Err(e) = network.command_tx.send(NetworkCommand::BroadcastTransaction(data)).await {
+                        warn!("Failed to re-announce tx hash={}: {}", tx.body.hash, e);
+                    }
+                }
+            });
+        }
+
+        // Start database maintenance
+        if self.config.storage.maintenance_enabled {
+            let maintenance = DbMaintenance::new(
+                self.db.clone(),
+                &self.config.storage,
+                self.metrics_collector.clone(),
+            );
+            self.spawn_until_shutdown(async move {
+                maintenance.start().await;
+            });
+        } else {
+            info!("Database maintenance disabled");
+        }
+
+        // Drop storage slots of deleted accounts
+        let persistent_state = self.persistent_state.clone();
+        self.spawn_until_shutdown(async move {
+            persistent_state.run_storage_gc().await;
+        });
+
+        // Refresh the consensus gauges
+        let consensus = self.consensus.clone();
+        self.spawn_until_shutdown(async move {
+            let mut interval = tokio::time::interval(CONSENSUS_METRICS_INTERVAL);
+            loop {
+                interval.tick().await;
+                consensus.status().await.record();
+            }
         });
+
+        // Drop receipts of blocks whose bodies were pruned
+        let mut pruned_rx = self.blockchain.subscribe_pruned();
+        let evm_executor = self.evm_executor.clone();
+        self.spawn_until_shutdown(async move {
+            while pruned_rx.changed().await.is_ok() {
+                let pruned_below = *pruned_rx.borrow_and_update();
+                evm_executor.receipt_db().prune_before(pruned_below.max(0) as u64).await;
+            }
+        });
+
+        // Start block producer; it stops on its own so a block being
+        // committed at shutdown is not cut off
+        let producer = self.block_producer.clone();
+        let shutdown = self.shutdown.clone();
+        self.tasks.push(tokio::spawn(async move {
+            producer.run_until(shutdown.cancelled()).await;
+        }));
         info!("Block Producer started");
 
         // Start consensus engine (for block production in future)
@@ -268,8 +467,60 @@ impl NornNode {
             self.run_loop(rx).await;
         }
 
+        self.shutdown().await
+    }
+
+    /// Token that stops the node when cancelled; `start` then shuts down
+    /// and returns
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Stop all subsystems and persist state
+    ///
+    /// Cancels the spawned tasks, which are dropped, and waits for them to
+    /// end. The block producer and block finalization stop between blocks,
+    /// so a block being produced or imported is committed first. Then
+    /// commits the state root, saves the transaction pool if
+    /// `persist_mempool` is set, rewrites the local transaction journal and
+    /// flushes the database.
+    pub async fn shutdown(&mut self) -> Result<()> {
+        info!("Shutting down Norn Node...");
+        self.shutdown.cancel();
+
+        for task in self.tasks.drain(..) {
+            if let Err(e) = task.await {
+                warn!("Task failed during shutdown: {}", e);
+            }
+        }
+        self.blockchain.finalizer_stopped().await;
+
+        self.state_manager.update_state_root().await?;
+        if self.config.txpool.persist_mempool {
+            self.tx_pool.save(self.db.as_ref()).await?;
+        }
+        if let Some(enhanced_pool) = &self.enhanced_pool {
+            enhanced_pool.rotate_journal().await?;
+        }
+        self.db.flush()?;
+
+        info!("Norn Node stopped");
         Ok(())
     }
+
+    /// Spawn `task`, dropping it when the node shuts down
+    fn spawn_until_shutdown<F>(&mut self, task: F)
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let shutdown = self.shutdown.clone();
+        self.tasks.push(tokio::spawn(async move {
+            tokio::select! {
+                _ = shutdown.cancelled() => {}
+                _ = task => {}
+            }
+        }));
+    }
     
     pub async fn run_loop(&mut self, mut network_events: tokio::sync::mpsc::Receiver<norn_network::service::NetworkEvent>) {
         loop {
@@ -288,6 +539,9 @@ impl NornNode {
                                     // Handle consensus messages
                                     // warn!("Received consensus message ({} bytes) - TODO: implement handling", data.len());
                                 }
+                                norn_network::service::NetworkEvent::PeerStatusReceived { peer, status } => {
+                                    self.syncer.handle_peer_status(peer, &status).await;
+                                }
                                 _ => {}
                             }
                         }
@@ -298,7 +552,58 @@ impl NornNode {
                     info!("Shutdown signal received");
                     break;
                 }
+                _ = self.shutdown.cancelled() => break,
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use norn_common::types::Hash;
+    use std::time::Duration;
+
+    fn test_config(data_dir: &str) -> NodeConfig {
+        serde_json::from_value(serde_json::json!({
+            "core": { "consensus": { "pub_key": "", "prv_key": "" } },
+            "network": {
+                "listen_address": "/ip4/127.0.0.1/tcp/0",
+                "bootstrap_peers": [],
+                "mdns": false
+            },
+            "rpc_address": "127.0.0.1:0",
+            "data_dir": data_dir,
+            "storage": { "maintenance_enabled": false }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_state_before_returning() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = test_config(temp_dir.path().to_str().unwrap());
+        let node = NornNode::new(config, Keypair::generate_ed25519()).await.unwrap();
+
+        let shutdown = node.shutdown_token();
+        let state_manager = node.state_manager.clone();
+        let blockchain = node.blockchain.clone();
+        assert_eq!(state_manager.get_state_root().await.unwrap(), Hash::default());
+
+        let running = tokio::spawn(node.start());
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        shutdown.cancel();
+        running.await.unwrap().unwrap();
+
+        // The state root was committed before `start` returned
+        assert_eq!(
+            state_manager.get_state_root().await.unwrap(),
+            state_manager.compute_state_root().await.unwrap()
+        );
+
+        // The producer has stopped
+        let height = blockchain.latest_block.read().await.header.height;
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(blockchain.latest_block.read().await.header.height, height);
+    }
+}
@@ -5,7 +5,7 @@
 pub mod syncer;
 pub mod reorg_handler;
 
-pub use syncer::BlockSyncer;
+pub use syncer::{BlockSyncer, ChainBodyStore};
 pub use reorg_handler::ReorgHandler;pub mod fast_sync;
 
 pub use fast_sync::{
@@ -4,10 +4,14 @@
 
 use std::sync::Arc;
 use norn_core::blockchain::Blockchain;
+use norn_core::events::{EventBus, ReorgDetected};
+use norn_core::txpool_enhanced::EnhancedTxPool;
 use norn_common::types::{Block, Hash};
 use tracing::{info, warn, debug, error};
 use anyhow::Result;
 
+use crate::metrics::MetricsCollector;
+
 /// Reorganization result
 #[derive(Debug)]
 pub struct ReorgResult {
@@ -26,12 +30,39 @@ pub struct ReorgResult {
 /// Reorganization handler for blockchain forks
 pub struct ReorgHandler {
     blockchain: Arc<Blockchain>,
+    events: Option<Arc<EventBus>>,
+    metrics: Option<Arc<MetricsCollector>>,
+    txpool: Option<Arc<EnhancedTxPool>>,
 }
 
 impl ReorgHandler {
     /// Create a new reorg handler
     pub fn new(blockchain: Arc<Blockchain>) -> Self {
-        Self { blockchain }
+        Self {
+            blockchain,
+            events: None,
+            metrics: None,
+            txpool: None,
+        }
+    }
+
+    /// Publish `ReorgDetected` on `events` after every successful reorg
+    pub fn with_event_bus(mut self, events: Arc<EventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Record the depth of every successful reorg in `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Return transactions of reverted blocks to `txpool` after every
+    /// successful reorg, dropping the ones the new chain includes
+    pub fn with_txpool(mut self, txpool: Arc<EnhancedTxPool>) -> Self {
+        self.txpool = Some(txpool);
+        self
     }
 
     /// Check if a reorganization is needed
@@ -93,7 +124,7 @@ impl ReorgHandler {
         // Find the fork point
         let fork_point = self.find_fork_point_internal(&old_tip, &new_chain).await;
 
-        let fork_height = match fork_point {
+        let fork_height = match &fork_point {
             Some(ref hash) => {
                 self.blockchain.get_block_by_hash(hash)
                     .await
@@ -130,6 +161,12 @@ impl ReorgHandler {
             reverted_count += 1;
         }
 
+        // Keep the reverted blocks so their transactions can go back to the pool
+        let reverted_blocks = match &self.txpool {
+            Some(_) => self.collect_reverted_blocks(&old_tip, fork_height).await,
+            None => Vec::new(),
+        };
+
         // Apply new chain blocks
         info!("Applying {} blocks from new chain", new_chain.len());
         let mut applied_count = 0u64;
@@ -158,9 +195,41 @@ impl ReorgHandler {
             applied_count += 1;
         }
 
+        // Blocks no higher than the old tip were stored off the canonical
+        // chain, so switch the indexes over to the new branch explicitly
+        if applied_count > 0 {
+            let new_tip = new_chain.last().expect("new chain is not empty");
+            if let Err(e) = self.blockchain.set_canonical_head(new_tip).await {
+                error!("Failed to switch to the new chain head during reorg: {:?}", e);
+                return Ok(ReorgResult {
+                    old_tip: old_tip_hash,
+                    new_tip: new_tip_hash,
+                    reverted_count,
+                    applied_count,
+                    success: false,
+                });
+            }
+        }
+
         info!("Chain reorganization completed: reverted {} blocks, applied {} blocks",
               reverted_count, applied_count);
 
+        if let Some(txpool) = &self.txpool {
+            let applied_blocks: Vec<Block> = new_chain.into_iter()
+                .filter(|block| block.header.height > fork_height)
+                .collect();
+            txpool.handle_reorg(&reverted_blocks, &applied_blocks).await;
+        }
+
+        if reverted_count > 0 {
+            self.report_reorg(ReorgDetected {
+                depth: reverted_count,
+                old_head: old_tip_hash,
+                new_head: new_tip_hash,
+                common_ancestor: fork_point.unwrap_or_default(),
+            });
+        }
+
         Ok(ReorgResult {
             old_tip: old_tip_hash,
             new_tip: new_tip_hash,
@@ -170,6 +239,35 @@ impl ReorgHandler {
         })
     }
 
+    /// Walk back from `old_tip` to collect the blocks above `fork_height`
+    async fn collect_reverted_blocks(&self, old_tip: &Block, fork_height: i64) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        let mut current = Some(old_tip.clone());
+
+        while let Some(block) = current.filter(|b| b.header.height > fork_height) {
+            current = self.blockchain.get_block_by_hash(&block.header.prev_block_hash).await;
+            blocks.push(block);
+        }
+
+        if blocks.len() as i64 != old_tip.header.height - fork_height {
+            warn!("Only found {} of the reverted blocks above height {}", blocks.len(), fork_height);
+        }
+        blocks
+    }
+
+    /// Record a completed reorg in metrics and publish it to subscribers
+    fn report_reorg(&self, event: ReorgDetected) {
+        warn!("Chain reorganization of depth {} ({:?} -> {:?})",
+              event.depth, event.old_head, event.new_head);
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record_reorg(event.depth);
+        }
+        if let Some(events) = &self.events {
+            events.publish(event);
+        }
+    }
+
     /// Find the common ancestor (fork point) between two chains
     ///
     /// This is the internal implementation that works with an actual chain
@@ -275,6 +373,7 @@ mod tests {
                 params: vec![],
                 gas_limit: 1000000,
                 base_fee: 1000000000, // 1 Gwei
+                logs_bloom: Vec::new(),
                 state_root: Hash::default(),
             },
             transactions: vec![],
@@ -294,25 +393,30 @@ mod tests {
             // Update the block hash to be unique
             block.header.block_hash = Hash([i as u8; 32]);
 
-            // Add block
-            blockchain.add_block(block.clone()).await;
+            append_test_block(&blockchain, &db, block).await;
+        }
 
-            // Wait for async processing
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        (blockchain, db, temp_dir)
+    }
 
-            // Manually update latest_block for test reliability
-            let mut current_latest = blockchain.latest_block.write().await;
-            if block.header.height > current_latest.header.height {
-                *current_latest = block.clone();
-            }
+    /// Helper to add a block on top of the test blockchain
+    async fn append_test_block(blockchain: &Blockchain, db: &SledDB, block: Block) {
+        // Add block
+        blockchain.add_block(block.clone()).await;
+
+        // Wait for async processing
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
-            // Store in database
-            let block_bytes = norn_common::utils::codec::serialize(&block).unwrap();
-            let db_key = norn_common::utils::db_keys::block_hash_to_db_key(&block.header.block_hash);
-            let _ = db.insert(&db_key, &block_bytes).await;
+        // Manually update latest_block for test reliability
+        let mut current_latest = blockchain.latest_block.write().await;
+        if block.header.height > current_latest.header.height {
+            *current_latest = block.clone();
         }
 
-        (blockchain, db, temp_dir)
+        // Store in database
+        let block_bytes = norn_common::utils::codec::serialize(&block).unwrap();
+        let db_key = norn_common::utils::db_keys::block_hash_to_db_key(&block.header.block_hash);
+        let _ = db.insert(&db_key, &block_bytes).await;
     }
 
     #[tokio::test]
@@ -512,6 +616,87 @@ mod tests {
         assert!(result.success);
     }
 
+    #[tokio::test]
+    async fn test_reorg_emits_event_with_depth() {
+        let (blockchain, _db, _temp_dir) = create_test_blockchain().await;
+        let events = Arc::new(EventBus::default());
+        let mut reorgs = events.subscribe::<ReorgDetected>();
+        let handler = ReorgHandler::new(blockchain.clone())
+            .with_event_bus(events)
+            .with_metrics(Arc::new(MetricsCollector::new()));
+
+        let old_tip_hash = blockchain.latest_block.read().await.header.block_hash;
+
+        // Competing chain forking off height 1, replacing heights 2 and 3
+        let fork_point_hash = Hash([1u8; 32]);
+        let mut new_chain = vec![];
+        let mut prev_hash = fork_point_hash;
+        for i in 2..=4 {
+            let mut block = create_test_block(i, prev_hash);
+            block.header.block_hash = Hash([20 + i as u8; 32]);
+            prev_hash = block.header.block_hash;
+            new_chain.push(block);
+        }
+
+        let result = handler.execute_reorg(new_chain).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.reverted_count, 2);
+
+        let event = reorgs.try_recv().unwrap();
+        assert_eq!(event.depth, 2);
+        assert_eq!(event.old_head, old_tip_hash);
+        assert_eq!(event.new_head, Hash([24u8; 32]));
+        assert_eq!(event.common_ancestor, fork_point_hash);
+    }
+
+    #[tokio::test]
+    async fn test_reorg_returns_orphaned_transactions_to_pool() {
+        let (blockchain, db, _temp_dir) = create_test_blockchain().await;
+        let txpool = Arc::new(EnhancedTxPool::new());
+        let handler = ReorgHandler::new(blockchain.clone()).with_txpool(txpool.clone());
+
+        let tx = |id: u8, nonce: i64| Transaction {
+            body: TransactionBody {
+                hash: Hash([id; 32]),
+                address: Address([id; 20]),
+                nonce,
+                ..Default::default()
+            },
+        };
+
+        // Blocks 4 and 5 of the old chain carry transactions
+        let mut prev_hash = Hash([3u8; 32]);
+        for (i, txs) in [(4, vec![tx(41, 0), tx(42, 0)]), (5, vec![tx(51, 0)])] {
+            let mut block = create_test_block(i, prev_hash);
+            block.header.block_hash = Hash([i as u8; 32]);
+            block.transactions = txs;
+            prev_hash = block.header.block_hash;
+            append_test_block(&blockchain, &db, block).await;
+        }
+
+        // The competing chain forks off height 3 and re-mines transaction 41
+        let mut new_chain = vec![];
+        let mut prev_hash = Hash([3u8; 32]);
+        for i in 4..=6 {
+            let mut block = create_test_block(i, prev_hash);
+            block.header.block_hash = Hash([30 + i as u8; 32]);
+            if i == 4 {
+                block.transactions = vec![tx(41, 0)];
+            }
+            prev_hash = block.header.block_hash;
+            new_chain.push(block);
+        }
+
+        let result = handler.execute_reorg(new_chain).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.reverted_count, 2);
+
+        assert!(!txpool.contains(&Hash([41u8; 32])).await);
+        assert!(txpool.contains(&Hash([42u8; 32])).await);
+        assert!(txpool.contains(&Hash([51u8; 32])).await);
+        assert_eq!(txpool.stats().await.size, 2);
+    }
+
     #[tokio::test]
     async fn test_find_fork_point_between_chains() {
         let (blockchain, _db, _temp_dir) = create_test_blockchain().await;
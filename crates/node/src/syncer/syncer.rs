@@ -7,9 +7,15 @@ use std::time::Duration;
 use std::collections::HashMap;
 use tokio::sync::RwLock;
 use tokio::time::interval;
+use norn_core::block_buffer::verify_vrfs;
 use norn_core::blockchain::Blockchain;
 use norn_network::NetworkService;
+use norn_network::block_bodies::BlockBodyStore;
+use norn_network::messages::sync::{BlockBody, SyncStatusMessage};
+use norn_network::service::NetworkCommand;
 use norn_common::types::{Block, Hash};
+use libp2p::PeerId;
+use norn_core::readiness::SyncReadiness;
 use tracing::{info, debug, warn, error};
 
 /// Block syncer state
@@ -38,6 +44,11 @@ pub struct SyncConfig {
     pub check_interval_secs: u64,
     /// Maximum number of pending block requests
     pub max_pending_requests: usize,
+    /// How long to wait for a peer to report its tip before the node treats
+    /// its local chain as current
+    pub peer_wait_secs: u64,
+    /// How long a peer's reported tip counts towards the sync target
+    pub peer_status_ttl_secs: u64,
 }
 
 impl Default for SyncConfig {
@@ -47,6 +58,8 @@ impl Default for SyncConfig {
             timeout_secs: 30,
             check_interval_secs: 5,
             max_pending_requests: 10,
+            peer_wait_secs: 30,
+            peer_status_ttl_secs: 60,
         }
     }
 }
@@ -57,8 +70,11 @@ pub struct BlockSyncer {
     network: Arc<NetworkService>,
     config: SyncConfig,
     state: Arc<RwLock<SyncState>>,
-    target_height: Arc<RwLock<i64>>,
+    /// Latest chain tip reported by each peer and when it was reported
+    peer_tips: Arc<RwLock<HashMap<PeerId, (i64, std::time::Instant)>>>,
     pending_blocks: Arc<RwLock<HashMap<i64, PendingBlock>>>,
+    readiness: SyncReadiness,
+    started_at: std::time::Instant,
 }
 
 /// Pending block request
@@ -86,11 +102,19 @@ impl BlockSyncer {
             network,
             config,
             state: Arc::new(RwLock::new(SyncState::Idle)),
-            target_height: Arc::new(RwLock::new(0)),
+            peer_tips: Arc::new(RwLock::new(HashMap::new())),
             pending_blocks: Arc::new(RwLock::new(HashMap::new())),
+            readiness: SyncReadiness::default(),
+            started_at: std::time::Instant::now(),
         }
     }
 
+    /// Mark `readiness` ready once the node has caught up with its peers
+    pub fn with_readiness(mut self, readiness: SyncReadiness) -> Self {
+        self.readiness = readiness;
+        self
+    }
+
     /// Start the syncer
     pub async fn start(&self) {
         info!("Block syncer started");
@@ -109,14 +133,48 @@ impl BlockSyncer {
 
     /// Perform a sync check
     async fn sync_check(&self) -> anyhow::Result<()> {
-        let local_height = {
+        // Blocks past a refused fork can't be imported
+        if let Some(refused) = self.blockchain.refused_reorg() {
+            *self.state.write().await = SyncState::Error;
+            anyhow::bail!(
+                "Halted after refusing the fork at {:?} ({} blocks deep above height {}); resync the node",
+                refused.tip,
+                refused.depth,
+                refused.fork_height
+            );
+        }
+
+        let (local_height, latest_hash) = {
             let latest = self.blockchain.latest_block.read().await;
-            latest.header.height
+            (latest.header.height, latest.header.block_hash)
+        };
+
+        let target = self.target_height().await;
+        self.announce_status(local_height, latest_hash, target).await;
+
+        let target = match target {
+            Some(target) => target,
+            None => {
+                // Without a current peer tip there is nothing to catch up
+                // with, whether or not peers are connected
+                let waited = self.started_at.elapsed() >= Duration::from_secs(self.config.peer_wait_secs);
+                if waited && !self.readiness.is_ready() {
+                    info!(
+                        "No peer reported its tip, serving local chain at height {} ({} peers connected)",
+                        local_height,
+                        self.network.status.peer_count()
+                    );
+                    self.readiness.set_ready();
+                }
+                return Ok(());
+            }
         };
 
-        let target = *self.target_height.read().await;
-        
         if local_height >= target {
+            if !self.readiness.is_ready() {
+                info!("Initial sync complete at height {}, serving state queries", local_height);
+                self.readiness.set_ready();
+            }
             let mut state = self.state.write().await;
             if *state != SyncState::Idle {
                 *state = SyncState::Complete;
@@ -139,15 +197,51 @@ impl BlockSyncer {
         Ok(())
     }
 
-    /// Update target height from peer announcement
-    pub async fn update_target_height(&self, height: i64) {
-        let mut target = self.target_height.write().await;
-        if height > *target {
-            info!("Updated target height to {}", height);
-            *target = height;
+    /// Broadcast the local chain tip so peers can track their sync target
+    async fn announce_status(&self, height: i64, latest_hash: Hash, target: Option<i64>) {
+        let state = *self.state.read().await;
+        let status = SyncStatusMessage {
+            current_height: height.max(0) as u64,
+            latest_hash,
+            sync_state: format!("{:?}", state),
+            target_height: target.map(|t| t.max(0) as u64),
+            downloaded_blocks: 0,
+            verified_blocks: 0,
+            estimated_time_remaining: None,
+        };
+        if let Err(e) = self.network.command_tx.send(NetworkCommand::AnnounceStatus(status)).await {
+            warn!("Failed to announce sync status: {}", e);
         }
     }
 
+    /// Track the chain tip `peer` announced
+    pub async fn handle_peer_status(&self, peer: PeerId, status: &SyncStatusMessage) {
+        self.record_peer_tip(peer, status.current_height.min(i64::MAX as u64) as i64).await;
+    }
+
+    /// Record `height` as the latest tip reported by `peer`
+    pub async fn record_peer_tip(&self, peer: PeerId, height: i64) {
+        let previous = self.peer_tips.write().await.insert(peer, (height, std::time::Instant::now()));
+        if previous.map(|(h, _)| h) != Some(height) {
+            debug!("Peer {} reported tip {}", peer, height);
+        }
+    }
+
+    /// The sync target: the median of the tips peers reported recently
+    ///
+    /// Peer reports are unverified, so no single peer can move the target:
+    /// it is the lower median, a height at least half of the peers claim to
+    /// have reached. Reports older than `peer_status_ttl_secs` are dropped.
+    async fn target_height(&self) -> Option<i64> {
+        let ttl = Duration::from_secs(self.config.peer_status_ttl_secs);
+        let mut tips = self.peer_tips.write().await;
+        tips.retain(|_, (_, seen)| seen.elapsed() < ttl);
+
+        let mut heights: Vec<i64> = tips.values().map(|(height, _)| *height).collect();
+        heights.sort_unstable();
+        heights.get(heights.len().saturating_sub(1) / 2).copied()
+    }
+
     /// Get current sync state
     pub async fn get_state(&self) -> SyncState {
         *self.state.read().await
@@ -155,7 +249,7 @@ impl BlockSyncer {
 
     /// Get current target height
     pub async fn get_target_height(&self) -> i64 {
-        *self.target_height.read().await
+        self.target_height().await.unwrap_or(0)
     }
 
     /// Check if currently syncing
@@ -170,7 +264,7 @@ impl BlockSyncer {
             let latest = self.blockchain.latest_block.read().await;
             latest.header.height
         };
-        let target = *self.target_height.read().await;
+        let target = self.get_target_height().await;
         
         if target == 0 {
             return 1.0;
@@ -237,7 +331,13 @@ impl BlockSyncer {
             warn!("Received block {} but expected {}", height, expected_height);
             return Ok(());
         }
-        
+
+        // Synced blocks get the same VRF check as gossiped ones
+        let Some(block) = verify_vrfs(vec![block]).await.pop() else {
+            warn!("Rejected synced block {} with invalid VRF proof", height);
+            return Ok(());
+        };
+
         // Save block
         self.blockchain.save_block(&block).await?;
         info!("Applied block at height {}", height);
@@ -245,15 +345,6 @@ impl BlockSyncer {
         Ok(())
     }
 
-    /// Handle received blocks (batch)
-    pub async fn handle_blocks(&self, blocks: Vec<Block>) {
-        for block in blocks {
-            if let Err(e) = self.handle_block(block).await {
-                error!("Failed to handle block: {}", e);
-            }
-        }
-    }
-
     /// Clean up timed out requests
     pub async fn cleanup_pending(&self) {
         let timeout = Duration::from_secs(self.config.timeout_secs);
@@ -266,6 +357,32 @@ impl BlockSyncer {
     }
 }
 
+/// Serves block bodies to peers from the local chain
+pub struct ChainBodyStore {
+    blockchain: Arc<Blockchain>,
+}
+
+impl ChainBodyStore {
+    pub fn new(blockchain: Arc<Blockchain>) -> Self {
+        Self { blockchain }
+    }
+}
+
+#[async_trait::async_trait]
+impl BlockBodyStore for ChainBodyStore {
+    async fn block_body(&self, hash: &Hash) -> Option<BlockBody> {
+        let block = self.blockchain.get_block_by_hash(hash).await?;
+        // Pruned blocks only keep their header
+        if self.blockchain.is_block_pruned(block.header.height) {
+            return None;
+        }
+        Some(BlockBody {
+            block_hash: block.header.block_hash,
+            transactions: block.transactions,
+        })
+    }
+}
+
 /// Block request message
 struct BlockRequest {
     height: i64,
@@ -287,6 +404,97 @@ mod tests {
         assert_ne!(SyncState::Idle, SyncState::Complete);
     }
 
+    async fn test_syncer(config: SyncConfig) -> (tempfile::TempDir, Arc<Blockchain>, BlockSyncer, SyncReadiness) {
+        use norn_storage::SledDB;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = Arc::new(SledDB::new(temp_dir.path()).unwrap());
+        let blockchain = Blockchain::new_with_fixed_genesis(db).await;
+        let network_config = norn_network::config::NetworkConfig {
+            listen_address: "/ip4/127.0.0.1/tcp/0".to_string(),
+            mdns: false,
+            ..Default::default()
+        };
+        let network = Arc::new(
+            NetworkService::start(network_config, libp2p::identity::Keypair::generate_ed25519())
+                .await
+                .unwrap(),
+        );
+        let readiness = SyncReadiness::syncing();
+        let syncer = BlockSyncer::with_config(blockchain.clone(), network, config).with_readiness(readiness.clone());
+        (temp_dir, blockchain, syncer, readiness)
+    }
+
+    #[tokio::test]
+    async fn test_lagging_node_not_ready_until_caught_up() {
+        let (_dir, blockchain, syncer, readiness) = test_syncer(SyncConfig::default()).await;
+
+        // No peer tip known yet
+        syncer.sync_check().await.unwrap();
+        assert!(!readiness.is_ready());
+
+        syncer.record_peer_tip(PeerId::random(), 2).await;
+        syncer.sync_check().await.unwrap();
+        assert!(!readiness.is_ready());
+
+        for height in 1..=2 {
+            let mut block = Block::default();
+            block.header.height = height;
+            block.header.prev_block_hash = blockchain.latest_block.read().await.header.block_hash;
+            block.header.block_hash = Hash([height as u8; 32]);
+            blockchain.commit_block(&block).await.unwrap();
+
+            syncer.sync_check().await.unwrap();
+            assert_eq!(readiness.is_ready(), height == 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_target_is_median_of_peer_tips() {
+        let (_dir, _blockchain, syncer, _readiness) = test_syncer(SyncConfig::default()).await;
+        let peers: Vec<PeerId> = (0..3).map(|_| PeerId::random()).collect();
+
+        // A single peer claiming a far tip doesn't move the target
+        syncer.record_peer_tip(peers[0], 10).await;
+        syncer.record_peer_tip(peers[1], 12).await;
+        syncer.record_peer_tip(peers[2], 1_000_000).await;
+        assert_eq!(syncer.get_target_height().await, 12);
+
+        // Neither do two peers out of four
+        syncer.record_peer_tip(PeerId::random(), 1_000_000).await;
+        assert_eq!(syncer.get_target_height().await, 12);
+
+        // A peer's newer report replaces its older one
+        syncer.record_peer_tip(peers[1], 8).await;
+        assert_eq!(syncer.get_target_height().await, 10);
+    }
+
+    #[tokio::test]
+    async fn test_stale_peer_tips_expire() {
+        let config = SyncConfig { peer_status_ttl_secs: 0, peer_wait_secs: 0, ..SyncConfig::default() };
+        let (_dir, _blockchain, syncer, readiness) = test_syncer(config).await;
+
+        syncer.record_peer_tip(PeerId::random(), 50).await;
+        assert_eq!(syncer.get_target_height().await, 0);
+
+        // With no current tip to catch up with the node serves its chain
+        syncer.sync_check().await.unwrap();
+        assert!(readiness.is_ready());
+    }
+
+    #[tokio::test]
+    async fn test_synced_block_without_vrf_rejected() {
+        let (_dir, blockchain, syncer, _readiness) = test_syncer(SyncConfig::default()).await;
+
+        let mut block = Block::default();
+        block.header.height = 1;
+        block.header.prev_block_hash = blockchain.latest_block.read().await.header.block_hash;
+        block.header.block_hash = Hash([1; 32]);
+        syncer.handle_block(block.clone()).await.unwrap();
+
+        assert!(blockchain.get_block_by_hash(&block.header.block_hash).await.is_none());
+    }
+
     #[test]
     fn test_sync_config_default() {
         let config = SyncConfig::default();
@@ -1,22 +1,69 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use norn_core::txpool::TxPool;
-use norn_common::types::Transaction;
+use norn_core::txpool_enhanced::EnhancedTxPool;
+use norn_common::types::{Hash, Transaction};
 use norn_common::utils::codec;
-use tracing::{warn, info};
+use norn_crypto::transaction::verify_transaction;
+use norn_network::TransactionStore;
+use tracing::{debug, warn, info};
 
 pub struct TxHandler {
     pool: Arc<TxPool>,
+    /// Tracks admitted transactions for expiry and re-announcement
+    enhanced_pool: Option<Arc<EnhancedTxPool>>,
+    chain_id: u64,
+    /// Gossiped transactions that reached signature verification
+    signature_checks: AtomicU64,
 }
 
 impl TxHandler {
-    pub fn new(pool: Arc<TxPool>) -> Self {
-        Self { pool }
+    pub fn new(pool: Arc<TxPool>, chain_id: u64) -> Self {
+        Self {
+            pool,
+            enhanced_pool: None,
+            chain_id,
+            signature_checks: AtomicU64::new(0),
+        }
+    }
+
+    /// Also add admitted transactions to the enhanced pool
+    pub fn with_enhanced_pool(mut self, pool: Arc<EnhancedTxPool>) -> Self {
+        self.enhanced_pool = Some(pool);
+        self
+    }
+
+    /// Number of gossiped transactions whose signature has been checked
+    pub fn signature_checks(&self) -> u64 {
+        self.signature_checks.load(Ordering::Relaxed)
     }
 
     pub async fn handle_tx_data(&self, data: Vec<u8>) {
         match codec::deserialize::<Transaction>(&data) {
             Ok(tx) => {
+                // Cheap check first: transactions for another chain never verify
+                if let Some(chain_id) = tx.body.chain_id {
+                    if chain_id != self.chain_id {
+                        debug!(
+                            "Dropping tx hash={} for chain id {} (node chain id {})",
+                            tx.body.hash, chain_id, self.chain_id
+                        );
+                        return;
+                    }
+                }
+
+                self.signature_checks.fetch_add(1, Ordering::Relaxed);
+                if let Err(e) = verify_transaction(&tx) {
+                    warn!("Dropping tx hash={} with invalid signature: {:?}", tx.body.hash, e);
+                    return;
+                }
+
                 info!("Received tx hash={}", tx.body.hash);
+                if let Some(enhanced_pool) = &self.enhanced_pool {
+                    if let Err(e) = enhanced_pool.add(tx.clone()).await {
+                        debug!("Enhanced pool rejected tx hash={}: {}", tx.body.hash, e);
+                    }
+                }
                 self.pool.add(tx);
             }
             Err(e) => {
@@ -25,3 +72,73 @@ impl TxHandler {
         }
     }
 }
+
+/// Serves announced transactions to peers from the local pool
+pub struct PoolTxStore {
+    pool: Arc<TxPool>,
+}
+
+impl PoolTxStore {
+    pub fn new(pool: Arc<TxPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionStore for PoolTxStore {
+    async fn transaction(&self, hash: &Hash) -> Option<Transaction> {
+        self.pool.get(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use norn_crypto::transaction::TransactionSigner;
+    use norn_crypto::ecdsa::KeyPair;
+
+    fn signed_tx(chain_id: Option<u64>) -> Transaction {
+        let mut signer = TransactionSigner::new(KeyPair::random());
+        let mut tx = signer
+            .create_transaction(
+                norn_common::types::Address([2u8; 20]),
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                21_000,
+                i64::MAX,
+            )
+            .unwrap();
+        tx.body.chain_id = chain_id;
+        tx
+    }
+
+    #[tokio::test]
+    async fn test_wrong_chain_id_dropped_before_signature_check() {
+        let pool = Arc::new(TxPool::new());
+        let handler = TxHandler::new(pool.clone(), 31337);
+
+        let tx = signed_tx(Some(1));
+        handler.handle_tx_data(codec::serialize(&tx).unwrap()).await;
+        assert_eq!(handler.signature_checks(), 0);
+        assert!(!pool.contains(&tx.body.hash));
+
+        let tx = signed_tx(Some(31337));
+        handler.handle_tx_data(codec::serialize(&tx).unwrap()).await;
+        assert_eq!(handler.signature_checks(), 1);
+        assert!(pool.contains(&tx.body.hash));
+    }
+
+    #[tokio::test]
+    async fn test_admitted_tx_added_to_enhanced_pool() {
+        let pool = Arc::new(TxPool::new());
+        let enhanced_pool = Arc::new(EnhancedTxPool::new());
+        let handler = TxHandler::new(pool.clone(), 31337).with_enhanced_pool(enhanced_pool.clone());
+
+        let tx = signed_tx(Some(31337));
+        handler.handle_tx_data(codec::serialize(&tx).unwrap()).await;
+        assert!(pool.contains(&tx.body.hash));
+        assert!(enhanced_pool.contains(&tx.body.hash).await);
+    }
+}
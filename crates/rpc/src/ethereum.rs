@@ -4,6 +4,7 @@
 //! Supports standard eth_* methods like eth_getBalance, eth_call, eth_getBlockByNumber, etc.
 
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::net::SocketAddr;
 use jsonrpsee::core::{async_trait, RpcResult};
 use jsonrpsee::proc_macros::rpc;
@@ -13,13 +14,22 @@ use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
 use anyhow::anyhow;
 use norn_core::blockchain::Blockchain;
-use norn_core::state::{AccountStateManager, AccountStateConfig};
-use norn_core::evm::{EVMExecutor, EVMConfig, EVMContext};
-use norn_core::TxPool;
+use norn_core::consensus::metrics::ConsensusStatus;
+use norn_core::consensus::povf::PoVFEngine;
+use norn_core::state::{AccountStateManager, AccountStateConfig, StateArchive, StateReadCache};
+use norn_core::evm::{Bloom, EVMError, EVMExecutor, EVMContext};
+use norn_core::{RawTransactionStore, TxPool};
 use norn_common::types::{Address, Hash, Transaction, PublicKey};
+use norn_common::utils::address::to_checksum_address;
+use norn_network::NetworkStatus;
+use norn_storage::{RecoveryOutcome, SledDB};
+use norn_core::readiness::SyncReadiness;
 use num_bigint::BigUint;
 use keccak_hash::keccak256;
 
+/// Ethereum wire protocol version reported by `eth_protocolVersion` (eth/68)
+pub const ETH_PROTOCOL_VERSION: u64 = 68;
+
 /// Ethereum JSON-RPC API
 #[rpc(server)]
 pub trait EthereumRpc {
@@ -27,6 +37,10 @@ pub trait EthereumRpc {
     #[method(name = "web3_clientVersion")]
     async fn client_version(&self) -> RpcResult<String>;
 
+    /// Keccak-256 hash of the given hex data
+    #[method(name = "web3_sha3")]
+    async fn sha3(&self, data: String) -> RpcResult<String>;
+
     /// Get accounts (MetaMask requires this to return list of accounts)
     #[method(name = "eth_accounts")]
     async fn accounts(&self) -> RpcResult<Vec<Address>>;
@@ -63,13 +77,20 @@ pub trait EthereumRpc {
     #[method(name = "eth_gasPrice")]
     async fn gas_price(&self) -> RpcResult<String>;
 
-    /// Estimate gas for a transaction
+    /// Estimate gas for a transaction, optionally as if in an overridden block
     #[method(name = "eth_estimateGas")]
-    async fn estimate_gas(&self, request: CallRequest) -> RpcResult<String>;
+    async fn estimate_gas(&self, request: CallRequest, block_overrides: Option<BlockOverride>) -> RpcResult<String>;
 
-    /// Call a contract method without creating a transaction
+    /// Call a contract method without creating a transaction, optionally
+    /// against overridden account state and block fields
     #[method(name = "eth_call")]
-    async fn call(&self, request: CallRequest, block: BlockNumber) -> RpcResult<String>;
+    async fn call(
+        &self,
+        request: CallRequest,
+        block: BlockNumber,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<BlockOverride>,
+    ) -> RpcResult<String>;
 
     /// Get a transaction by hash
     #[method(name = "eth_getTransactionByHash")]
@@ -95,6 +116,10 @@ pub trait EthereumRpc {
     #[method(name = "eth_sendRawTransaction")]
     async fn send_raw_transaction(&self, data: String) -> RpcResult<Hash>;
 
+    /// Get the signed, RLP-encoded bytes of a transaction submitted raw
+    #[method(name = "eth_getRawTransactionByHash")]
+    async fn get_raw_transaction_by_hash(&self, hash: Hash) -> RpcResult<Option<String>>;
+
     /// Send a transaction (for wallet integration)
     #[method(name = "eth_sendTransaction")]
     async fn send_transaction(&self, request: TransactionRequest) -> RpcResult<Hash>;
@@ -131,6 +156,26 @@ pub trait EthereumRpc {
     #[method(name = "eth_syncing")]
     async fn syncing(&self) -> RpcResult<bool>;
 
+    /// Get the Ethereum protocol version
+    #[method(name = "eth_protocolVersion")]
+    async fn protocol_version(&self) -> RpcResult<String>;
+
+    /// Get the number of connected peers
+    #[method(name = "net_peerCount")]
+    async fn peer_count(&self) -> RpcResult<String>;
+
+    /// Get whether the node is listening for peer connections
+    #[method(name = "net_listening")]
+    async fn listening(&self) -> RpcResult<bool>;
+
+    /// Get the consensus round, proposer and validator set (null without consensus)
+    #[method(name = "norn_consensusStatus")]
+    async fn consensus_status(&self) -> RpcResult<Option<ConsensusStatus>>;
+
+    /// Get the outcome of the last WAL recovery (null if recovery never ran)
+    #[method(name = "norn_recoveryStatus")]
+    async fn recovery_status(&self) -> RpcResult<Option<RecoveryOutcome>>;
+
     /// Get transaction count by block hash
     #[method(name = "eth_getBlockTransactionCountByHash")]
     async fn get_block_transaction_count_by_hash(&self, hash: Hash) -> RpcResult<String>;
@@ -148,6 +193,14 @@ pub trait EthereumRpc {
     /// Development only: Mint ETH to an address (faucet)
     #[method(name = "dev_faucet")]
     async fn dev_faucet(&self, address: Address, amount: String) -> RpcResult<bool>;
+
+    /// Development only: Write a storage slot of an address directly
+    #[method(name = "dev_setStorageAt")]
+    async fn dev_set_storage_at(&self, address: Address, slot: String, value: String) -> RpcResult<bool>;
+
+    /// Development only: Replace the code of an address directly
+    #[method(name = "dev_setCode")]
+    async fn dev_set_code(&self, address: Address, code: String) -> RpcResult<bool>;
 }
 
 /// Block identifier for RPC calls
@@ -217,6 +270,152 @@ pub struct CallRequest {
     pub data: Option<String>,
 }
 
+/// State-override set for eth_call, keyed by account address
+pub type StateOverride = std::collections::HashMap<Address, AccountOverride>;
+
+/// Overrides for a single account, in Geth's eth_call format
+///
+/// All values are hex strings; `state` replaces the whole storage of the
+/// account while `stateDiff` only sets the given slots.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    /// Replacement balance (in wei)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<String>,
+    /// Replacement nonce
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    /// Replacement runtime code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Full replacement of the account storage (slot => value)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<std::collections::HashMap<String, String>>,
+    /// Storage slots to set (slot => value)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<std::collections::HashMap<String, String>>,
+}
+
+impl AccountOverride {
+    /// Convert to the executor's override, rejecting malformed hex
+    fn to_evm_override(&self) -> Result<norn_core::evm::AccountOverride, ErrorObject<'static>> {
+        if self.state.is_some() && self.state_diff.is_some() {
+            return Err(ErrorObject::owned(
+                ErrorCode::InvalidParams.code(),
+                "state and stateDiff can't be set for the same account",
+                None::<()>,
+            ));
+        }
+
+        let invalid = || ErrorObject::from(ErrorCode::InvalidParams);
+        let quantity = |v: &str| -> Result<BigUint, ErrorObject<'static>> {
+            let digits = v.strip_prefix("0x").unwrap_or(v);
+            BigUint::parse_bytes(if digits.is_empty() { b"0" } else { digits.as_bytes() }, 16)
+                .ok_or_else(invalid)
+        };
+        let slots = |map: &std::collections::HashMap<String, String>| {
+            map.iter()
+                .map(|(slot, value)| Ok((parse_word(slot)?, parse_word(value)?)))
+                .collect::<Result<std::collections::HashMap<_, _>, ErrorObject<'static>>>()
+        };
+
+        Ok(norn_core::evm::AccountOverride {
+            balance: self.balance.as_deref().map(quantity).transpose()?,
+            nonce: self.nonce.as_deref()
+                .map(|n| quantity(n).and_then(|n| u64::try_from(n).map_err(|_| invalid())))
+                .transpose()?,
+            code: self.code.as_deref()
+                .map(|c| hex::decode(c.strip_prefix("0x").unwrap_or(c)).map_err(|_| invalid()))
+                .transpose()?,
+            state: self.state.as_ref().map(slots).transpose()?,
+            state_diff: self.state_diff.as_ref().map(slots).transpose()?.unwrap_or_default(),
+        })
+    }
+}
+
+/// Parse a hex storage slot or value into a 32-byte big-endian word
+fn parse_word(v: &str) -> Result<[u8; 32], ErrorObject<'static>> {
+    let digits = v.strip_prefix("0x").unwrap_or(v);
+    // Quantities such as "0x0" may have an odd number of digits
+    let padded;
+    let digits = if digits.len() % 2 == 1 {
+        padded = format!("0{}", digits);
+        &padded
+    } else {
+        digits
+    };
+    let bytes = hex::decode(digits).map_err(|_| ErrorObject::from(ErrorCode::InvalidParams))?;
+    if bytes.len() > 32 {
+        return Err(ErrorObject::from(ErrorCode::InvalidParams));
+    }
+    let mut word = [0u8; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(word)
+}
+
+/// Key of `slot` in the account storage, the way the revm adapter looks it up
+fn storage_key(slot: &[u8; 32]) -> Vec<u8> {
+    slot.iter().skip_while(|&&b| b == 0).copied().collect()
+}
+
+/// Block fields to override for a single eth_call or eth_estimateGas, in
+/// Geth's format
+///
+/// All values are hex strings; unset fields keep the value the call would
+/// otherwise run with.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverride {
+    /// Block number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>,
+    /// Block timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    /// Block gas limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<String>,
+    /// Block coinbase
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_recipient: Option<Address>,
+    /// Base fee per gas
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_fee: Option<String>,
+}
+
+impl BlockOverride {
+    /// Apply the overrides to `ctx`, rejecting malformed hex
+    ///
+    /// The gas price is raised to an overridden base fee so the call stays
+    /// valid in the overridden block.
+    fn apply(&self, ctx: &mut EVMContext) -> Result<(), ErrorObject<'static>> {
+        let quantity = |v: &str| {
+            let digits = v.strip_prefix("0x").unwrap_or(v);
+            u64::from_str_radix(if digits.is_empty() { "0" } else { digits }, 16)
+                .map_err(|_| ErrorObject::from(ErrorCode::InvalidParams))
+        };
+
+        if let Some(number) = self.number.as_deref() {
+            ctx.block_number = quantity(number)?;
+        }
+        if let Some(time) = self.time.as_deref() {
+            ctx.block_timestamp = quantity(time)?;
+        }
+        if let Some(gas_limit) = self.gas_limit.as_deref() {
+            ctx.block_gas_limit = quantity(gas_limit)?;
+        }
+        if let Some(fee_recipient) = self.fee_recipient {
+            ctx.block_coinbase = fee_recipient;
+        }
+        if let Some(base_fee) = self.base_fee.as_deref() {
+            ctx.block_base_fee = quantity(base_fee)?;
+            ctx.tx_gas_price = ctx.tx_gas_price.max(ctx.block_base_fee);
+        }
+        Ok(())
+    }
+}
+
 /// Transaction request for eth_sendTransaction
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionRequest {
@@ -280,6 +479,8 @@ pub struct TransactionReceipt {
     pub logs_bloom: String,
     /// Status (1 for success, 0 for failure)
     pub status: String,
+    /// Whether the block is at least `finality_depth` blocks below the head
+    pub finalized: bool,
 }
 
 /// Log entry
@@ -315,9 +516,35 @@ pub struct LogFilter {
     /// Contract address
     #[serde(skip_serializing_if = "Option::is_none")]
     pub address: Option<Address>,
-    /// Topics to filter by
+    /// Topics to filter by, per position; `null` matches any topic
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub topics: Option<Vec<Option<Hash>>>,
+    pub topics: Option<Vec<Option<TopicFilter>>>,
+}
+
+/// Topics accepted at one position of a log filter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TopicFilter {
+    /// Exactly this topic
+    Single(Hash),
+    /// Any of these topics; an empty list matches anything
+    AnyOf(Vec<Hash>),
+}
+
+impl TopicFilter {
+    /// The accepted topics
+    pub fn topics(&self) -> &[Hash] {
+        match self {
+            TopicFilter::Single(topic) => std::slice::from_ref(topic),
+            TopicFilter::AnyOf(topics) => topics,
+        }
+    }
+
+    /// Whether `topic` is accepted at this position
+    pub fn matches(&self, topic: &[u8]) -> bool {
+        let accepted = self.topics();
+        accepted.is_empty() || accepted.iter().any(|t| t.0.as_slice() == topic)
+    }
 }
 
 /// Fee history information
@@ -358,10 +585,81 @@ pub struct Block {
     pub transactions_root: String,
     /// Receipts root (not used in norn)
     pub receipts_root: String,
+    /// Bloom of the logs in the block's receipts
+    pub logs_bloom: String,
     /// Extra data
     pub extra_data: String,
     /// Transactions
-    pub transactions: Vec<Transaction>,
+    pub transactions: BlockTransactions,
+}
+
+/// Transactions of an RPC block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BlockTransactions {
+    /// Transaction hashes only
+    Hashes(#[serde(with = "prefixed_hashes")] Vec<Hash>),
+    /// Full transactions
+    Full(Vec<Transaction>),
+}
+
+/// Hashes as 0x-prefixed hex strings, the way Ethereum clients expect them
+mod prefixed_hashes {
+    use norn_common::types::Hash;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(hashes: &[Hash], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(hashes.iter().map(|hash| format!("0x{}", hex::encode(hash.0))))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Hash>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|hash| {
+                let bytes = hex::decode(hash.strip_prefix("0x").unwrap_or(hash)).map_err(D::Error::custom)?;
+                let bytes: [u8; 32] = bytes.try_into().map_err(|_| D::Error::custom("hash must be 32 bytes"))?;
+                Ok(Hash(bytes))
+            })
+            .collect()
+    }
+}
+
+/// Limits applied to Ethereum RPC requests
+#[derive(Debug, Clone)]
+pub struct RpcConfig {
+    /// Maximum gas an eth_call or eth_estimateGas may use; requests asking
+    /// for more are capped. Lowered to the block gas limit when that is
+    /// smaller, so calls never get more gas than an included transaction.
+    pub call_gas_cap: u64,
+
+    /// Wall-clock limit for a single eth_call
+    pub call_timeout: std::time::Duration,
+
+    /// Widest block range an eth_getLogs query may span
+    pub max_log_range: u64,
+
+    /// Most logs a single eth_getLogs query may return
+    pub max_log_results: usize,
+
+    /// Serve dev_* methods that write state directly; for test networks only
+    pub enable_dev_methods: bool,
+
+    /// Blocks a block must be buried under before its receipts are
+    /// reported finalized
+    pub finality_depth: u64,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            call_gas_cap: 5_000_000,
+            call_timeout: std::time::Duration::from_secs(5),
+            max_log_range: 10_000,
+            max_log_results: 10_000,
+            enable_dev_methods: false,
+            finality_depth: 3,
+        }
+    }
 }
 
 /// Ethereum RPC implementation
@@ -371,6 +669,14 @@ pub struct EthereumRpcImpl {
     evm_executor: Arc<EVMExecutor>,
     tx_pool: Arc<TxPool>,
     chain_id: u64,
+    config: RpcConfig,
+    readiness: SyncReadiness,
+    state_cache: Option<Arc<StateReadCache>>,
+    network: NetworkStatus,
+    raw_txs: Arc<RawTransactionStore>,
+    consensus: Option<Arc<PoVFEngine>>,
+    archive: Option<Arc<StateArchive>>,
+    recovery_db: Option<Arc<SledDB>>,
 }
 
 impl EthereumRpcImpl {
@@ -388,9 +694,91 @@ impl EthereumRpcImpl {
             evm_executor,
             tx_pool,
             chain_id,
+            config: RpcConfig::default(),
+            readiness: SyncReadiness::default(),
+            state_cache: None,
+            network: NetworkStatus::default(),
+            raw_txs: Arc::new(RawTransactionStore::new()),
+            consensus: None,
+            archive: None,
+            recovery_db: None,
         }
     }
 
+    /// Report the consensus status of `engine`
+    pub fn with_consensus(mut self, engine: Arc<PoVFEngine>) -> Self {
+        self.consensus = Some(engine);
+        self
+    }
+
+    /// Report the last WAL recovery recorded in `db`
+    pub fn with_recovery_status(mut self, db: Arc<SledDB>) -> Self {
+        self.recovery_db = Some(db);
+        self
+    }
+
+    /// Keep the bytes of raw transactions in `store`
+    pub fn with_raw_tx_store(mut self, store: Arc<RawTransactionStore>) -> Self {
+        self.raw_txs = store;
+        self
+    }
+
+    /// Report peers and listening state from `network`
+    pub fn with_network_status(mut self, network: NetworkStatus) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Answer eth_call at past blocks by replaying from `archive` snapshots
+    ///
+    /// Without an archive such calls run against the latest state.
+    pub fn with_state_archive(mut self, archive: Arc<StateArchive>) -> Self {
+        self.archive = Some(archive);
+        self
+    }
+
+    /// Serve latest balances and nonces from `cache`
+    pub fn with_state_cache(mut self, cache: Arc<StateReadCache>) -> Self {
+        self.state_cache = Some(cache);
+        self
+    }
+
+    /// Reject state-dependent calls until `readiness` is set
+    pub fn with_readiness(mut self, readiness: SyncReadiness) -> Self {
+        self.readiness = readiness;
+        self
+    }
+
+    /// Highest block buried `finality_depth` blocks below the head, if any
+    async fn finalized_height(&self) -> Option<u64> {
+        let head = self.blockchain.latest_block.read().await.header.height.max(0) as u64;
+        head.checked_sub(self.config.finality_depth)
+    }
+
+    /// Error out unless dev_* methods are enabled in the config
+    fn ensure_dev_methods(&self) -> RpcResult<()> {
+        if self.config.enable_dev_methods {
+            Ok(())
+        } else {
+            Err(ErrorObject::owned(-32000, "dev methods are disabled", None::<()>))
+        }
+    }
+
+    /// Error out while the node is still catching up with the chain
+    fn ensure_synced(&self) -> RpcResult<()> {
+        if self.readiness.is_ready() {
+            Ok(())
+        } else {
+            Err(ErrorObject::owned(-32000, "node syncing", None::<()>))
+        }
+    }
+
+    /// Use `config` instead of the default request limits
+    pub fn with_config(mut self, config: RpcConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     /// Get block number for a BlockNumber enum
     async fn resolve_block_number(&self, block: BlockNumber) -> Option<i64> {
         let latest = self.blockchain.latest_block.read().await;
@@ -409,8 +797,84 @@ impl EthereumRpcImpl {
         }
     }
 
+    /// State to answer a query at `block`
+    ///
+    /// `pending` is served from the pending overlay; every other tag reads the
+    /// latest state.
+    async fn state_at(&self, block: &BlockNumber) -> Arc<AccountStateManager> {
+        match block {
+            BlockNumber::Pending => Arc::clone(self.pending_executor().await.state_manager()),
+            _ => Arc::clone(&self.state_manager),
+        }
+    }
+
+    /// Executor to run a call at `block`, see `state_at`
+    async fn executor_at(&self, block: &BlockNumber) -> Arc<EVMExecutor> {
+        match block {
+            BlockNumber::Pending => Arc::new(self.pending_executor().await),
+            _ => Arc::clone(&self.evm_executor),
+        }
+    }
+
+    /// Executor over the state after the past block `block`, rebuilt by the
+    /// state archive; `None` for the latest state or without an archive
+    async fn archived_executor(&self, block: &BlockNumber) -> RpcResult<Option<Arc<EVMExecutor>>> {
+        let (Some(archive), BlockNumber::Number(height)) = (&self.archive, block) else {
+            return Ok(None);
+        };
+        let head = self.blockchain.latest_block.read().await.header.height.max(0) as u64;
+        if *height >= head {
+            return Ok(None);
+        }
+
+        archive.executor_at(&self.blockchain, *height).await
+            .map(Some)
+            .map_err(|e| ErrorObject::owned(-32000, e.to_string(), None::<()>))
+    }
+
+    /// Build the pending overlay: the latest state with the pooled
+    /// transactions applied on top
+    ///
+    /// Only EVM transactions are applied; transactions that fail to execute
+    /// are skipped. The latest state is left untouched.
+    async fn pending_executor(&self) -> EVMExecutor {
+        let overlay = self.evm_executor.overlay().await;
+
+        let ctx = {
+            let latest = self.blockchain.latest_block.read().await;
+            EVMContext {
+                block_number: latest.header.height as u64 + 1,
+                block_coinbase: latest.header.public_key.to_address(),
+                block_gas_limit: latest.header.gas_limit as u64,
+                ..EVMContext::default()
+            }
+        };
+
+        for tx in self.tx_pool.pending() {
+            if let Err(e) = overlay.execute(&tx, &ctx).await {
+                tracing::debug!("Skipping pending transaction {} in overlay: {:?}", tx.body.hash, e);
+            }
+        }
+
+        overlay
+    }
+
+    /// Gas a call under `ctx` may use: the `requested` amount, at most the
+    /// configured cap and the block gas limit
+    ///
+    /// Returns the gas limit and the cap that applied.
+    fn call_gas(&self, requested: Option<&str>, ctx: &EVMContext) -> (u64, u64) {
+        let cap = self.config.call_gas_cap.min(ctx.block_gas_limit);
+        let gas_limit = requested
+            .and_then(|g| u64::from_str_radix(g.strip_prefix("0x").unwrap_or(g), 16).ok())
+            .map_or(cap, |g| g.min(cap));
+        (gas_limit, cap)
+    }
+
     /// Convert norn block to RPC block format
-    fn convert_block(&self, block: &norn_common::types::Block) -> Block {
+    ///
+    /// Without `full_transactions` the block lists transaction hashes only.
+    fn convert_block(&self, block: &norn_common::types::Block, full_transactions: bool) -> Block {
         let miner_address = block.header.public_key.to_address();
         Block {
             hash: format!("0x{}", block.header.block_hash),
@@ -418,14 +882,22 @@ impl EthereumRpcImpl {
             number: format!("0x{:x}", block.header.height),
             timestamp: format!("0x{:x}", block.header.timestamp),
             sha3_uncles: format!("0x{}", hex::encode(block.header.merkle_root.0)),
-            miner: format!("0x{}", hex::encode(miner_address.0)),
+            miner: to_checksum_address(&miner_address),
             gas_limit: format!("0x{:x}", block.header.gas_limit),
             gas_used: format!("0x0"), // Not tracked in norn yet
             state_root: format!("0x{}", block.header.state_root),
             transactions_root: format!("0x{}", block.header.merkle_root),
             receipts_root: format!("0x{}", Hash::default()), // Not implemented
+            logs_bloom: format!(
+                "0x{}",
+                hex::encode(Bloom::from_slice(&block.header.logs_bloom).unwrap_or_default().as_bytes())
+            ),
             extra_data: String::new(),
-            transactions: block.transactions.clone(),
+            transactions: if full_transactions {
+                BlockTransactions::Full(block.transactions.clone())
+            } else {
+                BlockTransactions::Hashes(block.transactions.iter().map(|tx| tx.body.hash).collect())
+            },
         }
     }
 }
@@ -436,6 +908,13 @@ impl EthereumRpcServer for EthereumRpcImpl {
         Ok("norn-rust/v0.1.0".to_string())
     }
 
+    async fn sha3(&self, data: String) -> RpcResult<String> {
+        let bytes = hex::decode(data.strip_prefix("0x").unwrap_or(&data)).map_err(|_| {
+            ErrorObject::owned(ErrorCode::InvalidParams.code(), "data is not valid hex", None::<()>)
+        })?;
+        Ok(format!("0x{}", hex::encode(keccak_hash::keccak(&bytes).0)))
+    }
+
     async fn accounts(&self) -> RpcResult<Vec<Address>> {
         // MetaMask doesn't require the node to manage accounts
         // Return empty array - accounts are managed by MetaMask itself
@@ -443,12 +922,18 @@ impl EthereumRpcServer for EthereumRpcImpl {
     }
 
     async fn get_balance(&self, address: Address, block: BlockNumber) -> RpcResult<String> {
+        self.ensure_synced()?;
+        let state = self.state_at(&block).await;
+        let pending = matches!(block, BlockNumber::Pending);
         let _block_num = self.resolve_block_number(block).await
             .ok_or_else(|| ErrorObject::from(ErrorCode::InvalidParams))?;
 
-        // Get balance from state manager
-        let balance = self.state_manager.get_balance(&address).await
-            .map_err(|_| ErrorObject::from(ErrorCode::InternalError))?;
+        // Committed balances come from the read cache, pending ones from the overlay
+        let balance = match &self.state_cache {
+            Some(cache) if !pending => cache.get_balance(&address).await,
+            _ => state.get_balance(&address).await,
+        }
+        .map_err(|_| ErrorObject::from(ErrorCode::InternalError))?;
 
         // Convert BigUint to hex string (in wei)
         Ok(format!("0x{:x}", balance))
@@ -459,30 +944,41 @@ impl EthereumRpcServer for EthereumRpcImpl {
         Ok(format!("0x{:x}", latest.header.height))
     }
 
-    async fn get_block_by_hash(&self, hash: Hash, _full_transactions: bool) -> RpcResult<Option<Block>> {
+    async fn get_block_by_hash(&self, hash: Hash, full_transactions: bool) -> RpcResult<Option<Block>> {
         let block = self.blockchain.get_block_by_hash(&hash).await;
-        Ok(block.map(|b| self.convert_block(&b)))
+        if let Some(b) = &block {
+            if full_transactions && self.blockchain.is_block_pruned(b.header.height) {
+                return Err(pruned_error("block body"));
+            }
+        }
+        Ok(block.map(|b| self.convert_block(&b, full_transactions)))
     }
 
-    async fn get_block_by_number(&self, block: BlockNumber, _full_transactions: bool) -> RpcResult<Option<Block>> {
+    async fn get_block_by_number(&self, block: BlockNumber, full_transactions: bool) -> RpcResult<Option<Block>> {
         let block_num = self.resolve_block_number(block).await
             .ok_or_else(|| ErrorObject::from(ErrorCode::InvalidParams))?;
 
-        // For now, only latest is supported
-        if block_num == 0 {
-            let genesis = norn_common::genesis::get_genesis_block();
-            return Ok(Some(self.convert_block(&genesis)));
+        {
+            let latest = self.blockchain.latest_block.read().await;
+            if latest.header.height == block_num {
+                return Ok(Some(self.convert_block(&latest, full_transactions)));
+            }
         }
 
-        let latest = self.blockchain.latest_block.read().await;
-        if latest.header.height == block_num {
-            Ok(Some(self.convert_block(&latest)))
-        } else {
-            Ok(None)
+        let block = match self.blockchain.get_block_by_height(block_num).await {
+            Some(block) => block,
+            // A fresh chain may not have persisted genesis yet
+            None if block_num == 0 => norn_common::genesis::get_genesis_block(),
+            None => return Ok(None),
+        };
+        if full_transactions && self.blockchain.is_block_pruned(block_num) {
+            return Err(pruned_error("block body"));
         }
+        Ok(Some(self.convert_block(&block, full_transactions)))
     }
 
     async fn get_code(&self, address: Address, _block: BlockNumber) -> RpcResult<String> {
+        self.ensure_synced()?;
         // Get account to check code hash
         let account = self.state_manager.get_account(&address).await
             .map_err(|_| ErrorObject::from(ErrorCode::InternalError))?;
@@ -501,19 +997,8 @@ impl EthereumRpcServer for EthereumRpcImpl {
     }
 
     async fn get_storage_at(&self, address: Address, position: String, _block: BlockNumber) -> RpcResult<String> {
-        // Parse position as hex string and convert to bytes
-        let pos = if position.starts_with("0x") {
-            &position[2..]
-        } else {
-            &position
-        };
-
-        // Convert hex string to key bytes (32 bytes for storage slot)
-        let mut key = [0u8; 32];
-        if let Ok(pos_bytes) = hex::decode(pos) {
-            let len = pos_bytes.len().min(32);
-            key[..len].copy_from_slice(&pos_bytes);
-        }
+        self.ensure_synced()?;
+        let key = storage_key(&parse_word(&position)?);
 
         // Get storage value
         let value = self.state_manager.get_storage(&address, &key).await
@@ -523,8 +1008,12 @@ impl EthereumRpcServer for EthereumRpcImpl {
     }
 
     async fn get_transaction_count(&self, address: Address, _block: BlockNumber) -> RpcResult<String> {
-        let nonce = self.state_manager.get_nonce(&address).await
-            .map_err(|_| ErrorObject::from(ErrorCode::InternalError))?;
+        self.ensure_synced()?;
+        let nonce = match &self.state_cache {
+            Some(cache) => cache.get_nonce(&address).await,
+            None => self.state_manager.get_nonce(&address).await,
+        }
+        .map_err(|_| ErrorObject::from(ErrorCode::InternalError))?;
 
         Ok(format!("0x{:x}", nonce))
     }
@@ -535,16 +1024,26 @@ impl EthereumRpcServer for EthereumRpcImpl {
         Ok("0x3b9aca00".to_string()) // 1 Gwei in hex
     }
 
-    async fn estimate_gas(&self, request: CallRequest) -> RpcResult<String> {
+    async fn estimate_gas(&self, request: CallRequest, block_overrides: Option<BlockOverride>) -> RpcResult<String> {
+        self.ensure_synced()?;
         // Create EVM context
-        let latest = self.blockchain.latest_block.read().await;
-        let ctx = EVMContext {
-            block_number: latest.header.height as u64,
-            block_timestamp: latest.header.timestamp as u64,
-            block_coinbase: latest.header.public_key.to_address(),
-            block_gas_limit: latest.header.gas_limit as u64,
-            tx_gas_price: 1_000_000_000, // 1 Gwei
+        let mut ctx = {
+            let latest = self.blockchain.latest_block.read().await;
+            EVMContext {
+                block_number: latest.header.height as u64,
+                block_timestamp: latest.header.timestamp as u64,
+                block_coinbase: latest.header.public_key.to_address(),
+                block_gas_limit: latest.header.gas_limit as u64,
+                tx_gas_price: 1_000_000_000, // 1 Gwei
+                tx_priority_fee: None,
+                block_base_fee: 0,
+                cancel: None,
+            }
         };
+        if let Some(overrides) = block_overrides {
+            overrides.apply(&mut ctx)?;
+        }
+        let (gas_limit, gas_cap) = self.call_gas(request.gas.as_deref(), &ctx);
 
         // Parse call data
         let data = request.data.and_then(|d| if d.starts_with("0x") {
@@ -554,34 +1053,45 @@ impl EthereumRpcServer for EthereumRpcImpl {
         }).unwrap_or_default();
 
         let from = request.from.unwrap_or(Address::default());
-        let value = request.value.and_then(|v| v.parse::<u128>().ok()).unwrap_or(0);
-
-        // Check if this is a contract creation (to is None)
-        if request.to.is_none() && !data.is_empty() {
-            // Contract creation - estimate gas for deployment
-            // For now, return a reasonable estimate for contract creation
-            // TODO: Actually execute the create to get accurate gas estimation
-            Ok("0x186a0".to_string()) // 100,000 in hex
-        } else {
-            // Contract call
-            let to = request.to.unwrap_or(Address::default());
-            let _result = self.evm_executor.call_contract(
-                from,
-                to,
-                value,
+        let nonce = self.evm_executor.state_manager().get_nonce(&from).await.map_err(|e| {
+            tracing::error!("Failed to read nonce in estimate_gas: {:?}", e);
+            ErrorObject::from(ErrorCode::InternalError)
+        })?;
+        // A missing `to` is a contract creation
+        let tx = Transaction {
+            body: norn_common::types::TransactionBody {
+                address: from,
+                receiver: request.to.unwrap_or(Address::default()),
+                gas: gas_limit as i64,
+                nonce: nonce as i64,
                 data,
-                1_000_000,
-            ).await.map_err(|e| {
-                tracing::error!("call_contract failed in estimate_gas: {:?}", e);
-                ErrorObject::from(ErrorCode::InternalError)
-            })?;
+                value: request.value,
+                tx_type: norn_common::types::TransactionType::EVM,
+                ..Default::default()
+            },
+        };
 
-            // Return estimated gas (simplified - should be actual gas used)
-            Ok("0x5208".to_string()) // 21000 in hex
+        match self.evm_executor.estimate_gas(&tx, &ctx).await {
+            Ok(gas) => Ok(format!("0x{:x}", gas)),
+            // A transaction that doesn't fit could never be included either
+            Err(EVMError::OutOfGas) => Err(ErrorObject::owned(
+                -32000,
+                format!("gas required exceeds allowance ({}, cap {})", gas_limit, gas_cap),
+                None::<()>,
+            )),
+            Err(EVMError::Revert(data)) => Err(ErrorObject::owned(3, "execution reverted", Some(data))),
+            Err(e) => Err(ErrorObject::owned(-32000, e.to_string(), None::<()>)),
         }
     }
 
-    async fn call(&self, request: CallRequest, _block: BlockNumber) -> RpcResult<String> {
+    async fn call(
+        &self,
+        request: CallRequest,
+        block: BlockNumber,
+        state_overrides: Option<StateOverride>,
+        block_overrides: Option<BlockOverride>,
+    ) -> RpcResult<String> {
+        self.ensure_synced()?;
         // Parse call data
         let data = request.data.and_then(|d| if d.starts_with("0x") {
             hex::decode(&d[2..]).ok()
@@ -598,36 +1108,101 @@ impl EthereumRpcServer for EthereumRpcImpl {
             return Err(ErrorObject::from(ErrorCode::InvalidRequest));
         }
 
-        let result = self.evm_executor.call_contract(
-            from,
-            request.to.unwrap_or(Address::default()),
-            value,
-            data,
-            5_000_000,
-        ).await.map_err(|e| {
+        let mut executor = match self.archived_executor(&block).await? {
+            Some(executor) => executor,
+            None => self.executor_at(&block).await,
+        };
+        if let Some(overrides) = state_overrides {
+            let overrides = overrides.iter()
+                .map(|(address, account)| Ok((*address, account.to_evm_override()?)))
+                .collect::<Result<std::collections::HashMap<_, _>, ErrorObject<'static>>>()?;
+            executor = Arc::new(executor.overlay_with_overrides(&overrides).await.map_err(|e| {
+                tracing::error!("Failed to apply state overrides: {:?}", e);
+                ErrorObject::from(ErrorCode::InternalError)
+            })?);
+        }
+
+        let mut ctx = EVMContext {
+            block_gas_limit: self.blockchain.latest_block.read().await.header.gas_limit as u64,
+            ..EVMContext::default()
+        };
+        if let Some(overrides) = block_overrides {
+            overrides.apply(&mut ctx)?;
+        }
+
+        // Honour the requested gas, but never beyond the caps
+        let (gas_limit, gas_cap) = self.call_gas(request.gas.as_deref(), &ctx);
+
+        // Run on its own task so the timeout doesn't depend on the call
+        // yielding back to this one. On timeout the cancel flag stops the
+        // interpreter, which never yields while it runs.
+        let to = request.to.unwrap_or(Address::default());
+        let cancel = Arc::new(AtomicBool::new(false));
+        ctx.cancel = Some(cancel.clone());
+        let mut call = tokio::spawn(async move {
+            executor.call_contract_with_context(from, to, value, data, gas_limit, &ctx).await
+        });
+        let timeout = self.config.call_timeout;
+        let result = match tokio::time::timeout(timeout, &mut call).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => {
+                tracing::error!("call_contract task failed: {:?}", e);
+                return Err(ErrorObject::from(ErrorCode::InternalError));
+            }
+            Err(_) => {
+                cancel.store(true, Ordering::Relaxed);
+                call.abort();
+                return Err(ErrorObject::owned(
+                    -32000,
+                    format!("execution aborted (timeout = {:?})", timeout),
+                    None::<()>,
+                ));
+            }
+        }.map_err(|e| {
             tracing::error!("call_contract failed: {:?}", e);
             ErrorObject::from(ErrorCode::InternalError)
         })?;
 
+        if !result.success && result.error.as_deref().is_some_and(|e| e.contains("OutOfGas")) {
+            return Err(ErrorObject::owned(
+                -32000,
+                format!("out of gas: call exceeded gas limit {} (cap {})", gas_limit, gas_cap),
+                None::<()>,
+            ));
+        }
+
         Ok(format!("0x{}", hex::encode(&result.output)))
     }
 
     async fn get_transaction_by_hash(&self, hash: Hash) -> RpcResult<Option<Transaction>> {
         let tx = self.blockchain.get_transaction_by_hash(&hash).await;
+        if tx.is_none() && self.blockchain.is_transaction_pruned(&hash).await {
+            return Err(pruned_error("transaction"));
+        }
         Ok(tx)
     }
 
     async fn get_transaction_receipt(&self, hash: Hash) -> RpcResult<Option<TransactionReceipt>> {
+        if self.blockchain.is_transaction_pruned(&hash).await {
+            return Err(pruned_error("receipt"));
+        }
+
         // Try to get receipt from EVM executor's receipt database
         let receipt = self.evm_executor.receipt_db().get_receipt(&hash).await;
 
         match receipt {
             Ok(Some(r)) => {
+                // Prefer the canonical location, which a reorg may have moved
+                let (block_hash, tx_index) = self.blockchain
+                    .get_transaction_location(&hash)
+                    .await
+                    .unwrap_or((r.block_hash, r.tx_index));
+
                 // Convert our Receipt to TransactionReceipt
                 let converted = TransactionReceipt {
                     transaction_hash: r.tx_hash,
-                    transaction_index: format!("0x{:x}", r.tx_index),
-                    block_hash: r.block_hash,
+                    transaction_index: format!("0x{:x}", tx_index),
+                    block_hash,
                     block_number: format!("0x{:x}", r.block_number),
                     from: r.from,
                     to: r.to,
@@ -636,9 +1211,9 @@ impl EthereumRpcServer for EthereumRpcImpl {
                     contract_address: r.contract_address,
                     logs: r.logs.iter().map(|l| Log {
                         log_index: format!("0x{:x}", l.log_index),
-                        transaction_index: format!("0x{:x}", l.log_index),
+                        transaction_index: format!("0x{:x}", tx_index),
                         transaction_hash: l.tx_hash,
-                        block_hash: l.block_hash,
+                        block_hash,
                         block_number: format!("0x{:x}", l.block_number),
                         address: l.address,
                         topics: l.topics.clone(),
@@ -646,6 +1221,7 @@ impl EthereumRpcServer for EthereumRpcImpl {
                     }).collect(),
                     logs_bloom: format!("0x{}", hex::encode(&r.logs_bloom.as_bytes())),
                     status: if r.status { "0x1".to_string() } else { "0x0".to_string() },
+                    finalized: self.finalized_height().await.is_some_and(|h| r.block_number <= h),
                 };
                 Ok(Some(converted))
             },
@@ -676,14 +1252,20 @@ impl EthereumRpcServer for EthereumRpcImpl {
             }
         };
 
-        // Convert to norn transaction
+        // Convert to norn transaction, recovering the sender from the signature
         let norn_tx = match eth_tx.to_norn_transaction() {
             Ok(tx) => tx,
-            Err(_) => {
-                tracing::error!("Failed to convert Ethereum transaction to norn transaction");
-                return Err(ErrorObject::from(ErrorCode::InternalError));
+            Err(e) => {
+                tracing::error!("Failed to convert Ethereum transaction to norn transaction: {}", e);
+                return Err(ErrorObject::from(ErrorCode::InvalidParams));
             }
         };
+        // The transaction is identified by its Ethereum hash, which only
+        // matches the submitted bytes for a canonical encoding
+        if norn_tx.body.hash.0 != keccak_hash::keccak(&tx_bytes).0 {
+            tracing::error!("Raw transaction is not canonically encoded");
+            return Err(ErrorObject::from(ErrorCode::InvalidParams));
+        }
 
         // Validate transaction
         // 1. Check nonce
@@ -725,7 +1307,12 @@ impl EthereumRpcServer for EthereumRpcImpl {
             return Err(ErrorObject::from(ErrorCode::InvalidParams));
         }
 
-        // Submit to transaction pool
+        // Submit to transaction pool, keeping the original encoding
+        self.raw_txs.insert(norn_tx.body.hash, tx_bytes).await
+            .map_err(|e| {
+                tracing::error!("Failed to store raw transaction: {:?}", e);
+                ErrorObject::from(ErrorCode::InternalError)
+            })?;
         self.tx_pool.add(norn_tx.clone());
 
         tracing::info!(
@@ -739,6 +1326,12 @@ impl EthereumRpcServer for EthereumRpcImpl {
         Ok(norn_tx.body.hash)
     }
 
+    async fn get_raw_transaction_by_hash(&self, hash: Hash) -> RpcResult<Option<String>> {
+        let raw = self.raw_txs.get(&hash).await
+            .map_err(|_| ErrorObject::from(ErrorCode::InternalError))?;
+        Ok(raw.map(|raw| format!("0x{}", hex::encode(raw))))
+    }
+
     async fn send_transaction(&self, request: TransactionRequest) -> RpcResult<Hash> {
         use norn_common::build_mode;
 
@@ -810,6 +1403,9 @@ impl EthereumRpcServer for EthereumRpcImpl {
         // Update account balance in state manager
         match self.state_manager.update_balance(&address, amount_biguint).await {
             Ok(_) => {
+                if let Some(cache) = &self.state_cache {
+                    cache.invalidate(&address);
+                }
                 tracing::info!("Successfully minted {} ETH to {:?}", amount, address);
                 Ok(true)
             }
@@ -820,6 +1416,37 @@ impl EthereumRpcServer for EthereumRpcImpl {
         }
     }
 
+    async fn dev_set_storage_at(&self, address: Address, slot: String, value: String) -> RpcResult<bool> {
+        self.ensure_dev_methods()?;
+        let key = storage_key(&parse_word(&slot)?);
+        let value = parse_word(&value)?;
+        tracing::info!("dev_setStorageAt: address={:?}, slot={}", address, slot);
+
+        self.state_manager.set_storage(&address, key, value.to_vec()).await
+            .map_err(|e| {
+                tracing::error!("Failed to set storage: {:?}", e);
+                ErrorObject::from(ErrorCode::InternalError)
+            })?;
+        Ok(true)
+    }
+
+    async fn dev_set_code(&self, address: Address, code: String) -> RpcResult<bool> {
+        self.ensure_dev_methods()?;
+        let code = hex::decode(code.strip_prefix("0x").unwrap_or(&code))
+            .map_err(|_| ErrorObject::from(ErrorCode::InvalidParams))?;
+        tracing::info!("dev_setCode: address={:?}, size={} bytes", address, code.len());
+
+        self.evm_executor.set_code(address, code).await
+            .map_err(|e| {
+                tracing::error!("Failed to set code: {:?}", e);
+                ErrorObject::from(ErrorCode::InternalError)
+            })?;
+        if let Some(cache) = &self.state_cache {
+            cache.invalidate(&address);
+        }
+        Ok(true)
+    }
+
     async fn get_uncle_count_by_block_hash(&self, _hash: Hash) -> RpcResult<String> {
         Ok("0x0".to_string())
     }
@@ -849,12 +1476,42 @@ impl EthereumRpcServer for EthereumRpcImpl {
     }
 
     async fn syncing(&self) -> RpcResult<bool> {
-        Ok(false)
+        Ok(!self.readiness.is_ready())
+    }
+
+    async fn protocol_version(&self) -> RpcResult<String> {
+        Ok(format!("0x{:x}", ETH_PROTOCOL_VERSION))
+    }
+
+    async fn peer_count(&self) -> RpcResult<String> {
+        Ok(format!("0x{:x}", self.network.peer_count()))
+    }
+
+    async fn listening(&self) -> RpcResult<bool> {
+        Ok(self.network.is_listening())
+    }
+
+    async fn consensus_status(&self) -> RpcResult<Option<ConsensusStatus>> {
+        match &self.consensus {
+            Some(engine) => Ok(Some(engine.status().await)),
+            None => Ok(None),
+        }
+    }
+
+    async fn recovery_status(&self) -> RpcResult<Option<RecoveryOutcome>> {
+        let Some(db) = &self.recovery_db else {
+            return Ok(None);
+        };
+        RecoveryOutcome::load(db).map_err(|e| {
+            tracing::error!("Failed to read recovery outcome: {}", e);
+            ErrorObject::from(ErrorCode::InternalError)
+        })
     }
 
     async fn get_block_transaction_count_by_hash(&self, hash: Hash) -> RpcResult<String> {
         let block = self.blockchain.get_block_by_hash(&hash).await;
         match block {
+            Some(b) if self.blockchain.is_block_pruned(b.header.height) => Err(pruned_error("block body")),
             Some(b) => Ok(format!("0x{:x}", b.transactions.len())),
             None => Err(ErrorObject::from(ErrorCode::InvalidParams)),
         }
@@ -919,7 +1576,7 @@ impl EthereumRpcServer for EthereumRpcImpl {
 
     async fn get_latest_block(&self) -> RpcResult<Option<Block>> {
         let latest = self.blockchain.latest_block.read().await;
-        Ok(Some(self.convert_block(&latest)))
+        Ok(Some(self.convert_block(&latest, true)))
     }
 
     async fn get_logs(&self, filter: LogFilter) -> RpcResult<Vec<Log>> {
@@ -946,21 +1603,56 @@ impl EthereumRpcServer for EthereumRpcImpl {
             None => Some(current_height as u64),
         };
 
+        if let (Some(from), Some(to)) = (from_block, to_block) {
+            let range = to.saturating_sub(from).saturating_add(1);
+            if to >= from && range > self.config.max_log_range {
+                return Err(ErrorObject::owned(
+                    -32005,
+                    format!(
+                        "block range too large: {} blocks requested, limit is {}; narrow the range",
+                        range, self.config.max_log_range
+                    ),
+                    None::<()>,
+                ));
+            }
+        }
+
         // Convert topics
-        let topics = filter.topics.unwrap_or_default();
-
-        // Query receipts
-        let receipts = receipt_db.filter_receipts(
-            None, // block_hash - not used when we have range
-            from_block,
-            to_block,
-            filter.address.as_ref(),
-            &topics,
-        ).await
-            .map_err(|e| {
-                tracing::error!("Failed to filter receipts: {:?}", e);
-                ErrorObject::from(ErrorCode::InternalError)
-            })?;
+        let topics: Vec<Option<Vec<Hash>>> = filter.topics.unwrap_or_default()
+            .into_iter()
+            .map(|position| position.map(|f| f.topics().to_vec()))
+            .collect();
+
+        // Receipts are only fetched for blocks whose logs bloom may match
+        let filtering = filter.address.is_some() || topics.iter().any(Option::is_some);
+        let mut receipts = Vec::new();
+        if let (Some(from), Some(to)) = (from_block, to_block) {
+            for height in from..=to.min(current_height.max(0) as u64) {
+                let Some(block) = self.blockchain.get_block_by_height(height as i64).await else {
+                    continue;
+                };
+                if filtering {
+                    if let Some(bloom) = Bloom::from_slice(&block.header.logs_bloom) {
+                        if !bloom.might_match(filter.address.as_ref(), &topics) {
+                            continue;
+                        }
+                    }
+                }
+
+                let block_receipts = receipt_db.filter_receipts(
+                    Some(&block.header.block_hash),
+                    None,
+                    None,
+                    filter.address.as_ref(),
+                    &topics,
+                ).await
+                    .map_err(|e| {
+                        tracing::error!("Failed to filter receipts: {:?}", e);
+                        ErrorObject::from(ErrorCode::InternalError)
+                    })?;
+                receipts.extend(block_receipts);
+            }
+        }
 
         // Convert receipts to logs
         let mut logs = Vec::new();
@@ -974,26 +1666,14 @@ impl EthereumRpcServer for EthereumRpcImpl {
                 }
 
                 // Filter by topics if specified
-                let mut topics_match = true;
-                for (i, topic_opt) in topics.iter().enumerate() {
-                    if let Some(topic) = topic_opt {
-                        let log_has_topic = receipt_log.topics.get(i)
-                            .map_or(false, |t| *t == *topic);
-                        if !log_has_topic {
-                            topics_match = false;
-                            break;
-                        }
-                    }
-                }
-
-                if !topics_match {
+                if !receipt_log.matches_topics(&topics) {
                     continue;
                 }
 
                 // Convert receipt log to RPC Log format
                 let log = Log {
                     log_index: format!("0x{:x}", receipt_log.log_index),
-                    transaction_index: format!("0x{:x}", receipt_log.log_index), // Use log_index as tx_index
+                    transaction_index: format!("0x{:x}", receipt_log.tx_index),
                     transaction_hash: receipt_log.tx_hash,
                     block_hash: receipt_log.block_hash,
                     block_number: format!("0x{:x}", receipt_log.block_number),
@@ -1001,6 +1681,16 @@ impl EthereumRpcServer for EthereumRpcImpl {
                     topics: receipt_log.topics,
                     data: format!("0x{}", hex::encode(&receipt_log.data)),
                 };
+                if logs.len() >= self.config.max_log_results {
+                    return Err(ErrorObject::owned(
+                        -32005,
+                        format!(
+                            "query returned more than {} results; narrow the block range",
+                            self.config.max_log_results
+                        ),
+                        None::<()>,
+                    ));
+                }
                 logs.push(log);
             }
         }
@@ -1042,6 +1732,14 @@ pub async fn start_ethereum_rpc_server(
         }
     })?;
 
+    module.register_async_method("web3_sha3", move |params, ethereum_rpc| {
+        let ethereum_rpc = ethereum_rpc.clone();
+        async move {
+            let (data,): (String,) = params.parse()?;
+            ethereum_rpc.sha3(data).await
+        }
+    })?;
+
     module.register_async_method("eth_accounts", move |_params, ethereum_rpc| {
         let ethereum_rpc = ethereum_rpc.clone();
         async move {
@@ -1083,8 +1781,12 @@ pub async fn start_ethereum_rpc_server(
     module.register_async_method("eth_call", move |params, ethereum_rpc| {
         let ethereum_rpc = ethereum_rpc.clone();
         async move {
-            let (call, block): (CallRequest, BlockNumber) = params.parse()?;
-            ethereum_rpc.call(call, block).await
+            let mut params = params.sequence();
+            let call: CallRequest = params.next()?;
+            let block: BlockNumber = params.next()?;
+            let state_overrides: Option<StateOverride> = params.optional_next()?;
+            let block_overrides: Option<BlockOverride> = params.optional_next()?;
+            ethereum_rpc.call(call, block, state_overrides, block_overrides).await
         }
     })?;
 
@@ -1096,6 +1798,14 @@ pub async fn start_ethereum_rpc_server(
         }
     })?;
 
+    module.register_async_method("eth_getRawTransactionByHash", move |params, ethereum_rpc| {
+        let ethereum_rpc = ethereum_rpc.clone();
+        async move {
+            let (hash,): (Hash,) = params.parse()?;
+            ethereum_rpc.get_raw_transaction_by_hash(hash).await
+        }
+    })?;
+
     module.register_async_method("eth_getTransactionCount", move |params, ethereum_rpc| {
         let ethereum_rpc = ethereum_rpc.clone();
         async move {
@@ -1107,8 +1817,10 @@ pub async fn start_ethereum_rpc_server(
     module.register_async_method("eth_estimateGas", move |params, ethereum_rpc| {
         let ethereum_rpc = ethereum_rpc.clone();
         async move {
-            let call: CallRequest = params.parse()?;
-            ethereum_rpc.estimate_gas(call).await
+            let mut params = params.sequence();
+            let call: CallRequest = params.next()?;
+            let block_overrides: Option<BlockOverride> = params.optional_next()?;
+            ethereum_rpc.estimate_gas(call, block_overrides).await
         }
     })?;
 
@@ -1120,6 +1832,30 @@ pub async fn start_ethereum_rpc_server(
         }
     })?;
 
+    module.register_async_method("eth_getStorageAt", move |params, ethereum_rpc| {
+        let ethereum_rpc = ethereum_rpc.clone();
+        async move {
+            let (addr, position, block): (Address, String, BlockNumber) = params.parse()?;
+            ethereum_rpc.get_storage_at(addr, position, block).await
+        }
+    })?;
+
+    module.register_async_method("dev_setStorageAt", move |params, ethereum_rpc| {
+        let ethereum_rpc = ethereum_rpc.clone();
+        async move {
+            let (addr, slot, value): (Address, String, String) = params.parse()?;
+            ethereum_rpc.dev_set_storage_at(addr, slot, value).await
+        }
+    })?;
+
+    module.register_async_method("dev_setCode", move |params, ethereum_rpc| {
+        let ethereum_rpc = ethereum_rpc.clone();
+        async move {
+            let (addr, code): (Address, String) = params.parse()?;
+            ethereum_rpc.dev_set_code(addr, code).await
+        }
+    })?;
+
     module.register_async_method("eth_chainId", move |_params, ethereum_rpc| {
         let ethereum_rpc = ethereum_rpc.clone();
         async move {
@@ -1202,6 +1938,41 @@ pub async fn start_ethereum_rpc_server(
         }
     })?;
 
+    module.register_async_method("eth_protocolVersion", move |_params, ethereum_rpc| {
+        let ethereum_rpc = ethereum_rpc.clone();
+        async move {
+            ethereum_rpc.protocol_version().await
+        }
+    })?;
+
+    module.register_async_method("net_peerCount", move |_params, ethereum_rpc| {
+        let ethereum_rpc = ethereum_rpc.clone();
+        async move {
+            ethereum_rpc.peer_count().await
+        }
+    })?;
+
+    module.register_async_method("net_listening", move |_params, ethereum_rpc| {
+        let ethereum_rpc = ethereum_rpc.clone();
+        async move {
+            ethereum_rpc.listening().await
+        }
+    })?;
+
+    module.register_async_method("norn_consensusStatus", move |_params, ethereum_rpc| {
+        let ethereum_rpc = ethereum_rpc.clone();
+        async move {
+            ethereum_rpc.consensus_status().await
+        }
+    })?;
+
+    module.register_async_method("norn_recoveryStatus", move |_params, ethereum_rpc| {
+        let ethereum_rpc = ethereum_rpc.clone();
+        async move {
+            ethereum_rpc.recovery_status().await
+        }
+    })?;
+
     module.register_async_method("eth_getBlockTransactionCountByHash", move |params, ethereum_rpc| {
         let ethereum_rpc = ethereum_rpc.clone();
         async move {
@@ -1235,6 +2006,11 @@ pub async fn start_ethereum_rpc_server(
     Ok(())
 }
 
+/// Error returned for data removed by block body pruning
+fn pruned_error(what: &str) -> ErrorObject<'static> {
+    ErrorObject::owned(-32000, format!("{} pruned: node only keeps recent blocks", what), None::<()>)
+}
+
 // Helper extension to convert public key to address
 pub trait ToAddress {
     fn to_address(&self) -> Address;
@@ -1257,8 +2033,34 @@ impl ToAddress for norn_common::types::PublicKey {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use norn_core::evm::EVMConfig;
     use norn_storage::SledDB;
 
+    /// Node behind an RPC built by [`test_rpc`]
+    struct RpcNode {
+        db: Arc<SledDB>,
+        blockchain: Arc<Blockchain>,
+        state_manager: Arc<AccountStateManager>,
+        evm_executor: Arc<EVMExecutor>,
+        tx_pool: Arc<TxPool>,
+        _dir: tempfile::TempDir,
+    }
+
+    /// RPC for chain 31337 over a fresh chain with an empty state
+    async fn test_rpc() -> (EthereumRpcImpl, RpcNode) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(SledDB::new(dir.path().to_str().unwrap()).unwrap());
+        let blockchain = Blockchain::new_with_fixed_genesis(db.clone()).await;
+        let state_manager = Arc::new(AccountStateManager::default());
+        let evm_executor = Arc::new(EVMExecutor::new(state_manager.clone(), EVMConfig::default()));
+        let tx_pool = Arc::new(TxPool::new());
+
+        let rpc = EthereumRpcImpl::new(
+            blockchain.clone(), state_manager.clone(), evm_executor.clone(), tx_pool.clone(), 31337,
+        );
+        (rpc, RpcNode { db, blockchain, state_manager, evm_executor, tx_pool, _dir: dir })
+    }
+
     #[tokio::test]
     async fn test_block_number_parsing() {
         // Test BlockNumber deserialization
@@ -1274,14 +2076,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_balance() {
-        let temp_dir = tempfile::tempdir().unwrap();
-        let db = Arc::new(SledDB::new(temp_dir.path().to_str().unwrap()).unwrap());
-        let blockchain = norn_core::blockchain::Blockchain::new_with_fixed_genesis(db).await;
-        let state_manager = Arc::new(AccountStateManager::default());
-        let evm_executor = Arc::new(EVMExecutor::new(state_manager.clone(), EVMConfig::default()));
-        let tx_pool = Arc::new(norn_core::TxPool::new());
-
-        let rpc = EthereumRpcImpl::new(blockchain, state_manager, evm_executor, tx_pool, 31337);
+        let (rpc, _node) = test_rpc().await;
 
         let address = Address([1u8; 20]);
         let balance = rpc.get_balance(address, BlockNumber::Latest).await.unwrap();
@@ -1291,16 +2086,834 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_chain_id() {
+    async fn test_get_balance_of_funded_account() {
+        use norn_core::test_support::TestNode;
+
+        let node = TestNode::builder()
+            .with_account(Address([1u8; 20]), 1000u32)
+            .build()
+            .await;
+        let rpc = EthereumRpcImpl::new(node.blockchain, node.state_manager, node.evm_executor, node.tx_pool, 31337);
+
+        assert_eq!(rpc.get_balance(Address([1u8; 20]), BlockNumber::Latest).await.unwrap(), "0x3e8");
+    }
+
+    #[tokio::test]
+    async fn test_get_block_by_number_transaction_detail() {
+        use norn_common::types::TransactionType;
+        use norn_core::test_support::TestNode;
+
+        let sender = Address([1u8; 20]);
+        let node = TestNode::builder()
+            .with_account(sender, 1_000_000_000_000_000_000u128)
+            .build()
+            .await;
+        let mut tx = Transaction::default();
+        tx.body.hash = Hash([7u8; 32]);
+        tx.body.address = sender;
+        tx.body.receiver = Address([2u8; 20]);
+        tx.body.gas = 21_000;
+        tx.body.tx_type = TransactionType::EVM;
+        tx.body.value = Some("1000".to_string());
+        node.tx_pool.add(tx);
+        node.produce_block(true).await;
+        node.produce_block(false).await;
+        let rpc = EthereumRpcImpl::new(node.blockchain, node.state_manager, node.evm_executor, node.tx_pool, 31337);
+
+        let block = rpc.get_block_by_number(BlockNumber::Number(1), false).await.unwrap().unwrap();
+        assert!(matches!(block.transactions, BlockTransactions::Hashes(ref hashes) if hashes == &[Hash([7u8; 32])]));
+        let json = serde_json::to_value(&block).unwrap();
+        assert_eq!(json["transactions"], serde_json::json!([format!("0x{}", "07".repeat(32))]));
+        let decoded: BlockTransactions = serde_json::from_value(json["transactions"].clone()).unwrap();
+        assert!(matches!(decoded, BlockTransactions::Hashes(ref hashes) if hashes == &[Hash([7u8; 32])]));
+
+        let block = rpc.get_block_by_number(BlockNumber::Number(1), true).await.unwrap().unwrap();
+        match block.transactions {
+            BlockTransactions::Full(txs) => {
+                assert_eq!(txs.len(), 1);
+                assert_eq!(txs[0].body.hash, Hash([7u8; 32]));
+                assert_eq!(txs[0].body.receiver, Address([2u8; 20]));
+            }
+            BlockTransactions::Hashes(_) => panic!("expected full transactions"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pending_state_overlay() {
+        use norn_common::types::TransactionType;
+
+        let (rpc, node) = test_rpc().await;
+
+        let sender = Address([1u8; 20]);
+        let receiver = Address([2u8; 20]);
+        node.state_manager.add_balance(&sender, &BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+
+        // Contract returning BALANCE(receiver)
+        let mut code = vec![0x73];
+        code.extend_from_slice(&receiver.0);
+        code.extend_from_slice(&[0x31, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+        let (contract, _) = node.evm_executor.create_contract(sender, 0, code, 0, 100_000).await.unwrap();
+
+        let mut tx = Transaction::default();
+        tx.body.hash = Hash([7u8; 32]);
+        tx.body.address = sender;
+        tx.body.receiver = receiver;
+        tx.body.tx_type = TransactionType::EVM;
+        tx.body.value = Some("1000".to_string());
+        node.tx_pool.add(tx);
+
+        assert_eq!(rpc.get_balance(receiver, BlockNumber::Pending).await.unwrap(), "0x3e8");
+        assert_eq!(rpc.get_balance(receiver, BlockNumber::Latest).await.unwrap(), "0x0");
+
+        let request = CallRequest {
+            to: Some(contract),
+            from: Some(sender),
+            value: None,
+            gas: None,
+            gas_price: None,
+            data: Some("0x".to_string()),
+        };
+        let pending = rpc.call(request.clone(), BlockNumber::Pending, None, None).await.unwrap();
+        assert_eq!(pending, format!("0x{:064x}", 1000));
+        let latest = rpc.call(request, BlockNumber::Latest, None, None).await.unwrap();
+        assert_eq!(latest, format!("0x{:064x}", 0));
+    }
+
+    #[tokio::test]
+    async fn test_call_with_state_overrides() {
+        let (rpc, node) = test_rpc().await;
+
+        let sender = Address([1u8; 20]);
+        node.state_manager.add_balance(&sender, &BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+
+        // Contract returning the constant 1
+        let code = vec![0x60, 0x01, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+        let (contract, _) = node.evm_executor.create_contract(sender, 0, code.clone(), 0, 100_000).await.unwrap();
+
+        let request = CallRequest {
+            to: Some(contract),
+            from: Some(sender),
+            value: None,
+            gas: None,
+            gas_price: None,
+            data: Some("0x".to_string()),
+        };
+
+        // Override the code with one returning SLOAD(0), and slot 0 with 42
+        let overrides: StateOverride = serde_json::from_value(serde_json::json!({
+            format!("0x{}", hex::encode(contract.0)): {
+                "code": "0x60005460005260206000f3",
+                "stateDiff": { "0x00": "0x2a" }
+            }
+        })).unwrap();
+        let overridden = rpc.call(request.clone(), BlockNumber::Latest, Some(overrides), None).await.unwrap();
+        assert_eq!(overridden, format!("0x{:064x}", 42));
+
+        // Real state is untouched
+        let latest = rpc.call(request, BlockNumber::Latest, None, None).await.unwrap();
+        assert_eq!(latest, format!("0x{:064x}", 1));
+        assert_eq!(node.evm_executor.code_storage().get_code_by_address(&contract).await.unwrap(), Some(code));
+        assert_eq!(node.state_manager.get_storage(&contract, &[0x00]).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_call_with_block_overrides() {
+        let (rpc, node) = test_rpc().await;
+
+        let sender = Address([1u8; 20]);
+        node.state_manager.add_balance(&sender, &BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+
+        // Contract returning TIMESTAMP
+        let code = vec![0x42, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+        let (contract, _) = node.evm_executor.create_contract(sender, 0, code, 0, 100_000).await.unwrap();
+
+        let request = CallRequest {
+            to: Some(contract),
+            from: Some(sender),
+            value: None,
+            gas: None,
+            gas_price: None,
+            data: None,
+        };
+        let overrides: BlockOverride = serde_json::from_value(serde_json::json!({
+            "time": "0x7fffffff",
+            "number": "0x64",
+            "baseFee": "0x77359400"
+        })).unwrap();
+        let overridden = rpc.call(request.clone(), BlockNumber::Latest, None, Some(overrides)).await.unwrap();
+        assert_eq!(overridden, format!("0x{:064x}", 0x7fffffffu64));
+
+        // The override applies to that call only
+        let latest = rpc.call(request.clone(), BlockNumber::Latest, None, None).await.unwrap();
+        assert_ne!(latest, overridden);
+
+        let malformed = BlockOverride { time: Some("0xzz".to_string()), ..BlockOverride::default() };
+        let err = rpc.call(request, BlockNumber::Latest, None, Some(malformed)).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidParams.code());
+    }
+
+    #[tokio::test]
+    async fn test_call_gas_cap() {
+        let (rpc, node) = test_rpc().await;
+
+        let sender = Address([1u8; 20]);
+        node.state_manager.add_balance(&sender, &BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+
+        // An endless loop, and a contract returning the constant 1
+        let (looping, _) = node.evm_executor.create_contract(sender, 0, vec![0x5b, 0x60, 0x00, 0x56], 0, 100_000).await.unwrap();
+        let (constant, _) = node.evm_executor.create_contract(
+            sender, 1, vec![0x60, 0x01, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3], 0, 100_000
+        ).await.unwrap();
+
+        let config = RpcConfig { call_gas_cap: 30_000, ..RpcConfig::default() };
+        let rpc = rpc.with_config(config);
+
+        let request = |to| CallRequest {
+            to: Some(to),
+            from: Some(sender),
+            value: None,
+            // Asking for more than the cap doesn't lift it
+            gas: Some("0x2faf080".to_string()),
+            gas_price: None,
+            data: Some("0x".to_string()),
+        };
+
+        let err = rpc.call(request(looping), BlockNumber::Latest, None, None).await.unwrap_err();
+        assert_eq!(err.code(), -32000);
+        assert!(err.message().contains("out of gas"), "{}", err.message());
+
+        let ok = rpc.call(request(constant), BlockNumber::Latest, None, None).await.unwrap();
+        assert_eq!(ok, format!("0x{:064x}", 1));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_timed_out_call_stops_running() {
+        let (rpc, node) = test_rpc().await;
+
+        let sender = Address([1u8; 20]);
+        node.state_manager.add_balance(&sender, &BigUint::from(10u128.pow(20))).await.unwrap();
+        let (looping, _) = node.evm_executor.create_contract(sender, 0, vec![0x5b, 0x60, 0x00, 0x56], 0, 100_000).await.unwrap();
+
+        // Enough gas for the loop to spin for minutes
+        let config = RpcConfig {
+            call_gas_cap: 10_000_000_000,
+            call_timeout: std::time::Duration::from_millis(200),
+            ..RpcConfig::default()
+        };
+        let rpc = rpc.with_config(config);
+        let unbounded = BlockOverride { gas_limit: Some("0x2540be400".to_string()), ..BlockOverride::default() };
+
+        let request = CallRequest {
+            to: Some(looping),
+            from: Some(sender),
+            value: None,
+            gas: None,
+            gas_price: None,
+            data: Some("0x".to_string()),
+        };
+        let idle = Arc::strong_count(&node.evm_executor);
+        let err = rpc.call(request, BlockNumber::Latest, None, Some(unbounded)).await.unwrap_err();
+        assert_eq!(err.code(), -32000);
+        assert!(err.message().contains("execution aborted"), "{}", err.message());
+
+        // The call's task holds the executor until the interpreter stops
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while Arc::strong_count(&node.evm_executor) > idle {
+            assert!(std::time::Instant::now() < deadline, "timed-out call is still running");
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_at_past_block_replays_from_archive() {
+        use norn_common::types::TransactionType;
+        use norn_core::test_support::TestNode;
+
+        let sender = Address([1u8; 20]);
+        let receiver = Address([2u8; 20]);
+        let node = TestNode::builder()
+            .with_account(sender, 1_000_000_000_000_000_000u128)
+            .build()
+            .await;
+
+        // Returns the balance of `receiver`
+        let mut code = vec![0x73];
+        code.extend_from_slice(&receiver.0);
+        code.extend_from_slice(&[0x31, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+        let (contract, _) = node.evm_executor.create_contract(sender, 0, code, 0, 100_000).await.unwrap();
+
+        let router = Arc::new(norn_core::execution::TransactionRouter::new(Some(node.evm_executor.clone()), 30_000_000));
+        let archive = Arc::new(StateArchive::new(router, 4));
+        archive.record(0).await.unwrap();
+        for (id, value) in [(1u8, 1000u32), (2, 2000), (3, 3000)] {
+            let mut tx = Transaction::default();
+            tx.body.hash = Hash([id; 32]);
+            tx.body.address = sender;
+            tx.body.receiver = receiver;
+            tx.body.gas = 21_000;
+            tx.body.tx_type = TransactionType::EVM;
+            tx.body.value = Some(value.to_string());
+            node.tx_pool.add(tx);
+            node.produce_block(true).await;
+        }
+
+        let rpc = EthereumRpcImpl::new(node.blockchain, node.state_manager, node.evm_executor, node.tx_pool, 31337)
+            .with_state_archive(archive);
+        let request = CallRequest {
+            to: Some(contract),
+            from: Some(sender),
+            value: None,
+            gas: None,
+            gas_price: None,
+            data: Some("0x".to_string()),
+        };
+
+        let at_2 = rpc.call(request.clone(), BlockNumber::Number(2), None, None).await.unwrap();
+        assert_eq!(at_2, format!("0x{:064x}", 3000));
+        let latest = rpc.call(request, BlockNumber::Latest, None, None).await.unwrap();
+        assert_eq!(latest, format!("0x{:064x}", 6000));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_gas_capped_at_block_gas_limit() {
+        let (rpc, node) = test_rpc().await;
+
+        let sender = Address([1u8; 20]);
+        node.state_manager.add_balance(&sender, &BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+
+        // An endless loop
+        let (looping, _) = node.evm_executor.create_contract(sender, 0, vec![0x5b, 0x60, 0x00, 0x56], 0, 100_000).await.unwrap();
+
+        let request = CallRequest {
+            to: Some(looping),
+            from: Some(sender),
+            value: None,
+            gas: Some("0x2faf080".to_string()),
+            gas_price: None,
+            data: Some("0x".to_string()),
+        };
+        // A block gas limit below the configured cap
+        let small_block = BlockOverride { gas_limit: Some("0x7530".to_string()), ..BlockOverride::default() };
+
+        let err = rpc.estimate_gas(request.clone(), Some(small_block.clone())).await.unwrap_err();
+        assert_eq!(err.code(), -32000);
+        assert!(err.message().contains("cap 30000"), "{}", err.message());
+
+        let err = rpc.call(request, BlockNumber::Latest, None, Some(small_block)).await.unwrap_err();
+        assert!(err.message().contains("cap 30000"), "{}", err.message());
+    }
+
+    #[tokio::test]
+    async fn test_get_code_returns_runtime_code() {
+        use norn_common::types::TransactionType;
+
+        let (rpc, node) = test_rpc().await;
+
+        let sender = Address([1u8; 20]);
+        node.state_manager.add_balance(&sender, &BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+
+        // Runtime: return 42
+        let runtime = vec![0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+        // Constructor: SSTORE(0, 42), then return the runtime code appended below
+        let mut init_code = vec![
+            0x60, 0x2a, 0x60, 0x00, 0x55,
+            0x60, runtime.len() as u8, 0x80, 0x60, 0x10, 0x60, 0x00, 0x39, 0x60, 0x00, 0xf3,
+        ];
+        init_code.extend_from_slice(&runtime);
+
+        let mut tx = Transaction::default();
+        tx.body.address = sender;
+        tx.body.tx_type = TransactionType::EVM;
+        tx.body.gas = 200_000;
+        tx.body.data = init_code.clone();
+        let result = node.evm_executor.execute(&tx, &EVMContext::default()).await.unwrap();
+        assert!(result.success, "{:?}", result);
+        let contract = norn_core::evm::CodeStorage::calculate_create_address(sender, 0);
+
+        let code = rpc.get_code(contract, BlockNumber::Latest).await.unwrap();
+        assert_eq!(code, format!("0x{}", hex::encode(&runtime)));
+        assert_ne!(code, format!("0x{}", hex::encode(&init_code)));
+
+        let request = CallRequest {
+            to: Some(contract),
+            from: Some(sender),
+            value: None,
+            gas: None,
+            gas_price: None,
+            data: None,
+        };
+        let output = rpc.call(request, BlockNumber::Latest, None, None).await.unwrap();
+        assert_eq!(output, format!("0x{:064x}", 42));
+    }
+
+    #[tokio::test]
+    async fn test_pruned_data_errors() {
+        use norn_core::pruning::PruningMode;
+
         let temp_dir = tempfile::tempdir().unwrap();
         let db = Arc::new(SledDB::new(temp_dir.path().to_str().unwrap()).unwrap());
-        let blockchain = norn_core::blockchain::Blockchain::new_with_fixed_genesis(db).await;
+        let blockchain = norn_core::blockchain::Blockchain::new_with_pruning(
+            db,
+            norn_common::genesis::get_genesis_block(),
+            PruningMode::Full { keep_recent: 2 },
+        ).await;
+
+        let mut blocks = Vec::new();
+        let mut parent = blockchain.latest_block.read().await.header.block_hash;
+        for height in 1..=4u8 {
+            let mut block = norn_common::types::Block::default();
+            block.header.height = height as i64;
+            block.header.block_hash = Hash([height; 32]);
+            block.header.prev_block_hash = parent;
+            parent = block.header.block_hash;
+            let mut tx = Transaction::default();
+            tx.body.hash = Hash([0x10 + height; 32]);
+            block.transactions.push(tx);
+            blockchain.commit_block(&block).await.unwrap();
+            blocks.push(block);
+        }
+
         let state_manager = Arc::new(AccountStateManager::default());
         let evm_executor = Arc::new(EVMExecutor::new(state_manager.clone(), EVMConfig::default()));
         let tx_pool = Arc::new(norn_core::TxPool::new());
-
         let rpc = EthereumRpcImpl::new(blockchain, state_manager, evm_executor, tx_pool, 31337);
 
+        // Block 1 is pruned, block 4 is retained
+        let err = rpc.get_transaction_by_hash(Hash([0x11; 32])).await.unwrap_err();
+        assert!(err.message().contains("pruned"));
+        let err = rpc.get_transaction_receipt(Hash([0x11; 32])).await.unwrap_err();
+        assert!(err.message().contains("pruned"));
+        let err = rpc.get_block_by_hash(Hash([1; 32]), true).await.unwrap_err();
+        assert!(err.message().contains("pruned"));
+
+        // The header is still served
+        let header = rpc.get_block_by_hash(Hash([1; 32]), false).await.unwrap().unwrap();
+        assert_eq!(header.number, "0x1");
+
+        assert!(rpc.get_transaction_by_hash(Hash([0x14; 32])).await.unwrap().is_some());
+        // Unknown transactions are not reported as pruned
+        assert!(rpc.get_transaction_by_hash(Hash([0xff; 32])).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_receipt_reports_transaction_index() {
+        use norn_core::evm::{Receipt, ReceiptLog};
+
+        let (rpc, node) = test_rpc().await;
+
+        let mut block = norn_common::types::Block::default();
+        block.header.height = 1;
+        block.header.block_hash = Hash([1; 32]);
+        block.header.prev_block_hash = node.blockchain.latest_block.read().await.header.block_hash;
+        for i in 0..2u8 {
+            let mut tx = Transaction::default();
+            tx.body.hash = Hash([0x10 + i; 32]);
+            block.transactions.push(tx);
+        }
+        node.blockchain.commit_block(&block).await.unwrap();
+
+        // The second transaction emits two logs
+        let tx_hash = Hash([0x11; 32]);
+        let log = ReceiptLog {
+            log_index: 0,
+            tx_index: 0,
+            tx_hash: Hash::default(),
+            block_hash: Hash::default(),
+            block_number: 0,
+            address: Address([7u8; 20]),
+            topics: vec![],
+            data: vec![],
+        };
+        let receipt = Receipt::new(tx_hash, Hash([1; 32]), 1, 1)
+            .with_logs(vec![log.clone(), log]);
+        node.evm_executor.receipt_db().put_receipt(receipt).await.unwrap();
+
+        let receipt = rpc.get_transaction_receipt(tx_hash).await.unwrap().unwrap();
+        assert_eq!(receipt.transaction_index, "0x1");
+        assert_eq!(receipt.logs.len(), 2);
+        assert!(receipt.logs.iter().all(|l| l.transaction_index == "0x1"));
+        assert_eq!(receipt.logs[1].log_index, "0x1");
+
+        let filter = LogFilter {
+            from_block: Some(BlockNumber::Earliest),
+            to_block: Some(BlockNumber::Latest),
+            address: None,
+            topics: None,
+        };
+        let logs = rpc.get_logs(filter).await.unwrap();
+        assert_eq!(logs.len(), 2);
+        assert!(logs.iter().all(|l| l.transaction_index == "0x1"));
+    }
+
+    #[tokio::test]
+    async fn test_receipt_finalized_once_buried() {
+        use norn_core::evm::Receipt;
+
+        let (rpc, node) = test_rpc().await;
+
+        let mut parent = node.blockchain.latest_block.read().await.header.block_hash;
+        let mut next_block = |height: u8| {
+            let mut block = norn_common::types::Block::default();
+            block.header.height = height as i64;
+            block.header.block_hash = Hash([height; 32]);
+            block.header.prev_block_hash = parent;
+            parent = block.header.block_hash;
+            block
+        };
+
+        let tx_hash = Hash([0x10; 32]);
+        let mut block = next_block(1);
+        let mut tx = Transaction::default();
+        tx.body.hash = tx_hash;
+        block.transactions.push(tx);
+        node.blockchain.commit_block(&block).await.unwrap();
+        node.evm_executor.receipt_db().put_receipt(Receipt::new(tx_hash, Hash([1; 32]), 1, 0)).await.unwrap();
+
+        let config = RpcConfig { finality_depth: 2, ..RpcConfig::default() };
+        let rpc = rpc.with_config(config);
+        assert!(!rpc.get_transaction_receipt(tx_hash).await.unwrap().unwrap().finalized);
+
+        node.blockchain.commit_block(&next_block(2)).await.unwrap();
+        assert!(!rpc.get_transaction_receipt(tx_hash).await.unwrap().unwrap().finalized);
+
+        node.blockchain.commit_block(&next_block(3)).await.unwrap();
+        assert!(rpc.get_transaction_receipt(tx_hash).await.unwrap().unwrap().finalized);
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_limits() {
+        use norn_core::evm::{Receipt, ReceiptLog};
+
+        let (rpc, node) = test_rpc().await;
+
+        let log = ReceiptLog {
+            log_index: 0,
+            tx_index: 0,
+            tx_hash: Hash::default(),
+            block_hash: Hash::default(),
+            block_number: 0,
+            address: Address([7u8; 20]),
+            topics: vec![],
+            data: vec![],
+        };
+        let genesis = node.blockchain.latest_block.read().await.header.block_hash;
+        let receipt = Receipt::new(Hash([0x11; 32]), genesis, 0, 0)
+            .with_logs(vec![log.clone(), log.clone(), log]);
+        node.evm_executor.receipt_db().put_receipt(receipt).await.unwrap();
+
+        let config = RpcConfig { max_log_range: 100, max_log_results: 2, ..RpcConfig::default() };
+        let rpc = rpc.with_config(config);
+
+        let filter = |from, to| LogFilter {
+            from_block: Some(BlockNumber::Number(from)),
+            to_block: Some(BlockNumber::Number(to)),
+            address: None,
+            topics: None,
+        };
+
+        let err = rpc.get_logs(filter(0, 1000)).await.unwrap_err();
+        assert!(err.message().contains("block range too large"));
+        assert!(err.message().contains("limit is 100"));
+
+        let err = rpc.get_logs(filter(0, 0)).await.unwrap_err();
+        assert!(err.message().contains("query returned more than 2 results"));
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_prefilters_by_bloom() {
+        use norn_common::types::TransactionType;
+        use norn_core::evm::{Receipt, ReceiptLog};
+        use norn_core::test_support::TestNode;
+
+        let sender = Address([1u8; 20]);
+        let node = TestNode::builder()
+            .with_account(sender, 1_000_000_000_000_000_000u128)
+            .build()
+            .await;
+
+        // LOG1(0, 0, 42); STOP
+        let code = vec![0x60, 0x2a, 0x60, 0x00, 0x60, 0x00, 0xa1, 0x00];
+        let (contract, _) = node.evm_executor.create_contract(sender, 0, code, 0, 100_000).await.unwrap();
+        let mut tx = Transaction::default();
+        tx.body.hash = Hash([8u8; 32]);
+        tx.body.address = sender;
+        tx.body.receiver = contract;
+        tx.body.gas = 100_000;
+        tx.body.tx_type = TransactionType::EVM;
+        tx.body.data = vec![0x01];
+        node.tx_pool.add(tx);
+        node.produce_block(true).await;
+
+        // A receipt the bloom of its block doesn't cover is never looked at
+        let empty = node.produce_block(false).await;
+        let stray = ReceiptLog {
+            log_index: 0,
+            tx_index: 0,
+            tx_hash: Hash([9u8; 32]),
+            block_hash: empty.header.block_hash,
+            block_number: 2,
+            address: contract,
+            topics: vec![],
+            data: vec![],
+        };
+        node.evm_executor.receipt_db()
+            .put_receipt(Receipt::new(Hash([9u8; 32]), empty.header.block_hash, 2, 0).with_logs(vec![stray]))
+            .await
+            .unwrap();
+
+        let rpc = EthereumRpcImpl::new(node.blockchain, node.state_manager, node.evm_executor, node.tx_pool, 31337);
+        let filter = |address| LogFilter {
+            from_block: Some(BlockNumber::Number(1)),
+            to_block: Some(BlockNumber::Number(2)),
+            address: Some(address),
+            topics: None,
+        };
+        let logs = rpc.get_logs(filter(contract)).await.unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].transaction_hash, Hash([8u8; 32]));
+        assert_eq!(logs[0].block_number, "0x1");
+        assert!(rpc.get_logs(filter(Address([0xee; 20]))).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_topic_positions() {
+        use norn_core::evm::{Receipt, ReceiptLog};
+
+        let (rpc, node) = test_rpc().await;
+
+        let log = |topics: Vec<Hash>| ReceiptLog {
+            log_index: 0,
+            tx_index: 0,
+            tx_hash: Hash::default(),
+            block_hash: Hash::default(),
+            block_number: 0,
+            address: Address([7u8; 20]),
+            topics,
+            data: vec![],
+        };
+        let (a, b, c, d) = (Hash([0xaa; 32]), Hash([0xbb; 32]), Hash([0xcc; 32]), Hash([0xdd; 32]));
+        let genesis = node.blockchain.latest_block.read().await.header.block_hash;
+        let receipt = Receipt::new(Hash([0x11; 32]), genesis, 0, 0).with_logs(vec![
+            log(vec![a, d, c]),
+            log(vec![b, a, c]),
+            log(vec![d, a, c]),
+            log(vec![a, b, d]),
+        ]);
+        node.evm_executor.receipt_db().put_receipt(receipt).await.unwrap();
+
+        let filter: LogFilter = serde_json::from_value(serde_json::json!({
+            "topics": [[a, b], null, c]
+        })).unwrap();
+        let logs = rpc.get_logs(filter).await.unwrap();
+        let indexes: Vec<_> = logs.iter().map(|l| l.log_index.as_str()).collect();
+        assert_eq!(indexes, vec!["0x0", "0x1"]);
+
+        // A wildcard at position 0 with a single topic at position 1
+        let filter: LogFilter = serde_json::from_value(serde_json::json!({
+            "topics": [null, a]
+        })).unwrap();
+        let logs = rpc.get_logs(filter).await.unwrap();
+        let indexes: Vec<_> = logs.iter().map(|l| l.log_index.as_str()).collect();
+        assert_eq!(indexes, vec!["0x1", "0x2"]);
+    }
+
+    #[tokio::test]
+    async fn test_state_calls_rejected_while_syncing() {
+        let (rpc, _node) = test_rpc().await;
+
+        let readiness = SyncReadiness::syncing();
+        let rpc = rpc.with_readiness(readiness.clone());
+        let address = Address([1u8; 20]);
+
+        assert!(rpc.syncing().await.unwrap());
+        let err = rpc.get_balance(address, BlockNumber::Latest).await.unwrap_err();
+        assert_eq!(err.message(), "node syncing");
+        let err = rpc.get_transaction_count(address, BlockNumber::Latest).await.unwrap_err();
+        assert_eq!(err.message(), "node syncing");
+        // Chain metadata is still served
+        assert_eq!(rpc.chain_id().await.unwrap(), "0x7a69");
+
+        readiness.set_ready();
+        assert!(!rpc.syncing().await.unwrap());
+        assert_eq!(rpc.get_balance(address, BlockNumber::Latest).await.unwrap(), "0x0");
+    }
+
+    #[tokio::test]
+    async fn test_dev_set_storage_and_code() {
+        let (rpc, node) = test_rpc().await;
+        let contract = Address([5u8; 20]);
+
+        let err = rpc.dev_set_storage_at(contract, "0x0".to_string(), "0x1".to_string()).await.unwrap_err();
+        assert_eq!(err.message(), "dev methods are disabled");
+
+        let sender = Address([1u8; 20]);
+        node.state_manager.add_balance(&sender, &BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+        let rpc = rpc.with_config(RpcConfig { enable_dev_methods: true, ..RpcConfig::default() });
+
+        // Contract returning SLOAD(1)
+        let code = "0x60015460005260206000f3";
+        assert!(rpc.dev_set_code(contract, code.to_string()).await.unwrap());
+        assert_eq!(rpc.get_code(contract, BlockNumber::Latest).await.unwrap(), code);
+
+        assert!(rpc.dev_set_storage_at(contract, "0x1".to_string(), "0x2a".to_string()).await.unwrap());
+        assert_eq!(
+            rpc.get_storage_at(contract, "0x1".to_string(), BlockNumber::Latest).await.unwrap(),
+            format!("0x{:064x}", 42)
+        );
+
+        // Contracts see the seeded slot too
+        let request = CallRequest {
+            to: Some(contract),
+            from: Some(sender),
+            value: None,
+            gas: None,
+            gas_price: None,
+            data: Some("0x".to_string()),
+        };
+        assert_eq!(rpc.call(request, BlockNumber::Latest, None, None).await.unwrap(), format!("0x{:064x}", 42));
+    }
+
+    /// EIP-155 transaction from `key`, hex-encoded for eth_sendRawTransaction
+    fn signed_raw_transaction(key: &k256::ecdsa::SigningKey, nonce: i64, to: Address, data: Vec<u8>) -> String {
+        use norn_crypto::ethereum;
+
+        let mut body = norn_common::types::TransactionBody {
+            receiver: to,
+            gas: 21000,
+            nonce,
+            data,
+            tx_type: norn_common::types::TransactionType::EVM,
+            chain_id: Some(31337),
+            value: Some("0".to_string()),
+            gas_price: Some(1000),
+            ..Default::default()
+        };
+        ethereum::sign(&mut body, key).unwrap();
+        format!("0x{}", hex::encode(ethereum::encode_signed(&body)))
+    }
+
+    #[tokio::test]
+    async fn test_raw_transaction_round_trip() {
+        let (rpc, node) = test_rpc().await;
+        let key = k256::ecdsa::SigningKey::from_slice(&[0x46; 32]).unwrap();
+        let sender = norn_crypto::ethereum::address_of(key.verifying_key());
+        node.state_manager.add_balance(&sender, &BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+
+        let raw = signed_raw_transaction(&key, 0, Address([0xab; 20]), vec![]);
+
+        let hash = rpc.send_raw_transaction(raw.clone()).await.unwrap();
+        assert_eq!(hash.0, keccak_hash::keccak(hex::decode(&raw[2..]).unwrap()).0);
+        assert_eq!(rpc.get_raw_transaction_by_hash(hash).await.unwrap(), Some(raw));
+        assert_eq!(rpc.get_raw_transaction_by_hash(Hash([9u8; 32])).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_raw_transaction_persisted() {
+        let (rpc, node) = test_rpc().await;
+        let db = node.db.clone();
+        let key = k256::ecdsa::SigningKey::from_slice(&[0x46; 32]).unwrap();
+        let sender = norn_crypto::ethereum::address_of(key.verifying_key());
+        node.state_manager.add_balance(&sender, &BigUint::from(1_000_000_000_000_000_000u128)).await.unwrap();
+        let rpc = rpc.with_raw_tx_store(Arc::new(RawTransactionStore::with_db(db.clone())));
+
+        let raw = signed_raw_transaction(&key, 3, Address([0xcd; 20]), vec![0x01, 0x02]);
+        let hash = rpc.send_raw_transaction(raw.clone()).await.unwrap();
+
+        let key = norn_common::utils::db_keys::raw_tx_hash_to_db_key(&hash);
+        let stored = norn_common::traits::DBInterface::get(db.as_ref(), &key).await.unwrap();
+        assert_eq!(stored, Some(hex::decode(&raw[2..]).unwrap()));
+
+        // A fresh store over the same database still serves it
+        let store = RawTransactionStore::with_db(db);
+        assert_eq!(store.get(&hash).await.unwrap(), hex::decode(&raw[2..]).ok());
+    }
+
+    #[tokio::test]
+    async fn test_web3_sha3() {
+        let (rpc, _node) = test_rpc().await;
+
+        // keccak256("hello world")
+        assert_eq!(
+            rpc.sha3("0x68656c6c6f20776f726c64".to_string()).await.unwrap(),
+            "0x47173285a8d7341e5e972fc677286384f802f8ef42a5ec5f03bbfa254cb01fad"
+        );
+        // keccak256 of no data
+        assert_eq!(
+            rpc.sha3("0x".to_string()).await.unwrap(),
+            "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+
+        let err = rpc.sha3("0xzz".to_string()).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidParams.code());
+    }
+
+    #[tokio::test]
+    async fn test_network_status_methods() {
+        let (rpc, _node) = test_rpc().await;
+
+        let network = NetworkStatus::default();
+        let rpc = rpc.with_network_status(network.clone());
+
+        assert_eq!(rpc.protocol_version().await.unwrap(), "0x44");
+        assert_eq!(rpc.peer_count().await.unwrap(), "0x0");
+        assert!(!rpc.listening().await.unwrap());
+
+        network.listener_opened();
+        for _ in 0..17 {
+            network.peer_connected();
+        }
+        assert_eq!(rpc.peer_count().await.unwrap(), "0x11");
+        assert!(rpc.listening().await.unwrap());
+
+        network.peer_disconnected();
+        assert_eq!(rpc.peer_count().await.unwrap(), "0x10");
+    }
+
+    #[tokio::test]
+    async fn test_consensus_status() {
+        use norn_core::consensus::povf::PoVFConfig;
+        use norn_crypto::vdf::SimpleVDF;
+        use norn_crypto::vrf::VRFKeyPair;
+
+        let (rpc, _node) = test_rpc().await;
+        assert_eq!(rpc.consensus_status().await.unwrap(), None);
+
+        let mut config = PoVFConfig::default();
+        config.validator_stakes.insert(PublicKey::default(), 50);
+        let engine = PoVFEngine::new(config, Arc::new(SimpleVDF::new()), VRFKeyPair::generate(), 3, None);
+        let rpc = rpc.with_consensus(Arc::new(engine));
+
+        let status = rpc.consensus_status().await.unwrap().unwrap();
+        assert_eq!(status.round, 3);
+        assert_eq!(status.validator_count, 1);
+        assert_eq!(status.total_stake, 50);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_status() {
+        use norn_storage::{RecoveryStatus, WALEntry, WALStateManager};
+
+        let (rpc, node) = test_rpc().await;
+        let rpc = rpc.with_recovery_status(node.db.clone());
+        assert_eq!(rpc.recovery_status().await.unwrap(), None);
+
+        let wal_dir = tempfile::tempdir().unwrap();
+        let manager = WALStateManager::new(wal_dir.path(), node.db.clone()).unwrap();
+        manager.wal().write(WALEntry::CreateAccount { address: [1u8; 20], data: vec![1] }).unwrap();
+        manager.wal().write(WALEntry::CreateAccount { address: [2u8; 20], data: vec![2] }).unwrap();
+        manager.wal().sync().unwrap();
+        manager.recover().await.unwrap();
+
+        let outcome = rpc.recovery_status().await.unwrap().unwrap();
+        assert_eq!(outcome.status, RecoveryStatus::Recovered { entries_applied: 2, checkpoint_block: None });
+        assert_eq!(outcome.entries_replayed, 2);
+        assert_eq!(outcome.corruption_detected, 0);
+    }
+
+    #[tokio::test]
+    async fn test_chain_id() {
+        let (rpc, _node) = test_rpc().await;
+
         let chain_id = rpc.chain_id().await.unwrap();
         assert_eq!(chain_id, "0x7a69"); // 31337 in hex
     }
@@ -41,5 +41,5 @@ pub fn create_ethereum_rpc(
 }
 
 // Re-export for convenience
-pub use crate::ethereum::start_ethereum_rpc_server;
+pub use crate::ethereum::{start_ethereum_rpc_server, RpcConfig, TopicFilter};
 pub use crate::websocket::{WebSocketServer, WebSocketConfig, EventBroadcaster, SubscriptionType};
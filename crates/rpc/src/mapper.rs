@@ -39,6 +39,11 @@ impl From<Transaction> for proto::Transaction {
             height: t.body.height as u64,
             block_hash: hex::encode(t.body.block_hash.0),
             index: t.body.index as u64,
+            tx_type: match t.body.tx_type {
+                TransactionType::Native => 0,
+                TransactionType::EVM => 1,
+            },
+            value: t.body.value.unwrap_or_default(),
         }
     }
 }
@@ -99,9 +104,9 @@ impl From<proto::Transaction> for Transaction {
                 timestamp: p.timestamp as i64,
                 public,
                 signature: hex::decode(&p.signature).unwrap_or_default(),
-                tx_type: TransactionType::default(),
+                tx_type: if p.tx_type == 1 { TransactionType::EVM } else { TransactionType::Native },
                 chain_id: None,
-                value: None,
+                value: (!p.value.is_empty()).then_some(p.value),
                 gas_price: None,
                 max_fee_per_gas: None,
                 max_priority_fee_per_gas: None,
@@ -160,6 +165,7 @@ impl From<proto::Block> for Block {
             params: hex::decode(&proto_header.params).unwrap_or_default(),
             gas_limit: proto_header.gas_limit as i64,
             base_fee: 1_000_000_000, // Default base fee
+            logs_bloom: Vec::new(),
         };
 
         let transactions: Vec<Transaction> = proto
@@ -3,10 +3,11 @@
 //! This module handles parsing of RLP-encoded Ethereum transactions,
 //! supporting legacy, EIP-2930, and EIP-1559 transaction types.
 
-use norn_common::types::{Hash, Transaction, TransactionBody, TransactionType, PublicKey, AccessListItem, Address};
+use norn_common::types::{Hash, Transaction, TransactionBody, TransactionType, AccessListItem, Address};
+use norn_crypto::ethereum;
+use num_bigint::BigUint;
 use rlp::{Rlp, RlpStream, DecoderError};
 use anyhow::{Result, anyhow};
-use k256::ecdsa::{Signature, VerifyingKey};
 use std::str::FromStr;
 
 /// Ethereum transaction type identifiers
@@ -272,15 +273,59 @@ impl EthereumTransaction {
 
     /// Compute the signing hash for this transaction
     pub fn compute_signing_hash(&self) -> Result<[u8; 32]> {
-        // For now, return a placeholder
-        // In a real implementation, this would compute keccak256(message)
-        Ok([0u8; 32])
+        Ok(ethereum::signing_hash(&self.unsigned_body()?).0)
     }
 
     /// Convert to Norn Transaction
+    ///
+    /// The sender is recovered from the signature. The result keeps the
+    /// Ethereum signature and hash, so it verifies like any other
+    /// transaction; see [`norn_crypto::ethereum`].
     pub fn to_norn_transaction(&self) -> Result<Transaction> {
-        // Placeholder conversion
-        Ok(Transaction::default())
+        let mut body = self.unsigned_body()?;
+
+        let y_parity = match self.tx_type {
+            Some(_) => self.v,
+            None if self.chain_id.is_some() => self.v - 35 - 2 * self.chain_id.unwrap_or(0),
+            None => self.v.wrapping_sub(27),
+        };
+        if y_parity > 1 || self.r.len() > 32 || self.s.len() > 32 {
+            return Err(anyhow!("Invalid signature values"));
+        }
+        let mut signature = vec![0u8; 65];
+        signature[32 - self.r.len()..32].copy_from_slice(&self.r);
+        signature[64 - self.s.len()..64].copy_from_slice(&self.s);
+        signature[64] = y_parity as u8;
+        body.signature = signature;
+
+        let key = ethereum::recover_signer(&body).ok_or_else(|| anyhow!("Invalid signature"))?;
+        body.public = ethereum::public_key_of(&key);
+        body.address = ethereum::address_of(&key);
+        body.hash = ethereum::transaction_hash(&body);
+
+        Ok(Transaction { body })
+    }
+
+    /// Body with the fields of this transaction, without sender and signature
+    fn unsigned_body(&self) -> Result<TransactionBody> {
+        if self.value.len() > 32 {
+            return Err(anyhow!("Invalid value length: {}", self.value.len()));
+        }
+        let is_eip1559 = self.tx_type == Some(TX_TYPE_EIP1559);
+        Ok(TransactionBody {
+            receiver: self.to.unwrap_or_default(),
+            gas: i64::try_from(self.gas_limit).map_err(|_| anyhow!("Gas limit too large"))?,
+            nonce: i64::try_from(self.nonce).map_err(|_| anyhow!("Nonce too large"))?,
+            data: self.data.clone(),
+            tx_type: TransactionType::EVM,
+            chain_id: self.chain_id,
+            value: Some(BigUint::from_bytes_be(&self.value).to_string()),
+            gas_price: (!is_eip1559).then_some(self.gas_price_or_max_priority_fee),
+            max_fee_per_gas: self.max_fee_per_gas,
+            max_priority_fee_per_gas: is_eip1559.then_some(self.gas_price_or_max_priority_fee),
+            access_list: self.tx_type.map(|_| self.access_list.clone()),
+            ..Default::default()
+        })
     }
 }
 
@@ -356,4 +401,30 @@ mod tests {
         let hash = tx.compute_signing_hash();
         assert!(hash.is_ok());
     }
+
+    #[test]
+    fn test_to_norn_transaction_recovers_sender() {
+        // The example transaction of EIP-155
+        let raw = hex::decode(
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        ).unwrap();
+        let tx = EthereumTransaction::parse(&raw).unwrap().to_norn_transaction().unwrap();
+        assert_eq!(hex::encode(tx.body.address.0), "9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+        assert_eq!(tx.body.hash.0, keccak_hash::keccak(&raw).0);
+        assert_eq!(tx.body.value.as_deref(), Some("1000000000000000000"));
+        assert!(ethereum::verify(&tx.body).is_some());
+
+        // Zero r and s recover no key
+        let mut stream = RlpStream::new_list(9);
+        stream.append(&0u64);
+        stream.append(&1000u64);
+        stream.append(&21000u64);
+        stream.append_empty_data();
+        stream.append_empty_data();
+        stream.append_empty_data();
+        stream.append(&27u64);
+        stream.append_empty_data();
+        stream.append_empty_data();
+        assert!(EthereumTransaction::parse(&stream.out()).unwrap().to_norn_transaction().is_err());
+    }
 }
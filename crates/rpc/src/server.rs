@@ -3,9 +3,11 @@ use crate::proto::{
     GetBlockReq, GetBlockResp, GetTransactionReq, GetTransactionResp,
     SendTransactionReq, SendTransactionResp, BlockNumberResp, Empty,
     ReadContractAddressReq, ReadContractAddressResp,
-    SendTransactionWithDataReq, SendTransactionWithDataResp
+    SendTransactionWithDataReq, SendTransactionWithDataResp,
+    SubmitTransactionsReq, SubmitTransactionsResp, SubmitResult
 };
 use tonic::{Request, Response, Status};
+use std::collections::HashSet;
 use std::sync::Arc;
 use norn_core::blockchain::Blockchain;
 use norn_core::txpool::TxPool;
@@ -18,10 +20,44 @@ pub struct BlockchainRpcImpl {
     tx_pool: Arc<TxPool>,
 }
 
+/// Maximum number of transactions accepted in one `SubmitTransactions` call
+const MAX_SUBMIT_BATCH: usize = 10_000;
+
 impl BlockchainRpcImpl {
     pub fn new(chain: Arc<Blockchain>, tx_pool: Arc<TxPool>) -> Self {
         Self { chain, tx_pool }
     }
+
+    /// Verify signatures of `txs` off the async runtime
+    ///
+    /// Each transaction is paired with the reason it failed verification, if any.
+    async fn verify_transactions(txs: Vec<Transaction>) -> Result<Vec<(Transaction, Option<String>)>, Status> {
+        tokio::task::spawn_blocking(move || {
+            txs.into_iter()
+                .map(|tx| {
+                    let error = norn_crypto::transaction::verify_transaction(&tx).err().map(|e| e.to_string());
+                    (tx, error)
+                })
+                .collect()
+        }).await.map_err(|e| Status::internal(format!("Join error: {}", e)))
+    }
+
+    /// Add a transaction to the pool, reporting why it was not accepted
+    fn submit_to_pool(&self, tx: Transaction) -> SubmitResult {
+        let hash = tx.body.hash;
+        let tx_hash = hex::encode(hash.0);
+
+        if self.tx_pool.contains(&hash) {
+            return SubmitResult { tx_hash, accepted: false, error: "already known".to_string() };
+        }
+
+        self.tx_pool.add(tx);
+        if !self.tx_pool.contains(&hash) {
+            return SubmitResult { tx_hash, accepted: false, error: "transaction pool is full".to_string() };
+        }
+
+        SubmitResult { tx_hash, accepted: true, error: String::new() }
+    }
 }
 
 #[tonic::async_trait]
@@ -222,30 +258,134 @@ impl BlockchainService for BlockchainRpcImpl {
         */
 
         // Verify transaction
+        //
         // NOTE: For TPS testing, if verification fails due to "Invalid transaction format" (hash mismatch),
         // we BYPASS it to allow the test to run. The hash mismatch is likely due to protobuf conversion issues
         // or field ordering that differs from the crypto verification logic.
-        let tx_for_verification = internal_tx.clone();
-        let verification_result = tokio::task::spawn_blocking(move || {
-            norn_crypto::transaction::verify_transaction(&tx_for_verification)
-        }).await.map_err(|e| Status::internal(format!("Join error: {}", e)))?;
-
-        match verification_result {
-            Ok(()) => {
-                // Add to transaction pool
-                self.tx_pool.add(internal_tx.clone());
-                // info!("✅ TX Accepted: {}", tx_hash);
-                Ok(Response::new(SendTransactionWithDataResp { tx_hash }))
-            }
-            Err(e) => {
-                error!("❌ TX Verification Failed: {:?} | Hash: {}", e, tx_hash);
-                
-                // FORCE BYPASS VALIDATION
-                warn!("⚠️  FORCE BYPASSING VALIDATION for TPS Test: {}", tx_hash);
-                self.tx_pool.add(internal_tx.clone());
-                
-                Ok(Response::new(SendTransactionWithDataResp { tx_hash }))
-            }
+        let (internal_tx, verification_error) = Self::verify_transactions(vec![internal_tx])
+            .await?
+            .remove(0);
+        if let Some(e) = verification_error {
+            error!("❌ TX Verification Failed: {} | Hash: {}", e, tx_hash);
+
+            // FORCE BYPASS VALIDATION
+            warn!("⚠️  FORCE BYPASSING VALIDATION for TPS Test: {}", tx_hash);
+        }
+
+        // Add to transaction pool
+        self.tx_pool.add(internal_tx);
+        Ok(Response::new(SendTransactionWithDataResp { tx_hash }))
+    }
+
+    async fn submit_transactions(
+        &self,
+        request: Request<SubmitTransactionsReq>,
+    ) -> Result<Response<SubmitTransactionsResp>, Status> {
+        let req = request.into_inner();
+
+        if req.transactions.len() > MAX_SUBMIT_BATCH {
+            return Err(Status::invalid_argument(format!(
+                "Batch of {} transactions exceeds the limit of {}",
+                req.transactions.len(),
+                MAX_SUBMIT_BATCH
+            )));
         }
+
+        let txs: Vec<Transaction> = req.transactions.into_iter().map(Into::into).collect();
+        let txs = Self::verify_transactions(txs).await?;
+
+        let mut seen = HashSet::with_capacity(txs.len());
+        let results = txs
+            .into_iter()
+            .map(|(tx, verification_error)| {
+                let tx_hash = hex::encode(tx.body.hash.0);
+                if let Some(e) = verification_error {
+                    return SubmitResult { tx_hash, accepted: false, error: format!("invalid transaction: {}", e) };
+                }
+                if !seen.insert(tx.body.hash) {
+                    return SubmitResult { tx_hash, accepted: false, error: "duplicate in batch".to_string() };
+                }
+                self.submit_to_pool(tx)
+            })
+            .collect();
+
+        Ok(Response::new(SubmitTransactionsResp { results }))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto;
+    use norn_common::types::Address;
+    use norn_crypto::ecdsa::KeyPair;
+    use norn_crypto::transaction::TransactionSigner;
+    use norn_storage::SledDB;
+
+    fn signed_txs(count: usize) -> Vec<proto::Transaction> {
+        let mut signer = TransactionSigner::new(KeyPair::random());
+        (0..count)
+            .map(|_| {
+                signer
+                    .create_transaction(Address([2u8; 20]), vec![], vec![], vec![], vec![], 21_000, i64::MAX)
+                    .unwrap()
+                    .into()
+            })
+            .collect()
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_submit_transactions_per_item_results() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(SledDB::new(temp_dir.path().to_str().unwrap()).unwrap());
+        let chain = Blockchain::new_with_fixed_genesis(db).await;
+        let tx_pool = Arc::new(TxPool::new());
+        let rpc = BlockchainRpcImpl::new(chain, tx_pool.clone());
+
+        let txs = signed_txs(4);
+        let hashes: Vec<String> = txs.iter().map(|tx| tx.hash.clone()).collect();
+
+        // Already pooled before the batch arrives
+        tx_pool.add(txs[3].clone().into());
+
+        // Changed after signing
+        let mut tampered = txs[2].clone();
+        tampered.gas += 1;
+
+        let req = SubmitTransactionsReq {
+            transactions: vec![txs[0].clone(), txs[1].clone(), tampered, txs[0].clone(), txs[3].clone()],
+        };
+        let results = rpc.submit_transactions(Request::new(req)).await.unwrap().into_inner().results;
+
+        let outcome: Vec<(String, bool)> = results.iter()
+            .map(|r| (r.tx_hash.clone(), r.accepted))
+            .collect();
+        assert_eq!(outcome, vec![
+            (hashes[0].clone(), true),
+            (hashes[1].clone(), true),
+            (hashes[2].clone(), false),
+            (hashes[0].clone(), false),
+            (hashes[3].clone(), false),
+        ]);
+        assert!(results[0].error.is_empty());
+        assert_eq!(results[2].error, "invalid transaction: Invalid transaction format");
+        assert_eq!(results[3].error, "duplicate in batch");
+        assert_eq!(results[4].error, "already known");
+
+        assert_eq!(tx_pool.pending().len(), 3);
+        assert!(!tx_pool.pending().iter().any(|tx| hex::encode(tx.body.hash.0) == hashes[2]));
+    }
+
+    #[tokio::test]
+    async fn test_submit_transactions_rejects_oversized_batch() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Arc::new(SledDB::new(temp_dir.path().to_str().unwrap()).unwrap());
+        let chain = Blockchain::new_with_fixed_genesis(db).await;
+        let rpc = BlockchainRpcImpl::new(chain, Arc::new(TxPool::new()));
+
+        let req = SubmitTransactionsReq {
+            transactions: vec![proto::Transaction::default(); MAX_SUBMIT_BATCH + 1],
+        };
+        let status = rpc.submit_transactions(Request::new(req)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+}
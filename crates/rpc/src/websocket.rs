@@ -21,6 +21,10 @@ use uuid::Uuid;
 
 use norn_core::blockchain::Blockchain;
 use norn_common::types::{Transaction, Block, Hash, Address};
+use norn_common::utils::address::to_checksum_address;
+
+use crate::ethereum::TopicFilter;
+use norn_core::readiness::SyncReadiness;
 
 /// Log filter for eth_subscribe logs
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,9 +34,9 @@ pub struct LogFilter {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub address: Option<Vec<Vec<u8>>>,
 
-    /// Filter by topics (array of topic hashes)
+    /// Filter by topics, per position; `null` matches any topic
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub topics: Option<Vec<Vec<u8>>>,
+    pub topics: Option<Vec<Option<TopicFilter>>>,
 
     /// Filter by block range - from block (inclusive)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -68,17 +72,10 @@ impl LogFilter {
 
         // Check topics filter
         if let Some(ref filter_topics) = self.topics {
-            if !filter_topics.is_empty() {
-                // All filter topics must match (wildcard matching with empty bytes)
-                if filter_topics.len() > log.topics.len() {
-                    return false;
-                }
-
-                for (i, filter_topic) in filter_topics.iter().enumerate() {
-                    if !filter_topic.is_empty() {
-                        if i >= log.topics.len() || filter_topic != &log.topics[i] {
-                            return false;
-                        }
+            for (i, position) in filter_topics.iter().enumerate() {
+                if let Some(accepted) = position {
+                    if !log.topics.get(i).is_some_and(|t| accepted.matches(t)) {
+                        return false;
                     }
                 }
             }
@@ -439,6 +436,7 @@ pub struct WebSocketServer {
     broadcaster: EventBroadcaster,
     blockchain: Arc<Blockchain>,
     connection_manager: Arc<ConnectionManager>,
+    readiness: SyncReadiness,
 }
 
 impl WebSocketServer {
@@ -453,9 +451,16 @@ impl WebSocketServer {
             broadcaster,
             blockchain,
             connection_manager: Arc::new(ConnectionManager::new()),
+            readiness: SyncReadiness::default(),
         }
     }
 
+    /// Only allow `syncing` subscriptions until `readiness` is set
+    pub fn with_readiness(mut self, readiness: SyncReadiness) -> Self {
+        self.readiness = readiness;
+        self
+    }
+
     /// Build the router
     pub fn router(&self) -> Router {
         Router::new()
@@ -465,6 +470,7 @@ impl WebSocketServer {
                 self.broadcaster.clone(),
                 self.blockchain.clone(),
                 self.connection_manager.clone(),
+                self.readiness.clone(),
             ))
     }
 
@@ -483,14 +489,15 @@ impl WebSocketServer {
 /// WebSocket handler
 async fn ws_handler(
     ws: WebSocketUpgrade,
-    State((broadcaster, blockchain, connection_manager)): State<(
+    State((broadcaster, blockchain, connection_manager, readiness)): State<(
         EventBroadcaster,
         Arc<Blockchain>,
         Arc<ConnectionManager>,
+        SyncReadiness,
     )>,
 ) -> impl IntoResponse {
     ws.on_upgrade(move |socket| {
-        handle_socket(socket, broadcaster, blockchain, connection_manager)
+        handle_socket(socket, broadcaster, blockchain, connection_manager, readiness)
     })
 }
 
@@ -500,6 +507,7 @@ async fn handle_socket(
     broadcaster: EventBroadcaster,
     blockchain: Arc<Blockchain>,
     connection_manager: Arc<ConnectionManager>,
+    readiness: SyncReadiness,
 ) {
     // Split the socket into sender and receiver
     let (mut sender, mut receiver) = socket.split();
@@ -561,6 +569,7 @@ async fn handle_socket(
                         &mut subscription_counter,
                         &conn_id,
                         &connection_manager,
+                        &readiness,
                     ).await;
                 } else {
                     let error = WsMessage::error(-32700, "Parse error".to_string());
@@ -593,6 +602,7 @@ async fn handle_socket(
 }
 
 /// Handle client messages
+#[allow(clippy::too_many_arguments)]
 async fn handle_client_message(
     req: &serde_json::Value,
     broadcaster: &EventBroadcaster,
@@ -601,6 +611,7 @@ async fn handle_client_message(
     subscription_counter: &mut u32,
     conn_id: &str,
     connection_manager: &Arc<ConnectionManager>,
+    readiness: &SyncReadiness,
 ) {
     let method = req.get("method").and_then(|m| m.as_str());
     let id = req.get("id");
@@ -610,6 +621,12 @@ async fn handle_client_message(
             if let Some(params) = req.get("params").and_then(|p| p.as_array()) {
                 if let Some(subscription_type) = params.first().and_then(|t| t.as_str()) {
                     if let Some(sub_type) = SubscriptionType::from_str(subscription_type) {
+                        // Chain data is stale until the initial sync completes
+                        if sub_type != SubscriptionType::Syncing && !readiness.is_ready() {
+                            let _ = event_tx.send(WsMessage::error(-32000, "node syncing".to_string()));
+                            return;
+                        }
+
                         *subscription_counter += 1;
                         let subscription_id = format!("0x{:x}", subscription_counter);
 
@@ -759,7 +776,7 @@ fn start_event_forwarding(
                         let data = serde_json::json!({
                             "subscription": sub_id,
                             "result": {
-                                "address": format!("0x{}", hex::encode(&notification.log.address.0)),
+                                "address": to_checksum_address(&notification.log.address),
                                 "topics": notification.log.topics.iter()
                                     .map(|t| format!("0x{}", hex::encode(t)))
                                     .collect::<Vec<_>>(),
@@ -854,8 +871,7 @@ mod tests {
     #[test]
     fn test_log_filter_topic_matching() {
         let mut filter = LogFilter::new();
-        let topic = vec![vec![1u8; 32]];
-        filter.topics = Some(topic.clone());
+        filter.topics = Some(vec![Some(TopicFilter::Single(Hash([1u8; 32])))]);
 
         let log = Log {
             address: Address([0u8; 20]),
@@ -884,6 +900,36 @@ mod tests {
         assert!(!filter.matches(&log_mismatch));
     }
 
+    #[test]
+    fn test_log_filter_topic_or_and_wildcards() {
+        let a = format!("0x{}", hex::encode([0xaau8; 32]));
+        let b = format!("0x{}", hex::encode([0xbbu8; 32]));
+        let c = format!("0x{}", hex::encode([0xccu8; 32]));
+        let filter: LogFilter = serde_json::from_value(serde_json::json!({
+            "topics": [[a, b], null, c]
+        })).unwrap();
+
+        let log_with = |topics: Vec<[u8; 32]>| Log {
+            address: Address([0u8; 20]),
+            topics: topics.into_iter().map(|t| t.to_vec()).collect(),
+            data: vec![],
+            block_number: 1,
+            block_hash: Hash([0u8; 32]),
+            transaction_hash: Hash([0u8; 32]),
+            log_index: 0,
+            transaction_index: 0,
+        };
+
+        assert!(filter.matches(&log_with(vec![[0xaa; 32], [0x01; 32], [0xcc; 32]])));
+        assert!(filter.matches(&log_with(vec![[0xbb; 32], [0x02; 32], [0xcc; 32], [0x03; 32]])));
+        // Position 0 must be A or B
+        assert!(!filter.matches(&log_with(vec![[0xdd; 32], [0x01; 32], [0xcc; 32]])));
+        // Position 2 must be C
+        assert!(!filter.matches(&log_with(vec![[0xaa; 32], [0x01; 32], [0xdd; 32]])));
+        // A wildcard still requires the later positions to exist
+        assert!(!filter.matches(&log_with(vec![[0xaa; 32]])));
+    }
+
     #[tokio::test]
     async fn test_log_publication_and_subscription() {
         let broadcaster = EventBroadcaster::new();
@@ -945,4 +991,31 @@ mod tests {
 
         assert_eq!(manager.get_connection_count().await, 0);
     }
+
+    #[tokio::test]
+    async fn test_subscribe_rejected_while_syncing() {
+        let broadcaster = EventBroadcaster::new();
+        let manager = Arc::new(ConnectionManager::new());
+        manager.register("conn1".to_string(), "127.0.0.1:8080".to_string()).await;
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let mut subscriptions = HashMap::new();
+        let mut counter = 0;
+        let readiness = SyncReadiness::syncing();
+
+        let subscribe = |kind: &str| serde_json::json!({"id": 1, "method": "eth_subscribe", "params": [kind]});
+
+        handle_client_message(&subscribe("newHeads"), &broadcaster, &event_tx, &mut subscriptions, &mut counter, "conn1", &manager, &readiness).await;
+        let msg = event_rx.try_recv().unwrap();
+        assert_eq!(msg.error.unwrap()["message"], "node syncing");
+        assert!(subscriptions.is_empty());
+
+        // Sync progress can still be followed
+        handle_client_message(&subscribe("syncing"), &broadcaster, &event_tx, &mut subscriptions, &mut counter, "conn1", &manager, &readiness).await;
+        assert!(event_rx.try_recv().unwrap().error.is_none());
+
+        readiness.set_ready();
+        handle_client_message(&subscribe("newHeads"), &broadcaster, &event_tx, &mut subscriptions, &mut counter, "conn1", &manager, &readiness).await;
+        assert!(event_rx.try_recv().unwrap().error.is_none());
+        assert_eq!(subscriptions.len(), 2);
+    }
 }
@@ -0,0 +1,86 @@
+//! Storage error types
+//!
+//! Every fallible operation of `SledDB`, the WAL and WAL recovery returns a
+//! `StorageError`, so callers can tell a corrupted database apart from a
+//! missing key or a failed disk write.
+
+use norn_common::error::{DatabaseError, NornError};
+use thiserror::Error;
+
+/// Storage layer errors
+#[derive(Debug, Error)]
+pub enum StorageError {
+    /// Reading or writing the underlying files failed
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A value could not be encoded or decoded
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    /// Stored data failed an integrity check
+    #[error("Storage corruption: {0}")]
+    Corruption(String),
+
+    /// A write raced with a concurrent writer and was not applied
+    #[error("Transaction conflict: {0}")]
+    TransactionConflict(String),
+
+    /// A required record does not exist
+    #[error("Not found: {0}")]
+    NotFound(String),
+}
+
+/// Storage result type
+pub type Result<T> = std::result::Result<T, StorageError>;
+
+impl StorageError {
+    /// I/O error with a custom message
+    pub(crate) fn io(message: impl Into<String>) -> Self {
+        Self::Io(std::io::Error::other(message.into()))
+    }
+}
+
+/// Prefix an I/O error with what was being done, keeping its kind
+pub(crate) fn io_context(context: impl std::fmt::Display) -> impl FnOnce(std::io::Error) -> StorageError {
+    move |e| StorageError::Io(std::io::Error::new(e.kind(), format!("{}: {}", context, e)))
+}
+
+/// Error for a lock poisoned by a panicking holder
+pub(crate) fn lock_error<T>(e: std::sync::PoisonError<T>) -> StorageError {
+    StorageError::io(format!("Lock poisoned: {}", e))
+}
+
+impl From<sled::Error> for StorageError {
+    fn from(e: sled::Error) -> Self {
+        match e {
+            sled::Error::Io(e) => Self::Io(e),
+            sled::Error::Corruption { at, .. } => match at {
+                Some(at) => Self::Corruption(format!("sled data corrupted at {}", at)),
+                None => Self::Corruption("sled data corrupted".to_string()),
+            },
+            sled::Error::CollectionNotFound(name) => {
+                Self::NotFound(format!("tree {:?}", String::from_utf8_lossy(&name)))
+            }
+            other => Self::io(other.to_string()),
+        }
+    }
+}
+
+impl From<bincode::Error> for StorageError {
+    fn from(e: bincode::Error) -> Self {
+        Self::Serialization(e.to_string())
+    }
+}
+
+impl From<StorageError> for NornError {
+    fn from(e: StorageError) -> Self {
+        match e {
+            StorageError::Io(e) => NornError::Io(e),
+            StorageError::Serialization(msg) => NornError::Serialization(msg),
+            StorageError::Corruption(_) => NornError::Database(DatabaseError::Corruption),
+            StorageError::TransactionConflict(msg) => NornError::Database(DatabaseError::TransactionFailed(msg)),
+            StorageError::NotFound(msg) => NornError::Database(DatabaseError::KeyNotFound(msg)),
+        }
+    }
+}
@@ -1,7 +1,11 @@
+pub mod error;
 pub mod sled;
 pub mod wal;
 pub mod recovery;
+pub mod snapshot;
 
-pub use sled::SledDB;
-pub use wal::{WAL, WALEntry, WALConfig};
-pub use recovery::{WALRecoveryManager, WALStateManager, RecoveryStatus};
+pub use error::StorageError;
+pub use sled::{SledDB, SledConfig, CompactionStats};
+pub use snapshot::DbSnapshot;
+pub use wal::{WAL, WALEntry, WALConfig, WALCorruption, WALScan, SequencedEntry};
+pub use recovery::{WALRecoveryManager, WALStateManager, RecoveryOutcome, RecoveryStatus, RecoveryReport};
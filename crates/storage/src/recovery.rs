@@ -3,19 +3,29 @@
 //! This module provides recovery functionality using the WAL,
 //! allowing the database to recover to a consistent state after a crash.
 
-use crate::wal::{WAL, WALEntry, WALConfig};
-use norn_common::error::{NornError, Result};
+use crate::wal::{WAL, WALEntry, WALConfig, WALCorruption, WALScan, SequencedEntry};
+use crate::error::{Result, StorageError};
 use norn_common::types::Hash;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 use tracing::{info, warn, error, debug};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::SledDB;
 
+/// Database key holding the identity of the WAL and the sequence number of
+/// the last entry of it applied by recovery
+const LAST_APPLIED_KEY: &[u8] = b"wal_last_applied_sequence";
+
+/// Database key holding the outcome of the last recovery run
+const LAST_RECOVERY_KEY: &[u8] = b"wal_last_recovery";
+
 /// Recovery status
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
 pub enum RecoveryStatus {
     /// No recovery needed (clean shutdown)
     Clean,
@@ -32,46 +42,117 @@ pub enum RecoveryStatus {
     },
 }
 
-/// WAL recovery manager
-pub struct WALRecoveryManager {
-    /// WAL instance
-    wal: Arc<WAL>,
+/// Outcome of the last recovery run
+///
+/// Stored in the database by [`WALRecoveryManager::recover`], so it can be
+/// reported after the fact by the node and the CLI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryOutcome {
+    /// How the run ended
+    pub status: RecoveryStatus,
 
-    /// Database
-    db: Arc<SledDB>,
+    /// Entries applied to the database, including those applied before a failure
+    pub entries_replayed: usize,
+
+    /// Corrupted or unreadable WAL entries detected
+    pub corruption_detected: usize,
+
+    /// How long the run took, in milliseconds
+    pub duration_ms: u64,
+
+    /// When the run ended, in seconds since the Unix epoch
+    pub finished_at: u64,
 }
 
-impl WALRecoveryManager {
-    /// Create a new recovery manager
-    pub fn new(wal: Arc<WAL>, db: Arc<SledDB>) -> Self {
-        Self { wal, db }
+impl RecoveryOutcome {
+    fn new(status: RecoveryStatus, entries_replayed: usize, corruption_detected: usize, duration: Duration) -> Self {
+        Self {
+            status,
+            entries_replayed,
+            corruption_detected,
+            duration_ms: duration.as_millis() as u64,
+            finished_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        }
     }
 
-    /// Recover from crash using WAL
-    pub async fn recover(&self) -> Result<RecoveryStatus> {
-        info!("Starting WAL recovery");
+    /// Outcome of the last recovery run on `db`, if recovery ever ran
+    pub fn load(db: &SledDB) -> Result<Option<Self>> {
+        db.get_sync(LAST_RECOVERY_KEY)?
+            .map(|bytes| bincode::deserialize(&bytes).map_err(StorageError::from))
+            .transpose()
+    }
 
-        // Read all WAL entries
-        let entries = self.wal.read_all()?;
+    fn store(&self, db: &SledDB) -> Result<()> {
+        db.insert_sync(LAST_RECOVERY_KEY, &bincode::serialize(self)?)
+    }
+}
 
-        if entries.is_empty() {
-            info!("No WAL entries found, clean shutdown");
-            return Ok(RecoveryStatus::Clean);
-        }
+/// Summary of what WAL recovery would do, produced by a dry run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecoveryReport {
+    /// Number of entries that would be applied
+    pub entries_to_apply: usize,
+
+    /// Entries skipped because an earlier recovery already applied them
+    pub entries_already_applied: usize,
+
+    /// Entries that would be applied, by entry type
+    pub entries_by_type: BTreeMap<&'static str, usize>,
+
+    /// Transactions that were rolled back or never committed
+    pub discarded_transactions: usize,
+
+    /// Last checkpoint found in the WAL
+    pub checkpoint_block: Option<u64>,
+
+    /// Corruption detected while reading the WAL
+    pub corruption: Vec<WALCorruption>,
+}
+
+impl RecoveryReport {
+    /// Whether the WAL was read without detecting corruption
+    pub fn is_clean(&self) -> bool {
+        self.corruption.is_empty()
+    }
+}
+
+/// Entries recovery will apply, in replay order
+struct RecoveryPlan {
+    /// Entries outside of transactions and from committed transactions,
+    /// with their WAL sequence numbers, in ascending sequence order
+    entries: Vec<SequencedEntry>,
 
-        let mut entries_applied = 0;
-        let mut checkpoint_block = None;
+    /// Transactions that were rolled back or never committed
+    discarded_transactions: usize,
+
+    /// Last checkpoint seen
+    checkpoint_block: Option<u64>,
+}
+
+impl RecoveryPlan {
+    /// Group raw WAL entries into what recovery should apply
+    ///
+    /// Entries before a checkpoint are already durable in the database, so
+    /// replay starts after the last checkpoint marker. A transaction still
+    /// open at the checkpoint has not been applied yet and is kept.
+    fn build(entries: Vec<SequencedEntry>) -> Self {
+        let mut plan = Self {
+            entries: Vec::new(),
+            discarded_transactions: 0,
+            checkpoint_block: None,
+        };
 
         // Group entries by transactions
-        let mut current_transaction: Option<(u64, Vec<WALEntry>)> = None;
-        let mut committed_transactions = Vec::new();
+        let mut current_transaction: Option<(u64, Vec<SequencedEntry>)> = None;
 
-        for entry in entries {
+        for (sequence, entry) in entries {
             match entry {
                 WALEntry::TransactionBegin { id } => {
                     if current_transaction.is_some() {
                         warn!("Nested transaction detected, rolling back previous");
                         // Previous transaction was not committed, discard it
+                        plan.discarded_transactions += 1;
                     }
                     current_transaction = Some((id, Vec::new()));
                 }
@@ -79,9 +160,12 @@ impl WALRecoveryManager {
                 WALEntry::TransactionCommit { id } => {
                     if let Some((tid, entries)) = current_transaction.take() {
                         if tid == id {
-                            committed_transactions.push((tid, entries));
+                            // Nothing else is written while a transaction is
+                            // open, so appending keeps the sequence order
+                            plan.entries.extend(entries);
                         } else {
                             warn!("Transaction ID mismatch: expected {}, got {}", tid, id);
+                            plan.discarded_transactions += 1;
                         }
                     }
                 }
@@ -93,75 +177,201 @@ impl WALRecoveryManager {
                         } else {
                             warn!("Transaction rollback ID mismatch: expected {}, got {}", tid, id);
                         }
+                        plan.discarded_transactions += 1;
                     }
                 }
 
                 WALEntry::Checkpoint { block_number, block_hash: _ } => {
-                    checkpoint_block = Some(block_number);
+                    plan.checkpoint_block = Some(block_number);
+                    plan.entries.clear();
+                    plan.discarded_transactions = 0;
                     info!("Found checkpoint at block {}", block_number);
                 }
 
                 entry => {
                     // Add to current transaction or apply directly
                     if let Some((_, ref mut entries)) = current_transaction {
-                        entries.push(entry);
+                        entries.push((sequence, entry));
                     } else {
-                        // Not in a transaction, apply directly
-                        if let Err(e) = self.apply_entry(&entry).await {
-                            error!("Failed to apply WAL entry: {:?}", e);
-                            return Ok(RecoveryStatus::Failed {
-                                reason: format!("Failed to apply entry: {}", e),
-                            });
-                        }
-                        entries_applied += 1;
+                        plan.entries.push((sequence, entry));
                     }
                 }
             }
         }
 
-        // Apply committed transactions
-        for (_id, entries) in committed_transactions {
-            for entry in entries {
-                if let Err(e) = self.apply_entry(&entry).await {
-                    error!("Failed to apply transaction entry: {:?}", e);
-                    return Ok(RecoveryStatus::Failed {
-                        reason: format!("Failed to apply transaction: {}", e),
-                    });
+        if current_transaction.is_some() {
+            plan.discarded_transactions += 1;
+        }
+
+        plan
+    }
+}
+
+/// WAL recovery manager
+pub struct WALRecoveryManager {
+    /// WAL instance
+    wal: Arc<WAL>,
+
+    /// Database
+    db: Arc<SledDB>,
+}
+
+impl WALRecoveryManager {
+    /// Create a new recovery manager
+    pub fn new(wal: Arc<WAL>, db: Arc<SledDB>) -> Self {
+        Self { wal, db }
+    }
+
+    /// Recover from crash using WAL
+    ///
+    /// Replay is idempotent: the sequence number of the last applied entry is
+    /// stored in the database together with each entry, and entries up to it
+    /// are skipped, so re-running an interrupted recovery does not apply
+    /// anything twice. The marker belongs to one WAL; if the WAL directory was
+    /// recreated, replay starts over. The outcome is
+    /// recorded for [`RecoveryOutcome::load`], including when the WAL can't be
+    /// read.
+    pub async fn recover(&self) -> Result<RecoveryStatus> {
+        info!("Starting WAL recovery");
+        let started = Instant::now();
+
+        let scan = self.wal.scan()?;
+        let corruption_detected = scan.corruption.len();
+        let mut entries_replayed = 0;
+        let result = self.replay(scan, &mut entries_replayed).await;
+
+        let status = match &result {
+            Ok(status) => status.clone(),
+            Err(e) => RecoveryStatus::Failed { reason: e.to_string() },
+        };
+        RecoveryOutcome::new(status, entries_replayed, corruption_detected, started.elapsed()).store(&self.db)?;
+
+        result
+    }
+
+    /// Apply the scanned entries not applied yet, counting them in `entries_applied`
+    ///
+    /// Entries with a bad checksum are skipped; a WAL file that can't be
+    /// read to the end fails recovery.
+    async fn replay(&self, scan: WALScan, entries_applied: &mut usize) -> Result<RecoveryStatus> {
+        for issue in scan.corruption {
+            match issue {
+                WALCorruption::ChecksumMismatch { sequence, .. } => {
+                    warn!("WAL entry checksum mismatch at sequence {}", sequence);
+                }
+                WALCorruption::Unreadable { reason, .. } => {
+                    return Err(StorageError::Corruption(reason));
                 }
-                entries_applied += 1;
             }
         }
 
-        info!("WAL recovery completed: {} entries applied, checkpoint at {:?}",
-              entries_applied, checkpoint_block);
+        if scan.entries.is_empty() {
+            info!("No WAL entries found, clean shutdown");
+            return Ok(RecoveryStatus::Clean);
+        }
+
+        let plan = RecoveryPlan::build(scan.entries);
+        let last_applied = self.last_applied_sequence()?;
+        let mut entries_skipped = 0;
+
+        for (sequence, entry) in &plan.entries {
+            if last_applied.is_some_and(|last| *sequence <= last) {
+                entries_skipped += 1;
+                continue;
+            }
+
+            if let Err(e) = self.apply_entry(*sequence, entry).await {
+                error!("Failed to apply WAL entry: {:?}", e);
+                return Ok(RecoveryStatus::Failed {
+                    reason: format!("Failed to apply entry: {}", e),
+                });
+            }
+            *entries_applied += 1;
+        }
+
+        let checkpoint_block = plan.checkpoint_block;
+        info!("WAL recovery completed: {} entries applied, {} already applied, checkpoint at {:?}",
+              entries_applied, entries_skipped, checkpoint_block);
 
         Ok(RecoveryStatus::Recovered {
-            entries_applied,
+            entries_applied: *entries_applied,
             checkpoint_block,
         })
     }
 
+    /// Report what `recover` would replay, without touching the database
+    pub fn dry_run(&self) -> Result<RecoveryReport> {
+        let scan = self.wal.scan()?;
+        let plan = RecoveryPlan::build(scan.entries);
+        let last_applied = self.last_applied_sequence()?;
+
+        let mut report = RecoveryReport {
+            checkpoint_block: plan.checkpoint_block,
+            discarded_transactions: plan.discarded_transactions,
+            corruption: scan.corruption,
+            ..Default::default()
+        };
+        for (sequence, entry) in &plan.entries {
+            if last_applied.is_some_and(|last| *sequence <= last) {
+                report.entries_already_applied += 1;
+                continue;
+            }
+            report.entries_to_apply += 1;
+            *report.entries_by_type.entry(entry.kind()).or_insert(0) += 1;
+        }
+
+        info!("WAL recovery dry run: {} entries would be applied, {} corrupted",
+              report.entries_to_apply, report.corruption.len());
+
+        Ok(report)
+    }
+
+    /// Sequence number of the last entry of this WAL applied by recovery
+    ///
+    /// A marker left by another WAL, e.g. before the WAL directory was
+    /// recreated, is ignored.
+    fn last_applied_sequence(&self) -> Result<Option<u64>> {
+        let value = self.db.get_sync(LAST_APPLIED_KEY)?;
+
+        match value {
+            Some(bytes) => {
+                let bytes: [u8; 16] = bytes.as_slice().try_into()
+                    .map_err(|_| StorageError::Corruption("Invalid last applied WAL entry marker".to_string()))?;
+                let wal_id = u64::from_be_bytes(bytes[..8].try_into().unwrap());
+                if wal_id != self.wal.id() {
+                    info!("Last applied WAL entry marker belongs to another WAL, replaying from the start");
+                    return Ok(None);
+                }
+                Ok(Some(u64::from_be_bytes(bytes[8..].try_into().unwrap())))
+            }
+            None => Ok(None),
+        }
+    }
+
     /// Apply a single WAL entry to the database
-    async fn apply_entry(&self, entry: &WALEntry) -> Result<()> {
+    ///
+    /// The entry is written in one transaction with the marker recording
+    /// `sequence` as the last applied entry.
+    async fn apply_entry(&self, sequence: u64, entry: &WALEntry) -> Result<()> {
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+        let mut deletes = Vec::new();
+
         match entry {
             WALEntry::CreateAccount { address, data } => {
-                let key = format!("account_{}", hex::encode(address));
-                self.db.insert_sync(key.as_bytes(), data)
-                    .map_err(|e| NornError::Internal(format!("Failed to insert account: {}", e)))?;
+                keys.push(format!("account_{}", hex::encode(address)).into_bytes());
+                values.push(data.clone());
                 debug!("Recovered account {}", hex::encode(address));
             }
 
             WALEntry::UpdateAccount { address, data } => {
-                let key = format!("account_{}", hex::encode(address));
-                self.db.insert_sync(key.as_bytes(), data)
-                    .map_err(|e| NornError::Internal(format!("Failed to update account: {}", e)))?;
+                keys.push(format!("account_{}", hex::encode(address)).into_bytes());
+                values.push(data.clone());
                 debug!("Updated account {}", hex::encode(address));
             }
 
             WALEntry::DeleteAccount { address } => {
-                let key = format!("account_{}", hex::encode(address));
-                self.db.remove_sync(key.as_bytes())
-                    .map_err(|e| NornError::Internal(format!("Failed to delete account: {}", e)))?;
+                deletes.push(format!("account_{}", hex::encode(address)).into_bytes());
                 debug!("Deleted account {}", hex::encode(address));
             }
 
@@ -170,8 +380,8 @@ impl WALRecoveryManager {
                     hex::encode(address),
                     hex::encode(key)
                 );
-                self.db.insert_sync(storage_key.as_bytes(), value)
-                    .map_err(|e| NornError::Internal(format!("Failed to write storage: {}", e)))?;
+                keys.push(storage_key.into_bytes());
+                values.push(value.clone());
                 debug!("Recovered storage for {}", hex::encode(address));
             }
 
@@ -180,8 +390,7 @@ impl WALRecoveryManager {
                     hex::encode(address),
                     hex::encode(key)
                 );
-                self.db.remove_sync(storage_key.as_bytes())
-                    .map_err(|e| NornError::Internal(format!("Failed to delete storage: {}", e)))?;
+                deletes.push(storage_key.into_bytes());
                 debug!("Deleted storage for {}", hex::encode(address));
             }
 
@@ -194,7 +403,12 @@ impl WALRecoveryManager {
             }
         }
 
-        Ok(())
+        let mut marker = self.wal.id().to_be_bytes().to_vec();
+        marker.extend_from_slice(&sequence.to_be_bytes());
+        keys.push(LAST_APPLIED_KEY.to_vec());
+        values.push(marker);
+
+        self.db.transaction(&keys, &values, &deletes)
     }
 }
 
@@ -205,6 +419,9 @@ pub struct WALStateManager {
 
     /// WAL instance
     wal: Arc<WAL>,
+
+    /// Database
+    db: Arc<SledDB>,
 }
 
 impl WALStateManager {
@@ -212,9 +429,9 @@ impl WALStateManager {
     pub fn new(wal_dir: impl AsRef<Path>, db: Arc<SledDB>) -> Result<Self> {
         let config = WALConfig::default();
         let wal = Arc::new(WAL::new(wal_dir, config)?);
-        let recovery = Arc::new(WALRecoveryManager::new(wal.clone(), db));
+        let recovery = Arc::new(WALRecoveryManager::new(wal.clone(), db.clone()));
 
-        Ok(Self { recovery, wal })
+        Ok(Self { recovery, wal, db })
     }
 
     /// Perform recovery on startup
@@ -222,6 +439,29 @@ impl WALStateManager {
         self.recovery.recover().await
     }
 
+    /// Report what recovery would replay without applying it
+    pub fn dry_run(&self) -> Result<RecoveryReport> {
+        self.recovery.dry_run()
+    }
+
+    /// Outcome of the last recovery run, if recovery ever ran
+    pub fn last_recovery(&self) -> Result<Option<RecoveryOutcome>> {
+        RecoveryOutcome::load(&self.db)
+    }
+
+    /// Flush the database and checkpoint the WAL at the given block
+    ///
+    /// WAL files before the checkpoint are removed, so later recovery only
+    /// replays entries written after it. Callers must make sure every entry
+    /// written so far has been applied to the database before checkpointing.
+    pub fn checkpoint(&self, block_number: u64, block_hash: [u8; 32]) -> Result<()> {
+        self.db.flush()?;
+        self.wal.checkpoint_and_truncate(block_number, block_hash)?;
+
+        info!("WAL checkpoint at block {}", block_number);
+        Ok(())
+    }
+
     /// Get WAL instance for writing
     pub fn wal(&self) -> &WAL {
         &self.wal
@@ -256,13 +496,13 @@ mod tests {
         let db = Arc::new(SledDB::new(&db_dir).unwrap());
         let wal = Arc::new(WAL::new(temp_dir.path().join("wal"), WALConfig::default()).unwrap());
 
-        // Write some WAL entries
+        // Write some WAL entries after a checkpoint
+        wal.checkpoint(100, [5u8; 32]).unwrap();
+
         wal.write(WALEntry::CreateAccount {
             address: [1u8; 20],
             data: vec![2, 3, 4],
         }).unwrap();
-
-        wal.checkpoint(100, [5u8; 32]).unwrap();
         wal.sync().unwrap();
 
         // Create new recovery manager (simulating restart)
@@ -282,4 +522,256 @@ mod tests {
         let data = db.get_sync(key.as_bytes()).unwrap().unwrap();
         assert_eq!(data, vec![2, 3, 4]);
     }
+
+    #[tokio::test]
+    async fn test_dry_run_matches_recovery() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_dir = temp_dir.path().join("db");
+        std::fs::create_dir(&db_dir).unwrap();
+
+        let db = Arc::new(SledDB::new(&db_dir).unwrap());
+        let wal = Arc::new(WAL::new(temp_dir.path().join("wal"), WALConfig::default()).unwrap());
+
+        wal.checkpoint(7, [0u8; 32]).unwrap();
+        wal.write(WALEntry::CreateAccount { address: [1u8; 20], data: vec![1] }).unwrap();
+        wal.write(WALEntry::TransactionBegin { id: 1 }).unwrap();
+        wal.write(WALEntry::UpdateAccount { address: [1u8; 20], data: vec![2] }).unwrap();
+        wal.write(WALEntry::WriteStorage { address: [1u8; 20], key: vec![3], value: vec![4] }).unwrap();
+        wal.write(WALEntry::TransactionCommit { id: 1 }).unwrap();
+        wal.write(WALEntry::TransactionBegin { id: 2 }).unwrap();
+        wal.write(WALEntry::DeleteAccount { address: [1u8; 20] }).unwrap();
+        wal.write(WALEntry::TransactionRollback { id: 2 }).unwrap();
+        wal.sync().unwrap();
+
+        let recovery = WALRecoveryManager::new(wal.clone(), db.clone());
+        let report = recovery.dry_run().unwrap();
+
+        assert!(report.is_clean());
+        assert_eq!(report.entries_to_apply, 3);
+        assert_eq!(report.entries_by_type.get("create_account"), Some(&1));
+        assert_eq!(report.entries_by_type.get("update_account"), Some(&1));
+        assert_eq!(report.entries_by_type.get("write_storage"), Some(&1));
+        assert_eq!(report.entries_by_type.get("delete_account"), None);
+        assert_eq!(report.discarded_transactions, 1);
+        assert_eq!(report.checkpoint_block, Some(7));
+
+        // Dry run leaves the database untouched
+        let key = format!("account_{}", hex::encode([1u8; 20]));
+        assert!(db.get_sync(key.as_bytes()).unwrap().is_none());
+
+        match recovery.recover().await.unwrap() {
+            RecoveryStatus::Recovered { entries_applied, checkpoint_block } => {
+                assert_eq!(entries_applied, report.entries_to_apply);
+                assert_eq!(checkpoint_block, report.checkpoint_block);
+            }
+            status => panic!("Expected Recovered status, got {:?}", status),
+        }
+    }
+
+    #[test]
+    fn test_dry_run_reports_corruption() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_dir = temp_dir.path().join("db");
+        std::fs::create_dir(&db_dir).unwrap();
+
+        let db = Arc::new(SledDB::new(&db_dir).unwrap());
+        let wal_dir = temp_dir.path().join("wal");
+        let wal = Arc::new(WAL::new(&wal_dir, WALConfig::default()).unwrap());
+
+        wal.write(WALEntry::CreateAccount { address: [1u8; 20], data: vec![1] }).unwrap();
+        wal.sync().unwrap();
+
+        // Simulate a torn write at the end of the log
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(wal_dir.join("wal-0.log"))
+            .unwrap();
+        std::io::Write::write_all(&mut file, &[16, 0, 0, 0, 1, 2]).unwrap();
+
+        let report = WALRecoveryManager::new(wal, db).dry_run().unwrap();
+
+        assert_eq!(report.entries_to_apply, 1);
+        assert_eq!(report.corruption.len(), 1);
+        assert!(matches!(report.corruption[0], WALCorruption::Unreadable { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_recovery_starts_from_checkpoint() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_dir = temp_dir.path().join("db");
+        std::fs::create_dir(&db_dir).unwrap();
+        let wal_dir = temp_dir.path().join("wal");
+
+        let db = Arc::new(SledDB::new(&db_dir).unwrap());
+        let manager = WALStateManager::new(&wal_dir, db.clone()).unwrap();
+
+        // Entries applied to the database before the checkpoint
+        for i in 0..3u8 {
+            let entry = WALEntry::CreateAccount { address: [i; 20], data: vec![i] };
+            manager.wal().write(entry).unwrap();
+            let key = format!("account_{}", hex::encode([i; 20]));
+            db.insert_sync(key.as_bytes(), &[i]).unwrap();
+        }
+        manager.checkpoint(10, [1u8; 32]).unwrap();
+
+        // Entries not yet applied when the node stops
+        manager.wal().write(WALEntry::UpdateAccount { address: [0u8; 20], data: vec![9] }).unwrap();
+        manager.wal().write(WALEntry::CreateAccount { address: [7u8; 20], data: vec![7] }).unwrap();
+        manager.wal().sync().unwrap();
+        drop(manager);
+
+        // Only the checkpoint file is left behind, next to the WAL identity
+        assert_eq!(std::fs::read_dir(&wal_dir).unwrap().count(), 2);
+
+        let manager = WALStateManager::new(&wal_dir, db.clone()).unwrap();
+        match manager.recover().await.unwrap() {
+            RecoveryStatus::Recovered { entries_applied, checkpoint_block } => {
+                assert_eq!(entries_applied, 2);
+                assert_eq!(checkpoint_block, Some(10));
+            }
+            status => panic!("Expected Recovered status, got {:?}", status),
+        }
+
+        let get = |address: [u8; 20]| {
+            db.get_sync(format!("account_{}", hex::encode(address)).as_bytes()).unwrap()
+        };
+        assert_eq!(get([0u8; 20]), Some(vec![9]));
+        assert_eq!(get([1u8; 20]), Some(vec![1]));
+        assert_eq!(get([7u8; 20]), Some(vec![7]));
+    }
+
+    #[tokio::test]
+    async fn test_replay_is_idempotent() {
+        let temp_dir = TempDir::new().unwrap();
+        let wal = Arc::new(WAL::new(temp_dir.path().join("wal"), WALConfig::default()).unwrap());
+
+        wal.write(WALEntry::CreateAccount { address: [1u8; 20], data: vec![1] }).unwrap();
+        wal.write(WALEntry::WriteStorage { address: [1u8; 20], key: vec![1], value: vec![2] }).unwrap();
+        wal.write(WALEntry::TransactionBegin { id: 1 }).unwrap();
+        wal.write(WALEntry::UpdateAccount { address: [1u8; 20], data: vec![3] }).unwrap();
+        wal.write(WALEntry::DeleteStorage { address: [1u8; 20], key: vec![1] }).unwrap();
+        wal.write(WALEntry::TransactionCommit { id: 1 }).unwrap();
+        wal.sync().unwrap();
+
+        let open_db = |name: &str| {
+            let dir = temp_dir.path().join(name);
+            std::fs::create_dir(&dir).unwrap();
+            Arc::new(SledDB::new(&dir).unwrap())
+        };
+        // The outcome record differs per run, so it is left out
+        let dump = |db: &SledDB| -> Vec<(Vec<u8>, Vec<u8>)> {
+            db.iter_prefix(b"")
+                .map(|kv| kv.unwrap())
+                .filter(|(key, _)| key.as_slice() != LAST_RECOVERY_KEY)
+                .collect()
+        };
+
+        let once = open_db("once");
+        WALRecoveryManager::new(wal.clone(), once.clone()).recover().await.unwrap();
+
+        let twice = open_db("twice");
+        let recovery = WALRecoveryManager::new(wal.clone(), twice.clone());
+        recovery.recover().await.unwrap();
+
+        // State changed after recovery must not be overwritten by a second replay
+        let account_key = format!("account_{}", hex::encode([1u8; 20]));
+        once.insert_sync(account_key.as_bytes(), &[4]).unwrap();
+        twice.insert_sync(account_key.as_bytes(), &[4]).unwrap();
+
+        match recovery.recover().await.unwrap() {
+            RecoveryStatus::Recovered { entries_applied, .. } => assert_eq!(entries_applied, 0),
+            status => panic!("Expected Recovered status, got {:?}", status),
+        }
+        assert_eq!(recovery.dry_run().unwrap().entries_already_applied, 4);
+        assert_eq!(dump(&once), dump(&twice));
+
+        // Entries written after the last replay are still applied
+        wal.write(WALEntry::DeleteAccount { address: [1u8; 20] }).unwrap();
+        match recovery.recover().await.unwrap() {
+            RecoveryStatus::Recovered { entries_applied, .. } => assert_eq!(entries_applied, 1),
+            status => panic!("Expected Recovered status, got {:?}", status),
+        }
+        assert!(twice.get_sync(account_key.as_bytes()).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_replay_after_wal_recreated() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_dir = temp_dir.path().join("db");
+        std::fs::create_dir(&db_dir).unwrap();
+        let wal_dir = temp_dir.path().join("wal");
+        let db = Arc::new(SledDB::new(&db_dir).unwrap());
+
+        let wal = Arc::new(WAL::new(&wal_dir, WALConfig::default()).unwrap());
+        for i in 0..3u8 {
+            wal.write(WALEntry::CreateAccount { address: [i; 20], data: vec![i] }).unwrap();
+        }
+        wal.sync().unwrap();
+        WALRecoveryManager::new(wal.clone(), db.clone()).recover().await.unwrap();
+        let old_id = wal.id();
+        drop(wal);
+
+        // The new WAL numbers its entries from 1 again
+        std::fs::remove_dir_all(&wal_dir).unwrap();
+        let wal = Arc::new(WAL::new(&wal_dir, WALConfig::default()).unwrap());
+        assert_ne!(wal.id(), old_id);
+        assert_eq!(wal.write(WALEntry::CreateAccount { address: [9u8; 20], data: vec![9] }).unwrap(), 1);
+        wal.sync().unwrap();
+
+        let recovery = WALRecoveryManager::new(wal, db.clone());
+        assert_eq!(recovery.dry_run().unwrap().entries_to_apply, 1);
+        match recovery.recover().await.unwrap() {
+            RecoveryStatus::Recovered { entries_applied, .. } => assert_eq!(entries_applied, 1),
+            status => panic!("Expected Recovered status, got {:?}", status),
+        }
+        let key = format!("account_{}", hex::encode([9u8; 20]));
+        assert_eq!(db.get_sync(key.as_bytes()).unwrap(), Some(vec![9]));
+
+        // The marker now belongs to the new WAL
+        assert_eq!(recovery.dry_run().unwrap().entries_already_applied, 1);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_outcome_is_recorded() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_dir = temp_dir.path().join("db");
+        std::fs::create_dir(&db_dir).unwrap();
+        let wal_dir = temp_dir.path().join("wal");
+
+        let db = Arc::new(SledDB::new(&db_dir).unwrap());
+        let manager = WALStateManager::new(&wal_dir, db.clone()).unwrap();
+        assert_eq!(manager.last_recovery().unwrap(), None);
+
+        let wal = manager.wal();
+        wal.checkpoint(3, [0u8; 32]).unwrap();
+        wal.write(WALEntry::CreateAccount { address: [1u8; 20], data: vec![1] }).unwrap();
+        wal.write(WALEntry::TransactionBegin { id: 1 }).unwrap();
+        wal.write(WALEntry::WriteStorage { address: [1u8; 20], key: vec![1], value: vec![2] }).unwrap();
+        wal.write(WALEntry::UpdateAccount { address: [1u8; 20], data: vec![3] }).unwrap();
+        wal.write(WALEntry::TransactionCommit { id: 1 }).unwrap();
+        wal.write(WALEntry::TransactionBegin { id: 2 }).unwrap();
+        wal.write(WALEntry::DeleteAccount { address: [1u8; 20] }).unwrap();
+        wal.sync().unwrap();
+
+        let status = manager.recover().await.unwrap();
+        let outcome = manager.last_recovery().unwrap().unwrap();
+        assert_eq!(outcome.status, status);
+        assert_eq!(outcome.status, RecoveryStatus::Recovered { entries_applied: 3, checkpoint_block: Some(3) });
+        assert_eq!(outcome.entries_replayed, 3);
+        assert_eq!(outcome.corruption_detected, 0);
+        assert!(outcome.finished_at > 0);
+
+        // A torn write at the end of the log fails the next run
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(wal_dir.join("wal-0.log"))
+            .unwrap();
+        std::io::Write::write_all(&mut file, &[16, 0, 0, 0, 1, 2]).unwrap();
+
+        assert!(manager.recover().await.is_err());
+        let outcome = RecoveryOutcome::load(&db).unwrap().unwrap();
+        assert!(matches!(outcome.status, RecoveryStatus::Failed { .. }));
+        assert_eq!(outcome.entries_replayed, 0);
+        assert_eq!(outcome.corruption_detected, 1);
+    }
 }
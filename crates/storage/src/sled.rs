@@ -1,112 +1,362 @@
-use anyhow::{Context, Result};
 use async_trait::async_trait;
+use lazy_static::lazy_static;
 use norn_common::traits::DBInterface;
+use prometheus::{Gauge, IntCounterVec, Opts};
+use serde::Deserialize;
+use sled::transaction::{ConflictableTransactionResult, TransactionError};
 use sled::Tree;
 use std::path::Path;
+#[cfg(any(test, feature = "fault-injection"))]
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::error::{Result, StorageError};
+use crate::snapshot::{DbSnapshot, SnapshotRegistry};
+
+// Metrics (registered by the node's metrics collector)
+//
+// sled does not expose its page cache counters, so reads are classified by
+// whether the key was found.
+lazy_static! {
+    pub static ref DB_READS_TOTAL: IntCounterVec = IntCounterVec::new(
+        Opts::new("norn_db_reads_total", "Total number of database key lookups"),
+        &["result"]
+    ).unwrap();
+
+    pub static ref DB_CACHE_CAPACITY_BYTES: Gauge = Gauge::new(
+        "norn_db_cache_capacity_bytes",
+        "Configured sled page cache capacity in bytes"
+    ).unwrap();
+}
+
+/// Record a key lookup in the read metrics
+fn record_read(found: bool) {
+    let result = if found { "hit" } else { "miss" };
+    DB_READS_TOTAL.with_label_values(&[result]).inc();
+}
+
+/// Sled database configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct SledConfig {
+    /// Maximum size of sled's page cache in bytes
+    #[serde(default = "default_cache_capacity_bytes")]
+    pub cache_capacity_bytes: u64,
+    /// Operations taking longer than this many milliseconds are logged at
+    /// WARN; unset disables the check
+    #[serde(default)]
+    pub slow_op_warn_ms: Option<u64>,
+}
+
+impl Default for SledConfig {
+    fn default() -> Self {
+        Self {
+            cache_capacity_bytes: default_cache_capacity_bytes(),
+            slow_op_warn_ms: None,
+        }
+    }
+}
+
+fn default_cache_capacity_bytes() -> u64 { 1024 * 1024 * 1024 }
 
 pub struct SledDB {
     db: Arc<Tree>,
+    /// Database handle owning the tree, used for whole-database operations
+    root: sled::Db,
+    /// Live read snapshots
+    snapshots: Arc<SnapshotRegistry>,
+    commit_fault: CommitFault,
+    slow_ops: SlowOpLog,
+}
+
+/// Crash point injected into transactions by `SledDB::inject_commit_fault`
+///
+/// Only exists in tests and with the `fault-injection` feature; otherwise
+/// it is empty and checking it does nothing.
+#[derive(Clone)]
+struct CommitFault {
+    /// Writes after which transactions panic, `usize::MAX` when disabled
+    #[cfg(any(test, feature = "fault-injection"))]
+    after: Arc<AtomicUsize>,
+}
+
+impl Default for CommitFault {
+    fn default() -> Self {
+        Self {
+            #[cfg(any(test, feature = "fault-injection"))]
+            after: Arc::new(AtomicUsize::new(usize::MAX)),
+        }
+    }
+}
+
+impl CommitFault {
+    /// Panic if the fault is set to fire once `staged` writes are staged
+    #[cfg(any(test, feature = "fault-injection"))]
+    fn check(&self, staged: usize) {
+        if staged == self.after.load(Ordering::SeqCst) {
+            panic!("injected fault after {} transaction writes", staged);
+        }
+    }
+
+    #[cfg(not(any(test, feature = "fault-injection")))]
+    #[inline(always)]
+    fn check(&self, _staged: usize) {}
+}
+
+/// Logs operations slower than a threshold
+#[derive(Debug, Clone, Copy, Default)]
+struct SlowOpLog(Option<Duration>);
+
+impl SlowOpLog {
+    /// Number of leading key bytes included in the log
+    const KEY_PREFIX_LEN: usize = 8;
+
+    /// Run `f`, the operation `op` on `key`, and warn if it was slow
+    fn time<T>(self, op: &'static str, key: &[u8], f: impl FnOnce() -> T) -> T {
+        let Some(threshold) = self.0 else {
+            return f();
+        };
+
+        let started = Instant::now();
+        let result = f();
+        let elapsed = started.elapsed();
+        if elapsed > threshold {
+            let key_prefix = hex::encode(&key[..key.len().min(Self::KEY_PREFIX_LEN)]);
+            warn!("Slow database {} on key 0x{}: {:?}", op, key_prefix, elapsed);
+        }
+        result
+    }
+}
+
+/// First key of a batch, identifying it in slow operation logs
+fn first_key(keys: &[Vec<u8>]) -> &[u8] {
+    keys.first().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// Result of a `SledDB::compact` run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactionStats {
+    /// Size on disk before compaction, in bytes
+    pub size_before: u64,
+    /// Size on disk after compaction, in bytes
+    pub size_after: u64,
+}
+
+impl CompactionStats {
+    /// Bytes reclaimed by the compaction
+    pub fn bytes_reclaimed(&self) -> u64 {
+        self.size_before.saturating_sub(self.size_after)
+    }
 }
 
 impl SledDB {
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let db = sled::open(path).context("Failed to open Sled database")?;
+        Self::with_config(path, &SledConfig::default())
+    }
+
+    /// Open a database with custom sled settings
+    pub fn with_config<P: AsRef<Path>>(path: P, config: &SledConfig) -> Result<Self> {
+        let db = sled::Config::new()
+            .path(path)
+            .cache_capacity(config.cache_capacity_bytes)
+            .open()?;
+        DB_CACHE_CAPACITY_BYTES.set(config.cache_capacity_bytes as f64);
 
         // Use the default tree for now, could support multiple trees later
-        let tree = db.open_tree("default").context("Failed to open default tree")?;
+        let tree = db.open_tree("default")?;
 
         Ok(Self {
             db: Arc::new(tree),
+            root: db,
+            snapshots: Arc::default(),
+            commit_fault: CommitFault::default(),
+            slow_ops: SlowOpLog(config.slow_op_warn_ms.map(Duration::from_millis)),
         })
     }
 
     /// Create a new SledDB instance from an existing sled::Db
     pub fn from_db(db: sled::Db) -> Result<Self> {
-        let tree = db.open_tree("default").context("Failed to open default tree")?;
+        let tree = db.open_tree("default")?;
         Ok(Self {
             db: Arc::new(tree),
+            root: db,
+            snapshots: Arc::default(),
+            commit_fault: CommitFault::default(),
+            slow_ops: SlowOpLog::default(),
         })
     }
+
+    /// Take a consistent point-in-time read view of the database
+    ///
+    /// Writes made through this `SledDB` after the snapshot is taken are not
+    /// visible through it, so multi-key reads never see a half-applied update.
+    pub fn snapshot(&self) -> DbSnapshot {
+        self.snapshots.snapshot(self.db.clone())
+    }
 }
 
 #[async_trait]
 impl DBInterface for SledDB {
-    async fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+    async fn get(&self, key: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
         let db = self.db.clone();
+        let slow_ops = self.slow_ops;
         let key = key.to_vec();
 
         // Sled operations are generally fast, but we'll use spawn_blocking for consistency
         tokio::task::spawn_blocking(move || {
-            match db.get(&key) {
-                Ok(Some(value)) => Ok(Some(value.to_vec())),
-                Ok(None) => Ok(None),
-                Err(e) => Err(anyhow::anyhow!("Failed to get from SledDB: {}", e)),
+            match slow_ops.time("get", &key, || db.get(&key)) {
+                Ok(Some(value)) => {
+                    record_read(true);
+                    Ok(Some(value.to_vec()))
+                }
+                Ok(None) => {
+                    record_read(false);
+                    Ok(None)
+                }
+                Err(e) => Err(StorageError::from(e).into()),
             }
         }).await?
     }
 
-    async fn insert(&self, key: &[u8], value: &[u8]) -> Result<()> {
+    async fn insert(&self, key: &[u8], value: &[u8]) -> anyhow::Result<()> {
         let db = self.db.clone();
+        let snapshots = self.snapshots.clone();
+        let slow_ops = self.slow_ops;
         let key = key.to_vec();
         let value = value.to_vec();
 
         tokio::task::spawn_blocking(move || {
-            db.insert(key.as_slice(), value.as_slice())
-                .map(|_| ())
-                .map_err(|e| anyhow::anyhow!("Failed to insert into SledDB: {}", e))
+            slow_ops.time("insert", &key, || {
+                snapshots.write(&db, &[key.as_slice()], || {
+                    db.insert(key.as_slice(), value.as_slice())
+                        .map(|_| ())
+                        .map_err(|e| anyhow::Error::from(StorageError::from(e)))
+                })
+            })
         }).await?
     }
 
-    async fn remove(&self, key: &[u8]) -> Result<()> {
+    async fn remove(&self, key: &[u8]) -> anyhow::Result<()> {
         let db = self.db.clone();
+        let snapshots = self.snapshots.clone();
+        let slow_ops = self.slow_ops;
         let key = key.to_vec();
 
         tokio::task::spawn_blocking(move || {
-            db.remove(key.as_slice())
-                .map(|_| ())
-                .map_err(|e| anyhow::anyhow!("Failed to remove from SledDB: {}", e))
+            slow_ops.time("remove", &key, || {
+                snapshots.write(&db, &[key.as_slice()], || {
+                    db.remove(key.as_slice())
+                        .map(|_| ())
+                        .map_err(|e| anyhow::Error::from(StorageError::from(e)))
+                })
+            })
         }).await?
     }
 
-    async fn batch_insert(&self, keys: &[Vec<u8>], values: &[Vec<u8>]) -> Result<()> {
+    async fn batch_insert(&self, keys: &[Vec<u8>], values: &[Vec<u8>]) -> anyhow::Result<()> {
         if keys.len() != values.len() {
             anyhow::bail!("Batch insert failed: Key/Value length mismatch");
         }
 
         let db = self.db.clone();
+        let snapshots = self.snapshots.clone();
+        let slow_ops = self.slow_ops;
         let keys = keys.to_vec();
         let values = values.to_vec();
 
         tokio::task::spawn_blocking(move || {
-            // Simple batch insert without transaction for simplicity
-            for (key, value) in keys.iter().zip(values.iter()) {
-                db.insert(key.as_slice(), value.as_slice())
-                    .map_err(|e| anyhow::anyhow!("Failed to insert into SledDB: {}", e))?;
-            }
-            Ok(())
+            let key_refs: Vec<&[u8]> = keys.iter().map(Vec::as_slice).collect();
+            slow_ops.time("batch insert", first_key(&keys), || {
+                snapshots.write(&db, &key_refs, || {
+                    // Simple batch insert without transaction for simplicity
+                    for (key, value) in keys.iter().zip(values.iter()) {
+                        db.insert(key.as_slice(), value.as_slice())
+                            .map_err(|e| anyhow::Error::from(StorageError::from(e)))?;
+                    }
+                    Ok(())
+                })
+            })
         }).await?
     }
 
-    async fn batch_delete(&self, keys: &[Vec<u8>]) -> Result<()> {
+    async fn batch_write(&self, keys: &[Vec<u8>], values: &[Vec<u8>], deletes: &[Vec<u8>]) -> anyhow::Result<()> {
         let db = self.db.clone();
+        let snapshots = self.snapshots.clone();
+        let commit_fault = self.commit_fault.clone();
+        let slow_ops = self.slow_ops;
         let keys = keys.to_vec();
+        let values = values.to_vec();
+        let deletes = deletes.to_vec();
 
         tokio::task::spawn_blocking(move || {
-            // Simple batch delete without transaction for simplicity
-            for key in keys.iter() {
-                db.remove(key.as_slice())
-                    .map_err(|e| anyhow::anyhow!("Failed to remove from SledDB: {}", e))?;
-            }
-            Ok(())
+            slow_ops.time("batch write", first_key(&keys), || {
+                apply_transaction(&db, &snapshots, &commit_fault, &keys, &values, &deletes)
+            })
+            .map_err(anyhow::Error::from)
+        }).await?
+    }
+
+    async fn batch_delete(&self, keys: &[Vec<u8>]) -> anyhow::Result<()> {
+        let db = self.db.clone();
+        let snapshots = self.snapshots.clone();
+        let slow_ops = self.slow_ops;
+        let keys = keys.to_vec();
+
+        tokio::task::spawn_blocking(move || {
+            let key_refs: Vec<&[u8]> = keys.iter().map(Vec::as_slice).collect();
+            slow_ops.time("batch delete", first_key(&keys), || {
+                snapshots.write(&db, &key_refs, || {
+                    // Simple batch delete without transaction for simplicity
+                    for key in keys.iter() {
+                        db.remove(key.as_slice())
+                            .map_err(|e| anyhow::Error::from(StorageError::from(e)))?;
+                    }
+                    Ok(())
+                })
+            })
         }).await?
     }
 }
 
+/// Stage all writes in a sled transaction, which applies them atomically
+fn apply_transaction(
+    tree: &Tree,
+    snapshots: &SnapshotRegistry,
+    commit_fault: &CommitFault,
+    keys: &[Vec<u8>],
+    values: &[Vec<u8>],
+    deletes: &[Vec<u8>],
+) -> Result<()> {
+    if keys.len() != values.len() {
+        return Err(StorageError::io("Transaction key/value length mismatch"));
+    }
+
+    let touched: Vec<&[u8]> = keys.iter().chain(deletes).map(Vec::as_slice).collect();
+    snapshots.write(tree, &touched, || {
+        tree.transaction(|tx| -> ConflictableTransactionResult<(), ()> {
+            for (staged, (key, value)) in keys.iter().zip(values).enumerate() {
+                commit_fault.check(staged);
+                tx.insert(key.as_slice(), value.as_slice())?;
+            }
+            for key in deletes {
+                tx.remove(key.as_slice())?;
+            }
+            Ok(())
+        })
+        .map_err(|e| match e {
+            TransactionError::Storage(e) => StorageError::from(e),
+            TransactionError::Abort(()) => StorageError::TransactionConflict("transaction aborted".to_string()),
+        })
+    })
+}
+
 // Additional utility methods specific to Sled
 impl SledDB {
-    /// Get the underlying sled::Db for advanced operations
-    pub fn underlying_db(&self) -> &sled::Tree {
+    /// Get the underlying sled::Tree for advanced operations
+    ///
+    /// Writes made directly on the tree are visible to existing snapshots.
+    pub fn underlying_tree(&self) -> &sled::Tree {
         &self.db
     }
 
@@ -116,30 +366,79 @@ impl SledDB {
         let key = key.to_vec();
 
         tokio::task::spawn_blocking(move || {
-            db.contains_key(&key)
-                .map_err(|e| anyhow::anyhow!("Failed to check key existence in SledDB: {}", e))
-        }).await?
+            db.contains_key(&key).map_err(StorageError::from)
+        })
+        .await
+        .map_err(|e| StorageError::io(format!("SledDB lookup task failed: {}", e)))?
     }
 
     /// Synchronous insert (for compatibility with persistent state module)
     pub fn insert_sync(&self, key: &[u8], value: &[u8]) -> Result<()> {
-        self.db.insert(key, value)
-            .map(|_| ())
-            .map_err(|e| anyhow::anyhow!("Failed to insert into SledDB: {}", e))
+        self.slow_ops.time("insert", key, || {
+            self.snapshots.write(&self.db, &[key], || {
+                self.db.insert(key, value)?;
+                Ok(())
+            })
+        })
     }
 
     /// Synchronous get (for compatibility with persistent state module)
     pub fn get_sync(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
-        self.db.get(key)
-            .map(|v| v.map(|ivec| ivec.to_vec()))
-            .map_err(|e| anyhow::anyhow!("Failed to get from SledDB: {}", e))
+        let value = self.slow_ops.time("get", key, || self.db.get(key))?.map(|ivec| ivec.to_vec());
+        record_read(value.is_some());
+        Ok(value)
     }
 
     /// Synchronous remove (for compatibility with persistent state module)
     pub fn remove_sync(&self, key: &[u8]) -> Result<()> {
-        self.db.remove(key)
-            .map(|_| ())
-            .map_err(|e| anyhow::anyhow!("Failed to remove from SledDB: {}", e))
+        self.slow_ops.time("remove", key, || {
+            self.snapshots.write(&self.db, &[key], || {
+                self.db.remove(key)?;
+                Ok(())
+            })
+        })
+    }
+
+    /// Insert `keys`/`values` and remove `deletes` in one sled transaction
+    ///
+    /// Either every write is applied or none is, even if the process dies
+    /// part-way through.
+    pub fn transaction(&self, keys: &[Vec<u8>], values: &[Vec<u8>], deletes: &[Vec<u8>]) -> Result<()> {
+        self.slow_ops.time("transaction", first_key(keys), || {
+            apply_transaction(&self.db, &self.snapshots, &self.commit_fault, keys, values, deletes)
+        })
+    }
+
+    /// Make transactions panic after staging `writes` writes, to check that
+    /// a crash mid-commit leaves nothing behind; `None` disables the fault
+    #[cfg(any(test, feature = "fault-injection"))]
+    pub fn inject_commit_fault(&self, writes: Option<usize>) {
+        self.commit_fault.after.store(writes.unwrap_or(usize::MAX), Ordering::SeqCst);
+    }
+
+    /// Flush pending writes to disk
+    pub fn flush(&self) -> Result<()> {
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// Size of the database on disk, in bytes
+    pub fn size_on_disk(&self) -> Result<u64> {
+        Ok(self.root.size_on_disk()?)
+    }
+
+    /// Run database maintenance
+    ///
+    /// Flushes every tree so dirty pages are written out and segments holding
+    /// only stale data can be reclaimed by sled's segment cleaner.
+    pub fn compact(&self) -> Result<CompactionStats> {
+        let size_before = self.size_on_disk()?;
+
+        self.db.flush()?;
+        self.root.flush()?;
+
+        let size_after = self.size_on_disk()?;
+        Ok(CompactionStats { size_before, size_after })
     }
 
     /// Iterate over keys with a prefix
@@ -147,7 +446,7 @@ impl SledDB {
         self.db.scan_prefix(prefix)
             .map(|res| {
                 res.map(|(k, v)| (k.to_vec(), v.to_vec()))
-                    .map_err(|e| anyhow::anyhow!("DB iteration error: {}", e))
+                    .map_err(StorageError::from)
             })
     }
 }
@@ -215,4 +514,165 @@ mod tests {
         db.insert(b"test_key", b"test_value").await.unwrap();
         assert!(db.contains_key(b"test_key").await.unwrap());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_compact() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = SledDB::new(temp_dir.path()).unwrap();
+
+        for i in 0..2000u32 {
+            db.insert_sync(&i.to_be_bytes(), &[0u8; 256]).unwrap();
+        }
+        for i in 0..1900u32 {
+            db.remove_sync(&i.to_be_bytes()).unwrap();
+        }
+
+        let stats = db.compact().unwrap();
+        assert!(stats.size_before > 0);
+        assert_eq!(stats.bytes_reclaimed(), stats.size_before.saturating_sub(stats.size_after));
+
+        // Reads still work after compaction
+        assert_eq!(db.get_sync(&0u32.to_be_bytes()).unwrap(), None);
+        assert_eq!(db.get(&1999u32.to_be_bytes()).await.unwrap(), Some(vec![0u8; 256]));
+        assert_eq!(db.iter_prefix(b"").count(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_sees_old_values() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = SledDB::new(temp_dir.path()).unwrap();
+
+        db.insert_sync(b"account_a", b"1").unwrap();
+        db.insert_sync(b"account_b", b"2").unwrap();
+        db.insert_sync(b"storage_a", b"3").unwrap();
+
+        let snapshot = db.snapshot();
+
+        db.insert_sync(b"account_a", b"10").unwrap();
+        db.remove(b"account_b").await.unwrap();
+        db.batch_insert(&[b"account_c".to_vec(), b"storage_a".to_vec()], &[b"4".to_vec(), b"30".to_vec()])
+            .await
+            .unwrap();
+
+        // The snapshot still sees the state at the time it was taken
+        assert_eq!(snapshot.get(b"account_a").unwrap(), Some(b"1".to_vec()));
+        assert_eq!(snapshot.get(b"account_b").unwrap(), Some(b"2".to_vec()));
+        assert!(!snapshot.contains_key(b"account_c").unwrap());
+        assert_eq!(snapshot.get(b"storage_a").unwrap(), Some(b"3".to_vec()));
+        assert_eq!(
+            snapshot.iter_prefix(b"account_").unwrap(),
+            vec![(b"account_a".to_vec(), b"1".to_vec()), (b"account_b".to_vec(), b"2".to_vec())]
+        );
+
+        // The live database sees the new state
+        assert_eq!(db.get_sync(b"account_a").unwrap(), Some(b"10".to_vec()));
+        assert_eq!(db.get_sync(b"account_b").unwrap(), None);
+        assert_eq!(db.get_sync(b"account_c").unwrap(), Some(b"4".to_vec()));
+
+        // A later snapshot sees the new state
+        let later = db.snapshot();
+        assert_eq!(later.get(b"storage_a").unwrap(), Some(b"30".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_small_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = SledConfig { cache_capacity_bytes: 64 * 1024, ..SledConfig::default() };
+        let db = SledDB::with_config(temp_dir.path(), &config).unwrap();
+
+        // Far more data than fits in the cache
+        for i in 0..2000u32 {
+            db.insert_sync(&i.to_be_bytes(), &[i as u8; 512]).unwrap();
+        }
+        db.flush().unwrap();
+
+        let hits = DB_READS_TOTAL.with_label_values(&["hit"]).get();
+        let misses = DB_READS_TOTAL.with_label_values(&["miss"]).get();
+
+        for i in (0..2000u32).step_by(97) {
+            assert_eq!(db.get_sync(&i.to_be_bytes()).unwrap(), Some(vec![i as u8; 512]));
+        }
+        assert_eq!(db.get(&5000u32.to_be_bytes()).await.unwrap(), None);
+
+        assert!(DB_READS_TOTAL.with_label_values(&["hit"]).get() >= hits + 21);
+        assert!(DB_READS_TOTAL.with_label_values(&["miss"]).get() > misses);
+    }
+
+    #[test]
+    fn test_open_corrupted_db_reports_corruption() {
+        let temp_dir = TempDir::new().unwrap();
+        let db = SledDB::new(temp_dir.path()).unwrap();
+        db.insert_sync(b"key", b"value").unwrap();
+        db.flush().unwrap();
+        drop(db);
+
+        // Overwrite sled's config header with garbage
+        std::fs::write(temp_dir.path().join("conf"), b"garbage: text\nmore stuff here\n").unwrap();
+
+        match SledDB::new(temp_dir.path()) {
+            Err(StorageError::Corruption(_)) => {}
+            Err(e) => panic!("expected corruption error, got {:?}", e),
+            Ok(_) => panic!("expected corrupted database to fail to open"),
+        }
+    }
+
+    #[test]
+    fn test_transaction_is_all_or_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let keys: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i]).collect();
+        let values: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i + 10]).collect();
+
+        let db = SledDB::new(temp_dir.path()).unwrap();
+        db.insert_sync(b"old", b"value").unwrap();
+        db.inject_commit_fault(Some(2));
+        let crashed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            db.transaction(&keys, &values, &[b"old".to_vec()])
+        }));
+        assert!(crashed.is_err());
+
+        // Checked on the same handle: reopening races the lock of the old one
+        assert!(keys.iter().all(|key| db.get_sync(key).unwrap().is_none()));
+        assert_eq!(db.get_sync(b"old").unwrap(), Some(b"value".to_vec()));
+
+        db.inject_commit_fault(None);
+        db.transaction(&keys, &values, &[b"old".to_vec()]).unwrap();
+        assert_eq!(db.get_sync(&[3]).unwrap(), Some(vec![13]));
+        assert_eq!(db.get_sync(b"old").unwrap(), None);
+    }
+
+    #[test]
+    fn test_slow_operations_logged() {
+        #[derive(Clone, Default)]
+        struct Logs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+        impl std::io::Write for Logs {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = SledConfig { slow_op_warn_ms: Some(0), ..SledConfig::default() };
+        let db = SledDB::with_config(temp_dir.path(), &config).unwrap();
+
+        let logs = Logs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::WARN)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            db.insert_sync(b"blob:large", &vec![0xAB; 4 * 1024 * 1024]).unwrap();
+        });
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("WARN"));
+        assert!(logs.contains(&format!("Slow database insert on key 0x{}", hex::encode(b"blob:lar"))));
+    }
+}
@@ -0,0 +1,129 @@
+//! Point-in-time read views of SledDB
+//!
+//! sled has no native snapshots, so a snapshot keeps the values that were
+//! overwritten after it was taken. Every write made through `SledDB` first
+//! copies the previous value of the key into all live snapshots; snapshot
+//! reads prefer those saved values over the live tree.
+//!
+//! Writes that go straight to `SledDB::underlying_tree` bypass this and are
+//! visible to snapshots.
+
+use crate::error::{Result, StorageError};
+use sled::Tree;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, Weak};
+
+/// Values overwritten since a snapshot was taken (`None` if the key did not exist)
+type UndoLog = Mutex<HashMap<Vec<u8>, Option<Vec<u8>>>>;
+
+/// Tracks live snapshots of a tree
+#[derive(Default)]
+pub(crate) struct SnapshotRegistry {
+    /// Held shared by writers and exclusively while taking a snapshot, so a
+    /// snapshot never observes half of a batch
+    write_gate: RwLock<()>,
+
+    /// Undo logs of live snapshots, in creation order
+    live: Mutex<Vec<Weak<UndoLog>>>,
+}
+
+impl SnapshotRegistry {
+    /// Take a snapshot of the tree
+    pub(crate) fn snapshot(&self, tree: Arc<Tree>) -> DbSnapshot {
+        let _gate = self.write_gate.write().unwrap_or_else(PoisonError::into_inner);
+
+        let undo = Arc::new(UndoLog::default());
+        let mut live = self.live.lock().unwrap_or_else(PoisonError::into_inner);
+        live.retain(|log| log.strong_count() > 0);
+        live.push(Arc::downgrade(&undo));
+
+        DbSnapshot { tree, undo }
+    }
+
+    /// Apply a write to `keys`, preserving their previous values for live snapshots
+    pub(crate) fn write<T, E: From<StorageError>>(
+        &self,
+        tree: &Tree,
+        keys: &[&[u8]],
+        write: impl FnOnce() -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        let _gate = self.write_gate.read().unwrap_or_else(PoisonError::into_inner);
+
+        let undos: Vec<Arc<UndoLog>> = {
+            let mut live = self.live.lock().unwrap_or_else(PoisonError::into_inner);
+            live.retain(|log| log.strong_count() > 0);
+            live.iter().filter_map(Weak::upgrade).collect()
+        };
+        if undos.is_empty() {
+            return write();
+        }
+
+        // Lock in creation order so concurrent writers can't deadlock; readers
+        // of these snapshots wait until the write is done
+        let mut guards: Vec<MutexGuard<'_, _>> = undos
+            .iter()
+            .map(|log| log.lock().unwrap_or_else(PoisonError::into_inner))
+            .collect();
+
+        for key in keys {
+            if guards.iter().all(|undo| undo.contains_key(*key)) {
+                continue;
+            }
+            let previous = tree.get(key).map_err(StorageError::from)?.map(|value| value.to_vec());
+            for undo in guards.iter_mut() {
+                undo.entry(key.to_vec()).or_insert_with(|| previous.clone());
+            }
+        }
+
+        write()
+    }
+}
+
+/// Consistent point-in-time read view of a `SledDB`
+///
+/// Obtained from `SledDB::snapshot`. Writes made after the snapshot was taken
+/// are not visible through it.
+pub struct DbSnapshot {
+    tree: Arc<Tree>,
+    undo: Arc<UndoLog>,
+}
+
+impl DbSnapshot {
+    fn undo(&self) -> MutexGuard<'_, HashMap<Vec<u8>, Option<Vec<u8>>>> {
+        self.undo.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the value of a key as of the snapshot
+    pub fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let undo = self.undo();
+        if let Some(saved) = undo.get(key) {
+            return Ok(saved.clone());
+        }
+        Ok(self.tree.get(key)?.map(|ivec| ivec.to_vec()))
+    }
+
+    /// Check if a key existed as of the snapshot
+    pub fn contains_key(&self, key: &[u8]) -> Result<bool> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    /// Collect all key/value pairs with a prefix as of the snapshot, in key order
+    pub fn iter_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let undo = self.undo();
+        let mut entries = BTreeMap::new();
+
+        for item in self.tree.scan_prefix(prefix) {
+            let (key, value) = item?;
+            if !undo.contains_key(key.as_ref()) {
+                entries.insert(key.to_vec(), value.to_vec());
+            }
+        }
+        let saved = undo
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .filter_map(|(key, value)| Some((key.clone(), value.clone()?)));
+        entries.extend(saved);
+
+        Ok(entries.into_iter().collect())
+    }
+}
@@ -4,7 +4,7 @@
 //! and enable crash recovery. All writes are first logged to the WAL
 //! before being applied to the main database.
 
-use norn_common::error::{NornError, Result};
+use crate::error::{io_context, lock_error, Result, StorageError};
 use serde::{Serialize, Deserialize};
 use std::fs::{File, OpenOptions};
 use std::io::{self, Read, Write, Seek, SeekFrom, BufWriter, BufReader};
@@ -69,6 +69,52 @@ pub enum WALEntry {
     },
 }
 
+impl WALEntry {
+    /// Short name of the entry type, used in recovery reports
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WALEntry::CreateAccount { .. } => "create_account",
+            WALEntry::UpdateAccount { .. } => "update_account",
+            WALEntry::DeleteAccount { .. } => "delete_account",
+            WALEntry::WriteStorage { .. } => "write_storage",
+            WALEntry::DeleteStorage { .. } => "delete_storage",
+            WALEntry::Checkpoint { .. } => "checkpoint",
+            WALEntry::TransactionBegin { .. } => "transaction_begin",
+            WALEntry::TransactionCommit { .. } => "transaction_commit",
+            WALEntry::TransactionRollback { .. } => "transaction_rollback",
+        }
+    }
+}
+
+/// WAL entry paired with its sequence number
+pub type SequencedEntry = (u64, WALEntry);
+
+/// Corruption detected while reading the WAL
+#[derive(Debug, Clone, PartialEq)]
+pub enum WALCorruption {
+    /// Entry was readable but its checksum did not match; the entry is skipped
+    ChecksumMismatch {
+        file: PathBuf,
+        sequence: u64,
+    },
+
+    /// The rest of the file could not be read
+    Unreadable {
+        file: PathBuf,
+        reason: String,
+    },
+}
+
+/// Result of scanning the WAL without failing on corruption
+#[derive(Debug, Clone, Default)]
+pub struct WALScan {
+    /// Entries that passed checksum verification, with their sequence numbers
+    pub entries: Vec<SequencedEntry>,
+
+    /// Corruption encountered along the way
+    pub corruption: Vec<WALCorruption>,
+}
+
 /// WAL entry with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct WALEntryWithMeta {
@@ -157,11 +203,17 @@ impl Default for WALConfig {
     }
 }
 
+/// File in the WAL directory holding the identity of the WAL
+const WAL_ID_FILE: &str = "wal.id";
+
 /// Write-Ahead Log
 pub struct WAL {
     /// WAL directory
     wal_dir: PathBuf,
 
+    /// Identity of the WAL, generated when its directory is first used
+    id: u64,
+
     /// Current WAL file
     current_file: Arc<Mutex<BufWriter<File>>>,
 
@@ -188,7 +240,9 @@ impl WAL {
 
         // Create WAL directory if it doesn't exist
         std::fs::create_dir_all(&wal_dir)
-            .map_err(|e| NornError::Internal(format!("Failed to create WAL directory: {}", e)))?;
+            .map_err(io_context("Failed to create WAL directory"))?;
+
+        let id = Self::load_or_create_id(&wal_dir)?;
 
         // Find existing WAL files
         let existing_files = Self::list_wal_files(&wal_dir)?;
@@ -200,7 +254,7 @@ impl WAL {
         } else {
             // Recover existing WAL
             let max_file = *existing_files.iter().max().unwrap();
-            let sequence = Self::recover_sequence(&wal_dir, max_file)?;
+            let sequence = Self::recover_sequence(&wal_dir, &existing_files)?;
             info!("Recovering WAL at {:?}, file={}, sequence={}", wal_dir, max_file, sequence);
             (max_file, sequence)
         };
@@ -210,10 +264,11 @@ impl WAL {
             .create(true)
             .append(true)
             .open(&current_path)
-            .map_err(|e| NornError::Internal(format!("Failed to open WAL file: {}", e)))?;
+            .map_err(io_context("Failed to open WAL file"))?;
 
         let wal = Self {
             wal_dir,
+            id,
             current_file: Arc::new(Mutex::new(BufWriter::new(current_file))),
             current_path: Arc::new(Mutex::new(current_path)),
             file_number: Arc::new(Mutex::new(file_number)),
@@ -230,12 +285,20 @@ impl WAL {
         Ok(wal)
     }
 
+    /// Identity of the WAL
+    ///
+    /// Sequence numbers are only meaningful within one WAL: a recreated WAL
+    /// directory gets a new identity and starts its sequence over.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
     /// Write an entry to the WAL
     pub fn write(&self, entry: WALEntry) -> Result<u64> {
         // Get next sequence number
         let sequence = {
             let mut seq = self.sequence.lock()
-                .map_err(|e| NornError::Internal(format!("WAL lock error: {}", e)))?;
+                .map_err(lock_error)?;
             *seq += 1;
             let seq = *seq;
             seq
@@ -246,31 +309,30 @@ impl WAL {
 
         // Verify checksum before writing
         if !entry_with_meta.verify_checksum() {
-            return Err(NornError::Internal("WAL checksum verification failed".to_string()));
+            return Err(StorageError::Corruption("WAL checksum verification failed".to_string()));
         }
 
         // Serialize entry
-        let data = bincode::serialize(&entry_with_meta)
-            .map_err(|e| NornError::Internal(format!("Failed to serialize WAL entry: {}", e)))?;
+        let data = bincode::serialize(&entry_with_meta)?;
 
         // Write length prefix (4 bytes)
         let len = data.len() as u32;
 
         {
             let mut file = self.current_file.lock()
-                .map_err(|e| NornError::Internal(format!("WAL lock error: {}", e)))?;
+                .map_err(lock_error)?;
 
             file.write_all(&len.to_le_bytes())
-                .map_err(|e| NornError::Internal(format!("Failed to write WAL length: {}", e)))?;
+                .map_err(io_context("Failed to write WAL length"))?;
 
             // Write entry data
             file.write_all(&data)
-                .map_err(|e| NornError::Internal(format!("Failed to write WAL entry: {}", e)))?;
+                .map_err(io_context("Failed to write WAL entry"))?;
 
             // Flush if configured
             if self.config.sync_on_write {
                 file.flush()
-                    .map_err(|e| NornError::Internal(format!("Failed to flush WAL: {}", e)))?;
+                    .map_err(io_context("Failed to flush WAL"))?;
             }
         }
 
@@ -284,7 +346,7 @@ impl WAL {
         // Update checkpoint counter
         {
             let mut counter = self.entries_since_checkpoint.lock()
-                .map_err(|e| NornError::Internal(format!("WAL lock error: {}", e)))?;
+                .map_err(lock_error)?;
             *counter += 1;
         }
 
@@ -293,10 +355,19 @@ impl WAL {
 
     /// Read all entries from WAL (for recovery)
     pub fn read_all(&self) -> Result<Vec<WALEntry>> {
+        Ok(self.read_all_sequenced()?
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect())
+    }
+
+    /// Read all entries from WAL together with their sequence numbers
+    pub fn read_all_sequenced(&self) -> Result<Vec<SequencedEntry>> {
         let mut entries = Vec::new();
 
         // Read from all WAL files in order
-        let wal_files = Self::list_wal_files(&self.wal_dir)?;
+        let mut wal_files = Self::list_wal_files(&self.wal_dir)?;
+        wal_files.sort();
 
         for &file_num in &wal_files {
             let path = self.wal_dir.join(format!("wal-{}.log", file_num));
@@ -307,6 +378,23 @@ impl WAL {
         Ok(entries)
     }
 
+    /// Scan all WAL files, collecting corruption instead of failing on it
+    pub fn scan(&self) -> Result<WALScan> {
+        let mut scan = WALScan::default();
+
+        let mut wal_files = Self::list_wal_files(&self.wal_dir)?;
+        wal_files.sort();
+
+        for file_num in wal_files {
+            let path = self.wal_dir.join(format!("wal-{}.log", file_num));
+            let (entries, corruption) = Self::scan_file(&path)?;
+            scan.entries.extend(entries);
+            scan.corruption.extend(corruption);
+        }
+
+        Ok(scan)
+    }
+
     /// Create a checkpoint marker
     pub fn checkpoint(&self, block_number: u64, block_hash: [u8; 32]) -> Result<()> {
         info!("Creating WAL checkpoint at block {}", block_number);
@@ -321,23 +409,48 @@ impl WAL {
         // Reset checkpoint counter
         {
             let mut counter = self.entries_since_checkpoint.lock()
-                .map_err(|e| NornError::Internal(format!("WAL lock error: {}", e)))?;
+                .map_err(lock_error)?;
             *counter = 0;
         }
 
         Ok(())
     }
 
+    /// Start a new WAL file with a checkpoint marker and remove all older files
+    ///
+    /// Every entry written before the checkpoint must already be durable in
+    /// the database, since those entries are no longer available for recovery.
+    pub fn checkpoint_and_truncate(&self, block_number: u64, block_hash: [u8; 32]) -> Result<()> {
+        self.rotate()?;
+        self.checkpoint(block_number, block_hash)?;
+        self.sync()?;
+
+        let current = *self.file_number.lock()
+            .map_err(lock_error)?;
+
+        for file_num in Self::list_wal_files(&self.wal_dir)? {
+            if file_num >= current {
+                continue;
+            }
+            let path = self.wal_dir.join(format!("wal-{}.log", file_num));
+            std::fs::remove_file(&path)
+                .map_err(io_context(format!("Failed to remove WAL file {:?}", path)))?;
+            debug!("Removed WAL file {:?} before checkpoint", path);
+        }
+
+        Ok(())
+    }
+
     /// Sync the WAL to disk
     pub fn sync(&self) -> Result<()> {
         let mut file = self.current_file.lock()
-            .map_err(|e| NornError::Internal(format!("WAL lock error: {}", e)))?;
+            .map_err(lock_error)?;
 
         file.flush()
-            .map_err(|e| NornError::Internal(format!("Failed to sync WAL: {}", e)))?;
+            .map_err(io_context("Failed to sync WAL"))?;
 
         file.get_ref().sync_all()
-            .map_err(|e| NornError::Internal(format!("Failed to sync WAL file: {}", e)))?;
+            .map_err(io_context("Failed to sync WAL file"))?;
 
         Ok(())
     }
@@ -370,9 +483,9 @@ impl WAL {
     /// Check if file rotation is needed
     fn should_rotate(&self) -> Result<bool> {
         let current_path = self.current_path.lock()
-            .map_err(|e| NornError::Internal(format!("WAL lock error: {}", e)))?;
+            .map_err(lock_error)?;
         let metadata = std::fs::metadata(&*current_path)
-            .map_err(|e| NornError::Internal(format!("Failed to get WAL file metadata: {}", e)))?;
+            .map_err(io_context("Failed to get WAL file metadata"))?;
 
         Ok(metadata.len() >= self.config.max_file_size as u64)
     }
@@ -387,31 +500,31 @@ impl WAL {
         // Increment file number
         {
             let mut file_number = self.file_number.lock()
-                .map_err(|e| NornError::Internal(format!("WAL lock error: {}", e)))?;
+                .map_err(lock_error)?;
             *file_number += 1;
         }
 
         // Create new file
         let new_file_number = *self.file_number.lock()
-            .map_err(|e| NornError::Internal(format!("WAL lock error: {}", e)))?;
+            .map_err(lock_error)?;
         let new_path = self.wal_dir.join(format!("wal-{}.log", new_file_number));
 
         let new_file = OpenOptions::new()
             .create(true)
             .write(true)
             .open(&new_path)
-            .map_err(|e| NornError::Internal(format!("Failed to create new WAL file: {}", e)))?;
+            .map_err(io_context("Failed to create new WAL file"))?;
 
         // Replace current file and path
         {
             let mut file_guard = self.current_file.lock()
-                .map_err(|e| NornError::Internal(format!("WAL lock error: {}", e)))?;
+                .map_err(lock_error)?;
             *file_guard = BufWriter::new(new_file);
         }
 
         {
             let mut current_path = self.current_path.lock()
-                .map_err(|e| NornError::Internal(format!("WAL lock error: {}", e)))?;
+                .map_err(lock_error)?;
             *current_path = new_path;
         }
 
@@ -421,14 +534,41 @@ impl WAL {
         Ok(())
     }
 
+    /// Read the identity of the WAL in `wal_dir`, generating it for a new one
+    fn load_or_create_id(wal_dir: &Path) -> Result<u64> {
+        let path = wal_dir.join(WAL_ID_FILE);
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                let bytes: [u8; 8] = bytes.as_slice().try_into()
+                    .map_err(|_| StorageError::Corruption(format!("Invalid WAL identity in {:?}", path)))?;
+                Ok(u64::from_be_bytes(bytes))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let mut hasher = Sha256::new();
+                hasher.update(SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_le_bytes());
+                hasher.update(std::process::id().to_le_bytes());
+                hasher.update(wal_dir.as_os_str().as_encoded_bytes());
+                let id = u64::from_be_bytes(hasher.finalize()[..8].try_into().unwrap());
+
+                let mut file = File::create(&path)
+                    .map_err(io_context("Failed to create WAL identity file"))?;
+                file.write_all(&id.to_be_bytes())
+                    .and_then(|_| file.sync_all())
+                    .map_err(io_context("Failed to write WAL identity file"))?;
+                Ok(id)
+            }
+            Err(e) => Err(io_context("Failed to read WAL identity file")(e)),
+        }
+    }
+
     /// List all WAL files in directory
     fn list_wal_files(wal_dir: &Path) -> Result<Vec<u64>> {
         let mut files = Vec::new();
 
         for entry in std::fs::read_dir(wal_dir)
-            .map_err(|e| NornError::Internal(format!("Failed to read WAL directory: {}", e)))?
+            .map_err(io_context("Failed to read WAL directory"))?
         {
-            let entry = entry.map_err(|e| NornError::Internal(format!("Failed to read directory entry: {}", e)))?;
+            let entry = entry.map_err(io_context("Failed to read directory entry"))?;
             let path = entry.path();
 
             if path.extension().and_then(|s| s.to_str()) == Some("log") {
@@ -447,65 +587,106 @@ impl WAL {
         Ok(files)
     }
 
-    /// Recover sequence number from WAL file
-    fn recover_sequence(wal_dir: &Path, file_num: u64) -> Result<u64> {
-        let path = wal_dir.join(format!("wal-{}.log", file_num));
-        let entries = Self::read_file(&path)?;
-
-        // For now, estimate from entries count
-        // A better approach would be to track this in a separate metadata file
-        if entries.is_empty() {
-            Ok(0)
-        } else {
-            Ok(entries.len() as u64)
+    /// Recover the last sequence number written to the WAL
+    ///
+    /// Sequence numbers must keep increasing across restarts, since recovery
+    /// uses them to skip entries that were already applied.
+    fn recover_sequence(wal_dir: &Path, files: &[u64]) -> Result<u64> {
+        let mut sequence = 0;
+
+        for file_num in files {
+            let path = wal_dir.join(format!("wal-{}.log", file_num));
+            let (entries, _) = Self::scan_file(&path)?;
+            if let Some((last, _)) = entries.iter().max_by_key(|(seq, _)| *seq) {
+                sequence = sequence.max(*last);
+            }
         }
+
+        Ok(sequence)
     }
 
     /// Read entries from a single WAL file
-    fn read_file(path: &Path) -> Result<Vec<WALEntry>> {
+    fn read_file(path: &Path) -> Result<Vec<SequencedEntry>> {
+        let (entries, corruption) = Self::scan_file(path)?;
+
+        for issue in corruption {
+            match issue {
+                WALCorruption::ChecksumMismatch { sequence, .. } => {
+                    warn!("WAL entry checksum mismatch at sequence {}", sequence);
+                }
+                WALCorruption::Unreadable { reason, .. } => {
+                    return Err(StorageError::Corruption(reason));
+                }
+            }
+        }
+
+        debug!("Read {} WAL entries from {:?}", entries.len(), path);
+        Ok(entries)
+    }
+
+    /// Read entries from a single WAL file, reporting corruption separately
+    ///
+    /// Entries with a bad checksum are skipped; reading stops at the first
+    /// entry that cannot be framed or decoded.
+    fn scan_file(path: &Path) -> Result<(Vec<SequencedEntry>, Vec<WALCorruption>)> {
         let file = File::open(path)
-            .map_err(|e| NornError::Internal(format!("Failed to open WAL file {:?}: {}", path, e)))?;
+            .map_err(io_context(format!("Failed to open WAL file {:?}", path)))?;
 
         let mut reader = BufReader::new(file);
         let mut entries = Vec::new();
+        let mut corruption = Vec::new();
+        let unreadable = |reason: String| WALCorruption::Unreadable {
+            file: path.to_path_buf(),
+            reason,
+        };
 
         loop {
             // Read length prefix
             let mut len_bytes = [0u8; 4];
             if let Err(e) = reader.read_exact(&mut len_bytes) {
-                if e.kind() == io::ErrorKind::UnexpectedEof {
-                    break; // End of file
+                if e.kind() != io::ErrorKind::UnexpectedEof {
+                    corruption.push(unreadable(format!("Failed to read WAL entry length: {}", e)));
                 }
-                return Err(NornError::Internal(format!("Failed to read WAL entry length: {}", e)));
+                break; // End of file
             }
 
             let len = u32::from_le_bytes(len_bytes) as usize;
 
             // Sanity check
             if len > 10_000_000 {
-                return Err(NornError::Internal(format!("WAL entry too large: {} bytes", len)));
+                corruption.push(unreadable(format!("WAL entry too large: {} bytes", len)));
+                break;
             }
 
             // Read entry data
             let mut data = vec![0u8; len];
-            reader.read_exact(&mut data)
-                .map_err(|e| NornError::Internal(format!("Failed to read WAL entry data: {}", e)))?;
+            if let Err(e) = reader.read_exact(&mut data) {
+                corruption.push(unreadable(format!("Failed to read WAL entry data: {}", e)));
+                break;
+            }
 
             // Deserialize entry with metadata
-            let entry_with_meta: WALEntryWithMeta = bincode::deserialize(&data)
-                .map_err(|e| NornError::Internal(format!("Failed to deserialize WAL entry: {}", e)))?;
+            let entry_with_meta: WALEntryWithMeta = match bincode::deserialize(&data) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    corruption.push(unreadable(format!("Failed to deserialize WAL entry: {}", e)));
+                    break;
+                }
+            };
 
             // Verify checksum
             if !entry_with_meta.verify_checksum() {
-                warn!("WAL entry checksum mismatch at sequence {}", entry_with_meta.sequence);
+                corruption.push(WALCorruption::ChecksumMismatch {
+                    file: path.to_path_buf(),
+                    sequence: entry_with_meta.sequence,
+                });
                 continue;
             }
 
-            entries.push(entry_with_meta.entry);
+            entries.push((entry_with_meta.sequence, entry_with_meta.entry));
         }
 
-        debug!("Read {} WAL entries from {:?}", entries.len(), path);
-        Ok(entries)
+        Ok((entries, corruption))
     }
 }
 
@@ -1,6 +1,6 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::configure()
-        .build_server(false)
+        .build_server(true)
         .compile(
             &["../../../crates/rpc/proto/blockchain.proto"],
             &["../../../crates/rpc/proto"],
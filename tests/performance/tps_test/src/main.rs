@@ -2,17 +2,96 @@ mod transaction_generator;
 mod rpc_client;
 mod statistics;
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use anyhow::Result;
 use tracing::{info, warn, error};
 use tracing_subscriber;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 use tokio::time::Instant;
 
-use transaction_generator::TransactionGenerator;
-use rpc_client::BlockchainRpcClient;
+use norn_common::types::{Transaction, TransactionBody, TransactionType};
+use norn_crypto::transaction::TransactionSigner;
+use transaction_generator::{TransactionGenerator, Workload};
+use rpc_client::{BlockchainRpcClient, ReceiptOrTimeout};
 use statistics::TestStatistics;
 
+/// 突发模式下每个周期的时长
+const BURST_PERIOD: Duration = Duration::from_secs(10);
+
+/// 突发模式下每个周期中发送交易的时间占比
+const BURST_DUTY_CYCLE: f64 = 0.2;
+
+/// 注资转账的 gas 上限
+const FUNDING_GAS: i64 = 21_000;
+
+/// 等待交易打包时同时轮询的最大交易数
+const MAX_CONCURRENT_WAITS: usize = 64;
+
+/// 速率为 0 时的轮询间隔
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 负载模式
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum LoadProfile {
+    /// 以固定速率持续发送
+    Sustained,
+    /// 每个周期开头以高速率集中发送，其余时间空闲，平均速率等于目标速率
+    Burst,
+    /// 速率从 0 线性增加到目标速率，用于寻找饱和点
+    Ramp,
+}
+
+impl LoadProfile {
+    /// 测试开始后 `elapsed` 时刻的瞬时目标速率（TPS）
+    fn target_rate(self, rate: u64, elapsed: Duration, duration: Duration) -> f64 {
+        let rate = rate as f64;
+        match self {
+            LoadProfile::Sustained => rate,
+            LoadProfile::Burst => {
+                let phase = (elapsed.as_secs_f64() % BURST_PERIOD.as_secs_f64()) / BURST_PERIOD.as_secs_f64();
+                if phase < BURST_DUTY_CYCLE {
+                    rate / BURST_DUTY_CYCLE
+                } else {
+                    0.0
+                }
+            }
+            LoadProfile::Ramp => {
+                let progress = if duration.is_zero() {
+                    1.0
+                } else {
+                    (elapsed.as_secs_f64() / duration.as_secs_f64()).min(1.0)
+                };
+                rate * progress
+            }
+        }
+    }
+}
+
+/// 按目标速率发送一批交易所需的间隔，速率为 0 时返回 None
+fn batch_interval(rate: f64, batch_size: usize) -> Option<Duration> {
+    if rate <= 0.0 {
+        return None;
+    }
+    Some(Duration::from_secs_f64(batch_size as f64 / rate))
+}
+
+/// 距离下一批次还需睡眠的时长，无需再等待时返回 None
+///
+/// `interval` 为当前速率下的批次间隔（速率为 0 时为 None），`since_batch` 为
+/// 距上一批次开始的时间。单次睡眠不超过 [`IDLE_POLL_INTERVAL`]，以便速率变化后
+/// 及时重新计算间隔，也不超过测试剩余时间 `remaining`。
+fn pacing_sleep(interval: Option<Duration>, since_batch: Duration, remaining: Duration) -> Option<Duration> {
+    let wait = match interval {
+        Some(interval) => interval.saturating_sub(since_batch),
+        None => IDLE_POLL_INTERVAL,
+    };
+    let sleep = wait.min(IDLE_POLL_INTERVAL).min(remaining);
+    (!sleep.is_zero()).then_some(sleep)
+}
+
 /// TPS 测试配置
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -25,10 +104,26 @@ struct Args {
     #[arg(short, long, default_value_t = 60)]
     duration: u64,
 
-    /// 目标 TPS（每秒交易数）
+    /// 目标 TPS（每秒交易数），ramp 模式下为最终速率
     #[arg(short = 'r', long, default_value_t = 100)]
     rate: u64,
 
+    /// 交易负载类型
+    #[arg(short = 'w', long, value_enum, default_value_t = Workload::Native)]
+    workload: Workload,
+
+    /// ERC-20 测试合约地址（十六进制），erc20 和 mixed 负载必填
+    #[arg(long)]
+    erc20_contract: Option<String>,
+
+    /// 随机种子，指定后每次运行生成相同的交易序列
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// 负载模式
+    #[arg(short = 'p', long, value_enum, default_value_t = LoadProfile::Sustained)]
+    profile: LoadProfile,
+
     /// 并发连接数
     #[arg(short = 'c', long, default_value_t = 10)]
     concurrent: usize,
@@ -37,9 +132,31 @@ struct Args {
     #[arg(short = 'b', long, default_value_t = 10)]
     batch_size: usize,
 
+    /// 每批交易通过一次 SubmitTransactions 请求提交
+    #[arg(long, default_value_t = false)]
+    batch_submit: bool,
+
     /// 监控间隔（秒）
     #[arg(short = 'i', long, default_value_t = 5)]
     monitor_interval: u64,
+
+    /// 发送结束后等待交易打包的超时（秒），超时未打包的交易提价重发一次
+    #[arg(long, default_value_t = 30)]
+    inclusion_timeout: u64,
+
+    /// 注资账户的 secp256k1 私钥（十六进制），用于在测试前为 ERC-20 发送者转入原生代币
+    ///
+    /// 该账户需要在创世配置中有余额。未指定时不注资，ERC-20 交易会因余额不足而失败。
+    #[arg(long)]
+    funder_key: Option<String>,
+
+    /// 注资账户当前的 nonce
+    #[arg(long, default_value_t = 0)]
+    funder_nonce: u64,
+
+    /// 为每个 ERC-20 发送者转入的金额（十进制，最小单位）
+    #[arg(long, default_value = "1000000000000000000")]
+    fund_amount: String,
 }
 
 #[tokio::main]
@@ -56,9 +173,14 @@ async fn main() -> Result<()> {
     info!("RPC 地址: {}", args.rpc_address);
     info!("测试持续时间: {} 秒", args.duration);
     info!("目标 TPS: {}", args.rate);
+    info!("负载模式: {:?}", args.profile);
+    info!("交易负载: {:?}", args.workload);
+    info!("随机种子: {:?}", args.seed);
     info!("并发连接数: {}", args.concurrent);
     info!("批次大小: {}", args.batch_size);
+    info!("批量提交: {}", args.batch_submit);
     info!("监控间隔: {} 秒", args.monitor_interval);
+    info!("打包超时: {} 秒", args.inclusion_timeout);
     info!("=======================");
 
     // 连接到 RPC 服务器
@@ -71,65 +193,113 @@ async fn main() -> Result<()> {
     info!("📊 当前区块高度: {}", initial_block_number);
 
     // 初始化交易生成器
-    let mut generator = TransactionGenerator::new();
+    let erc20_contract = match (args.workload, &args.erc20_contract) {
+        (Workload::Native, _) => norn_common::types::Address::default(),
+        (_, Some(address)) => parse_address(address)?,
+        (_, None) => anyhow::bail!("--erc20-contract is required for the {:?} workload", args.workload),
+    };
+    let mut generator = match args.seed {
+        Some(seed) => TransactionGenerator::with_seed_and_workload(seed, args.workload, erc20_contract),
+        None => TransactionGenerator::with_workload(args.workload, erc20_contract),
+    };
+
+    // 为 ERC-20 发送者注资
+    let senders = generator.erc20_senders();
+    if !senders.is_empty() {
+        match &args.funder_key {
+            Some(key) => {
+                let funding = Funding { key, nonce: args.funder_nonce, amount: &args.fund_amount };
+                fund_senders(&mut client, funding, &senders, Duration::from_secs(args.inclusion_timeout)).await?;
+            }
+            None => warn!("⚠️  未指定 --funder-key，{} 个 ERC-20 发送者没有余额", senders.len()),
+        }
+    }
 
     // 初始化统计跟踪器
     let mut stats = TestStatistics::new(args.rate, initial_block_number);
 
-    // 计算批次间隔时间
-    let batches_per_sec = (args.rate as f64 / args.batch_size as f64).max(0.1);
-    let batch_interval = Duration::from_millis((1000.0 / batches_per_sec) as u64);
-
-    info!("🎯 开始发送交易（目标 TPS: {}）", args.rate);
-    info!("⏱️  批次间隔: {:?}", batch_interval);
+    info!("🎯 开始发送交易（目标 TPS: {}，模式: {:?}）", args.rate, args.profile);
 
     let test_start = Instant::now();
     let test_duration = Duration::from_secs(args.duration);
+    let monitor_interval = Duration::from_secs(args.monitor_interval);
 
     // 交易发送循环
     let mut total_sent = 0u64;
-    let mut batch_count = 0u64;
+    let mut pending = Vec::new();
+    let mut last_report = Instant::now();
 
     while test_start.elapsed() < test_duration {
         let batch_start = Instant::now();
 
+        // 根据负载模式计算当前批次间隔
+        let rate = args.profile.target_rate(args.rate, test_start.elapsed(), test_duration);
+        if batch_interval(rate, args.batch_size).is_none() {
+            let remaining = test_duration.saturating_sub(test_start.elapsed());
+            tokio::time::sleep(IDLE_POLL_INTERVAL.min(remaining)).await;
+            continue;
+        }
+
         // 生成并发送一批交易
-        for _ in 0..args.batch_size {
-            let tx = generator.generate_random_transaction();
+        let batch: Vec<_> = (0..args.batch_size)
+            .map(|_| generator.generate_transaction())
+            .collect();
 
-            let send_time = chrono::Utc::now().timestamp_millis();
-            match client.send_transaction_with_data(&tx).await {
-                Ok(_tx_hash) => {
-                    stats.track_submission(send_time);
-                    total_sent += 1;
+        let send_time = chrono::Utc::now().timestamp_millis();
+        let results = if args.batch_submit {
+            client.send_transaction_batch(&batch).await
+        } else {
+            client.send_batch(&batch).await
+        };
 
-                    if total_sent % 1000 == 0 {
-                        info!("📦 已发送 {} 笔交易", total_sent);
+        match results {
+            Ok(results) => {
+                for (tx, result) in batch.iter().zip(results) {
+                    match result {
+                        Ok(_tx_hash) => {
+                            stats.record_submission(tx.body.hash, send_time);
+                            pending.push(tx.clone());
+                            total_sent += 1;
+
+                            if total_sent % 1000 == 0 {
+                                info!("📦 已发送 {} 笔交易", total_sent);
+                            }
+                        }
+                        Err(e) => {
+                            error!("❌ 发送交易失败: {} | 原因: {}", e, e.root_cause());
+                            stats.track_failed_submission();
+                        }
                     }
                 }
-                Err(e) => {
-                    error!("❌ 发送交易失败: {} | 原因: {}", e, e.root_cause());
+            }
+            Err(e) => {
+                error!("❌ 批量提交失败: {} | 原因: {}", e, e.root_cause());
+                for _ in 0..batch.len() {
                     stats.track_failed_submission();
                 }
             }
         }
 
-        batch_count += 1;
-
         // 定期监控进度
-        if batch_count % (args.monitor_interval * 1000 / batch_interval.as_millis() as u64) == 0 {
+        if last_report.elapsed() >= monitor_interval {
+            last_report = Instant::now();
             let elapsed = test_start.elapsed().as_secs_f64();
             let current_tps = total_sent as f64 / elapsed;
             info!("📈 进度报告:");
             info!("   已发送: {} 笔交易", total_sent);
             info!("   当前速率: {:.2} TPS", current_tps);
+            info!("   目标速率: {:.2} TPS", rate);
             info!("   已用时间: {:.1} 秒", elapsed);
         }
 
-        // 等待下一个批次
-        let elapsed = batch_start.elapsed();
-        if elapsed < batch_interval {
-            tokio::time::sleep(batch_interval - elapsed).await;
+        // 等待下一个批次；速率随时间变化，每次醒来后按当前速率重新计算间隔
+        loop {
+            let rate = args.profile.target_rate(args.rate, test_start.elapsed(), test_duration);
+            let remaining = test_duration.saturating_sub(test_start.elapsed());
+            match pacing_sleep(batch_interval(rate, args.batch_size), batch_start.elapsed(), remaining) {
+                Some(sleep) => tokio::time::sleep(sleep).await,
+                None => break,
+            }
         }
     }
 
@@ -139,9 +309,10 @@ async fn main() -> Result<()> {
     info!("   发送耗时: {:?}", send_duration);
     info!("   发送速率: {:.2} TPS", total_sent as f64 / send_duration.as_secs_f64());
 
-    // 等待一段时间让所有交易被打包
-    info!("⏳ 等待交易打包（30秒）...");
-    tokio::time::sleep(Duration::from_secs(30)).await;
+    // 等待所有交易被打包
+    let inclusion_timeout = Duration::from_secs(args.inclusion_timeout);
+    info!("⏳ 等待交易打包（{} 秒）...", args.inclusion_timeout);
+    await_inclusion(&mut client, &generator, &stats, pending, inclusion_timeout).await?;
 
     // 监控区块链以计算实际 TPS
     info!("🔍 开始监控区块链打包情况...");
@@ -160,6 +331,136 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// 解析十六进制地址（可带 0x 前缀）
+fn parse_address(s: &str) -> Result<norn_common::types::Address> {
+    let bytes = hex::decode(s.trim_start_matches("0x"))?;
+    if bytes.len() != 20 {
+        anyhow::bail!("Invalid address length: expected 20 bytes, got {}", bytes.len());
+    }
+    let mut address = norn_common::types::Address::default();
+    address.0.copy_from_slice(&bytes);
+    Ok(address)
+}
+
+/// 注资账户参数
+struct Funding<'a> {
+    /// secp256k1 私钥（十六进制，可带 0x 前缀）
+    key: &'a str,
+    /// 注资账户当前的 nonce
+    nonce: u64,
+    /// 每个账户的转入金额
+    amount: &'a str,
+}
+
+/// 从注资账户向每个 `senders` 转账，等待全部打包后返回
+async fn fund_senders(
+    client: &mut BlockchainRpcClient,
+    funding: Funding<'_>,
+    senders: &[norn_common::types::Address],
+    timeout: Duration,
+) -> Result<()> {
+    let key = hex::decode(funding.key.trim_start_matches("0x"))?;
+    let key = k256::ecdsa::SigningKey::from_slice(&key)
+        .map_err(|e| anyhow::anyhow!("Invalid funder key: {}", e))?;
+    let signer = TransactionSigner::with_signer(key, TransactionType::EVM);
+    info!("💰 从 0x{} 为 {} 个 ERC-20 发送者注资", hex::encode(signer.address().0), senders.len());
+
+    let now = chrono::Utc::now().timestamp();
+    let mut transfers = Vec::with_capacity(senders.len());
+    for (offset, sender) in senders.iter().enumerate() {
+        let body = TransactionBody {
+            address: signer.address(),
+            receiver: *sender,
+            gas: FUNDING_GAS,
+            nonce: (funding.nonce + offset as u64) as i64,
+            expire: now + timeout.as_secs() as i64 + 60,
+            timestamp: now,
+            tx_type: TransactionType::EVM,
+            value: Some(funding.amount.to_string()),
+            ..Default::default()
+        };
+        let tx = signer.sign_body(body);
+        client.send_transaction_with_data(&tx).await?;
+        transfers.push(tx);
+    }
+
+    let stuck = wait_all(client, &transfers, timeout).await?;
+    if !stuck.is_empty() {
+        anyhow::bail!("{} funding transfers were not included within {:?}", stuck.len(), timeout);
+    }
+    info!("✅ 注资完成");
+    Ok(())
+}
+
+/// 等待已提交的交易被打包，超时未打包的交易提价重发一次
+async fn await_inclusion(
+    client: &mut BlockchainRpcClient,
+    generator: &TransactionGenerator,
+    stats: &TestStatistics,
+    pending: Vec<Transaction>,
+    timeout: Duration,
+) -> Result<()> {
+    let stuck = wait_all(client, &pending, timeout).await?;
+    if stuck.is_empty() {
+        info!("✅ 全部 {} 笔交易已打包", pending.len());
+        return Ok(());
+    }
+
+    warn!("⚠️  {} 笔交易在 {:?} 内未打包，提价重新提交", stuck.len(), timeout);
+    let mut replacements = Vec::with_capacity(stuck.len());
+    for tx in &stuck {
+        match client.resubmit_with_bumped_fee(tx, |body| generator.resign(body)).await {
+            Ok(replacement) => {
+                stats.record_resubmission(&tx.body.hash, replacement.body.hash);
+                replacements.push(replacement);
+            }
+            Err(e) => error!("❌ 重新提交交易 {} 失败: {}", hex::encode(tx.body.hash.0), e),
+        }
+    }
+
+    let still_stuck = wait_all(client, &replacements, timeout).await?;
+    if still_stuck.is_empty() {
+        info!("✅ {} 笔替换交易已打包", replacements.len());
+    } else {
+        warn!("⚠️  {} 笔替换交易在 {:?} 内仍未打包", still_stuck.len(), timeout);
+    }
+    Ok(())
+}
+
+/// 在 `timeout` 内并发等待交易打包，返回超时仍未打包的交易
+///
+/// 同时轮询的交易数不超过 [`MAX_CONCURRENT_WAITS`]，所有交易共用同一个截止时间。
+async fn wait_all(
+    client: &mut BlockchainRpcClient,
+    txs: &[Transaction],
+    timeout: Duration,
+) -> Result<Vec<Transaction>> {
+    let deadline = Instant::now() + timeout;
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_WAITS));
+    let mut waits = JoinSet::new();
+    for (index, tx) in txs.iter().enumerate() {
+        let mut client = client.clone();
+        let permits = permits.clone();
+        let hash = tx.body.hash;
+        waits.spawn(async move {
+            let _permit = permits.acquire_owned().await?;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let outcome = client.wait_for_inclusion(&hash, remaining).await?;
+            anyhow::Ok((index, outcome))
+        });
+    }
+
+    let mut stuck = Vec::new();
+    while let Some(joined) = waits.join_next().await {
+        let (index, outcome) = joined??;
+        if let ReceiptOrTimeout::Timeout { .. } = outcome {
+            stuck.push(index);
+        }
+    }
+    stuck.sort_unstable();
+    Ok(stuck.into_iter().map(|index| txs[index].clone()).collect())
+}
+
 /// 监控区块链并统计实际 TPS
 async fn monitor_blockchain(
     client: &mut BlockchainRpcClient,
@@ -179,6 +480,11 @@ async fn monitor_blockchain(
         match client.get_block_by_number(height).await {
             Ok(Some(block)) => {
                 let tx_count = block.transactions.len() as u64;
+                // 区块时间戳单位为秒，区块产生于该秒内的某一时刻，取该秒的中点
+                let block_ts_ms = block.header.timestamp * 1000 + 500;
+                for tx in &block.transactions {
+                    stats.record_inclusion(&tx.body.hash, block_ts_ms);
+                }
                 total_transactions += tx_count;
                 total_blocks += 1;
 
@@ -220,3 +526,87 @@ async fn monitor_blockchain(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ramp_rate_increases() {
+        let duration = Duration::from_secs(60);
+        let rates: Vec<f64> = (0..=6)
+            .map(|i| LoadProfile::Ramp.target_rate(1000, Duration::from_secs(i * 10), duration))
+            .collect();
+
+        assert_eq!(rates[0], 0.0);
+        assert!(rates.windows(2).all(|w| w[1] > w[0]));
+        assert!((rates[3] - 500.0).abs() < 1e-9);
+        assert_eq!(rates[6], 1000.0);
+
+        // 超过测试时长后保持最终速率
+        assert_eq!(LoadProfile::Ramp.target_rate(1000, Duration::from_secs(90), duration), 1000.0);
+    }
+
+    #[test]
+    fn test_burst_rate() {
+        let duration = Duration::from_secs(60);
+        let rate = |secs: f64| LoadProfile::Burst.target_rate(100, Duration::from_secs_f64(secs), duration);
+
+        assert_eq!(rate(0.0), 500.0);
+        assert_eq!(rate(1.5), 500.0);
+        assert_eq!(rate(5.0), 0.0);
+        assert_eq!(rate(11.0), 500.0);
+        assert_eq!(batch_interval(rate(5.0), 10), None);
+    }
+
+    #[test]
+    fn test_batch_interval() {
+        assert_eq!(batch_interval(100.0, 10), Some(Duration::from_millis(100)));
+        assert_eq!(batch_interval(LoadProfile::Sustained.target_rate(50, Duration::ZERO, Duration::from_secs(1)), 10), Some(Duration::from_millis(200)));
+    }
+
+    #[tokio::test]
+    async fn test_wait_all_returns_stuck_transactions() {
+        let txs: Vec<Transaction> = (0..6u8)
+            .map(|i| {
+                let mut tx = Transaction::default();
+                tx.body.hash = norn_common::types::Hash([i; 32]);
+                tx
+            })
+            .collect();
+        // 偶数编号的交易在 200ms 后打包，奇数编号的交易永远不打包
+        let delays: Vec<_> = txs.iter().step_by(2).map(|tx| (tx.body.hash, Duration::from_millis(200))).collect();
+        let mut client = rpc_client::mock::connect(&delays).await;
+
+        let timeout = Duration::from_millis(800);
+        let started = Instant::now();
+        let stuck = wait_all(&mut client, &txs, timeout).await.unwrap();
+
+        let stuck: Vec<_> = stuck.iter().map(|tx| tx.body.hash).collect();
+        let expected: Vec<_> = txs.iter().skip(1).step_by(2).map(|tx| tx.body.hash).collect();
+        assert_eq!(stuck, expected);
+        // 所有交易共用一个截止时间并发等待
+        assert!(started.elapsed() < timeout * 2);
+    }
+
+    #[test]
+    fn test_pacing_sleep_is_clamped() {
+        let secs = Duration::from_secs;
+
+        // 间隔已过或测试已结束时不再等待
+        assert_eq!(pacing_sleep(Some(Duration::from_millis(50)), Duration::from_millis(60), secs(10)), None);
+        assert_eq!(pacing_sleep(Some(secs(5)), Duration::ZERO, Duration::ZERO), None);
+
+        // 剩余等待时间短于轮询间隔时按剩余时间睡眠
+        assert_eq!(pacing_sleep(Some(Duration::from_millis(80)), Duration::from_millis(50), secs(10)), Some(Duration::from_millis(30)));
+
+        // ramp 刚开始时速率极低，间隔很长，单次睡眠仍不超过轮询间隔
+        let duration = secs(60);
+        let early = batch_interval(LoadProfile::Ramp.target_rate(100, Duration::from_millis(1), duration), 10);
+        assert!(early.unwrap() > duration);
+        assert_eq!(pacing_sleep(early, Duration::ZERO, duration), Some(IDLE_POLL_INTERVAL));
+
+        // 不超过测试剩余时间
+        assert_eq!(pacing_sleep(None, Duration::ZERO, Duration::from_millis(20)), Some(Duration::from_millis(20)));
+    }
+}
@@ -1,8 +1,11 @@
+use std::time::Duration;
+
 use anyhow::{Context, Result};
+use tokio::time::Instant;
 use tonic::transport::Channel;
 use tonic::Request;
 
-use norn_common::types::{Transaction, Hash, TransactionType};
+use norn_common::types::{Transaction, TransactionBody, Hash, TransactionType};
 
 // 生成的 protobuf 代码
 pub mod blockchain {
@@ -10,9 +13,39 @@ pub mod blockchain {
 }
 
 use blockchain::blockchain_service_client::BlockchainServiceClient;
-use blockchain::{Empty, GetBlockReq, SendTransactionWithDataReq, Transaction as ProtoTransaction};
+use blockchain::{Empty, GetBlockReq, GetTransactionReq, SendTransactionWithDataReq, SubmitTransactionsReq, Transaction as ProtoTransaction};
+
+/// 等待交易打包时的轮询间隔
+const INCLUSION_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 替换交易的手续费至少提高的百分比
+pub const MIN_FEE_BUMP_PERCENT: i64 = 10;
+
+/// 将 gas 提高 `percent`，且至少提高 1
+pub fn bump_gas(gas: i64, percent: i64) -> i64 {
+    let bumped = gas.saturating_mul(100 + percent).saturating_add(99) / 100;
+    bumped.max(gas.saturating_add(1))
+}
+
+/// 将手续费价格提高 `percent`，且至少提高 1
+pub fn bump_price(price: u64, percent: i64) -> u64 {
+    let bumped = price.saturating_mul(100 + percent as u64).saturating_add(99) / 100;
+    bumped.max(price.saturating_add(1))
+}
+
+/// 等待交易打包的结果
+#[derive(Debug, Clone)]
+pub enum ReceiptOrTimeout {
+    /// 交易已打包，返回带有区块高度和区块哈希的交易
+    Included(Box<Transaction>),
+    /// 等待 `waited` 后交易仍未打包
+    Timeout { hash: Hash, waited: Duration },
+}
 
 /// RPC 客户端
+///
+/// 克隆的客户端共享同一个连接，可在多个任务中并发使用。
+#[derive(Clone)]
 pub struct BlockchainRpcClient {
     client: BlockchainServiceClient<Channel>,
 }
@@ -98,6 +131,63 @@ impl BlockchainRpcClient {
         }
     }
 
+    /// 根据哈希获取已打包的交易，尚未打包时返回 None
+    pub async fn get_transaction_by_hash(&mut self, hash: &Hash) -> Result<Option<Transaction>> {
+        let request = Request::new(GetTransactionReq {
+            hash: hex::encode(hash.0),
+        });
+
+        match self.client.get_transaction_by_hash(request).await {
+            Ok(response) => Ok(response.into_inner().body.map(Into::into)),
+            Err(e) => {
+                if e.code() == tonic::Code::NotFound {
+                    Ok(None)
+                } else {
+                    Err(anyhow::anyhow!("Failed to get transaction: {}", e))
+                }
+            }
+        }
+    }
+
+    /// 轮询交易直到被打包，超过 `timeout` 仍未打包时返回 [`ReceiptOrTimeout::Timeout`]
+    pub async fn wait_for_inclusion(&mut self, hash: &Hash, timeout: Duration) -> Result<ReceiptOrTimeout> {
+        let started = Instant::now();
+        loop {
+            if let Some(tx) = self.get_transaction_by_hash(hash).await? {
+                return Ok(ReceiptOrTimeout::Included(Box::new(tx)));
+            }
+
+            let waited = started.elapsed();
+            if waited >= timeout {
+                return Ok(ReceiptOrTimeout::Timeout { hash: *hash, waited });
+            }
+            tokio::time::sleep(INCLUSION_POLL_INTERVAL.min(timeout - waited)).await;
+        }
+    }
+
+    /// 以更高的手续费重新发送未打包的交易，返回替换交易
+    ///
+    /// 替换交易沿用原交易的 nonce，gas 和已设置的 gas_price、
+    /// max_fee_per_gas、max_priority_fee_per_gas 均提高
+    /// [`MIN_FEE_BUMP_PERCENT`]，以满足交易池的替换规则。`sign` 用发送者的
+    /// 密钥重新签名修改后的交易体，无法签名时返回 None。
+    pub async fn resubmit_with_bumped_fee(
+        &mut self,
+        tx: &Transaction,
+        sign: impl FnOnce(TransactionBody) -> Option<Transaction>,
+    ) -> Result<Transaction> {
+        let mut body = tx.body.clone();
+        body.gas = bump_gas(tx.body.gas, MIN_FEE_BUMP_PERCENT);
+        let bump = |price: u64| bump_price(price, MIN_FEE_BUMP_PERCENT);
+        body.gas_price = body.gas_price.map(bump);
+        body.max_fee_per_gas = body.max_fee_per_gas.map(bump);
+        body.max_priority_fee_per_gas = body.max_priority_fee_per_gas.map(bump);
+        let replacement = sign(body).context("No signer for the transaction sender")?;
+
+        self.send_transaction_with_data(&replacement).await?;
+        Ok(replacement)
+    }
+
     /// 发送交易（带完整数据）
     pub async fn send_transaction_with_data(&mut self, tx: &Transaction) -> Result<String> {
         let proto_tx: ProtoTransaction = tx.clone().into();
@@ -115,7 +205,35 @@ impl BlockchainRpcClient {
         Ok(response.into_inner().tx_hash)
     }
 
-    /// 发送批量交易
+    /// 单次请求提交一批交易，按顺序返回每笔交易的接受/拒绝结果
+    pub async fn send_transaction_batch(&mut self, transactions: &[Transaction]) -> Result<Vec<Result<String>>> {
+        let request = Request::new(SubmitTransactionsReq {
+            transactions: transactions.iter().cloned().map(Into::into).collect(),
+        });
+
+        let response = self
+            .client
+            .submit_transactions(request)
+            .await
+            .context("Failed to submit transaction batch")?;
+
+        let results = response
+            .into_inner()
+            .results
+            .into_iter()
+            .map(|r| {
+                if r.accepted {
+                    Ok(r.tx_hash)
+                } else {
+                    Err(anyhow::anyhow!("Transaction {} rejected: {}", r.tx_hash, r.error))
+                }
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    /// 逐笔发送批量交易
     pub async fn send_batch(&mut self, transactions: &[Transaction]) -> Result<Vec<Result<String>>> {
         let mut results = Vec::with_capacity(transactions.len());
 
@@ -186,9 +304,9 @@ impl From<ProtoTransaction> for Transaction {
                 timestamp: proto.timestamp as i64,
                 public,
                 signature: hex::decode(&proto.signature).unwrap_or_default(),
-                tx_type: TransactionType::default(),
+                tx_type: if proto.tx_type == 1 { TransactionType::EVM } else { TransactionType::Native },
                 chain_id: None,
-                value: None,
+                value: (!proto.value.is_empty()).then_some(proto.value),
                 max_fee_per_gas: None,
                 max_priority_fee_per_gas: None,
                 access_list: None,
@@ -218,6 +336,11 @@ impl From<Transaction> for ProtoTransaction {
             height: tx.body.height as u64,
             block_hash: hex::encode(tx.body.block_hash.0),
             index: tx.body.index as u64,
+            tx_type: match tx.body.tx_type {
+                TransactionType::Native => 0,
+                TransactionType::EVM => 1,
+            },
+            value: tx.body.value.unwrap_or_default(),
         }
     }
 }
@@ -272,6 +395,7 @@ impl From<blockchain::Block> for norn_common::types::Block {
             params: hex::decode(&proto_header.params).unwrap_or_default(),
             gas_limit: proto_header.gas_limit as i64,
             base_fee: 0, // Default base fee
+            logs_bloom: Vec::new(),
         };
 
         let transactions: Vec<Transaction> = proto
@@ -286,3 +410,174 @@ impl From<blockchain::Block> for norn_common::types::Block {
         }
     }
 }
+
+/// 测试用的 RPC 服务，交易在指定延迟后才能查询到
+#[cfg(test)]
+pub mod mock {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+    use tonic::{Request, Response, Status};
+
+    use norn_common::types::{Hash, Transaction};
+
+    use super::blockchain::blockchain_service_server::{BlockchainService, BlockchainServiceServer};
+    use super::blockchain::*;
+    use super::BlockchainRpcClient;
+
+    /// 交易哈希（十六进制）到可查询时刻的映射
+    struct MockService {
+        included_at: HashMap<String, Instant>,
+    }
+
+    #[tonic::async_trait]
+    impl BlockchainService for MockService {
+        async fn get_block_number(&self, _: Request<Empty>) -> Result<Response<BlockNumberResp>, Status> {
+            Ok(Response::new(BlockNumberResp { number: 1 }))
+        }
+
+        async fn get_block_by_hash(&self, _: Request<GetBlockReq>) -> Result<Response<GetBlockResp>, Status> {
+            Err(Status::unimplemented("mock"))
+        }
+
+        async fn get_block_by_number(&self, _: Request<GetBlockReq>) -> Result<Response<GetBlockResp>, Status> {
+            Err(Status::unimplemented("mock"))
+        }
+
+        async fn get_transaction_by_hash(&self, request: Request<GetTransactionReq>) -> Result<Response<GetTransactionResp>, Status> {
+            let hash = request.into_inner().hash;
+            match self.included_at.get(&hash) {
+                Some(at) if Instant::now() >= *at => {
+                    let mut tx = Transaction::default();
+                    tx.body.hash.0.copy_from_slice(&hex::decode(&hash).unwrap());
+                    tx.body.height = 1;
+                    Ok(Response::new(GetTransactionResp { body: Some(tx.into()) }))
+                }
+                _ => Err(Status::not_found("transaction not found")),
+            }
+        }
+
+        async fn get_transaction_by_block_hash_and_index(&self, _: Request<GetTransactionReq>) -> Result<Response<GetTransactionResp>, Status> {
+            Err(Status::unimplemented("mock"))
+        }
+
+        async fn get_transaction_by_block_number_and_index(&self, _: Request<GetTransactionReq>) -> Result<Response<GetTransactionResp>, Status> {
+            Err(Status::unimplemented("mock"))
+        }
+
+        async fn read_contract_address(&self, _: Request<ReadContractAddressReq>) -> Result<Response<ReadContractAddressResp>, Status> {
+            Err(Status::unimplemented("mock"))
+        }
+
+        async fn send_transaction(&self, _: Request<SendTransactionReq>) -> Result<Response<SendTransactionResp>, Status> {
+            Err(Status::unimplemented("mock"))
+        }
+
+        async fn send_transaction_with_data(&self, request: Request<SendTransactionWithDataReq>) -> Result<Response<SendTransactionWithDataResp>, Status> {
+            let tx = request.into_inner().transaction.ok_or_else(|| Status::invalid_argument("missing transaction"))?;
+            Ok(Response::new(SendTransactionWithDataResp { tx_hash: tx.hash }))
+        }
+
+        async fn submit_transactions(&self, _: Request<SubmitTransactionsReq>) -> Result<Response<SubmitTransactionsResp>, Status> {
+            Err(Status::unimplemented("mock"))
+        }
+    }
+
+    /// 启动 mock 服务并连接，`delays` 中的交易在对应延迟后可查询，其余交易永远查询不到
+    pub async fn connect(delays: &[(Hash, Duration)]) -> BlockchainRpcClient {
+        let now = Instant::now();
+        let service = MockService {
+            included_at: delays.iter().map(|(hash, delay)| (hex::encode(hash.0), now + *delay)).collect(),
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(BlockchainServiceServer::new(service))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+
+        BlockchainRpcClient::connect(&addr.to_string()).await.unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use norn_core::txpool_enhanced::EnhancedTxPool;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_for_inclusion_after_delay() {
+        let hash = Hash([1; 32]);
+        let mut client = mock::connect(&[(hash, Duration::from_millis(300))]).await;
+
+        // 延迟期间查询不到
+        assert!(client.get_transaction_by_hash(&hash).await.unwrap().is_none());
+
+        match client.wait_for_inclusion(&hash, Duration::from_secs(5)).await.unwrap() {
+            ReceiptOrTimeout::Included(tx) => {
+                assert_eq!(tx.body.hash, hash);
+                assert_eq!(tx.body.height, 1);
+            }
+            timeout => panic!("expected inclusion, got {:?}", timeout),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_inclusion_times_out() {
+        let hash = Hash([2; 32]);
+        let mut client = mock::connect(&[(Hash([1; 32]), Duration::ZERO)]).await;
+
+        let timeout = Duration::from_millis(600);
+        match client.wait_for_inclusion(&hash, timeout).await.unwrap() {
+            ReceiptOrTimeout::Timeout { hash: timed_out, waited } => {
+                assert_eq!(timed_out, hash);
+                assert!(waited >= timeout);
+            }
+            included => panic!("expected timeout, got {:?}", included),
+        }
+    }
+
+    #[test]
+    fn test_bump_gas() {
+        assert_eq!(bump_gas(50_000, MIN_FEE_BUMP_PERCENT), 55_000);
+        assert_eq!(bump_gas(1_001, MIN_FEE_BUMP_PERCENT), 1_102);
+        // 很小的 gas 也会提高
+        assert_eq!(bump_gas(1, MIN_FEE_BUMP_PERCENT), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resubmit_with_bumped_fee_replaces_in_pool() {
+        let pool = EnhancedTxPool::new();
+        let mut client = mock::connect(&[]).await;
+
+        let fees = [(Some(1_000), None), (None, Some(1_001)), (Some(1), None)];
+        for (i, (gas_price, max_fee_per_gas)) in fees.into_iter().enumerate() {
+            let mut tx = Transaction::default();
+            tx.body.address.0[0] = i as u8;
+            tx.body.hash = Hash([i as u8; 32]);
+            tx.body.gas = 21_000;
+            tx.body.gas_price = gas_price;
+            tx.body.max_fee_per_gas = max_fee_per_gas;
+            pool.add(tx.clone()).await.unwrap();
+
+            let replacement = client
+                .resubmit_with_bumped_fee(&tx, |body| {
+                    let mut replacement = Transaction { body };
+                    replacement.body.hash.0[31] ^= 0xff;
+                    Some(replacement)
+                })
+                .await
+                .unwrap();
+            assert_eq!(replacement.body.nonce, tx.body.nonce);
+
+            // 交易池按替换规则接受提价后的交易，并移除原交易
+            pool.add(replacement.clone()).await.unwrap();
+            assert!(!pool.contains(&tx.body.hash).await);
+            assert!(pool.contains(&replacement.body.hash).await);
+        }
+    }
+}
@@ -1,7 +1,10 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicU64, AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
+use norn_common::types::Hash;
+
 /// 交易跟踪器
 #[derive(Debug)]
 pub struct TransactionTracker {
@@ -23,6 +26,9 @@ pub struct TestStatistics {
     /// 失败的交易数
     failed_transactions: Arc<AtomicU64>,
 
+    /// 以更高手续费重新提交的交易数
+    resubmitted_transactions: Arc<AtomicU64>,
+
     /// 实际打包的交易数
     packed_transactions: Arc<AtomicU64>,
 
@@ -32,6 +38,15 @@ pub struct TestStatistics {
     /// 总区块数
     total_blocks: Arc<AtomicU64>,
 
+    /// 已提交交易的提交时间（按交易哈希）
+    trackers: Mutex<HashMap<Hash, TransactionTracker>>,
+
+    /// 提交到打包的延迟（毫秒）
+    inclusion_latencies: Mutex<Vec<i64>>,
+
+    /// 区块时间早于提交时间的样本数（时钟偏差），不计入延迟分布
+    skewed_latencies: Arc<AtomicU64>,
+
     /// 测试开始时间
     test_start: Option<Instant>,
 
@@ -47,9 +62,13 @@ impl TestStatistics {
             start_block,
             submitted_transactions: Arc::new(AtomicU64::new(0)),
             failed_transactions: Arc::new(AtomicU64::new(0)),
+            resubmitted_transactions: Arc::new(AtomicU64::new(0)),
             packed_transactions: Arc::new(AtomicU64::new(0)),
             actual_tps: Arc::new(AtomicU64::new(0)),
             total_blocks: Arc::new(AtomicU64::new(0)),
+            trackers: Mutex::new(HashMap::new()),
+            inclusion_latencies: Mutex::new(Vec::new()),
+            skewed_latencies: Arc::new(AtomicU64::new(0)),
             test_start: Some(Instant::now()),
             _completed: Arc::new(AtomicBool::new(false)),
         }
@@ -60,6 +79,68 @@ impl TestStatistics {
         self.submitted_transactions.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// 跟踪交易提交，并记录提交时间（毫秒）用于计算打包延迟
+    pub fn record_submission(&self, tx_hash: Hash, timestamp: i64) {
+        self.track_submission(timestamp);
+        self.trackers.lock().unwrap().insert(tx_hash, TransactionTracker {
+            submission_time: timestamp,
+            included: false,
+        });
+    }
+
+    /// 记录交易被打包的区块时间戳（毫秒）
+    ///
+    /// 未记录提交时间或已记录过打包的交易会被忽略。区块时间早于提交时间的
+    /// 样本来自时钟偏差，只计数而不计入延迟分布：截断为 0 会使百分位数偏低。
+    pub fn record_inclusion(&self, tx_hash: &Hash, block_ts: i64) {
+        let mut trackers = self.trackers.lock().unwrap();
+        let Some(tracker) = trackers.get_mut(tx_hash) else {
+            return;
+        };
+        if tracker.included {
+            return;
+        }
+        tracker.included = true;
+
+        let latency = block_ts - tracker.submission_time;
+        if latency < 0 {
+            self.skewed_latencies.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.inclusion_latencies.lock().unwrap().push(latency);
+    }
+
+    /// 记录替换交易，打包延迟仍从原交易的提交时间算起
+    pub fn record_resubmission(&self, original: &Hash, replacement: Hash) {
+        self.resubmitted_transactions.fetch_add(1, Ordering::Relaxed);
+        let mut trackers = self.trackers.lock().unwrap();
+        if let Some(tracker) = trackers.remove(original) {
+            trackers.insert(replacement, tracker);
+        }
+    }
+
+    /// 已记录打包延迟的交易数
+    pub fn included(&self) -> usize {
+        self.inclusion_latencies.lock().unwrap().len()
+    }
+
+    /// 因时钟偏差未计入延迟分布的样本数
+    pub fn skewed(&self) -> u64 {
+        self.skewed_latencies.load(Ordering::Relaxed)
+    }
+
+    /// 打包延迟的百分位数（毫秒，最近秩法），无数据时返回 None
+    pub fn latency_percentile(&self, percentile: f64) -> Option<i64> {
+        let mut latencies = self.inclusion_latencies.lock().unwrap().clone();
+        if latencies.is_empty() {
+            return None;
+        }
+        latencies.sort_unstable();
+
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * latencies.len() as f64).ceil() as usize;
+        Some(latencies[rank.saturating_sub(1)])
+    }
+
     /// 跟踪失败的交易提交
     pub fn track_failed_submission(&self) {
         self.failed_transactions.fetch_add(1, Ordering::Relaxed);
@@ -82,6 +163,11 @@ impl TestStatistics {
         self.failed_transactions.load(Ordering::Relaxed)
     }
 
+    /// 获取重新提交的交易数
+    pub fn resubmitted(&self) -> u64 {
+        self.resubmitted_transactions.load(Ordering::Relaxed)
+    }
+
     /// 获取打包交易数
     pub fn packed(&self) -> u64 {
         self.packed_transactions.load(Ordering::Relaxed)
@@ -142,6 +228,7 @@ impl TestStatistics {
         println!("\n📦 交易提交统计:");
         println!("   ├─ 已提交: {} 笔", self.submitted());
         println!("   ├─ 失败: {} 笔", self.failed());
+        println!("   ├─ 提价重发: {} 笔", self.resubmitted());
         println!("   ├─ 成功率: {:.2}%", self.success_rate());
         println!("   └─ 提交速率: {:.2} TPS",
             self.submitted() as f64 / elapsed.as_secs_f64().max(1.0));
@@ -153,6 +240,22 @@ impl TestStatistics {
         println!("   ├─ 达成率: {:.2}%", self.achievement_rate());
         println!("   └─ 平均每块交易: {:.2}", self.avg_tx_per_block());
 
+        println!("\n⏱️  打包延迟统计:");
+        match (
+            self.latency_percentile(50.0),
+            self.latency_percentile(95.0),
+            self.latency_percentile(99.0),
+        ) {
+            (Some(p50), Some(p95), Some(p99)) => {
+                println!("   ├─ 样本数: {} 笔", self.included());
+                println!("   ├─ P50: {} ms", p50);
+                println!("   ├─ P95: {} ms", p95);
+                println!("   ├─ P99: {} ms", p99);
+                println!("   └─ 时钟偏差样本: {} 笔（未计入）", self.skewed());
+            }
+            _ => println!("   └─ 无打包延迟数据"),
+        }
+
         println!("\n📈 性能分析:");
         let achievement = self.achievement_rate();
         if achievement >= 90.0 {
@@ -242,6 +345,53 @@ mod tests {
         assert!((stats.success_rate() - 66.66).abs() < 0.1);
     }
 
+    #[test]
+    fn test_latency_percentiles() {
+        let stats = TestStatistics::new(100, 0);
+        assert_eq!(stats.latency_percentile(95.0), None);
+
+        // 延迟为 1..=100 毫秒，提交顺序与延迟无关
+        for i in 0..100u8 {
+            let latency = ((i as i64 * 37) % 100) + 1;
+            let hash = Hash([i; 32]);
+            stats.record_submission(hash, 1_000);
+            stats.record_inclusion(&hash, 1_000 + latency);
+        }
+        // 重复记录和未知交易不计入
+        stats.record_inclusion(&Hash([0; 32]), 50_000);
+        stats.record_inclusion(&Hash([200; 32]), 50_000);
+
+        // 区块时间早于提交时间的样本不会以 0 计入而拉低百分位数
+        for i in 100..110u8 {
+            let hash = Hash([i; 32]);
+            stats.record_submission(hash, 1_000);
+            stats.record_inclusion(&hash, 900);
+        }
+
+        assert_eq!(stats.included(), 100);
+        assert_eq!(stats.skewed(), 10);
+        assert_eq!(stats.latency_percentile(50.0), Some(50));
+        assert_eq!(stats.latency_percentile(95.0), Some(95));
+        assert_eq!(stats.latency_percentile(99.0), Some(99));
+    }
+
+    #[test]
+    fn test_resubmission_keeps_submission_time() {
+        let stats = TestStatistics::new(100, 0);
+        let original = Hash([1; 32]);
+        let replacement = Hash([2; 32]);
+        stats.record_submission(original, 1_000);
+        stats.record_resubmission(&original, replacement);
+
+        // 原交易不再计入，替换交易的延迟从原提交时间算起
+        stats.record_inclusion(&original, 2_000);
+        stats.record_inclusion(&replacement, 3_000);
+
+        assert_eq!(stats.submitted(), 1);
+        assert_eq!(stats.resubmitted(), 1);
+        assert_eq!(stats.latency_percentile(50.0), Some(2_000));
+    }
+
     #[test]
     fn test_achievement_rate() {
         let stats = TestStatistics::new(100, 0);
@@ -1,27 +1,141 @@
 use norn_crypto::ecdsa::KeyPair;
 use norn_crypto::transaction::TransactionSigner;
-use norn_common::types::{Address, Transaction};
-use rand::Rng;
+use norn_common::types::{Address, Transaction, TransactionBody, TransactionType};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// ERC-20 `transfer(address,uint256)` 函数选择器
+pub const ERC20_TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+
+/// ERC-20 转账交易的 gas 上限
+const ERC20_TRANSFER_GAS: i64 = 60_000;
+
+/// 生成 ERC-20 交易时轮流使用的发送者数量
+const ERC20_SENDER_COUNT: usize = 16;
+
+/// 固定种子生成的交易使用的时间戳，使交易哈希不依赖当前时间
+const SEEDED_TIMESTAMP: i64 = 1_700_000_000;
+
+/// 交易负载类型
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Workload {
+    /// 随机原生交易
+    Native,
+    /// 调用测试合约的 ERC-20 转账交易
+    Erc20,
+    /// 原生交易与 ERC-20 转账交易各占一半
+    Mixed,
+}
 
 /// 交易生成器
 pub struct TransactionGenerator {
+    rng: StdRng,
+    /// 是否使用固定种子（固定时间戳且不过期）
+    seeded: bool,
     signer: TransactionSigner,
+    workload: Workload,
+    /// ERC-20 测试合约地址
+    erc20_contract: Address,
+    /// ERC-20 交易发送者，每个发送者独立维护 nonce
+    erc20_senders: Vec<TransactionSigner>,
+    next_sender: usize,
 }
 
 impl TransactionGenerator {
-    /// 创建新的交易生成器
+    /// 创建新的交易生成器（仅生成原生交易）
     pub fn new() -> Self {
-        let keypair = KeyPair::random();
-        let signer = TransactionSigner::new(keypair);
-        Self { signer }
+        Self::with_workload(Workload::Native, Address::default())
+    }
+
+    /// 创建指定负载类型的交易生成器，ERC-20 交易发往 `erc20_contract`
+    pub fn with_workload(workload: Workload, erc20_contract: Address) -> Self {
+        Self::build(StdRng::from_entropy(), false, workload, erc20_contract)
+    }
+
+    /// 使用固定种子创建交易生成器，相同种子生成完全相同的交易序列
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_seed_and_workload(seed, Workload::Native, Address::default())
+    }
+
+    /// 使用固定种子创建指定负载类型的交易生成器
+    ///
+    /// 密钥由种子派生，交易时间戳固定且不过期。
+    pub fn with_seed_and_workload(seed: u64, workload: Workload, erc20_contract: Address) -> Self {
+        Self::build(StdRng::seed_from_u64(seed), true, workload, erc20_contract)
+    }
+
+    fn build(mut rng: StdRng, seeded: bool, workload: Workload, erc20_contract: Address) -> Self {
+        let signer = random_signer(&mut rng);
+        let erc20_senders = match workload {
+            Workload::Native => Vec::new(),
+            Workload::Erc20 | Workload::Mixed => (0..ERC20_SENDER_COUNT)
+                .map(|_| random_evm_signer(&mut rng))
+                .collect(),
+        };
+
+        Self {
+            rng,
+            seeded,
+            signer,
+            workload,
+            erc20_contract,
+            erc20_senders,
+            next_sender: 0,
+        }
+    }
+
+    /// 按负载类型生成下一笔交易
+    pub fn generate_transaction(&mut self) -> Transaction {
+        match self.workload {
+            Workload::Native => self.generate_random_transaction(),
+            Workload::Erc20 => self.generate_erc20_transfer(),
+            Workload::Mixed => {
+                if self.rng.gen_bool(0.5) {
+                    self.generate_erc20_transfer()
+                } else {
+                    self.generate_random_transaction()
+                }
+            }
+        }
+    }
+
+    /// 生成调用测试合约 `transfer` 的已签名 EVM 交易
+    ///
+    /// 发送者轮流使用，每个发送者的 nonce 依次递增。
+    pub fn generate_erc20_transfer(&mut self) -> Transaction {
+        if self.erc20_senders.is_empty() {
+            let signer = random_evm_signer(&mut self.rng);
+            self.erc20_senders.push(signer);
+        }
+        let index = self.next_sender % self.erc20_senders.len();
+        self.next_sender = self.next_sender.wrapping_add(1);
+
+        let recipient = self.generate_random_address();
+        let amount: u64 = self.rng.gen_range(1..1_000);
+        let data = encode_erc20_transfer(&recipient, amount);
+        let (timestamp, expire) = self.timestamp_and_expire();
+
+        let sender = &mut self.erc20_senders[index];
+        let mut body = sender
+            .create_transaction_at(self.erc20_contract, Vec::new(), Vec::new(), Vec::new(), data, ERC20_TRANSFER_GAS, expire, timestamp)
+            .expect("Failed to create transaction")
+            .body;
+
+        // 在签名前设置转账金额，签名后不再修改交易体
+        body.value = Some("0".to_string());
+        sender.sign_body(body)
+    }
+
+    /// ERC-20 交易发送者地址，测试开始前需要为这些账户注资
+    pub fn erc20_senders(&self) -> Vec<Address> {
+        self.erc20_senders.iter().map(|signer| signer.address()).collect()
     }
 
     /// 生成随机交易
     pub fn generate_random_transaction(&mut self) -> Transaction {
-        let mut rng = rand::thread_rng();
-
         // 生成随机接收地址
         let receiver = self.generate_random_address();
+        let rng = &mut self.rng;
 
         // 生成随机数据
         let event_size = rng.gen_range(10..100);
@@ -49,25 +163,45 @@ impl TransactionGenerator {
 
         // 生成随机 gas 和过期时间
         let gas = rng.gen_range(1000..100000);
-        let expire = chrono::Utc::now().timestamp() + rng.gen_range(300..3600);
+        let (timestamp, expire) = self.timestamp_and_expire();
 
         // 创建交易
         let tx = self
             .signer
-            .create_transaction(receiver, event, opt, state, data, gas, expire)
+            .create_transaction_at(receiver, event, opt, state, data, gas, expire, timestamp)
             .expect("Failed to create transaction");
 
         tx
     }
 
+    /// 用交易发送者的密钥重新签名 `body`，nonce 保持不变
+    ///
+    /// 发送者不是本生成器创建的签名者时返回 None。
+    pub fn resign(&self, body: TransactionBody) -> Option<Transaction> {
+        std::iter::once(&self.signer)
+            .chain(&self.erc20_senders)
+            .find(|signer| signer.address() == body.address)
+            .map(|signer| signer.sign_body(body))
+    }
+
     /// 生成随机地址
-    fn generate_random_address(&self) -> Address {
-        let mut rng = rand::thread_rng();
+    fn generate_random_address(&mut self) -> Address {
         let mut addr = Address::default();
-        rng.fill(&mut addr.0);
+        self.rng.fill(&mut addr.0);
         addr
     }
 
+    /// 交易时间戳和随机过期时间；固定种子时使用固定时间戳且不过期
+    fn timestamp_and_expire(&mut self) -> (i64, i64) {
+        let ttl = self.rng.gen_range(300..3600);
+        if self.seeded {
+            (SEEDED_TIMESTAMP, 0)
+        } else {
+            let now = chrono::Utc::now().timestamp();
+            (now, now + ttl)
+        }
+    }
+
     /// 批量生成交易
     pub fn generate_batch(&mut self, count: usize) -> Vec<Transaction> {
         (0..count)
@@ -83,14 +217,56 @@ impl TransactionGenerator {
         let state = vec![b'S'; 10];
         let data = vec![b'D'; data_size];
         let gas = 50000;
-        let expire = chrono::Utc::now().timestamp() + 3600;
+        let (timestamp, expire) = if self.seeded {
+            (SEEDED_TIMESTAMP, 0)
+        } else {
+            let now = chrono::Utc::now().timestamp();
+            (now, now + 3600)
+        };
 
         self.signer
-            .create_transaction(receiver, event, opt, state, data, gas, expire)
+            .create_transaction_at(receiver, event, opt, state, data, gas, expire, timestamp)
             .expect("Failed to create transaction")
     }
 }
 
+/// 用 `rng` 派生的私钥创建签名者
+fn random_signer(rng: &mut StdRng) -> TransactionSigner {
+    loop {
+        let secret: [u8; 32] = rng.gen();
+        // 极少数字节串不是合法私钥（为 0 或不小于曲线阶），重新生成即可
+        if let Ok(keypair) = KeyPair::from_private_key_hex(&hex::encode(secret)) {
+            return TransactionSigner::new(keypair);
+        }
+    }
+}
+
+/// 用 `rng` 派生的 secp256k1 私钥创建 EVM 交易签名者
+fn random_evm_signer(rng: &mut StdRng) -> TransactionSigner {
+    loop {
+        let secret: [u8; 32] = rng.gen();
+        if let Ok(key) = k256::ecdsa::SigningKey::from_slice(&secret) {
+            return TransactionSigner::with_signer(key, TransactionType::EVM);
+        }
+    }
+}
+
+/// 编码 ERC-20 `transfer(to, amount)` 调用数据
+fn encode_erc20_transfer(to: &Address, amount: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(4 + 32 * 2);
+    data.extend_from_slice(&ERC20_TRANSFER_SELECTOR);
+
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(&to.0);
+    data.extend_from_slice(&word);
+
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&amount.to_be_bytes());
+    data.extend_from_slice(&word);
+
+    data
+}
+
 impl Default for TransactionGenerator {
     fn default() -> Self {
         Self::new()
@@ -122,6 +298,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_generate_erc20_workload() {
+        let contract = Address([0xc0; 20]);
+        let mut generator = TransactionGenerator::with_workload(Workload::Erc20, contract);
+        let batch: Vec<_> = (0..ERC20_SENDER_COUNT * 3)
+            .map(|_| generator.generate_transaction())
+            .collect();
+
+        let mut nonces = std::collections::HashMap::new();
+        for tx in &batch {
+            assert_eq!(tx.body.tx_type, TransactionType::EVM);
+            assert_eq!(tx.body.value.as_deref(), Some("0"));
+            assert_eq!(tx.body.receiver, contract);
+            assert_eq!(&tx.body.data[..4], &ERC20_TRANSFER_SELECTOR);
+            assert_eq!(tx.body.data.len(), 4 + 32 * 2);
+            assert!(norn_crypto::transaction::verify_transaction(tx).is_ok());
+
+            // 每个发送者的 nonce 从 0 开始连续递增
+            let next = nonces.entry(tx.body.address).or_insert(0i64);
+            assert_eq!(tx.body.nonce, *next);
+            *next += 1;
+        }
+        assert_eq!(nonces.len(), ERC20_SENDER_COUNT);
+        let senders: std::collections::HashSet<_> = generator.erc20_senders().into_iter().collect();
+        assert!(nonces.keys().all(|sender| senders.contains(sender)));
+
+        // EVM 发送者也能重新签名
+        let mut body = batch[0].body.clone();
+        body.gas += 1;
+        let resigned = generator.resign(body).unwrap();
+        assert_eq!(resigned.body.tx_type, TransactionType::EVM);
+        assert!(norn_crypto::transaction::verify_transaction(&resigned).is_ok());
+    }
+
+    #[test]
+    fn test_seeded_generators_are_reproducible() {
+        let contract = Address([0xc0; 20]);
+        let mut a = TransactionGenerator::with_seed_and_workload(42, Workload::Mixed, contract);
+        let mut b = TransactionGenerator::with_seed_and_workload(42, Workload::Mixed, contract);
+        let mut c = TransactionGenerator::with_seed_and_workload(43, Workload::Mixed, contract);
+
+        let hashes = |g: &mut TransactionGenerator| -> Vec<_> {
+            (0..20).map(|_| g.generate_transaction().body.hash).collect()
+        };
+        let (a, b, c) = (hashes(&mut a), hashes(&mut b), hashes(&mut c));
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        let mut seeded = TransactionGenerator::with_seed(7);
+        assert_eq!(
+            seeded.generate_random_transaction().body.hash,
+            TransactionGenerator::with_seed(7).generate_random_transaction().body.hash
+        );
+    }
+
+    #[test]
+    fn test_resign_keeps_sender_and_nonce() {
+        let mut generator = TransactionGenerator::with_seed(7);
+        let txs = generator.generate_batch(2);
+
+        let mut body = txs[1].body.clone();
+        body.gas += 1;
+        let resigned = generator.resign(body).unwrap();
+        assert_eq!(resigned.body.address, txs[1].body.address);
+        assert_eq!(resigned.body.nonce, txs[1].body.nonce);
+        assert!(norn_crypto::transaction::verify_transaction(&resigned).is_ok());
+
+        let mut stranger = txs[0].body.clone();
+        stranger.address = Address::default();
+        assert!(generator.resign(stranger).is_none());
+    }
+
     #[test]
     fn test_generate_fixed_size_transaction() {
         let mut generator = TransactionGenerator::new();
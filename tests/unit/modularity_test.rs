@@ -22,6 +22,7 @@ async fn test_block_creation() {
             params: vec![],
             gas_limit: 30_000_000,
             base_fee: 1000000000,
+            logs_bloom: Vec::new(),
         },
         transactions: vec![],
     };
@@ -119,6 +120,7 @@ async fn test_blockchain_initialization() {
             params: vec![],
             gas_limit: 30_000_000,
             base_fee: 1000000000,
+            logs_bloom: Vec::new(),
         },
         transactions: vec![],
     };